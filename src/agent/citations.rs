@@ -0,0 +1,175 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::tools::{
+    FETCH_URL_TOOL_NAME, READ_NOTE_TOOL_NAME, READ_WORKSPACE_FILE_TOOL_NAME, SAVE_NOTE_TOOL_NAME,
+    SEARCH_NOTES_TOOL_NAME, SEARCH_WORKSPACE_TOOL_NAME,
+};
+
+pub use crate::api::TurnCitation;
+
+use super::ExecutedToolCall;
+
+/// Maps the `[n]` markers of `final_text` to the tool calls they cite. A JSON answer
+/// (`json_object` or `json_schema` formats) is data, where `[1]` is an array rather
+/// than a marker, so it cites nothing.
+pub(crate) fn extract_citations(
+    final_text: &str,
+    tool_calls: &[ExecutedToolCall],
+) -> Vec<TurnCitation> {
+    if serde_json::from_str::<Value>(final_text.trim())
+        .is_ok_and(|answer| answer.is_object() || answer.is_array())
+    {
+        return Vec::new();
+    }
+    parse_citation_markers(final_text)
+        .into_iter()
+        .filter_map(|marker| {
+            let tool_call_index = usize::try_from(marker).ok()?.checked_sub(1)?;
+            let call = tool_calls.get(tool_call_index)?;
+            Some(TurnCitation {
+                marker,
+                tool_call_index,
+                tool_name: call.tool_name.clone(),
+                sources: citation_sources(call),
+            })
+        })
+        .collect()
+}
+
+/// Returns unique `[n]` marker numbers in ascending order.
+fn parse_citation_markers(text: &str) -> Vec<u32> {
+    let mut markers = BTreeSet::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find(']') else {
+            break;
+        };
+        let candidate = &rest[..close];
+        if !candidate.is_empty()
+            && candidate.chars().all(|ch| ch.is_ascii_digit())
+            && let Ok(marker) = candidate.parse::<u32>()
+            && marker > 0
+        {
+            markers.insert(marker);
+        }
+    }
+
+    markers.into_iter().collect()
+}
+
+fn citation_sources(call: &ExecutedToolCall) -> Vec<String> {
+    let Ok(payload) = serde_json::from_str::<Value>(&call.output) else {
+        return Vec::new();
+    };
+
+    match call.tool_name.as_str() {
+        FETCH_URL_TOOL_NAME => payload
+            .get("final_url")
+            .and_then(Value::as_str)
+            .map(|url| vec![url.to_owned()])
+            .unwrap_or_default(),
+        SAVE_NOTE_TOOL_NAME | READ_NOTE_TOOL_NAME | READ_WORKSPACE_FILE_TOOL_NAME => payload
+            .get("path")
+            .and_then(Value::as_str)
+            .map(|path| vec![path.to_owned()])
+            .unwrap_or_default(),
+        SEARCH_NOTES_TOOL_NAME | SEARCH_WORKSPACE_TOOL_NAME => payload
+            .get("results")
+            .and_then(Value::as_array)
+            .map(|results| {
+                results
+                    .iter()
+                    .filter_map(|result| result.get("path").and_then(Value::as_str))
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ExecutedToolCall, TurnCitation, extract_citations, parse_citation_markers};
+
+    fn call(tool_name: &str, output: &str) -> ExecutedToolCall {
+        ExecutedToolCall {
+            tool_name: tool_name.to_owned(),
+            arguments: serde_json::Value::Null,
+            output: output.to_owned(),
+            latency: Duration::ZERO,
+            attempts: 1,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn parse_citation_markers_collects_unique_numeric_markers() {
+        let markers =
+            parse_citation_markers("Rust is fast [2]. Also safe [1][2]. See [x] and [0].");
+        assert_eq!(markers, vec![1, 2]);
+    }
+
+    #[test]
+    fn extract_citations_maps_markers_to_tool_sources() {
+        let tool_calls = vec![
+            call(
+                "fetch_url",
+                r#"{"url":"https://example.com","final_url":"https://example.com/final","status_code":200}"#,
+            ),
+            call(
+                "search_notes",
+                r#"{"query":"rust","results":[{"title":"a","path":"notes/a.md"},{"title":"b","path":"notes/b.md"}]}"#,
+            ),
+            call("save_note", r#"{"title":"x","path":"notes/x.md"}"#),
+        ];
+
+        let citations =
+            extract_citations("Fetched [1], found [2], saved [3], bogus [9].", &tool_calls);
+
+        assert_eq!(
+            citations,
+            vec![
+                TurnCitation {
+                    marker: 1,
+                    tool_call_index: 0,
+                    tool_name: "fetch_url".to_owned(),
+                    sources: vec!["https://example.com/final".to_owned()],
+                },
+                TurnCitation {
+                    marker: 2,
+                    tool_call_index: 1,
+                    tool_name: "search_notes".to_owned(),
+                    sources: vec!["notes/a.md".to_owned(), "notes/b.md".to_owned()],
+                },
+                TurnCitation {
+                    marker: 3,
+                    tool_call_index: 2,
+                    tool_name: "save_note".to_owned(),
+                    sources: vec!["notes/x.md".to_owned()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn json_answers_cite_nothing() {
+        let tool_calls = [call("fetch_url", r#"{"final_url":"https://example.com/"}"#)];
+        assert!(extract_citations(r#"{"ids":[1],"matrix":[[1]]}"#, &tool_calls).is_empty());
+        assert!(extract_citations("[1]", &tool_calls).is_empty());
+        assert_eq!(extract_citations("Fetched [1].", &tool_calls).len(), 1);
+    }
+
+    #[test]
+    fn extract_citations_tolerates_non_json_tool_output() {
+        let citations = extract_citations("See [1].", &[call("fetch_url", "not json")]);
+        assert_eq!(citations.len(), 1);
+        assert!(citations[0].sources.is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{Result, anyhow};
+
+use super::session_summary::{build_summary_transcript, truncate_chars};
+use crate::model::client::{ChatResponse, ModelClient, ModelMessage};
+
+const COMPACTION_PROMPT: &str = "You compact a chat so it can continue without its earlier messages. Summarize the transcript you are given, including any earlier summary it starts with, as plain text: the user's goals and preferences, facts and decisions established so far, names, paths, and URLs mentioned, and open questions. Write at most 200 words and do not address the user.";
+const MAX_COMPACTION_SUMMARY_CHARS: usize = 2_000;
+
+/// Summary of `conversation` and the `previous` summary it continues, or `None` when
+/// it holds no user turn to compact.
+pub(super) async fn generate_compaction_summary(
+    client: &ModelClient,
+    previous: Option<&str>,
+    conversation: &[ModelMessage],
+) -> Result<Option<String>> {
+    let Some(transcript) = compaction_transcript(previous, conversation) else {
+        return Ok(None);
+    };
+
+    let response = client
+        .chat(COMPACTION_PROMPT, &transcript)
+        .await
+        .map_err(|error| anyhow!("compaction model call failed: {error}"))?;
+    let ChatResponse::FinalText { text } = response else {
+        return Err(anyhow!(
+            "compaction model call returned tool calls instead of text"
+        ));
+    };
+    let summary = text.trim();
+    if summary.is_empty() {
+        return Err(anyhow!("compaction model call returned an empty summary"));
+    }
+
+    Ok(Some(truncate_chars(summary, MAX_COMPACTION_SUMMARY_CHARS)))
+}
+
+fn compaction_transcript(previous: Option<&str>, conversation: &[ModelMessage]) -> Option<String> {
+    let transcript = build_summary_transcript(conversation)?;
+    Some(match previous {
+        Some(previous) => format!("earlier summary: {previous}\n{transcript}"),
+        None => transcript,
+    })
+}
+
+/// `system_prompt` followed by the `summary` of the turns compaction replaced.
+pub(super) fn system_prompt_with_summary(system_prompt: String, summary: Option<&str>) -> String {
+    match summary {
+        Some(summary) => format!(
+            "{system_prompt}\n\nEarlier turns of this conversation were compacted into this summary:\n{summary}"
+        ),
+        None => system_prompt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::client::ModelMessage;
+
+    use super::{compaction_transcript, system_prompt_with_summary};
+
+    #[test]
+    fn compaction_carries_the_previous_summary_forward() {
+        let conversation = vec![
+            ModelMessage::system("system prompt"),
+            ModelMessage::user("rename the crate"),
+            ModelMessage::assistant_text("Renamed it to mjolne."),
+        ];
+
+        assert_eq!(
+            compaction_transcript(Some("User works on a Rust CLI."), &conversation).as_deref(),
+            Some(
+                "earlier summary: User works on a Rust CLI.\nuser: rename the crate\nassistant: Renamed it to mjolne."
+            )
+        );
+        assert!(compaction_transcript(Some("kept"), &conversation[..1]).is_none());
+        assert_eq!(
+            system_prompt_with_summary("Be brief.".to_owned(), Some("Renamed the crate.")),
+            "Be brief.\n\nEarlier turns of this conversation were compacted into this summary:\nRenamed the crate."
+        );
+        assert_eq!(
+            system_prompt_with_summary("Be brief.".to_owned(), None),
+            "Be brief."
+        );
+    }
+}
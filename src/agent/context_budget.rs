@@ -0,0 +1,129 @@
+pub use crate::api::ContextUsage;
+use crate::model::client::{MessageRole, ModelMessage};
+use crate::tr;
+
+/// Rough characters per token for English prose and code; close enough for a gauge,
+/// not for billing.
+const CHARS_PER_TOKEN: usize = 4;
+/// Share of the budget at which the indicator turns to a warning.
+const WARNING_PERCENT: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextLevel {
+    Normal,
+    /// At least 80% of the budget.
+    NearLimit,
+    OverBudget,
+}
+
+impl ContextUsage {
+    pub fn measure(conversation: &[ModelMessage], budget_tokens: u32) -> Self {
+        let chars = conversation
+            .iter()
+            .map(|message| {
+                message.content.chars().count()
+                    + message
+                        .tool_calls
+                        .iter()
+                        .map(|call| call.name.chars().count() + call.arguments.to_string().len())
+                        .sum::<usize>()
+            })
+            .sum::<usize>();
+        Self {
+            messages: conversation
+                .iter()
+                .filter(|message| message.role != MessageRole::System)
+                .count(),
+            approx_tokens: chars.div_ceil(CHARS_PER_TOKEN),
+            budget_tokens: budget_tokens as usize,
+        }
+    }
+
+    pub fn level(&self) -> ContextLevel {
+        if self.approx_tokens > self.budget_tokens {
+            ContextLevel::OverBudget
+        } else if self.approx_tokens * 100 >= self.budget_tokens * WARNING_PERCENT {
+            ContextLevel::NearLimit
+        } else {
+            ContextLevel::Normal
+        }
+    }
+
+    /// `label()` in yellow near the limit and red over it, for the REPL prompt.
+    pub(super) fn prompt_status(&self) -> String {
+        let color = match self.level() {
+            ContextLevel::Normal => return self.label(),
+            ContextLevel::NearLimit => "33",
+            ContextLevel::OverBudget => "31",
+        };
+        format!("\x1b[{color}m{}\x1b[0m", self.label())
+    }
+
+    /// `4 msgs · ~1.2k/8.2k tokens`, localized.
+    pub fn label(&self) -> String {
+        tr!(
+            "context.usage",
+            messages = self.messages,
+            tokens = compact_count(self.approx_tokens),
+            budget = compact_count(self.budget_tokens)
+        )
+    }
+}
+
+/// `950`, `1.2k`, `12k`: short enough for a prompt.
+fn compact_count(count: usize) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..10_000 => format!("{:.1}k", count as f64 / 1_000.0),
+        _ => format!("{}k", count / 1_000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ContextLevel, ContextUsage, compact_count};
+    use crate::model::client::{ModelMessage, ModelToolCall};
+
+    #[test]
+    fn context_usage_counts_messages_and_approximate_tokens() {
+        let conversation = vec![
+            ModelMessage::system("s".repeat(40)),
+            ModelMessage::user("u".repeat(20)),
+            ModelMessage::assistant_tool_calls(
+                "",
+                vec![ModelToolCall {
+                    id: "call_1".to_owned(),
+                    name: "fetch_url".to_owned(),
+                    arguments: json!({}),
+                }],
+            ),
+            ModelMessage::tool_result("t".repeat(9), None, None),
+        ];
+
+        let usage = ContextUsage::measure(&conversation, 100);
+        assert_eq!(
+            usage,
+            ContextUsage {
+                messages: 3,
+                // 40 + 20 + "fetch_url" (9) + "{}" (2) + 9 = 80 chars.
+                approx_tokens: 20,
+                budget_tokens: 100,
+            }
+        );
+        assert_eq!(usage.level(), ContextLevel::Normal);
+        assert_eq!(
+            ContextUsage::measure(&conversation, 25).level(),
+            ContextLevel::NearLimit
+        );
+        assert_eq!(
+            ContextUsage::measure(&conversation, 19).level(),
+            ContextLevel::OverBudget
+        );
+
+        assert_eq!(compact_count(950), "950");
+        assert_eq!(compact_count(8_192), "8.2k");
+        assert_eq!(compact_count(32_768), "32k");
+    }
+}
@@ -0,0 +1,23 @@
+use super::ErrorCode;
+use crate::tools::PolicyRule;
+
+impl ErrorCode {
+    pub(super) fn for_policy_rule(rule: PolicyRule) -> Self {
+        match rule {
+            PolicyRule::Disabled => Self::ToolPolicyDisabled,
+            PolicyRule::Offline => Self::ToolPolicyOffline,
+            PolicyRule::Domain => Self::ToolPolicyDomain,
+            PolicyRule::Scheme => Self::ToolPolicyScheme,
+            PolicyRule::ContentType => Self::ToolPolicyContentType,
+            PolicyRule::Size => Self::ToolPolicySize,
+            PolicyRule::NoteTarget => Self::ToolPolicyNoteTarget,
+            PolicyRule::NotePath => Self::ToolPolicyNotePath,
+            PolicyRule::Overwrite => Self::ToolPolicyOverwrite,
+            PolicyRule::Delete => Self::ToolPolicyDelete,
+            PolicyRule::NoteQuota => Self::ToolPolicyNoteQuota,
+            PolicyRule::WorkspacePath => Self::ToolPolicyWorkspacePath,
+            PolicyRule::WorkspaceFileType => Self::ToolPolicyWorkspaceFileType,
+            PolicyRule::Command => Self::ToolPolicyCommand,
+        }
+    }
+}
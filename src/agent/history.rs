@@ -0,0 +1,506 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::warn;
+
+use super::history_audit::{sign_line, signing_key};
+use crate::build_info;
+use crate::config::AgentSettings;
+use crate::model::client::ModelMessage;
+use crate::text::truncate_graphemes;
+
+pub const DEFAULT_HISTORY_SEARCH_LIMIT: usize = 20;
+const HISTORY_SNIPPET_CHARS: usize = 160;
+/// Bytes read at a time from the end of the ledger when looking for its last entry.
+const TAIL_CHUNK_BYTES: u64 = 8 * 1024;
+
+/// Serializes appends from concurrent turns of one process, such as parallel studio turns.
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+static NEXT_HISTORY_SEQ: AtomicU64 = AtomicU64::new(1);
+
+/// Where a recorded turn ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySource {
+    Chat,
+    Repl,
+    Studio,
+    /// `POST /chat` and `/chat/stream` turns of `serve`.
+    Server,
+    /// Prompt runs of `serve` schedules.
+    Schedule,
+}
+
+impl HistorySource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::Repl => "repl",
+            Self::Studio => "studio",
+            Self::Server => "server",
+            Self::Schedule => "schedule",
+        }
+    }
+}
+
+/// One completed turn in the `AGENT_HISTORY_FILE` ledger. A turn continuing an earlier
+/// conversation names the previous turn in `parent_id`, so a conversation is rebuilt by
+/// following parents back to the first turn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub recorded_at_ms: u64,
+    /// Build that recorded the turn; empty in entries written before builds were recorded.
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub git_commit: String,
+    pub source: HistorySource,
+    /// Who sent the turn (`POST /chat` `user`, `--user`), when given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub message: String,
+    pub answer: String,
+    /// Messages the turn added: the user message, tool calls and results, and the answer.
+    pub messages: Vec<ModelMessage>,
+    /// `signature` of the entry before this one in the ledger, chaining signed entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_signature: Option<String>,
+    /// Hex HMAC-SHA256 of the entry without this field, keyed by
+    /// `AGENT_HISTORY_SIGNING_KEY`; unset while signing is off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// A `history search` hit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryMatch {
+    pub id: String,
+    pub recorded_at_ms: u64,
+    pub source: HistorySource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub message: String,
+    /// The first line of the answer (else the message) containing a query term.
+    pub snippet: String,
+}
+
+/// The append-only JSON Lines turn ledger at `AGENT_HISTORY_FILE`.
+#[derive(Debug, Clone)]
+pub struct TurnHistory {
+    path: PathBuf,
+    /// Signs appended entries when set.
+    signing_key: Option<hmac::Key>,
+}
+
+impl TurnHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            signing_key: None,
+        }
+    }
+
+    pub fn with_signing_key(mut self, signing_key: Option<hmac::Key>) -> Self {
+        self.signing_key = signing_key;
+        self
+    }
+
+    /// The configured ledger, or `None` when `AGENT_HISTORY_FILE` is unset.
+    pub fn from_settings(settings: &AgentSettings) -> Option<Self> {
+        settings
+            .history_file
+            .as_deref()
+            .map(|path| Self::new(path).with_signing_key(signing_key(settings)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a completed turn and returns its record. With a signing key the entry is
+    /// signed and chained to the ledger's last entry while the file is locked, so
+    /// processes sharing the ledger keep one chain.
+    pub fn record(
+        &self,
+        parent_id: Option<&str>,
+        source: HistorySource,
+        user: Option<&str>,
+        messages: Vec<ModelMessage>,
+        answer: &str,
+    ) -> Result<HistoryRecord> {
+        let recorded_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let message = messages
+            .first()
+            .map(|message| message.content.clone())
+            .unwrap_or_default();
+        let mut record = HistoryRecord {
+            id: format!(
+                "{recorded_at_ms:x}-{:x}-{}",
+                std::process::id(),
+                NEXT_HISTORY_SEQ.fetch_add(1, Ordering::Relaxed)
+            ),
+            parent_id: parent_id.map(str::to_owned),
+            recorded_at_ms,
+            version: build_info::VERSION.to_owned(),
+            git_commit: build_info::GIT_COMMIT.to_owned(),
+            source,
+            user: user.map(str::to_owned),
+            message,
+            answer: answer.to_owned(),
+            messages,
+            prev_signature: None,
+            signature: None,
+        };
+
+        let _guard = APPEND_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(parent) = self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open {}", self.path.display()))?;
+        if self.signing_key.is_some() {
+            file.lock()
+                .with_context(|| format!("failed to lock {}", self.path.display()))?;
+            record.prev_signature = self.last_signature(&mut file)?;
+        }
+        let mut line = serde_json::to_string(&record).context("failed to encode history record")?;
+        if let Some(key) = &self.signing_key {
+            let (signed_line, signature) = sign_line(key, &line)?;
+            line = signed_line;
+            record.signature = Some(signature);
+        }
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("failed to append to {}", self.path.display()))?;
+        Ok(record)
+    }
+
+    /// `signature` of the last entry in `file`, read from its end. A last line that does
+    /// not parse, such as one cut short by a crash, falls back to the last readable entry.
+    fn last_signature(&self, file: &mut fs::File) -> Result<Option<String>> {
+        let read_error = || format!("failed to read {}", self.path.display());
+        let mut end = file.seek(SeekFrom::End(0)).with_context(read_error)?;
+        let mut tail = Vec::new();
+        let last_line = loop {
+            let start = end.saturating_sub(TAIL_CHUNK_BYTES);
+            let mut chunk = vec![0; (end - start) as usize];
+            file.seek(SeekFrom::Start(start)).with_context(read_error)?;
+            file.read_exact(&mut chunk).with_context(read_error)?;
+            chunk.append(&mut tail);
+            tail = chunk;
+            end = start;
+            let content = tail.trim_ascii_end();
+            if let Some(newline) = content.iter().rposition(|byte| *byte == b'\n') {
+                break &content[newline + 1..];
+            }
+            if start == 0 {
+                break content;
+            }
+        };
+        if last_line.is_empty() {
+            return Ok(None);
+        }
+        match serde_json::from_slice::<HistoryRecord>(last_line) {
+            Ok(record) => Ok(record.signature),
+            Err(_) => Ok(self.load()?.pop().and_then(|record| record.signature)),
+        }
+    }
+
+    /// Every readable record in file order. A missing ledger has none; lines that do not
+    /// parse, such as one cut short by a crash, are skipped.
+    pub fn load(&self) -> Result<Vec<HistoryRecord>> {
+        let raw = match fs::read_to_string(&self.path) {
+            Ok(raw) => raw,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read {}", self.path.display()));
+            }
+        };
+        let mut skipped = 0;
+        let records = raw
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                serde_json::from_str::<HistoryRecord>(line)
+                    .inspect_err(|_| skipped += 1)
+                    .ok()
+            })
+            .collect();
+        if skipped > 0 {
+            warn!(path = %self.path.display(), skipped, "skipped unreadable history lines");
+        }
+        Ok(records)
+    }
+
+    /// Newest turns whose message or answer contains every word of `query`, ignoring case.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<HistoryMatch>> {
+        Ok(search_history(&self.load()?, query, limit))
+    }
+
+    /// The conversation up to and including turn `id`, oldest message first.
+    pub fn conversation_until(&self, id: &str) -> Result<Vec<ModelMessage>> {
+        let records = self.load()?;
+        let by_id = records
+            .iter()
+            .map(|record| (record.id.as_str(), record))
+            .collect::<HashMap<_, _>>();
+        let mut chain = Vec::new();
+        let mut next = Some(id);
+        while let Some(current) = next {
+            let Some(record) = by_id.get(current) else {
+                if chain.is_empty() {
+                    bail!("no turn `{id}` in {}", self.path.display());
+                }
+                bail!(
+                    "turn `{current}`, an earlier turn of `{id}`, is missing from {}",
+                    self.path.display()
+                );
+            };
+            if chain.len() == records.len() {
+                bail!(
+                    "turn `{id}` has a cyclic parent chain in {}",
+                    self.path.display()
+                );
+            }
+            chain.push(*record);
+            next = record.parent_id.as_deref();
+        }
+        Ok(chain
+            .into_iter()
+            .rev()
+            .flat_map(|record| record.messages.iter().cloned())
+            .collect())
+    }
+}
+
+/// Newest-first matches of every lowercase word of `query` in `records`.
+pub fn search_history(records: &[HistoryRecord], query: &str, limit: usize) -> Vec<HistoryMatch> {
+    let terms = query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    records
+        .iter()
+        .rev()
+        .filter(|record| {
+            let text = format!("{}\n{}", record.message, record.answer).to_lowercase();
+            terms.iter().all(|term| text.contains(term.as_str()))
+        })
+        .take(limit)
+        .map(|record| HistoryMatch {
+            id: record.id.clone(),
+            recorded_at_ms: record.recorded_at_ms,
+            source: record.source,
+            user: record.user.clone(),
+            message: record.message.clone(),
+            snippet: history_snippet(record, &terms),
+        })
+        .collect()
+}
+
+fn history_snippet(record: &HistoryRecord, terms: &[String]) -> String {
+    let line = [&record.answer, &record.message]
+        .into_iter()
+        .flat_map(|text| text.lines())
+        .find(|line| {
+            let line = line.to_lowercase();
+            terms.iter().any(|term| line.contains(term.as_str()))
+        })
+        .unwrap_or_default();
+    truncate_graphemes(line.trim(), HISTORY_SNIPPET_CHARS, "…")
+}
+
+/// `recorded_at_ms` as `YYYY-MM-DD HH:MM:SS UTC`.
+pub fn format_recorded_at(recorded_at_ms: u64) -> String {
+    let Ok(at) = OffsetDateTime::from_unix_timestamp_nanos(i128::from(recorded_at_ms) * 1_000_000)
+    else {
+        return recorded_at_ms.to_string();
+    };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        at.year(),
+        u8::from(at.month()),
+        at.day(),
+        at.hour(),
+        at.minute(),
+        at.second()
+    )
+}
+
+/// `history search`: prints the newest turns matching `query`.
+pub fn run_history_search_command(
+    settings: &AgentSettings,
+    query: &str,
+    limit: usize,
+    json: bool,
+) -> Result<()> {
+    let Some(history) = TurnHistory::from_settings(settings) else {
+        bail!("AGENT_HISTORY_FILE is not set, so no turns are recorded");
+    };
+    let matches = history.search(query, limit)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&matches).context("failed to encode history matches")?
+        );
+        return Ok(());
+    }
+    if matches.is_empty() {
+        println!("No recorded turns match `{}`.", query.trim());
+        return Ok(());
+    }
+    for hit in matches {
+        let source = match &hit.user {
+            Some(user) => format!("{} ({user})", hit.source.as_str()),
+            None => hit.source.as_str().to_owned(),
+        };
+        println!(
+            "{}  {}  {source}\n  > {}\n  {}\n",
+            hit.id,
+            format_recorded_at(hit.recorded_at_ms),
+            truncate_graphemes(hit.message.lines().next().unwrap_or_default(), 100, "…"),
+            hit.snippet
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{HistorySource, TurnHistory, format_recorded_at};
+    use crate::build_info;
+    use crate::model::client::ModelMessage;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    fn turn(message: &str, answer: &str) -> Vec<ModelMessage> {
+        vec![
+            ModelMessage::user(message),
+            ModelMessage::assistant_text(answer),
+        ]
+    }
+
+    #[test]
+    fn recorded_turns_are_searchable_and_resume_along_their_parents() {
+        let dir = temp_path("turn-history");
+        let history = TurnHistory::new(dir.join("nested/history.jsonl"));
+        assert!(history.load().expect("missing ledger").is_empty());
+
+        let first = history
+            .record(
+                None,
+                HistorySource::Repl,
+                Some("ada"),
+                turn("Plan the Tokio migration", "Start with the runtime."),
+                "Start with the runtime.",
+            )
+            .expect("record first turn");
+        let second = history
+            .record(
+                Some(&first.id),
+                HistorySource::Repl,
+                Some("ada"),
+                turn(
+                    "And the tests?",
+                    "Port the TOKIO tests last.\nThen benchmark.",
+                ),
+                "Port the TOKIO tests last.\nThen benchmark.",
+            )
+            .expect("record second turn");
+        history
+            .record(
+                None,
+                HistorySource::Chat,
+                None,
+                turn("weather", "Sunny."),
+                "Sunny.",
+            )
+            .expect("record unrelated turn");
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.version, build_info::VERSION);
+        assert_eq!(first.git_commit, build_info::GIT_COMMIT);
+
+        let hits = history.search("tokio", 10).expect("search");
+        assert_eq!(
+            hits.iter().map(|hit| hit.id.as_str()).collect::<Vec<_>>(),
+            [second.id.as_str(), first.id.as_str()]
+        );
+        assert_eq!(hits[0].snippet, "Port the TOKIO tests last.");
+        assert_eq!(hits[0].user.as_deref(), Some("ada"));
+        assert_eq!(hits[1].snippet, "Plan the Tokio migration");
+        assert_eq!(
+            history.search("tokio runtime", 10).expect("search").len(),
+            1
+        );
+        assert_eq!(history.search("tokio", 1).expect("search").len(), 1);
+        assert!(history.search("  ", 10).expect("search").is_empty());
+
+        let conversation = history.conversation_until(&second.id).expect("resume");
+        assert_eq!(
+            conversation
+                .iter()
+                .map(|message| message.content.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "Plan the Tokio migration",
+                "Start with the runtime.",
+                "And the tests?",
+                "Port the TOKIO tests last.\nThen benchmark."
+            ]
+        );
+        assert!(history.conversation_until("unknown").is_err());
+
+        let mut raw = fs::read_to_string(history.path()).expect("read ledger");
+        raw.push_str(
+            "{\"id\":\"old\",\"recorded_at_ms\":1,\"source\":\"chat\",\"message\":\"m\",\"answer\":\"a\",\"messages\":[]}\n",
+        );
+        raw.push_str("{\"id\": \"cut-");
+        fs::write(history.path(), raw).expect("append a torn line");
+        let records = history.load().expect("load");
+        assert_eq!(records.len(), 4);
+        assert_eq!(
+            records[3].version, "",
+            "entries without build metadata still load"
+        );
+        remove_dir_if_exists(&dir);
+    }
+
+    #[test]
+    fn recorded_at_is_formatted_in_utc() {
+        assert_eq!(
+            format_recorded_at(1_773_446_400_000),
+            "2026-03-14 00:00:00 UTC"
+        );
+    }
+}
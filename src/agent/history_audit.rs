@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use ring::hmac;
+use serde::Serialize;
+
+use super::history::HistoryRecord;
+use crate::config::AgentSettings;
+use crate::text::hex_lower;
+
+/// The `AGENT_HISTORY_SIGNING_KEY` HMAC-SHA256 key, if one is configured.
+pub(super) fn signing_key(settings: &AgentSettings) -> Option<hmac::Key> {
+    settings
+        .history_signing_key
+        .as_deref()
+        .map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()))
+}
+
+/// Signs `unsigned`, the JSON line of a record without its `signature`, and returns the
+/// hex HMAC-SHA256 with the ledger line: the same bytes with `signature` added as the
+/// last field. The line includes `prev_signature`, so each signature also covers the
+/// entry before it.
+pub(super) fn sign_line(key: &hmac::Key, unsigned: &str) -> Result<(String, String)> {
+    let Some(fields) = unsigned.strip_suffix('}') else {
+        bail!("history record did not encode as a JSON object");
+    };
+    let signature = hex_lower(hmac::sign(key, unsigned.as_bytes()).as_ref());
+    let line = format!("{fields},\"signature\":\"{signature}\"}}");
+    Ok((line, signature))
+}
+
+/// Whether `signature` is the HMAC of the exact bytes of `line` before its trailing
+/// `signature` field. Any other byte change, reordered fields or whitespace included,
+/// fails the check.
+fn line_signature_matches(key: &hmac::Key, line: &str, signature: &str) -> bool {
+    let suffix = format!(",\"signature\":\"{signature}\"}}");
+    let Some(fields) = line.trim_end().strip_suffix(suffix.as_str()) else {
+        return false;
+    };
+    hex_lower(hmac::sign(key, format!("{fields}}}").as_bytes()).as_ref()) == signature
+}
+
+/// What `audit verify` found in a ledger.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LedgerAudit {
+    /// Readable entries, signed or not.
+    pub entries: usize,
+    pub signed: usize,
+    /// Entries recorded before signing was turned on; they precede every signed one.
+    pub unsigned_before_signing: usize,
+    /// Signature of the newest signed entry; keep a copy elsewhere to detect truncation.
+    pub head_signature: Option<String>,
+    /// One line per failed check, naming the ledger line; empty when the chain is intact.
+    pub problems: Vec<String>,
+}
+
+/// Checks every entry of the ledger at `path`: signed entries must carry a valid
+/// signature and name the previous signed entry in `prev_signature`, and no unsigned or
+/// unreadable line may follow the first signed one.
+pub fn audit_ledger(path: &Path, key: &hmac::Key) -> Result<LedgerAudit> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut audit = LedgerAudit::default();
+    // Line number and signature of the latest signed entry.
+    let mut previous: Option<(usize, String)> = None;
+    for (index, line) in raw.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(record) = serde_json::from_str::<HistoryRecord>(line) else {
+            audit
+                .problems
+                .push(format!("line {line_number}: not a readable ledger entry"));
+            continue;
+        };
+        audit.entries += 1;
+        let Some(signature) = record.signature.as_deref() else {
+            if previous.is_some() {
+                audit.problems.push(format!(
+                    "line {line_number} ({}): unsigned entry after signed entries",
+                    record.id
+                ));
+            } else {
+                audit.unsigned_before_signing += 1;
+            }
+            continue;
+        };
+        audit.signed += 1;
+        if !line_signature_matches(key, line, signature) {
+            audit.problems.push(format!(
+                "line {line_number} ({}): signature does not match; the entry was changed or signed with another key",
+                record.id
+            ));
+        }
+        let expected_previous = previous.as_ref().map(|(_, signature)| signature.as_str());
+        if record.prev_signature.as_deref() != expected_previous {
+            let after = match &previous {
+                Some((previous_line, _)) => format!("line {previous_line}"),
+                None => "the start of the signed entries".to_owned(),
+            };
+            audit.problems.push(format!(
+                "line {line_number} ({}): chain broken after {after}; an entry was removed, reordered, or inserted",
+                record.id
+            ));
+        }
+        previous = Some((line_number, signature.to_owned()));
+    }
+    audit.head_signature = previous.map(|(_, signature)| signature);
+    Ok(audit)
+}
+
+/// `audit verify`: checks the signatures and chain of the turn ledger at `file`, else
+/// `AGENT_HISTORY_FILE`, and fails when any check does.
+pub fn run_audit_verify_command(
+    settings: &AgentSettings,
+    file: Option<&Path>,
+    json: bool,
+) -> Result<()> {
+    let Some(path) = file.or(settings.history_file.as_deref().map(Path::new)) else {
+        bail!("AGENT_HISTORY_FILE is not set; pass --file to name a ledger");
+    };
+    let Some(key) = signing_key(settings) else {
+        bail!("AGENT_HISTORY_SIGNING_KEY is not set, so signatures cannot be checked");
+    };
+    let audit = audit_ledger(path, &key)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&audit).context("failed to encode ledger audit")?
+        );
+    } else {
+        for problem in &audit.problems {
+            println!("FAIL {problem}");
+        }
+        println!(
+            "{}: {} entries, {} signed, {} unsigned before signing",
+            path.display(),
+            audit.entries,
+            audit.signed,
+            audit.unsigned_before_signing
+        );
+        if let Some(head) = &audit.head_signature {
+            println!("head signature: {head}");
+        }
+    }
+    if !audit.problems.is_empty() {
+        bail!(
+            "{} problem(s) found in {}",
+            audit.problems.len(),
+            path.display()
+        );
+    }
+    if audit.signed == 0 {
+        bail!("{} has no signed entries", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use ring::hmac;
+
+    use super::audit_ledger;
+    use crate::agent::history::{HistorySource, TurnHistory};
+    use crate::model::client::ModelMessage;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    fn record(history: &TurnHistory, message: &str) {
+        history
+            .record(
+                None,
+                HistorySource::Chat,
+                None,
+                vec![
+                    ModelMessage::user(message),
+                    ModelMessage::assistant_text("ok"),
+                ],
+                "ok",
+            )
+            .expect("record turn");
+    }
+
+    #[test]
+    fn audit_detects_edited_removed_and_unsigned_entries() {
+        let dir = temp_path("history-audit");
+        let path = dir.join("history.jsonl");
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"ledger-secret");
+        record(&TurnHistory::new(&path), "before signing");
+        let signed = TurnHistory::new(&path).with_signing_key(Some(key.clone()));
+        for message in ["first", "second", "third"] {
+            record(&signed, message);
+        }
+
+        let audit = audit_ledger(&path, &key).expect("audit");
+        assert_eq!(
+            (audit.entries, audit.signed, audit.unsigned_before_signing),
+            (4, 3, 1)
+        );
+        assert!(audit.problems.is_empty(), "{:?}", audit.problems);
+        let other_key = hmac::Key::new(hmac::HMAC_SHA256, b"other");
+        assert_eq!(
+            audit_ledger(&path, &other_key)
+                .expect("audit")
+                .problems
+                .len(),
+            3
+        );
+
+        let intact = fs::read_to_string(&path).expect("read ledger");
+        let lines = intact.lines().collect::<Vec<_>>();
+        fs::write(&path, intact.replacen("\"second\"", "\"edited\"", 1)).expect("edit");
+        let problems = audit_ledger(&path, &key).expect("audit").problems;
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].starts_with("line 3 "), "{problems:?}");
+
+        // Same record once parsed, different bytes on disk.
+        fs::write(&path, intact.replacen("\"first\"", "\"\\u0066irst\"", 1)).expect("re-escape");
+        let problems = audit_ledger(&path, &key).expect("audit").problems;
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].starts_with("line 2 "), "{problems:?}");
+
+        fs::write(&path, format!("{}\n{}\n{}\n", lines[0], lines[1], lines[3])).expect("remove");
+        let problems = audit_ledger(&path, &key).expect("audit").problems;
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(
+            problems[0].contains("chain broken after line 2"),
+            "{problems:?}"
+        );
+
+        fs::write(&path, format!("{}\n{}\n", lines[1], lines[0])).expect("reorder");
+        let problems = audit_ledger(&path, &key).expect("audit").problems;
+        assert!(
+            problems[0].contains("unsigned entry after signed"),
+            "{problems:?}"
+        );
+
+        fs::write(&path, format!("{intact}{{\"id\": \"cut-\n")).expect("tear last line");
+        record(&signed, "after a torn line");
+        let problems = audit_ledger(&path, &key).expect("audit").problems;
+        assert_eq!(problems, ["line 5: not a readable ledger entry"]);
+        remove_dir_if_exists(&dir);
+    }
+}
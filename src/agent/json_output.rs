@@ -0,0 +1,148 @@
+use serde_json::{Map, Value};
+
+use crate::text::grapheme_count;
+
+use super::ChatTurnOutcome;
+use super::session_summary::truncate_chars;
+use super::timeline::timeline_events;
+
+/// Serializes a turn outcome for `chat --json` and `POST /chat`, adding a flat `timeline`
+/// of model and tool call start/end events built from `trace.timeline`.
+///
+/// With `max_field_chars`, tool-call `output`, `error`, and string `arguments` values longer
+/// than the limit are shortened, and the call gets `truncated: true` plus the list of
+/// `truncated_fields`. `None` (`--full`) keeps every field intact.
+pub fn outcome_json(
+    outcome: &ChatTurnOutcome,
+    max_field_chars: Option<usize>,
+) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(outcome)?;
+    if let Some(fields) = value.as_object_mut() {
+        fields.insert(
+            "timeline".to_owned(),
+            serde_json::to_value(timeline_events(&outcome.trace.timeline))?,
+        );
+    }
+    if let Some(max_chars) = max_field_chars
+        && let Some(calls) = value.get_mut("tool_calls").and_then(Value::as_array_mut)
+    {
+        for call in calls.iter_mut().filter_map(Value::as_object_mut) {
+            truncate_tool_call(call, max_chars);
+        }
+    }
+    Ok(value)
+}
+
+fn truncate_tool_call(call: &mut Map<String, Value>, max_chars: usize) {
+    let mut truncated_fields = Vec::new();
+    for field in ["output", "arguments", "error"] {
+        if let Some(value) = call.get_mut(field) {
+            truncate_strings(value, field.to_owned(), max_chars, &mut truncated_fields);
+        }
+    }
+    if !truncated_fields.is_empty() {
+        call.insert("truncated".to_owned(), Value::Bool(true));
+        call.insert("truncated_fields".to_owned(), Value::from(truncated_fields));
+    }
+}
+
+fn truncate_strings(
+    value: &mut Value,
+    path: String,
+    max_chars: usize,
+    truncated: &mut Vec<String>,
+) {
+    match value {
+        Value::String(text) if grapheme_count(text) > max_chars => {
+            *text = truncate_chars(text, max_chars);
+            truncated.push(path);
+        }
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                truncate_strings(field, format!("{path}.{name}"), max_chars, truncated);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                truncate_strings(item, format!("{path}[{index}]"), max_chars, truncated);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::outcome_json;
+    use crate::agent::{ChatTurnOutcome, ExecutedToolCall, TurnTraceSummary};
+
+    fn outcome_with_tool_call(output: &str, body: &str) -> ChatTurnOutcome {
+        ChatTurnOutcome {
+            final_text: "done".to_owned(),
+            trace: TurnTraceSummary {
+                version: crate::build_info::VERSION.to_owned(),
+                git_commit: crate::build_info::GIT_COMMIT.to_owned(),
+                input_chars: 4,
+                output_chars: Some(4),
+                steps_executed: 2,
+                model_calls: 2,
+                tool_calls: 1,
+                total_model_latency: Duration::from_millis(2),
+                total_tool_latency: Duration::from_millis(1),
+                tool_names: vec!["save_note".to_owned()],
+                stripped_tool_call_blocks: 0,
+                trimmed_messages: 0,
+                compacted_messages: 0,
+                user: None,
+                injection_detections: Vec::new(),
+                timeline: Vec::new(),
+                context: Default::default(),
+            },
+            tool_calls: vec![ExecutedToolCall {
+                tool_name: "save_note".to_owned(),
+                arguments: json!({ "title": "t", "body": body }),
+                output: output.to_owned(),
+                latency: Duration::from_millis(1),
+                attempts: 1,
+                error: None,
+            }],
+            citations: Vec::new(),
+            moderation: None,
+            was_repaired_locally: false,
+        }
+    }
+
+    #[test]
+    fn long_tool_fields_are_truncated_and_marked() {
+        let outcome = outcome_with_tool_call(&"x".repeat(20), &"b".repeat(12));
+
+        let value = outcome_json(&outcome, Some(10)).expect("outcome should serialize");
+
+        let call = &value["tool_calls"][0];
+        assert_eq!(call["output"], format!("{}…", "x".repeat(9)));
+        assert_eq!(call["arguments"]["title"], "t");
+        assert_eq!(call["arguments"]["body"], format!("{}…", "b".repeat(9)));
+        assert_eq!(call["truncated"], true);
+        assert_eq!(
+            call["truncated_fields"],
+            json!(["output", "arguments.body"])
+        );
+    }
+
+    #[test]
+    fn short_fields_and_full_output_are_left_intact() {
+        let outcome = outcome_with_tool_call("short", &"b".repeat(12));
+
+        let limited = outcome_json(&outcome, Some(100)).expect("outcome should serialize");
+        let full = outcome_json(&outcome, None).expect("outcome should serialize");
+
+        assert_eq!(limited, full);
+        assert_eq!(full["timeline"], json!([]));
+        assert!(full["tool_calls"][0].get("truncated").is_none());
+        assert_eq!(full["tool_calls"][0]["arguments"]["body"], "b".repeat(12));
+    }
+}
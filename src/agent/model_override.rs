@@ -0,0 +1,139 @@
+use crate::config::{AgentSettings, ModelProvider, is_local_base_url};
+
+/// A provider and/or model that replaces the configured ones for some turns: one HTTP
+/// `/chat` turn, one `chat --model` run, or the rest of a REPL session after `/model`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelOverride {
+    /// Defaults to the configured provider.
+    pub provider: Option<ModelProvider>,
+    /// Defaults to the configured model, which is only allowed with the same provider.
+    pub model: Option<String>,
+}
+
+impl ModelOverride {
+    pub fn model(model: impl Into<String>) -> Self {
+        Self {
+            provider: None,
+            model: Some(model.into()),
+        }
+    }
+
+    /// An override from raw request fields, or a client-facing reason they are invalid.
+    pub fn parse(provider: Option<&str>, model: Option<&str>) -> Result<Self, String> {
+        let provider = provider
+            .map(|raw| {
+                raw.parse::<ModelProvider>().map_err(|_| {
+                    format!(
+                        "unknown provider `{raw}`; expected `ollama`, `openai`, `gemini`, or `compatible`"
+                    )
+                })
+            })
+            .transpose()?;
+        let model = match model.map(str::trim) {
+            Some("") => return Err("`model` must not be empty".to_owned()),
+            model => model.map(str::to_owned),
+        };
+        Ok(Self { provider, model })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.provider.is_none() && self.model.is_none()
+    }
+
+    /// `settings` with this provider and model, or a client-facing reason they cannot
+    /// be used: a provider without its key or base URL, or a hosted one while
+    /// `AGENT_OFFLINE=true`.
+    pub fn resolve(&self, settings: &AgentSettings) -> Result<AgentSettings, String> {
+        let provider = self.provider.unwrap_or(settings.model_provider);
+        let model = match self.model.as_deref().map(str::trim) {
+            Some("") => return Err("`model` must not be empty".to_owned()),
+            Some(model) => model,
+            None if provider == settings.model_provider => settings.model.as_str(),
+            None => return Err(format!("provider `{provider}` requires a `model`")),
+        };
+        let stays_local = match provider {
+            ModelProvider::Ollama => is_local_base_url(&settings.ollama_base_url),
+            ModelProvider::Compatible => settings
+                .openai_compat_base_url
+                .as_deref()
+                .is_some_and(is_local_base_url),
+            ModelProvider::OpenAi | ModelProvider::Gemini => false,
+        };
+        if !stays_local && settings.offline {
+            return Err(format!(
+                "provider `{provider}` is unavailable while AGENT_OFFLINE=true"
+            ));
+        }
+        let missing_key = match provider {
+            ModelProvider::Ollama => None,
+            ModelProvider::OpenAi => settings
+                .openai_api_key
+                .is_none()
+                .then_some("OPENAI_API_KEY"),
+            ModelProvider::Gemini => settings
+                .gemini_api_key
+                .is_none()
+                .then_some("GEMINI_API_KEY"),
+            ModelProvider::Compatible => settings
+                .openai_compat_base_url
+                .is_none()
+                .then_some("OPENAI_COMPAT_BASE_URL"),
+        };
+        if let Some(key) = missing_key {
+            return Err(format!("provider `{provider}` requires {key} to be set"));
+        }
+        let mut resolved = settings.clone();
+        resolved.model_provider = provider;
+        resolved.model = model.to_owned();
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModelOverride;
+    use crate::agent::tests::test_settings;
+    use crate::config::ModelProvider;
+
+    #[test]
+    fn overrides_default_to_the_configured_provider_and_model() {
+        let mut settings = test_settings();
+        settings.model_provider = ModelProvider::Ollama;
+        settings.model = "qwen2.5:3b".to_owned();
+
+        let resolved = ModelOverride::model(" llama3 ")
+            .resolve(&settings)
+            .expect("a model on the configured provider");
+        assert_eq!(resolved.model_provider, ModelProvider::Ollama);
+        assert_eq!(resolved.model, "llama3");
+        assert_eq!(
+            ModelOverride::default().resolve(&settings),
+            Ok(settings.clone())
+        );
+
+        let provider_only =
+            ModelOverride::parse(Some("gemini"), None).expect("a known provider parses");
+        let error = provider_only
+            .resolve(&settings)
+            .expect_err("another provider needs a model");
+        assert!(error.contains("requires a `model`"));
+        assert!(ModelOverride::parse(Some("anthropic"), Some("x")).is_err());
+        assert!(ModelOverride::parse(None, Some("  ")).is_err());
+    }
+    #[test]
+    fn offline_overrides_only_reach_local_providers() {
+        let mut settings = test_settings();
+        settings.offline = true;
+        settings.ollama_base_url = "http://127.0.0.1:11434".to_owned();
+
+        ModelOverride::model("llama3")
+            .resolve(&settings)
+            .expect("a local Ollama stays available offline");
+
+        settings.ollama_base_url = "https://ollama.example.com".to_owned();
+        let error = ModelOverride::model("llama3")
+            .resolve(&settings)
+            .expect_err("a remote Ollama is a network provider");
+        assert!(error.contains("AGENT_OFFLINE"), "{error}");
+    }
+}
@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+
+pub use crate::api::{ModerationAction, ModerationDecision};
+use crate::config::{AgentSettings, ModerationMode, ModerationProvider};
+
+const OPENAI_MODERATION_URL: &str = "https://api.openai.com/v1/moderations";
+const OPENAI_MODERATION_MODEL: &str = "omni-moderation-latest";
+const REDACTED_PLACEHOLDER: &str = "[redacted]";
+
+/// Error attached to a turn whose final answer was withheld by moderation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("response blocked by moderation ({})", .decision.categories.join(", "))]
+pub(crate) struct ModerationBlocked {
+    pub decision: ModerationDecision,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ModeratedAnswer {
+    pub text: String,
+    pub decision: ModerationDecision,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModerationResponse {
+    results: Vec<OpenAiModerationResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModerationResult {
+    flagged: bool,
+    #[serde(default)]
+    categories: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Reviews final answers against the configured moderation provider.
+pub(crate) struct Moderator {
+    mode: ModerationMode,
+    provider: ModerationProvider,
+    matchers: Vec<(String, Regex)>,
+    openai_api_key: Option<String>,
+    http_client: reqwest::Client,
+    timeout: Duration,
+}
+
+impl Moderator {
+    pub fn from_settings(settings: &AgentSettings) -> Self {
+        let keyword_matchers = settings.moderation_keywords.iter().map(|keyword| {
+            let regex = Regex::new(&format!("(?i){}", regex::escape(keyword)));
+            (format!("keyword:{keyword}"), regex)
+        });
+        // Patterns are validated when settings load, so compile failures are not expected here.
+        let pattern_matchers = settings
+            .moderation_patterns
+            .iter()
+            .map(|pattern| (format!("pattern:{pattern}"), Regex::new(pattern)));
+        let matchers = keyword_matchers
+            .chain(pattern_matchers)
+            .filter_map(|(category, regex)| regex.ok().map(|regex| (category, regex)))
+            .collect();
+
+        Self {
+            mode: settings.moderation_mode,
+            provider: settings.moderation_provider,
+            matchers,
+            openai_api_key: settings.openai_api_key.clone(),
+            http_client: reqwest::Client::new(),
+            timeout: Duration::from_millis(settings.model_timeout_ms),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.mode != ModerationMode::Off
+    }
+
+    /// Returns the (possibly redacted) answer, or `ModerationBlocked` in block mode.
+    pub async fn review(&self, text: &str) -> Result<ModeratedAnswer> {
+        let (categories, redacted) = match self.provider {
+            ModerationProvider::Local => self.review_local(text),
+            ModerationProvider::OpenAi => {
+                let categories = self.review_openai(text).await?;
+                (categories, REDACTED_PLACEHOLDER.to_owned())
+            }
+        };
+        apply_moderation_mode(self.mode, self.provider, text, categories, redacted)
+    }
+
+    fn review_local(&self, text: &str) -> (Vec<String>, String) {
+        let mut categories = Vec::new();
+        let mut redacted = text.to_owned();
+        for (category, regex) in &self.matchers {
+            if regex.is_match(&redacted) {
+                categories.push(category.clone());
+                redacted = regex
+                    .replace_all(&redacted, REDACTED_PLACEHOLDER)
+                    .into_owned();
+            }
+        }
+        (categories, redacted)
+    }
+
+    async fn review_openai(&self, text: &str) -> Result<Vec<String>> {
+        let api_key = self
+            .openai_api_key
+            .as_deref()
+            .ok_or_else(|| anyhow!("OPENAI_API_KEY is required for openai moderation"))?;
+        let response = self
+            .http_client
+            .post(OPENAI_MODERATION_URL)
+            .bearer_auth(api_key)
+            .timeout(self.timeout)
+            .json(&json!({ "model": OPENAI_MODERATION_MODEL, "input": text }))
+            .send()
+            .await
+            .context("openai moderation request failed")?
+            .error_for_status()
+            .context("openai moderation returned an error status")?;
+        let body = response
+            .text()
+            .await
+            .context("failed to read openai moderation response")?;
+        parse_openai_moderation_categories(&body)
+    }
+}
+
+fn apply_moderation_mode(
+    mode: ModerationMode,
+    provider: ModerationProvider,
+    text: &str,
+    categories: Vec<String>,
+    redacted: String,
+) -> Result<ModeratedAnswer> {
+    let action = match (categories.is_empty(), mode) {
+        (true, _) | (false, ModerationMode::Off) => ModerationAction::Allowed,
+        (false, ModerationMode::Flag) => ModerationAction::Flagged,
+        (false, ModerationMode::Redact) => ModerationAction::Redacted,
+        (false, ModerationMode::Block) => ModerationAction::Blocked,
+    };
+    let decision = ModerationDecision {
+        action,
+        provider: provider.as_str().to_owned(),
+        categories,
+    };
+
+    match action {
+        ModerationAction::Blocked => Err(ModerationBlocked { decision }.into()),
+        ModerationAction::Redacted => Ok(ModeratedAnswer {
+            text: redacted,
+            decision,
+        }),
+        ModerationAction::Allowed | ModerationAction::Flagged => Ok(ModeratedAnswer {
+            text: text.to_owned(),
+            decision,
+        }),
+    }
+}
+
+/// Flagged categories from an OpenAI moderation response; empty when not flagged.
+fn parse_openai_moderation_categories(body: &str) -> Result<Vec<String>> {
+    let response = serde_json::from_str::<OpenAiModerationResponse>(body)
+        .context("failed to decode openai moderation response")?;
+    let result = response
+        .results
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("openai moderation response contained no results"))?;
+    if !result.flagged {
+        return Ok(Vec::new());
+    }
+
+    let mut categories = result
+        .categories
+        .into_iter()
+        .filter(|(_, flagged)| flagged.as_bool() == Some(true))
+        .map(|(category, _)| category)
+        .collect::<Vec<_>>();
+    categories.sort();
+    if categories.is_empty() {
+        categories.push("flagged".to_owned());
+    }
+    Ok(categories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ModerationAction, ModerationBlocked, Moderator, apply_moderation_mode,
+        parse_openai_moderation_categories,
+    };
+    use crate::agent::tests::test_settings;
+    use crate::config::{AgentSettings, ModerationMode, ModerationProvider};
+
+    fn local_moderator(mode: ModerationMode) -> Moderator {
+        let settings = AgentSettings {
+            moderation_mode: mode,
+            moderation_keywords: vec!["hunter2".to_owned()],
+            moderation_patterns: vec![r"\b\d{4}-\d{4}-\d{4}-\d{4}\b".to_owned()],
+            ..test_settings()
+        };
+        Moderator::from_settings(&settings)
+    }
+
+    #[tokio::test]
+    async fn local_review_flags_without_changing_text() {
+        let moderator = local_moderator(ModerationMode::Flag);
+
+        let answer = moderator
+            .review("The password is HUNTER2.")
+            .await
+            .expect("flag mode never blocks");
+
+        assert_eq!(answer.text, "The password is HUNTER2.");
+        assert_eq!(answer.decision.action, ModerationAction::Flagged);
+        assert_eq!(answer.decision.categories, vec!["keyword:hunter2"]);
+    }
+
+    #[tokio::test]
+    async fn local_review_redacts_keyword_and_pattern_matches() {
+        let moderator = local_moderator(ModerationMode::Redact);
+
+        let answer = moderator
+            .review("Card 1234-5678-9012-3456, password hunter2.")
+            .await
+            .expect("redact mode never blocks");
+
+        assert_eq!(answer.text, "Card [redacted], password [redacted].");
+        assert_eq!(answer.decision.action, ModerationAction::Redacted);
+        assert_eq!(answer.decision.categories.len(), 2);
+
+        let clean = moderator.review("Nothing to see.").await.expect("clean");
+        assert_eq!(clean.text, "Nothing to see.");
+        assert_eq!(clean.decision.action, ModerationAction::Allowed);
+    }
+
+    #[tokio::test]
+    async fn local_review_blocks_with_decision_attached() {
+        let moderator = local_moderator(ModerationMode::Block);
+
+        let error = moderator
+            .review("hunter2")
+            .await
+            .expect_err("block mode rejects matches");
+        let blocked = error
+            .downcast_ref::<ModerationBlocked>()
+            .expect("blocked error");
+
+        assert_eq!(blocked.decision.action, ModerationAction::Blocked);
+        assert_eq!(blocked.decision.provider, "local");
+    }
+
+    #[test]
+    fn apply_moderation_mode_replaces_whole_answer_for_provider_redaction() {
+        let answer = apply_moderation_mode(
+            ModerationMode::Redact,
+            ModerationProvider::OpenAi,
+            "bad text",
+            vec!["violence".to_owned()],
+            "[redacted]".to_owned(),
+        )
+        .expect("redacted");
+
+        assert_eq!(answer.text, "[redacted]");
+        assert_eq!(answer.decision.provider, "openai");
+    }
+
+    #[test]
+    fn parse_openai_moderation_categories_collects_flagged_categories() {
+        let flagged = r#"{"results":[{"flagged":true,"categories":{"hate":false,"violence":true,"harassment":true}}]}"#;
+        assert_eq!(
+            parse_openai_moderation_categories(flagged).expect("valid response"),
+            vec!["harassment".to_owned(), "violence".to_owned()]
+        );
+
+        let clean = r#"{"results":[{"flagged":false,"categories":{"violence":false}}]}"#;
+        assert!(
+            parse_openai_moderation_categories(clean)
+                .expect("valid response")
+                .is_empty()
+        );
+        assert!(parse_openai_moderation_categories(r#"{"results":[]}"#).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+
+use anyhow::{Context, Result};
+use tracing::warn;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::repl_input::ReplInput;
+use crate::editor::split_command_words;
+use crate::tr;
+
+pub(super) const PAGER_COMMAND: &str = "/pager";
+/// Used when neither the terminal nor `$LINES`/`$COLUMNS` report a size.
+const DEFAULT_TERMINAL_SIZE: (usize, usize) = (24, 80);
+
+/// Prints REPL answers, paging the ones taller than the terminal through `$PAGER` or the
+/// built-in pager.
+pub(super) struct ReplPager {
+    enabled: bool,
+    /// Off unless stdin and stdout are both terminals.
+    interactive: bool,
+}
+
+impl ReplPager {
+    pub(super) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            interactive: io::stdin().is_terminal() && io::stdout().is_terminal(),
+        }
+    }
+
+    /// Handles `/pager on|off` and returns the message to print.
+    pub(super) fn run_command(&mut self, rest: &str) -> String {
+        match rest.trim() {
+            "on" => {
+                self.enabled = true;
+                tr!("repl.pager.enabled").to_owned()
+            }
+            "off" => {
+                self.enabled = false;
+                tr!("repl.pager.disabled").to_owned()
+            }
+            _ => tr!(
+                "repl.pager.usage",
+                command = PAGER_COMMAND,
+                state = if self.enabled { "on" } else { "off" }
+            ),
+        }
+    }
+
+    pub(super) fn show(&self, text: &str, input: &mut ReplInput) -> Result<()> {
+        let (rows, columns) = terminal_size();
+        let lines = screen_lines(text, columns);
+        if !self.enabled || !self.interactive || lines.len() < rows {
+            println!("{text}");
+            return Ok(());
+        }
+        if let Some(command) = std::env::var("PAGER")
+            .ok()
+            .filter(|command| !command.trim().is_empty())
+        {
+            match spawn_external_pager(&command) {
+                Ok(child) => return feed_external_pager(child, text),
+                Err(error) => warn!(
+                    error = %format!("{error:#}"),
+                    "falling back to the built-in pager"
+                ),
+            }
+        }
+        run_builtin_pager(&lines, rows - 1, input)
+    }
+}
+
+fn spawn_external_pager(command: &str) -> Result<Child> {
+    let mut words = split_command_words(command).context("invalid `$PAGER`")?;
+    let program = words.remove(0);
+    Command::new(&program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to start pager `{program}`"))
+}
+
+fn feed_external_pager(mut child: Child, text: &str) -> Result<()> {
+    let written = child.stdin.take().map_or(Ok(()), |mut stdin| {
+        stdin
+            .write_all(text.as_bytes())
+            .and_then(|()| stdin.write_all(b"\n"))
+    });
+    child.wait().context("failed to wait for pager")?;
+    match written {
+        // The pager closes its input when quit before the end of the answer.
+        Err(error) if error.kind() != io::ErrorKind::BrokenPipe => {
+            Err(error).context("failed to write answer to pager")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Shows `lines` a page at a time, reading pager commands from the REPL input.
+fn run_builtin_pager(lines: &[String], page_rows: usize, input: &mut ReplInput) -> Result<()> {
+    let mut view = PagerView::new(lines, page_rows);
+    view.print_page();
+    loop {
+        let prompt = tr!(
+            "repl.pager.prompt",
+            first = view.top + 1,
+            last = view.bottom(),
+            total = lines.len()
+        );
+        let Some(command) = input.read_line_after(&prompt)? else {
+            return Ok(());
+        };
+        match view.apply(&command) {
+            PagerAction::Show => view.print_page(),
+            PagerAction::Prompt => {}
+            PagerAction::NotFound(pattern) => {
+                println!("{}", tr!("repl.pager.not_found", pattern = pattern));
+            }
+            PagerAction::Quit => return Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PagerAction {
+    Show,
+    Prompt,
+    NotFound(String),
+    Quit,
+}
+
+/// Built-in pager position: the first shown line and the last search.
+#[derive(Debug)]
+struct PagerView<'a> {
+    lines: &'a [String],
+    page_rows: usize,
+    top: usize,
+    /// Lowercased pattern repeated by `n`.
+    search: Option<String>,
+}
+
+impl<'a> PagerView<'a> {
+    fn new(lines: &'a [String], page_rows: usize) -> Self {
+        Self {
+            lines,
+            page_rows: page_rows.max(1),
+            top: 0,
+            search: None,
+        }
+    }
+
+    fn bottom(&self) -> usize {
+        (self.top + self.page_rows).min(self.lines.len())
+    }
+
+    fn print_page(&self) {
+        for line in &self.lines[self.top..self.bottom()] {
+            println!("{line}");
+        }
+    }
+
+    /// Enter pages forward (and quits on the last page), `b` pages back, `/text` searches
+    /// case-insensitively from the line after the top one, `n` repeats it, `q` quits.
+    fn apply(&mut self, command: &str) -> PagerAction {
+        match command.trim() {
+            "" if self.bottom() == self.lines.len() => PagerAction::Quit,
+            "" => {
+                self.top = self.bottom();
+                PagerAction::Show
+            }
+            "b" => {
+                self.top = self.top.saturating_sub(self.page_rows);
+                PagerAction::Show
+            }
+            "q" => PagerAction::Quit,
+            "n" => self.find_next(),
+            command => match command.strip_prefix('/') {
+                Some(pattern) if !pattern.is_empty() => {
+                    self.search = Some(pattern.to_lowercase());
+                    self.find_next()
+                }
+                Some(_) => self.find_next(),
+                None => PagerAction::Prompt,
+            },
+        }
+    }
+
+    fn find_next(&mut self) -> PagerAction {
+        let Some(pattern) = &self.search else {
+            return PagerAction::Prompt;
+        };
+        match self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(self.top + 1)
+            .find(|(_, line)| line.to_lowercase().contains(pattern.as_str()))
+        {
+            Some((index, _)) => {
+                self.top = index;
+                PagerAction::Show
+            }
+            None => PagerAction::NotFound(pattern.clone()),
+        }
+    }
+}
+
+/// `text` split into terminal rows, wrapping long lines at `columns` user-perceived
+/// characters.
+fn screen_lines(text: &str, columns: usize) -> Vec<String> {
+    let columns = columns.max(1);
+    let mut rows = Vec::new();
+    for line in text.lines() {
+        let graphemes = line.graphemes(true).collect::<Vec<_>>();
+        if graphemes.is_empty() {
+            rows.push(String::new());
+        }
+        rows.extend(graphemes.chunks(columns).map(|row| row.concat()));
+    }
+    rows
+}
+
+/// `(rows, columns)` of the terminal on stdout, then `$LINES`/`$COLUMNS`, then 24x80.
+fn terminal_size() -> (usize, usize) {
+    if let Some(size) = window_size() {
+        return size;
+    }
+    let from_env = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|value| *value > 1)
+    };
+    (
+        from_env("LINES").unwrap_or(DEFAULT_TERMINAL_SIZE.0),
+        from_env("COLUMNS").unwrap_or(DEFAULT_TERMINAL_SIZE.1),
+    )
+}
+
+#[cfg(unix)]
+fn window_size() -> Option<(usize, usize)> {
+    let size = rustix::termios::tcgetwinsize(io::stdout()).ok()?;
+    (size.ws_row > 1 && size.ws_col > 0)
+        .then(|| (usize::from(size.ws_row), usize::from(size.ws_col)))
+}
+
+#[cfg(not(unix))]
+fn window_size() -> Option<(usize, usize)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PagerAction, PagerView, screen_lines};
+
+    #[test]
+    fn builtin_pager_pages_searches_and_quits_at_the_end() {
+        let text = (1..=7)
+            .map(|line| format!("line {line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let lines = screen_lines(&text, 80);
+        let mut view = PagerView::new(&lines, 3);
+
+        assert_eq!(view.apply(""), PagerAction::Show);
+        assert_eq!((view.top, view.bottom()), (3, 6));
+        assert_eq!(view.apply("b"), PagerAction::Show);
+        assert_eq!(view.top, 0);
+
+        assert_eq!(view.apply("/LINE 5"), PagerAction::Show);
+        assert_eq!(view.top, 4);
+        assert_eq!(view.apply("n"), PagerAction::NotFound("line 5".to_owned()));
+        assert_eq!(view.apply("/line"), PagerAction::Show);
+        assert_eq!(view.top, 5);
+        assert_eq!(view.apply("x"), PagerAction::Prompt);
+
+        assert_eq!(view.apply(""), PagerAction::Quit);
+        assert_eq!(view.apply("q"), PagerAction::Quit);
+    }
+
+    #[test]
+    fn screen_lines_wrap_without_splitting_graphemes() {
+        assert_eq!(
+            screen_lines("abcde\n\ne\u{301}e\u{301}e\u{301}", 2),
+            ["ab", "cd", "e", "", "e\u{301}e\u{301}", "e\u{301}"]
+        );
+    }
+}
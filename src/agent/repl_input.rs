@@ -0,0 +1,296 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, IsTerminal, StdinLock, Write};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::tr;
+
+const FENCE: &str = "```";
+const CANCEL_COMMAND: &str = "/cancel";
+/// Lines arriving closer together than this came from one paste, not from typing.
+const PASTE_WINDOW: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PendingMode {
+    /// Inside a ``` block; `depth` counts nested ```lang blocks opened inside it.
+    Fence { depth: usize },
+    /// The previous line ended with `\`.
+    Continuation,
+    /// Several lines were pasted at once; an empty line sends them.
+    Paste,
+}
+
+/// Assembles REPL input lines into complete messages.
+#[derive(Debug, Default)]
+pub(super) struct ReplInputBuffer {
+    lines: Vec<String>,
+    mode: Option<PendingMode>,
+}
+
+impl ReplInputBuffer {
+    pub(super) fn is_pending(&self) -> bool {
+        self.mode.is_some()
+    }
+
+    /// Feeds lines that arrived together and returns any messages they complete.
+    pub(super) fn push_chunk(&mut self, chunk: &[String]) -> Vec<String> {
+        if self.mode.is_none()
+            && chunk.len() > 1
+            && chunk.first().is_some_and(|line| line.trim() != FENCE)
+        {
+            self.mode = Some(PendingMode::Paste);
+            self.lines.extend(chunk.iter().cloned());
+            return Vec::new();
+        }
+        chunk
+            .iter()
+            .filter_map(|line| self.push_line(line))
+            .collect()
+    }
+
+    fn push_line(&mut self, line: &str) -> Option<String> {
+        let trimmed = line.trim();
+        if self.mode.is_some() && trimmed == CANCEL_COMMAND {
+            self.lines.clear();
+            self.mode = None;
+            println!("{}", tr!("repl.multiline_discarded"));
+            return None;
+        }
+
+        match self.mode {
+            None if trimmed == FENCE => {
+                self.mode = Some(PendingMode::Fence { depth: 0 });
+                None
+            }
+            None => match line.strip_suffix('\\') {
+                Some(head) => {
+                    self.lines.push(head.to_owned());
+                    self.mode = Some(PendingMode::Continuation);
+                    None
+                }
+                None => Some(line.to_owned()),
+            },
+            Some(PendingMode::Fence { depth: 0 }) if trimmed == FENCE => self.finish(),
+            Some(PendingMode::Fence { depth }) => {
+                if trimmed == FENCE {
+                    self.mode = Some(PendingMode::Fence { depth: depth - 1 });
+                } else if trimmed.starts_with(FENCE) {
+                    self.mode = Some(PendingMode::Fence { depth: depth + 1 });
+                }
+                self.lines.push(line.to_owned());
+                None
+            }
+            Some(PendingMode::Continuation) => match line.strip_suffix('\\') {
+                Some(head) => {
+                    self.lines.push(head.to_owned());
+                    None
+                }
+                None => {
+                    self.lines.push(line.to_owned());
+                    self.finish()
+                }
+            },
+            Some(PendingMode::Paste) if trimmed.is_empty() => self.finish(),
+            Some(PendingMode::Paste) => {
+                self.lines.push(line.to_owned());
+                None
+            }
+        }
+    }
+
+    /// Sends whatever is buffered, e.g. when stdin closes mid-block.
+    pub(super) fn finish(&mut self) -> Option<String> {
+        self.mode = None;
+        let text = self.lines.join("\n");
+        self.lines.clear();
+        (!text.trim().is_empty()).then_some(text)
+    }
+}
+
+/// Reads stdin only while a prompt is waiting, so an external `$PAGER` owns the terminal
+/// between prompts. Lines that arrive together (a paste) are told apart from typed lines.
+pub(super) struct ReplInput {
+    stdin: StdinLock<'static>,
+    /// Off for piped stdin, where every line arrives at once.
+    detect_pastes: bool,
+    buffer: ReplInputBuffer,
+    ready: VecDeque<String>,
+    /// Shown in brackets before the `> ` prompt.
+    status: Option<String>,
+}
+
+impl ReplInput {
+    pub(super) fn new() -> Self {
+        let stdin = io::stdin();
+        Self {
+            detect_pastes: stdin.is_terminal(),
+            stdin: stdin.lock(),
+            buffer: ReplInputBuffer::default(),
+            ready: VecDeque::new(),
+            status: None,
+        }
+    }
+
+    pub(super) fn set_status(&mut self, status: Option<String>) {
+        self.status = status;
+    }
+
+    /// Prompts until a full message is available; `None` means stdin closed.
+    pub(super) fn next_input(&mut self) -> Result<Option<String>> {
+        loop {
+            if let Some(input) = self.ready.pop_front() {
+                return Ok(Some(input));
+            }
+
+            print!("{}", self.prompt());
+            io::stdout().flush().context("failed to flush prompt")?;
+
+            let Some(first) = self.read_line()? else {
+                return Ok(self.buffer.finish());
+            };
+            let mut chunk = vec![first];
+            while self.detect_pastes && stdin_ready_within(PASTE_WINDOW) {
+                let Some(line) = self.read_line()? else {
+                    break;
+                };
+                chunk.push(line);
+            }
+            let was_pending = self.buffer.is_pending();
+            self.ready.extend(self.buffer.push_chunk(&chunk));
+            if !was_pending && self.buffer.is_pending() && chunk.len() > 1 {
+                println!(
+                    "{}",
+                    tr!(
+                        "repl.pasted_lines",
+                        count = chunk.len(),
+                        cancel = CANCEL_COMMAND
+                    )
+                );
+            }
+        }
+    }
+
+    /// Shows `prompt` and reads one raw line, bypassing message assembly; `None` means
+    /// stdin closed.
+    pub(super) fn read_line_after(&mut self, prompt: &str) -> Result<Option<String>> {
+        print!("{prompt}");
+        io::stdout().flush().context("failed to flush prompt")?;
+        self.read_line()
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        if self
+            .stdin
+            .read_line(&mut line)
+            .context("failed to read input line")?
+            == 0
+        {
+            return Ok(None);
+        }
+        let trimmed = line.trim_end_matches('\n').trim_end_matches('\r').len();
+        line.truncate(trimmed);
+        Ok(Some(line))
+    }
+
+    fn prompt(&self) -> String {
+        match &self.status {
+            _ if self.buffer.is_pending() => "... ".to_owned(),
+            Some(status) => format!("[{status}] > "),
+            None => "> ".to_owned(),
+        }
+    }
+}
+
+/// Whether another line is readable from stdin within `timeout`.
+#[cfg(unix)]
+fn stdin_ready_within(timeout: Duration) -> bool {
+    use rustix::event::{PollFd, PollFlags, Timespec, poll};
+
+    let stdin = io::stdin();
+    let Ok(timeout) = Timespec::try_from(timeout) else {
+        return false;
+    };
+    let mut fds = [PollFd::new(&stdin, PollFlags::IN)];
+    poll(&mut fds, Some(&timeout)).is_ok_and(|ready| ready > 0)
+}
+
+/// Without `poll` on stdin, pasted lines are read one by one like typed lines.
+#[cfg(not(unix))]
+fn stdin_ready_within(_timeout: Duration) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplInputBuffer;
+
+    fn lines(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn feed_one_by_one(buffer: &mut ReplInputBuffer, raw: &[&str]) -> Vec<String> {
+        raw.iter()
+            .flat_map(|line| buffer.push_chunk(&lines(&[line])))
+            .collect()
+    }
+
+    #[test]
+    fn fences_and_backslash_continuation_build_multi_line_messages() {
+        let mut buffer = ReplInputBuffer::default();
+
+        let messages = feed_one_by_one(
+            &mut buffer,
+            &[
+                "```",
+                "Review this:",
+                "```rust",
+                "fn main() {}",
+                "```",
+                "```",
+                "first \\",
+                "second",
+                "single",
+            ],
+        );
+
+        assert_eq!(
+            messages,
+            [
+                "Review this:\n```rust\nfn main() {}\n```",
+                "first \nsecond",
+                "single"
+            ]
+        );
+        assert!(!buffer.is_pending());
+    }
+
+    #[test]
+    fn pasted_lines_wait_for_an_empty_line() {
+        let mut buffer = ReplInputBuffer::default();
+
+        assert!(
+            buffer
+                .push_chunk(&lines(&["Paragraph one.", "", "Paragraph two"]))
+                .is_empty()
+        );
+        assert!(buffer.is_pending());
+        assert!(buffer.push_chunk(&lines(&["continues."])).is_empty());
+
+        assert_eq!(
+            buffer.push_chunk(&lines(&[""])),
+            ["Paragraph one.\n\nParagraph two\ncontinues."]
+        );
+        assert!(!buffer.is_pending());
+    }
+
+    #[test]
+    fn cancel_discards_pending_input() {
+        let mut buffer = ReplInputBuffer::default();
+
+        let messages = feed_one_by_one(&mut buffer, &["```", "draft", "/cancel", "hello"]);
+
+        assert_eq!(messages, ["hello"]);
+    }
+}
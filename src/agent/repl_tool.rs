@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+use tokio::time::timeout;
+
+use crate::tools::{ToolDispatchError, ToolRuntimeConfig, dispatch_tool_call};
+
+pub(super) const REPL_TOOL_COMMAND: &str = "/tool";
+
+/// Parses the text after `/tool` into a tool name and JSON arguments; omitted arguments
+/// mean `{}`.
+fn parse_repl_tool_command(rest: &str) -> Result<(String, Value), String> {
+    let rest = rest.trim();
+    let (name, raw_args) = rest
+        .split_once(char::is_whitespace)
+        .map_or((rest, ""), |(name, args)| (name, args.trim()));
+    if name.is_empty() {
+        return Err(format!("usage: {REPL_TOOL_COMMAND} <name> <json-args>"));
+    }
+    if raw_args.is_empty() {
+        return Ok((name.to_owned(), json!({})));
+    }
+    let args = serde_json::from_str::<Value>(raw_args)
+        .map_err(|error| format!("tool arguments must be JSON: {error}"))?;
+    Ok((name.to_owned(), args))
+}
+
+/// Runs `/tool` directly against the session's tool runtime, bypassing the model, and
+/// returns the pretty-printed payload or structured error.
+pub(super) async fn run_repl_tool_command(rest: &str, runtime: &ToolRuntimeConfig) -> String {
+    let (tool_name, args) = match parse_repl_tool_command(rest) {
+        Ok(parsed) => parsed,
+        Err(message) => return message,
+    };
+    let tool_timeout_ms = runtime.timeout_ms_for(&tool_name);
+
+    let started_at = Instant::now();
+    let result = timeout(
+        Duration::from_millis(tool_timeout_ms),
+        dispatch_tool_call(&tool_name, args, runtime),
+    )
+    .await;
+    let latency_ms = started_at.elapsed().as_millis();
+
+    let rendered = match result {
+        Ok(Ok(output)) => json!({
+            "tool": output.tool_name,
+            "latency_ms": latency_ms,
+            "payload": output.payload,
+        }),
+        Ok(Err(error)) => json!({
+            "tool": tool_name,
+            "latency_ms": latency_ms,
+            "error": tool_error_json(&error),
+        }),
+        Err(_) => json!({
+            "tool": tool_name,
+            "latency_ms": latency_ms,
+            "error": {
+                "kind": "timeout",
+                "message": format!("tool timed out after {tool_timeout_ms}ms"),
+            },
+        }),
+    };
+    serde_json::to_string_pretty(&rendered).unwrap_or_else(|_| rendered.to_string())
+}
+
+fn tool_error_json(error: &ToolDispatchError) -> Value {
+    let (kind, reason) = match error {
+        ToolDispatchError::UnknownTool { .. } => ("unknown_tool", None),
+        ToolDispatchError::InvalidArgs { reason, .. } => ("invalid_args", Some(reason)),
+        ToolDispatchError::PolicyViolation { reason, .. } => ("policy_violation", Some(reason)),
+        ToolDispatchError::ExecutionFailed { reason, .. } => ("execution_failed", Some(reason)),
+        ToolDispatchError::InvalidOutput { reason, .. } => ("invalid_output", Some(reason)),
+    };
+    json!({
+        "kind": kind,
+        "reason": reason,
+        "message": error.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{parse_repl_tool_command, run_repl_tool_command};
+    use crate::agent::ChatSession;
+    use crate::agent::tests::test_settings;
+
+    #[test]
+    fn parse_repl_tool_command_reads_name_and_json_args() {
+        assert_eq!(
+            parse_repl_tool_command(r#" search_notes {"query": "rust", "limit": 2} "#),
+            Ok((
+                "search_notes".to_owned(),
+                json!({ "query": "rust", "limit": 2 })
+            ))
+        );
+        assert_eq!(
+            parse_repl_tool_command("search_notes"),
+            Ok(("search_notes".to_owned(), json!({})))
+        );
+        assert!(parse_repl_tool_command("").is_err());
+        assert!(
+            parse_repl_tool_command("search_notes {query}")
+                .expect_err("invalid JSON should fail")
+                .starts_with("tool arguments must be JSON")
+        );
+    }
+
+    #[tokio::test]
+    async fn run_repl_tool_command_reports_policy_errors_as_json() {
+        let settings = test_settings();
+        let session = ChatSession::new(&settings);
+
+        let rendered = run_repl_tool_command(
+            r#"fetch_url {"url": "https://blocked.invalid/"}"#,
+            &session.tool_runtime,
+        )
+        .await;
+
+        let value: Value = serde_json::from_str(&rendered).expect("output should be JSON");
+        assert_eq!(value["tool"], "fetch_url");
+        assert_eq!(value["error"]["kind"], "policy_violation");
+    }
+}
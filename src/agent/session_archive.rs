@@ -0,0 +1,176 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::session_summary::SessionSummary;
+use crate::export::{ExportFormat, Transcript};
+
+/// A finished session kept in `SESSION_ARCHIVE_DIR`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchivedSession {
+    pub session_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Writes `transcript` as JSON to `<dir>/<session_id>.json`, titled by `session_summary`
+/// when one was generated. `session_id` must be a plain file stem; a later session
+/// archived under the same id replaces the file.
+pub fn archive_transcript(
+    dir: &Path,
+    session_id: &str,
+    mut transcript: Transcript,
+    session_summary: Option<&SessionSummary>,
+) -> Result<PathBuf> {
+    if let Some(session_summary) = session_summary {
+        transcript.title = session_summary.title.clone();
+        transcript.summary =
+            (!session_summary.summary.is_empty()).then(|| session_summary.summary.clone());
+    }
+    let path = archive_path(dir, session_id);
+    transcript.write_to(&path, ExportFormat::Json)?;
+    Ok(path)
+}
+
+/// Reads the transcript archived for `session_id`; `None` when there is none.
+pub fn load_archived_transcript(dir: &Path, session_id: &str) -> Result<Option<Transcript>> {
+    let path = archive_path(dir, session_id);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed to read `{}`", path.display()));
+        }
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .with_context(|| format!("`{}` is not an archived transcript", path.display()))
+}
+
+/// Archived sessions, most recently archived first. Files that are not archived
+/// transcripts are skipped; a missing directory lists nothing.
+pub fn list_archived_sessions(dir: &Path) -> Result<Vec<ArchivedSession>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed to read `{}`", dir.display()));
+        }
+    };
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(session_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let Ok(Some(transcript)) = load_archived_transcript(dir, session_id) else {
+            continue;
+        };
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        sessions.push((
+            modified,
+            ArchivedSession {
+                session_id: session_id.to_owned(),
+                title: transcript.title,
+                summary: transcript.summary,
+            },
+        ));
+    }
+    sessions.sort_by(|(left_time, left), (right_time, right)| {
+        right_time
+            .cmp(left_time)
+            .then_with(|| left.session_id.cmp(&right.session_id))
+    });
+    Ok(sessions.into_iter().map(|(_, session)| session).collect())
+}
+
+fn archive_path(dir: &Path, session_id: &str) -> PathBuf {
+    dir.join(format!("{session_id}.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{
+        ArchivedSession, archive_transcript, list_archived_sessions, load_archived_transcript,
+    };
+    use crate::agent::SessionSummary;
+    use crate::export::Transcript;
+    use crate::model::client::ModelMessage;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn archived_sessions_list_their_generated_titles() {
+        let dir = temp_path("session-archive");
+        let conversation = [
+            ModelMessage::user("find rust notes"),
+            ModelMessage::assistant_text("None found."),
+        ];
+        let summary = SessionSummary {
+            title: "Rust notes search".to_owned(),
+            summary: "Looked for rust notes and found none.".to_owned(),
+        };
+        archive_transcript(
+            &dir,
+            "alpha",
+            Transcript::from_conversation("Session alpha", &conversation),
+            Some(&summary),
+        )
+        .expect("archive should be written");
+        archive_transcript(
+            &dir,
+            "beta",
+            Transcript::from_conversation("Session beta", &conversation),
+            None,
+        )
+        .expect("archive should be written");
+        fs::write(dir.join("stray.json"), "not a transcript").expect("stray file");
+
+        let archived = load_archived_transcript(&dir, "alpha")
+            .expect("archive should load")
+            .expect("archive should exist");
+        assert_eq!(archived.title, "Rust notes search");
+        assert_eq!(archived.entries.len(), 2);
+        assert!(
+            load_archived_transcript(&dir, "gamma")
+                .expect("missing archive is not an error")
+                .is_none()
+        );
+
+        let mut listed = list_archived_sessions(&dir).expect("archive should list");
+        listed.sort_by(|left, right| left.session_id.cmp(&right.session_id));
+        assert_eq!(
+            listed,
+            [
+                ArchivedSession {
+                    session_id: "alpha".to_owned(),
+                    title: "Rust notes search".to_owned(),
+                    summary: Some("Looked for rust notes and found none.".to_owned()),
+                },
+                ArchivedSession {
+                    session_id: "beta".to_owned(),
+                    title: "Session beta".to_owned(),
+                    summary: None,
+                },
+            ]
+        );
+        assert!(
+            list_archived_sessions(&dir.join("missing"))
+                .expect("missing directory lists nothing")
+                .is_empty()
+        );
+        remove_dir_if_exists(&dir);
+    }
+}
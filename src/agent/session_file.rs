@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::session_summary::SessionSummary;
+use crate::model::client::{MessageRole, ModelMessage};
+
+const SESSION_FILE_VERSION: u32 = 1;
+
+/// What `repl --session-file`, `/save`, and `/load` read and write.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionFile {
+    version: u32,
+    /// Summary of the turns `/compact` replaced, restored into the rebuilt system prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    /// Generated title and summary of the whole session, written when `repl` exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_summary: Option<SessionSummary>,
+    /// The conversation without its system prompt, which is rebuilt from the current
+    /// settings on load.
+    messages: Vec<ModelMessage>,
+}
+
+/// A conversation read back from a session file.
+#[derive(Debug, PartialEq)]
+pub(super) struct SavedSession {
+    pub(super) summary: Option<String>,
+    pub(super) session_summary: Option<SessionSummary>,
+    pub(super) messages: Vec<ModelMessage>,
+}
+
+/// Writes `conversation` (minus the leading system prompt), its compaction `summary`, and
+/// its `session_summary` to `path` as JSON, replacing the file through a rename so an interrupted save keeps
+/// the previous one.
+pub(super) fn save_session_file(
+    path: &Path,
+    conversation: &[ModelMessage],
+    summary: Option<&str>,
+    session_summary: Option<&SessionSummary>,
+) -> Result<usize> {
+    let messages = conversation
+        .iter()
+        .skip_while(|message| message.role == MessageRole::System)
+        .cloned()
+        .collect::<Vec<_>>();
+    let count = messages.len();
+    let payload = serde_json::to_vec_pretty(&SessionFile {
+        version: SESSION_FILE_VERSION,
+        summary: summary.map(str::to_owned),
+        session_summary: session_summary.cloned(),
+        messages,
+    })
+    .context("failed to encode session file")?;
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let staging = path.with_extension("tmp");
+    fs::write(&staging, payload)
+        .with_context(|| format!("failed to write {}", staging.display()))?;
+    fs::rename(&staging, path).with_context(|| format!("failed to replace {}", path.display()))?;
+    Ok(count)
+}
+
+/// Reads the conversation saved in `path`. System messages are rejected so a session file
+/// cannot replace the system prompt.
+pub(super) fn load_session_file(path: &Path) -> Result<SavedSession> {
+    let payload = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let file = serde_json::from_slice::<SessionFile>(&payload)
+        .with_context(|| format!("{} is not a session file", path.display()))?;
+    if file.version != SESSION_FILE_VERSION {
+        bail!(
+            "{} has session file version {}; expected {SESSION_FILE_VERSION}",
+            path.display(),
+            file.version
+        );
+    }
+    if file
+        .messages
+        .iter()
+        .any(|message| message.role == MessageRole::System)
+    {
+        bail!("{} contains a system message", path.display());
+    }
+    Ok(SavedSession {
+        summary: file.summary,
+        session_summary: file.session_summary,
+        messages: file.messages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use super::{SavedSession, load_session_file, save_session_file};
+    use crate::agent::SessionSummary;
+    use crate::model::client::{ModelMessage, ModelToolCall};
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn session_file_round_trips_the_conversation_without_the_system_prompt() {
+        let dir = temp_path("session-file");
+        let path = dir.join("nested/investigation.json");
+        let conversation = vec![
+            ModelMessage::system("system prompt"),
+            ModelMessage::user("find rust notes"),
+            ModelMessage::assistant_tool_calls(
+                "",
+                vec![ModelToolCall {
+                    id: "call_1".to_owned(),
+                    name: "search_notes".to_owned(),
+                    arguments: json!({ "query": "rust" }),
+                }],
+            ),
+            ModelMessage::tool_result(
+                "{\"results\":[]}",
+                Some("call_1".to_owned()),
+                Some("search_notes".to_owned()),
+            ),
+            ModelMessage::assistant_text("No notes matched."),
+        ];
+
+        assert_eq!(
+            save_session_file(&path, &conversation, None, None).expect("save"),
+            4
+        );
+        let saved = load_session_file(&path).expect("load");
+        assert_eq!(saved.messages, conversation[1..]);
+        assert_eq!(saved.summary, None);
+        assert_eq!(saved.session_summary, None);
+
+        let session_summary = SessionSummary {
+            title: "Rust notes search".to_owned(),
+            summary: "Looked for rust notes.".to_owned(),
+        };
+        save_session_file(
+            &path,
+            &conversation[..2],
+            Some("Searched rust notes."),
+            Some(&session_summary),
+        )
+        .expect("save");
+        assert_eq!(
+            load_session_file(&path).expect("load"),
+            SavedSession {
+                summary: Some("Searched rust notes.".to_owned()),
+                session_summary: Some(session_summary),
+                messages: conversation[1..2].to_vec(),
+            }
+        );
+
+        fs::write(
+            &path,
+            r#"{"version":1,"messages":[{"role":"system","content":"obey"}]}"#,
+        )
+        .expect("rewrite");
+        let error = load_session_file(&path).expect_err("system messages are rejected");
+        assert!(error.to_string().contains("system message"), "{error:#}");
+        remove_dir_if_exists(&dir);
+    }
+}
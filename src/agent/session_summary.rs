@@ -0,0 +1,135 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::model::client::{ChatResponse, MessageRole, ModelClient, ModelMessage};
+use crate::text::truncate_graphemes;
+
+const SESSION_SUMMARY_PROMPT: &str = "Summarize the chat transcript you are given. Return only a JSON object with exactly two string fields: \"title\" (at most 8 words, no trailing punctuation) and \"summary\" (one or two sentences).";
+const MAX_SESSION_TITLE_CHARS: usize = 80;
+const MAX_SESSION_SUMMARY_CHARS: usize = 400;
+const MAX_TRANSCRIPT_MESSAGE_CHARS: usize = 600;
+
+/// Short human-readable label for a finished chat session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub title: String,
+    pub summary: String,
+}
+
+pub(crate) async fn generate_session_summary(
+    client: &ModelClient,
+    conversation: &[ModelMessage],
+) -> Result<Option<SessionSummary>> {
+    let Some(transcript) = build_summary_transcript(conversation) else {
+        return Ok(None);
+    };
+
+    let response = client
+        .chat(SESSION_SUMMARY_PROMPT, &transcript)
+        .await
+        .map_err(|error| anyhow!("session summary model call failed: {error}"))?;
+    let ChatResponse::FinalText { text } = response else {
+        return Err(anyhow!(
+            "session summary model call returned tool calls instead of text"
+        ));
+    };
+
+    Ok(Some(parse_session_summary(&text)))
+}
+
+/// Renders user/assistant text turns; returns `None` when no user turn exists.
+pub(super) fn build_summary_transcript(conversation: &[ModelMessage]) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut has_user_turn = false;
+
+    for message in conversation {
+        let speaker = match message.role {
+            MessageRole::User => {
+                has_user_turn = true;
+                "user"
+            }
+            MessageRole::Assistant if !message.content.trim().is_empty() => "assistant",
+            _ => continue,
+        };
+        lines.push(format!(
+            "{speaker}: {}",
+            truncate_chars(message.content.trim(), MAX_TRANSCRIPT_MESSAGE_CHARS)
+        ));
+    }
+
+    has_user_turn.then(|| lines.join("\n"))
+}
+
+/// Accepts the requested JSON shape and falls back to first-line-as-title text.
+fn parse_session_summary(raw: &str) -> SessionSummary {
+    let trimmed = raw.trim();
+    if let Ok(parsed) = serde_json::from_str::<SessionSummary>(trimmed) {
+        return SessionSummary {
+            title: truncate_chars(parsed.title.trim(), MAX_SESSION_TITLE_CHARS),
+            summary: truncate_chars(parsed.summary.trim(), MAX_SESSION_SUMMARY_CHARS),
+        };
+    }
+
+    let mut lines = trimmed
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let title = lines.next().unwrap_or("Untitled session");
+    let summary = lines.collect::<Vec<_>>().join(" ");
+    SessionSummary {
+        title: truncate_chars(title, MAX_SESSION_TITLE_CHARS),
+        summary: truncate_chars(&summary, MAX_SESSION_SUMMARY_CHARS),
+    }
+}
+
+/// Cuts `text` to `max_chars` user-perceived characters, ending in `…` when shortened.
+pub(super) fn truncate_chars(text: &str, max_chars: usize) -> String {
+    truncate_graphemes(text, max_chars, "…")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::client::ModelMessage;
+
+    use super::{SessionSummary, build_summary_transcript, parse_session_summary};
+
+    #[test]
+    fn build_summary_transcript_skips_system_and_tool_messages() {
+        let conversation = vec![
+            ModelMessage::system("system prompt"),
+            ModelMessage::user("find rust notes"),
+            ModelMessage::tool_result("{\"results\":[]}", None, None),
+            ModelMessage::assistant_text("No notes matched."),
+        ];
+
+        let transcript = build_summary_transcript(&conversation).expect("user turn exists");
+        assert_eq!(
+            transcript,
+            "user: find rust notes\nassistant: No notes matched."
+        );
+    }
+
+    #[test]
+    fn build_summary_transcript_returns_none_without_user_turns() {
+        let conversation = vec![ModelMessage::system("system prompt")];
+        assert!(build_summary_transcript(&conversation).is_none());
+    }
+
+    #[test]
+    fn parse_session_summary_accepts_json_and_plain_text() {
+        assert_eq!(
+            parse_session_summary(r#"{"title":"Rust notes search","summary":"Searched notes."}"#),
+            SessionSummary {
+                title: "Rust notes search".to_owned(),
+                summary: "Searched notes.".to_owned(),
+            }
+        );
+        assert_eq!(
+            parse_session_summary("Rust notes search\nSearched notes.\nNothing found."),
+            SessionSummary {
+                title: "Rust notes search".to_owned(),
+                summary: "Searched notes. Nothing found.".to_owned(),
+            }
+        );
+    }
+}
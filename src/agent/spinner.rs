@@ -0,0 +1,168 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+
+use super::turn_events::{TurnEvent, TurnEventSink};
+use crate::tools::ToolProgress;
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+#[derive(Debug)]
+struct RunningTool {
+    name: String,
+    started_at: Instant,
+    progress: Option<ToolProgress>,
+}
+
+/// One-line stderr status for the tool call a CLI turn is waiting on. Only drawn when
+/// stderr is a terminal, so piped output and logs stay clean.
+pub(super) struct ToolSpinner {
+    running: Arc<Mutex<Option<RunningTool>>>,
+    redraw: JoinHandle<()>,
+}
+
+impl ToolSpinner {
+    pub fn start_if_terminal() -> Option<Self> {
+        io::stderr().is_terminal().then(Self::start)
+    }
+
+    fn start() -> Self {
+        let running = Arc::new(Mutex::new(None::<RunningTool>));
+        let drawn = Arc::clone(&running);
+        let redraw = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(REDRAW_INTERVAL);
+            for frame in 0_usize.. {
+                ticker.tick().await;
+                let line = drawn
+                    .lock()
+                    .expect("spinner lock poisoned")
+                    .as_ref()
+                    .map(|tool| {
+                        spinner_line(
+                            frame,
+                            &tool.name,
+                            tool.started_at.elapsed(),
+                            tool.progress.as_ref(),
+                        )
+                    });
+                if let Some(line) = line {
+                    let mut stderr = io::stderr().lock();
+                    let _ = write!(stderr, "{CLEAR_LINE}{line}");
+                    let _ = stderr.flush();
+                }
+            }
+        });
+        Self { running, redraw }
+    }
+
+    /// A `TurnEventSink` for `ChatSession::with_event_sink`.
+    pub fn sink(&self) -> TurnEventSink {
+        let running = Arc::clone(&self.running);
+        Arc::new(move |event| {
+            let mut running = running.lock().expect("spinner lock poisoned");
+            match event {
+                TurnEvent::ToolStarted { tool_name, .. } => {
+                    *running = Some(RunningTool {
+                        name: tool_name,
+                        started_at: Instant::now(),
+                        progress: None,
+                    });
+                }
+                TurnEvent::ToolProgress { progress, .. } => {
+                    if let Some(tool) = running.as_mut() {
+                        tool.progress = Some(progress);
+                    }
+                }
+                TurnEvent::ToolFinished { .. } => {
+                    if running.take().is_some() {
+                        clear_stderr_line();
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn finish(self) {
+        self.redraw.abort();
+        if self
+            .running
+            .lock()
+            .expect("spinner lock poisoned")
+            .take()
+            .is_some()
+        {
+            clear_stderr_line();
+        }
+    }
+}
+
+fn clear_stderr_line() {
+    let mut stderr = io::stderr().lock();
+    let _ = write!(stderr, "{CLEAR_LINE}");
+    let _ = stderr.flush();
+}
+
+/// Such as `⠹ fetch_url 2.4s · 340.0 KB / 1.0 MB (33%)`, or `⠹ search_notes 0.3s`
+/// before a tool reports progress.
+fn spinner_line(
+    frame: usize,
+    tool_name: &str,
+    elapsed: Duration,
+    progress: Option<&ToolProgress>,
+) -> String {
+    let frame = FRAMES[frame % FRAMES.len()];
+    let Some(progress) = progress else {
+        return format!("{frame} {tool_name} {:.1}s", elapsed.as_secs_f64());
+    };
+    let status = ToolProgress {
+        elapsed,
+        ..progress.clone()
+    }
+    .label();
+    match progress.percent() {
+        Some(percent) => format!("{frame} {tool_name} {status} ({percent}%)"),
+        None => format!("{frame} {tool_name} {status}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::spinner_line;
+    use crate::tools::ToolProgress;
+
+    #[test]
+    fn spinner_line_shows_live_elapsed_and_download_progress() {
+        assert_eq!(
+            spinner_line(0, "search_notes", Duration::from_millis(340), None),
+            "⠋ search_notes 0.3s"
+        );
+        let progress = ToolProgress {
+            elapsed: Duration::from_millis(500),
+            bytes_received: 512 * 1024,
+            bytes_total: Some(2 * 1024 * 1024),
+        };
+        assert_eq!(
+            spinner_line(
+                11,
+                "fetch_url",
+                Duration::from_millis(2400),
+                Some(&progress)
+            ),
+            "⠙ fetch_url 2.4s · 512.0 KB / 2.0 MB (25%)"
+        );
+        let unknown_total = ToolProgress {
+            bytes_total: None,
+            ..progress
+        };
+        assert_eq!(
+            spinner_line(2, "fetch_url", Duration::from_secs(1), Some(&unknown_total)),
+            "⠹ fetch_url 1.0s · 512.0 KB"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Write as _;
+use std::time::Duration;
+
+pub use crate::api::{TimelineEvent, TimelineEventKind, TimelineSpan, TimelineSpanKind};
+use crate::export::escape_html;
+
+impl TimelineSpanKind {
+    fn css_class(self) -> &'static str {
+        match self {
+            Self::ModelCall => "model",
+            Self::ToolCall => "tool",
+        }
+    }
+}
+
+/// Start and end events for every span, ordered by time. Events at the same instant
+/// keep recording order, so a call's end precedes the next call's start.
+pub fn timeline_events(spans: &[TimelineSpan]) -> Vec<TimelineEvent> {
+    let mut tool_calls = 0..;
+    let mut events = spans
+        .iter()
+        .flat_map(|span| {
+            let (start, end, tool_call_index) = match span.kind {
+                TimelineSpanKind::ModelCall => (
+                    TimelineEventKind::ModelCallStart,
+                    TimelineEventKind::ModelCallEnd,
+                    None,
+                ),
+                TimelineSpanKind::ToolCall => (
+                    TimelineEventKind::ToolStart,
+                    TimelineEventKind::ToolEnd,
+                    tool_calls.next(),
+                ),
+            };
+            [(start, span.start), (end, span.end)].map(|(event, at)| TimelineEvent {
+                event,
+                at_ms: at.as_micros() as f64 / 1_000.0,
+                step: span.step,
+                label: span.label.clone(),
+                tool_call_index,
+            })
+        })
+        .collect::<Vec<_>>();
+    events.sort_by(|left, right| left.at_ms.total_cmp(&right.at_ms));
+    events
+}
+
+/// End of the latest span; the scale used by waterfall views.
+pub fn timeline_extent(spans: &[TimelineSpan]) -> Duration {
+    spans.iter().map(|span| span.end).max().unwrap_or_default()
+}
+
+/// Renders a self-contained HTML waterfall with one row per span.
+pub fn render_timeline_html(title: &str, spans: &[TimelineSpan]) -> String {
+    let extent_ms = timeline_extent(spans).as_secs_f64() * 1_000.0;
+    let scale = if extent_ms > 0.0 { extent_ms } else { 1.0 };
+
+    let mut rows = String::new();
+    for span in spans {
+        let start_ms = span.start.as_secs_f64() * 1_000.0;
+        let duration_ms = span.duration().as_secs_f64() * 1_000.0;
+        let left = start_ms / scale * 100.0;
+        let width = (duration_ms / scale * 100.0).max(0.3);
+        let _ = writeln!(
+            rows,
+            r#"<div class="row"><div class="label">{label} <span class="step">step {step}</span></div><div class="track"><div class="bar {class}" style="left:{left:.3}%;width:{width:.3}%" title="{label}: {start_ms:.1}ms → {end_ms:.1}ms"></div><span class="duration" style="left:calc({left:.3}% + {width:.3}% + 4px)">{duration_ms:.1}ms</span></div></div>"#,
+            label = escape_html(&span.label),
+            step = span.step,
+            class = span.kind.css_class(),
+            end_ms = start_ms + duration_ms,
+        );
+    }
+    if spans.is_empty() {
+        rows.push_str("<p class=\"empty\">No model or tool calls were recorded.</p>\n");
+    }
+
+    format!(
+        r#"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font: 13px system-ui, sans-serif; margin: 24px; color: #1e2f45; }}
+h1 {{ font-size: 16px; margin: 0 0 4px; }}
+.summary {{ color: #5b6f88; margin-bottom: 16px; }}
+.row {{ display: flex; align-items: center; height: 24px; }}
+.label {{ width: 220px; flex: none; overflow: hidden; white-space: nowrap; text-overflow: ellipsis; }}
+.step {{ color: #8799ae; font-size: 11px; }}
+.track {{ position: relative; flex: 1; height: 16px; background: #f0f5fa; border-radius: 3px; }}
+.bar {{ position: absolute; top: 0; height: 16px; border-radius: 3px; }}
+.bar.model {{ background: #447ebc; }}
+.bar.tool {{ background: #e0a046; }}
+.duration {{ position: absolute; top: 1px; font-size: 11px; color: #5b6f88; white-space: nowrap; }}
+.empty {{ color: #8799ae; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<div class="summary">{count} calls · {extent_ms:.1}ms total · <span style="color:#447ebc">■</span> model <span style="color:#e0a046">■</span> tool</div>
+{rows}</body>
+</html>
+"#,
+        title = escape_html(title),
+        count = spans.len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        TimelineEventKind, TimelineSpan, TimelineSpanKind, render_timeline_html, timeline_events,
+        timeline_extent,
+    };
+
+    fn span(kind: TimelineSpanKind, label: &str, start_ms: u64, end_ms: u64) -> TimelineSpan {
+        TimelineSpan {
+            kind,
+            label: label.to_owned(),
+            step: 1,
+            start: Duration::from_millis(start_ms),
+            end: Duration::from_millis(end_ms),
+        }
+    }
+
+    #[test]
+    fn render_timeline_html_positions_bars_relative_to_turn_extent() {
+        let spans = vec![
+            span(TimelineSpanKind::ModelCall, "model", 0, 100),
+            span(TimelineSpanKind::ToolCall, "fetch_url", 100, 200),
+        ];
+
+        let html = render_timeline_html("Turn <1>", &spans);
+
+        assert_eq!(timeline_extent(&spans), Duration::from_millis(200));
+        assert!(html.contains("<title>Turn &lt;1&gt;</title>"));
+        assert!(html.contains(r#"class="bar model" style="left:0.000%;width:50.000%""#));
+        assert!(html.contains(r#"class="bar tool" style="left:50.000%;width:50.000%""#));
+        assert!(html.contains("2 calls · 200.0ms total"));
+    }
+
+    #[test]
+    fn timeline_events_flatten_spans_in_time_order() {
+        let spans = vec![
+            span(TimelineSpanKind::ModelCall, "model · qwen", 0, 100),
+            span(TimelineSpanKind::ToolCall, "fetch_url", 100, 250),
+            span(TimelineSpanKind::ToolCall, "search_notes", 101, 180),
+        ];
+
+        let events = timeline_events(&spans);
+
+        let order = events
+            .iter()
+            .map(|event| (event.event, event.at_ms, event.tool_call_index))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            [
+                (TimelineEventKind::ModelCallStart, 0.0, None),
+                (TimelineEventKind::ModelCallEnd, 100.0, None),
+                (TimelineEventKind::ToolStart, 100.0, Some(0)),
+                (TimelineEventKind::ToolStart, 101.0, Some(1)),
+                (TimelineEventKind::ToolEnd, 180.0, Some(1)),
+                (TimelineEventKind::ToolEnd, 250.0, Some(0)),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&events[2]).expect("event serializes"),
+            serde_json::json!({
+                "event": "tool_start",
+                "at_ms": 100.0,
+                "step": 1,
+                "label": "fetch_url",
+                "tool_call_index": 0,
+            })
+        );
+    }
+
+    #[test]
+    fn render_timeline_html_handles_empty_timeline() {
+        let html = render_timeline_html("Turn", &[]);
+        assert!(html.contains("No model or tool calls were recorded."));
+    }
+}
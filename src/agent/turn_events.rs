@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::tools::{ToolProgress, ToolProgressSink};
+
+/// Live tool activity while a turn runs, for surfaces that show progress before the
+/// outcome exists. `tool_call_index` counts tool calls from 0 within the turn and
+/// matches the index in `ChatTurnOutcome.tool_calls`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurnEvent {
+    ToolStarted {
+        tool_call_index: usize,
+        step: u32,
+        tool_name: String,
+        arguments: Value,
+    },
+    ToolProgress {
+        tool_call_index: usize,
+        tool_name: String,
+        progress: ToolProgress,
+    },
+    ToolFinished {
+        tool_call_index: usize,
+        tool_name: String,
+        latency: Duration,
+        succeeded: bool,
+    },
+}
+
+/// Receives `TurnEvent`s; called from the task running the turn, so it must not block.
+pub type TurnEventSink = Arc<dyn Fn(TurnEvent) + Send + Sync>;
+
+/// Tool calls of one model step reporting to a sink.
+#[derive(Clone, Copy)]
+pub(super) struct ToolEventScope<'a> {
+    pub sink: &'a TurnEventSink,
+    /// Turn-wide index of the step's first tool call.
+    pub first_tool_call_index: usize,
+}
+
+impl ToolEventScope<'_> {
+    /// A `ToolProgressSink` forwarding `fetch_url`-style progress as `TurnEvent::ToolProgress`.
+    pub fn progress_sink(&self, tool_call_index: usize, tool_name: &str) -> ToolProgressSink {
+        let sink = Arc::clone(self.sink);
+        let tool_name = tool_name.to_owned();
+        ToolProgressSink::new(move |progress| {
+            sink(TurnEvent::ToolProgress {
+                tool_call_index,
+                tool_name: tool_name.clone(),
+                progress,
+            });
+        })
+    }
+}
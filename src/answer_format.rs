@@ -1,9 +1,84 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::Deserialize;
 use serde_json::Value;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::json_schema::schema_violations;
+
+/// Minimum stopword hits before language detection is considered conclusive.
+const MIN_LANGUAGE_SIGNAL_WORDS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StructuredAnswerFormat {
     JsonObject,
     MarkdownBullets,
+    /// A header row followed by data rows, all with the same number of columns.
+    CsvTable(TableDelimiter),
+    /// JSON matching a schema the caller supplies; never detected from a prompt, so it is
+    /// not in `ALL`.
+    JsonSchema(Value),
+}
+
+impl StructuredAnswerFormat {
+    pub const ALL: [Self; 4] = [
+        Self::JsonObject,
+        Self::MarkdownBullets,
+        Self::CsvTable(TableDelimiter::Comma),
+        Self::CsvTable(TableDelimiter::Tab),
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::JsonObject => "json_object",
+            Self::MarkdownBullets => "markdown_bullets",
+            Self::CsvTable(TableDelimiter::Comma) => "csv_table",
+            Self::CsvTable(TableDelimiter::Tab) => "tsv_table",
+            Self::JsonSchema(_) => "json_schema",
+        }
+    }
+
+    /// Answers in this format are JSON, so fenced JSON may be unwrapped locally.
+    pub fn is_json(&self) -> bool {
+        matches!(self, Self::JsonObject | Self::JsonSchema(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableDelimiter {
+    #[default]
+    Comma,
+    Tab,
+}
+
+impl TableDelimiter {
+    fn as_char(self) -> char {
+        match self {
+            Self::Comma => ',',
+            Self::Tab => '\t',
+        }
+    }
+}
+
+/// Shape a CSV/TSV answer must have beyond a consistent column count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvTableRules<'a> {
+    pub delimiter: TableDelimiter,
+    pub columns: Option<usize>,
+    /// Expected header cells, compared case-insensitively; empty accepts any header.
+    pub header: &'a [String],
+}
+
+impl CsvTableRules<'_> {
+    pub fn any(delimiter: TableDelimiter) -> Self {
+        Self {
+            delimiter,
+            columns: None,
+            header: &[],
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,19 +87,375 @@ pub enum StructuredAnswerFormatError {
     JsonNotObject,
     JsonParseError(String),
     NonBulletLines(Vec<String>),
+    /// 1-based line of a quoted field that never closes.
+    CsvUnterminatedQuote(usize),
+    /// The first row has empty or purely numeric cells, so it is data rather than a header.
+    CsvMissingHeader(Vec<String>),
+    CsvHeaderMismatch {
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    CsvNoDataRows,
+    CsvColumnCount {
+        expected: usize,
+        found: usize,
+    },
+    /// `(line, columns)` of rows whose width differs from the header's `expected`.
+    CsvRaggedRows {
+        expected: usize,
+        rows: Vec<(usize, usize)>,
+    },
+    /// One `<JSON pointer>: <problem>` line per mismatch with the requested schema.
+    JsonSchemaViolations(Vec<String>),
+}
+
+impl fmt::Display for StructuredAnswerFormatError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyAnswer => formatter.write_str("answer is empty"),
+            Self::JsonNotObject => formatter.write_str("answer is JSON but not an object"),
+            Self::JsonParseError(error) => write!(formatter, "answer is not valid JSON: {error}"),
+            Self::NonBulletLines(lines) => {
+                write!(
+                    formatter,
+                    "non-bullet lines detected: {}",
+                    lines.join(" | ")
+                )
+            }
+            Self::CsvUnterminatedQuote(line) => {
+                write!(formatter, "unterminated quoted field on line {line}")
+            }
+            Self::CsvMissingHeader(cells) => write!(
+                formatter,
+                "first row does not look like a header: {}",
+                cells.join(" | ")
+            ),
+            Self::CsvHeaderMismatch { expected, found } => write!(
+                formatter,
+                "header is `{}`, expected `{}`",
+                found.join(" | "),
+                expected.join(" | ")
+            ),
+            Self::CsvNoDataRows => formatter.write_str("table has a header but no data rows"),
+            Self::CsvColumnCount { expected, found } => {
+                write!(formatter, "table has {found} columns, expected {expected}")
+            }
+            Self::CsvRaggedRows { expected, rows } => write!(
+                formatter,
+                "rows with a different column count than the header ({expected}): {}",
+                rows.iter()
+                    .map(|(line, columns)| format!("line {line} has {columns}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::JsonSchemaViolations(violations) => write!(
+                formatter,
+                "answer does not match the JSON schema: {}",
+                violations.join("; ")
+            ),
+        }
+    }
+}
+
+/// Deployment-wide answer language configured via `AGENT_RESPONSE_LANGUAGE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseLanguage {
+    English,
+    Norwegian,
+    Swedish,
+    Danish,
+    German,
+    French,
+    Spanish,
+    Dutch,
+}
+
+impl ResponseLanguage {
+    const ALL: [Self; 8] = [
+        Self::English,
+        Self::Norwegian,
+        Self::Swedish,
+        Self::Danish,
+        Self::German,
+        Self::French,
+        Self::Spanish,
+        Self::Dutch,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Norwegian => "Norwegian",
+            Self::Swedish => "Swedish",
+            Self::Danish => "Danish",
+            Self::German => "German",
+            Self::French => "French",
+            Self::Spanish => "Spanish",
+            Self::Dutch => "Dutch",
+        }
+    }
+
+    fn stopwords(self) -> &'static [&'static str] {
+        match self {
+            Self::English => &[
+                "the", "and", "is", "are", "of", "to", "with", "that", "this", "you", "it", "for",
+                "not", "have", "be", "was", "what", "which", "in",
+            ],
+            Self::Norwegian => &[
+                "og", "ikke", "jeg", "det", "er", "til", "som", "med", "på", "av", "har", "en",
+                "et", "vi", "kan", "skal", "hva", "hvordan", "også", "være", "eller", "fra",
+                "noen", "ble", "bare", "meg", "deg", "kanskje", "mye", "nå",
+            ],
+            Self::Swedish => &[
+                "och", "inte", "jag", "är", "att", "som", "med", "på", "av", "har", "en", "ett",
+                "det", "vi", "kan", "ska", "vad", "hur", "också", "vara", "eller", "från",
+                "mycket", "nu",
+            ],
+            Self::Danish => &[
+                "og", "ikke", "jeg", "det", "er", "til", "som", "med", "på", "af", "har", "en",
+                "et", "vi", "kan", "skal", "hvad", "hvordan", "også", "være", "eller", "fra",
+                "nogen", "blev", "kun", "mig", "dig", "måske", "meget", "nu",
+            ],
+            Self::German => &[
+                "und", "der", "die", "das", "ist", "nicht", "ich", "mit", "zu", "den", "von",
+                "ein", "eine", "auf", "für", "sie", "es", "sind", "auch", "wie", "oder",
+            ],
+            Self::French => &[
+                "le", "la", "les", "et", "est", "un", "une", "des", "du", "de", "que", "qui",
+                "pas", "pour", "dans", "avec", "ce", "sont", "je", "vous", "il",
+            ],
+            Self::Spanish => &[
+                "el", "la", "los", "las", "y", "es", "un", "una", "de", "que", "en", "no", "por",
+                "para", "con", "se", "del", "son", "lo", "como",
+            ],
+            Self::Dutch => &[
+                "de", "het", "een", "en", "is", "van", "niet", "ik", "met", "op", "dat", "zijn",
+                "voor", "die", "ook", "wat", "hoe", "maar",
+            ],
+        }
+    }
+}
+
+impl FromStr for ResponseLanguage {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "en" | "english" => Ok(Self::English),
+            "no" | "nb" | "nn" | "norwegian" | "norsk" => Ok(Self::Norwegian),
+            "sv" | "swedish" | "svenska" => Ok(Self::Swedish),
+            "da" | "danish" | "dansk" => Ok(Self::Danish),
+            "de" | "german" | "deutsch" => Ok(Self::German),
+            "fr" | "french" | "français" | "francais" => Ok(Self::French),
+            "es" | "spanish" | "español" | "espanol" => Ok(Self::Spanish),
+            "nl" | "dutch" | "nederlands" => Ok(Self::Dutch),
+            other => Err(anyhow!(
+                "invalid AGENT_RESPONSE_LANGUAGE `{other}`; expected one of en, no, sv, da, de, fr, es, nl (or the language name)"
+            )),
+        }
+    }
+}
+
+/// Stopword-based guess of the answer language; `None` when the signal is too weak or tied.
+pub fn detect_response_language(answer: &str) -> Option<ResponseLanguage> {
+    let scores = language_scores(answer);
+    let best = scores.iter().map(|(_, score)| *score).max()?;
+    if best < MIN_LANGUAGE_SIGNAL_WORDS {
+        return None;
+    }
+
+    let mut leaders = scores.iter().filter(|(_, score)| *score == best);
+    match (leaders.next(), leaders.next()) {
+        (Some((language, _)), None) => Some(*language),
+        _ => None,
+    }
+}
+
+/// Short or inconclusive answers pass; otherwise the expected language must score at
+/// least as high as every other supported language.
+pub fn answer_matches_response_language(language: ResponseLanguage, answer: &str) -> bool {
+    let scores = language_scores(answer);
+    let best = scores
+        .iter()
+        .map(|(_, score)| *score)
+        .max()
+        .unwrap_or_default();
+    if best < MIN_LANGUAGE_SIGNAL_WORDS {
+        return true;
+    }
+
+    scores
+        .iter()
+        .any(|(candidate, score)| *candidate == language && *score == best)
+}
+
+fn language_scores(answer: &str) -> Vec<(ResponseLanguage, usize)> {
+    let words = answer
+        .split(|ch: char| !ch.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+
+    ResponseLanguage::ALL
+        .iter()
+        .map(|language| {
+            let stopwords = language.stopwords();
+            let score = words
+                .iter()
+                .filter(|word| stopwords.contains(&word.as_str()))
+                .count();
+            (*language, score)
+        })
+        .collect()
+}
+
+/// One text transform applied to final answers before they are checked and returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnswerPostProcessor {
+    /// Unwraps a single surrounding ``` fence, only when a JSON answer was requested.
+    StripJsonFences,
+    NormalizeLineEndings,
+    TrimTrailingWhitespace,
+    CollapseBlankLines,
+}
+
+/// Used when `ANSWER_POST_PROCESSORS` is unset; trailing whitespace is kept because two
+/// trailing spaces are a markdown line break, and fenced JSON is left to the fence
+/// tolerance check so the outcome can report the local repair.
+pub const DEFAULT_ANSWER_POST_PROCESSORS: [AnswerPostProcessor; 1] =
+    [AnswerPostProcessor::NormalizeLineEndings];
+
+impl AnswerPostProcessor {
+    const ALL: [Self; 4] = [
+        Self::StripJsonFences,
+        Self::NormalizeLineEndings,
+        Self::TrimTrailingWhitespace,
+        Self::CollapseBlankLines,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::StripJsonFences => "strip_json_fences",
+            Self::NormalizeLineEndings => "normalize_line_endings",
+            Self::TrimTrailingWhitespace => "trim_trailing_whitespace",
+            Self::CollapseBlankLines => "collapse_blank_lines",
+        }
+    }
+
+    pub fn apply(
+        self,
+        answer: String,
+        requested_format: Option<&StructuredAnswerFormat>,
+    ) -> String {
+        match self {
+            Self::StripJsonFences => {
+                if requested_format.is_some_and(StructuredAnswerFormat::is_json) {
+                    unwrap_code_fence(&answer).unwrap_or(answer)
+                } else {
+                    answer
+                }
+            }
+            Self::NormalizeLineEndings => answer.replace("\r\n", "\n").replace('\r', "\n"),
+            Self::TrimTrailingWhitespace => answer
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim_end()
+                .to_owned(),
+            Self::CollapseBlankLines => {
+                let mut collapsed = Vec::new();
+                for line in answer.lines() {
+                    let blank = line.trim().is_empty();
+                    if !(blank
+                        && collapsed
+                            .last()
+                            .is_some_and(|last: &&str| last.trim().is_empty()))
+                    {
+                        collapsed.push(line);
+                    }
+                }
+                collapsed.join("\n")
+            }
+        }
+    }
+}
+
+impl FromStr for AnswerPostProcessor {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|processor| processor.as_str() == value)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(Self::as_str);
+                anyhow!(
+                    "invalid answer post-processor `{value}`; expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Runs the configured post-processors in order.
+pub fn apply_answer_post_processors(
+    processors: &[AnswerPostProcessor],
+    requested_format: Option<&StructuredAnswerFormat>,
+    answer: String,
+) -> String {
+    processors.iter().fold(answer, |answer, processor| {
+        processor.apply(answer, requested_format)
+    })
+}
+
+/// Lenient JSON check: an answer that is exactly one fenced block holding a JSON object
+/// is accepted as that object, without asking the model to reformat.
+pub fn unwrap_fenced_json_object(answer: &str) -> Option<String> {
+    unwrap_code_fence(answer).filter(|body| validate_json_object(body).is_ok())
 }
 
-pub fn answer_matches_structured_format(format: StructuredAnswerFormat, answer: &str) -> bool {
+/// Body of an answer that is exactly one fenced block (```json or bare ```).
+fn unwrap_code_fence(answer: &str) -> Option<String> {
+    let inner = answer.trim().strip_prefix("```")?.strip_suffix("```")?;
+    let (info, body) = inner.split_once('\n')?;
+    if info.trim().contains(char::is_whitespace) || body.contains("```") {
+        return None;
+    }
+    Some(body.trim().to_owned())
+}
+
+pub fn answer_matches_structured_format(format: &StructuredAnswerFormat, answer: &str) -> bool {
     validate_structured_answer_format(format, answer).is_ok()
 }
 
 pub fn validate_structured_answer_format(
-    format: StructuredAnswerFormat,
+    format: &StructuredAnswerFormat,
     answer: &str,
 ) -> Result<(), StructuredAnswerFormatError> {
     match format {
         StructuredAnswerFormat::JsonObject => validate_json_object(answer),
         StructuredAnswerFormat::MarkdownBullets => validate_markdown_bullets(answer),
+        StructuredAnswerFormat::CsvTable(delimiter) => {
+            validate_csv_table(answer, CsvTableRules::any(*delimiter))
+        }
+        StructuredAnswerFormat::JsonSchema(schema) => validate_json_schema(schema, answer),
+    }
+}
+
+/// Parses `answer` as JSON and checks it against `schema` with the lightweight validator
+/// in `crate::json_schema`.
+fn validate_json_schema(schema: &Value, answer: &str) -> Result<(), StructuredAnswerFormatError> {
+    let value = serde_json::from_str::<Value>(answer)
+        .map_err(|error| StructuredAnswerFormatError::JsonParseError(error.to_string()))?;
+    let violations = schema_violations(schema, &value);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(StructuredAnswerFormatError::JsonSchemaViolations(
+            violations,
+        ))
     }
 }
 
@@ -60,17 +491,185 @@ fn validate_markdown_bullets(answer: &str) -> Result<(), StructuredAnswerFormatE
     }
 }
 
+/// Checks that `answer` is a delimited table with a header row and at least one data row,
+/// every row as wide as the header. Quoted fields may contain the delimiter and `""`
+/// escapes but not line breaks; blank lines are ignored.
+pub fn validate_csv_table(
+    answer: &str,
+    rules: CsvTableRules<'_>,
+) -> Result<(), StructuredAnswerFormatError> {
+    let mut rows = Vec::new();
+    for (index, line) in answer.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells = split_delimited_record(line, rules.delimiter.as_char())
+            .ok_or(StructuredAnswerFormatError::CsvUnterminatedQuote(index + 1))?;
+        rows.push((index + 1, cells));
+    }
+    let Some(((_, header), data)) = rows.split_first() else {
+        return Err(StructuredAnswerFormatError::EmptyAnswer);
+    };
+
+    if rules.header.is_empty() {
+        if header
+            .iter()
+            .any(|cell| cell.is_empty() || cell.parse::<f64>().is_ok())
+        {
+            return Err(StructuredAnswerFormatError::CsvMissingHeader(
+                header.clone(),
+            ));
+        }
+    } else if header.len() != rules.header.len()
+        || header
+            .iter()
+            .zip(rules.header)
+            .any(|(found, expected)| !found.eq_ignore_ascii_case(expected.trim()))
+    {
+        return Err(StructuredAnswerFormatError::CsvHeaderMismatch {
+            expected: rules.header.to_vec(),
+            found: header.clone(),
+        });
+    }
+
+    if let Some(expected) = rules.columns
+        && header.len() != expected
+    {
+        return Err(StructuredAnswerFormatError::CsvColumnCount {
+            expected,
+            found: header.len(),
+        });
+    }
+    if data.is_empty() {
+        return Err(StructuredAnswerFormatError::CsvNoDataRows);
+    }
+
+    let ragged: Vec<(usize, usize)> = data
+        .iter()
+        .filter(|(_, cells)| cells.len() != header.len())
+        .map(|(line, cells)| (*line, cells.len()))
+        .collect();
+    if ragged.is_empty() {
+        Ok(())
+    } else {
+        Err(StructuredAnswerFormatError::CsvRaggedRows {
+            expected: header.len(),
+            rows: ragged,
+        })
+    }
+}
+
+/// Splits one CSV/TSV line into trimmed cells; `None` when a quoted field is unterminated.
+fn split_delimited_record(line: &str, delimiter: char) -> Option<Vec<String>> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                in_quotes = true;
+            }
+            _ if ch == delimiter && !in_quotes => {
+                cells.push(cell.trim().to_owned());
+                cell.clear();
+            }
+            _ => cell.push(ch),
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    cells.push(cell.trim().to_owned());
+    Some(cells)
+}
+
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
+
     use super::{
-        StructuredAnswerFormat, StructuredAnswerFormatError, answer_matches_structured_format,
-        validate_structured_answer_format,
+        AnswerPostProcessor, CsvTableRules, DEFAULT_ANSWER_POST_PROCESSORS, ResponseLanguage,
+        StructuredAnswerFormat, StructuredAnswerFormatError, TableDelimiter,
+        answer_matches_response_language, answer_matches_structured_format,
+        apply_answer_post_processors, detect_response_language, unwrap_fenced_json_object,
+        validate_csv_table, validate_structured_answer_format,
     };
 
+    #[test]
+    fn strip_json_fences_applies_only_to_json_requests() {
+        let processors = [
+            AnswerPostProcessor::NormalizeLineEndings,
+            AnswerPostProcessor::StripJsonFences,
+        ];
+        let fenced = "```json\r\n{\"ok\": true}\r\n```".to_owned();
+
+        assert_eq!(
+            apply_answer_post_processors(
+                &processors,
+                Some(&StructuredAnswerFormat::JsonObject),
+                fenced.clone()
+            ),
+            r#"{"ok": true}"#
+        );
+        assert_eq!(
+            apply_answer_post_processors(&DEFAULT_ANSWER_POST_PROCESSORS, None, fenced),
+            "```json\n{\"ok\": true}\n```"
+        );
+    }
+
+    #[test]
+    fn fenced_json_object_is_unwrapped_only_when_the_body_is_an_object() {
+        assert_eq!(
+            unwrap_fenced_json_object("```json\n{\"ok\": true}\n```").as_deref(),
+            Some(r#"{"ok": true}"#)
+        );
+        assert_eq!(
+            unwrap_fenced_json_object("```\n{}\n```").as_deref(),
+            Some("{}")
+        );
+        assert_eq!(unwrap_fenced_json_object("```json\n[1, 2]\n```"), None);
+        assert_eq!(unwrap_fenced_json_object("{\"ok\": true}"), None);
+        assert_eq!(
+            unwrap_fenced_json_object("```json\n{}\n```\nand more\n```\n{}\n```"),
+            None
+        );
+    }
+
+    #[test]
+    fn whitespace_post_processors_compose_in_order() {
+        let processors = [
+            AnswerPostProcessor::TrimTrailingWhitespace,
+            AnswerPostProcessor::CollapseBlankLines,
+        ];
+
+        assert_eq!(
+            apply_answer_post_processors(
+                &processors,
+                None,
+                "Intro  \n\n\n \n- one\t\n- two\n\n".to_owned()
+            ),
+            "Intro\n\n- one\n- two"
+        );
+        assert_eq!(
+            "Collapse_Blank_Lines"
+                .parse::<AnswerPostProcessor>()
+                .expect("names are case-insensitive"),
+            AnswerPostProcessor::CollapseBlankLines
+        );
+        assert!("shout".parse::<AnswerPostProcessor>().is_err());
+    }
+
     #[test]
     fn json_object_validation_accepts_objects() {
         assert!(answer_matches_structured_format(
-            StructuredAnswerFormat::JsonObject,
+            &StructuredAnswerFormat::JsonObject,
             r#"{"ok":true}"#
         ));
     }
@@ -78,7 +677,7 @@ mod tests {
     #[test]
     fn json_object_validation_rejects_non_objects() {
         let error =
-            validate_structured_answer_format(StructuredAnswerFormat::JsonObject, "[1,2,3]")
+            validate_structured_answer_format(&StructuredAnswerFormat::JsonObject, "[1,2,3]")
                 .expect_err("non-object json should fail");
         assert_eq!(error, StructuredAnswerFormatError::JsonNotObject);
     }
@@ -86,7 +685,7 @@ mod tests {
     #[test]
     fn json_object_validation_rejects_invalid_json() {
         let error =
-            validate_structured_answer_format(StructuredAnswerFormat::JsonObject, "not-json")
+            validate_structured_answer_format(&StructuredAnswerFormat::JsonObject, "not-json")
                 .expect_err("invalid json should fail");
         let StructuredAnswerFormatError::JsonParseError(message) = error else {
             panic!("expected json parse error");
@@ -94,10 +693,33 @@ mod tests {
         assert!(!message.is_empty());
     }
 
+    #[test]
+    fn json_schema_validation_reports_each_violation() {
+        let format = StructuredAnswerFormat::JsonSchema(json!({
+            "type": "object",
+            "required": ["name", "year"],
+            "properties": {"year": {"type": "integer"}}
+        }));
+        assert!(answer_matches_structured_format(
+            &format,
+            r#"{"name": "rust", "year": 2015}"#
+        ));
+        let error = validate_structured_answer_format(&format, r#"{"year": "2015"}"#)
+            .expect_err("schema mismatch should fail");
+        assert_eq!(
+            error.to_string(),
+            "answer does not match the JSON schema: /: missing required `name`; /year: expected integer, found string"
+        );
+        assert!(matches!(
+            validate_structured_answer_format(&format, "year: 2015"),
+            Err(StructuredAnswerFormatError::JsonParseError(_))
+        ));
+    }
+
     #[test]
     fn markdown_bullets_validation_accepts_bullets() {
         assert!(answer_matches_structured_format(
-            StructuredAnswerFormat::MarkdownBullets,
+            &StructuredAnswerFormat::MarkdownBullets,
             "- one\n- two"
         ));
     }
@@ -105,7 +727,7 @@ mod tests {
     #[test]
     fn markdown_bullets_validation_rejects_empty_answer() {
         let error =
-            validate_structured_answer_format(StructuredAnswerFormat::MarkdownBullets, "  \n \n")
+            validate_structured_answer_format(&StructuredAnswerFormat::MarkdownBullets, "  \n \n")
                 .expect_err("empty answer should fail");
         assert_eq!(error, StructuredAnswerFormatError::EmptyAnswer);
     }
@@ -113,7 +735,7 @@ mod tests {
     #[test]
     fn markdown_bullets_validation_reports_non_bullet_lines() {
         let error = validate_structured_answer_format(
-            StructuredAnswerFormat::MarkdownBullets,
+            &StructuredAnswerFormat::MarkdownBullets,
             "- one\nnot bullet\n- two",
         )
         .expect_err("non-bullet lines should fail");
@@ -122,4 +744,118 @@ mod tests {
         };
         assert_eq!(lines, vec!["not bullet".to_owned()]);
     }
+
+    #[test]
+    fn csv_table_validation_checks_header_and_row_widths() {
+        let any_csv = CsvTableRules::any(TableDelimiter::Comma);
+        assert_eq!(
+            validate_csv_table("name,year\n\"Rust, the language\",2015\n\ngo,2009", any_csv),
+            Ok(())
+        );
+        assert_eq!(
+            validate_csv_table(
+                "name\tyear\nrust\t2015",
+                CsvTableRules::any(TableDelimiter::Tab)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_csv_table("rust,2015\ngo,2009", any_csv),
+            Err(StructuredAnswerFormatError::CsvMissingHeader(vec![
+                "rust".to_owned(),
+                "2015".to_owned()
+            ]))
+        );
+        assert_eq!(
+            validate_csv_table("name,year", any_csv),
+            Err(StructuredAnswerFormatError::CsvNoDataRows)
+        );
+        assert_eq!(
+            validate_csv_table("name,year\nrust\ngo,2009,x", any_csv),
+            Err(StructuredAnswerFormatError::CsvRaggedRows {
+                expected: 2,
+                rows: vec![(2, 1), (3, 3)],
+            })
+        );
+        assert_eq!(
+            validate_csv_table("name,\"year\nrust,2015", any_csv),
+            Err(StructuredAnswerFormatError::CsvUnterminatedQuote(1))
+        );
+    }
+
+    #[test]
+    fn csv_table_validation_applies_expected_columns_and_header() {
+        let header = vec!["Name".to_owned(), "Year".to_owned()];
+        let rules = CsvTableRules {
+            delimiter: TableDelimiter::Comma,
+            columns: Some(2),
+            header: &header,
+        };
+        assert_eq!(validate_csv_table("name,year\nrust,2015", rules), Ok(()));
+        let error = validate_csv_table("name,released\nrust,2015", rules)
+            .expect_err("wrong header should fail");
+        assert_eq!(
+            error.to_string(),
+            "header is `name | released`, expected `Name | Year`"
+        );
+
+        let three_columns = CsvTableRules {
+            delimiter: TableDelimiter::Comma,
+            columns: Some(3),
+            header: &[],
+        };
+        assert_eq!(
+            validate_csv_table("name,year\nrust,2015", three_columns),
+            Err(StructuredAnswerFormatError::CsvColumnCount {
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn response_language_parses_codes_and_names() {
+        assert_eq!(
+            "nb".parse::<ResponseLanguage>().expect("code"),
+            ResponseLanguage::Norwegian
+        );
+        assert_eq!(
+            " Norwegian ".parse::<ResponseLanguage>().expect("name"),
+            ResponseLanguage::Norwegian
+        );
+        assert!("klingon".parse::<ResponseLanguage>().is_err());
+    }
+
+    #[test]
+    fn detect_response_language_identifies_common_languages() {
+        assert_eq!(
+            detect_response_language("The build is green and the tests pass with no warnings."),
+            Some(ResponseLanguage::English)
+        );
+        assert_eq!(
+            detect_response_language("Bygget er grønt, og testene har ikke noen advarsler nå."),
+            Some(ResponseLanguage::Norwegian)
+        );
+        assert_eq!(
+            detect_response_language("Der Build ist grün und die Tests sind auch in Ordnung."),
+            Some(ResponseLanguage::German)
+        );
+        assert_eq!(detect_response_language("OK"), None);
+    }
+
+    #[test]
+    fn answer_language_check_passes_short_answers_and_rejects_mismatches() {
+        assert!(answer_matches_response_language(
+            ResponseLanguage::Norwegian,
+            "cargo test"
+        ));
+        assert!(answer_matches_response_language(
+            ResponseLanguage::Norwegian,
+            "Det er ikke mulig å bygge prosjektet med denne versjonen."
+        ));
+        assert!(!answer_matches_response_language(
+            ResponseLanguage::Norwegian,
+            "It is not possible to build the project with this version."
+        ));
+    }
 }
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Coarse grouping of `ErrorCode`s that HTTP statuses and webhook `error_kind` follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatTurnErrorKind {
+    BadRequest,
+    Upstream,
+    Internal,
+    Blocked,
+}
+
+impl ChatTurnErrorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BadRequest => "bad_request",
+            Self::Upstream => "upstream",
+            Self::Internal => "internal",
+            Self::Blocked => "blocked",
+        }
+    }
+}
+
+/// Stable, machine-readable reason a chat turn failed. The strings never change once
+/// released; `ChatTurnErrorKind` is the coarser grouping HTTP statuses are derived from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, thiserror::Error)]
+#[error("{}", self.as_str())]
+pub enum ErrorCode {
+    InputTooLong,
+    OutputTooLong,
+    ToolOutputTooLong,
+    GuardrailMaxSteps,
+    GuardrailToolCap,
+    GuardrailToolCallsPerStep,
+    GuardrailConsecutiveToolSteps,
+    ToolUnknown,
+    ToolInvalidArgs,
+    ToolPolicyDisabled,
+    ToolPolicyOffline,
+    ToolPolicyDomain,
+    ToolPolicyScheme,
+    ToolPolicyContentType,
+    ToolPolicySize,
+    ToolPolicyNoteTarget,
+    ToolPolicyNotePath,
+    ToolPolicyOverwrite,
+    ToolPolicyDelete,
+    ToolPolicyNoteQuota,
+    ToolPolicyWorkspacePath,
+    ToolPolicyWorkspaceFileType,
+    ToolPolicyCommand,
+    UpstreamModel,
+    UpstreamModeration,
+    UpstreamTool,
+    ToolFailed,
+    ToolTimeout,
+    ToolInvalidOutput,
+    ModerationBlocked,
+    Internal,
+}
+
+impl ErrorCode {
+    pub const ALL: [Self; 31] = [
+        Self::InputTooLong,
+        Self::OutputTooLong,
+        Self::ToolOutputTooLong,
+        Self::GuardrailMaxSteps,
+        Self::GuardrailToolCap,
+        Self::GuardrailToolCallsPerStep,
+        Self::GuardrailConsecutiveToolSteps,
+        Self::ToolUnknown,
+        Self::ToolInvalidArgs,
+        Self::ToolPolicyDisabled,
+        Self::ToolPolicyOffline,
+        Self::ToolPolicyDomain,
+        Self::ToolPolicyScheme,
+        Self::ToolPolicyContentType,
+        Self::ToolPolicySize,
+        Self::ToolPolicyNoteTarget,
+        Self::ToolPolicyNotePath,
+        Self::ToolPolicyOverwrite,
+        Self::ToolPolicyDelete,
+        Self::ToolPolicyNoteQuota,
+        Self::ToolPolicyWorkspacePath,
+        Self::ToolPolicyWorkspaceFileType,
+        Self::ToolPolicyCommand,
+        Self::UpstreamModel,
+        Self::UpstreamModeration,
+        Self::UpstreamTool,
+        Self::ToolFailed,
+        Self::ToolTimeout,
+        Self::ToolInvalidOutput,
+        Self::ModerationBlocked,
+        Self::Internal,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InputTooLong => "E_INPUT_TOO_LONG",
+            Self::OutputTooLong => "E_OUTPUT_TOO_LONG",
+            Self::ToolOutputTooLong => "E_TOOL_OUTPUT_TOO_LONG",
+            Self::GuardrailMaxSteps => "E_GUARDRAIL_MAX_STEPS",
+            Self::GuardrailToolCap => "E_GUARDRAIL_TOOL_CAP",
+            Self::GuardrailToolCallsPerStep => "E_GUARDRAIL_TOOL_CALLS_PER_STEP",
+            Self::GuardrailConsecutiveToolSteps => "E_GUARDRAIL_CONSECUTIVE_TOOL_STEPS",
+            Self::ToolUnknown => "E_TOOL_UNKNOWN",
+            Self::ToolInvalidArgs => "E_TOOL_INVALID_ARGS",
+            Self::ToolPolicyDisabled => "E_TOOL_POLICY_DISABLED",
+            Self::ToolPolicyOffline => "E_TOOL_POLICY_OFFLINE",
+            Self::ToolPolicyDomain => "E_TOOL_POLICY_DOMAIN",
+            Self::ToolPolicyScheme => "E_TOOL_POLICY_SCHEME",
+            Self::ToolPolicyContentType => "E_TOOL_POLICY_CONTENT_TYPE",
+            Self::ToolPolicySize => "E_TOOL_POLICY_SIZE",
+            Self::ToolPolicyNoteTarget => "E_TOOL_POLICY_NOTE_TARGET",
+            Self::ToolPolicyNotePath => "E_TOOL_POLICY_NOTE_PATH",
+            Self::ToolPolicyOverwrite => "E_TOOL_POLICY_OVERWRITE",
+            Self::ToolPolicyDelete => "E_TOOL_POLICY_DELETE",
+            Self::ToolPolicyNoteQuota => "E_TOOL_POLICY_NOTE_QUOTA",
+            Self::ToolPolicyWorkspacePath => "E_TOOL_POLICY_WORKSPACE_PATH",
+            Self::ToolPolicyWorkspaceFileType => "E_TOOL_POLICY_WORKSPACE_FILE_TYPE",
+            Self::ToolPolicyCommand => "E_TOOL_POLICY_COMMAND",
+            Self::UpstreamModel => "E_UPSTREAM_MODEL",
+            Self::UpstreamModeration => "E_UPSTREAM_MODERATION",
+            Self::UpstreamTool => "E_UPSTREAM_TOOL",
+            Self::ToolFailed => "E_TOOL_FAILED",
+            Self::ToolTimeout => "E_TOOL_TIMEOUT",
+            Self::ToolInvalidOutput => "E_TOOL_INVALID_OUTPUT",
+            Self::ModerationBlocked => "E_MODERATION_BLOCKED",
+            Self::Internal => "E_INTERNAL",
+        }
+    }
+
+    pub fn kind(self) -> ChatTurnErrorKind {
+        match self {
+            Self::InputTooLong
+            | Self::OutputTooLong
+            | Self::ToolOutputTooLong
+            | Self::GuardrailMaxSteps
+            | Self::GuardrailToolCap
+            | Self::GuardrailToolCallsPerStep
+            | Self::GuardrailConsecutiveToolSteps
+            | Self::ToolUnknown
+            | Self::ToolInvalidArgs
+            | Self::ToolPolicyDisabled
+            | Self::ToolPolicyOffline
+            | Self::ToolPolicyDomain
+            | Self::ToolPolicyScheme
+            | Self::ToolPolicyContentType
+            | Self::ToolPolicySize
+            | Self::ToolPolicyNoteTarget
+            | Self::ToolPolicyNotePath
+            | Self::ToolPolicyOverwrite
+            | Self::ToolPolicyDelete
+            | Self::ToolPolicyNoteQuota
+            | Self::ToolPolicyWorkspacePath
+            | Self::ToolPolicyWorkspaceFileType
+            | Self::ToolPolicyCommand => ChatTurnErrorKind::BadRequest,
+            Self::UpstreamModel | Self::UpstreamModeration | Self::UpstreamTool => {
+                ChatTurnErrorKind::Upstream
+            }
+            Self::ToolFailed | Self::ToolTimeout | Self::ToolInvalidOutput | Self::Internal => {
+                ChatTurnErrorKind::Internal
+            }
+            Self::ModerationBlocked => ChatTurnErrorKind::Blocked,
+        }
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Self::ALL
+            .into_iter()
+            .find(|code| code.as_str() == raw)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown error code `{raw}`")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{ChatTurnErrorKind, ErrorCode};
+
+    #[test]
+    fn error_codes_are_unique_prefixed_and_grouped_by_kind() {
+        let strings = ErrorCode::ALL
+            .iter()
+            .map(|code| code.as_str())
+            .collect::<HashSet<_>>();
+        assert_eq!(strings.len(), ErrorCode::ALL.len());
+        assert!(strings.iter().all(|code| code.starts_with("E_")));
+
+        assert_eq!(
+            ErrorCode::GuardrailToolCap.kind(),
+            ChatTurnErrorKind::BadRequest
+        );
+        assert_eq!(ErrorCode::UpstreamModel.kind(), ChatTurnErrorKind::Upstream);
+        assert_eq!(ErrorCode::ToolTimeout.kind(), ChatTurnErrorKind::Internal);
+        assert_eq!(
+            serde_json::to_value(ErrorCode::ToolPolicyDomain).expect("serialize"),
+            "E_TOOL_POLICY_DOMAIN"
+        );
+        assert_eq!(
+            serde_json::from_str::<ErrorCode>("\"E_TOOL_POLICY_DOMAIN\"").expect("deserialize"),
+            ErrorCode::ToolPolicyDomain
+        );
+        assert!(serde_json::from_str::<ErrorCode>("\"E_NOPE\"").is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::graph::ArchitectureGraph;
+
+mod error_code;
+mod transcript;
+mod turn;
+
+pub use self::error_code::{ChatTurnErrorKind, ErrorCode};
+pub use self::transcript::{
+    ExportFormat, Transcript, TranscriptEntry, TranscriptRole, TranscriptToolCall,
+};
+pub use self::turn::{
+    ContextUsage, ExecutedToolCall, InjectionDetection, ModerationAction, ModerationDecision,
+    TimelineEvent, TimelineEventKind, TimelineSpan, TimelineSpanKind, TurnCitation,
+    TurnTraceSummary,
+};
+
+/// Body of `POST /chat` and `POST /chat/stream`. The server rejects unknown fields, so
+/// the client serializes this same type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChatRequest {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Skip `JSON_OUTPUT_MAX_FIELD_CHARS` truncation of tool-call fields.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub full: bool,
+    /// Run this turn on another model, and `provider` if set, than the active one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Who sends the turn, for traces, the turn ledger, note authorship, and `/metrics`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl ChatRequest {
+    /// A stateless turn with the server's active model.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Continues (or starts) the server-side session `session_id`.
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+}
+
+/// Body of every JSON error response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: String,
+    /// Stable error code, for failed chat turns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    /// The decision that withheld the answer, for turns blocked by moderation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation: Option<ModerationDecision>,
+}
+
+impl ErrorBody {
+    pub fn new(error: String) -> Self {
+        Self {
+            error,
+            code: None,
+            moderation: None,
+        }
+    }
+}
+
+/// Body of `GET /graph` when the graph changed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphBody {
+    pub graph: ArchitectureGraph,
+}
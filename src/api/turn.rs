@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutedToolCall {
+    pub tool_name: String,
+    /// Arguments as parsed from the model's tool call.
+    pub arguments: serde_json::Value,
+    pub output: String,
+    pub latency: Duration,
+    pub attempts: u32,
+    /// Last transient failure for calls that succeeded on a retry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnTraceSummary {
+    /// Package version and commit of the build that ran the turn.
+    pub version: String,
+    pub git_commit: String,
+    pub input_chars: usize,
+    pub output_chars: Option<usize>,
+    pub steps_executed: u32,
+    pub model_calls: u32,
+    pub tool_calls: u32,
+    pub total_model_latency: Duration,
+    pub total_tool_latency: Duration,
+    pub tool_names: Vec<String>,
+    pub stripped_tool_call_blocks: usize,
+    pub injection_detections: Vec<InjectionDetection>,
+    /// Earlier messages dropped during the turn to stay under `AGENT_MAX_CONTEXT_TOKENS`.
+    pub trimmed_messages: usize,
+    /// Earlier messages replaced by a summary before the turn because the conversation
+    /// was above `AGENT_AUTO_COMPACT`.
+    pub compacted_messages: usize,
+    /// Who sent the turn, when the caller named one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Per-call start/end offsets for waterfall views.
+    pub timeline: Vec<TimelineSpan>,
+    /// Conversation size after the turn.
+    pub context: ContextUsage,
+}
+
+/// Links an inline `[n]` marker in the final answer to the tool call it cites.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnCitation {
+    pub marker: u32,
+    pub tool_call_index: usize,
+    pub tool_name: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineSpanKind {
+    ModelCall,
+    ToolCall,
+}
+
+/// One model or tool call, with start/end offsets from the beginning of the turn.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineSpan {
+    pub kind: TimelineSpanKind,
+    pub label: String,
+    pub step: u32,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+impl TimelineSpan {
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    ModelCallStart,
+    ModelCallEnd,
+    ToolStart,
+    ToolEnd,
+}
+
+/// One span boundary in the flat `timeline` of `chat --json` and `POST /chat` outcomes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub event: TimelineEventKind,
+    /// Milliseconds since the turn started, with microsecond precision.
+    pub at_ms: f64,
+    pub step: u32,
+    pub label: String,
+    /// Position of the call in the outcome's `tool_calls`; tool events only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    Allowed,
+    Flagged,
+    Redacted,
+    Blocked,
+}
+
+/// Outcome of the moderation pass over one final answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModerationDecision {
+    pub action: ModerationAction,
+    pub provider: String,
+    pub categories: Vec<String>,
+}
+
+/// One heuristic prompt-injection match inside a tool result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InjectionDetection {
+    pub tool_call_index: usize,
+    pub tool_name: String,
+    pub pattern: String,
+}
+
+/// Conversation size against `AGENT_CONTEXT_BUDGET_TOKENS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ContextUsage {
+    /// Retained messages after the system prompt.
+    pub messages: usize,
+    /// Characters of every message (system prompt, tool calls, and tool results included)
+    /// divided by four.
+    pub approx_tokens: usize,
+    pub budget_tokens: usize,
+}
@@ -0,0 +1,274 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow, ensure};
+
+use crate::config::AgentSettings;
+use crate::model::client::ModelClient;
+
+mod serve;
+
+pub use self::serve::{
+    DEFAULT_LOAD_TEST_CONCURRENCY, DEFAULT_LOAD_TEST_REQUESTS, DEFAULT_MOCK_LATENCY_MS,
+    LoadTestOptions, LoadTestReport, format_load_test_report, run_load_test_command,
+};
+#[cfg(test)]
+pub(crate) use self::serve::{load_test_settings, spawn_mock_model};
+
+pub const DEFAULT_BENCH_REQUESTS: u32 = 5;
+
+const BENCH_SYSTEM_PROMPT: &str = "You are a concise assistant. Answer in two or three sentences.";
+
+/// Fixed prompts sent in rotation so runs are comparable across models and providers.
+const BENCH_PROMPTS: &[&str] = &[
+    "Explain what a hash map is.",
+    "Summarize the difference between TCP and UDP.",
+    "Why do compilers perform constant folding?",
+    "Describe what a mutex protects against.",
+    "What is the purpose of a README file in a software project?",
+];
+
+/// One timed request against a model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchSample {
+    pub latency: Duration,
+    pub output_tokens: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchSummary {
+    pub model: String,
+    pub requests: usize,
+    pub failures: usize,
+    pub p50: Option<Duration>,
+    pub p90: Option<Duration>,
+    pub p99: Option<Duration>,
+    /// Output tokens per second over successful requests that reported a token count.
+    pub tokens_per_second: Option<f64>,
+}
+
+impl BenchSummary {
+    pub fn failure_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.requests as f64
+        }
+    }
+}
+
+pub async fn run_bench_command(
+    settings: &AgentSettings,
+    models: &[String],
+    requests: u32,
+) -> Result<()> {
+    ensure!(requests > 0, "--requests must be greater than zero");
+    let models = if models.is_empty() {
+        vec![settings.model.clone()]
+    } else {
+        models.to_vec()
+    };
+
+    println!(
+        "Benchmarking {} model(s) on {} with {} request(s) each",
+        models.len(),
+        settings.model_provider.as_str(),
+        requests
+    );
+
+    let mut summaries = Vec::with_capacity(models.len());
+    for model in &models {
+        let samples = run_bench_model(settings, model, requests).await;
+        for (index, sample) in samples.iter().enumerate() {
+            if let Some(error) = &sample.error {
+                println!("  [{model}] request {} failed: {error}", index + 1);
+            }
+        }
+        summaries.push(summarize_bench_samples(model, &samples));
+    }
+
+    print!("{}", format_bench_table(&summaries));
+
+    if summaries
+        .iter()
+        .all(|summary| summary.failures == summary.requests)
+    {
+        return Err(anyhow!("every benchmark request failed"));
+    }
+    Ok(())
+}
+
+async fn run_bench_model(settings: &AgentSettings, model: &str, requests: u32) -> Vec<BenchSample> {
+    let mut bench_settings = settings.clone();
+    bench_settings.model = model.to_owned();
+    // Retries would hide provider failures and skew latency.
+    bench_settings.model_max_retries = 0;
+    let client = ModelClient::new(bench_settings);
+
+    let mut samples = Vec::with_capacity(requests as usize);
+    for index in 0..requests as usize {
+        let prompt = BENCH_PROMPTS[index % BENCH_PROMPTS.len()];
+        let started_at = Instant::now();
+        let result = client.chat_measured(BENCH_SYSTEM_PROMPT, prompt).await;
+        let latency = started_at.elapsed();
+        samples.push(match result {
+            Ok(measured) => BenchSample {
+                latency,
+                output_tokens: measured.output_tokens,
+                error: None,
+            },
+            Err(error) => BenchSample {
+                latency,
+                output_tokens: None,
+                error: Some(error.to_string()),
+            },
+        });
+    }
+    samples
+}
+
+/// Nearest-rank percentile of sorted latencies.
+fn percentile(sorted: &[Duration], percent: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    let index = rank.clamp(1, sorted.len()) - 1;
+    Some(sorted[index])
+}
+
+pub fn summarize_bench_samples(model: &str, samples: &[BenchSample]) -> BenchSummary {
+    let successes = samples
+        .iter()
+        .filter(|sample| sample.error.is_none())
+        .collect::<Vec<_>>();
+    let mut latencies = successes
+        .iter()
+        .map(|sample| sample.latency)
+        .collect::<Vec<_>>();
+    latencies.sort();
+
+    let (tokens, seconds) = successes
+        .iter()
+        .filter_map(|sample| {
+            sample
+                .output_tokens
+                .map(|tokens| (tokens, sample.latency.as_secs_f64()))
+        })
+        .fold((0_u64, 0.0_f64), |(tokens, seconds), (t, s)| {
+            (tokens + t, seconds + s)
+        });
+    let tokens_per_second = (seconds > 0.0).then(|| tokens as f64 / seconds);
+
+    BenchSummary {
+        model: model.to_owned(),
+        requests: samples.len(),
+        failures: samples.len() - successes.len(),
+        p50: percentile(&latencies, 50.0),
+        p90: percentile(&latencies, 90.0),
+        p99: percentile(&latencies, 99.0),
+        tokens_per_second,
+    }
+}
+
+pub fn format_bench_table(summaries: &[BenchSummary]) -> String {
+    let model_width = summaries
+        .iter()
+        .map(|summary| summary.model.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("model".len());
+
+    let mut table = format!(
+        "{:<model_width$}  {:>9}  {:>9}  {:>9}  {:>9}  {:>8}\n",
+        "model", "p50", "p90", "p99", "tok/s", "failures"
+    );
+    for summary in summaries {
+        let tokens_per_second = summary
+            .tokens_per_second
+            .map(|rate| format!("{rate:.1}"))
+            .unwrap_or_else(|| "-".to_owned());
+        table.push_str(&format!(
+            "{:<model_width$}  {:>9}  {:>9}  {:>9}  {:>9}  {:>7.0}%\n",
+            summary.model,
+            format_latency(summary.p50),
+            format_latency(summary.p90),
+            format_latency(summary.p99),
+            tokens_per_second,
+            summary.failure_rate() * 100.0
+        ));
+    }
+    table
+}
+
+fn format_latency(latency: Option<Duration>) -> String {
+    latency
+        .map(|latency| format!("{}ms", latency.as_millis()))
+        .unwrap_or_else(|| "-".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{BenchSample, format_bench_table, percentile, summarize_bench_samples};
+
+    fn ok(latency_ms: u64, output_tokens: Option<u64>) -> BenchSample {
+        BenchSample {
+            latency: Duration::from_millis(latency_ms),
+            output_tokens,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let sorted = (1..=10).map(Duration::from_millis).collect::<Vec<_>>();
+
+        assert_eq!(percentile(&sorted, 50.0), Some(Duration::from_millis(5)));
+        assert_eq!(percentile(&sorted, 90.0), Some(Duration::from_millis(9)));
+        assert_eq!(percentile(&sorted, 99.0), Some(Duration::from_millis(10)));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn summarize_bench_samples_excludes_failures_from_latency_and_throughput() {
+        let samples = vec![
+            ok(1_000, Some(50)),
+            ok(3_000, Some(150)),
+            ok(2_000, None),
+            BenchSample {
+                latency: Duration::from_millis(10),
+                output_tokens: None,
+                error: Some("timed out".to_owned()),
+            },
+        ];
+
+        let summary = summarize_bench_samples("llama3", &samples);
+
+        assert_eq!(summary.requests, 4);
+        assert_eq!(summary.failures, 1);
+        assert_eq!(summary.p50, Some(Duration::from_millis(2_000)));
+        assert_eq!(summary.p99, Some(Duration::from_millis(3_000)));
+        assert_eq!(summary.tokens_per_second, Some(50.0));
+        assert!((summary.failure_rate() - 0.25).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn format_bench_table_aligns_one_row_per_model() {
+        let summaries = vec![
+            summarize_bench_samples("qwen2.5:7b", &[ok(120, Some(24))]),
+            summarize_bench_samples("llama3", &[]),
+        ];
+
+        let table = format_bench_table(&summaries);
+        let lines = table.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("model       "));
+        assert!(lines[1].starts_with("qwen2.5:7b  "));
+        assert!(lines[1].contains("120ms"));
+        assert!(lines[1].contains("200.0"));
+        assert!(lines[2].contains("-"));
+    }
+}
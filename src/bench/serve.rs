@@ -0,0 +1,282 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, ensure};
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tracing::warn;
+
+use super::{BENCH_PROMPTS, BenchSample, BenchSummary, format_latency, summarize_bench_samples};
+use crate::config::{AgentSettings, ModelProvider, ModerationMode};
+use crate::server::serve_http_on_listener;
+
+pub const DEFAULT_LOAD_TEST_REQUESTS: u32 = 200;
+pub const DEFAULT_LOAD_TEST_CONCURRENCY: u32 = 16;
+pub const DEFAULT_MOCK_LATENCY_MS: u64 = 50;
+
+const MOCK_MODEL: &str = "bench-mock";
+const MOCK_ANSWER: &str = "This is a canned answer from the bench mock model.";
+const MOCK_EVAL_COUNT: u64 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadTestOptions {
+    pub requests: u32,
+    pub concurrency: u32,
+    /// Delay the mock model waits before answering, standing in for provider latency.
+    pub mock_latency: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadTestReport {
+    pub options: LoadTestOptions,
+    pub elapsed: Duration,
+    pub summary: BenchSummary,
+}
+
+impl LoadTestReport {
+    /// Completed requests per second over the whole run, failures included.
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.summary.requests as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Runs the HTTP server in-process against a mock Ollama endpoint and drives `/chat` with
+/// concurrent requests, so server overhead can be measured without a real provider.
+pub async fn run_load_test_command(
+    settings: &AgentSettings,
+    options: LoadTestOptions,
+) -> Result<()> {
+    ensure!(options.requests > 0, "--requests must be greater than zero");
+    ensure!(
+        options.concurrency > 0,
+        "--concurrency must be greater than zero"
+    );
+
+    let mock_addr = spawn_mock_model(options.mock_latency).await?;
+    let server_settings = load_test_settings(settings, &format!("http://{mock_addr}"));
+    let server_listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .context("failed to bind load-test HTTP server")?;
+    let server_addr = server_listener
+        .local_addr()
+        .context("failed to read load-test HTTP server address")?;
+    tokio::spawn(async move {
+        if let Err(error) =
+            serve_http_on_listener(&server_settings, server_listener, "127.0.0.1:0").await
+        {
+            warn!(error = %error, "load-test HTTP server exited");
+        }
+    });
+
+    println!(
+        "Load testing http://{server_addr}/chat with {} request(s) at concurrency {} (mock model latency {}ms)",
+        options.requests,
+        options.concurrency,
+        options.mock_latency.as_millis()
+    );
+
+    let report = drive_load(&format!("http://{server_addr}/chat"), options).await;
+    print!("{}", format_load_test_report(&report));
+
+    if report.summary.failures == report.summary.requests {
+        return Err(anyhow!("every load-test request failed"));
+    }
+    Ok(())
+}
+
+pub(crate) fn load_test_settings(settings: &AgentSettings, mock_base_url: &str) -> AgentSettings {
+    let mut server_settings = settings.clone();
+    server_settings.model_provider = ModelProvider::Ollama;
+    server_settings.model = MOCK_MODEL.to_owned();
+    server_settings.ollama_base_url = mock_base_url.to_owned();
+    server_settings.model_max_retries = 0;
+    // Keep each turn to exactly one mock model call with no external traffic.
+    server_settings.moderation_mode = ModerationMode::Off;
+    server_settings.response_language = None;
+    server_settings.model_debug_stream_file = None;
+    server_settings
+}
+
+pub(crate) async fn spawn_mock_model(latency: Duration) -> Result<std::net::SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .context("failed to bind mock model endpoint")?;
+    let addr = listener
+        .local_addr()
+        .context("failed to read mock model endpoint address")?;
+    let app = Router::new()
+        .route("/api/chat", post(handle_mock_chat))
+        .with_state(latency);
+    tokio::spawn(async move {
+        if let Err(error) = axum::serve(listener, app).await {
+            warn!(error = %error, "mock model endpoint exited");
+        }
+    });
+    Ok(addr)
+}
+
+async fn handle_mock_chat(
+    State(latency): State<Duration>,
+    Json(_request): Json<Value>,
+) -> Json<Value> {
+    tokio::time::sleep(latency).await;
+    Json(mock_chat_response())
+}
+
+fn mock_chat_response() -> Value {
+    json!({
+        "model": MOCK_MODEL,
+        "message": { "role": "assistant", "content": MOCK_ANSWER },
+        "done": true,
+        "eval_count": MOCK_EVAL_COUNT,
+    })
+}
+
+async fn drive_load(url: &str, options: LoadTestOptions) -> LoadTestReport {
+    let client = reqwest::Client::new();
+    let next_request = Arc::new(AtomicU32::new(0));
+    let started_at = Instant::now();
+
+    let workers = (0..options.concurrency.min(options.requests))
+        .map(|_| {
+            let client = client.clone();
+            let url = url.to_owned();
+            let next_request = Arc::clone(&next_request);
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                loop {
+                    let index = next_request.fetch_add(1, Ordering::Relaxed);
+                    if index >= options.requests {
+                        break;
+                    }
+                    let prompt = BENCH_PROMPTS[index as usize % BENCH_PROMPTS.len()];
+                    samples.push(send_chat_request(&client, &url, prompt).await);
+                }
+                samples
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut samples = Vec::with_capacity(options.requests as usize);
+    for worker in workers {
+        match worker.await {
+            Ok(worker_samples) => samples.extend(worker_samples),
+            Err(error) => warn!(error = %error, "load-test worker panicked"),
+        }
+    }
+
+    LoadTestReport {
+        options,
+        elapsed: started_at.elapsed(),
+        summary: summarize_bench_samples("POST /chat", &samples),
+    }
+}
+
+async fn send_chat_request(client: &reqwest::Client, url: &str, prompt: &str) -> BenchSample {
+    let started_at = Instant::now();
+    let result = client
+        .post(url)
+        .json(&json!({ "message": prompt }))
+        .send()
+        .await;
+    let error = match result {
+        Ok(response) if response.status().is_success() => {
+            // Drain the body so latency covers the full response.
+            response.bytes().await.err().map(|error| error.to_string())
+        }
+        Ok(response) => Some(format!("HTTP {}", response.status())),
+        Err(error) => Some(error.to_string()),
+    };
+    BenchSample {
+        latency: started_at.elapsed(),
+        output_tokens: None,
+        error,
+    }
+}
+
+pub fn format_load_test_report(report: &LoadTestReport) -> String {
+    let summary = &report.summary;
+    format!(
+        "requests {}  concurrency {}  elapsed {:.2}s  throughput {:.1} req/s\n\
+         latency p50 {}  p90 {}  p99 {}\n\
+         failures {} ({:.0}%)\n",
+        summary.requests,
+        report.options.concurrency,
+        report.elapsed.as_secs_f64(),
+        report.throughput(),
+        format_latency(summary.p50),
+        format_latency(summary.p90),
+        format_latency(summary.p99),
+        summary.failures,
+        summary.failure_rate() * 100.0
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        LoadTestOptions, LoadTestReport, format_load_test_report, load_test_settings,
+        mock_chat_response,
+    };
+    use crate::agent::tests::test_settings;
+    use crate::bench::{BenchSample, summarize_bench_samples};
+    use crate::config::{ModelProvider, ModerationMode};
+
+    #[test]
+    fn load_test_settings_route_model_calls_to_mock_endpoint() {
+        let mut settings = test_settings();
+        settings.model_provider = ModelProvider::OpenAi;
+        settings.moderation_mode = ModerationMode::Block;
+
+        let server_settings = load_test_settings(&settings, "http://127.0.0.1:4000");
+
+        assert_eq!(server_settings.model_provider, ModelProvider::Ollama);
+        assert_eq!(server_settings.ollama_base_url, "http://127.0.0.1:4000");
+        assert_eq!(server_settings.model_max_retries, 0);
+        assert_eq!(server_settings.moderation_mode, ModerationMode::Off);
+        assert_eq!(mock_chat_response()["message"]["role"], "assistant");
+    }
+
+    #[test]
+    fn format_load_test_report_includes_throughput_and_failures() {
+        let samples = vec![
+            BenchSample {
+                latency: Duration::from_millis(40),
+                output_tokens: None,
+                error: None,
+            },
+            BenchSample {
+                latency: Duration::from_millis(60),
+                output_tokens: None,
+                error: Some("HTTP 502 Bad Gateway".to_owned()),
+            },
+        ];
+        let report = LoadTestReport {
+            options: LoadTestOptions {
+                requests: 2,
+                concurrency: 2,
+                mock_latency: Duration::from_millis(30),
+            },
+            elapsed: Duration::from_millis(500),
+            summary: summarize_bench_samples("POST /chat", &samples),
+        };
+
+        let text = format_load_test_report(&report);
+
+        assert!((report.throughput() - 4.0).abs() < f64::EPSILON);
+        assert!(text.contains("throughput 4.0 req/s"));
+        assert!(text.contains("latency p50 40ms"));
+        assert!(text.contains("failures 1 (50%)"));
+    }
+}
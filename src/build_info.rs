@@ -0,0 +1,74 @@
+use std::sync::LazyLock;
+
+use serde::Serialize;
+
+/// Package version from `Cargo.toml`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Short commit hash the binary was built from; `unknown` outside a git checkout unless
+/// `MJOLNE_GIT_COMMIT` is set at build time.
+pub const GIT_COMMIT: &str = env!("MJOLNE_GIT_COMMIT");
+/// UTC date (`YYYY-MM-DD`) the build script last ran, or `SOURCE_DATE_EPOCH` when set.
+pub const BUILD_DATE: &str = env!("MJOLNE_BUILD_DATE");
+const FEATURES: &str = env!("MJOLNE_FEATURES");
+
+/// `--version` text: the package version followed by commit, build date, and features.
+pub static LONG_VERSION: LazyLock<String> = LazyLock::new(|| {
+    let features = enabled_features();
+    format!(
+        "{VERSION}\ncommit: {GIT_COMMIT}\nbuilt: {BUILD_DATE}\nfeatures: {}",
+        if features.is_empty() {
+            "none".to_owned()
+        } else {
+            features.join(", ")
+        }
+    )
+});
+
+/// Build details served by `GET /health` and `GET /capabilities`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub git_commit: &'static str,
+    pub build_date: &'static str,
+    pub features: Vec<&'static str>,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        Self {
+            git_commit: GIT_COMMIT,
+            build_date: BUILD_DATE,
+            features: enabled_features(),
+        }
+    }
+}
+
+/// Cargo features enabled for this build, sorted.
+pub fn enabled_features() -> Vec<&'static str> {
+    FEATURES
+        .split(',')
+        .filter(|feature| !feature.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BUILD_DATE, BuildInfo, GIT_COMMIT, LONG_VERSION, VERSION};
+
+    #[test]
+    fn long_version_lists_commit_date_and_features() {
+        let lines = LONG_VERSION.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], VERSION);
+        assert_eq!(lines[1], format!("commit: {GIT_COMMIT}"));
+        assert_eq!(lines[2], format!("built: {BUILD_DATE}"));
+        assert!(lines[3].starts_with("features: "));
+        assert!(!GIT_COMMIT.is_empty());
+        assert!(
+            BUILD_DATE.len() == 10 && BUILD_DATE.as_bytes()[4] == b'-',
+            "{BUILD_DATE}"
+        );
+
+        let value = serde_json::to_value(BuildInfo::current()).expect("build info serializes");
+        assert_eq!(value["git_commit"], GIT_COMMIT);
+        assert!(value["features"].is_array());
+    }
+}
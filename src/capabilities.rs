@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::answer_format::StructuredAnswerFormat;
+use crate::build_info::{BuildInfo, VERSION};
+use crate::config::AgentSettings;
+use crate::guardrails::Guardrails;
+use crate::tr;
+
+/// What this deployment supports, as served by `GET /capabilities` and
+/// `capabilities --json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub build: BuildInfo,
+    pub provider: &'static str,
+    pub model: String,
+    pub tools: Vec<String>,
+    /// `AGENT_OFFLINE`: network tools are refused and the provider is a local Ollama.
+    pub offline: bool,
+    pub limits: CapabilityLimits,
+    pub guardrails: Guardrails,
+    /// Formats the loop detects in prompts and enforces with one reformat request.
+    pub answer_formats: Vec<&'static str>,
+    pub response_language: Option<&'static str>,
+    pub moderation_mode: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CapabilityLimits {
+    pub max_steps: u32,
+    pub max_tool_calls: u32,
+    pub max_tool_calls_per_step: u32,
+    pub max_consecutive_tool_steps: u32,
+    pub max_input_chars: u32,
+    pub max_output_chars: u32,
+    pub tool_timeout_ms: u64,
+    /// Tools with their own deadline instead of `tool_timeout_ms`.
+    pub tool_timeout_overrides_ms: BTreeMap<String, u64>,
+    pub model_timeout_ms: u64,
+    pub fetch_url_max_bytes: u32,
+}
+
+impl Capabilities {
+    pub fn from_settings(settings: &AgentSettings) -> Self {
+        Self {
+            version: VERSION,
+            build: BuildInfo::current(),
+            provider: settings.model_provider.as_str(),
+            model: settings.model.clone(),
+            tools: settings.offered_tools(),
+            offline: settings.offline,
+            limits: CapabilityLimits {
+                max_steps: settings.max_steps,
+                max_tool_calls: settings.max_tool_calls,
+                max_tool_calls_per_step: settings.max_tool_calls_per_step,
+                max_consecutive_tool_steps: settings.max_consecutive_tool_steps,
+                max_input_chars: settings.max_input_chars,
+                max_output_chars: settings.max_output_chars,
+                tool_timeout_ms: settings.tool_timeout_ms,
+                tool_timeout_overrides_ms: settings.tool_timeout_overrides_ms.clone(),
+                model_timeout_ms: settings.model_timeout_ms,
+                fetch_url_max_bytes: settings.fetch_url_max_bytes,
+            },
+            guardrails: Guardrails::from_settings(settings),
+            answer_formats: StructuredAnswerFormat::ALL
+                .iter()
+                .map(|format| format.as_str())
+                .collect(),
+            response_language: settings.response_language.map(|language| language.name()),
+            moderation_mode: settings.moderation_mode.as_str(),
+        }
+    }
+}
+
+/// `capabilities`: prints a short summary, or the full report with `json`.
+pub fn run_capabilities_command(settings: &AgentSettings, json: bool) -> anyhow::Result<()> {
+    let capabilities = Capabilities::from_settings(settings);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&capabilities)?);
+        return Ok(());
+    }
+    println!(
+        "mjolne_vibes {} ({}, {})",
+        capabilities.version, capabilities.build.git_commit, capabilities.build.build_date
+    );
+    println!(
+        "{}",
+        tr!(
+            "capabilities.provider",
+            provider = capabilities.provider,
+            model = capabilities.model
+        )
+    );
+    println!(
+        "{}",
+        tr!("capabilities.tools", tools = capabilities.tools.join(", "))
+    );
+    println!(
+        "{}",
+        tr!(
+            "capabilities.answer_formats",
+            formats = capabilities.answer_formats.join(", ")
+        )
+    );
+    let limits = &capabilities.limits;
+    println!(
+        "{}",
+        tr!(
+            "capabilities.limits",
+            steps = limits.max_steps,
+            tool_calls = limits.max_tool_calls,
+            per_step = limits.max_tool_calls_per_step,
+            input = limits.max_input_chars,
+            output = limits.max_output_chars,
+            tool_timeout = limits.tool_timeout_ms,
+            model_timeout = limits.model_timeout_ms
+        )
+    );
+    let guardrails = &capabilities.guardrails;
+    println!(
+        "{}",
+        tr!(
+            "capabilities.guardrails",
+            domains = guardrails.fetch_url_allowed_domains.join(", "),
+            commands = guardrails.allowed_commands.join(", "),
+            overwrite = guardrails.save_note_allow_overwrite,
+            delete = guardrails.save_note_allow_delete,
+            working_dir = guardrails.tool_working_dir_set
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Capabilities;
+    use crate::agent::tests::test_settings;
+
+    #[test]
+    fn capabilities_report_settings_tools_and_formats() {
+        let mut settings = test_settings();
+        settings.max_steps = 5;
+
+        let value = serde_json::to_value(Capabilities::from_settings(&settings))
+            .expect("capabilities should serialize");
+
+        assert_eq!(value["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(value["build"]["git_commit"], crate::build_info::GIT_COMMIT);
+        assert_eq!(value["provider"], "ollama");
+        assert_eq!(value["model"], settings.model);
+        assert_eq!(
+            value["tools"],
+            json!([
+                "search_notes",
+                "fetch_url",
+                "save_note",
+                "read_note",
+                "delete_note",
+                "read_workspace_file",
+                "search_workspace"
+            ])
+        );
+        assert_eq!(value["offline"], false);
+        assert_eq!(value["limits"]["max_steps"], 5);
+        assert_eq!(value["guardrails"]["save_note_allow_overwrite"], false);
+        assert_eq!(value["guardrails"]["allowed_commands"], json!([]));
+        assert_eq!(
+            value["answer_formats"],
+            json!(["json_object", "markdown_bullets", "csv_table", "tsv_table"])
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use reqwest::{Response, StatusCode, Url};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::api::{
+    ChatRequest, ErrorBody, ExecutedToolCall, ExportFormat, GraphBody, ModerationDecision,
+    TimelineEvent, Transcript, TurnCitation, TurnTraceSummary,
+};
+use crate::graph::ArchitectureGraph;
+
+/// Why a `MjolneClient` call failed.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("invalid server URL `{0}`; expected an http or https base URL")]
+    InvalidUrl(String),
+    #[error("request to the server failed: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// The server answered with an error status; `body.code` is set for failed turns.
+    #[error("server returned HTTP {status}: {}", .body.error)]
+    Api { status: StatusCode, body: ErrorBody },
+    #[error("unexpected response from the server: {0}")]
+    Decode(String),
+}
+
+/// A completed turn: the `POST /chat` body, also carried by the `done` stream event.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChatResponse {
+    pub final_text: String,
+    /// Echoes the request's `session_id`.
+    pub session_id: Option<String>,
+    /// Tool calls in order; long fields are truncated unless the request set `full`.
+    pub tool_calls: Vec<ExecutedToolCall>,
+    pub citations: Vec<TurnCitation>,
+    pub moderation: Option<ModerationDecision>,
+    pub was_repaired_locally: bool,
+    /// The turn trace summary: counts, latencies, context usage, and timeline spans.
+    pub trace: TurnTraceSummary,
+    pub timeline: Vec<TimelineEvent>,
+}
+
+/// One `GET /tools` entry.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RemoteTool {
+    pub name: String,
+    pub signature: String,
+    pub description: String,
+    /// JSON schema of the arguments.
+    pub parameters: Value,
+    /// JSON schema of the result.
+    pub output: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolsBody {
+    tools: Vec<RemoteTool>,
+}
+
+/// One `POST /chat/stream` Server-Sent Event.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum ChatStreamEvent {
+    ToolStarted {
+        tool_call_index: usize,
+        step: u32,
+        tool_name: String,
+        arguments: Value,
+    },
+    ToolProgress {
+        tool_call_index: usize,
+        tool_name: String,
+        elapsed_ms: u64,
+        bytes_received: u64,
+        bytes_total: Option<u64>,
+    },
+    ToolFinished {
+        tool_call_index: usize,
+        tool_name: String,
+        latency_ms: u64,
+        succeeded: bool,
+    },
+    /// The whole final answer, sent once just before `Done`.
+    Answer { text: String },
+    /// The last event of a completed turn.
+    Done(Box<ChatResponse>),
+    /// The last event of a failed turn, with the status `POST /chat` would have returned.
+    Error {
+        status: u16,
+        #[serde(flatten)]
+        body: ErrorBody,
+    },
+}
+
+/// The events of one streamed turn, read as the server sends them.
+#[derive(Debug)]
+pub struct ChatStream {
+    response: Response,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl ChatStream {
+    /// The next event, or `None` once the `done` or `error` event was returned.
+    pub async fn next_event(&mut self) -> Result<Option<ChatStreamEvent>, ClientError> {
+        while !self.finished {
+            if let Some(end) = self.buffer.windows(2).position(|pair| pair == b"\n\n") {
+                let block = self.buffer.drain(..end + 2).collect::<Vec<_>>();
+                let Some(event) = parse_sse_block(&block)? else {
+                    continue;
+                };
+                self.finished = matches!(
+                    event,
+                    ChatStreamEvent::Done(_) | ChatStreamEvent::Error { .. }
+                );
+                return Ok(Some(event));
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk),
+                None => {
+                    return Err(ClientError::Decode(
+                        "chat stream ended before its final event".to_owned(),
+                    ));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the remaining events and returns the turn like `MjolneClient::chat`.
+    pub async fn finish(mut self) -> Result<ChatResponse, ClientError> {
+        while let Some(event) = self.next_event().await? {
+            match event {
+                ChatStreamEvent::Done(response) => return Ok(*response),
+                ChatStreamEvent::Error { status, body } => {
+                    let status = StatusCode::from_u16(status)
+                        .map_err(|error| ClientError::Decode(error.to_string()))?;
+                    return Err(ClientError::Api { status, body });
+                }
+                _ => {}
+            }
+        }
+        Err(ClientError::Decode(
+            "chat stream ended before its final event".to_owned(),
+        ))
+    }
+}
+
+/// An `event:`/`data:` block as a typed event; `None` for keep-alive comments.
+fn parse_sse_block(block: &[u8]) -> Result<Option<ChatStreamEvent>, ClientError> {
+    let block = std::str::from_utf8(block)
+        .map_err(|error| ClientError::Decode(format!("chat stream is not UTF-8: {error}")))?;
+    let mut name = None;
+    let mut data = Vec::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            name = Some(value.trim());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    let Some(name) = name else {
+        return Ok(None);
+    };
+    let data = serde_json::from_str::<Value>(&data.join("\n")).map_err(|error| {
+        ClientError::Decode(format!("`{name}` event data is not JSON: {error}"))
+    })?;
+    serde_json::from_value(json!({ "event": name, "data": data }))
+        .map(Some)
+        .map_err(|error| ClientError::Decode(format!("unexpected `{name}` event: {error}")))
+}
+
+/// Typed async client for the `serve` HTTP API.
+#[derive(Debug, Clone)]
+pub struct MjolneClient {
+    http: reqwest::Client,
+    base_url: Url,
+}
+
+impl MjolneClient {
+    /// Client for the server at `base_url`, such as `http://127.0.0.1:8080`.
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// Sends every request through `http`, for custom timeouts, proxies, or TLS roots.
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Result<Self, ClientError> {
+        let invalid = || ClientError::InvalidUrl(base_url.trim().to_owned());
+        let base_url = Url::parse(base_url.trim()).map_err(|_| invalid())?;
+        if !matches!(base_url.scheme(), "http" | "https") || base_url.cannot_be_a_base() {
+            return Err(invalid());
+        }
+        Ok(Self { http, base_url })
+    }
+
+    /// `POST /chat`: runs one turn and waits for its outcome.
+    pub async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, ClientError> {
+        let response = self
+            .http
+            .post(self.url(&["chat"]))
+            .json(request)
+            .send()
+            .await?;
+        decode_json(response).await
+    }
+
+    /// `POST /chat/stream`: runs one turn and returns its events as they arrive.
+    pub async fn chat_stream(&self, request: &ChatRequest) -> Result<ChatStream, ClientError> {
+        let response = self
+            .http
+            .post(self.url(&["chat", "stream"]))
+            .json(request)
+            .send()
+            .await?;
+        Ok(ChatStream {
+            response: check_status(response).await?,
+            buffer: Vec::new(),
+            finished: false,
+        })
+    }
+
+    /// `GET /sessions/{id}/transcript`: the session's retained history.
+    pub async fn session_transcript(&self, session_id: &str) -> Result<Transcript, ClientError> {
+        let response = self
+            .http
+            .get(self.url(&["sessions", session_id, "transcript"]))
+            .send()
+            .await?;
+        decode_json(response).await
+    }
+
+    /// The same transcript rendered as Markdown, HTML, or JSON text.
+    pub async fn session_transcript_rendered(
+        &self,
+        session_id: &str,
+        format: ExportFormat,
+    ) -> Result<String, ClientError> {
+        let response = self
+            .http
+            .get(self.url(&["sessions", session_id, "transcript"]))
+            .query(&[("format", format.as_str())])
+            .send()
+            .await?;
+        Ok(check_status(response).await?.text().await?)
+    }
+
+    /// `GET /graph`: the served workspace graph, or `None` when it is still at
+    /// `after_revision`. Fails with HTTP 503 before the first build.
+    pub async fn graph(
+        &self,
+        after_revision: Option<u64>,
+    ) -> Result<Option<ArchitectureGraph>, ClientError> {
+        let mut request = self.http.get(self.url(&["graph"]));
+        if let Some(revision) = after_revision {
+            request = request.query(&[("after_revision", revision)]);
+        }
+        let response = request.send().await?;
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+        let body = decode_json::<GraphBody>(response).await?;
+        Ok(Some(body.graph))
+    }
+
+    /// `GET /tools`: the tools the server offers, with their schemas.
+    pub async fn tools(&self) -> Result<Vec<RemoteTool>, ClientError> {
+        let response = self.http.get(self.url(&["tools"])).send().await?;
+        Ok(decode_json::<ToolsBody>(response).await?.tools)
+    }
+
+    /// `segments` appended to the base URL, each percent-encoded.
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("base URL was checked to have a path")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+}
+
+/// `response` when its status is a success, else its error body as `ClientError::Api`.
+async fn check_status(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let text = response.text().await?;
+    // Body extraction failures (malformed JSON, unknown fields) come back as plain text.
+    let body = serde_json::from_str::<ErrorBody>(&text).unwrap_or(ErrorBody::new(text));
+    Err(ClientError::Api { status, body })
+}
+
+async fn decode_json<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    let bytes = check_status(response).await?.bytes().await?;
+    serde_json::from_slice(&bytes).map_err(|error| ClientError::Decode(error.to_string()))
+}
+
+#[cfg(all(test, feature = "runtime"))]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use super::{ChatStreamEvent, ClientError, MjolneClient, parse_sse_block};
+    use crate::agent::tests::test_settings;
+    use crate::bench::{load_test_settings, spawn_mock_model};
+    use crate::export::{ExportFormat, TranscriptRole};
+    use crate::server::{ChatRequest, serve_http_on_listener};
+
+    #[test]
+    fn sse_blocks_decode_to_typed_events() {
+        let answer = parse_sse_block(b"event: answer\ndata: {\"text\":\"hi\"}\n\n").expect("parse");
+        assert_eq!(
+            answer,
+            Some(ChatStreamEvent::Answer {
+                text: "hi".to_owned()
+            })
+        );
+        assert_eq!(parse_sse_block(b":\n\n").expect("keep-alive"), None);
+        let error = parse_sse_block(
+            b"event: error\ndata: {\"error\":\"max steps\",\"code\":\"E_GUARDRAIL_MAX_STEPS\",\"status\":400}\n\n",
+        )
+        .expect("parse");
+        let Some(ChatStreamEvent::Error { status, body }) = error else {
+            panic!("expected an error event, got {error:?}");
+        };
+        assert_eq!(status, 400);
+        assert_eq!(body.code, Some(crate::agent::ErrorCode::GuardrailMaxSteps));
+        assert!(MjolneClient::new("ftp://devbox").is_err());
+    }
+
+    #[tokio::test]
+    async fn client_round_trips_every_endpoint_against_the_server() {
+        let Ok(mock_addr) = spawn_mock_model(Duration::ZERO).await else {
+            eprintln!("skipping: local TCP bind is not permitted in this environment");
+            return;
+        };
+        let settings = load_test_settings(&test_settings(), &format!("http://{mock_addr}"));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("server listener should bind");
+        let server_addr = listener.local_addr().expect("server address");
+        tokio::spawn(async move {
+            let _ = serve_http_on_listener(&settings, listener, "127.0.0.1:0").await;
+        });
+        let client = MjolneClient::new(&format!("http://{server_addr}")).expect("client");
+
+        let tools = client.tools().await.expect("tools");
+        assert!(tools.iter().any(|tool| tool.name == "search_notes"));
+
+        let reply = client
+            .chat(&ChatRequest::new("hello").with_session_id("client-1"))
+            .await
+            .expect("chat");
+        assert!(!reply.final_text.is_empty());
+        assert_eq!(reply.session_id.as_deref(), Some("client-1"));
+        assert!(!reply.timeline.is_empty());
+
+        let mut stream = client
+            .chat_stream(&ChatRequest::new("again").with_session_id("client-1"))
+            .await
+            .expect("stream");
+        let mut events = Vec::new();
+        while let Some(event) = stream.next_event().await.expect("event") {
+            events.push(event);
+        }
+        assert!(matches!(events[0], ChatStreamEvent::Answer { .. }));
+        assert!(matches!(events.last(), Some(ChatStreamEvent::Done(_))));
+
+        let transcript = client
+            .session_transcript("client-1")
+            .await
+            .expect("transcript");
+        let users = transcript
+            .entries
+            .iter()
+            .filter(|entry| entry.role == TranscriptRole::User)
+            .count();
+        assert_eq!(users, 2);
+        let markdown = client
+            .session_transcript_rendered("client-1", ExportFormat::Markdown)
+            .await
+            .expect("markdown transcript");
+        assert!(markdown.contains("again"), "{markdown}");
+
+        let mut rejected = ChatRequest::new("hello");
+        rejected.provider = Some("nope".to_owned());
+        match client.chat(&rejected).await {
+            Err(ClientError::Api { status, body }) => {
+                assert_eq!(status, StatusCode::BAD_REQUEST);
+                assert!(body.error.contains("nope"), "{}", body.error);
+            }
+            other => panic!("expected an API error, got {other:?}"),
+        }
+        match client.session_transcript("missing").await {
+            Err(ClientError::Api { status, .. }) => assert_eq!(status, StatusCode::NOT_FOUND),
+            other => panic!("expected an API error, got {other:?}"),
+        }
+
+        let graph = loop {
+            match client.graph(None).await {
+                Ok(graph) => break graph.expect("a graph without after_revision"),
+                Err(ClientError::Api { status, .. })
+                    if status == StatusCode::SERVICE_UNAVAILABLE =>
+                {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                Err(error) => panic!("graph request failed: {error}"),
+            }
+        };
+        assert!(!graph.nodes.is_empty());
+        assert_eq!(
+            client.graph(Some(graph.revision)).await.expect("graph"),
+            None
+        );
+    }
+}
@@ -1,12 +1,26 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
 
-use anyhow::{Context, Result, anyhow, ensure};
+use anyhow::{Context, Result, anyhow, bail, ensure};
+
+use crate::answer_format::{AnswerPostProcessor, DEFAULT_ANSWER_POST_PROCESSORS, ResponseLanguage};
+use crate::i18n::Locale;
+use crate::tools::{
+    NotesQuota, RUN_COMMAND_TOOL_NAME, all_tool_names, is_network_tool, tool_definitions,
+};
 
 pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
 pub const DEFAULT_OLLAMA_MODEL: &str = "qwen2.5:3b";
 pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4.1-mini";
+pub const DEFAULT_GEMINI_MODEL: &str = "gemini-2.0-flash";
+pub const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+pub const DEFAULT_OLLAMA_EMBEDDING_MODEL: &str = "nomic-embed-text";
+pub const DEFAULT_OPENAI_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+pub const DEFAULT_GEMINI_EMBEDDING_MODEL: &str = "text-embedding-004";
 pub const DEFAULT_MAX_STEPS: u32 = 8;
 pub const DEFAULT_MAX_TOOL_CALLS: u32 = 8;
 pub const DEFAULT_MAX_TOOL_CALLS_PER_STEP: u32 = 4;
@@ -18,14 +32,47 @@ pub const DEFAULT_FETCH_URL_MAX_BYTES: u32 = 100_000;
 pub const DEFAULT_FETCH_URL_FOLLOW_REDIRECTS: bool = false;
 pub const DEFAULT_MODEL_TIMEOUT_MS: u64 = 20_000;
 pub const DEFAULT_MODEL_MAX_RETRIES: u32 = 2;
+pub const DEFAULT_AGENT_OFFLINE: bool = false;
+pub const DEFAULT_MODEL_BATCH_SIZE: u32 = 64;
+pub const DEFAULT_MODEL_BATCH_REQUESTS_PER_MINUTE: u32 = 0;
 pub const DEFAULT_FETCH_URL_ALLOWED_DOMAINS: &str = "example.com";
 pub const DEFAULT_NOTES_DIR: &str = "notes";
 pub const DEFAULT_SAVE_NOTE_ALLOW_OVERWRITE: bool = false;
+pub const DEFAULT_SAVE_NOTE_ALLOW_DELETE: bool = false;
+pub const DEFAULT_NOTES_MAX_FILES: u32 = 10_000;
+pub const DEFAULT_NOTES_MAX_TOTAL_BYTES: u64 = 104_857_600;
+pub const DEFAULT_NOTES_MAX_NOTE_BYTES: u32 = 1_048_576;
+pub const DEFAULT_SESSION_SUMMARY_ENABLED: bool = false;
+pub const DEFAULT_SERVER_SESSION_IDLE_TIMEOUT_MS: u64 = 900_000;
+pub const DEFAULT_SERVER_SESSION_MAX_MESSAGES: u32 = 64;
+pub const DEFAULT_SERVER_MAX_SESSIONS: u32 = 256;
+pub const DEFAULT_SERVER_WEBHOOK_MAX_RETRIES: u32 = 3;
+pub const DEFAULT_SERVER_WEBHOOK_TIMEOUT_MS: u64 = 5_000;
+pub const DEFAULT_STUDIO_MAX_PARALLEL_TURNS: u32 = 1;
+pub const MAX_STUDIO_PARALLEL_TURNS: u32 = 4;
+pub const DEFAULT_STUDIO_SNAPSHOT_MEMORY_BUDGET_MB: u32 = 64;
+pub const DEFAULT_TOOL_ENV_ALLOWLIST: &str = "PATH";
+pub const DEFAULT_READ_WORKSPACE_FILE_EXTENSIONS: &str = "rs,toml,md,txt,json,yaml,yml";
+pub const DEFAULT_READ_WORKSPACE_FILE_MAX_BYTES: u32 = 65_536;
+pub const DEFAULT_SEARCH_WORKSPACE_MAX_RESULTS: u32 = 50;
+pub const DEFAULT_SEARCH_WORKSPACE_MAX_BYTES: u32 = 4_194_304;
+pub const DEFAULT_RUN_COMMAND_MAX_OUTPUT_BYTES: u32 = 16_384;
+pub const DEFAULT_INJECTION_DETECTION_ENABLED: bool = true;
+pub const DEFAULT_JSON_OUTPUT_MAX_FIELD_CHARS: u32 = 4_000;
+pub const DEFAULT_CONTEXT_BUDGET_TOKENS: u32 = 8_192;
+pub const DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST: u32 = 32;
+pub const DEFAULT_HTTP_POOL_IDLE_TIMEOUT_MS: u64 = 90_000;
+pub const DEFAULT_HTTP_TCP_KEEPALIVE_MS: u64 = 60_000;
+pub const DEFAULT_UPDATE_CHECK_URL: &str =
+    "https://api.github.com/repos/Skarsh/mjolne_vibes/releases/latest";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ModelProvider {
     Ollama,
     OpenAi,
+    Gemini,
+    /// Any server speaking the OpenAI chat completions format at `OPENAI_COMPAT_BASE_URL`.
+    Compatible,
 }
 
 impl ModelProvider {
@@ -33,13 +80,27 @@ impl ModelProvider {
         match self {
             Self::Ollama => "ollama",
             Self::OpenAi => "openai",
+            Self::Gemini => "gemini",
+            Self::Compatible => "compatible",
+        }
+    }
+
+    /// `None` for compatible servers, whose model names only the user knows.
+    pub fn default_model(self) -> Option<&'static str> {
+        match self {
+            Self::Ollama => Some(DEFAULT_OLLAMA_MODEL),
+            Self::OpenAi => Some(DEFAULT_OPENAI_MODEL),
+            Self::Gemini => Some(DEFAULT_GEMINI_MODEL),
+            Self::Compatible => None,
         }
     }
 
-    pub fn default_model(self) -> &'static str {
+    pub fn default_embedding_model(self) -> Option<&'static str> {
         match self {
-            Self::Ollama => DEFAULT_OLLAMA_MODEL,
-            Self::OpenAi => DEFAULT_OPENAI_MODEL,
+            Self::Ollama => Some(DEFAULT_OLLAMA_EMBEDDING_MODEL),
+            Self::OpenAi => Some(DEFAULT_OPENAI_EMBEDDING_MODEL),
+            Self::Gemini => Some(DEFAULT_GEMINI_EMBEDDING_MODEL),
+            Self::Compatible => None,
         }
     }
 }
@@ -57,19 +118,91 @@ impl FromStr for ModelProvider {
         match value.trim().to_ascii_lowercase().as_str() {
             "ollama" => Ok(Self::Ollama),
             "openai" => Ok(Self::OpenAi),
+            "gemini" => Ok(Self::Gemini),
+            "compatible" | "openai-compatible" => Ok(Self::Compatible),
             other => Err(anyhow!(
-                "invalid MODEL_PROVIDER `{other}`; expected `ollama` or `openai`"
+                "invalid MODEL_PROVIDER `{other}`; expected `ollama`, `openai`, `gemini`, or `compatible`"
             )),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationMode {
+    Off,
+    Flag,
+    Redact,
+    Block,
+}
+
+impl ModerationMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Flag => "flag",
+            Self::Redact => "redact",
+            Self::Block => "block",
+        }
+    }
+}
+
+impl FromStr for ModerationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "flag" => Ok(Self::Flag),
+            "redact" => Ok(Self::Redact),
+            "block" => Ok(Self::Block),
+            other => Err(anyhow!(
+                "invalid MODERATION_MODE `{other}`; expected `off`, `flag`, `redact`, or `block`"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationProvider {
+    Local,
+    OpenAi,
+}
+
+impl ModerationProvider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::OpenAi => "openai",
+        }
+    }
+}
+
+impl FromStr for ModerationProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "openai" => Ok(Self::OpenAi),
+            other => Err(anyhow!(
+                "invalid MODERATION_PROVIDER `{other}`; expected `local` or `openai`"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct AgentSettings {
     pub model_provider: ModelProvider,
     pub model: String,
     pub ollama_base_url: String,
     pub openai_api_key: Option<String>,
+    pub gemini_api_key: Option<String>,
+    pub gemini_base_url: String,
+    pub openai_compat_base_url: Option<String>,
+    pub openai_compat_api_key: Option<String>,
+    /// `AGENT_OFFLINE`: network tools are refused and the provider must be a local Ollama.
+    pub offline: bool,
     pub max_steps: u32,
     pub max_tool_calls: u32,
     pub max_tool_calls_per_step: u32,
@@ -77,14 +210,136 @@ pub struct AgentSettings {
     pub max_input_chars: u32,
     pub max_output_chars: u32,
     pub tool_timeout_ms: u64,
+    /// Per-tool deadlines from `<TOOL_NAME>_TIMEOUT_MS`, keyed by tool name; other tools
+    /// use `tool_timeout_ms`.
+    pub tool_timeout_overrides_ms: BTreeMap<String, u64>,
     pub fetch_url_max_bytes: u32,
     pub fetch_url_follow_redirects: bool,
     pub fetch_url_allowed_domains: Vec<String>,
+    /// Tools offered to the model and allowed to run; defaults to every registered tool.
+    pub enabled_tools: Vec<String>,
     pub notes_dir: String,
     pub save_note_allow_overwrite: bool,
+    /// Lets `delete_note` remove notes; each call must still pass `confirm: true`.
+    pub save_note_allow_delete: bool,
+    /// Most note files `save_note` may leave in `notes_dir`.
+    pub notes_max_files: u32,
+    /// Most bytes all notes in `notes_dir` may take after a `save_note` write.
+    pub notes_max_total_bytes: u64,
+    /// Most bytes one note may have after a `save_note` write, appends included.
+    pub notes_max_note_bytes: u32,
     pub model_timeout_ms: u64,
     pub model_max_retries: u32,
+    /// Model `ModelClient::embed` sends inputs to; defaults per provider.
+    pub embedding_model: String,
+    /// Most inputs sent in one batched provider request.
+    pub model_batch_size: u32,
+    /// Pace for consecutive batched requests; 0 sends them back to back.
+    pub model_batch_requests_per_minute: u32,
     pub studio_subsystem_rules_file: Option<String>,
+    pub graph_dependency_rules_file: Option<String>,
+    pub session_summary_enabled: bool,
+    /// Directory finished REPL, HTTP, and studio sessions are archived to as JSON
+    /// transcripts; unset archives none.
+    pub session_archive_dir: Option<String>,
+    /// JSON Lines ledger of completed `chat`, `repl`, and studio turns; unset records none.
+    pub history_file: Option<String>,
+    /// HMAC-SHA256 key signing and chaining each ledger entry; unsigned when unset.
+    pub history_signing_key: Option<String>,
+    pub server_session_idle_timeout_ms: u64,
+    pub server_session_max_messages: u32,
+    pub server_max_sessions: u32,
+    /// Endpoints that receive a JSON notification when a `serve` chat turn ends.
+    pub server_webhook_urls: Vec<String>,
+    /// HMAC-SHA256 key for the `X-Mjolne-Signature` header; unsigned when unset.
+    pub server_webhook_secret: Option<String>,
+    pub server_webhook_max_retries: u32,
+    pub server_webhook_timeout_ms: u64,
+    /// JSON file of cron-scheduled prompts that `serve` runs in the background.
+    pub server_schedule_file: Option<String>,
+    /// Bearer token for `serve` admin endpoints; they are disabled while unset.
+    pub server_admin_token: Option<String>,
+    /// `provider/model` pairs a `/chat` turn may switch to without the admin token.
+    pub server_chat_model_allowlist: Vec<String>,
+    /// Env-format file `PATCH /admin/policy` persists to; applied over the environment
+    /// when `serve` starts.
+    pub server_policy_file: Option<String>,
+    /// JSONL file receiving one audit entry per `PATCH /admin/policy` change.
+    pub server_policy_audit_file: Option<String>,
+    pub studio_max_parallel_turns: u32,
+    pub studio_snapshot_memory_budget_mb: u32,
+    pub studio_snapshot_spill_dir: Option<String>,
+    /// Starts studio with the frame-time profiling overlay shown (`F12` toggles it).
+    pub studio_profile_overlay: bool,
+    /// Gives each studio session its own notes subdirectory (`STUDIO_SESSION_NOTES`).
+    pub studio_session_notes: bool,
+    pub tool_working_dir: Option<String>,
+    pub tool_env_allowlist: Vec<String>,
+    /// Root `read_workspace_file` is confined to. Not read from the environment: `studio
+    /// --workspace` sets it; otherwise the tool working directory is used.
+    pub workspace_root: Option<PathBuf>,
+    /// Extensions (lowercase, without the dot) `read_workspace_file` may read.
+    pub read_workspace_file_extensions: Vec<String>,
+    /// Most bytes one `read_workspace_file` call returns, whatever `max_bytes` asks for.
+    pub read_workspace_file_max_bytes: u32,
+    /// Most matches one `search_workspace` call returns, whatever `max_results` asks for.
+    pub search_workspace_max_results: u32,
+    /// File bytes one `search_workspace` call scans before it stops and reports `truncated`.
+    pub search_workspace_max_bytes: u32,
+    /// Programs `run_command` may start, by bare name (`TOOL_ALLOWED_COMMANDS`); empty keeps
+    /// the tool from being offered at all.
+    pub tool_allowed_commands: Vec<String>,
+    /// Bytes of stdout and of stderr one `run_command` call returns.
+    pub run_command_max_output_bytes: u32,
+    pub injection_detection_enabled: bool,
+    /// Run the tool calls of one model step concurrently when all of them are read-only.
+    pub parallel_tool_calls: bool,
+    pub moderation_mode: ModerationMode,
+    pub moderation_provider: ModerationProvider,
+    pub moderation_keywords: Vec<String>,
+    pub moderation_patterns: Vec<String>,
+    pub response_language: Option<ResponseLanguage>,
+    /// Language of CLI, REPL, and studio strings (`MJOLNE_LANG`).
+    pub ui_locale: Locale,
+    pub model_debug_stream_file: Option<String>,
+    pub editor_command: Option<String>,
+    pub model_cache_dir: Option<String>,
+    /// Model used only for the format-repair call; defaults to `model`.
+    pub format_repair_model: Option<String>,
+    /// Sampling temperature used only for the format-repair call.
+    pub format_repair_temperature: Option<f32>,
+    /// Transforms applied in order to every final answer.
+    pub answer_post_processors: Vec<AnswerPostProcessor>,
+    /// Accept a requested JSON object wrapped in one ``` fence instead of asking for a reformat.
+    pub json_fence_tolerance: bool,
+    /// Longest tool-call field kept in `chat --json` and `POST /chat` bodies unless full
+    /// output is requested.
+    pub json_output_max_field_chars: u32,
+    /// Latest-release endpoint queried by `update check`.
+    pub update_check_url: String,
+    /// Run the update check in the background when `serve` or `studio` starts.
+    pub update_check_on_start: bool,
+    /// Page REPL answers taller than the terminal; `/pager on|off` toggles it per session.
+    pub repl_pager: bool,
+    /// Approximate tokens the model's context holds; the REPL prompt and studio top bar
+    /// compare conversation size against it.
+    pub context_budget_tokens: u32,
+    /// Approximate tokens above which chat sessions drop their oldest turns; 0 keeps
+    /// every turn.
+    pub max_context_tokens: u32,
+    /// Approximate tokens above which chat sessions summarize their earlier turns before
+    /// the next one; 0 never compacts automatically.
+    pub auto_compact_tokens: u32,
+    /// Idle connections the shared HTTP client keeps per host.
+    pub http_pool_max_idle_per_host: u32,
+    pub http_pool_idle_timeout_ms: u64,
+    /// TCP keep-alive probe interval; 0 disables probes.
+    pub http_tcp_keepalive_ms: u64,
+    /// Offer HTTP/2 to `https` servers; off forces HTTP/1.1.
+    pub http2_enabled: bool,
+    /// Proxy for model and `fetch_url` requests; when unset, `HTTPS_PROXY`/`HTTP_PROXY`
+    /// apply as usual.
+    pub http_proxy_url: Option<String>,
 }
 
 impl AgentSettings {
@@ -97,7 +352,11 @@ impl AgentSettings {
             .parse::<ModelProvider>()
             .context("failed to parse MODEL_PROVIDER")?;
 
-        let model = env::var("MODEL").unwrap_or_else(|_| model_provider.default_model().to_owned());
+        let model = match (env::var("MODEL"), model_provider.default_model()) {
+            (Ok(model), _) => model,
+            (Err(_), Some(default_model)) => default_model.to_owned(),
+            (Err(_), None) => bail!("MODEL must be set when MODEL_PROVIDER is `{model_provider}`"),
+        };
         ensure!(!model.trim().is_empty(), "MODEL cannot be empty");
 
         let ollama_base_url =
@@ -108,6 +367,7 @@ impl AgentSettings {
         );
 
         let openai_api_key = read_optional_env("OPENAI_API_KEY");
+        let offline = parse_bool_env("AGENT_OFFLINE", DEFAULT_AGENT_OFFLINE)?;
         if model_provider == ModelProvider::OpenAi {
             let has_key = openai_api_key
                 .as_deref()
@@ -119,6 +379,33 @@ impl AgentSettings {
             );
         }
 
+        let gemini_api_key = read_optional_env("GEMINI_API_KEY");
+        if model_provider == ModelProvider::Gemini {
+            let has_key = gemini_api_key
+                .as_deref()
+                .map(|key| !key.trim().is_empty())
+                .unwrap_or(false);
+            ensure!(
+                has_key,
+                "GEMINI_API_KEY must be set when MODEL_PROVIDER is `gemini`"
+            );
+        }
+        let gemini_base_url =
+            env::var("GEMINI_BASE_URL").unwrap_or_else(|_| DEFAULT_GEMINI_BASE_URL.to_owned());
+        ensure!(
+            !gemini_base_url.trim().is_empty(),
+            "GEMINI_BASE_URL cannot be empty"
+        );
+
+        let openai_compat_base_url = read_optional_env("OPENAI_COMPAT_BASE_URL")
+            .map(|raw| parse_http_url("OPENAI_COMPAT_BASE_URL", raw.trim()))
+            .transpose()?;
+        ensure!(
+            model_provider != ModelProvider::Compatible || openai_compat_base_url.is_some(),
+            "OPENAI_COMPAT_BASE_URL must be set when MODEL_PROVIDER is `compatible`"
+        );
+        let openai_compat_api_key = read_optional_env("OPENAI_COMPAT_API_KEY");
+
         let max_steps = parse_positive_u32_env("AGENT_MAX_STEPS", DEFAULT_MAX_STEPS)?;
         let max_tool_calls =
             parse_positive_u32_env("AGENT_MAX_TOOL_CALLS", DEFAULT_MAX_TOOL_CALLS)?;
@@ -136,6 +423,7 @@ impl AgentSettings {
             parse_positive_u32_env("AGENT_MAX_OUTPUT_CHARS", DEFAULT_MAX_OUTPUT_CHARS)?;
 
         let tool_timeout_ms = parse_positive_u64_env("TOOL_TIMEOUT_MS", DEFAULT_TOOL_TIMEOUT_MS)?;
+        let tool_timeout_overrides_ms = parse_tool_timeout_overrides()?;
         let fetch_url_max_bytes =
             parse_positive_u32_env("FETCH_URL_MAX_BYTES", DEFAULT_FETCH_URL_MAX_BYTES)?;
         let fetch_url_follow_redirects = parse_bool_env(
@@ -148,24 +436,238 @@ impl AgentSettings {
             &env::var("FETCH_URL_ALLOWED_DOMAINS")
                 .unwrap_or_else(|_| DEFAULT_FETCH_URL_ALLOWED_DOMAINS.to_owned()),
         )?;
+        let enabled_tools = match read_optional_env("ENABLED_TOOLS") {
+            Some(raw) => parse_enabled_tools("ENABLED_TOOLS", &raw)?,
+            None => all_tool_names(),
+        };
         let notes_dir = env::var("NOTES_DIR").unwrap_or_else(|_| DEFAULT_NOTES_DIR.to_owned());
         ensure!(!notes_dir.trim().is_empty(), "NOTES_DIR cannot be empty");
         let save_note_allow_overwrite = parse_bool_env(
             "SAVE_NOTE_ALLOW_OVERWRITE",
             DEFAULT_SAVE_NOTE_ALLOW_OVERWRITE,
         )?;
+        let save_note_allow_delete =
+            parse_bool_env("SAVE_NOTE_ALLOW_DELETE", DEFAULT_SAVE_NOTE_ALLOW_DELETE)?;
+        let notes_max_files = parse_positive_u32_env("NOTES_MAX_FILES", DEFAULT_NOTES_MAX_FILES)?;
+        let notes_max_total_bytes =
+            parse_positive_u64_env("NOTES_MAX_TOTAL_BYTES", DEFAULT_NOTES_MAX_TOTAL_BYTES)?;
+        let notes_max_note_bytes =
+            parse_positive_u32_env("NOTES_MAX_NOTE_BYTES", DEFAULT_NOTES_MAX_NOTE_BYTES)?;
 
         let model_timeout_ms =
             parse_positive_u64_env("MODEL_TIMEOUT_MS", DEFAULT_MODEL_TIMEOUT_MS)?;
 
         let model_max_retries = parse_u32_env("MODEL_MAX_RETRIES", DEFAULT_MODEL_MAX_RETRIES)?;
+        // Compatible servers have no known embedding model; the chat model is the guess.
+        let embedding_model = env::var("EMBEDDING_MODEL").unwrap_or_else(|_| {
+            model_provider
+                .default_embedding_model()
+                .unwrap_or(&model)
+                .to_owned()
+        });
+        ensure!(
+            !embedding_model.trim().is_empty(),
+            "EMBEDDING_MODEL cannot be empty"
+        );
+        let model_batch_size =
+            parse_positive_u32_env("MODEL_BATCH_SIZE", DEFAULT_MODEL_BATCH_SIZE)?;
+        let model_batch_requests_per_minute = parse_u32_env(
+            "MODEL_BATCH_REQUESTS_PER_MINUTE",
+            DEFAULT_MODEL_BATCH_REQUESTS_PER_MINUTE,
+        )?;
         let studio_subsystem_rules_file = read_optional_env("STUDIO_SUBSYSTEM_RULES_FILE");
+        let graph_dependency_rules_file = read_optional_env("GRAPH_DEPENDENCY_RULES_FILE");
+        let session_summary_enabled =
+            parse_bool_env("AGENT_SESSION_SUMMARY", DEFAULT_SESSION_SUMMARY_ENABLED)?;
+        let session_archive_dir = read_optional_env("SESSION_ARCHIVE_DIR");
+        let history_file = read_optional_env("AGENT_HISTORY_FILE");
+        let history_signing_key = read_optional_env("AGENT_HISTORY_SIGNING_KEY");
+        let server_session_idle_timeout_ms = parse_positive_u64_env(
+            "SERVER_SESSION_IDLE_TIMEOUT_MS",
+            DEFAULT_SERVER_SESSION_IDLE_TIMEOUT_MS,
+        )?;
+        let server_session_max_messages = parse_positive_u32_env(
+            "SERVER_SESSION_MAX_MESSAGES",
+            DEFAULT_SERVER_SESSION_MAX_MESSAGES,
+        )?;
+        let server_max_sessions =
+            parse_positive_u32_env("SERVER_MAX_SESSIONS", DEFAULT_SERVER_MAX_SESSIONS)?;
+        let server_webhook_urls = read_optional_env("SERVER_WEBHOOK_URLS")
+            .map(|raw| parse_webhook_urls("SERVER_WEBHOOK_URLS", &raw))
+            .transpose()?
+            .unwrap_or_default();
+        let server_webhook_secret = read_optional_env("SERVER_WEBHOOK_SECRET");
+        let server_webhook_max_retries = parse_u32_env(
+            "SERVER_WEBHOOK_MAX_RETRIES",
+            DEFAULT_SERVER_WEBHOOK_MAX_RETRIES,
+        )?;
+        let server_webhook_timeout_ms = parse_positive_u64_env(
+            "SERVER_WEBHOOK_TIMEOUT_MS",
+            DEFAULT_SERVER_WEBHOOK_TIMEOUT_MS,
+        )?;
+        let server_schedule_file = read_optional_env("SERVER_SCHEDULE_FILE");
+        let server_admin_token = read_optional_env("SERVER_ADMIN_TOKEN");
+        let server_chat_model_allowlist = parse_chat_model_allowlist(
+            "SERVER_CHAT_MODEL_ALLOWLIST",
+            &env::var("SERVER_CHAT_MODEL_ALLOWLIST").unwrap_or_default(),
+        )?;
+        let server_policy_file = read_optional_env("SERVER_POLICY_FILE");
+        let server_policy_audit_file = read_optional_env("SERVER_POLICY_AUDIT_FILE");
+        let studio_max_parallel_turns = parse_positive_u32_env(
+            "STUDIO_MAX_PARALLEL_TURNS",
+            DEFAULT_STUDIO_MAX_PARALLEL_TURNS,
+        )?;
+        ensure!(
+            studio_max_parallel_turns <= MAX_STUDIO_PARALLEL_TURNS,
+            "STUDIO_MAX_PARALLEL_TURNS must be at most {MAX_STUDIO_PARALLEL_TURNS}"
+        );
+        let studio_snapshot_memory_budget_mb = parse_positive_u32_env(
+            "STUDIO_SNAPSHOT_MEMORY_MB",
+            DEFAULT_STUDIO_SNAPSHOT_MEMORY_BUDGET_MB,
+        )?;
+        let studio_snapshot_spill_dir = read_optional_env("STUDIO_SNAPSHOT_SPILL_DIR");
+        let studio_profile_overlay = parse_bool_env("STUDIO_PROFILE_OVERLAY", false)?;
+        let studio_session_notes = parse_bool_env("STUDIO_SESSION_NOTES", false)?;
+        let tool_working_dir = read_optional_env("TOOL_WORKING_DIR");
+        if let Some(dir) = &tool_working_dir {
+            ensure!(
+                std::path::Path::new(dir).is_dir(),
+                "TOOL_WORKING_DIR `{dir}` is not an existing directory"
+            );
+        }
+        let tool_env_allowlist = parse_env_name_list(
+            "TOOL_ENV_ALLOWLIST",
+            &env::var("TOOL_ENV_ALLOWLIST")
+                .unwrap_or_else(|_| DEFAULT_TOOL_ENV_ALLOWLIST.to_owned()),
+        )?;
+        let read_workspace_file_extensions = parse_extension_list(
+            "READ_WORKSPACE_FILE_EXTENSIONS",
+            &env::var("READ_WORKSPACE_FILE_EXTENSIONS")
+                .unwrap_or_else(|_| DEFAULT_READ_WORKSPACE_FILE_EXTENSIONS.to_owned()),
+        )?;
+        let read_workspace_file_max_bytes = parse_positive_u32_env(
+            "READ_WORKSPACE_FILE_MAX_BYTES",
+            DEFAULT_READ_WORKSPACE_FILE_MAX_BYTES,
+        )?;
+        let search_workspace_max_results = parse_positive_u32_env(
+            "SEARCH_WORKSPACE_MAX_RESULTS",
+            DEFAULT_SEARCH_WORKSPACE_MAX_RESULTS,
+        )?;
+        let search_workspace_max_bytes = parse_positive_u32_env(
+            "SEARCH_WORKSPACE_MAX_BYTES",
+            DEFAULT_SEARCH_WORKSPACE_MAX_BYTES,
+        )?;
+        let tool_allowed_commands = parse_command_allowlist(
+            "TOOL_ALLOWED_COMMANDS",
+            &env::var("TOOL_ALLOWED_COMMANDS").unwrap_or_default(),
+        )?;
+        let run_command_max_output_bytes = parse_positive_u32_env(
+            "RUN_COMMAND_MAX_OUTPUT_BYTES",
+            DEFAULT_RUN_COMMAND_MAX_OUTPUT_BYTES,
+        )?;
+        let injection_detection_enabled = parse_bool_env(
+            "AGENT_INJECTION_DETECTION",
+            DEFAULT_INJECTION_DETECTION_ENABLED,
+        )?;
+        let parallel_tool_calls = parse_bool_env("AGENT_PARALLEL_TOOL_CALLS", false)?;
+        let moderation_mode = env::var("MODERATION_MODE")
+            .unwrap_or_else(|_| ModerationMode::Off.as_str().to_owned())
+            .parse::<ModerationMode>()
+            .context("failed to parse MODERATION_MODE")?;
+        let moderation_provider = env::var("MODERATION_PROVIDER")
+            .unwrap_or_else(|_| ModerationProvider::Local.as_str().to_owned())
+            .parse::<ModerationProvider>()
+            .context("failed to parse MODERATION_PROVIDER")?;
+        if offline {
+            let model_base_url = match model_provider {
+                ModelProvider::Compatible => openai_compat_base_url.as_deref().unwrap_or_default(),
+                _ => &ollama_base_url,
+            };
+            validate_offline_providers(
+                model_provider,
+                model_base_url,
+                moderation_mode,
+                moderation_provider,
+            )?;
+        }
+        if moderation_mode != ModerationMode::Off
+            && moderation_provider == ModerationProvider::OpenAi
+        {
+            ensure!(
+                openai_api_key.is_some(),
+                "OPENAI_API_KEY must be set when MODERATION_PROVIDER is `openai`"
+            );
+        }
+        let moderation_keywords = read_optional_env("MODERATION_KEYWORDS")
+            .map(|raw| parse_moderation_keywords(&raw))
+            .unwrap_or_default();
+        let moderation_patterns = match read_optional_env("MODERATION_PATTERNS_FILE") {
+            Some(path) => {
+                let raw = std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read MODERATION_PATTERNS_FILE `{path}`"))?;
+                parse_moderation_patterns(&raw)
+                    .with_context(|| format!("invalid MODERATION_PATTERNS_FILE `{path}`"))?
+            }
+            None => Vec::new(),
+        };
+        let response_language = read_optional_env("AGENT_RESPONSE_LANGUAGE")
+            .map(|raw| raw.parse::<ResponseLanguage>())
+            .transpose()
+            .context("failed to parse AGENT_RESPONSE_LANGUAGE")?;
+        let ui_locale = read_optional_env("MJOLNE_LANG")
+            .map(|raw| raw.parse::<Locale>())
+            .transpose()?
+            .unwrap_or_default();
+        let model_debug_stream_file = read_optional_env("MODEL_DEBUG_STREAM_FILE");
+        let editor_command = read_optional_env("EDITOR_COMMAND");
+        let model_cache_dir = read_optional_env("MODEL_CACHE_DIR");
+        let format_repair_model = read_optional_env("FORMAT_REPAIR_MODEL");
+        let format_repair_temperature = read_optional_env("FORMAT_REPAIR_TEMPERATURE")
+            .map(|raw| parse_temperature_value("FORMAT_REPAIR_TEMPERATURE", &raw))
+            .transpose()?;
+        let answer_post_processors = match read_optional_env("ANSWER_POST_PROCESSORS") {
+            Some(raw) => parse_answer_post_processors(&raw)
+                .context("failed to parse ANSWER_POST_PROCESSORS")?,
+            None => DEFAULT_ANSWER_POST_PROCESSORS.to_vec(),
+        };
+        let json_fence_tolerance = parse_bool_env("ANSWER_JSON_FENCE_TOLERANCE", true)?;
+        let json_output_max_field_chars = parse_positive_u32_env(
+            "JSON_OUTPUT_MAX_FIELD_CHARS",
+            DEFAULT_JSON_OUTPUT_MAX_FIELD_CHARS,
+        )?;
+        let update_check_url = read_optional_env("UPDATE_CHECK_URL")
+            .unwrap_or_else(|| DEFAULT_UPDATE_CHECK_URL.to_owned());
+        let update_check_on_start = parse_bool_env("UPDATE_CHECK_ON_START", false)?;
+        let repl_pager = parse_bool_env("REPL_PAGER", true)?;
+        let context_budget_tokens =
+            parse_positive_u32_env("AGENT_CONTEXT_BUDGET_TOKENS", DEFAULT_CONTEXT_BUDGET_TOKENS)?;
+        let max_context_tokens = parse_u32_env("AGENT_MAX_CONTEXT_TOKENS", 0)?;
+        let auto_compact_tokens = parse_u32_env("AGENT_AUTO_COMPACT", 0)?;
+        let http_pool_max_idle_per_host = parse_u32_env(
+            "HTTP_POOL_MAX_IDLE_PER_HOST",
+            DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST,
+        )?;
+        let http_pool_idle_timeout_ms = parse_positive_u64_env(
+            "HTTP_POOL_IDLE_TIMEOUT_MS",
+            DEFAULT_HTTP_POOL_IDLE_TIMEOUT_MS,
+        )?;
+        let http_tcp_keepalive_ms =
+            parse_u64_env("HTTP_TCP_KEEPALIVE_MS", DEFAULT_HTTP_TCP_KEEPALIVE_MS)?;
+        let http2_enabled = parse_bool_env("HTTP2_ENABLED", true)?;
+        let http_proxy_url = read_optional_env("HTTP_PROXY_URL")
+            .map(|raw| parse_http_url("HTTP_PROXY_URL", &raw))
+            .transpose()?;
 
         Ok(Self {
             model_provider,
             model,
             ollama_base_url,
             openai_api_key,
+            gemini_api_key,
+            gemini_base_url,
+            openai_compat_base_url,
+            openai_compat_api_key,
+            offline,
             max_steps,
             max_tool_calls,
             max_tool_calls_per_step,
@@ -173,18 +675,330 @@ impl AgentSettings {
             max_input_chars,
             max_output_chars,
             tool_timeout_ms,
+            tool_timeout_overrides_ms,
             fetch_url_max_bytes,
             fetch_url_follow_redirects,
             fetch_url_allowed_domains,
+            enabled_tools,
             notes_dir,
             save_note_allow_overwrite,
+            save_note_allow_delete,
+            notes_max_files,
+            notes_max_total_bytes,
+            notes_max_note_bytes,
             model_timeout_ms,
             model_max_retries,
+            embedding_model,
+            model_batch_size,
+            model_batch_requests_per_minute,
             studio_subsystem_rules_file,
+            graph_dependency_rules_file,
+            session_summary_enabled,
+            session_archive_dir,
+            history_file,
+            history_signing_key,
+            server_session_idle_timeout_ms,
+            server_session_max_messages,
+            server_max_sessions,
+            server_webhook_urls,
+            server_webhook_secret,
+            server_webhook_max_retries,
+            server_webhook_timeout_ms,
+            server_schedule_file,
+            server_admin_token,
+            server_chat_model_allowlist,
+            server_policy_file,
+            server_policy_audit_file,
+            studio_max_parallel_turns,
+            studio_snapshot_memory_budget_mb,
+            studio_snapshot_spill_dir,
+            studio_profile_overlay,
+            studio_session_notes,
+            tool_working_dir,
+            workspace_root: None,
+            read_workspace_file_extensions,
+            read_workspace_file_max_bytes,
+            search_workspace_max_results,
+            search_workspace_max_bytes,
+            tool_allowed_commands,
+            run_command_max_output_bytes,
+            tool_env_allowlist,
+            injection_detection_enabled,
+            parallel_tool_calls,
+            moderation_mode,
+            moderation_provider,
+            moderation_keywords,
+            moderation_patterns,
+            response_language,
+            ui_locale,
+            model_debug_stream_file,
+            editor_command,
+            model_cache_dir,
+            format_repair_model,
+            format_repair_temperature,
+            answer_post_processors,
+            json_fence_tolerance,
+            json_output_max_field_chars,
+            update_check_url,
+            update_check_on_start,
+            repl_pager,
+            context_budget_tokens,
+            max_context_tokens,
+            auto_compact_tokens,
+            http_pool_max_idle_per_host,
+            http_pool_idle_timeout_ms,
+            http_tcp_keepalive_ms,
+            http2_enabled,
+            http_proxy_url,
+        })
+    }
+
+    /// `NOTES_MAX_*` limits `save_note` enforces.
+    pub fn notes_quota(&self) -> NotesQuota {
+        NotesQuota {
+            max_files: self.notes_max_files as usize,
+            max_total_bytes: self.notes_max_total_bytes,
+            max_note_bytes: self.notes_max_note_bytes as usize,
+        }
+    }
+
+    /// `ENABLED_TOOLS` minus the network tools `AGENT_OFFLINE` refuses and `run_command`
+    /// without `TOOL_ALLOWED_COMMANDS`, in registry order: what the model is offered and
+    /// `/capabilities` lists.
+    pub fn offered_tools(&self) -> Vec<String> {
+        tool_definitions()
+            .iter()
+            .map(|tool| tool.name)
+            .filter(|name| self.enabled_tools.iter().any(|enabled| enabled == name))
+            .filter(|name| !(self.offline && is_network_tool(name)))
+            .filter(|name| *name != RUN_COMMAND_TOOL_NAME || !self.tool_allowed_commands.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    pub fn apply_override(&mut self, setting: &SettingsOverride) {
+        match setting {
+            SettingsOverride::MaxSteps(value) => self.max_steps = *value,
+            SettingsOverride::MaxToolCalls(value) => self.max_tool_calls = *value,
+            SettingsOverride::MaxToolCallsPerStep(value) => self.max_tool_calls_per_step = *value,
+            SettingsOverride::MaxConsecutiveToolSteps(value) => {
+                self.max_consecutive_tool_steps = *value;
+            }
+            SettingsOverride::MaxInputChars(value) => self.max_input_chars = *value,
+            SettingsOverride::MaxOutputChars(value) => self.max_output_chars = *value,
+            SettingsOverride::ToolTimeoutMs(value) => self.tool_timeout_ms = *value,
+            SettingsOverride::FetchUrlMaxBytes(value) => self.fetch_url_max_bytes = *value,
+            SettingsOverride::FetchUrlFollowRedirects(value) => {
+                self.fetch_url_follow_redirects = *value;
+            }
+            SettingsOverride::FetchUrlAllowedDomains(domains) => {
+                self.fetch_url_allowed_domains = domains.clone();
+            }
+            SettingsOverride::SaveNoteAllowOverwrite(value) => {
+                self.save_note_allow_overwrite = *value;
+            }
+            SettingsOverride::SaveNoteAllowDelete(value) => self.save_note_allow_delete = *value,
+            SettingsOverride::EnabledTools(tools) => self.enabled_tools = tools.clone(),
+        }
+    }
+
+    /// Current value of every overridable setting in env syntax, keyed by env name, so
+    /// it round-trips through `SettingsOverride::parse`.
+    pub fn override_values(&self) -> BTreeMap<&'static str, String> {
+        OVERRIDABLE_ENV_NAMES
+            .iter()
+            .map(|name| {
+                let value = match *name {
+                    "AGENT_MAX_STEPS" => self.max_steps.to_string(),
+                    "AGENT_MAX_TOOL_CALLS" => self.max_tool_calls.to_string(),
+                    "AGENT_MAX_TOOL_CALLS_PER_STEP" => self.max_tool_calls_per_step.to_string(),
+                    "AGENT_MAX_CONSECUTIVE_TOOL_STEPS" => {
+                        self.max_consecutive_tool_steps.to_string()
+                    }
+                    "AGENT_MAX_INPUT_CHARS" => self.max_input_chars.to_string(),
+                    "AGENT_MAX_OUTPUT_CHARS" => self.max_output_chars.to_string(),
+                    "TOOL_TIMEOUT_MS" => self.tool_timeout_ms.to_string(),
+                    "FETCH_URL_MAX_BYTES" => self.fetch_url_max_bytes.to_string(),
+                    "FETCH_URL_FOLLOW_REDIRECTS" => self.fetch_url_follow_redirects.to_string(),
+                    "FETCH_URL_ALLOWED_DOMAINS" => self.fetch_url_allowed_domains.join(","),
+                    "SAVE_NOTE_ALLOW_OVERWRITE" => self.save_note_allow_overwrite.to_string(),
+                    "SAVE_NOTE_ALLOW_DELETE" => self.save_note_allow_delete.to_string(),
+                    "ENABLED_TOOLS" if self.enabled_tools.is_empty() => "none".to_owned(),
+                    "ENABLED_TOOLS" => self.enabled_tools.join(","),
+                    other => unreachable!("unhandled overridable setting {other}"),
+                };
+                (*name, value)
+            })
+            .collect()
+    }
+}
+
+/// A single settings change expressed as an env var, e.g. an eval case's `setup.env`.
+/// Only per-turn tool and loop limits can be overridden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsOverride {
+    MaxSteps(u32),
+    MaxToolCalls(u32),
+    MaxToolCallsPerStep(u32),
+    MaxConsecutiveToolSteps(u32),
+    MaxInputChars(u32),
+    MaxOutputChars(u32),
+    ToolTimeoutMs(u64),
+    FetchUrlMaxBytes(u32),
+    FetchUrlFollowRedirects(bool),
+    FetchUrlAllowedDomains(Vec<String>),
+    SaveNoteAllowOverwrite(bool),
+    SaveNoteAllowDelete(bool),
+    EnabledTools(Vec<String>),
+}
+
+const OVERRIDABLE_ENV_NAMES: &[&str] = &[
+    "AGENT_MAX_STEPS",
+    "AGENT_MAX_TOOL_CALLS",
+    "AGENT_MAX_TOOL_CALLS_PER_STEP",
+    "AGENT_MAX_CONSECUTIVE_TOOL_STEPS",
+    "AGENT_MAX_INPUT_CHARS",
+    "AGENT_MAX_OUTPUT_CHARS",
+    "TOOL_TIMEOUT_MS",
+    "FETCH_URL_MAX_BYTES",
+    "FETCH_URL_FOLLOW_REDIRECTS",
+    "FETCH_URL_ALLOWED_DOMAINS",
+    "SAVE_NOTE_ALLOW_OVERWRITE",
+    "SAVE_NOTE_ALLOW_DELETE",
+    "ENABLED_TOOLS",
+];
+
+impl SettingsOverride {
+    /// Parses `name=raw` with the same rules `AgentSettings::from_env` applies to `name`.
+    pub fn parse(name: &str, raw: &str) -> Result<Self> {
+        Ok(match name {
+            "AGENT_MAX_STEPS" => Self::MaxSteps(parse_positive_u32_value(name, raw)?),
+            "AGENT_MAX_TOOL_CALLS" => Self::MaxToolCalls(parse_positive_u32_value(name, raw)?),
+            "AGENT_MAX_TOOL_CALLS_PER_STEP" => {
+                Self::MaxToolCallsPerStep(parse_positive_u32_value(name, raw)?)
+            }
+            "AGENT_MAX_CONSECUTIVE_TOOL_STEPS" => {
+                Self::MaxConsecutiveToolSteps(parse_positive_u32_value(name, raw)?)
+            }
+            "AGENT_MAX_INPUT_CHARS" => Self::MaxInputChars(parse_positive_u32_value(name, raw)?),
+            "AGENT_MAX_OUTPUT_CHARS" => Self::MaxOutputChars(parse_positive_u32_value(name, raw)?),
+            "TOOL_TIMEOUT_MS" => {
+                let value = raw
+                    .trim()
+                    .parse::<u64>()
+                    .with_context(|| format!("failed to parse {name} as u64"))?;
+                ensure!(value > 0, "{name} must be greater than 0");
+                Self::ToolTimeoutMs(value)
+            }
+            "FETCH_URL_MAX_BYTES" => Self::FetchUrlMaxBytes(parse_positive_u32_value(name, raw)?),
+            "FETCH_URL_FOLLOW_REDIRECTS" => {
+                Self::FetchUrlFollowRedirects(parse_bool_value(name, raw)?)
+            }
+            "FETCH_URL_ALLOWED_DOMAINS" => {
+                Self::FetchUrlAllowedDomains(parse_domain_allowlist(name, raw)?)
+            }
+            "SAVE_NOTE_ALLOW_OVERWRITE" => {
+                Self::SaveNoteAllowOverwrite(parse_bool_value(name, raw)?)
+            }
+            "SAVE_NOTE_ALLOW_DELETE" => Self::SaveNoteAllowDelete(parse_bool_value(name, raw)?),
+            "ENABLED_TOOLS" => Self::EnabledTools(parse_enabled_tools(name, raw)?),
+            _ => {
+                return Err(anyhow!(
+                    "{name} cannot be overridden; supported: {}",
+                    OVERRIDABLE_ENV_NAMES.join(", ")
+                ));
+            }
         })
     }
 }
 
+fn parse_positive_u32_value(name: &str, raw: &str) -> Result<u32> {
+    let value = raw
+        .trim()
+        .parse::<u32>()
+        .with_context(|| format!("failed to parse {name} as u32"))?;
+    ensure_positive_u32(name, value)
+}
+
+fn parse_temperature_value(name: &str, raw: &str) -> Result<f32> {
+    let value = raw
+        .trim()
+        .parse::<f32>()
+        .with_context(|| format!("failed to parse {name} as a number"))?;
+    ensure!(
+        (0.0..=2.0).contains(&value),
+        "{name} must be between 0.0 and 2.0"
+    );
+    Ok(value)
+}
+
+/// Comma-separated registered tool names; `none` disables every tool.
+fn parse_enabled_tools(name: &str, raw: &str) -> Result<Vec<String>> {
+    if raw.trim().eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+    let mut tools = Vec::new();
+    for tool in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|tool| !tool.is_empty())
+    {
+        ensure!(
+            tool_definitions()
+                .iter()
+                .any(|definition| definition.name == tool),
+            "{name} contains unknown tool `{tool}`; expected {} or `none`",
+            all_tool_names().join(", ")
+        );
+        if !tools.iter().any(|known| known == tool) {
+            tools.push(tool.to_owned());
+        }
+    }
+    ensure!(
+        !tools.is_empty(),
+        "{name} must list at least one tool or be `none`"
+    );
+    Ok(tools)
+}
+
+/// Comma-separated post-processor names, applied in the given order; `none` disables all.
+fn parse_answer_post_processors(raw: &str) -> Result<Vec<AnswerPostProcessor>> {
+    if raw.trim().eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+    let mut processors = Vec::new();
+    for name in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let processor = name.parse::<AnswerPostProcessor>()?;
+        ensure!(
+            !processors.contains(&processor),
+            "duplicate answer post-processor `{}`",
+            processor.as_str()
+        );
+        processors.push(processor);
+    }
+    Ok(processors)
+}
+
+/// Reads `SEARCH_NOTES_TIMEOUT_MS`, `FETCH_URL_TIMEOUT_MS`, ... for every registered tool.
+fn parse_tool_timeout_overrides() -> Result<BTreeMap<String, u64>> {
+    let mut overrides = BTreeMap::new();
+    for tool in tool_definitions() {
+        let name = tool_timeout_env_name(tool.name);
+        if env::var(&name).is_ok() {
+            overrides.insert(tool.name.to_owned(), parse_positive_u64_env(&name, 0)?);
+        }
+    }
+    Ok(overrides)
+}
+
+pub fn tool_timeout_env_name(tool_name: &str) -> String {
+    format!("{}_TIMEOUT_MS", tool_name.to_ascii_uppercase())
+}
+
 fn read_optional_env(name: &str) -> Option<String> {
     env::var(name).ok().and_then(|value| {
         let trimmed = value.trim();
@@ -279,9 +1093,219 @@ fn parse_domain_allowlist(name: &str, raw: &str) -> Result<Vec<String>> {
     Ok(domains)
 }
 
+/// Parses a comma-separated list of env var names; an empty list is allowed.
+fn parse_env_name_list(name: &str, raw: &str) -> Result<Vec<String>> {
+    let mut names = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    for entry in &names {
+        let mut chars = entry.chars();
+        let valid_start = chars
+            .next()
+            .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_');
+        ensure!(
+            valid_start && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_'),
+            "{name} contains invalid variable name `{entry}`"
+        );
+    }
+
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Parses comma-separated program names such as `cargo, git` into a sorted list. Entries
+/// must be bare names: paths and whitespace are rejected so the allowlist cannot be
+/// sidestepped with `./cargo` or `/tmp/git`.
+fn parse_command_allowlist(name: &str, raw: &str) -> Result<Vec<String>> {
+    let mut commands = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    for entry in &commands {
+        ensure!(
+            entry
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+                && !entry.starts_with('.'),
+            "{name} contains invalid command `{entry}`; list bare program names"
+        );
+    }
+    commands.sort();
+    commands.dedup();
+    Ok(commands)
+}
+
+/// Parses comma-separated `provider/model` pairs such as `ollama/llama3, openai/gpt-4o-mini`
+/// into sorted entries with the provider lowercased; the model is everything after the
+/// first `/`.
+fn parse_chat_model_allowlist(name: &str, raw: &str) -> Result<Vec<String>> {
+    let mut entries = Vec::new();
+    for entry in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let Some((provider, model)) = entry.split_once('/') else {
+            bail!("{name} contains invalid entry `{entry}`; expected `provider/model`");
+        };
+        let provider = provider
+            .parse::<ModelProvider>()
+            .with_context(|| format!("{name} contains unknown provider in `{entry}`"))?;
+        let model = model.trim();
+        ensure!(
+            !model.is_empty(),
+            "{name} entry `{entry}` has an empty model"
+        );
+        entries.push(format!("{provider}/{model}"));
+    }
+    entries.sort();
+    entries.dedup();
+    Ok(entries)
+}
+
+/// Parses comma-separated file extensions such as `rs, .TOML` into sorted lowercase
+/// names without the dot.
+fn parse_extension_list(name: &str, raw: &str) -> Result<Vec<String>> {
+    let mut extensions = raw
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').to_ascii_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .collect::<Vec<_>>();
+    for entry in &extensions {
+        ensure!(
+            entry.chars().all(|ch| ch.is_ascii_alphanumeric()),
+            "{name} contains invalid extension `{entry}`"
+        );
+    }
+    ensure!(
+        !extensions.is_empty(),
+        "{name} must list at least one extension"
+    );
+    extensions.sort();
+    extensions.dedup();
+    Ok(extensions)
+}
+
+/// Parses a comma-separated list of `http`/`https` URLs, keeping their order.
+fn parse_webhook_urls(name: &str, raw: &str) -> Result<Vec<String>> {
+    let mut urls = Vec::new();
+    for entry in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let url = reqwest::Url::parse(entry)
+            .with_context(|| format!("{name} contains invalid URL `{entry}`"))?;
+        ensure!(
+            matches!(url.scheme(), "http" | "https"),
+            "{name} URL `{entry}` must use http or https"
+        );
+        if !urls.contains(&url.to_string()) {
+            urls.push(url.to_string());
+        }
+    }
+    ensure!(!urls.is_empty(), "{name} must list at least one URL");
+    Ok(urls)
+}
+
+/// Accepts an `http` or `https` URL, credentials included.
+fn parse_http_url(name: &str, raw: &str) -> Result<String> {
+    let url = reqwest::Url::parse(raw).with_context(|| format!("{name} is not a valid URL"))?;
+    ensure!(
+        matches!(url.scheme(), "http" | "https"),
+        "{name} must use http or https"
+    );
+    ensure!(url.host_str().is_some(), "{name} must name a host");
+    Ok(raw.to_owned())
+}
+
+/// `AGENT_OFFLINE` only allows providers on this machine or its private network;
+/// `model_base_url` is the Ollama or compatible server the model provider calls.
+fn validate_offline_providers(
+    model_provider: ModelProvider,
+    model_base_url: &str,
+    moderation_mode: ModerationMode,
+    moderation_provider: ModerationProvider,
+) -> Result<()> {
+    let base_url_name = match model_provider {
+        ModelProvider::Ollama => "OLLAMA_BASE_URL",
+        ModelProvider::Compatible => "OPENAI_COMPAT_BASE_URL",
+        ModelProvider::OpenAi | ModelProvider::Gemini => {
+            bail!("AGENT_OFFLINE=true requires MODEL_PROVIDER=ollama or MODEL_PROVIDER=compatible")
+        }
+    };
+    ensure!(
+        is_local_base_url(model_base_url),
+        "AGENT_OFFLINE=true requires {base_url_name} on localhost or a private IP address, not `{model_base_url}`"
+    );
+    ensure!(
+        moderation_mode == ModerationMode::Off || moderation_provider == ModerationProvider::Local,
+        "AGENT_OFFLINE=true requires MODERATION_PROVIDER=local"
+    );
+    Ok(())
+}
+
+/// Whether `raw` points at `localhost` or a loopback, private, or link-local address.
+/// Other host names are rejected since resolving them could leave the machine.
+pub(crate) fn is_local_base_url(raw: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(raw.trim()) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => {
+            ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local()
+        }
+        Err(_) => host.eq_ignore_ascii_case("localhost"),
+    }
+}
+
+fn parse_moderation_keywords(raw: &str) -> Vec<String> {
+    let mut keywords = raw
+        .split(',')
+        .map(|keyword| keyword.trim().to_lowercase())
+        .filter(|keyword| !keyword.is_empty())
+        .collect::<Vec<_>>();
+    keywords.sort();
+    keywords.dedup();
+    keywords
+}
+
+/// One regex per line; blank lines and `#` comments are skipped.
+fn parse_moderation_patterns(raw: &str) -> Result<Vec<String>> {
+    raw.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, pattern)| {
+            regex::Regex::new(pattern)
+                .with_context(|| format!("line {line_number}: invalid regex `{pattern}`"))?;
+            Ok(pattern.to_owned())
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ensure_positive_u32, parse_bool_value, parse_domain_allowlist};
+    use super::{
+        AnswerPostProcessor, ModelProvider, ModerationMode, ModerationProvider, SettingsOverride,
+        ensure_positive_u32, is_local_base_url, parse_answer_post_processors, parse_bool_value,
+        parse_chat_model_allowlist, parse_command_allowlist, parse_domain_allowlist,
+        parse_enabled_tools, parse_env_name_list, parse_extension_list, parse_http_url,
+        parse_moderation_keywords, parse_moderation_patterns, parse_temperature_value,
+        parse_webhook_urls, tool_timeout_env_name, validate_offline_providers,
+    };
+    use crate::agent::tests::test_settings;
 
     #[test]
     fn ensure_positive_u32_accepts_positive_values() {
@@ -296,6 +1320,75 @@ mod tests {
         assert!(error.to_string().contains("greater than 0"));
     }
 
+    #[test]
+    fn parse_enabled_tools_supports_none_and_rejects_unknown_tools() {
+        assert_eq!(
+            parse_enabled_tools("ENABLED_TOOLS", "save_note, search_notes,save_note")
+                .expect("known tools should parse"),
+            ["save_note", "search_notes"]
+        );
+        assert!(
+            parse_enabled_tools("ENABLED_TOOLS", " NONE ")
+                .expect("none should parse")
+                .is_empty()
+        );
+        assert!(parse_enabled_tools("ENABLED_TOOLS", "shell").is_err());
+        assert!(parse_enabled_tools("ENABLED_TOOLS", " , ").is_err());
+    }
+
+    #[test]
+    fn override_values_round_trip_through_settings_override() {
+        let mut settings = test_settings();
+        settings.enabled_tools = Vec::new();
+        settings.max_input_chars = 512;
+        let mut rebuilt = test_settings();
+        for (name, raw) in settings.override_values() {
+            let setting = SettingsOverride::parse(name, &raw).expect("value should parse");
+            rebuilt.apply_override(&setting);
+        }
+        assert_eq!(rebuilt, settings);
+    }
+
+    #[test]
+    fn parse_temperature_value_accepts_only_the_supported_range() {
+        assert_eq!(
+            parse_temperature_value("FORMAT_REPAIR_TEMPERATURE", " 0.2 ")
+                .expect("0.2 should parse"),
+            0.2
+        );
+        assert!(parse_temperature_value("FORMAT_REPAIR_TEMPERATURE", "2.5").is_err());
+        assert!(parse_temperature_value("FORMAT_REPAIR_TEMPERATURE", "-0.1").is_err());
+        assert!(parse_temperature_value("FORMAT_REPAIR_TEMPERATURE", "cold").is_err());
+    }
+
+    #[test]
+    fn parse_answer_post_processors_keeps_order_and_supports_none() {
+        assert_eq!(
+            parse_answer_post_processors("collapse_blank_lines, trim_trailing_whitespace")
+                .expect("known names should parse"),
+            [
+                AnswerPostProcessor::CollapseBlankLines,
+                AnswerPostProcessor::TrimTrailingWhitespace
+            ]
+        );
+        assert!(
+            parse_answer_post_processors(" None ")
+                .expect("none should parse")
+                .is_empty()
+        );
+        assert!(parse_answer_post_processors("trim_trailing_whitespace,shout").is_err());
+        assert!(parse_answer_post_processors("collapse_blank_lines,collapse_blank_lines").is_err());
+    }
+
+    #[test]
+    fn tool_timeout_env_names_follow_tool_names() {
+        assert_eq!(tool_timeout_env_name("fetch_url"), "FETCH_URL_TIMEOUT_MS");
+        assert_eq!(
+            tool_timeout_env_name("search_notes"),
+            "SEARCH_NOTES_TIMEOUT_MS"
+        );
+    }
+
     #[test]
     fn parse_bool_value_accepts_truthy_and_falsy_values() {
         assert!(parse_bool_value("SAVE_NOTE_ALLOW_OVERWRITE", "true").expect("true should parse"));
@@ -352,4 +1445,238 @@ mod tests {
                 .contains("FETCH_URL_ALLOWED_DOMAINS contains invalid domain")
         );
     }
+
+    #[test]
+    fn parse_env_name_list_allows_empty_and_rejects_invalid_names() {
+        assert_eq!(
+            parse_env_name_list("TOOL_ENV_ALLOWLIST", " PATH, HOME ,PATH,").expect("valid list"),
+            vec!["HOME".to_owned(), "PATH".to_owned()]
+        );
+        assert!(
+            parse_env_name_list("TOOL_ENV_ALLOWLIST", "")
+                .expect("empty list")
+                .is_empty()
+        );
+        let error = parse_env_name_list("TOOL_ENV_ALLOWLIST", "PATH,1BAD")
+            .expect_err("invalid name should fail");
+        assert!(error.to_string().contains("1BAD"));
+    }
+
+    #[test]
+    fn parse_command_allowlist_accepts_bare_names_only() {
+        assert_eq!(
+            parse_command_allowlist("TOOL_ALLOWED_COMMANDS", " git, cargo ,git,")
+                .expect("valid list"),
+            ["cargo", "git"]
+        );
+        assert!(
+            parse_command_allowlist("TOOL_ALLOWED_COMMANDS", "")
+                .expect("empty list")
+                .is_empty()
+        );
+        for invalid in ["./cargo", "/usr/bin/git", "rm -rf", ".."] {
+            assert!(
+                parse_command_allowlist("TOOL_ALLOWED_COMMANDS", invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_chat_model_allowlist_normalizes_provider_model_pairs() {
+        assert_eq!(
+            parse_chat_model_allowlist(
+                "SERVER_CHAT_MODEL_ALLOWLIST",
+                " OpenAI/gpt-4o-mini, ollama/qwen2.5:3b,ollama/qwen2.5:3b,"
+            )
+            .expect("valid list"),
+            ["ollama/qwen2.5:3b", "openai/gpt-4o-mini"]
+        );
+        for invalid in ["llama3", "anthropic/claude", "ollama/ "] {
+            assert!(
+                parse_chat_model_allowlist("SERVER_CHAT_MODEL_ALLOWLIST", invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_extension_list_normalizes_and_rejects_invalid_entries() {
+        assert_eq!(
+            parse_extension_list("READ_WORKSPACE_FILE_EXTENSIONS", " rs, .TOML,rs,")
+                .expect("valid list"),
+            ["rs", "toml"]
+        );
+        assert!(parse_extension_list("READ_WORKSPACE_FILE_EXTENSIONS", ",").is_err());
+        assert!(parse_extension_list("READ_WORKSPACE_FILE_EXTENSIONS", "tar.gz").is_err());
+    }
+
+    #[test]
+    fn parse_webhook_urls_keeps_order_and_rejects_non_http_urls() {
+        let urls = parse_webhook_urls(
+            "SERVER_WEBHOOK_URLS",
+            " https://hooks.example.com/a , http://localhost:9000/b,https://hooks.example.com/a",
+        )
+        .expect("http urls should parse");
+        assert_eq!(
+            urls,
+            ["https://hooks.example.com/a", "http://localhost:9000/b"]
+        );
+
+        for raw in [" , ", "ftp://example.com/hook", "not a url"] {
+            assert!(
+                parse_webhook_urls("SERVER_WEBHOOK_URLS", raw).is_err(),
+                "{raw}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_http_url_accepts_http_urls_only() {
+        assert_eq!(
+            parse_http_url("HTTP_PROXY_URL", "http://user:pw@proxy.internal:3128")
+                .expect("http proxy should parse"),
+            "http://user:pw@proxy.internal:3128"
+        );
+        for raw in [
+            "socks5://proxy.internal:1080",
+            "proxy.internal:3128",
+            "not a url",
+        ] {
+            assert!(parse_http_url("HTTP_PROXY_URL", raw).is_err(), "{raw}");
+        }
+    }
+
+    #[test]
+    fn offline_mode_requires_a_local_ollama_and_hides_network_tools() {
+        for local in [
+            "http://localhost:11434",
+            "http://127.0.0.1:11434",
+            "http://192.168.1.20:11434",
+            "http://[::1]:11434",
+        ] {
+            assert!(is_local_base_url(local), "{local}");
+        }
+        for remote in [
+            "http://ollama.example.com",
+            "http://8.8.8.8:11434",
+            "not a url",
+        ] {
+            assert!(!is_local_base_url(remote), "{remote}");
+        }
+
+        let offline = |provider, url, moderation| {
+            validate_offline_providers(provider, url, ModerationMode::Block, moderation)
+        };
+        offline(
+            ModelProvider::Ollama,
+            "http://localhost:11434",
+            ModerationProvider::Local,
+        )
+        .expect("local ollama is allowed");
+        let error = offline(
+            ModelProvider::OpenAi,
+            "http://localhost:11434",
+            ModerationProvider::Local,
+        )
+        .expect_err("openai is remote");
+        assert!(
+            error.to_string().contains("MODEL_PROVIDER=ollama"),
+            "{error}"
+        );
+        assert!(
+            offline(
+                ModelProvider::Ollama,
+                "http://ollama.example.com",
+                ModerationProvider::Local
+            )
+            .is_err()
+        );
+        offline(
+            ModelProvider::Compatible,
+            "http://127.0.0.1:8080/v1",
+            ModerationProvider::Local,
+        )
+        .expect("a local compatible server is allowed");
+        let error = offline(
+            ModelProvider::Compatible,
+            "https://openrouter.ai/api/v1",
+            ModerationProvider::Local,
+        )
+        .expect_err("a remote compatible server is not");
+        assert!(
+            error.to_string().contains("OPENAI_COMPAT_BASE_URL"),
+            "{error}"
+        );
+        assert!(
+            offline(
+                ModelProvider::Ollama,
+                "http://localhost:11434",
+                ModerationProvider::OpenAi
+            )
+            .is_err()
+        );
+
+        let mut settings = test_settings();
+        settings.offline = true;
+        assert!(
+            !settings
+                .offered_tools()
+                .iter()
+                .any(|tool| tool == "fetch_url")
+        );
+        assert!(
+            settings
+                .offered_tools()
+                .iter()
+                .any(|tool| tool == "search_notes")
+        );
+    }
+
+    #[test]
+    fn offered_tools_include_run_command_only_with_allowed_commands() {
+        let mut settings = test_settings();
+        assert!(!settings.offered_tools().contains(&"run_command".to_owned()));
+        settings.tool_allowed_commands = vec!["cargo".to_owned()];
+        assert!(settings.offered_tools().contains(&"run_command".to_owned()));
+    }
+
+    #[test]
+    fn model_provider_parses_compatible_aliases() {
+        for raw in ["compatible", " OpenAI-Compatible "] {
+            assert_eq!(
+                raw.parse::<ModelProvider>().expect("valid provider"),
+                ModelProvider::Compatible
+            );
+        }
+        assert_eq!(ModelProvider::Compatible.default_model(), None);
+        assert!("vllm".parse::<ModelProvider>().is_err());
+    }
+
+    #[test]
+    fn moderation_mode_parses_known_values() {
+        assert_eq!(
+            " Block ".parse::<ModerationMode>().expect("valid mode"),
+            ModerationMode::Block
+        );
+        assert!("strict".parse::<ModerationMode>().is_err());
+    }
+
+    #[test]
+    fn parse_moderation_keywords_normalizes_and_deduplicates() {
+        assert_eq!(
+            parse_moderation_keywords("Secret, password,,secret"),
+            vec!["password".to_owned(), "secret".to_owned()]
+        );
+    }
+
+    #[test]
+    fn parse_moderation_patterns_skips_comments_and_rejects_invalid_regex() {
+        assert_eq!(
+            parse_moderation_patterns("# cards\n\\b\\d{16}\\b\n\n").expect("valid patterns"),
+            vec!["\\b\\d{16}\\b".to_owned()]
+        );
+        let error = parse_moderation_patterns("ok\n(unclosed").expect_err("invalid regex");
+        assert!(format!("{error:#}").contains("line 2"));
+    }
 }
@@ -0,0 +1,363 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, ensure};
+use time::{Date, OffsetDateTime};
+
+use crate::agent::{ChatTurnErrorKind, resolve_tool_working_dir};
+use crate::config::AgentSettings;
+use crate::http::SharedHttpClient;
+use crate::model::client::{ChatResponse, ModelClient};
+use crate::text::truncate_graphemes;
+use crate::tools::{extract_note_title, list_searchable_note_paths, save_note_outside_turn};
+
+pub const DEFAULT_DIGEST_DAYS: u32 = 1;
+/// Longest window a digest may cover.
+pub const MAX_DIGEST_DAYS: u32 = 366;
+const DIGEST_SYSTEM_PROMPT: &str = "You write a digest of a person's recently changed notes. You are given the title, file, modification time, and opening text of each note. Summarize what was worked on and thought about: group related notes into themes, call out decisions, open questions, and follow-ups, and refer to notes by title. Use short Markdown sections and bullet points, do not add a top-level heading, and do not invent anything that is not in the notes.";
+/// Digest notes are titled `Digest YYYY-MM-DD` and saved as `digest-YYYY-MM-DD.md`.
+const DIGEST_TITLE_PREFIX: &str = "Digest ";
+const DIGEST_FILE_PREFIX: &str = "digest-";
+/// Most recently modified notes beyond this are listed by title only.
+const MAX_DIGEST_EXCERPTS: usize = 40;
+const MAX_DIGEST_EXCERPT_CHARS: usize = 1_500;
+
+/// A note changed within the digest window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestNote {
+    pub file: String,
+    pub title: String,
+    pub modified: OffsetDateTime,
+    pub content: String,
+}
+
+/// Why a digest could not be produced.
+#[derive(Debug, thiserror::Error)]
+pub enum DigestError {
+    #[error("failed to read notes: {0}")]
+    Notes(String),
+    #[error("digest model call failed: {0}")]
+    Model(String),
+    #[error("failed to save digest note: {0}")]
+    Save(String),
+}
+
+impl DigestError {
+    /// Turn error kind used when a scheduled digest fails.
+    pub fn kind(&self) -> ChatTurnErrorKind {
+        match self {
+            Self::Model(_) => ChatTurnErrorKind::Upstream,
+            Self::Notes(_) | Self::Save(_) => ChatTurnErrorKind::Internal,
+        }
+    }
+}
+
+/// A generated digest and, unless it was a dry run, where it was saved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestOutcome {
+    pub title: String,
+    pub note_count: usize,
+    pub text: String,
+    pub saved_path: Option<String>,
+}
+
+/// Notes in `notes_dir` modified at or after `since`, newest first. Earlier digests are
+/// left out so one digest never summarizes another.
+pub fn collect_digest_notes(
+    notes_dir: &Path,
+    since: SystemTime,
+) -> Result<Vec<DigestNote>, DigestError> {
+    let paths = list_searchable_note_paths(notes_dir)
+        .map_err(|error| DigestError::Notes(error.to_string()))?;
+    let mut notes = Vec::new();
+    for path in paths {
+        let Some(file) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if is_digest_note_file(file) {
+            continue;
+        }
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|error| {
+                DigestError::Notes(format!("failed to inspect `{}`: {error}", path.display()))
+            })?;
+        if modified < since {
+            continue;
+        }
+        let content = fs::read_to_string(&path).map_err(|error| {
+            DigestError::Notes(format!("failed to read `{}`: {error}", path.display()))
+        })?;
+        notes.push(DigestNote {
+            file: file.to_owned(),
+            title: extract_note_title(&content, &path),
+            modified: OffsetDateTime::from(modified),
+            content,
+        });
+    }
+    notes.sort_by(|left, right| {
+        right
+            .modified
+            .cmp(&left.modified)
+            .then_with(|| left.file.cmp(&right.file))
+    });
+    Ok(notes)
+}
+
+/// Title of the digest note for `date`, for example `Digest 2026-03-14`.
+pub fn digest_title(date: Date) -> String {
+    format!("{DIGEST_TITLE_PREFIX}{}", format_date(date))
+}
+
+/// Whether `file` is a saved digest (`digest-YYYY-MM-DD.md`).
+fn is_digest_note_file(file: &str) -> bool {
+    let Some(date) = Path::new(file)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.strip_prefix(DIGEST_FILE_PREFIX))
+    else {
+        return false;
+    };
+    let parts = date.split('-').collect::<Vec<_>>();
+    matches!(parts[..], [year, month, day]
+        if year.len() == 4 && month.len() == 2 && day.len() == 2
+            && parts.iter().all(|part| part.bytes().all(|byte| byte.is_ascii_digit())))
+}
+
+fn format_date(date: Date) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day()
+    )
+}
+
+pub fn build_digest_prompt(notes: &[DigestNote], days: u32) -> String {
+    let mut prompt = format!("Notes changed in the last {days} day(s): {}\n", notes.len());
+    for (index, note) in notes.iter().enumerate() {
+        let modified = note.modified;
+        prompt.push_str(&format!(
+            "\n## {} ({}, modified {} {:02}:{:02} UTC)\n",
+            note.title,
+            note.file,
+            format_date(modified.date()),
+            modified.hour(),
+            modified.minute()
+        ));
+        if index < MAX_DIGEST_EXCERPTS {
+            prompt.push_str(&truncate_graphemes(
+                note.content.trim(),
+                MAX_DIGEST_EXCERPT_CHARS,
+                "…",
+            ));
+            prompt.push('\n');
+        }
+    }
+    prompt
+}
+
+pub async fn generate_digest(
+    client: &ModelClient,
+    notes: &[DigestNote],
+    days: u32,
+) -> Result<String, DigestError> {
+    let response = client
+        .chat(DIGEST_SYSTEM_PROMPT, &build_digest_prompt(notes, days))
+        .await
+        .map_err(|error| DigestError::Model(error.to_string()))?;
+    let ChatResponse::FinalText { text } = response else {
+        return Err(DigestError::Model(
+            "the model returned tool calls instead of text".to_owned(),
+        ));
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(DigestError::Model(
+            "the model returned empty text".to_owned(),
+        ));
+    }
+    Ok(text.to_owned())
+}
+
+/// Digest note body: the summary followed by wikilinks to the summarized notes, so they
+/// show the digest among their backlinks.
+fn digest_note_body(summary: &str, notes: &[DigestNote]) -> String {
+    let mut body = format!("{summary}\n\n## Notes\n");
+    for note in notes {
+        body.push_str(&format!("\n- [[{}]]", note.title));
+    }
+    body
+}
+
+/// Summarizes the notes changed in the last `days` days and, with `save`, writes the
+/// summary to today's (UTC) digest note. Returns `None` without calling the model when
+/// no note changed.
+pub async fn run_digest(
+    settings: &AgentSettings,
+    days: u32,
+    http_client: SharedHttpClient,
+    save: bool,
+) -> Result<Option<DigestOutcome>, DigestError> {
+    let notes_dir = resolve_tool_working_dir(settings).join(&settings.notes_dir);
+    let now = SystemTime::now();
+    let since = now
+        .checked_sub(Duration::from_secs(u64::from(days) * 24 * 60 * 60))
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let notes = collect_digest_notes(&notes_dir, since)?;
+    if notes.is_empty() {
+        return Ok(None);
+    }
+
+    let client = ModelClient::with_http_client(settings.clone(), http_client);
+    let text = generate_digest(&client, &notes, days).await?;
+    let title = digest_title(OffsetDateTime::from(now).date());
+    let saved_path = if save {
+        let saved = save_note_outside_turn(
+            &title,
+            &digest_note_body(&text, &notes),
+            &notes_dir,
+            settings.save_note_allow_overwrite,
+            settings.notes_quota(),
+        )
+        .map_err(|error| DigestError::Save(error.to_string()))?;
+        saved["path"].as_str().map(str::to_owned)
+    } else {
+        None
+    };
+    Ok(Some(DigestOutcome {
+        title,
+        note_count: notes.len(),
+        text,
+        saved_path,
+    }))
+}
+
+/// `digest`: summarizes recently changed notes and saves the summary as a dated note.
+pub async fn run_digest_command(settings: &AgentSettings, days: u32, dry_run: bool) -> Result<()> {
+    ensure!(
+        (1..=MAX_DIGEST_DAYS).contains(&days),
+        "--days must be between 1 and {MAX_DIGEST_DAYS}"
+    );
+    let outcome = run_digest(
+        settings,
+        days,
+        SharedHttpClient::from_settings(settings),
+        !dry_run,
+    )
+    .await?;
+    let Some(outcome) = outcome else {
+        println!("No notes changed in the last {days} day(s); no digest written.");
+        return Ok(());
+    };
+    match &outcome.saved_path {
+        Some(path) => println!(
+            "{} ({} notes) saved to {path}\n\n{}",
+            outcome.title, outcome.note_count, outcome.text
+        ),
+        None => println!(
+            "{} ({} notes, not saved)\n\n{}",
+            outcome.title, outcome.note_count, outcome.text
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+
+    use time::{Date, Month, OffsetDateTime};
+
+    use super::{
+        DigestNote, build_digest_prompt, collect_digest_notes, digest_note_body, digest_title,
+        is_digest_note_file,
+    };
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn digest_notes_are_titled_by_date_and_recognized_by_file_name() {
+        let date = Date::from_calendar_date(2026, Month::March, 4).expect("valid date");
+        assert_eq!(digest_title(date), "Digest 2026-03-04");
+        assert!(is_digest_note_file("digest-2026-03-04.md"));
+        for file in [
+            "digest-of-rust.md",
+            "digest-2026-3-4.md",
+            "daily-2026-03-04.md",
+        ] {
+            assert!(!is_digest_note_file(file), "{file}");
+        }
+    }
+
+    #[test]
+    fn collect_skips_old_notes_earlier_digests_and_non_notes() {
+        let notes_dir = temp_path("digest-notes");
+        fs::create_dir_all(&notes_dir).expect("notes dir");
+        fs::write(
+            notes_dir.join("rust-tips.md"),
+            "# Rust Tips\n\nUse let-chains.\n",
+        )
+        .expect("write note");
+        fs::write(notes_dir.join("todo.txt"), "buy milk\n").expect("write note");
+        fs::write(notes_dir.join("digest-2026-03-04.md"), "# Digest\n").expect("write digest");
+        fs::write(notes_dir.join("image.png"), "not a note").expect("write file");
+
+        let since = SystemTime::now() - Duration::from_secs(60);
+        let notes = collect_digest_notes(&notes_dir, since).expect("notes should collect");
+        let mut files = notes
+            .iter()
+            .map(|note| note.file.as_str())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, ["rust-tips.md", "todo.txt"]);
+        let rust = notes
+            .iter()
+            .find(|note| note.file == "rust-tips.md")
+            .expect("rust note");
+        assert_eq!(rust.title, "Rust Tips");
+
+        let future = SystemTime::now() + Duration::from_secs(60);
+        assert!(
+            collect_digest_notes(&notes_dir, future)
+                .expect("notes should collect")
+                .is_empty()
+        );
+        assert!(
+            collect_digest_notes(&notes_dir.join("missing"), since)
+                .expect("a missing notes dir has no notes")
+                .is_empty()
+        );
+        remove_dir_if_exists(&notes_dir);
+    }
+
+    #[test]
+    fn prompt_and_body_list_every_note() {
+        let note = |file: &str, title: &str, content: &str| DigestNote {
+            file: file.to_owned(),
+            title: title.to_owned(),
+            modified: OffsetDateTime::UNIX_EPOCH,
+            content: content.to_owned(),
+        };
+        let notes = [
+            note(
+                "rust-tips.md",
+                "Rust Tips",
+                "# Rust Tips\n\nUse let-chains.",
+            ),
+            note("todo.txt", "todo", "buy milk"),
+        ];
+
+        let prompt = build_digest_prompt(&notes, 7);
+        assert!(prompt.starts_with("Notes changed in the last 7 day(s): 2\n"));
+        assert!(prompt.contains("## Rust Tips (rust-tips.md, modified 1970-01-01 00:00 UTC)"));
+        assert!(prompt.contains("Use let-chains."));
+        assert!(prompt.contains("buy milk"));
+
+        assert_eq!(
+            digest_note_body("Worked on Rust.", &notes),
+            "Worked on Rust.\n\n## Notes\n\n- [[Rust Tips]]\n- [[todo]]"
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+
+use crate::config::AgentSettings;
+use crate::graph::{
+    ArchitectureEdgeKind, ArchitectureGraph, build_workspace_graph, resolve_workspace_roots,
+};
+use crate::tr;
+
+const PATH_PLACEHOLDER: &str = "{path}";
+const LINE_PLACEHOLDER: &str = "{line}";
+
+/// Editors that accept `+LINE PATH`.
+const PLUS_LINE_EDITORS: &[&str] = &[
+    "vi",
+    "vim",
+    "nvim",
+    "nano",
+    "emacs",
+    "emacsclient",
+    "micro",
+    "kak",
+];
+/// Editors that accept `-g PATH:LINE`.
+const GOTO_FLAG_EDITORS: &[&str] = &["code", "code-insiders", "codium", "cursor"];
+
+/// A resolved editor invocation: program plus arguments, no shell involved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorLaunch {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl EditorLaunch {
+    pub fn spawn(&self) -> Result<Child> {
+        Command::new(&self.program)
+            .args(&self.args)
+            .spawn()
+            .with_context(|| format!("failed to start editor `{}`", self.program))
+    }
+}
+
+/// Picks how to open `path`: `EDITOR_COMMAND` template, then `$VISUAL`, then `$EDITOR`,
+/// then the platform opener (which cannot jump to a line).
+pub fn resolve_editor_launch(
+    settings: &AgentSettings,
+    path: &Path,
+    line: Option<u32>,
+) -> Result<EditorLaunch> {
+    let visual = std::env::var("VISUAL").ok();
+    let editor = std::env::var("EDITOR").ok();
+    build_editor_launch(
+        settings.editor_command.as_deref(),
+        visual.as_deref().or(editor.as_deref()),
+        path,
+        line,
+    )
+}
+
+pub fn build_editor_launch(
+    template: Option<&str>,
+    env_editor: Option<&str>,
+    path: &Path,
+    line: Option<u32>,
+) -> Result<EditorLaunch> {
+    let path_text = path.display().to_string();
+    let line_text = line.unwrap_or(1).to_string();
+
+    if let Some(template) = template.filter(|value| !value.trim().is_empty()) {
+        let mut words = split_command_words(template)?;
+        if !words.iter().any(|word| word.contains(PATH_PLACEHOLDER)) {
+            words.push(PATH_PLACEHOLDER.to_owned());
+        }
+        let words = words
+            .into_iter()
+            .map(|word| {
+                word.replace(PATH_PLACEHOLDER, &path_text)
+                    .replace(LINE_PLACEHOLDER, &line_text)
+            })
+            .collect::<Vec<_>>();
+        return launch_from_words(words);
+    }
+
+    if let Some(editor) = env_editor.filter(|value| !value.trim().is_empty()) {
+        let mut words = split_command_words(editor)?;
+        let program_name = words
+            .first()
+            .and_then(|program| Path::new(program).file_stem())
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_owned();
+        if PLUS_LINE_EDITORS.contains(&program_name.as_str()) {
+            words.push(format!("+{line_text}"));
+            words.push(path_text);
+        } else if GOTO_FLAG_EDITORS.contains(&program_name.as_str()) {
+            words.push("-g".to_owned());
+            words.push(format!("{path_text}:{line_text}"));
+        } else {
+            words.push(path_text);
+        }
+        return launch_from_words(words);
+    }
+
+    Ok(platform_open_launch(path_text))
+}
+
+fn launch_from_words(mut words: Vec<String>) -> Result<EditorLaunch> {
+    ensure!(!words.is_empty(), "editor command is empty");
+    let program = words.remove(0);
+    Ok(EditorLaunch {
+        program,
+        args: words,
+    })
+}
+
+fn platform_open_launch(path: String) -> EditorLaunch {
+    if cfg!(target_os = "windows") {
+        EditorLaunch {
+            program: "cmd".to_owned(),
+            args: vec!["/C".to_owned(), "start".to_owned(), String::new(), path],
+        }
+    } else if cfg!(target_os = "macos") {
+        EditorLaunch {
+            program: "open".to_owned(),
+            args: vec![path],
+        }
+    } else {
+        EditorLaunch {
+            program: "xdg-open".to_owned(),
+            args: vec![path],
+        }
+    }
+}
+
+/// Splits a command line on whitespace, honoring single and double quotes.
+pub(crate) fn split_command_words(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for ch in command.chars() {
+        match (quote, ch) {
+            (Some(open), ch) if ch == open => quote = None,
+            (Some(_), ch) => current.push(ch),
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, ch) if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, ch) => {
+                current.push(ch);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        bail!("unterminated quote in editor command `{command}`");
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+/// Workspace-relative source file for a graph node. Modules without their own file
+/// (inline or unresolved) fall back to the file of the module that declares them.
+pub fn node_source_path<'a>(graph: &'a ArchitectureGraph, node_id: &str) -> Option<&'a str> {
+    let node = graph.nodes.iter().find(|node| node.id == node_id)?;
+    if let Some(path) = node.path.as_deref() {
+        return Some(path);
+    }
+    graph
+        .edges
+        .iter()
+        .find(|edge| edge.to == node_id && edge.relation == ArchitectureEdgeKind::DeclaresModule)
+        .and_then(|edge| node_source_path(graph, &edge.from))
+}
+
+/// Spawns the editor for a workspace-relative path without waiting for it to exit.
+pub fn open_workspace_path_detached(
+    settings: &AgentSettings,
+    workspace_root: &Path,
+    relative_path: &str,
+) -> Result<()> {
+    let launch = resolve_editor_launch(settings, &workspace_root.join(relative_path), None)?;
+    let mut child = launch.spawn()?;
+    // Reap the child so it does not linger as a zombie after the editor exits.
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// `graph open <node-id>`: resolves the node in the workspace graph and opens its file,
+/// waiting for the editor so terminal editors keep the console.
+pub fn run_graph_open_command(
+    settings: &AgentSettings,
+    node_id: &str,
+    workspace: Option<&Path>,
+    extra_roots: &[String],
+) -> Result<()> {
+    let workspace_roots = resolve_workspace_roots(workspace, extra_roots)
+        .context("failed to resolve workspace root for graph open")?;
+    let workspace_root = &workspace_roots.primary;
+    let graph = build_workspace_graph(&workspace_roots, 0)?;
+    if !graph.nodes.iter().any(|node| node.id == node_id) {
+        let suggestions = graph
+            .nodes
+            .iter()
+            .filter(|node| node.id.contains(node_id))
+            .take(5)
+            .map(|node| node.id.as_str())
+            .collect::<Vec<_>>();
+        return Err(if suggestions.is_empty() {
+            anyhow!("graph node `{node_id}` not found")
+        } else {
+            anyhow!(
+                "graph node `{node_id}` not found; did you mean: {}",
+                suggestions.join(", ")
+            )
+        });
+    }
+    let relative_path = node_source_path(&graph, node_id)
+        .ok_or_else(|| anyhow!("graph node `{node_id}` has no source file"))?;
+    let path: PathBuf = workspace_root.join(relative_path);
+
+    let launch = resolve_editor_launch(settings, &path, None)?;
+    println!(
+        "{}",
+        tr!(
+            "cli.opening_path",
+            path = path.display(),
+            program = launch.program
+        )
+    );
+    let status = launch
+        .spawn()?
+        .wait()
+        .with_context(|| format!("failed to wait for editor `{}`", launch.program))?;
+    ensure!(
+        status.success(),
+        "editor `{}` exited with {status}",
+        launch.program
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::UNIX_EPOCH;
+
+    use super::{EditorLaunch, build_editor_launch, node_source_path, split_command_words};
+    use crate::graph::{
+        ArchitectureEdge, ArchitectureEdgeKind, ArchitectureGraph, ArchitectureNode,
+        ArchitectureNodeKind,
+    };
+
+    #[test]
+    fn template_substitutes_path_and_line_or_appends_path() {
+        let path = Path::new("/work/src/lib.rs");
+
+        let launch =
+            build_editor_launch(Some("code -g {path}:{line}"), None, path, Some(12)).unwrap();
+        assert_eq!(
+            launch,
+            EditorLaunch {
+                program: "code".to_owned(),
+                args: vec!["-g".to_owned(), "/work/src/lib.rs:12".to_owned()],
+            }
+        );
+
+        let launch = build_editor_launch(Some("\"my editor\" --wait"), None, path, None).unwrap();
+        assert_eq!(launch.program, "my editor");
+        assert_eq!(launch.args, ["--wait", "/work/src/lib.rs"]);
+    }
+
+    #[test]
+    fn env_editor_uses_known_line_syntax() {
+        let path = Path::new("src/main.rs");
+
+        let vim = build_editor_launch(None, Some("/usr/bin/nvim"), path, Some(3)).unwrap();
+        assert_eq!(vim.args, ["+3", "src/main.rs"]);
+
+        let code = build_editor_launch(None, Some("code --wait"), path, None).unwrap();
+        assert_eq!(code.args, ["--wait", "-g", "src/main.rs:1"]);
+
+        let other = build_editor_launch(None, Some("gedit"), path, Some(3)).unwrap();
+        assert_eq!(other.args, ["src/main.rs"]);
+
+        assert!(split_command_words("code 'unterminated").is_err());
+    }
+
+    #[test]
+    fn node_source_path_falls_back_to_declaring_module_file() {
+        let node = |id: &str, path: Option<&str>| ArchitectureNode {
+            id: id.to_owned(),
+            display_label: id.to_owned(),
+            kind: ArchitectureNodeKind::Module,
+            path: path.map(str::to_owned),
+        };
+        let graph = ArchitectureGraph {
+            nodes: vec![
+                node("module:crate", Some("src/lib.rs")),
+                node("module:crate::tests", None),
+                node("module:crate::orphan", None),
+            ],
+            edges: vec![ArchitectureEdge {
+                from: "module:crate".to_owned(),
+                to: "module:crate::tests".to_owned(),
+                relation: ArchitectureEdgeKind::DeclaresModule,
+            }],
+            revision: 1,
+            generated_at: UNIX_EPOCH,
+        };
+
+        assert_eq!(
+            node_source_path(&graph, "module:crate::tests"),
+            Some("src/lib.rs")
+        );
+        assert_eq!(node_source_path(&graph, "module:crate::orphan"), None);
+        assert_eq!(node_source_path(&graph, "module:missing"), None);
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, ensure};
+use serde::{Deserialize, Serialize};
+
+use super::EvalRunReport;
+use super::summary::EvalSummary;
+
+/// Loads a summary written by an earlier `eval --summary` run.
+pub(super) fn load_eval_baseline(path: &Path) -> Result<EvalSummary> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("failed to read eval baseline `{}`", path.display()))?;
+    let baseline: EvalSummary = serde_json::from_str(&raw)
+        .with_context(|| format!("failed to parse eval baseline `{}`", path.display()))?;
+    ensure!(
+        baseline.total_cases > 0,
+        "eval baseline `{}` has no case results (status {:?})",
+        path.display(),
+        baseline.status
+    );
+    ensure!(
+        baseline.passed_case_ids.len() == baseline.passed_cases,
+        "eval baseline `{}` does not list its passed cases; re-run eval to regenerate it",
+        path.display()
+    );
+    Ok(baseline)
+}
+
+/// Per-case diff of a run against a baseline summary. Skipped cases count as not passing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct BaselineComparison {
+    pub baseline_path: String,
+    /// Passed in the baseline, failed or skipped now. These are the regressions.
+    pub newly_failing: Vec<String>,
+    pub newly_passing: Vec<String>,
+    pub unchanged_passing: Vec<String>,
+    pub unchanged_failing: Vec<String>,
+    /// Not in the baseline; never counted as regressions.
+    pub new_cases: Vec<String>,
+    /// In the baseline but not in this run's cases file.
+    pub removed_cases: Vec<String>,
+}
+
+impl BaselineComparison {
+    pub(super) fn new(
+        baseline_path: &Path,
+        baseline: &EvalSummary,
+        report: &EvalRunReport,
+    ) -> Self {
+        let passed_before = baseline
+            .passed_case_ids
+            .iter()
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+        let failed_before = baseline
+            .failed_case_ids
+            .iter()
+            .chain(&baseline.skipped_case_ids)
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+        let mut comparison = Self {
+            baseline_path: baseline_path.display().to_string(),
+            newly_failing: Vec::new(),
+            newly_passing: Vec::new(),
+            unchanged_passing: Vec::new(),
+            unchanged_failing: Vec::new(),
+            new_cases: Vec::new(),
+            removed_cases: Vec::new(),
+        };
+        let mut seen = BTreeSet::new();
+        for result in &report.case_results {
+            let case_id = result.case_id.as_str();
+            seen.insert(case_id);
+            let bucket = if passed_before.contains(case_id) {
+                if result.passed {
+                    &mut comparison.unchanged_passing
+                } else {
+                    &mut comparison.newly_failing
+                }
+            } else if failed_before.contains(case_id) {
+                if result.passed {
+                    &mut comparison.newly_passing
+                } else {
+                    &mut comparison.unchanged_failing
+                }
+            } else {
+                &mut comparison.new_cases
+            };
+            bucket.push(case_id.to_owned());
+        }
+        comparison.removed_cases = passed_before
+            .union(&failed_before)
+            .filter(|case_id| !seen.contains(*case_id))
+            .map(|case_id| (*case_id).to_owned())
+            .collect();
+        comparison
+    }
+
+    pub(super) fn print(&self) {
+        println!(
+            "Baseline {}: {} newly failing, {} newly passing, {} unchanged ({} passing, {} failing)",
+            self.baseline_path,
+            self.newly_failing.len(),
+            self.newly_passing.len(),
+            self.unchanged_passing.len() + self.unchanged_failing.len(),
+            self.unchanged_passing.len(),
+            self.unchanged_failing.len()
+        );
+        for (label, case_ids) in [
+            ("newly failing", &self.newly_failing),
+            ("newly passing", &self.newly_passing),
+            ("still failing", &self.unchanged_failing),
+            ("new cases", &self.new_cases),
+            ("removed cases", &self.removed_cases),
+        ] {
+            if !case_ids.is_empty() {
+                println!("  {label}: {}", case_ids.join(", "));
+            }
+        }
+    }
+
+    /// Fails only when a case that passed in the baseline no longer passes.
+    pub(super) fn check(&self) -> Result<()> {
+        if self.newly_failing.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "evaluation regressed against baseline `{}`: {} case(s) newly failing: {}",
+            self.baseline_path,
+            self.newly_failing.len(),
+            self.newly_failing.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::{BaselineComparison, load_eval_baseline};
+    use crate::eval::summary::{EvalSummary, write_eval_summary};
+    use crate::eval::{EvalCaseResult, EvalRunReport};
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    fn report(results: &[(&str, bool)]) -> EvalRunReport {
+        EvalRunReport::from_case_results(
+            Path::new("eval/cases.yaml"),
+            1.0,
+            results
+                .iter()
+                .map(|(case_id, passed)| EvalCaseResult {
+                    case_id: (*case_id).to_owned(),
+                    passed: *passed,
+                    checks: Vec::new(),
+                    error: None,
+                    error_code: None,
+                    final_text: None,
+                    used_tools: Vec::new(),
+                    skip_reason: None,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn comparison_against_a_written_summary_fails_only_on_regressions() {
+        let dir = temp_path("eval_baseline");
+        let path = dir.join("baseline.json");
+        let previous = report(&[
+            ("greeting", true),
+            ("notes", true),
+            ("fetch", false),
+            ("search", false),
+            ("retired", true),
+        ]);
+        write_eval_summary(
+            &path,
+            &EvalSummary::new(Path::new("eval/cases.yaml"), Some(&previous), None, None),
+        )
+        .expect("baseline should be written");
+        let baseline = load_eval_baseline(&path).expect("baseline should load");
+
+        let current = report(&[
+            ("greeting", true),
+            ("notes", false),
+            ("fetch", true),
+            ("search", false),
+            ("added", false),
+        ]);
+        let comparison = BaselineComparison::new(&path, &baseline, &current);
+        assert_eq!(comparison.newly_failing, ["notes"]);
+        assert_eq!(comparison.newly_passing, ["fetch"]);
+        assert_eq!(comparison.unchanged_passing, ["greeting"]);
+        assert_eq!(comparison.unchanged_failing, ["search"]);
+        assert_eq!(comparison.new_cases, ["added"]);
+        assert_eq!(comparison.removed_cases, ["retired"]);
+        let error = comparison.check().expect_err("regression should fail");
+        assert!(error.to_string().contains("1 case(s) newly failing: notes"));
+
+        let recovered = report(&[("greeting", true), ("notes", true), ("added", false)]);
+        assert!(
+            BaselineComparison::new(&path, &baseline, &recovered)
+                .check()
+                .is_ok()
+        );
+        remove_dir_if_exists(&dir);
+    }
+
+    #[test]
+    fn baseline_without_case_results_is_rejected() {
+        let dir = temp_path("eval_baseline_aborted");
+        let path = dir.join("baseline.json");
+        write_eval_summary(
+            &path,
+            &EvalSummary::new(Path::new("eval/cases.yaml"), None, None, None),
+        )
+        .expect("baseline should be written");
+        let error = load_eval_baseline(&path).expect_err("aborted baseline should be rejected");
+        assert!(error.to_string().contains("has no case results"), "{error}");
+
+        let mut legacy: serde_json::Value = serde_json::to_value(EvalSummary::new(
+            Path::new("eval/cases.yaml"),
+            Some(&report(&[("greeting", true)])),
+            None,
+            None,
+        ))
+        .expect("serialize");
+        legacy
+            .as_object_mut()
+            .expect("object")
+            .remove("passed_case_ids");
+        fs::write(&path, legacy.to_string()).expect("legacy baseline should be written");
+        let error = load_eval_baseline(&path).expect_err("legacy baseline should be rejected");
+        assert!(
+            error.to_string().contains("does not list its passed cases"),
+            "{error}"
+        );
+        remove_dir_if_exists(&dir);
+    }
+}
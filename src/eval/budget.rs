@@ -0,0 +1,239 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow, ensure};
+use futures_util::StreamExt;
+use futures_util::stream::FuturesOrdered;
+use serde::{Deserialize, Deserializer};
+use tokio::sync::Semaphore;
+
+use super::fixtures::IsolatedNotesDir;
+use super::{EvalCase, EvalCaseResult, eval_case_error, run_eval_case};
+use crate::config::AgentSettings;
+use crate::http::SharedHttpClient;
+
+/// Parses `90`, `90s`, `15m`, or `1h` into a duration; bare numbers are seconds.
+pub(super) fn parse_duration_spec(raw: &str) -> Result<Duration> {
+    let trimmed = raw.trim();
+    let (digits, unit_secs) = if let Some(value) = trimmed.strip_suffix('h') {
+        (value, 3_600)
+    } else if let Some(value) = trimmed.strip_suffix('m') {
+        (value, 60)
+    } else if let Some(value) = trimmed.strip_suffix('s') {
+        (value, 1)
+    } else {
+        (trimmed, 1)
+    };
+    let amount = digits
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| anyhow!("invalid duration `{raw}`; use seconds or a s/m/h suffix"))?;
+    ensure!(amount > 0, "duration `{raw}` must be greater than zero");
+    Ok(Duration::from_secs(amount.saturating_mul(unit_secs)))
+}
+
+pub(super) fn deserialize_optional_duration<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawDuration {
+        Seconds(u64),
+        Text(String),
+    }
+
+    let raw = Option::<RawDuration>::deserialize(deserializer)?;
+    raw.map(|raw| match raw {
+        RawDuration::Seconds(seconds) => parse_duration_spec(&seconds.to_string()),
+        RawDuration::Text(text) => parse_duration_spec(&text),
+    })
+    .transpose()
+    .map_err(serde::de::Error::custom)
+}
+
+/// Runs `cases` with at most `concurrency` in flight, stopping once `max_total_duration`
+/// is spent. Cases in flight when the budget runs out are cut off, and they and every
+/// case not yet started are reported as skipped with the reason. Results are reported and
+/// returned in case order. Every case shares one HTTP client, so later cases reuse the
+/// provider connection instead of handshaking again.
+///
+/// Above a concurrency of 1, each case runs in its own copy of the notes directory so
+/// cases that save or delete notes cannot see each other's files.
+pub(super) async fn run_cases_within_budget<'a>(
+    settings: &AgentSettings,
+    cases: impl IntoIterator<Item = &'a EvalCase>,
+    max_total_duration: Option<Duration>,
+    concurrency: u32,
+    mut on_result: impl FnMut(&EvalCaseResult),
+) -> Vec<EvalCaseResult> {
+    let http_client = SharedHttpClient::from_settings(settings);
+    let started_at = Instant::now();
+    let permits = Semaphore::new(concurrency.max(1) as usize);
+    let exhausted_reason: Mutex<Option<String>> = Mutex::new(None);
+    let isolate_notes = concurrency > 1;
+
+    let run_case = |case: &'a EvalCase| {
+        let (http_client, permits, exhausted_reason) = (&http_client, &permits, &exhausted_reason);
+        async move {
+            let _permit = permits
+                .acquire()
+                .await
+                .expect("eval semaphore is never closed");
+            if let Some(reason) = exhausted_reason.lock().expect("budget lock").clone() {
+                return EvalCaseResult::skipped(case, &reason);
+            }
+            // Dropped after the case, including when the budget cuts it off.
+            let isolated = if isolate_notes {
+                match IsolatedNotesDir::create(settings) {
+                    Ok(isolated) => Some(isolated),
+                    Err(error) => {
+                        return eval_case_error(case, format!("setup failed: {error:#}"), None);
+                    }
+                }
+            } else {
+                None
+            };
+            let case_settings = isolated
+                .as_ref()
+                .map_or(settings, |isolated| &isolated.settings);
+            let Some(budget) = max_total_duration else {
+                return run_eval_case(case_settings, case, http_client).await;
+            };
+            let remaining = budget.saturating_sub(started_at.elapsed());
+            let outcome =
+                tokio::time::timeout(remaining, run_eval_case(case_settings, case, http_client))
+                    .await;
+            let mut exhausted_reason = exhausted_reason.lock().expect("budget lock");
+            match outcome {
+                Ok(result) if started_at.elapsed() < budget => result,
+                // Either cut off mid-case or finished exactly at the limit; a result
+                // that overran is kept since the turn already completed.
+                Ok(result) => {
+                    exhausted_reason.get_or_insert_with(|| budget_exceeded_reason(budget));
+                    result
+                }
+                Err(_) => {
+                    let reason = budget_exceeded_reason(budget);
+                    let skipped =
+                        EvalCaseResult::skipped(case, &format!("{reason}; aborted while running"));
+                    exhausted_reason.get_or_insert(reason);
+                    skipped
+                }
+            }
+        }
+    };
+
+    let mut pending = cases
+        .into_iter()
+        .map(run_case)
+        .collect::<FuturesOrdered<_>>();
+    let mut results = Vec::new();
+    while let Some(result) = pending.next().await {
+        on_result(&result);
+        results.push(result);
+    }
+    results
+}
+
+fn budget_exceeded_reason(budget: Duration) -> String {
+    format!("max_total_duration {}s exceeded", budget.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{parse_duration_spec, run_cases_within_budget};
+    use crate::agent::tests::test_settings;
+    use crate::bench::{load_test_settings, spawn_mock_model};
+    use crate::eval::{AnswerFormat, EvalCase, EvalSuite};
+
+    #[test]
+    fn parse_duration_spec_accepts_seconds_and_units() {
+        assert_eq!(parse_duration_spec("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration_spec("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(
+            parse_duration_spec(" 15m ").unwrap(),
+            Duration::from_secs(900)
+        );
+        assert_eq!(
+            parse_duration_spec("1h").unwrap(),
+            Duration::from_secs(3_600)
+        );
+        assert!(parse_duration_spec("0").is_err());
+        assert!(parse_duration_spec("ten minutes").is_err());
+
+        let suite: EvalSuite =
+            serde_yaml::from_str("max_total_duration: 10m\ncases:\n  - id: a\n    prompt: hi\n")
+                .expect("suite should parse");
+        assert_eq!(suite.max_total_duration, Some(Duration::from_secs(600)));
+    }
+
+    fn case(id: &str) -> EvalCase {
+        EvalCase {
+            id: id.to_owned(),
+            prompt: "hi".to_owned(),
+            required_tools: Vec::new(),
+            answer_format: AnswerFormat::PlainText,
+            csv_table: Default::default(),
+            answer_must_contain: Vec::new(),
+            answer_must_not_contain: Vec::new(),
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
+            no_invented_tool_output: false,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: Vec::new(),
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn exhausted_budget_skips_remaining_cases_with_reason() {
+        let mut settings = test_settings();
+        settings.ollama_base_url = "http://127.0.0.1:9".to_owned();
+        let cases = [case("first"), case("second")];
+
+        let results =
+            run_cases_within_budget(&settings, &cases, Some(Duration::ZERO), 1, |_| {}).await;
+
+        assert_eq!(results.len(), 2);
+        // The first case is either cut off or fails fast against the closed port; either
+        // way the budget is spent before the second one starts.
+        assert!(results.iter().all(|result| !result.passed));
+        assert_eq!(
+            results[1].skip_reason.as_deref(),
+            Some("max_total_duration 0s exceeded")
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_cases_overlap_and_report_in_case_order() {
+        let Ok(addr) = spawn_mock_model(Duration::from_millis(400)).await else {
+            eprintln!("skipping: local TCP bind is not permitted in this environment");
+            return;
+        };
+        let settings = load_test_settings(&test_settings(), &format!("http://{addr}"));
+        let cases = [case("first"), case("second"), case("third")];
+
+        let started_at = Instant::now();
+        let mut reported = Vec::new();
+        let results = run_cases_within_budget(&settings, &cases, None, 3, |result| {
+            reported.push(result.case_id.clone());
+        })
+        .await;
+
+        assert!(
+            started_at.elapsed() < Duration::from_millis(1_000),
+            "{:?}",
+            started_at.elapsed()
+        );
+        assert_eq!(reported, ["first", "second", "third"]);
+        assert!(results.iter().all(|result| result.passed), "{results:?}");
+    }
+}
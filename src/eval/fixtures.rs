@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result, ensure};
+use serde::Deserialize;
+
+use super::{copy_notes_fixtures, create_eval_notes_dir, remove_eval_notes_dir};
+use crate::config::{AgentSettings, SettingsOverride};
+
+/// Per-case preparation applied before the prompt runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalCaseSetup {
+    /// Notes written into the eval notes directory, keyed by file name.
+    #[serde(default)]
+    pub notes: BTreeMap<String, String>,
+    /// Settings overrides for this case only, keyed by env var name (e.g.
+    /// `FETCH_URL_ALLOWED_DOMAINS`).
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Per-case cleanup run after the case, even when it fails or is cut off.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalCaseTeardown {
+    /// Note files removed after the case, e.g. ones the case saved.
+    #[serde(default)]
+    pub remove_notes: Vec<String>,
+    /// Removes every file in the notes directory after the case.
+    #[serde(default)]
+    pub clear_notes: bool,
+}
+
+/// Checks note names and env overrides at load time so a typo fails the suite, not a case.
+pub(super) fn validate_case_fixtures(
+    case_id: &str,
+    setup: &EvalCaseSetup,
+    teardown: &EvalCaseTeardown,
+) -> Result<()> {
+    for name in setup.notes.keys().chain(&teardown.remove_notes) {
+        ensure!(
+            is_plain_note_name(name),
+            "case `{case_id}` note `{name}` must be a plain file name"
+        );
+    }
+    for (name, value) in &setup.env {
+        SettingsOverride::parse(name, value)
+            .with_context(|| format!("case `{case_id}` has an invalid setup.env override"))?;
+    }
+    Ok(())
+}
+
+fn is_plain_note_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+/// Returns the settings for one case with its `setup.env` overrides applied.
+pub(super) fn case_settings(
+    settings: &AgentSettings,
+    setup: &EvalCaseSetup,
+) -> Result<AgentSettings> {
+    let mut case_settings = settings.clone();
+    for (name, value) in &setup.env {
+        case_settings.apply_override(&SettingsOverride::parse(name, value)?);
+    }
+    Ok(case_settings)
+}
+
+/// Undoes a case's setup notes and runs its teardown when dropped, so cleanup also
+/// happens when a budget timeout cancels the case mid-turn.
+pub(super) struct CaseFixtureGuard {
+    notes_dir: PathBuf,
+    /// Setup notes with whatever they replaced, restored on drop.
+    replaced: Vec<(PathBuf, Option<Vec<u8>>)>,
+    teardown: EvalCaseTeardown,
+}
+
+impl CaseFixtureGuard {
+    pub(super) fn apply(
+        notes_dir: &Path,
+        setup: &EvalCaseSetup,
+        teardown: &EvalCaseTeardown,
+    ) -> Result<Self> {
+        let mut guard = Self {
+            notes_dir: notes_dir.to_path_buf(),
+            replaced: Vec::new(),
+            teardown: teardown.clone(),
+        };
+        if setup.notes.is_empty() {
+            return Ok(guard);
+        }
+        fs::create_dir_all(notes_dir).with_context(|| {
+            format!(
+                "failed to create eval notes directory `{}`",
+                notes_dir.display()
+            )
+        })?;
+        for (name, contents) in &setup.notes {
+            let path = notes_dir.join(name);
+            let previous = fs::read(&path).ok();
+            fs::write(&path, contents)
+                .with_context(|| format!("failed to write setup note `{}`", path.display()))?;
+            guard.replaced.push((path, previous));
+        }
+        Ok(guard)
+    }
+}
+
+impl Drop for CaseFixtureGuard {
+    fn drop(&mut self) {
+        for (path, previous) in self.replaced.drain(..).rev() {
+            let result = match previous {
+                Some(contents) => fs::write(&path, contents),
+                None => fs::remove_file(&path),
+            };
+            if let Err(error) = result {
+                eprintln!(
+                    "warning: failed to restore setup note `{}`: {error}",
+                    path.display()
+                );
+            }
+        }
+        for name in &self.teardown.remove_notes {
+            let path = self.notes_dir.join(name);
+            if let Err(error) = fs::remove_file(&path)
+                && error.kind() != std::io::ErrorKind::NotFound
+            {
+                eprintln!(
+                    "warning: failed to remove note `{}`: {error}",
+                    path.display()
+                );
+            }
+        }
+        if self.teardown.clear_notes {
+            clear_note_files(&self.notes_dir);
+        }
+    }
+}
+
+/// A private copy of the eval notes directory for one case of a concurrent run, removed
+/// when dropped.
+pub(super) struct IsolatedNotesDir {
+    /// The run settings with `notes_dir` pointing at the copy.
+    pub(super) settings: AgentSettings,
+}
+
+impl IsolatedNotesDir {
+    pub(super) fn create(settings: &AgentSettings) -> Result<Self> {
+        let path = create_eval_notes_dir()?;
+        let isolated = Self {
+            settings: AgentSettings {
+                notes_dir: path.display().to_string(),
+                ..settings.clone()
+            },
+        };
+        let shared = Path::new(&settings.notes_dir);
+        if shared.is_dir() {
+            copy_notes_fixtures(shared, &path)?;
+        }
+        Ok(isolated)
+    }
+}
+
+impl Drop for IsolatedNotesDir {
+    fn drop(&mut self) {
+        remove_eval_notes_dir(Path::new(&self.settings.notes_dir));
+    }
+}
+
+fn clear_note_files(notes_dir: &Path) {
+    let Ok(entries) = fs::read_dir(notes_dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_file()
+            && let Err(error) = fs::remove_file(&path)
+        {
+            eprintln!(
+                "warning: failed to remove note `{}`: {error}",
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{
+        CaseFixtureGuard, EvalCaseSetup, EvalCaseTeardown, IsolatedNotesDir, case_settings,
+        validate_case_fixtures,
+    };
+    use crate::agent::tests::test_settings;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn setup_notes_are_restored_and_teardown_removes_saved_notes() {
+        let notes_dir = temp_path("eval_case_fixtures");
+        fs::create_dir_all(&notes_dir).expect("notes dir should be created");
+        fs::write(notes_dir.join("shared.md"), "fixture").expect("fixture should be written");
+
+        let setup = EvalCaseSetup {
+            notes: [
+                ("shared.md".to_owned(), "case override".to_owned()),
+                ("rust.md".to_owned(), "Rust ownership notes".to_owned()),
+            ]
+            .into(),
+            env: Default::default(),
+        };
+        let teardown = EvalCaseTeardown {
+            remove_notes: vec!["saved.md".to_owned()],
+            clear_notes: false,
+        };
+
+        let guard =
+            CaseFixtureGuard::apply(&notes_dir, &setup, &teardown).expect("setup should apply");
+        assert_eq!(
+            fs::read_to_string(notes_dir.join("rust.md")).expect("setup note should exist"),
+            "Rust ownership notes"
+        );
+        fs::write(notes_dir.join("saved.md"), "saved by the case").expect("note should save");
+        drop(guard);
+
+        assert_eq!(
+            fs::read_to_string(notes_dir.join("shared.md")).expect("fixture should remain"),
+            "fixture"
+        );
+        assert!(!notes_dir.join("rust.md").exists());
+        assert!(!notes_dir.join("saved.md").exists());
+        remove_dir_if_exists(&notes_dir);
+    }
+
+    #[test]
+    fn isolated_notes_dirs_start_from_the_shared_notes_and_are_removed_on_drop() {
+        let shared = temp_path("eval_shared_notes");
+        fs::create_dir_all(&shared).expect("notes dir should be created");
+        fs::write(shared.join("shared.md"), "fixture").expect("fixture should be written");
+        let mut settings = test_settings();
+        settings.notes_dir = shared.display().to_string();
+
+        let first = IsolatedNotesDir::create(&settings).expect("first copy");
+        let second = IsolatedNotesDir::create(&settings).expect("second copy");
+        let first_dir = first.settings.notes_dir.clone();
+        assert_ne!(first_dir, second.settings.notes_dir);
+        fs::write(format!("{first_dir}/saved.md"), "saved by one case").expect("save note");
+        assert_eq!(
+            fs::read_to_string(format!("{}/shared.md", second.settings.notes_dir))
+                .expect("shared note is copied"),
+            "fixture"
+        );
+        assert!(!shared.join("saved.md").exists());
+
+        drop(first);
+        assert!(!std::path::Path::new(&first_dir).exists());
+        drop(second);
+        remove_dir_if_exists(&shared);
+    }
+
+    #[test]
+    fn case_settings_apply_env_overrides() {
+        let settings = test_settings();
+        let setup = EvalCaseSetup {
+            notes: Default::default(),
+            env: [(
+                "FETCH_URL_ALLOWED_DOMAINS".to_owned(),
+                "Docs.RS, example.com".to_owned(),
+            )]
+            .into(),
+        };
+
+        let overridden = case_settings(&settings, &setup).expect("override should apply");
+
+        assert_eq!(
+            overridden.fetch_url_allowed_domains,
+            ["docs.rs", "example.com"]
+        );
+        assert_eq!(overridden.notes_dir, settings.notes_dir);
+    }
+
+    #[test]
+    fn validate_case_fixtures_rejects_nested_notes_and_unknown_overrides() {
+        let teardown = EvalCaseTeardown::default();
+        let nested = EvalCaseSetup {
+            notes: [("../escape.md".to_owned(), String::new())].into(),
+            env: Default::default(),
+        };
+        let unknown = EvalCaseSetup {
+            notes: Default::default(),
+            env: [("MODEL".to_owned(), "other".to_owned())].into(),
+        };
+
+        let error = validate_case_fixtures("nested", &nested, &teardown)
+            .expect_err("nested note path should fail");
+        assert!(error.to_string().contains("must be a plain file name"));
+        let error = validate_case_fixtures("unknown", &unknown, &teardown)
+            .expect_err("unsupported override should fail");
+        assert!(format!("{error:#}").contains("MODEL cannot be overridden"));
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+const MONTHS: &str = "january|february|march|april|may|june|july|august|september|october|november|december|jan|feb|mar|apr|jun|jul|aug|sept|sep|oct|nov|dec";
+
+static ISO_DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(\d{4})[-/](\d{1,2})[-/](\d{1,2})\b").expect("valid ISO date regex")
+});
+static MONTH_DAY_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?i)\b({MONTHS})\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?,?\s+(\d{{4}})\b"
+    ))
+    .expect("valid month-day-year regex")
+});
+static DAY_MONTH_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?i)\b(\d{{1,2}})(?:st|nd|rd|th)?\s+(?:of\s+)?({MONTHS})\.?,?\s+(\d{{4}})\b"
+    ))
+    .expect("valid day-month-year regex")
+});
+static MONTH_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"(?i)\b({MONTHS})\.?,?\s+(\d{{4}})\b")).expect("valid month-year regex")
+});
+
+/// Words that start sentences or name calendar units often enough that a capitalized
+/// occurrence says nothing about invented entities.
+const NON_ENTITY_WORDS: &[&str] = &[
+    "a",
+    "an",
+    "the",
+    "i",
+    "it",
+    "this",
+    "that",
+    "these",
+    "those",
+    "and",
+    "or",
+    "but",
+    "in",
+    "on",
+    "at",
+    "for",
+    "of",
+    "to",
+    "by",
+    "with",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// A calendar date mentioned in text; `day` is `None` for month-year mentions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct CalendarDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: Option<u8>,
+}
+
+impl CalendarDate {
+    fn new(year: &str, month: u8, day: Option<&str>) -> Option<Self> {
+        let year = year.parse().ok()?;
+        let day = match day {
+            Some(day) => Some(
+                day.parse::<u8>()
+                    .ok()
+                    .filter(|day| (1..=31).contains(day))?,
+            ),
+            None => None,
+        };
+        (1..=12)
+            .contains(&month)
+            .then_some(Self { year, month, day })
+    }
+
+    /// Whether a mention of `self` is backed by `known`; a month-year mention is backed by
+    /// any known date in that month.
+    pub fn is_backed_by(&self, known: &CalendarDate) -> bool {
+        self.year == known.year
+            && self.month == known.month
+            && (self.day.is_none() || self.day == known.day)
+    }
+}
+
+type DateParser = fn(&Captures<'_>) -> Option<CalendarDate>;
+
+/// Dates in ISO (`2015-05-15`, `2015/5/15`) and common textual forms (`May 15, 2015`,
+/// `15th of May 2015`, `Sept. 2015`), each with the text it was written as, plus `text`
+/// with those mentions blanked out so numeric checks do not report them twice.
+pub(super) fn extract_dates(text: &str) -> (Vec<(String, CalendarDate)>, String) {
+    let mut dates = Vec::new();
+    let mut remainder = text.to_owned();
+    let patterns: [(&Regex, DateParser); 4] = [
+        (&ISO_DATE, |captures| {
+            CalendarDate::new(&captures[1], captures[2].parse().ok()?, Some(&captures[3]))
+        }),
+        (&MONTH_DAY_YEAR, |captures| {
+            CalendarDate::new(
+                &captures[3],
+                month_number(&captures[1])?,
+                Some(&captures[2]),
+            )
+        }),
+        (&DAY_MONTH_YEAR, |captures| {
+            CalendarDate::new(
+                &captures[3],
+                month_number(&captures[2])?,
+                Some(&captures[1]),
+            )
+        }),
+        (&MONTH_YEAR, |captures| {
+            CalendarDate::new(&captures[2], month_number(&captures[1])?, None)
+        }),
+    ];
+    for (pattern, parse) in patterns {
+        let mut spans = Vec::new();
+        for captures in pattern.captures_iter(&remainder) {
+            let whole = captures.get(0).expect("match has a whole capture");
+            if let Some(date) = parse(&captures) {
+                dates.push((whole.as_str().to_owned(), date));
+                spans.push(whole.range());
+            }
+        }
+        // Blank rather than remove, so the later, looser patterns cannot re-match a
+        // fragment and byte offsets of the remaining spans stay valid.
+        for span in spans.into_iter().rev() {
+            let blank = " ".repeat(span.len());
+            remainder.replace_range(span, &blank);
+        }
+    }
+    (dates, remainder)
+}
+
+/// 1-based month for a full or abbreviated English month name.
+fn month_number(name: &str) -> Option<u8> {
+    let name = name.to_ascii_lowercase();
+    let months = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    months
+        .iter()
+        .position(|month| {
+            *month == name || (month[..3] == name) || (*month == "september" && name == "sept")
+        })
+        .map(|index| index as u8 + 1)
+}
+
+/// Runs of capitalized words that look like names (`Graydon Hoare`, `Mozilla Research`).
+/// A single capitalized word opening a sentence, list item, or line is skipped, as are
+/// all-caps acronyms and month names, which other checks cover.
+pub(super) fn extract_capitalized_entities(text: &str) -> BTreeSet<String> {
+    let mut entities = BTreeSet::new();
+    for line in text.lines() {
+        let mut run: Vec<&str> = Vec::new();
+        let mut run_opens_sentence = false;
+        let mut sentence_start = true;
+        for raw in line.split_whitespace() {
+            let word = raw.trim_matches(|ch: char| !ch.is_alphanumeric());
+            if word.is_empty() {
+                // Bullets and other bare punctuation keep the sentence-start state.
+                continue;
+            }
+            if is_entity_word(word) {
+                if run.is_empty() {
+                    run_opens_sentence = sentence_start;
+                }
+                run.push(word);
+            } else {
+                push_entity(&mut entities, &run, run_opens_sentence);
+                run.clear();
+            }
+            sentence_start = false;
+            let trailing = raw.trim_end_matches(['"', '\'', ')', ']', '*', '_']);
+            if trailing.ends_with(['.', '!', '?', ':', ';', ',']) {
+                push_entity(&mut entities, &run, run_opens_sentence);
+                run.clear();
+                sentence_start = !trailing.ends_with(',');
+            }
+        }
+        push_entity(&mut entities, &run, run_opens_sentence);
+    }
+    entities
+}
+
+fn is_entity_word(word: &str) -> bool {
+    let starts_upper = word.chars().next().is_some_and(char::is_uppercase);
+    let has_lower = word.chars().any(char::is_lowercase);
+    starts_upper
+        && has_lower
+        && !NON_ENTITY_WORDS.contains(&word.to_lowercase().as_str())
+        && month_number(word).is_none()
+}
+
+fn push_entity(entities: &mut BTreeSet<String>, run: &[&str], opens_sentence: bool) {
+    if run.is_empty() || (run.len() == 1 && opens_sentence) {
+        return;
+    }
+    let entity = run.join(" ");
+    if entity.chars().count() >= 3 {
+        entities.insert(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CalendarDate, extract_capitalized_entities, extract_dates};
+
+    fn date(year: u16, month: u8, day: Option<u8>) -> CalendarDate {
+        CalendarDate { year, month, day }
+    }
+
+    #[test]
+    fn dates_are_found_in_iso_and_textual_forms() {
+        let (dates, remainder) = extract_dates(
+            "Released 2015-05-15 (May 15, 2015); patch on 3rd of June 2015, roadmap for Sept. 2016.",
+        );
+        assert_eq!(
+            dates.iter().map(|(_, date)| *date).collect::<Vec<_>>(),
+            vec![
+                date(2015, 5, Some(15)),
+                date(2015, 5, Some(15)),
+                date(2015, 6, Some(3)),
+                date(2016, 9, None),
+            ]
+        );
+        assert_eq!(dates[3].0, "Sept. 2016");
+        assert!(!remainder.contains("2015") && !remainder.contains("2016"));
+
+        let (dates, _) = extract_dates("Version 2015-13-40 and mayor 2015 are not dates.");
+        assert!(dates.is_empty(), "{dates:?}");
+
+        assert!(date(2015, 5, None).is_backed_by(&date(2015, 5, Some(15))));
+        assert!(!date(2015, 5, Some(16)).is_backed_by(&date(2015, 5, Some(15))));
+    }
+
+    #[test]
+    fn capitalized_entities_skip_sentence_openers_and_acronyms() {
+        let entities = extract_capitalized_entities(
+            "Rust was started by Graydon Hoare at Mozilla Research. The JSON API is stable.\n- Summary: built by Ferris, then Niko Matsakis joined in May.",
+        );
+        assert_eq!(
+            entities.into_iter().collect::<Vec<_>>(),
+            vec![
+                "Ferris",
+                "Graydon Hoare",
+                "Mozilla Research",
+                "Niko Matsakis"
+            ]
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use super::AnswerPattern;
+
+/// One `json_assertions` entry: the value at `path` must exist and satisfy every
+/// configured predicate.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonAssertion {
+    pub path: JsonAnswerPath,
+    /// Exact value, compared as JSON (`1` and `1.0` differ; `null` is allowed).
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub equals: Option<Value>,
+    #[serde(default, rename = "type")]
+    pub value_type: Option<JsonValueType>,
+    /// Regex a string value must match.
+    #[serde(default)]
+    pub matches: Option<AnswerPattern>,
+    /// Lower bound for numbers, or for the length of strings and arrays.
+    #[serde(default)]
+    pub min: Option<f64>,
+    /// Upper bound for numbers, or for the length of strings and arrays.
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonValueType {
+    Null,
+    Boolean,
+    Number,
+    Integer,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonValueType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(number) if number.is_i64() || number.is_u64() => Self::Integer,
+            Value::Number(_) => Self::Number,
+            Value::String(_) => Self::String,
+            Value::Array(_) => Self::Array,
+            Value::Object(_) => Self::Object,
+        }
+    }
+
+    fn accepts(self, value: &Value) -> bool {
+        let actual = Self::of(value);
+        actual == self || (self == Self::Number && actual == Self::Integer)
+    }
+}
+
+/// A location in the answer, written as a JSON pointer (`/items/0/name`) or a
+/// dot/bracket JSONPath (`$.items[0].name`, `$['odd key']`). Wildcards and filters are
+/// not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonAnswerPath {
+    raw: String,
+    segments: Vec<String>,
+}
+
+impl JsonAnswerPath {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let segments = if raw.is_empty() {
+            Vec::new()
+        } else if let Some(pointer) = raw.strip_prefix('/') {
+            pointer
+                .split('/')
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                .collect()
+        } else if let Some(path) = raw.strip_prefix('$') {
+            parse_json_path_segments(path)
+                .map_err(|reason| format!("invalid JSONPath `{raw}`: {reason}"))?
+        } else {
+            return Err(format!(
+                "invalid path `{raw}`: expected a JSON pointer starting with `/` or a JSONPath starting with `$`"
+            ));
+        };
+        Ok(Self {
+            raw: raw.to_owned(),
+            segments,
+        })
+    }
+
+    fn resolve<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(root, |value, segment| match value {
+                Value::Object(map) => map.get(segment),
+                Value::Array(items) => segment
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get(index)),
+                _ => None,
+            })
+    }
+}
+
+impl fmt::Display for JsonAnswerPath {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.raw.is_empty() {
+            formatter.write_str("$")
+        } else {
+            formatter.write_str(&self.raw)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonAnswerPath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        Self::parse(raw.trim()).map_err(serde::de::Error::custom)
+    }
+}
+
+fn parse_json_path_segments(path: &str) -> Result<Vec<String>, String> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let key = &after_dot[..end];
+            if key.is_empty() || key == "*" {
+                return Err("expected a field name after `.`".to_owned());
+            }
+            segments.push(key.to_owned());
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket
+                .find(']')
+                .ok_or_else(|| "unclosed `[`".to_owned())?;
+            let inner = after_bracket[..end].trim();
+            let quoted = ['\'', '"'].into_iter().find_map(|quote| {
+                inner
+                    .strip_prefix(quote)
+                    .and_then(|key| key.strip_suffix(quote))
+            });
+            match quoted {
+                Some(key) => segments.push(key.to_owned()),
+                None if !inner.is_empty() && inner.bytes().all(|byte| byte.is_ascii_digit()) => {
+                    segments.push(inner.to_owned());
+                }
+                None => {
+                    return Err(format!(
+                        "`[{inner}]` must be an array index or a quoted field name"
+                    ));
+                }
+            }
+            rest = &after_bracket[end + 1..];
+        } else {
+            return Err(format!("unexpected `{rest}`"));
+        }
+    }
+    Ok(segments)
+}
+
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+impl JsonAssertion {
+    /// Why the assertion fails against `answer`, or `None` when it holds.
+    pub(super) fn failure(&self, answer: &Value) -> Option<String> {
+        let path = &self.path;
+        let Some(value) = path.resolve(answer) else {
+            return Some(format!("{path} is missing"));
+        };
+        if let Some(expected) = &self.equals
+            && value != expected
+        {
+            return Some(format!("{path} is {value}, expected {expected}"));
+        }
+        if let Some(expected) = self.value_type
+            && !expected.accepts(value)
+        {
+            return Some(format!(
+                "{path} is {}, expected {}",
+                JsonValueType::of(value).as_str(),
+                expected.as_str()
+            ));
+        }
+        if let Some(pattern) = &self.matches {
+            match value.as_str() {
+                Some(text) if pattern.is_match(text) => {}
+                Some(_) => {
+                    return Some(format!(
+                        "{path} is {value}, which does not match `{}`",
+                        pattern.as_str()
+                    ));
+                }
+                None => return Some(format!("{path} is not a string, so `matches` cannot apply")),
+            }
+        }
+        if self.min.is_some() || self.max.is_some() {
+            let (measure, what) = match value {
+                Value::Number(number) => (number.as_f64().unwrap_or_default(), "value"),
+                Value::String(text) => (text.chars().count() as f64, "length"),
+                Value::Array(items) => (items.len() as f64, "length"),
+                _ => {
+                    return Some(format!(
+                        "{path} is {}, so `min`/`max` cannot apply",
+                        JsonValueType::of(value).as_str()
+                    ));
+                }
+            };
+            if let Some(min) = self.min
+                && measure < min
+            {
+                return Some(format!("{path} {what} {measure} is below min {min}"));
+            }
+            if let Some(max) = self.max
+                && measure > max
+            {
+                return Some(format!("{path} {what} {measure} is above max {max}"));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{JsonAnswerPath, JsonAssertion};
+
+    fn assertion(yaml: &str) -> JsonAssertion {
+        serde_yaml::from_str(yaml).expect("assertion should parse")
+    }
+
+    #[test]
+    fn pointer_and_json_path_forms_resolve_the_same_value() {
+        let answer = json!({"items": [{"name": "rust", "a/b": 1}], "odd key": true});
+        for raw in [
+            "/items/0/name",
+            "$.items[0].name",
+            "$['items'][0][\"name\"]",
+        ] {
+            let path = JsonAnswerPath::parse(raw).expect("path should parse");
+            assert_eq!(path.resolve(&answer), Some(&json!("rust")), "{raw}");
+        }
+        let escaped = JsonAnswerPath::parse("/items/0/a~1b").expect("pointer");
+        assert_eq!(escaped.resolve(&answer), Some(&json!(1)));
+        let root = JsonAnswerPath::parse("$").expect("root");
+        assert_eq!(root.resolve(&answer), Some(&answer));
+
+        for invalid in ["items.name", "$.items[*]", "$.items[0", "$..name"] {
+            assert!(JsonAnswerPath::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn assertions_report_the_first_failed_predicate() {
+        let answer =
+            json!({"status": "ok", "count": 3, "tags": ["a"], "date": "2015-05-15", "gone": null});
+
+        assert_eq!(
+            assertion("path: /status\nequals: ok").failure(&answer),
+            None
+        );
+        assert_eq!(
+            assertion("path: /gone\nequals: null").failure(&answer),
+            None
+        );
+        assert_eq!(
+            assertion("path: $.count\ntype: number\nmin: 1\nmax: 5").failure(&answer),
+            None
+        );
+        assert_eq!(
+            assertion("path: /date\nmatches: '^\\d{4}-\\d{2}-\\d{2}$'").failure(&answer),
+            None
+        );
+
+        let failures = [
+            ("path: /missing", "/missing is missing"),
+            (
+                "path: /status\nequals: done",
+                "/status is \"ok\", expected \"done\"",
+            ),
+            (
+                "path: /count\ntype: string",
+                "/count is integer, expected string",
+            ),
+            ("path: $.tags\nmin: 2", "$.tags length 1 is below min 2"),
+            (
+                "path: /count\nmatches: '\\d'",
+                "/count is not a string, so `matches` cannot apply",
+            ),
+        ];
+        for (yaml, expected) in failures {
+            assert_eq!(
+                assertion(yaml).failure(&answer).as_deref(),
+                Some(expected),
+                "{yaml}"
+            );
+        }
+    }
+}
@@ -1,18 +1,43 @@
 use std::collections::{BTreeSet, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow, ensure};
-use serde::Deserialize;
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
 
-use crate::agent::{ChatTurnOutcome, run_chat_turn};
+use crate::agent::{ChatSession, ChatTurnOutcome, ErrorCode};
 use crate::answer_format::{
-    StructuredAnswerFormat, StructuredAnswerFormatError, validate_structured_answer_format,
+    CsvTableRules, StructuredAnswerFormat, StructuredAnswerFormatError, TableDelimiter,
+    validate_csv_table, validate_structured_answer_format,
 };
 use crate::config::AgentSettings;
+use crate::http::SharedHttpClient;
+use crate::json_schema::check_schema;
+use crate::preflight::run_model_preflight;
 use crate::test_support::temp_path;
 use crate::tools::tool_definitions;
 
+mod baseline;
+mod budget;
+mod fixtures;
+mod grounding;
+mod json_assertions;
+mod summary;
+mod watch;
+
+use self::baseline::{BaselineComparison, load_eval_baseline};
+use self::budget::run_cases_within_budget;
+use self::fixtures::{CaseFixtureGuard, case_settings, validate_case_fixtures};
+pub use self::fixtures::{EvalCaseSetup, EvalCaseTeardown};
+use self::grounding::{extract_capitalized_entities, extract_dates};
+pub use self::json_assertions::{JsonAnswerPath, JsonAssertion, JsonValueType};
+pub use self::summary::DEFAULT_EVAL_SUMMARY_PATH;
+use self::summary::{EvalSummary, write_eval_summary};
+pub use self::watch::run_eval_watch_command;
+
 pub const DEFAULT_EVAL_CASES_PATH: &str = "eval/cases.yaml";
 const DEFAULT_TARGET_PASS_RATE: f64 = 0.80;
 
@@ -21,6 +46,13 @@ const DEFAULT_TARGET_PASS_RATE: f64 = 0.80;
 pub struct EvalSuite {
     #[serde(default = "default_target_pass_rate")]
     pub target_pass_rate: f64,
+    /// Directory (relative to the cases file) whose files seed the eval notes directory.
+    #[serde(default)]
+    pub notes_fixtures_dir: Option<String>,
+    /// Wall-clock budget for the whole run (`90`, `90s`, `15m`, `1h`); cases left when it
+    /// runs out are skipped.
+    #[serde(default, deserialize_with = "budget::deserialize_optional_duration")]
+    pub max_total_duration: Option<Duration>,
     pub cases: Vec<EvalCase>,
 }
 
@@ -33,12 +65,83 @@ pub struct EvalCase {
     pub required_tools: Vec<String>,
     #[serde(default)]
     pub answer_format: AnswerFormat,
+    /// Table shape checked when `answer_format` is `csv_table`.
+    #[serde(default)]
+    pub csv_table: CsvTableExpectation,
     #[serde(default)]
     pub answer_must_contain: Vec<String>,
     #[serde(default)]
     pub answer_must_not_contain: Vec<String>,
+    /// Regexes the answer must match, such as `\d{4}-\d{2}-\d{2}` for an ISO date.
+    #[serde(default)]
+    pub answer_must_match: Vec<AnswerPattern>,
+    #[serde(default)]
+    pub answer_must_not_match: Vec<AnswerPattern>,
     #[serde(default)]
     pub no_invented_tool_output: bool,
+    /// Tokens the grounding check treats as if a tool had returned them, such as names
+    /// the model is expected to know.
+    #[serde(default)]
+    pub grounding_allowlist: Vec<String>,
+    #[serde(default)]
+    pub expected_tool_arguments: Vec<ExpectedToolArguments>,
+    /// Field-level checks against the parsed answer of a `json_object` case.
+    #[serde(default)]
+    pub json_assertions: Vec<JsonAssertion>,
+    /// Schema a `json_schema` answer must match; the agent is also given it in the system
+    /// prompt.
+    #[serde(default)]
+    pub json_schema: Option<Value>,
+    #[serde(default)]
+    pub setup: EvalCaseSetup,
+    #[serde(default)]
+    pub teardown: EvalCaseTeardown,
+}
+
+/// `answer_must_match`/`answer_must_not_match` regex, compiled when the suite is parsed so
+/// an invalid pattern fails the load instead of the case. Case-sensitive unless the
+/// pattern starts with `(?i)`.
+#[derive(Debug, Clone)]
+pub struct AnswerPattern(Regex);
+
+impl AnswerPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(Self)
+            .with_context(|| format!("invalid answer regex `{pattern}`"))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    fn is_match(&self, answer: &str) -> bool {
+        self.0.is_match(answer)
+    }
+}
+
+impl PartialEq for AnswerPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<'de> Deserialize<'de> for AnswerPattern {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pattern = String::deserialize(deserializer)?;
+        Self::new(&pattern).map_err(|error| serde::de::Error::custom(format!("{error:#}")))
+    }
+}
+
+/// Requires at least one `tool` call whose arguments contain every key/value in `arguments`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedToolArguments {
+    pub tool: String,
+    pub arguments: Value,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
@@ -48,6 +151,38 @@ pub enum AnswerFormat {
     PlainText,
     JsonObject,
     MarkdownBullets,
+    CsvTable,
+    JsonSchema,
+}
+
+/// `csv_table` case settings: `delimiter` (`comma` or `tab`), an optional exact `columns`
+/// count, and optional expected `header` names.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CsvTableExpectation {
+    #[serde(default)]
+    pub delimiter: TableDelimiter,
+    #[serde(default)]
+    pub columns: Option<usize>,
+    #[serde(default)]
+    pub header: Vec<String>,
+}
+
+impl CsvTableExpectation {
+    fn rules(&self) -> CsvTableRules<'_> {
+        CsvTableRules {
+            delimiter: self.delimiter,
+            columns: self.columns,
+            header: &self.header,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self.delimiter {
+            TableDelimiter::Comma => "CSV",
+            TableDelimiter::Tab => "TSV",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,8 +198,27 @@ pub struct EvalCaseResult {
     pub passed: bool,
     pub checks: Vec<EvalCheckResult>,
     pub error: Option<String>,
+    /// Set when the chat turn itself failed.
+    pub error_code: Option<ErrorCode>,
     pub final_text: Option<String>,
     pub used_tools: Vec<String>,
+    /// Set when the case did not run to completion because a run budget was exhausted.
+    pub skip_reason: Option<String>,
+}
+
+impl EvalCaseResult {
+    fn skipped(case: &EvalCase, reason: &str) -> Self {
+        Self {
+            case_id: case.id.clone(),
+            passed: false,
+            checks: Vec::new(),
+            error: None,
+            error_code: None,
+            final_text: None,
+            used_tools: Vec::new(),
+            skip_reason: Some(reason.to_owned()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -73,6 +227,7 @@ pub struct EvalRunReport {
     pub total_cases: usize,
     pub passed_cases: usize,
     pub failed_cases: usize,
+    pub skipped_cases: usize,
     pub pass_rate: f64,
     pub target_pass_rate: f64,
     pub case_results: Vec<EvalCaseResult>,
@@ -91,46 +246,211 @@ pub fn load_eval_suite(path: &Path) -> Result<EvalSuite> {
     Ok(suite)
 }
 
-pub async fn run_eval_suite(settings: &AgentSettings, cases_path: &Path) -> Result<EvalRunReport> {
+/// Resolves `notes_fixtures_dir` against the directory containing the cases file.
+pub fn notes_fixtures_path(suite: &EvalSuite, cases_path: &Path) -> Option<PathBuf> {
+    let fixtures_dir = suite.notes_fixtures_dir.as_deref()?;
+    let base = cases_path.parent().unwrap_or_else(|| Path::new(""));
+    Some(base.join(fixtures_dir))
+}
+
+/// Copies the top-level files of `fixtures_dir` into `notes_dir`, matching the flat
+/// layout `search_notes` reads.
+fn copy_notes_fixtures(fixtures_dir: &Path, notes_dir: &Path) -> Result<()> {
+    let entries = fs::read_dir(fixtures_dir).with_context(|| {
+        format!(
+            "failed to read notes fixtures directory `{}`",
+            fixtures_dir.display()
+        )
+    })?;
+    for entry in entries {
+        let entry = entry.with_context(|| {
+            format!(
+                "failed to list notes fixtures directory `{}`",
+                fixtures_dir.display()
+            )
+        })?;
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        fs::copy(&path, notes_dir.join(entry.file_name()))
+            .with_context(|| format!("failed to copy notes fixture `{}`", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Runs every case of the suite at `cases_path`, at most `concurrency` at a time.
+pub async fn run_eval_suite(
+    settings: &AgentSettings,
+    cases_path: &Path,
+    concurrency: u32,
+) -> Result<EvalRunReport> {
+    ensure!(concurrency > 0, "--concurrency must be greater than zero");
     let suite = load_eval_suite(cases_path)?;
-    let mut case_results = Vec::with_capacity(suite.cases.len());
+    if let Some(fixtures_dir) = notes_fixtures_path(&suite, cases_path) {
+        copy_notes_fixtures(&fixtures_dir, Path::new(&settings.notes_dir))?;
+    }
+    let case_results = run_cases_within_budget(
+        settings,
+        &suite.cases,
+        suite.max_total_duration,
+        concurrency,
+        |_| {},
+    )
+    .await;
+
+    Ok(EvalRunReport::from_case_results(
+        cases_path,
+        suite.target_pass_rate,
+        case_results,
+    ))
+}
 
-    for case in &suite.cases {
-        case_results.push(run_eval_case(settings, case).await);
+impl EvalRunReport {
+    fn from_case_results(
+        cases_path: &Path,
+        target_pass_rate: f64,
+        case_results: Vec<EvalCaseResult>,
+    ) -> Self {
+        let passed_cases = case_results.iter().filter(|result| result.passed).count();
+        let skipped_cases = case_results
+            .iter()
+            .filter(|result| result.skip_reason.is_some())
+            .count();
+        let total_cases = case_results.len();
+        let failed_cases = total_cases.saturating_sub(passed_cases + skipped_cases);
+        let pass_rate = if total_cases == 0 {
+            0.0
+        } else {
+            passed_cases as f64 / total_cases as f64
+        };
+
+        Self {
+            cases_path: cases_path.to_path_buf(),
+            total_cases,
+            passed_cases,
+            failed_cases,
+            skipped_cases,
+            pass_rate,
+            target_pass_rate,
+            case_results,
+        }
     }
 
-    let passed_cases = case_results.iter().filter(|result| result.passed).count();
-    let total_cases = case_results.len();
-    let failed_cases = total_cases.saturating_sub(passed_cases);
-    let pass_rate = if total_cases == 0 {
-        0.0
-    } else {
-        passed_cases as f64 / total_cases as f64
-    };
+    fn meets_target(&self) -> bool {
+        self.pass_rate + f64::EPSILON >= self.target_pass_rate
+    }
+}
 
-    Ok(EvalRunReport {
-        cases_path: cases_path.to_path_buf(),
-        total_cases,
-        passed_cases,
-        failed_cases,
-        pass_rate,
-        target_pass_rate: suite.target_pass_rate,
-        case_results,
-    })
+fn print_case_result(case: &EvalCaseResult) {
+    if case.passed {
+        println!("[PASS] {}", case.case_id);
+        return;
+    }
+    if let Some(reason) = &case.skip_reason {
+        println!("[SKIP] {}", case.case_id);
+        println!("  reason: {reason}");
+        return;
+    }
+
+    println!("[FAIL] {}", case.case_id);
+    match (&case.error, case.error_code) {
+        (Some(error), Some(code)) => println!("  error [{code}]: {error}"),
+        (Some(error), None) => println!("  error: {error}"),
+        (None, _) => {}
+    }
+    for check in case.checks.iter().filter(|check| !check.passed) {
+        println!("  check `{}`: {}", check.name, check.detail);
+    }
 }
 
-pub async fn run_eval_command(settings: &AgentSettings, cases_path: &Path) -> Result<()> {
-    let mut eval_settings = settings.clone();
-    let eval_notes_dir = create_eval_notes_dir()?;
-    eval_settings.notes_dir = eval_notes_dir.display().to_string();
+fn print_report_summary(report: &EvalRunReport) {
+    println!(
+        "Summary: {} passed, {} failed, {} skipped, pass rate {:.1}% (target {:.1}%)",
+        report.passed_cases,
+        report.failed_cases,
+        report.skipped_cases,
+        report.pass_rate * 100.0,
+        report.target_pass_rate * 100.0
+    );
+}
 
-    let report_result = run_eval_suite(&eval_settings, cases_path).await;
-    if let Err(error) = fs::remove_dir_all(&eval_notes_dir) {
+fn remove_eval_notes_dir(eval_notes_dir: &Path) {
+    if let Err(error) = fs::remove_dir_all(eval_notes_dir) {
         eprintln!(
             "warning: failed to remove eval notes directory `{}`: {error}",
             eval_notes_dir.display()
         );
     }
+}
+
+/// Runs the suite, `concurrency` cases at a time, and prints per-case results. With
+/// `preflight`, provider health is checked first so setup problems fail once instead of
+/// failing every case. A JSON summary is written to `summary_path` however the run ends.
+/// With `baseline_path`, the run is diffed against that earlier summary and fails only on
+/// regressions instead of on the pass-rate target.
+pub async fn run_eval_command(
+    settings: &AgentSettings,
+    cases_path: &Path,
+    preflight: bool,
+    concurrency: u32,
+    summary_path: &Path,
+    baseline_path: Option<&Path>,
+) -> Result<()> {
+    let baseline = baseline_path
+        .map(|path| load_eval_baseline(path).map(|baseline| (path, baseline)))
+        .transpose();
+    let run = match baseline {
+        Ok(baseline) => run_and_print_eval(settings, cases_path, preflight, concurrency)
+            .await
+            .map(|report| (report, baseline)),
+        Err(error) => Err(error),
+    };
+    let (report, comparison, result) = match run {
+        Ok((report, Some((baseline_path, baseline)))) => {
+            let comparison = BaselineComparison::new(baseline_path, &baseline, &report);
+            comparison.print();
+            let result = comparison.check();
+            (Some(report), Some(comparison), result)
+        }
+        Ok((report, None)) => {
+            let result = check_eval_report(&report);
+            (Some(report), None, result)
+        }
+        Err(error) => (None, None, Err(error)),
+    };
+
+    let summary = EvalSummary::new(
+        cases_path,
+        report.as_ref(),
+        result.as_ref().err(),
+        comparison,
+    );
+    let written = write_eval_summary(summary_path, &summary);
+    match (result, written) {
+        (Err(error), Err(write_error)) => {
+            eprintln!("warning: {write_error:#}");
+            Err(error)
+        }
+        (result, written) => result.and(written),
+    }
+}
+
+async fn run_and_print_eval(
+    settings: &AgentSettings,
+    cases_path: &Path,
+    preflight: bool,
+    concurrency: u32,
+) -> Result<EvalRunReport> {
+    if preflight {
+        run_model_preflight(settings).await?;
+    }
+    let mut eval_settings = settings.clone();
+    let eval_notes_dir = create_eval_notes_dir()?;
+    eval_settings.notes_dir = eval_notes_dir.display().to_string();
+
+    let report_result = run_eval_suite(&eval_settings, cases_path, concurrency).await;
+    remove_eval_notes_dir(&eval_notes_dir);
     let report = report_result?;
 
     println!(
@@ -139,32 +459,24 @@ pub async fn run_eval_command(settings: &AgentSettings, cases_path: &Path) -> Re
         report.cases_path.display()
     );
     for case in &report.case_results {
-        if case.passed {
-            println!("[PASS] {}", case.case_id);
-            continue;
-        }
-
-        println!("[FAIL] {}", case.case_id);
-        if let Some(error) = &case.error {
-            println!("  error: {error}");
-        }
-        for check in case.checks.iter().filter(|check| !check.passed) {
-            println!("  check `{}`: {}", check.name, check.detail);
-        }
+        print_case_result(case);
     }
+    print_report_summary(&report);
+    Ok(report)
+}
 
-    let pass_rate_percent = report.pass_rate * 100.0;
-    let target_percent = report.target_pass_rate * 100.0;
-    println!(
-        "Summary: {} passed, {} failed, pass rate {:.1}% (target {:.1}%)",
-        report.passed_cases, report.failed_cases, pass_rate_percent, target_percent
-    );
-
-    if report.pass_rate + f64::EPSILON < report.target_pass_rate {
+fn check_eval_report(report: &EvalRunReport) -> Result<()> {
+    if report.skipped_cases > 0 {
+        return Err(anyhow!(
+            "evaluation aborted: {} case(s) skipped after the run budget was exhausted",
+            report.skipped_cases
+        ));
+    }
+    if !report.meets_target() {
         return Err(anyhow!(
             "evaluation pass rate {:.1}% is below target {:.1}%",
-            pass_rate_percent,
-            target_percent
+            report.pass_rate * 100.0,
+            report.target_pass_rate * 100.0
         ));
     }
 
@@ -180,17 +492,49 @@ fn create_eval_notes_dir() -> Result<PathBuf> {
     Ok(path)
 }
 
-async fn run_eval_case(settings: &AgentSettings, case: &EvalCase) -> EvalCaseResult {
-    match run_chat_turn(settings, &case.prompt).await {
+async fn run_eval_case(
+    settings: &AgentSettings,
+    case: &EvalCase,
+    http_client: &SharedHttpClient,
+) -> EvalCaseResult {
+    let prepared = case_settings(settings, &case.setup).and_then(|case_settings| {
+        let guard = CaseFixtureGuard::apply(
+            Path::new(&case_settings.notes_dir),
+            &case.setup,
+            &case.teardown,
+        )?;
+        Ok((case_settings, guard))
+    });
+    // The guard lives until the end of this function, so teardown runs after the turn.
+    let (case_settings, _guard) = match prepared {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            return eval_case_error(case, format!("setup failed: {error:#}"), None);
+        }
+    };
+
+    let mut session = ChatSession::with_http_client(&case_settings, http_client.clone())
+        .with_answer_schema(case.json_schema.clone());
+    match session.chat_turn(&case.prompt).await {
         Ok(outcome) => evaluate_case_outcome(case, &outcome),
-        Err(error) => EvalCaseResult {
-            case_id: case.id.clone(),
-            passed: false,
-            checks: Vec::new(),
-            error: Some(error.to_string()),
-            final_text: None,
-            used_tools: Vec::new(),
-        },
+        Err(error) => eval_case_error(case, error.details(), Some(error.code())),
+    }
+}
+
+fn eval_case_error(
+    case: &EvalCase,
+    error: String,
+    error_code: Option<ErrorCode>,
+) -> EvalCaseResult {
+    EvalCaseResult {
+        case_id: case.id.clone(),
+        passed: false,
+        checks: Vec::new(),
+        error: Some(error),
+        error_code,
+        final_text: None,
+        used_tools: Vec::new(),
+        skip_reason: None,
     }
 }
 
@@ -203,9 +547,11 @@ fn evaluate_case_outcome(case: &EvalCase, outcome: &ChatTurnOutcome) -> EvalCase
 
     let checks = vec![
         check_required_tool_usage(case, &used_tools),
+        check_expected_tool_arguments(case, outcome),
         check_no_invented_tool_output(case, outcome),
         check_answer_format(case, &outcome.final_text),
         check_answer_content(case, &outcome.final_text),
+        check_json_assertions(case, &outcome.final_text),
     ];
     let passed = checks.iter().all(|check| check.passed);
 
@@ -214,8 +560,10 @@ fn evaluate_case_outcome(case: &EvalCase, outcome: &ChatTurnOutcome) -> EvalCase
         passed,
         checks,
         error: None,
+        error_code: None,
         final_text: Some(outcome.final_text.clone()),
         used_tools,
+        skip_reason: None,
     }
 }
 
@@ -251,6 +599,96 @@ fn check_required_tool_usage(case: &EvalCase, used_tools: &[String]) -> EvalChec
     }
 }
 
+fn check_expected_tool_arguments(case: &EvalCase, outcome: &ChatTurnOutcome) -> EvalCheckResult {
+    if case.expected_tool_arguments.is_empty() {
+        return EvalCheckResult {
+            name: "tool_arguments",
+            passed: true,
+            detail: "no expected tool arguments configured".to_owned(),
+        };
+    }
+
+    let unmatched: Vec<String> = case
+        .expected_tool_arguments
+        .iter()
+        .filter(|expected| {
+            !outcome.tool_calls.iter().any(|call| {
+                call.tool_name == expected.tool
+                    && json_contains_subset(&call.arguments, &expected.arguments)
+            })
+        })
+        .map(|expected| format!("{} {}", expected.tool, expected.arguments))
+        .collect();
+
+    if unmatched.is_empty() {
+        EvalCheckResult {
+            name: "tool_arguments",
+            passed: true,
+            detail: "all expected tool arguments were used".to_owned(),
+        }
+    } else {
+        EvalCheckResult {
+            name: "tool_arguments",
+            passed: false,
+            detail: format!("no tool call matched: {}", unmatched.join("; ")),
+        }
+    }
+}
+
+fn check_json_assertions(case: &EvalCase, answer: &str) -> EvalCheckResult {
+    if case.json_assertions.is_empty() {
+        return EvalCheckResult {
+            name: "json_assertions",
+            passed: true,
+            detail: "no json assertions configured".to_owned(),
+        };
+    }
+
+    let parsed = match serde_json::from_str::<Value>(answer) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            return EvalCheckResult {
+                name: "json_assertions",
+                passed: false,
+                detail: format!("answer is not valid JSON: {error}"),
+            };
+        }
+    };
+    let failures: Vec<String> = case
+        .json_assertions
+        .iter()
+        .filter_map(|assertion| assertion.failure(&parsed))
+        .collect();
+
+    if failures.is_empty() {
+        EvalCheckResult {
+            name: "json_assertions",
+            passed: true,
+            detail: format!("{} json assertions passed", case.json_assertions.len()),
+        }
+    } else {
+        EvalCheckResult {
+            name: "json_assertions",
+            passed: false,
+            detail: failures.join("; "),
+        }
+    }
+}
+
+/// Objects match when every expected key matches recursively; other values must be equal.
+fn json_contains_subset(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            expected.iter().all(|(key, expected_value)| {
+                actual
+                    .get(key)
+                    .is_some_and(|actual_value| json_contains_subset(actual_value, expected_value))
+            })
+        }
+        _ => actual == expected,
+    }
+}
+
 fn check_no_invented_tool_output(case: &EvalCase, outcome: &ChatTurnOutcome) -> EvalCheckResult {
     if !case.no_invented_tool_output {
         return EvalCheckResult {
@@ -268,24 +706,34 @@ fn check_no_invented_tool_output(case: &EvalCase, outcome: &ChatTurnOutcome) ->
         };
     }
 
-    let mut allowed_corpus = case.prompt.to_ascii_lowercase();
-    for call in &outcome.tool_calls {
-        allowed_corpus.push('\n');
-        allowed_corpus.push_str(&call.output.to_ascii_lowercase());
-    }
+    let sources: Vec<&str> = std::iter::once(case.prompt.as_str())
+        .chain(outcome.tool_calls.iter().map(|call| call.output.as_str()))
+        .chain(case.grounding_allowlist.iter().map(String::as_str))
+        .collect();
+    let allowed_corpus = sources.join("\n").to_lowercase();
 
     let unknown_quoted_fragments: Vec<String> = extract_quoted_fragments(&outcome.final_text)
         .into_iter()
         .filter(|fragment| fragment.chars().count() >= 4)
-        .filter(|fragment| !allowed_corpus.contains(&fragment.to_ascii_lowercase()))
+        .filter(|fragment| !allowed_corpus.contains(&fragment.to_lowercase()))
         .collect();
 
-    let mut allowed_numbers = extract_numeric_tokens(&case.prompt);
-    for call in &outcome.tool_calls {
-        allowed_numbers.extend(extract_numeric_tokens(&call.output));
+    let mut allowed_numbers = BTreeSet::new();
+    let mut allowed_dates = Vec::new();
+    for source in &sources {
+        allowed_numbers.extend(extract_numeric_tokens(source));
+        allowed_dates.extend(extract_dates(source).0.into_iter().map(|(_, date)| date));
     }
 
-    let unknown_numbers: Vec<String> = extract_numeric_tokens(&outcome.final_text)
+    // Dates are checked as dates, so `May 15, 2016` is not also reported as `2016`.
+    let (answer_dates, answer_without_dates) = extract_dates(&outcome.final_text);
+    let unknown_dates: Vec<String> = answer_dates
+        .into_iter()
+        .filter(|(_, date)| !allowed_dates.iter().any(|known| date.is_backed_by(known)))
+        .map(|(written, _)| written)
+        .collect();
+
+    let unknown_numbers: Vec<String> = extract_numeric_tokens(&answer_without_dates)
         .into_iter()
         .filter(|number| number.len() >= 3)
         .filter(|number| !allowed_numbers.contains(number))
@@ -296,7 +744,22 @@ fn check_no_invented_tool_output(case: &EvalCase, outcome: &ChatTurnOutcome) ->
         .filter(|url| !allowed_corpus.contains(&url.to_ascii_lowercase()))
         .collect();
 
-    if unknown_quoted_fragments.is_empty() && unknown_numbers.is_empty() && unknown_urls.is_empty()
+    // Every word of an entity must appear somewhere, so `Hoare, Graydon` in a tool output
+    // backs `Graydon Hoare` in the answer.
+    let unknown_entities: Vec<String> = extract_capitalized_entities(&outcome.final_text)
+        .into_iter()
+        .filter(|entity| {
+            entity
+                .split(' ')
+                .any(|word| !allowed_corpus.contains(&word.to_lowercase()))
+        })
+        .collect();
+
+    if unknown_quoted_fragments.is_empty()
+        && unknown_numbers.is_empty()
+        && unknown_urls.is_empty()
+        && unknown_dates.is_empty()
+        && unknown_entities.is_empty()
     {
         return EvalCheckResult {
             name: "no_invented_tool_output",
@@ -324,6 +787,18 @@ fn check_no_invented_tool_output(case: &EvalCase, outcome: &ChatTurnOutcome) ->
             unknown_urls.join(", ")
         ));
     }
+    if !unknown_dates.is_empty() {
+        details.push(format!(
+            "dates not found in tool outputs: {}",
+            unknown_dates.join(", ")
+        ));
+    }
+    if !unknown_entities.is_empty() {
+        details.push(format!(
+            "names not found in tool outputs: {}",
+            unknown_entities.join(", ")
+        ));
+    }
 
     EvalCheckResult {
         name: "no_invented_tool_output",
@@ -352,7 +827,7 @@ fn check_answer_format(case: &EvalCase, answer: &str) -> EvalCheckResult {
             }
         }
         AnswerFormat::JsonObject => {
-            match validate_structured_answer_format(StructuredAnswerFormat::JsonObject, answer) {
+            match validate_structured_answer_format(&StructuredAnswerFormat::JsonObject, answer) {
                 Ok(()) => EvalCheckResult {
                     name: format_name,
                     passed: true,
@@ -373,7 +848,7 @@ fn check_answer_format(case: &EvalCase, answer: &str) -> EvalCheckResult {
                     passed: false,
                     detail: "answer is empty".to_owned(),
                 },
-                Err(StructuredAnswerFormatError::NonBulletLines(_)) => EvalCheckResult {
+                Err(_) => EvalCheckResult {
                     name: format_name,
                     passed: false,
                     detail: "internal format validation mismatch for JSON object".to_owned(),
@@ -381,8 +856,10 @@ fn check_answer_format(case: &EvalCase, answer: &str) -> EvalCheckResult {
             }
         }
         AnswerFormat::MarkdownBullets => {
-            match validate_structured_answer_format(StructuredAnswerFormat::MarkdownBullets, answer)
-            {
+            match validate_structured_answer_format(
+                &StructuredAnswerFormat::MarkdownBullets,
+                answer,
+            ) {
                 Ok(()) => EvalCheckResult {
                     name: format_name,
                     passed: true,
@@ -398,14 +875,46 @@ fn check_answer_format(case: &EvalCase, answer: &str) -> EvalCheckResult {
                     passed: false,
                     detail: format!("non-bullet lines detected: {}", invalid.join(" | ")),
                 },
-                Err(StructuredAnswerFormatError::JsonNotObject)
-                | Err(StructuredAnswerFormatError::JsonParseError(_)) => EvalCheckResult {
+                Err(_) => EvalCheckResult {
                     name: format_name,
                     passed: false,
                     detail: "internal format validation mismatch for markdown bullets".to_owned(),
                 },
             }
         }
+        AnswerFormat::CsvTable => match validate_csv_table(answer, case.csv_table.rules()) {
+            Ok(()) => EvalCheckResult {
+                name: format_name,
+                passed: true,
+                detail: format!("answer is a {} table", case.csv_table.label()),
+            },
+            Err(error) => EvalCheckResult {
+                name: format_name,
+                passed: false,
+                detail: format!(
+                    "answer is not a valid {} table: {error}",
+                    case.csv_table.label()
+                ),
+            },
+        },
+        AnswerFormat::JsonSchema => {
+            let schema = case.json_schema.clone().unwrap_or_default();
+            match validate_structured_answer_format(
+                &StructuredAnswerFormat::JsonSchema(schema),
+                answer,
+            ) {
+                Ok(()) => EvalCheckResult {
+                    name: format_name,
+                    passed: true,
+                    detail: "answer matches the JSON schema".to_owned(),
+                },
+                Err(error) => EvalCheckResult {
+                    name: format_name,
+                    passed: false,
+                    detail: error.to_string(),
+                },
+            }
+        }
     }
 }
 
@@ -425,7 +934,25 @@ fn check_answer_content(case: &EvalCase, answer: &str) -> EvalCheckResult {
         .cloned()
         .collect();
 
-    if missing_required.is_empty() && forbidden_found.is_empty() {
+    let unmatched_patterns: Vec<&str> = case
+        .answer_must_match
+        .iter()
+        .filter(|pattern| !pattern.is_match(answer))
+        .map(AnswerPattern::as_str)
+        .collect();
+
+    let forbidden_matches: Vec<&str> = case
+        .answer_must_not_match
+        .iter()
+        .filter(|pattern| pattern.is_match(answer))
+        .map(AnswerPattern::as_str)
+        .collect();
+
+    if missing_required.is_empty()
+        && forbidden_found.is_empty()
+        && unmatched_patterns.is_empty()
+        && forbidden_matches.is_empty()
+    {
         EvalCheckResult {
             name: "answer_content",
             passed: true,
@@ -445,6 +972,18 @@ fn check_answer_content(case: &EvalCase, answer: &str) -> EvalCheckResult {
                 forbidden_found.join(", ")
             ));
         }
+        if !unmatched_patterns.is_empty() {
+            details.push(format!(
+                "required patterns not matched: {}",
+                unmatched_patterns.join(", ")
+            ));
+        }
+        if !forbidden_matches.is_empty() {
+            details.push(format!(
+                "forbidden patterns matched: {}",
+                forbidden_matches.join(", ")
+            ));
+        }
         EvalCheckResult {
             name: "answer_content",
             passed: false,
@@ -458,6 +997,13 @@ fn normalize_and_validate_suite(suite: &mut EvalSuite) -> Result<()> {
         (0.0..=1.0).contains(&suite.target_pass_rate),
         "target_pass_rate must be between 0.0 and 1.0"
     );
+    if let Some(fixtures_dir) = &mut suite.notes_fixtures_dir {
+        *fixtures_dir = fixtures_dir.trim().to_owned();
+        ensure!(
+            !fixtures_dir.is_empty(),
+            "notes_fixtures_dir cannot be empty when provided"
+        );
+    }
     ensure!(
         !suite.cases.is_empty(),
         "eval suite must contain at least one case"
@@ -493,11 +1039,75 @@ fn normalize_and_validate_suite(suite: &mut EvalSuite) -> Result<()> {
                 case.id
             );
         }
+        for expected in &case.expected_tool_arguments {
+            ensure!(
+                known_tools.contains(expected.tool.as_str()),
+                "case `{}` expects arguments for unknown tool `{}`",
+                case.id,
+                expected.tool
+            );
+        }
+        validate_case_csv_table(case)?;
+        validate_case_json_schema(case)?;
+        ensure!(
+            case.json_assertions.is_empty()
+                || matches!(
+                    case.answer_format,
+                    AnswerFormat::JsonObject | AnswerFormat::JsonSchema
+                ),
+            "case `{}` sets json_assertions but its answer_format is not json_object or json_schema",
+            case.id
+        );
+        validate_case_fixtures(&case.id, &case.setup, &case.teardown)?;
     }
 
     Ok(())
 }
 
+fn validate_case_json_schema(case: &EvalCase) -> Result<()> {
+    match (&case.json_schema, case.answer_format) {
+        (Some(schema), AnswerFormat::JsonSchema) => check_schema(schema)
+            .map_err(|error| anyhow!("case `{}` json_schema is not supported: {error}", case.id)),
+        (None, AnswerFormat::JsonSchema) => Err(anyhow!(
+            "case `{}` has answer_format json_schema but no json_schema",
+            case.id
+        )),
+        (Some(_), _) => Err(anyhow!(
+            "case `{}` sets json_schema but its answer_format is not json_schema",
+            case.id
+        )),
+        (None, _) => Ok(()),
+    }
+}
+
+fn validate_case_csv_table(case: &EvalCase) -> Result<()> {
+    let table = &case.csv_table;
+    if case.answer_format != AnswerFormat::CsvTable {
+        ensure!(
+            *table == CsvTableExpectation::default(),
+            "case `{}` sets csv_table but its answer_format is not csv_table",
+            case.id
+        );
+        return Ok(());
+    }
+    ensure!(
+        table.columns != Some(0),
+        "case `{}` csv_table.columns must be greater than 0",
+        case.id
+    );
+    if let Some(columns) = table.columns
+        && !table.header.is_empty()
+    {
+        ensure!(
+            table.header.len() == columns,
+            "case `{}` csv_table.header names {} columns but csv_table.columns is {columns}",
+            case.id,
+            table.header.len()
+        );
+    }
+    Ok(())
+}
+
 fn extract_quoted_fragments(text: &str) -> Vec<String> {
     let mut output = Vec::new();
     let mut current = String::new();
@@ -578,8 +1188,11 @@ fn trim_url_token(token: &str) -> String {
 mod tests {
     use std::time::Duration;
 
+    use serde_json::json;
+
     use super::{
-        AnswerFormat, EvalCase, EvalSuite, check_answer_content, check_answer_format,
+        AnswerFormat, EvalCase, EvalSuite, ExpectedToolArguments, check_answer_content,
+        check_answer_format, check_expected_tool_arguments, check_json_assertions,
         check_no_invented_tool_output, check_required_tool_usage, create_eval_notes_dir,
         extract_numeric_tokens, extract_quoted_fragments, extract_urls,
         normalize_and_validate_suite,
@@ -590,14 +1203,25 @@ mod tests {
     fn normalize_and_validate_suite_rejects_unknown_required_tool() {
         let mut suite = EvalSuite {
             target_pass_rate: 0.8,
+            notes_fixtures_dir: None,
+            max_total_duration: None,
             cases: vec![EvalCase {
                 id: "case-1".to_owned(),
                 prompt: "hello".to_owned(),
                 required_tools: vec!["not_a_tool".to_owned()],
                 answer_format: AnswerFormat::PlainText,
+                csv_table: Default::default(),
                 answer_must_contain: Vec::new(),
                 answer_must_not_contain: Vec::new(),
+                answer_must_match: Vec::new(),
+                answer_must_not_match: Vec::new(),
                 no_invented_tool_output: false,
+                grounding_allowlist: Vec::new(),
+                expected_tool_arguments: Vec::new(),
+                json_assertions: Vec::new(),
+                json_schema: None,
+                setup: Default::default(),
+                teardown: Default::default(),
             }],
         };
 
@@ -612,14 +1236,57 @@ mod tests {
             prompt: "hello".to_owned(),
             required_tools: vec!["fetch_url".to_owned()],
             answer_format: AnswerFormat::PlainText,
+            csv_table: Default::default(),
             answer_must_contain: Vec::new(),
             answer_must_not_contain: Vec::new(),
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
             no_invented_tool_output: false,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: Vec::new(),
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
         };
         let result = check_required_tool_usage(&case, &[]);
         assert!(!result.passed);
     }
 
+    #[test]
+    fn expected_tool_arguments_match_subset_of_call_arguments() {
+        let mut case = EvalCase {
+            id: "case-1".to_owned(),
+            prompt: "Fetch example.com".to_owned(),
+            required_tools: Vec::new(),
+            answer_format: AnswerFormat::PlainText,
+            csv_table: Default::default(),
+            answer_must_contain: Vec::new(),
+            answer_must_not_contain: Vec::new(),
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
+            no_invented_tool_output: false,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: vec![ExpectedToolArguments {
+                tool: "fetch_url".to_owned(),
+                arguments: json!({ "url": "https://example.com" }),
+            }],
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
+        };
+        let outcome = test_outcome("Example Domain", vec![("fetch_url", "{}")]);
+
+        let result = check_expected_tool_arguments(&case, &outcome);
+        assert!(result.passed, "{}", result.detail);
+
+        case.expected_tool_arguments[0].arguments = json!({ "url": "https://example.org" });
+        let result = check_expected_tool_arguments(&case, &outcome);
+        assert!(!result.passed);
+        assert!(result.detail.contains("https://example.org"));
+    }
+
     #[test]
     fn no_invented_tool_output_passes_when_answer_is_grounded() {
         let case = EvalCase {
@@ -627,9 +1294,18 @@ mod tests {
             prompt: "Use fetch_url and summarize example.com".to_owned(),
             required_tools: vec!["fetch_url".to_owned()],
             answer_format: AnswerFormat::PlainText,
+            csv_table: Default::default(),
             answer_must_contain: Vec::new(),
             answer_must_not_contain: Vec::new(),
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
             no_invented_tool_output: true,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: Vec::new(),
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
         };
         let outcome = test_outcome(
             "The page title is \"Example Domain\".",
@@ -650,9 +1326,18 @@ mod tests {
             prompt: "Use fetch_url on example.com".to_owned(),
             required_tools: vec!["fetch_url".to_owned()],
             answer_format: AnswerFormat::PlainText,
+            csv_table: Default::default(),
             answer_must_contain: Vec::new(),
             answer_must_not_contain: Vec::new(),
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
             no_invented_tool_output: true,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: Vec::new(),
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
         };
         let outcome = test_outcome(
             "Status was 404 and title was Example Domain.",
@@ -667,6 +1352,43 @@ mod tests {
         assert!(result.detail.contains("numbers not found"));
     }
 
+    #[test]
+    fn no_invented_tool_output_catches_dates_and_names_outside_the_allowlist() {
+        let mut suite = serde_yaml::from_str::<EvalSuite>(
+            r#"
+cases:
+  - id: release
+    prompt: Use fetch_url on the Rust blog and say when 1.0 shipped.
+    no_invented_tool_output: true
+"#,
+        )
+        .expect("suite should parse");
+        let tool_output = r#"{"content":"Rust 1.0 was released on 2015-05-15 by the Rust team."}"#;
+
+        let grounded = test_outcome(
+            "Rust 1.0 shipped on May 15, 2015, from the Rust team.",
+            vec![("fetch_url", tool_output)],
+        );
+        let result = check_no_invented_tool_output(&suite.cases[0], &grounded);
+        assert!(result.passed, "{}", result.detail);
+
+        let invented = test_outcome(
+            "Rust 1.0 shipped on 16 May 2015, announced by Graydon Hoare.",
+            vec![("fetch_url", tool_output)],
+        );
+        let result = check_no_invented_tool_output(&suite.cases[0], &invented);
+        assert!(!result.passed);
+        assert_eq!(
+            result.detail,
+            "dates not found in tool outputs: 16 May 2015; names not found in tool outputs: Graydon Hoare"
+        );
+
+        suite.cases[0].grounding_allowlist =
+            vec!["Graydon Hoare".to_owned(), "2015-05-16".to_owned()];
+        let result = check_no_invented_tool_output(&suite.cases[0], &invented);
+        assert!(result.passed, "{}", result.detail);
+    }
+
     #[test]
     fn answer_format_json_object_requires_json_object() {
         let case = EvalCase {
@@ -674,9 +1396,18 @@ mod tests {
             prompt: "Respond with JSON".to_owned(),
             required_tools: Vec::new(),
             answer_format: AnswerFormat::JsonObject,
+            csv_table: Default::default(),
             answer_must_contain: Vec::new(),
             answer_must_not_contain: Vec::new(),
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
             no_invented_tool_output: false,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: Vec::new(),
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
         };
 
         let result = check_answer_format(&case, r#"{"ok":true}"#);
@@ -686,6 +1417,91 @@ mod tests {
         assert!(!result.passed);
     }
 
+    #[test]
+    fn answer_format_csv_table_applies_case_columns_and_header() {
+        let mut suite = serde_yaml::from_str::<EvalSuite>(
+            r#"
+cases:
+  - id: releases
+    prompt: List releases as TSV.
+    answer_format: csv_table
+    csv_table:
+      delimiter: tab
+      header: [language, year]
+"#,
+        )
+        .expect("suite should parse");
+        normalize_and_validate_suite(&mut suite).expect("suite should validate");
+        let case = &suite.cases[0];
+
+        let result = check_answer_format(case, "Language\tYear\nrust\t2015\n");
+        assert!(result.passed, "{}", result.detail);
+        assert_eq!(result.detail, "answer is a TSV table");
+
+        let result = check_answer_format(case, "language\tyear\nrust\t2015\tstable");
+        assert!(!result.passed);
+        assert_eq!(
+            result.detail,
+            "answer is not a valid TSV table: rows with a different column count than the header (2): line 2 has 3"
+        );
+
+        let result = check_answer_format(case, "language,year\nrust,2015");
+        assert!(!result.passed);
+        assert!(result.detail.contains("header is `language,year`"));
+
+        suite.cases[0].csv_table.columns = Some(3);
+        let error = normalize_and_validate_suite(&mut suite)
+            .expect_err("header/columns disagreement should fail");
+        assert!(
+            error
+                .to_string()
+                .contains("csv_table.header names 2 columns")
+        );
+
+        suite.cases[0].answer_format = AnswerFormat::PlainText;
+        let error = normalize_and_validate_suite(&mut suite)
+            .expect_err("csv_table without csv_table format should fail");
+        assert!(error.to_string().contains("answer_format is not csv_table"));
+    }
+
+    #[test]
+    fn answer_format_json_schema_checks_the_case_schema() {
+        let mut suite = serde_yaml::from_str::<EvalSuite>(
+            r#"
+cases:
+  - id: release
+    prompt: Describe the first Rust release.
+    answer_format: json_schema
+    json_schema:
+      type: object
+      required: [name, year]
+      properties:
+        year: {type: integer, minimum: 2010}
+"#,
+        )
+        .expect("suite should parse");
+        normalize_and_validate_suite(&mut suite).expect("suite should validate");
+        let case = &suite.cases[0];
+
+        let result = check_answer_format(case, r#"{"name": "1.0", "year": 2015}"#);
+        assert!(result.passed, "{}", result.detail);
+        let result = check_answer_format(case, r#"{"year": 2009}"#);
+        assert_eq!(
+            result.detail,
+            "answer does not match the JSON schema: /: missing required `name`; /year: 2009 is below the minimum 2010"
+        );
+
+        suite.cases[0].json_schema = Some(serde_json::json!({"type": "object", "oneOf": []}));
+        let error = normalize_and_validate_suite(&mut suite)
+            .expect_err("unsupported keywords should fail the load");
+        assert!(error.to_string().contains("unsupported keyword `oneOf`"));
+
+        suite.cases[0].json_schema = None;
+        let error = normalize_and_validate_suite(&mut suite)
+            .expect_err("json_schema format without a schema should fail");
+        assert!(error.to_string().contains("but no json_schema"));
+    }
+
     #[test]
     fn answer_content_checks_required_and_forbidden_strings() {
         let case = EvalCase {
@@ -693,9 +1509,18 @@ mod tests {
             prompt: "hello".to_owned(),
             required_tools: Vec::new(),
             answer_format: AnswerFormat::PlainText,
+            csv_table: Default::default(),
             answer_must_contain: vec!["rust".to_owned()],
             answer_must_not_contain: vec!["python".to_owned()],
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
             no_invented_tool_output: false,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: Vec::new(),
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
         };
 
         let result = check_answer_content(&case, "Rust only");
@@ -705,6 +1530,83 @@ mod tests {
         assert!(!result.passed);
     }
 
+    #[test]
+    fn json_assertions_check_fields_of_json_object_answers() {
+        let mut suite = serde_yaml::from_str::<EvalSuite>(
+            r#"
+cases:
+  - id: echo
+    prompt: Return a JSON object with task and status.
+    answer_format: json_object
+    json_assertions:
+      - path: /status
+        equals: ok
+      - path: $.task
+        type: string
+        min: 1
+"#,
+        )
+        .expect("suite should parse");
+        normalize_and_validate_suite(&mut suite).expect("suite should validate");
+        let case = &suite.cases[0];
+
+        let result = check_json_assertions(case, r#"{"task":"echo","status":"ok"}"#);
+        assert!(result.passed, "{}", result.detail);
+
+        let result = check_json_assertions(case, r#"{"task":"","status":"done"}"#);
+        assert_eq!(
+            result.detail,
+            "/status is \"done\", expected \"ok\"; $.task length 0 is below min 1"
+        );
+        let result = check_json_assertions(case, "status: ok");
+        assert!(result.detail.starts_with("answer is not valid JSON"));
+
+        suite.cases[0].answer_format = AnswerFormat::PlainText;
+        let error = normalize_and_validate_suite(&mut suite)
+            .expect_err("json_assertions need a json_object case");
+        assert!(
+            error
+                .to_string()
+                .contains("answer_format is not json_object")
+        );
+    }
+
+    #[test]
+    fn answer_content_checks_regex_patterns() {
+        let suite = serde_yaml::from_str::<EvalSuite>(
+            r#"
+cases:
+  - id: release-date
+    prompt: When was Rust 1.0 released?
+    answer_must_match: ['\d{4}-\d{2}-\d{2}', '(?i)rust']
+    answer_must_not_match: ['\bTODO\b']
+"#,
+        )
+        .expect("suite should parse");
+        let case = &suite.cases[0];
+
+        let result = check_answer_content(case, "RUST 1.0 shipped on 2015-05-15.");
+        assert!(result.passed, "{}", result.detail);
+
+        let result = check_answer_content(case, "Rust 1.0 shipped in May 2015. TODO: day");
+        assert!(!result.passed);
+        assert_eq!(
+            result.detail,
+            "required patterns not matched: \\d{4}-\\d{2}-\\d{2}; forbidden patterns matched: \\bTODO\\b"
+        );
+
+        let error = serde_yaml::from_str::<EvalSuite>(
+            "cases:\n  - id: broken\n    prompt: hi\n    answer_must_match: ['(unclosed']\n",
+        )
+        .expect_err("invalid regex should fail to load");
+        let message = error.to_string();
+        assert!(
+            message.contains("invalid answer regex `(unclosed`"),
+            "{message}"
+        );
+        assert!(message.contains("answer_must_match"), "{message}");
+    }
+
     #[test]
     fn extract_helpers_capture_expected_values() {
         let numbers = extract_numeric_tokens("Status 200 and 12.5 ms");
@@ -736,6 +1638,8 @@ mod tests {
         ChatTurnOutcome {
             final_text: final_text.to_owned(),
             trace: TurnTraceSummary {
+                version: crate::build_info::VERSION.to_owned(),
+                git_commit: crate::build_info::GIT_COMMIT.to_owned(),
                 input_chars: 0,
                 output_chars: Some(final_text.chars().count()),
                 steps_executed: 1,
@@ -747,14 +1651,28 @@ mod tests {
                     .iter()
                     .map(|(name, _)| (*name).to_owned())
                     .collect(),
+                stripped_tool_call_blocks: 0,
+                trimmed_messages: 0,
+                compacted_messages: 0,
+                user: None,
+                injection_detections: Vec::new(),
+                timeline: Vec::new(),
+                context: Default::default(),
             },
             tool_calls: tool_calls
                 .into_iter()
                 .map(|(tool_name, output)| ExecutedToolCall {
                     tool_name: tool_name.to_owned(),
+                    arguments: json!({ "url": "https://example.com" }),
                     output: output.to_owned(),
+                    latency: Duration::from_millis(1),
+                    attempts: 1,
+                    error: None,
                 })
                 .collect(),
+            citations: Vec::new(),
+            moderation: None,
+            was_repaired_locally: false,
         }
     }
 }
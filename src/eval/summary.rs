@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::EvalRunReport;
+use super::baseline::BaselineComparison;
+use crate::agent::ErrorCode;
+
+pub const DEFAULT_EVAL_SUMMARY_PATH: &str = "eval-summary.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum EvalSummaryStatus {
+    Passed,
+    Failed,
+    /// The run stopped early: preflight or suite loading failed, or a budget ran out.
+    Aborted,
+}
+
+/// Compact end-of-run result for CI steps; the full per-case output stays on stdout.
+/// `eval --baseline` reads it back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct EvalSummary {
+    pub status: EvalSummaryStatus,
+    pub cases_path: String,
+    pub total_cases: usize,
+    pub passed_cases: usize,
+    pub failed_cases: usize,
+    pub skipped_cases: usize,
+    pub pass_rate: Option<f64>,
+    pub target_pass_rate: Option<f64>,
+    #[serde(default)]
+    pub passed_case_ids: Vec<String>,
+    pub failed_case_ids: Vec<String>,
+    pub skipped_case_ids: Vec<String>,
+    /// Failed cases per chat turn error code, for cases whose turn did not complete.
+    pub error_codes: BTreeMap<ErrorCode, usize>,
+    pub error: Option<String>,
+    /// Set for `eval --baseline` runs, which pass or fail on regressions instead of the
+    /// pass-rate target.
+    #[serde(default)]
+    pub baseline: Option<BaselineComparison>,
+}
+
+impl EvalSummary {
+    /// `report` is `None` when the run aborted before any case ran.
+    pub(super) fn new(
+        cases_path: &Path,
+        report: Option<&EvalRunReport>,
+        error: Option<&anyhow::Error>,
+        baseline: Option<BaselineComparison>,
+    ) -> Self {
+        let error = error.map(|error| format!("{error:#}"));
+        let Some(report) = report else {
+            return Self {
+                status: EvalSummaryStatus::Aborted,
+                cases_path: cases_path.display().to_string(),
+                total_cases: 0,
+                passed_cases: 0,
+                failed_cases: 0,
+                skipped_cases: 0,
+                pass_rate: None,
+                target_pass_rate: None,
+                passed_case_ids: Vec::new(),
+                failed_case_ids: Vec::new(),
+                skipped_case_ids: Vec::new(),
+                error_codes: BTreeMap::new(),
+                error,
+                baseline,
+            };
+        };
+
+        let status = if report.skipped_cases > 0 {
+            EvalSummaryStatus::Aborted
+        } else if error.is_some() || (baseline.is_none() && !report.meets_target()) {
+            EvalSummaryStatus::Failed
+        } else {
+            EvalSummaryStatus::Passed
+        };
+        let case_ids = |passed: bool, skipped: bool| {
+            report
+                .case_results
+                .iter()
+                .filter(|result| result.passed == passed && result.skip_reason.is_some() == skipped)
+                .map(|result| result.case_id.clone())
+                .collect()
+        };
+        let mut error_codes = BTreeMap::new();
+        for code in report
+            .case_results
+            .iter()
+            .filter_map(|result| result.error_code)
+        {
+            *error_codes.entry(code).or_insert(0) += 1;
+        }
+
+        Self {
+            status,
+            cases_path: report.cases_path.display().to_string(),
+            total_cases: report.total_cases,
+            passed_cases: report.passed_cases,
+            failed_cases: report.failed_cases,
+            skipped_cases: report.skipped_cases,
+            pass_rate: Some(report.pass_rate),
+            target_pass_rate: Some(report.target_pass_rate),
+            passed_case_ids: case_ids(true, false),
+            failed_case_ids: case_ids(false, false),
+            skipped_case_ids: case_ids(false, true),
+            error_codes,
+            error,
+            baseline,
+        }
+    }
+}
+
+pub(super) fn write_eval_summary(path: &Path, summary: &EvalSummary) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "failed to create eval summary directory `{}`",
+                parent.display()
+            )
+        })?;
+    }
+    let json = serde_json::to_string_pretty(summary).context("failed to serialize eval summary")?;
+    fs::write(path, format!("{json}\n"))
+        .with_context(|| format!("failed to write eval summary `{}`", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use anyhow::anyhow;
+    use serde_json::Value;
+
+    use super::{EvalSummary, EvalSummaryStatus, write_eval_summary};
+    use crate::agent::ErrorCode;
+    use crate::eval::{EvalCaseResult, EvalRunReport};
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    fn case_result(case_id: &str, passed: bool, skip_reason: Option<&str>) -> EvalCaseResult {
+        EvalCaseResult {
+            case_id: case_id.to_owned(),
+            passed,
+            checks: Vec::new(),
+            error: None,
+            error_code: None,
+            final_text: None,
+            used_tools: Vec::new(),
+            skip_reason: skip_reason.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn summary_lists_failed_and_skipped_case_ids() {
+        let report = EvalRunReport::from_case_results(
+            Path::new("eval/cases.yaml"),
+            0.5,
+            vec![
+                case_result("greeting", true, None),
+                EvalCaseResult {
+                    error: Some("policy blocked tool `fetch_url`".to_owned()),
+                    error_code: Some(ErrorCode::ToolPolicyDomain),
+                    ..case_result("fetch", false, None)
+                },
+                case_result("notes", false, Some("max_total_duration 60s exceeded")),
+            ],
+        );
+        let error = anyhow!("evaluation aborted");
+
+        let summary = EvalSummary::new(
+            Path::new("eval/cases.yaml"),
+            Some(&report),
+            Some(&error),
+            None,
+        );
+
+        assert_eq!(summary.status, EvalSummaryStatus::Aborted);
+        assert_eq!(summary.passed_case_ids, ["greeting"]);
+        assert_eq!(summary.failed_case_ids, ["fetch"]);
+        assert_eq!(summary.skipped_case_ids, ["notes"]);
+        assert_eq!(
+            serde_json::to_value(&summary.error_codes).expect("serialize"),
+            serde_json::json!({ "E_TOOL_POLICY_DOMAIN": 1 })
+        );
+        assert_eq!(summary.error.as_deref(), Some("evaluation aborted"));
+    }
+
+    #[test]
+    fn summary_is_written_when_the_run_aborts_before_any_case() {
+        let dir = temp_path("eval_summary");
+        let path = dir.join("nested").join("eval-summary.json");
+        let error = anyhow!("preflight: ollama is unreachable");
+
+        let summary = EvalSummary::new(Path::new("eval/cases.yaml"), None, Some(&error), None);
+        write_eval_summary(&path, &summary).expect("summary should be written");
+
+        let written: Value =
+            serde_json::from_str(&fs::read_to_string(&path).expect("summary file should exist"))
+                .expect("summary should be JSON");
+        assert_eq!(written["status"], "aborted");
+        assert_eq!(written["cases_path"], "eval/cases.yaml");
+        assert_eq!(written["pass_rate"], Value::Null);
+        assert_eq!(written["error"], "preflight: ollama is unreachable");
+        remove_dir_if_exists(&dir);
+    }
+}
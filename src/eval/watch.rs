@@ -0,0 +1,339 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tokio::time::{Instant, interval};
+
+use super::{
+    EvalCase, EvalCaseResult, EvalRunReport, EvalSuite, copy_notes_fixtures, create_eval_notes_dir,
+    load_eval_suite, notes_fixtures_path, print_case_result, print_report_summary,
+    remove_eval_notes_dir, run_cases_within_budget, run_model_preflight,
+};
+use crate::config::AgentSettings;
+use crate::graph::watch::{FileFingerprint, GraphWatchConfig, collect_paths_fingerprint};
+use crate::tools::{
+    DELETE_NOTE_TOOL_NAME, READ_NOTE_TOOL_NAME, SAVE_NOTE_TOOL_NAME, SEARCH_NOTES_TOOL_NAME,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchFingerprint {
+    cases: Vec<FileFingerprint>,
+    fixtures: Vec<FileFingerprint>,
+}
+
+fn watch_fingerprint(cases_path: &Path, fixtures_dir: Option<&Path>) -> WatchFingerprint {
+    WatchFingerprint {
+        cases: collect_paths_fingerprint(&[cases_path.to_path_buf()]),
+        fixtures: fixtures_dir
+            .map(|dir| collect_paths_fingerprint(&[dir.to_path_buf()]))
+            .unwrap_or_default(),
+    }
+}
+
+/// `eval --watch`: runs the suite once, then polls the cases file and notes fixtures and
+/// re-runs only the cases a change can affect, until Ctrl-C.
+pub async fn run_eval_watch_command(
+    settings: &AgentSettings,
+    cases_path: &Path,
+    preflight: bool,
+) -> Result<()> {
+    if preflight {
+        run_model_preflight(settings).await?;
+    }
+    let mut eval_settings = settings.clone();
+    let eval_notes_dir = create_eval_notes_dir()?;
+    eval_settings.notes_dir = eval_notes_dir.display().to_string();
+
+    let result = watch_eval_suite(&eval_settings, cases_path, &eval_notes_dir).await;
+    remove_eval_notes_dir(&eval_notes_dir);
+    result
+}
+
+async fn watch_eval_suite(
+    settings: &AgentSettings,
+    cases_path: &Path,
+    notes_dir: &Path,
+) -> Result<()> {
+    // Same cadence as the studio graph watcher: poll, then wait for edits to settle.
+    let config = GraphWatchConfig::default();
+    let mut suite = load_eval_suite(cases_path)?;
+    let mut fixtures_dir = notes_fixtures_path(&suite, cases_path);
+    let mut results = HashMap::<String, EvalCaseResult>::new();
+
+    println!(
+        "Watching {} for changes (Ctrl-C to stop)",
+        cases_path.display()
+    );
+    let all_case_ids = suite.cases.iter().map(|case| case.id.clone()).collect();
+    run_affected_cases(
+        settings,
+        &suite,
+        fixtures_dir.as_deref(),
+        notes_dir,
+        &all_case_ids,
+        &mut results,
+    )
+    .await;
+    print_watch_summary(cases_path, &suite, &results);
+
+    let mut last_fingerprint = watch_fingerprint(cases_path, fixtures_dir.as_deref());
+    let mut cases_changed = false;
+    let mut fixtures_changed = false;
+    let mut rerun_deadline = None;
+    let mut ticker = interval(config.poll_interval);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            _ = ticker.tick() => {}
+        }
+
+        let fingerprint = watch_fingerprint(cases_path, fixtures_dir.as_deref());
+        if fingerprint != last_fingerprint {
+            cases_changed |= fingerprint.cases != last_fingerprint.cases;
+            fixtures_changed |= fingerprint.fixtures != last_fingerprint.fixtures;
+            last_fingerprint = fingerprint;
+            rerun_deadline = Some(Instant::now() + config.debounce_interval);
+            continue;
+        }
+        let Some(deadline) = rerun_deadline else {
+            continue;
+        };
+        if Instant::now() < deadline {
+            continue;
+        }
+        rerun_deadline = None;
+
+        let mut affected = BTreeSet::new();
+        if cases_changed {
+            match load_eval_suite(cases_path) {
+                Ok(next_suite) => {
+                    let next_fixtures_dir = notes_fixtures_path(&next_suite, cases_path);
+                    let fixtures_moved = next_fixtures_dir != fixtures_dir;
+                    affected = affected_case_ids(
+                        Some(&suite),
+                        &next_suite,
+                        fixtures_changed || fixtures_moved,
+                    );
+                    results.retain(|case_id, _| {
+                        next_suite.cases.iter().any(|case| &case.id == case_id)
+                    });
+                    suite = next_suite;
+                    fixtures_dir = next_fixtures_dir;
+                    last_fingerprint = watch_fingerprint(cases_path, fixtures_dir.as_deref());
+                }
+                Err(error) => {
+                    // Keep watching so the author can fix the file.
+                    println!("[watch] {error:#}; keeping previous results");
+                    cases_changed = false;
+                    fixtures_changed = false;
+                    continue;
+                }
+            }
+        } else if fixtures_changed {
+            affected = affected_case_ids(None, &suite, true);
+        }
+        cases_changed = false;
+        fixtures_changed = false;
+
+        if affected.is_empty() {
+            println!("[watch] change detected; no cases affected");
+            print_watch_summary(cases_path, &suite, &results);
+            continue;
+        }
+        println!("[watch] re-running {} affected case(s)", affected.len());
+        run_affected_cases(
+            settings,
+            &suite,
+            fixtures_dir.as_deref(),
+            notes_dir,
+            &affected,
+            &mut results,
+        )
+        .await;
+        print_watch_summary(cases_path, &suite, &results);
+    }
+
+    println!("Stopped watching {}", cases_path.display());
+    Ok(())
+}
+
+/// Cases that are new or edited since `previous`, plus every notes-dependent case when
+/// the notes fixtures changed. `previous: None` means only fixtures changed.
+fn affected_case_ids(
+    previous: Option<&EvalSuite>,
+    next: &EvalSuite,
+    fixtures_changed: bool,
+) -> BTreeSet<String> {
+    next.cases
+        .iter()
+        .filter(|case| {
+            let edited = previous.is_some_and(|previous| {
+                !previous
+                    .cases
+                    .iter()
+                    .any(|previous_case| previous_case == *case)
+            });
+            edited || (fixtures_changed && case_uses_notes(case))
+        })
+        .map(|case| case.id.clone())
+        .collect()
+}
+
+fn case_uses_notes(case: &EvalCase) -> bool {
+    let notes_tools = [
+        SEARCH_NOTES_TOOL_NAME,
+        SAVE_NOTE_TOOL_NAME,
+        READ_NOTE_TOOL_NAME,
+        DELETE_NOTE_TOOL_NAME,
+    ];
+    case.required_tools
+        .iter()
+        .any(|tool| notes_tools.contains(&tool.as_str()))
+        || case
+            .expected_tool_arguments
+            .iter()
+            .any(|expected| notes_tools.contains(&expected.tool.as_str()))
+        || notes_tools.iter().any(|tool| case.prompt.contains(tool))
+}
+
+async fn run_affected_cases(
+    settings: &AgentSettings,
+    suite: &EvalSuite,
+    fixtures_dir: Option<&Path>,
+    notes_dir: &Path,
+    case_ids: &BTreeSet<String>,
+    results: &mut HashMap<String, EvalCaseResult>,
+) {
+    // Notes saved by earlier runs must not leak into re-runs.
+    if let Err(error) = reset_notes_dir(notes_dir, fixtures_dir) {
+        println!("[watch] {error:#}");
+    }
+    // The budget applies to each re-run, not to the lifetime of the watch session.
+    let cases = suite
+        .cases
+        .iter()
+        .filter(|case| case_ids.contains(&case.id));
+    for result in run_cases_within_budget(
+        settings,
+        cases,
+        suite.max_total_duration,
+        1,
+        print_case_result,
+    )
+    .await
+    {
+        results.insert(result.case_id.clone(), result);
+    }
+}
+
+fn reset_notes_dir(notes_dir: &Path, fixtures_dir: Option<&Path>) -> Result<()> {
+    if notes_dir.exists() {
+        fs::remove_dir_all(notes_dir).with_context(|| {
+            format!(
+                "failed to clear eval notes directory `{}`",
+                notes_dir.display()
+            )
+        })?;
+    }
+    fs::create_dir_all(notes_dir).with_context(|| {
+        format!(
+            "failed to create eval notes directory `{}`",
+            notes_dir.display()
+        )
+    })?;
+    if let Some(fixtures_dir) = fixtures_dir {
+        copy_notes_fixtures(fixtures_dir, notes_dir)?;
+    }
+    Ok(())
+}
+
+fn print_watch_summary(
+    cases_path: &Path,
+    suite: &EvalSuite,
+    results: &HashMap<String, EvalCaseResult>,
+) {
+    let case_results = suite
+        .cases
+        .iter()
+        .filter_map(|case| results.get(&case.id).cloned())
+        .collect();
+    let report = EvalRunReport::from_case_results(cases_path, suite.target_pass_rate, case_results);
+    print_report_summary(&report);
+    if !report.meets_target() {
+        println!("[watch] pass rate is below target");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{affected_case_ids, case_uses_notes};
+    use crate::eval::{AnswerFormat, EvalCase, EvalSuite};
+
+    fn case(id: &str, prompt: &str, required_tools: &[&str]) -> EvalCase {
+        EvalCase {
+            id: id.to_owned(),
+            prompt: prompt.to_owned(),
+            required_tools: required_tools.iter().map(|tool| tool.to_string()).collect(),
+            answer_format: AnswerFormat::PlainText,
+            csv_table: Default::default(),
+            answer_must_contain: Vec::new(),
+            answer_must_not_contain: Vec::new(),
+            answer_must_match: Vec::new(),
+            answer_must_not_match: Vec::new(),
+            no_invented_tool_output: false,
+            grounding_allowlist: Vec::new(),
+            expected_tool_arguments: Vec::new(),
+            json_assertions: Vec::new(),
+            json_schema: None,
+            setup: Default::default(),
+            teardown: Default::default(),
+        }
+    }
+
+    fn suite(cases: Vec<EvalCase>) -> EvalSuite {
+        EvalSuite {
+            target_pass_rate: 0.8,
+            notes_fixtures_dir: None,
+            max_total_duration: None,
+            cases,
+        }
+    }
+
+    #[test]
+    fn affected_case_ids_reruns_new_and_edited_cases_only() {
+        let previous = suite(vec![
+            case("greeting", "Say hello.", &[]),
+            case("fetch", "Fetch example.com.", &["fetch_url"]),
+        ]);
+        let next = suite(vec![
+            case("greeting", "Say hello.", &[]),
+            case("fetch", "Fetch https://example.com.", &["fetch_url"]),
+            case("notes", "Search notes.", &["search_notes"]),
+        ]);
+
+        let affected = affected_case_ids(Some(&previous), &next, false);
+
+        assert_eq!(affected.into_iter().collect::<Vec<_>>(), ["fetch", "notes"]);
+    }
+
+    #[test]
+    fn fixture_changes_rerun_notes_cases() {
+        let current = suite(vec![
+            case("greeting", "Say hello.", &[]),
+            case("search", "Find rust notes.", &["search_notes"]),
+            case("mention", "Use save_note to store this.", &[]),
+        ]);
+
+        let affected = affected_case_ids(None, &current, true);
+
+        assert_eq!(
+            affected.into_iter().collect::<Vec<_>>(),
+            ["mention", "search"]
+        );
+        assert!(!case_uses_notes(&current.cases[0]));
+    }
+}
@@ -0,0 +1,369 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+pub use crate::api::{
+    ExportFormat, Transcript, TranscriptEntry, TranscriptRole, TranscriptToolCall,
+};
+use crate::model::client::{MessageRole, ModelMessage};
+
+impl TranscriptRole {
+    fn label(self) -> &'static str {
+        match self {
+            Self::User => "User",
+            Self::Assistant => "Assistant",
+            Self::System => "System",
+        }
+    }
+}
+
+impl Transcript {
+    /// Builds a transcript from retained model messages. The system prompt is omitted,
+    /// tool results are attached to the calls that produced them, and consecutive
+    /// assistant messages within a turn become one entry.
+    pub fn from_conversation(title: impl Into<String>, conversation: &[ModelMessage]) -> Self {
+        let mut entries: Vec<TranscriptEntry> = Vec::new();
+        // Call ids of the open assistant entry, parallel to its `tool_calls`.
+        let mut call_ids: Vec<String> = Vec::new();
+        for message in conversation {
+            match message.role {
+                MessageRole::System => {}
+                MessageRole::User => {
+                    call_ids.clear();
+                    entries.push(TranscriptEntry {
+                        role: TranscriptRole::User,
+                        text: message.content.clone(),
+                        tool_calls: Vec::new(),
+                    });
+                }
+                MessageRole::Assistant => {
+                    let entry = match entries.last_mut() {
+                        Some(entry) if entry.role == TranscriptRole::Assistant => entry,
+                        _ => {
+                            call_ids.clear();
+                            entries.push(TranscriptEntry {
+                                role: TranscriptRole::Assistant,
+                                text: String::new(),
+                                tool_calls: Vec::new(),
+                            });
+                            entries.last_mut().expect("entry was just pushed")
+                        }
+                    };
+                    let text = message.content.trim();
+                    if !text.is_empty() {
+                        if !entry.text.is_empty() {
+                            entry.text.push_str("\n\n");
+                        }
+                        entry.text.push_str(text);
+                    }
+                    for call in &message.tool_calls {
+                        call_ids.push(call.id.clone());
+                        entry.tool_calls.push(TranscriptToolCall {
+                            name: call.name.clone(),
+                            arguments: call.arguments.clone(),
+                            output: None,
+                        });
+                    }
+                }
+                MessageRole::Tool => {
+                    let Some(entry) = entries
+                        .last_mut()
+                        .filter(|entry| entry.role == TranscriptRole::Assistant)
+                    else {
+                        continue;
+                    };
+                    let pending = |index: &usize| entry.tool_calls[*index].output.is_none();
+                    let by_id = message.tool_call_id.as_deref().and_then(|id| {
+                        (0..call_ids.len()).find(|index| call_ids[*index] == id && pending(index))
+                    });
+                    let by_name = || {
+                        (0..entry.tool_calls.len()).find(|index| {
+                            pending(index)
+                                && Some(entry.tool_calls[*index].name.as_str())
+                                    == message.tool_name.as_deref()
+                        })
+                    };
+                    if let Some(index) = by_id.or_else(by_name) {
+                        entry.tool_calls[index].output = Some(message.content.clone());
+                    }
+                }
+            }
+        }
+        Self {
+            title: title.into(),
+            summary: None,
+            entries,
+        }
+    }
+
+    pub fn render(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Markdown => render_markdown(self),
+            ExportFormat::Html => render_html(self),
+            ExportFormat::Json => {
+                let mut json = serde_json::to_string_pretty(self)
+                    .expect("transcript serialization should not fail");
+                json.push('\n');
+                json
+            }
+        }
+    }
+
+    /// Renders `format` into `path`, creating missing parent directories.
+    pub fn write_to(&self, path: &Path, format: ExportFormat) -> Result<()> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create `{}`", parent.display()))?;
+        }
+        fs::write(path, self.render(format))
+            .with_context(|| format!("failed to write transcript to `{}`", path.display()))
+    }
+}
+
+fn render_markdown(transcript: &Transcript) -> String {
+    let mut out = format!("# {}\n", transcript.title);
+    if let Some(summary) = &transcript.summary {
+        let _ = write!(out, "\n_{}_\n", summary.trim());
+    }
+    for entry in &transcript.entries {
+        let _ = write!(out, "\n## {}\n\n", entry.role.label());
+        for call in &entry.tool_calls {
+            // `<details>` keeps tool calls collapsed in GitHub and most Markdown viewers.
+            let _ = writeln!(
+                out,
+                "<details>\n<summary>Tool call: <code>{}</code></summary>\n",
+                escape_html(&call.name)
+            );
+            out.push_str("Arguments:\n\n");
+            push_markdown_code_block(&mut out, "json", &pretty_arguments(&call.arguments));
+            out.push_str("\nOutput:\n\n");
+            match &call.output {
+                Some(output) => push_markdown_code_block(&mut out, "", output),
+                None => out.push_str("_No result recorded._\n"),
+            }
+            out.push_str("\n</details>\n\n");
+        }
+        if !entry.text.is_empty() {
+            out.push_str(entry.text.trim_end());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Fences `code` with more backticks than any run inside it.
+fn push_markdown_code_block(out: &mut String, language: &str, code: &str) {
+    let longest_run = code.split(|ch| ch != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let _ = writeln!(out, "{fence}{language}\n{}\n{fence}", code.trim_end());
+}
+
+fn render_html(transcript: &Transcript) -> String {
+    let mut body = String::new();
+    for entry in &transcript.entries {
+        let role = entry.role.label();
+        let _ = writeln!(
+            body,
+            r#"<section class="entry {class}"><h2>{role}</h2>"#,
+            class = role.to_ascii_lowercase()
+        );
+        for call in &entry.tool_calls {
+            let output = match &call.output {
+                Some(output) => format!("<pre>{}</pre>", escape_html(output)),
+                None => "<p class=\"empty\">No result recorded.</p>".to_owned(),
+            };
+            let _ = writeln!(
+                body,
+                r#"<details><summary>Tool call: <code>{name}</code></summary><h3>Arguments</h3><pre>{arguments}</pre><h3>Output</h3>{output}</details>"#,
+                name = escape_html(&call.name),
+                arguments = escape_html(&pretty_arguments(&call.arguments)),
+            );
+        }
+        if !entry.text.is_empty() {
+            let _ = writeln!(
+                body,
+                r#"<div class="text">{}</div>"#,
+                escape_html(entry.text.trim_end())
+            );
+        }
+        body.push_str("</section>\n");
+    }
+    if transcript.entries.is_empty() {
+        body.push_str("<p class=\"empty\">No messages yet.</p>\n");
+    }
+
+    format!(
+        r#"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font: 14px system-ui, sans-serif; margin: 24px auto; max-width: 860px; color: #1e2f45; }}
+h1 {{ font-size: 18px; margin: 0 0 16px; }}
+h2 {{ font-size: 12px; text-transform: uppercase; letter-spacing: 0.04em; color: #5b6f88; margin: 0 0 6px; }}
+h3 {{ font-size: 12px; color: #5b6f88; margin: 8px 0 4px; }}
+.entry {{ border: 1px solid #d7e2ee; border-radius: 6px; padding: 10px 14px; margin-bottom: 10px; }}
+.entry.user {{ background: #f0f5fa; }}
+.entry.system {{ background: #fbf7ee; }}
+.text {{ white-space: pre-wrap; }}
+details {{ margin: 0 0 8px; }}
+summary {{ cursor: pointer; color: #447ebc; }}
+pre {{ background: #f6f8fa; padding: 8px; border-radius: 4px; overflow-x: auto; white-space: pre-wrap; }}
+.empty {{ color: #8799ae; }}
+.summary {{ color: #5b6f88; margin: -8px 0 16px; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{summary}{body}</body>
+</html>
+"#,
+        title = escape_html(&transcript.title),
+        summary = transcript
+            .summary
+            .as_deref()
+            .map_or_else(String::new, |summary| format!(
+                "<p class=\"summary\">{}</p>\n",
+                escape_html(summary.trim())
+            )),
+    )
+}
+
+fn pretty_arguments(arguments: &Value) -> String {
+    serde_json::to_string_pretty(arguments).unwrap_or_else(|_| arguments.to_string())
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use super::{ExportFormat, Transcript, TranscriptRole};
+    use crate::model::client::{ModelMessage, ModelToolCall};
+
+    fn sample_conversation() -> Vec<ModelMessage> {
+        vec![
+            ModelMessage::system("system prompt"),
+            ModelMessage::user("find <rust> notes"),
+            ModelMessage::assistant_tool_calls(
+                "",
+                vec![
+                    ModelToolCall {
+                        id: "call-1".to_owned(),
+                        name: "search_notes".to_owned(),
+                        arguments: json!({"query": "rust"}),
+                    },
+                    ModelToolCall {
+                        id: "call-2".to_owned(),
+                        name: "fetch_url".to_owned(),
+                        arguments: json!({"url": "https://example.com"}),
+                    },
+                ],
+            ),
+            ModelMessage::tool_result(
+                "page with ```fences```",
+                Some("call-2".to_owned()),
+                Some("fetch_url".to_owned()),
+            ),
+            ModelMessage::tool_result(
+                r#"{"results":[]}"#,
+                Some("call-1".to_owned()),
+                Some("search_notes".to_owned()),
+            ),
+            ModelMessage::assistant_text("No notes matched."),
+        ]
+    }
+
+    #[test]
+    fn from_conversation_groups_turns_and_pairs_tool_results() {
+        let transcript = Transcript::from_conversation("Chat", &sample_conversation());
+
+        assert_eq!(transcript.entries.len(), 2);
+        assert_eq!(transcript.entries[0].role, TranscriptRole::User);
+        let answer = &transcript.entries[1];
+        assert_eq!(answer.role, TranscriptRole::Assistant);
+        assert_eq!(answer.text, "No notes matched.");
+        assert_eq!(
+            answer.tool_calls[0].output.as_deref(),
+            Some(r#"{"results":[]}"#)
+        );
+        assert_eq!(
+            answer.tool_calls[1].output.as_deref(),
+            Some("page with ```fences```")
+        );
+    }
+
+    #[test]
+    fn renders_markdown_html_and_json_with_collapsible_tool_calls() {
+        let transcript = Transcript::from_conversation("Chat <1>", &sample_conversation());
+
+        let markdown = transcript.render(ExportFormat::Markdown);
+        assert!(markdown.starts_with("# Chat <1>\n\n## User\n\nfind <rust> notes\n"));
+        assert!(markdown.contains("<summary>Tool call: <code>search_notes</code></summary>"));
+        assert!(markdown.contains("````\npage with ```fences```\n````"));
+        assert!(markdown.ends_with("</details>\n\nNo notes matched.\n"));
+
+        let html = transcript.render(ExportFormat::Html);
+        assert!(html.contains("<title>Chat &lt;1&gt;</title>"));
+        assert!(html.contains(r#"<div class="text">find &lt;rust&gt; notes</div>"#));
+        assert_eq!(html.matches("<details>").count(), 2);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&transcript.render(ExportFormat::Json)).expect("valid json");
+        assert_eq!(json["entries"][0]["role"], "user");
+        assert!(json["entries"][0].get("tool_calls").is_none());
+        assert_eq!(json["entries"][1]["tool_calls"][0]["name"], "search_notes");
+        assert!(json.get("summary").is_none());
+
+        let summarized = Transcript {
+            summary: Some("Searched <rust> notes.".to_owned()),
+            ..transcript
+        };
+        assert!(
+            summarized
+                .render(ExportFormat::Markdown)
+                .starts_with("# Chat <1>\n\n_Searched <rust> notes._\n\n## User")
+        );
+        assert!(
+            summarized
+                .render(ExportFormat::Html)
+                .contains(r#"<p class="summary">Searched &lt;rust&gt; notes.</p>"#)
+        );
+    }
+
+    #[test]
+    fn formats_parse_from_names_and_extensions() {
+        assert_eq!(
+            "MD".parse::<ExportFormat>().ok(),
+            Some(ExportFormat::Markdown)
+        );
+        assert!("pdf".parse::<ExportFormat>().is_err());
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out/chat.HTM")),
+            Some(ExportFormat::Html)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("chat")), None);
+    }
+}
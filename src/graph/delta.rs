@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::ArchitectureGraph;
+
+/// Nodes a graph refresh touched, for the studio highlight overlay.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GraphChangeDelta {
+    /// Added or changed nodes, and both ends of every added or removed edge.
+    pub changed_node_ids: Vec<String>,
+    /// Unchanged neighbours one edge away from a changed node.
+    pub impact_node_ids: Vec<String>,
+}
+
+/// Empty without a `previous` graph, so the first build highlights nothing.
+pub fn graph_change_delta(
+    previous: Option<&ArchitectureGraph>,
+    current: &ArchitectureGraph,
+) -> GraphChangeDelta {
+    let Some(previous_graph) = previous else {
+        return GraphChangeDelta::default();
+    };
+
+    let previous_nodes_by_id = previous_graph
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect::<BTreeMap<_, _>>();
+    let current_nodes_by_id = current
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut changed_node_ids = BTreeSet::new();
+
+    for node in &current.nodes {
+        match previous_nodes_by_id.get(node.id.as_str()) {
+            None => {
+                changed_node_ids.insert(node.id.clone());
+            }
+            Some(previous_node) if *previous_node != node => {
+                changed_node_ids.insert(node.id.clone());
+            }
+            Some(_) => {}
+        }
+    }
+
+    let previous_edges = previous_graph
+        .edges
+        .iter()
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    let current_edges = current.edges.iter().cloned().collect::<BTreeSet<_>>();
+
+    for edge in current_edges.difference(&previous_edges) {
+        if current_nodes_by_id.contains_key(edge.from.as_str()) {
+            changed_node_ids.insert(edge.from.clone());
+        }
+        if current_nodes_by_id.contains_key(edge.to.as_str()) {
+            changed_node_ids.insert(edge.to.clone());
+        }
+    }
+
+    for edge in previous_edges.difference(&current_edges) {
+        if current_nodes_by_id.contains_key(edge.from.as_str()) {
+            changed_node_ids.insert(edge.from.clone());
+        }
+        if current_nodes_by_id.contains_key(edge.to.as_str()) {
+            changed_node_ids.insert(edge.to.clone());
+        }
+    }
+
+    let mut impact_node_ids = BTreeSet::new();
+    if !changed_node_ids.is_empty() {
+        for edge in &current.edges {
+            let from_changed = changed_node_ids.contains(edge.from.as_str());
+            let to_changed = changed_node_ids.contains(edge.to.as_str());
+            if from_changed && !to_changed {
+                impact_node_ids.insert(edge.to.clone());
+            } else if to_changed && !from_changed {
+                impact_node_ids.insert(edge.from.clone());
+            }
+        }
+    }
+
+    GraphChangeDelta {
+        changed_node_ids: changed_node_ids.into_iter().collect(),
+        impact_node_ids: impact_node_ids.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::graph_change_delta;
+    use crate::graph::{
+        ArchitectureEdge, ArchitectureEdgeKind, ArchitectureGraph, ArchitectureNode,
+        ArchitectureNodeKind,
+    };
+
+    fn graph_for_test(
+        revision: u64,
+        node_ids: &[&str],
+        edges: &[(&str, &str)],
+    ) -> ArchitectureGraph {
+        ArchitectureGraph {
+            nodes: node_ids
+                .iter()
+                .map(|node_id| ArchitectureNode {
+                    id: (*node_id).to_owned(),
+                    display_label: (*node_id).to_owned(),
+                    kind: ArchitectureNodeKind::Module,
+                    path: None,
+                })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(from, to)| ArchitectureEdge {
+                    from: (*from).to_owned(),
+                    to: (*to).to_owned(),
+                    relation: ArchitectureEdgeKind::DeclaresModule,
+                })
+                .collect(),
+            revision,
+            generated_at: UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn graph_change_delta_is_empty_without_previous_graph() {
+        let current = graph_for_test(2, &["module:crate"], &[("module:crate", "module:crate")]);
+        let delta = graph_change_delta(None, &current);
+        assert!(delta.changed_node_ids.is_empty());
+        assert!(delta.impact_node_ids.is_empty());
+    }
+
+    #[test]
+    fn graph_change_delta_detects_added_nodes_and_one_hop_impact() {
+        let previous = graph_for_test(
+            1,
+            &["module:crate", "module:crate::tools"],
+            &[("module:crate", "module:crate::tools")],
+        );
+        let current = graph_for_test(
+            2,
+            &[
+                "module:crate",
+                "module:crate::tools",
+                "module:crate::tools::parser",
+            ],
+            &[
+                ("module:crate", "module:crate::tools"),
+                ("module:crate::tools", "module:crate::tools::parser"),
+            ],
+        );
+
+        let delta = graph_change_delta(Some(&previous), &current);
+        assert_eq!(
+            delta.changed_node_ids,
+            vec![
+                "module:crate::tools".to_owned(),
+                "module:crate::tools::parser".to_owned()
+            ]
+        );
+        assert_eq!(delta.impact_node_ids, vec!["module:crate".to_owned()]);
+    }
+}
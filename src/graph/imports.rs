@@ -0,0 +1,364 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::{ArchitectureEdge, ArchitectureEdgeKind, GraphFragment, GraphSource, file_node_id};
+
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
+const SCRIPT_RESOLVE_SUFFIXES: &[&str] = &[".ts", ".tsx", ".js", ".jsx"];
+const SCRIPT_INDEX_FILES: &[&str] = &["index.ts", "index.tsx", "index.js", "index.jsx"];
+
+/// TypeScript/JavaScript and Python files as `file:` nodes, linked by `Imports` edges for
+/// imports that resolve to another workspace file. Scripts follow relative specifiers
+/// (`./x`, `../y`); Python follows relative imports and absolute imports rooted at the
+/// workspace or its `src/` directory. Package imports are left out.
+pub struct ImportGraphSource;
+
+impl GraphSource for ImportGraphSource {
+    fn name(&self) -> &'static str {
+        "imports"
+    }
+
+    fn handles(&self, relative_path: &Path) -> bool {
+        source_language(relative_path).is_some()
+    }
+
+    fn scan(
+        &self,
+        workspace_root: &Path,
+        files: &[PathBuf],
+        fragment: &mut GraphFragment,
+    ) -> Result<()> {
+        let known_files = files.iter().cloned().collect::<BTreeSet<_>>();
+        for relative_path in files {
+            let file_id = fragment.insert_file_node(relative_path);
+            let source = fs::read_to_string(workspace_root.join(relative_path))
+                .with_context(|| format!("failed to read `{}`", relative_path.display()))?;
+            let targets = match source_language(relative_path) {
+                Some(SourceLanguage::Script) => script_import_specifiers(&source)
+                    .iter()
+                    .filter_map(|specifier| {
+                        resolve_script_import(relative_path, specifier, &known_files)
+                    })
+                    .collect::<Vec<_>>(),
+                Some(SourceLanguage::Python) => python_imports(&source)
+                    .iter()
+                    .filter_map(|import| resolve_python_import(relative_path, import, &known_files))
+                    .collect(),
+                None => Vec::new(),
+            };
+            for target in targets {
+                if &target == relative_path {
+                    continue;
+                }
+                fragment.edges.insert(ArchitectureEdge {
+                    from: file_id.clone(),
+                    to: file_node_id(&target),
+                    relation: ArchitectureEdgeKind::Imports,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceLanguage {
+    Script,
+    Python,
+}
+
+fn source_language(path: &Path) -> Option<SourceLanguage> {
+    let extension = path.extension()?.to_str()?;
+    if SCRIPT_EXTENSIONS.contains(&extension) {
+        Some(SourceLanguage::Script)
+    } else if extension == "py" {
+        Some(SourceLanguage::Python)
+    } else {
+        None
+    }
+}
+
+/// Relative module specifiers from `import`/`export ... from`, side-effect imports,
+/// `require(...)`, and dynamic `import(...)`.
+fn script_import_specifiers(source: &str) -> Vec<String> {
+    let mut specifiers = Vec::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if line.starts_with("//") || line.starts_with('*') || line.starts_with("/*") {
+            continue;
+        }
+        let has_import = line.starts_with("import")
+            || line.starts_with("export")
+            || line.contains("from '")
+            || line.contains("from \"")
+            || line.contains("require(")
+            || line.contains("import(");
+        if !has_import {
+            continue;
+        }
+        specifiers.extend(
+            quoted_literals(line)
+                .into_iter()
+                .filter(|literal| literal.starts_with("./") || literal.starts_with("../")),
+        );
+    }
+    specifiers
+}
+
+fn quoted_literals(line: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if !matches!(ch, '\'' | '"' | '`') {
+            continue;
+        }
+        let literal = chars.by_ref().take_while(|next| *next != ch).collect();
+        literals.push(literal);
+    }
+    literals
+}
+
+fn resolve_script_import(
+    importing_file: &Path,
+    specifier: &str,
+    known_files: &BTreeSet<PathBuf>,
+) -> Option<PathBuf> {
+    let base = normalize_relative(&importing_file.parent()?.join(specifier))?;
+    // TypeScript ESM code imports `./x.js` for a `./x.ts` source file.
+    let without_script_extension = base
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| SCRIPT_EXTENSIONS.contains(ext))
+        .map(|_| base.with_extension(""));
+
+    let mut candidates = vec![base.clone()];
+    for stem in std::iter::once(&base).chain(without_script_extension.as_ref()) {
+        candidates.extend(SCRIPT_RESOLVE_SUFFIXES.iter().map(|suffix| {
+            let mut path = stem.clone().into_os_string();
+            path.push(suffix);
+            PathBuf::from(path)
+        }));
+    }
+    candidates.extend(SCRIPT_INDEX_FILES.iter().map(|index| base.join(index)));
+    candidates
+        .into_iter()
+        .find(|candidate| known_files.contains(candidate))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PythonImport {
+    /// Leading dots of a relative import; 0 for absolute imports.
+    level: usize,
+    module: String,
+    names: Vec<String>,
+}
+
+fn python_imports(source: &str) -> Vec<PythonImport> {
+    let mut imports = Vec::new();
+    for line in source.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if let Some(rest) = line.strip_prefix("import ") {
+            imports.extend(rest.split(',').filter_map(|part| {
+                let module = part.split_whitespace().next()?;
+                Some(PythonImport {
+                    level: 0,
+                    module: module.to_owned(),
+                    names: Vec::new(),
+                })
+            }));
+        } else if let Some(rest) = line.strip_prefix("from ")
+            && let Some((module, names)) = rest.split_once(" import ")
+        {
+            let module = module.trim();
+            let level = module.chars().take_while(|ch| *ch == '.').count();
+            let names = names
+                .trim_matches(|ch| matches!(ch, '(' | ')' | '\\') || ch.is_whitespace())
+                .split(',')
+                .filter_map(|name| name.split_whitespace().next())
+                .filter(|name| *name != "*" && *name != "(")
+                .map(str::to_owned)
+                .collect();
+            imports.push(PythonImport {
+                level,
+                module: module[level..].to_owned(),
+                names,
+            });
+        }
+    }
+    imports
+}
+
+fn resolve_python_import(
+    importing_file: &Path,
+    import: &PythonImport,
+    known_files: &BTreeSet<PathBuf>,
+) -> Option<PathBuf> {
+    let bases = if import.level > 0 {
+        let mut base = importing_file.parent()?.to_path_buf();
+        for _ in 1..import.level {
+            base = base.parent()?.to_path_buf();
+        }
+        vec![base]
+    } else {
+        vec![PathBuf::new(), PathBuf::from("src")]
+    };
+
+    let module_path = import
+        .module
+        .split('.')
+        .filter(|part| !part.is_empty())
+        .collect::<PathBuf>();
+    for base in &bases {
+        let module_dir = base.join(&module_path);
+        // `from pkg import submodule` names a module file more specifically than `pkg`.
+        for name in &import.names {
+            if let Some(path) = python_module_file(&module_dir.join(name), known_files) {
+                return Some(path);
+            }
+        }
+        if !import.module.is_empty()
+            && let Some(path) = python_module_file(&module_dir, known_files)
+        {
+            return Some(path);
+        }
+    }
+    None
+}
+
+fn python_module_file(module_dir: &Path, known_files: &BTreeSet<PathBuf>) -> Option<PathBuf> {
+    [
+        module_dir.with_extension("py"),
+        module_dir.join("__init__.py"),
+    ]
+    .into_iter()
+    .find(|candidate| known_files.contains(candidate))
+}
+
+/// Resolves `.` and `..` in a workspace-relative path; `None` when it escapes the root.
+fn normalize_relative(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::Normal(part) => normalized.push(part),
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::UNIX_EPOCH;
+
+    use crate::graph::{
+        ArchitectureEdgeKind, ArchitectureNodeKind, build_graph_from_sources, default_graph_sources,
+    };
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    use super::{PythonImport, python_imports, script_import_specifiers};
+
+    #[test]
+    fn import_parsers_extract_relative_scripts_and_python_modules() {
+        assert_eq!(
+            script_import_specifiers(
+                "import { a } from './a';\n\
+                 import React from 'react';\n\
+                 export * from \"../shared/b.js\";\n\
+                 }} from './multi';\n\
+                 const c = require('./c');\n\
+                 // import './commented';\n"
+            ),
+            ["./a", "../shared/b.js", "./multi", "./c"]
+        );
+        assert_eq!(
+            python_imports(
+                "import os, app.models as m\nfrom . import views  # local\nfrom ..core.db import (Session,\n"
+            ),
+            [
+                PythonImport {
+                    level: 0,
+                    module: "os".to_owned(),
+                    names: Vec::new(),
+                },
+                PythonImport {
+                    level: 0,
+                    module: "app.models".to_owned(),
+                    names: Vec::new(),
+                },
+                PythonImport {
+                    level: 1,
+                    module: String::new(),
+                    names: vec!["views".to_owned()],
+                },
+                PythonImport {
+                    level: 2,
+                    module: "core.db".to_owned(),
+                    names: vec!["Session".to_owned()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn default_sources_graph_rust_typescript_and_python_together() {
+        let root = temp_path("graph-polyglot");
+        for dir in ["src", "web/components", "app/api"] {
+            fs::create_dir_all(root.join(dir)).expect("dir should be created");
+        }
+        for (path, content) in [
+            ("src/lib.rs", "pub fn run() {}\n"),
+            ("web/main.ts", "import { Button } from './components';\n"),
+            ("web/components/index.ts", "export * from './button.js';\n"),
+            ("web/components/button.tsx", "import React from 'react';\n"),
+            ("app/__init__.py", ""),
+            (
+                "app/api/routes.py",
+                "from ..models import User\nimport app.settings\n",
+            ),
+            ("app/models.py", "import os\n"),
+            ("app/settings.py", ""),
+            ("notes.md", "import './ignored';\n"),
+        ] {
+            fs::write(root.join(path), content).expect("file should be written");
+        }
+
+        let graph = build_graph_from_sources(&root, &default_graph_sources(), 1, UNIX_EPOCH)
+            .expect("build should succeed");
+
+        assert!(graph.nodes.iter().any(|node| node.id == "module:crate"));
+        assert!(graph.nodes.iter().any(|node| {
+            node.id == "file:web/components/button.tsx" && node.kind == ArchitectureNodeKind::File
+        }));
+        assert!(!graph.nodes.iter().any(|node| node.id == "file:notes.md"));
+        let imports = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.relation == ArchitectureEdgeKind::Imports)
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            imports,
+            [
+                ("file:app/api/routes.py", "file:app/models.py"),
+                ("file:app/api/routes.py", "file:app/settings.py"),
+                (
+                    "file:web/components/index.ts",
+                    "file:web/components/button.tsx"
+                ),
+                ("file:web/main.ts", "file:web/components/index.ts"),
+            ]
+        );
+
+        remove_dir_if_exists(&root);
+    }
+}
@@ -6,8 +6,19 @@ use std::time::SystemTime;
 use anyhow::{Context, Result, ensure};
 use serde::{Deserialize, Serialize};
 
+pub mod delta;
+pub mod imports;
+#[cfg(feature = "runtime")]
+pub mod remote;
+#[cfg(feature = "runtime")]
+pub mod rules;
+#[cfg(feature = "runtime")]
+pub mod updates;
+#[cfg(feature = "runtime")]
 pub mod watch;
 
+pub use self::imports::ImportGraphSource;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ArchitectureGraph {
     pub nodes: Vec<ArchitectureNode>,
@@ -28,6 +39,8 @@ pub struct ArchitectureNode {
 pub enum ArchitectureNodeKind {
     File,
     Module,
+    /// A `mod name;` declaration with no matching `name.rs` or `name/mod.rs`.
+    UnresolvedModule,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -42,6 +55,244 @@ pub enum ArchitectureEdgeKind {
     DefinesModule,
     DeclaresModule,
     ResolvesToFile,
+    Imports,
+    Uses,
+}
+
+/// Resolves the workspace root for `studio` and `graph` commands: `workspace` when given
+/// (relative paths resolve against the launch directory), otherwise the launch directory.
+/// The result is canonicalized and must be an existing directory.
+pub fn resolve_workspace_root(workspace: Option<&Path>) -> Result<PathBuf> {
+    let current_dir = std::env::current_dir().context("failed to resolve current directory")?;
+    let Some(workspace) = workspace else {
+        return Ok(current_dir);
+    };
+    ensure!(
+        !workspace.as_os_str().is_empty(),
+        "--workspace cannot be empty"
+    );
+
+    let candidate = current_dir.join(workspace);
+    let workspace_root = fs::canonicalize(&candidate).with_context(|| {
+        format!(
+            "--workspace `{}` does not exist or is not accessible",
+            workspace.display()
+        )
+    })?;
+    ensure!(
+        workspace_root.is_dir(),
+        "--workspace `{}` is not a directory",
+        workspace.display()
+    );
+    Ok(workspace_root)
+}
+
+/// Resolves `--workspace` plus repeated `--extra-root` values into one root set.
+pub fn resolve_workspace_roots(
+    workspace: Option<&Path>,
+    extra_roots: &[String],
+) -> Result<WorkspaceRoots> {
+    let extra = extra_roots
+        .iter()
+        .map(|spec| ExtraWorkspaceRoot::parse(spec))
+        .collect::<Result<Vec<_>>>()?;
+    WorkspaceRoots::from(resolve_workspace_root(workspace)?).with_extra_roots(extra)
+}
+
+/// Marks node ids, paths, and subsystem keys that belong to an extra workspace root,
+/// for example `file:@shared/src/lib.rs` or `module:@shared::crate`.
+pub const EXTRA_ROOT_MARKER: char = '@';
+
+/// A directory graphed next to the primary workspace, such as a sibling library checkout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraWorkspaceRoot {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl ExtraWorkspaceRoot {
+    /// Parses a `--extra-root` value, `NAME=PATH` or `PATH`. Without a name the directory
+    /// name is used. Names may contain ASCII letters, digits, `-`, `_`, and `.`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (explicit_name, raw_path) = match spec.split_once('=') {
+            Some((name, path)) => (Some(name.trim()), path),
+            None => (None, spec),
+        };
+        let path = resolve_workspace_root(Some(Path::new(raw_path.trim())))
+            .with_context(|| format!("invalid --extra-root `{spec}`"))?;
+        let name = match explicit_name {
+            Some(name) => name.to_owned(),
+            None => path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default()
+                .to_owned(),
+        };
+        ensure!(
+            is_valid_root_name(&name),
+            "invalid --extra-root name `{name}`; use NAME=PATH with ASCII letters, digits, `-`, `_`, or `.`"
+        );
+        Ok(Self { name, path })
+    }
+}
+
+/// The primary workspace plus any extra roots. With no extra roots, graphs are identical to
+/// single-root graphs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceRoots {
+    pub primary: PathBuf,
+    pub extra: Vec<ExtraWorkspaceRoot>,
+}
+
+impl From<PathBuf> for WorkspaceRoots {
+    fn from(primary: PathBuf) -> Self {
+        Self {
+            primary,
+            extra: Vec::new(),
+        }
+    }
+}
+
+impl WorkspaceRoots {
+    /// Adds extra roots, rejecting duplicate names and roots that overlap another root.
+    pub fn with_extra_roots(mut self, extra: Vec<ExtraWorkspaceRoot>) -> Result<Self> {
+        for root in extra {
+            ensure!(
+                self.extra.iter().all(|existing| existing.name != root.name),
+                "duplicate --extra-root name `{}`",
+                root.name
+            );
+            for existing in std::iter::once(&self.primary)
+                .chain(self.extra.iter().map(|existing| &existing.path))
+            {
+                ensure!(
+                    !root.path.starts_with(existing) && !existing.starts_with(&root.path),
+                    "--extra-root `{}` overlaps `{}`",
+                    root.path.display(),
+                    existing.display()
+                );
+            }
+            self.extra.push(root);
+        }
+        Ok(self)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.primary.as_path())
+            .chain(self.extra.iter().map(|root| root.path.as_path()))
+    }
+}
+
+/// Splits a node id from an extra root into the root name and the id the node has in its
+/// own single-root graph. Returns `None` for primary-root ids.
+pub fn split_extra_root_node_id(node_id: &str) -> Option<(&str, String)> {
+    if let Some(rest) = node_id
+        .strip_prefix("file:")
+        .and_then(|rest| rest.strip_prefix(EXTRA_ROOT_MARKER))
+    {
+        let (root, path) = rest.split_once('/')?;
+        return Some((root, format!("file:{path}")));
+    }
+    let rest = node_id
+        .strip_prefix("module:")?
+        .strip_prefix(EXTRA_ROOT_MARKER)?;
+    let (root, module_path) = rest.split_once("::")?;
+    Some((root, format!("module:{module_path}")))
+}
+
+fn is_valid_root_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+}
+
+/// Builds one graph over every root with the default graph sources. Extra-root node ids are namespaced with
+/// `@name` and their paths are relative to the primary root (absolute when no
+/// relative path exists), so consumers that join paths onto the primary root keep working.
+pub fn build_workspace_graph(roots: &WorkspaceRoots, revision: u64) -> Result<ArchitectureGraph> {
+    build_workspace_graph_at(roots, revision, SystemTime::now())
+}
+
+pub fn build_workspace_graph_at(
+    roots: &WorkspaceRoots,
+    revision: u64,
+    generated_at: SystemTime,
+) -> Result<ArchitectureGraph> {
+    let sources = default_graph_sources();
+    let mut graph = build_graph_from_sources(&roots.primary, &sources, revision, generated_at)?;
+    if roots.extra.is_empty() {
+        return Ok(graph);
+    }
+
+    let mut nodes = graph
+        .nodes
+        .drain(..)
+        .map(|node| (node.id.clone(), node))
+        .collect::<BTreeMap<_, _>>();
+    let mut edges = graph.edges.drain(..).collect::<BTreeSet<_>>();
+    for root in &roots.extra {
+        let root_graph = build_graph_from_sources(&root.path, &sources, revision, generated_at)
+            .with_context(|| format!("failed to graph extra root `{}`", root.name))?;
+        let path_prefix = path_relative_to(&roots.primary, &root.path);
+        for node in root_graph.nodes {
+            let id = namespaced_node_id(&root.name, &node.id);
+            nodes.insert(
+                id.clone(),
+                ArchitectureNode {
+                    id,
+                    path: node.path.map(|path| {
+                        let path = path_prefix.join(path);
+                        if path.is_absolute() {
+                            path.display().to_string()
+                        } else {
+                            path_to_slash_string(&path)
+                        }
+                    }),
+                    ..node
+                },
+            );
+        }
+        edges.extend(root_graph.edges.into_iter().map(|edge| ArchitectureEdge {
+            from: namespaced_node_id(&root.name, &edge.from),
+            to: namespaced_node_id(&root.name, &edge.to),
+            relation: edge.relation,
+        }));
+    }
+
+    graph.nodes = nodes.into_values().collect();
+    graph.edges = edges.into_iter().collect();
+    Ok(graph)
+}
+
+fn namespaced_node_id(root_name: &str, node_id: &str) -> String {
+    if let Some(path) = node_id.strip_prefix("file:") {
+        format!("file:{EXTRA_ROOT_MARKER}{root_name}/{path}")
+    } else if let Some(module_path) = node_id.strip_prefix("module:") {
+        format!("module:{EXTRA_ROOT_MARKER}{root_name}::{module_path}")
+    } else {
+        format!("{EXTRA_ROOT_MARKER}{root_name}/{node_id}")
+    }
+}
+
+fn path_relative_to(base: &Path, target: &Path) -> PathBuf {
+    let base_components = base.components().collect::<Vec<_>>();
+    let target_components = target.components().collect::<Vec<_>>();
+    let shared = base_components
+        .iter()
+        .zip(&target_components)
+        .take_while(|(base, target)| base == target)
+        .count();
+    if shared == 0 {
+        return target.to_path_buf();
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in shared..base_components.len() {
+        relative.push("..");
+    }
+    relative.extend(&target_components[shared..]);
+    relative
 }
 
 pub fn build_rust_workspace_graph(
@@ -55,6 +306,17 @@ pub fn build_rust_workspace_graph_at(
     workspace_root: &Path,
     revision: u64,
     generated_at: SystemTime,
+) -> Result<ArchitectureGraph> {
+    build_graph_from_sources(workspace_root, &[&RustModuleSource], revision, generated_at)
+}
+
+/// Builds a graph of one root from every source in `sources`, scanning each file with the
+/// sources that handle it.
+pub fn build_graph_from_sources(
+    workspace_root: &Path,
+    sources: &[&dyn GraphSource],
+    revision: u64,
+    generated_at: SystemTime,
 ) -> Result<ArchitectureGraph> {
     ensure!(
         workspace_root.is_dir(),
@@ -62,15 +324,60 @@ pub fn build_rust_workspace_graph_at(
         workspace_root.display()
     );
 
-    let rust_files = collect_rust_files(workspace_root)?;
-    let rust_file_set = rust_files.iter().cloned().collect::<BTreeSet<_>>();
+    let files = collect_source_files(workspace_root, sources)?;
+    let mut fragment = GraphFragment::default();
+    for source in sources {
+        let source_files = files
+            .iter()
+            .filter(|path| source.handles(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        if source_files.is_empty() {
+            continue;
+        }
+        source
+            .scan(workspace_root, &source_files, &mut fragment)
+            .with_context(|| format!("{} graph source failed", source.name()))?;
+    }
+
+    Ok(ArchitectureGraph {
+        nodes: fragment.nodes.into_values().collect(),
+        edges: fragment.edges.into_iter().collect(),
+        revision,
+        generated_at,
+    })
+}
+
+/// Scans workspace files into graph nodes and edges. Each source claims files through
+/// `handles`; node ids must use the `file:` or `module:` prefix so extra-root namespacing
+/// and subsystem grouping apply to them.
+pub trait GraphSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn handles(&self, relative_path: &Path) -> bool;
+
+    /// Adds nodes and edges for `files`, which are workspace-relative, sorted, and all
+    /// accepted by `handles`.
+    fn scan(
+        &self,
+        workspace_root: &Path,
+        files: &[PathBuf],
+        fragment: &mut GraphFragment,
+    ) -> Result<()>;
+}
 
-    let mut nodes = BTreeMap::<String, ArchitectureNode>::new();
-    let mut edges = BTreeSet::<ArchitectureEdge>::new();
+/// Nodes and edges collected from the graph sources of one root.
+#[derive(Debug, Default)]
+pub struct GraphFragment {
+    pub nodes: BTreeMap<String, ArchitectureNode>,
+    pub edges: BTreeSet<ArchitectureEdge>,
+}
 
-    for relative_path in &rust_files {
+impl GraphFragment {
+    /// Inserts a `file:` node for `relative_path` and returns its id.
+    pub fn insert_file_node(&mut self, relative_path: &Path) -> String {
         let file_id = file_node_id(relative_path);
-        nodes.insert(
+        self.nodes.insert(
             file_id.clone(),
             ArchitectureNode {
                 id: file_id.clone(),
@@ -83,79 +390,144 @@ pub fn build_rust_workspace_graph_at(
                 path: Some(path_to_slash_string(relative_path)),
             },
         );
+        file_id
+    }
+}
 
-        let module_path = module_path_for_file(relative_path);
-        let module_id = module_node_id(&module_path);
-        nodes.insert(
-            module_id.clone(),
-            ArchitectureNode {
-                id: module_id.clone(),
-                display_label: module_path.clone(),
-                kind: ArchitectureNodeKind::Module,
-                path: Some(path_to_slash_string(relative_path)),
-            },
-        );
-        edges.insert(ArchitectureEdge {
-            from: file_id,
-            to: module_id.clone(),
-            relation: ArchitectureEdgeKind::DefinesModule,
-        });
+/// Sources used for studio, `serve`, and `graph` commands: Rust modules plus
+/// TypeScript/JavaScript and Python imports.
+pub fn default_graph_sources() -> [&'static dyn GraphSource; 2] {
+    [&RustModuleSource, &ImportGraphSource]
+}
+
+/// Rust files and their `mod` declarations, resolved to module files, plus `Uses` edges
+/// from top-level `use crate::`/`super::`/`self::` declarations to the used module.
+pub struct RustModuleSource;
+
+impl GraphSource for RustModuleSource {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn handles(&self, relative_path: &Path) -> bool {
+        relative_path.extension().and_then(|ext| ext.to_str()) == Some("rs")
+    }
 
-        let source = fs::read_to_string(workspace_root.join(relative_path))
-            .with_context(|| format!("failed to read `{}`", relative_path.display()))?;
-        for declaration in parse_module_declarations(&source) {
-            let child_path = format!("{module_path}::{}", declaration.name);
-            let child_id = module_node_id(&child_path);
-
-            nodes
-                .entry(child_id.clone())
-                .or_insert_with(|| ArchitectureNode {
-                    id: child_id.clone(),
-                    display_label: child_path,
+    fn scan(
+        &self,
+        workspace_root: &Path,
+        files: &[PathBuf],
+        fragment: &mut GraphFragment,
+    ) -> Result<()> {
+        let rust_file_set = files.iter().cloned().collect::<BTreeSet<_>>();
+        let mut module_uses = Vec::new();
+        let mut unresolved_modules = Vec::new();
+        for relative_path in files {
+            let file_id = fragment.insert_file_node(relative_path);
+
+            let module_path = module_path_for_file(relative_path);
+            let module_id = module_node_id(&module_path);
+            fragment.nodes.insert(
+                module_id.clone(),
+                ArchitectureNode {
+                    id: module_id.clone(),
+                    display_label: module_path.clone(),
                     kind: ArchitectureNodeKind::Module,
-                    path: None,
-                });
-            edges.insert(ArchitectureEdge {
-                from: module_id.clone(),
-                to: child_id.clone(),
-                relation: ArchitectureEdgeKind::DeclaresModule,
+                    path: Some(path_to_slash_string(relative_path)),
+                },
+            );
+            fragment.edges.insert(ArchitectureEdge {
+                from: file_id,
+                to: module_id.clone(),
+                relation: ArchitectureEdgeKind::DefinesModule,
             });
 
-            if declaration.inline {
-                continue;
+            let source = fs::read_to_string(workspace_root.join(relative_path))
+                .with_context(|| format!("failed to read `{}`", relative_path.display()))?;
+            module_uses.push((module_path.clone(), parse_use_paths(&source)));
+            for declaration in parse_module_declarations(&source) {
+                let child_path = format!("{module_path}::{}", declaration.name);
+                let child_id = module_node_id(&child_path);
+
+                fragment
+                    .nodes
+                    .entry(child_id.clone())
+                    .or_insert_with(|| ArchitectureNode {
+                        id: child_id.clone(),
+                        display_label: child_path,
+                        kind: ArchitectureNodeKind::Module,
+                        path: None,
+                    });
+                fragment.edges.insert(ArchitectureEdge {
+                    from: module_id.clone(),
+                    to: child_id.clone(),
+                    relation: ArchitectureEdgeKind::DeclaresModule,
+                });
+
+                if declaration.inline {
+                    continue;
+                }
+
+                match resolve_declared_module_file(relative_path, &declaration.name, &rust_file_set)
+                {
+                    Some(resolved_relative_file) => {
+                        fragment.edges.insert(ArchitectureEdge {
+                            from: child_id,
+                            to: file_node_id(&resolved_relative_file),
+                            relation: ArchitectureEdgeKind::ResolvesToFile,
+                        });
+                    }
+                    None if declaration.top_level => unresolved_modules.push(child_id),
+                    None => {}
+                }
             }
+        }
 
-            if let Some(resolved_relative_file) =
-                resolve_declared_module_file(relative_path, &declaration.name, &rust_file_set)
+        // A file elsewhere may still define the module (its node then has a path).
+        for module_id in unresolved_modules {
+            if let Some(node) = fragment.nodes.get_mut(&module_id)
+                && node.path.is_none()
             {
-                let resolved_file_id = file_node_id(&resolved_relative_file);
-                edges.insert(ArchitectureEdge {
-                    from: child_id,
-                    to: resolved_file_id,
-                    relation: ArchitectureEdgeKind::ResolvesToFile,
-                });
+                node.kind = ArchitectureNodeKind::UnresolvedModule;
             }
         }
-    }
 
-    Ok(ArchitectureGraph {
-        nodes: nodes.into_values().collect(),
-        edges: edges.into_iter().collect(),
-        revision,
-        generated_at,
-    })
+        // Resolved after every file is scanned so uses can target modules declared later.
+        for (module_path, use_paths) in module_uses {
+            let module_id = module_node_id(&module_path);
+            for use_path in use_paths {
+                let Some(target_id) = resolve_use_target(&module_path, &use_path, fragment) else {
+                    continue;
+                };
+                if target_id != module_id {
+                    fragment.edges.insert(ArchitectureEdge {
+                        from: module_id.clone(),
+                        to: target_id,
+                        relation: ArchitectureEdgeKind::Uses,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
-fn collect_rust_files(workspace_root: &Path) -> Result<Vec<PathBuf>> {
+/// Workspace-relative files any of `sources` handles, sorted by slash path. Build output,
+/// VCS, editor, and dependency directories are skipped.
+pub(crate) fn collect_source_files(
+    workspace_root: &Path,
+    sources: &[&dyn GraphSource],
+) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
-    collect_rust_files_recursive(workspace_root, workspace_root, &mut files)?;
+    collect_source_files_recursive(workspace_root, workspace_root, sources, &mut files)?;
     files.sort_by_key(|path| path_to_slash_string(path.as_path()));
     Ok(files)
 }
 
-fn collect_rust_files_recursive(
+fn collect_source_files_recursive(
     workspace_root: &Path,
     current_dir: &Path,
+    sources: &[&dyn GraphSource],
     files: &mut Vec<PathBuf>,
 ) -> Result<()> {
     let mut entries = fs::read_dir(current_dir)
@@ -174,11 +546,11 @@ fn collect_rust_files_recursive(
             if should_skip_dir(name.to_string_lossy().as_ref()) {
                 continue;
             }
-            collect_rust_files_recursive(workspace_root, &path, files)?;
+            collect_source_files_recursive(workspace_root, &path, sources, files)?;
             continue;
         }
 
-        if !file_type.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("rs") {
+        if !file_type.is_file() {
             continue;
         }
         let relative_path = path.strip_prefix(workspace_root).with_context(|| {
@@ -188,16 +560,19 @@ fn collect_rust_files_recursive(
                 path.display()
             )
         })?;
-        files.push(relative_path.to_path_buf());
+        if sources.iter().any(|source| source.handles(relative_path)) {
+            files.push(relative_path.to_path_buf());
+        }
     }
 
     Ok(())
 }
 
-fn should_skip_dir(name: &str) -> bool {
+/// Build output, VCS, editor, and dependency directories no workspace scan descends into.
+pub(crate) fn should_skip_dir(name: &str) -> bool {
     matches!(
         name,
-        "target" | ".git" | ".idea" | ".vscode" | "node_modules"
+        "target" | ".git" | ".idea" | ".vscode" | "node_modules" | "__pycache__" | ".venv" | "venv"
     )
 }
 
@@ -284,6 +659,8 @@ fn module_path_for_src_file(relative_path: &Path) -> String {
 struct ModuleDeclaration {
     name: String,
     inline: bool,
+    /// Unindented; indented declarations may sit in an inline module or a string literal.
+    top_level: bool,
 }
 
 fn parse_module_declarations(source: &str) -> Vec<ModuleDeclaration> {
@@ -331,12 +708,125 @@ fn parse_module_declarations(source: &str) -> Vec<ModuleDeclaration> {
         declarations.push(ModuleDeclaration {
             name: module_name,
             inline,
+            top_level: !line.starts_with(char::is_whitespace),
         });
     }
 
     declarations
 }
 
+/// Paths named by unindented `use` declarations, with `{...}` groups expanded and
+/// aliases dropped. Indented uses (inside functions or inline `mod tests`) are skipped
+/// because `super`/`self` there refer to a different module.
+fn parse_use_paths(source: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut statement: Option<String> = None;
+    for line in source.lines() {
+        let code = line.split("//").next().unwrap_or_default();
+        let pending = match statement.take() {
+            Some(mut pending) => {
+                pending.push(' ');
+                pending.push_str(code.trim());
+                pending
+            }
+            None => {
+                if code.starts_with(char::is_whitespace) {
+                    continue;
+                }
+                let Some((prefix, rest)) = code.split_once("use ") else {
+                    continue;
+                };
+                if !is_use_visibility(prefix.trim()) {
+                    continue;
+                }
+                rest.trim().to_owned()
+            }
+        };
+        match pending.split_once(';') {
+            Some((tree, _)) => paths.extend(expand_use_tree(tree)),
+            None => statement = Some(pending),
+        }
+    }
+    paths
+}
+
+fn expand_use_tree(tree: &str) -> Vec<String> {
+    let tree = tree.trim();
+    let Some(open) = tree.find('{') else {
+        let path = tree.split(" as ").next().unwrap_or_default().trim();
+        let path = path.strip_suffix("::self").unwrap_or(path);
+        return (!path.is_empty())
+            .then(|| path.to_owned())
+            .into_iter()
+            .collect();
+    };
+    let prefix = &tree[..open];
+    let inner = tree[open + 1..]
+        .strip_suffix('}')
+        .unwrap_or(&tree[open + 1..]);
+
+    let mut parts = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (index, ch) in inner.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&inner[start..]);
+
+    parts
+        .into_iter()
+        .flat_map(expand_use_tree)
+        .map(|path| match path.as_str() {
+            "self" => prefix.trim_end_matches("::").to_owned(),
+            _ => format!("{prefix}{path}"),
+        })
+        .collect()
+}
+
+/// The most specific known module a `use` path points into, for paths rooted at `crate`,
+/// `super`, or `self`.
+fn resolve_use_target(
+    module_path: &str,
+    use_path: &str,
+    fragment: &GraphFragment,
+) -> Option<String> {
+    let mut segments = use_path.split("::").map(str::trim).collect::<Vec<_>>();
+    let mut absolute = match segments.first().copied()? {
+        "crate" => vec!["crate"],
+        "self" | "super" => module_path.split("::").collect(),
+        _ => return None,
+    };
+    if segments[0] == "crate" {
+        segments.remove(0);
+    }
+    for segment in segments {
+        match segment {
+            "self" => {}
+            "super" => {
+                absolute.pop()?;
+            }
+            _ => absolute.push(segment),
+        }
+    }
+
+    (1..=absolute.len()).rev().find_map(|len| {
+        let candidate = module_node_id(&absolute[..len].join("::"));
+        fragment.nodes.contains_key(&candidate).then_some(candidate)
+    })
+}
+
+fn is_use_visibility(prefix: &str) -> bool {
+    prefix.is_empty() || prefix == "pub" || (prefix.starts_with("pub(") && prefix.ends_with(')'))
+}
+
 fn is_valid_mod_prefix(prefix: &str) -> bool {
     prefix.is_empty() || prefix.starts_with("pub")
 }
@@ -378,8 +868,10 @@ mod tests {
     use crate::test_support::{remove_dir_if_exists, temp_path};
 
     use super::{
-        ArchitectureEdgeKind, ArchitectureNodeKind, build_rust_workspace_graph_at,
-        parse_module_declarations, resolve_declared_module_file,
+        ArchitectureEdgeKind, ArchitectureNodeKind, ExtraWorkspaceRoot, WorkspaceRoots,
+        build_rust_workspace_graph_at, build_workspace_graph_at, parse_module_declarations,
+        parse_use_paths, resolve_declared_module_file, resolve_workspace_root,
+        split_extra_root_node_id,
     };
 
     #[test]
@@ -405,6 +897,32 @@ mod tests {
         assert!(!declarations[2].inline);
         assert_eq!(declarations[3].name, "inline_mod");
         assert!(declarations[3].inline);
+        assert!(
+            declarations
+                .iter()
+                .all(|declaration| !declaration.top_level)
+        );
+    }
+
+    #[test]
+    fn parse_use_paths_expands_groups_and_skips_nested_uses() {
+        let source = "use crate::graph::{self, watch::{GraphRefreshTrigger as T, spawn}};\n\
+                      pub(crate) use super::editor;\n\
+                      pub fn reuse() {}\n\
+                      use std::{\n    fs,\n    path::Path,\n};\n\
+                      mod tests {\n    use super::*;\n}\n";
+
+        assert_eq!(
+            parse_use_paths(source),
+            [
+                "crate::graph",
+                "crate::graph::watch::GraphRefreshTrigger",
+                "crate::graph::watch::spawn",
+                "super::editor",
+                "std::fs",
+                "std::path::Path",
+            ]
+        );
     }
 
     #[test]
@@ -489,8 +1007,11 @@ mod tests {
             "mod tools;\nmod inline { fn keep() {} }\n",
         )
         .expect("lib should be written");
-        fs::write(root.join("src/tools/mod.rs"), "pub mod parser;\n")
-            .expect("tools/mod.rs should be written");
+        fs::write(
+            root.join("src/tools/mod.rs"),
+            "pub mod parser;\nuse self::parser::parse;\nuse crate::{inline, tools};\n",
+        )
+        .expect("tools/mod.rs should be written");
         fs::write(root.join("src/tools/parser.rs"), "pub fn parse() {}\n")
             .expect("tools/parser.rs should be written");
 
@@ -531,6 +1052,68 @@ mod tests {
                 && edge.to == "module:crate::tools::parser"
                 && edge.relation == ArchitectureEdgeKind::DeclaresModule
         }));
+        let uses = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.relation == ArchitectureEdgeKind::Uses)
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            uses,
+            [
+                ("module:crate::tools", "module:crate::inline"),
+                ("module:crate::tools", "module:crate::tools::parser"),
+            ]
+        );
+
+        remove_dir_if_exists(&root);
+    }
+
+    #[test]
+    fn missing_module_files_become_unresolved_module_nodes() {
+        let root = temp_path("graph-unresolved-mod");
+        fs::create_dir_all(root.join("src")).expect("src directory should be created");
+        fs::write(
+            root.join("src/lib.rs"),
+            "mod present;\nmod typo;\nmod inline {\n    mod nested;\n}\n",
+        )
+        .expect("lib should be written");
+        fs::write(root.join("src/present.rs"), "").expect("present.rs should be written");
+
+        let graph = build_rust_workspace_graph_at(&root, 1, UNIX_EPOCH)
+            .expect("graph build should succeed");
+        let kind_of = |id: &str| {
+            graph
+                .nodes
+                .iter()
+                .find(|node| node.id == id)
+                .map(|node| node.kind)
+        };
+
+        assert_eq!(
+            kind_of("module:crate::present"),
+            Some(ArchitectureNodeKind::Module)
+        );
+        assert_eq!(
+            kind_of("module:crate::typo"),
+            Some(ArchitectureNodeKind::UnresolvedModule)
+        );
+        assert_eq!(
+            kind_of("module:crate::inline"),
+            Some(ArchitectureNodeKind::Module)
+        );
+        assert_eq!(
+            kind_of("module:crate::nested"),
+            Some(ArchitectureNodeKind::Module)
+        );
+        #[cfg(feature = "runtime")]
+        assert_eq!(
+            crate::graph::rules::unresolved_module_declarations(&graph),
+            [crate::graph::rules::UnresolvedModuleDeclaration {
+                module_id: "module:crate::typo".to_owned(),
+                declared_in: Some("src/lib.rs".to_owned()),
+            }]
+        );
 
         remove_dir_if_exists(&root);
     }
@@ -546,4 +1129,95 @@ mod tests {
                 .contains("workspace root must be a directory")
         );
     }
+
+    #[test]
+    fn workspace_override_is_canonicalized_and_validated() {
+        let root = temp_path("graph-workspace-override");
+        fs::create_dir_all(root.join("nested")).expect("workspace should be created");
+        fs::write(root.join("file.rs"), "").expect("file should be written");
+
+        let resolved = resolve_workspace_root(Some(&root.join("nested").join("..")))
+            .expect("existing directory should resolve");
+        assert_eq!(
+            resolved,
+            fs::canonicalize(&root).expect("root should exist")
+        );
+        assert_eq!(
+            resolve_workspace_root(None).expect("launch directory should resolve"),
+            std::env::current_dir().expect("current dir should resolve")
+        );
+
+        let not_dir = resolve_workspace_root(Some(&root.join("file.rs")))
+            .expect_err("file should be rejected");
+        assert!(not_dir.to_string().contains("is not a directory"));
+        let missing = resolve_workspace_root(Some(&root.join("missing")))
+            .expect_err("missing path should be rejected");
+        assert!(missing.to_string().contains("does not exist"));
+        assert!(resolve_workspace_root(Some(Path::new(""))).is_err());
+
+        remove_dir_if_exists(&root);
+    }
+
+    #[test]
+    fn extra_roots_are_namespaced_and_pathed_from_the_primary_root() {
+        let parent = temp_path("graph-multi-root");
+        fs::create_dir_all(&parent).expect("parent should be created");
+        let parent = fs::canonicalize(parent).expect("parent should resolve");
+        let primary = parent.join("app");
+        let shared = parent.join("shared");
+        for root in [&primary, &shared] {
+            fs::create_dir_all(root.join("src")).expect("src should be created");
+            fs::write(root.join("src/lib.rs"), "mod codec;\n").expect("lib should be written");
+            fs::write(root.join("src/codec.rs"), "").expect("codec should be written");
+        }
+        let single = build_rust_workspace_graph_at(&primary, 3, UNIX_EPOCH)
+            .expect("single-root build should succeed");
+        let roots = WorkspaceRoots::from(primary.clone())
+            .with_extra_roots(vec![
+                ExtraWorkspaceRoot::parse(&shared.display().to_string())
+                    .expect("extra root should parse"),
+            ])
+            .expect("extra root should be accepted");
+
+        let graph = build_workspace_graph_at(&roots, 3, UNIX_EPOCH).expect("build should succeed");
+
+        for node in &single.nodes {
+            assert!(graph.nodes.contains(node), "primary node {} kept", node.id);
+        }
+        let shared_file = graph
+            .nodes
+            .iter()
+            .find(|node| node.id == "file:@shared/src/codec.rs")
+            .expect("shared file node should exist");
+        assert_eq!(shared_file.path.as_deref(), Some("../shared/src/codec.rs"));
+        assert!(graph.edges.iter().any(|edge| {
+            edge.from == "module:@shared::crate::codec"
+                && edge.to == "file:@shared/src/codec.rs"
+                && edge.relation == ArchitectureEdgeKind::ResolvesToFile
+        }));
+        assert_eq!(
+            split_extra_root_node_id("module:@shared::crate::codec"),
+            Some(("shared", "module:crate::codec".to_owned()))
+        );
+        assert_eq!(split_extra_root_node_id("module:crate::codec"), None);
+
+        let duplicate = roots
+            .clone()
+            .with_extra_roots(vec![ExtraWorkspaceRoot {
+                name: "shared".to_owned(),
+                path: parent.clone(),
+            }])
+            .expect_err("duplicate names should be rejected");
+        assert!(duplicate.to_string().contains("duplicate"));
+        let nested = WorkspaceRoots::from(primary.clone())
+            .with_extra_roots(vec![ExtraWorkspaceRoot {
+                name: "inner".to_owned(),
+                path: primary.join("src"),
+            }])
+            .expect_err("nested roots should be rejected");
+        assert!(nested.to_string().contains("overlaps"));
+        assert!(ExtraWorkspaceRoot::parse(&format!("bad name={}", shared.display())).is_err());
+
+        remove_dir_if_exists(&parent);
+    }
 }
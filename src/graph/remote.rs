@@ -0,0 +1,295 @@
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+use tokio::time::{Duration, interval};
+use tracing::{debug, warn};
+
+use crate::graph::ArchitectureGraph;
+use crate::graph::updates::{GraphUpdateReceiver, GraphUpdateSender, graph_update_channel};
+use crate::graph::watch::{
+    GraphRefreshTrigger, GraphRefreshUpdate, GraphWatchCommand, GraphWatchHandle, GraphWatchHealth,
+    WatchErrorKind, merge_trigger, record_watch_error, update_health,
+};
+
+const DEFAULT_REMOTE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const REMOTE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct RemoteGraphBody {
+    graph: ArchitectureGraph,
+}
+
+/// Validates a `serve` base URL such as `http://devbox:8080` and gives its path a
+/// trailing `/`, so endpoints joined onto it keep a path prefix like `/mjolne`.
+pub fn parse_remote_graph_base_url(raw: &str) -> Result<Url> {
+    let mut url = Url::parse(raw.trim())
+        .with_context(|| format!("invalid remote graph URL `{}`", raw.trim()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!(
+            "remote graph URL `{url}` must use http or https, got `{}`",
+            url.scheme()
+        );
+    }
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    Ok(url)
+}
+
+/// Polls `GET {base_url}/graph` and emits refreshes through the same handle and
+/// update channel shape as the local graph watch worker.
+pub fn spawn_remote_graph_worker(
+    handle: &Handle,
+    base_url: Url,
+) -> (GraphWatchHandle, GraphUpdateReceiver) {
+    spawn_remote_graph_worker_with_poll_interval(handle, base_url, DEFAULT_REMOTE_POLL_INTERVAL)
+}
+
+fn spawn_remote_graph_worker_with_poll_interval(
+    handle: &Handle,
+    base_url: Url,
+    poll_interval: Duration,
+) -> (GraphWatchHandle, GraphUpdateReceiver) {
+    let (command_tx, command_rx) = unbounded_channel();
+    let (update_tx, update_rx) = graph_update_channel();
+    let health = Arc::new(Mutex::new(GraphWatchHealth::default()));
+    let watch_handle = GraphWatchHandle::new(command_tx, Arc::clone(&health));
+
+    let _task = handle.spawn(run_remote_graph_loop(
+        base_url,
+        poll_interval,
+        command_rx,
+        update_tx,
+        health,
+    ));
+
+    (watch_handle, update_rx)
+}
+
+async fn run_remote_graph_loop(
+    base_url: Url,
+    poll_interval: Duration,
+    mut command_rx: UnboundedReceiver<GraphWatchCommand>,
+    update_tx: GraphUpdateSender,
+    health: Arc<Mutex<GraphWatchHealth>>,
+) {
+    let graph_url = match base_url.join("graph") {
+        Ok(url) => url,
+        Err(error) => {
+            warn!(base_url = %base_url, error = %error, "invalid remote graph endpoint");
+            return;
+        }
+    };
+    let client = match reqwest::Client::builder()
+        .timeout(REMOTE_REQUEST_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(error) => {
+            warn!(error = %error, "failed to build remote graph HTTP client");
+            return;
+        }
+    };
+
+    let mut ticker = interval(poll_interval);
+    let mut paused = false;
+    let mut last_revision: Option<u64> = None;
+    let mut pending_trigger = Some(GraphRefreshTrigger::Startup);
+
+    loop {
+        let forced = tokio::select! {
+            maybe_command = command_rx.recv() => match maybe_command {
+                Some(GraphWatchCommand::TurnCompleted) => {
+                    pending_trigger = Some(merge_trigger(
+                        pending_trigger,
+                        GraphRefreshTrigger::TurnCompleted
+                    ));
+                    false
+                }
+                Some(GraphWatchCommand::RefreshNow) => {
+                    pending_trigger = Some(merge_trigger(
+                        pending_trigger,
+                        GraphRefreshTrigger::Manual
+                    ));
+                    true
+                }
+                Some(GraphWatchCommand::Pause) => {
+                    paused = true;
+                    update_health(&health, |health| health.paused = true);
+                    continue;
+                }
+                Some(GraphWatchCommand::Resume) => {
+                    paused = false;
+                    update_health(&health, |health| health.paused = false);
+                    continue;
+                }
+                Some(GraphWatchCommand::Shutdown) | None => break,
+            },
+            _ = ticker.tick(), if !paused => false,
+        };
+        if paused && !forced {
+            continue;
+        }
+
+        // Pending triggers always fetch the full graph so turn completions and
+        // manual refreshes are reported even when the remote revision is unchanged.
+        let after_revision = if pending_trigger.is_some() {
+            None
+        } else {
+            last_revision
+        };
+        match fetch_remote_graph(&client, &graph_url, after_revision).await {
+            Ok(Some(graph)) => {
+                let trigger = pending_trigger
+                    .take()
+                    .unwrap_or(GraphRefreshTrigger::FilesChanged);
+                last_revision = Some(graph.revision);
+                update_health(&health, |health| {
+                    health.last_refresh_at = Some(SystemTime::now());
+                    health.refresh_count = health.refresh_count.saturating_add(1);
+                });
+                debug!(
+                    url = %graph_url,
+                    trigger = trigger.label(),
+                    revision = graph.revision,
+                    "remote graph refresh completed"
+                );
+                if update_tx
+                    .send(GraphRefreshUpdate { graph, trigger })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Ok(None) => {}
+            Err(error) => {
+                warn!(url = %graph_url, error = %error, "remote graph refresh failed");
+                record_watch_error(&health, WatchErrorKind::Refresh, &error);
+            }
+        }
+    }
+}
+
+async fn fetch_remote_graph(
+    client: &reqwest::Client,
+    graph_url: &Url,
+    after_revision: Option<u64>,
+) -> Result<Option<ArchitectureGraph>> {
+    let mut request = client.get(graph_url.clone());
+    if let Some(revision) = after_revision {
+        request = request.query(&[("after_revision", revision)]);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("failed to reach `{graph_url}`"))?;
+
+    match response.status() {
+        StatusCode::NO_CONTENT => Ok(None),
+        status if status.is_success() => {
+            let body = response
+                .json::<RemoteGraphBody>()
+                .await
+                .with_context(|| format!("invalid graph payload from `{graph_url}`"))?;
+            Ok(Some(body.graph))
+        }
+        status => bail!("`{graph_url}` returned HTTP {status}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::UNIX_EPOCH;
+
+    use axum::extract::Query;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+    use tokio::runtime::Handle;
+    use tokio::time::{Duration, timeout};
+
+    use crate::graph::ArchitectureGraph;
+    use crate::graph::watch::GraphRefreshTrigger;
+
+    use super::{parse_remote_graph_base_url, spawn_remote_graph_worker_with_poll_interval};
+
+    #[test]
+    fn parse_remote_graph_base_url_requires_http_scheme() {
+        assert!(parse_remote_graph_base_url("http://devbox:8080").is_ok());
+        assert!(parse_remote_graph_base_url("ftp://devbox").is_err());
+        assert!(parse_remote_graph_base_url("not a url").is_err());
+
+        let prefixed = parse_remote_graph_base_url("https://devbox/mjolne").expect("valid URL");
+        assert_eq!(
+            prefixed.join("graph").expect("join").as_str(),
+            "https://devbox/mjolne/graph"
+        );
+        let root = parse_remote_graph_base_url("http://devbox:8080").expect("valid URL");
+        assert_eq!(
+            root.join("graph").expect("join").as_str(),
+            "http://devbox:8080/graph"
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_worker_emits_startup_and_turn_completion_updates() {
+        async fn serve_graph(Query(query): Query<HashMap<String, String>>) -> Response {
+            if query.contains_key("after_revision") {
+                return StatusCode::NO_CONTENT.into_response();
+            }
+            let graph = ArchitectureGraph {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                revision: 7,
+                generated_at: UNIX_EPOCH,
+            };
+            Json(json!({ "graph": graph })).into_response()
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("listener addr");
+        let app = Router::new().route("/graph", get(serve_graph));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let base_url =
+            parse_remote_graph_base_url(&format!("http://{addr}")).expect("valid base url");
+        let (watch_handle, mut update_rx) = spawn_remote_graph_worker_with_poll_interval(
+            &Handle::current(),
+            base_url,
+            Duration::from_millis(25),
+        );
+
+        let startup = timeout(Duration::from_secs(2), update_rx.recv())
+            .await
+            .expect("startup update should arrive")
+            .expect("startup update should be present");
+        assert_eq!(startup.trigger, GraphRefreshTrigger::Startup);
+        assert_eq!(startup.graph.revision, 7);
+
+        watch_handle.notify_turn_completed();
+        let turn = timeout(Duration::from_secs(2), update_rx.recv())
+            .await
+            .expect("turn update should arrive")
+            .expect("turn update should be present");
+        assert_eq!(turn.trigger, GraphRefreshTrigger::TurnCompleted);
+
+        let health = watch_handle.health();
+        assert_eq!(health.refresh_count, 2);
+        assert_eq!(health.error_count(), 0);
+
+        watch_handle.shutdown();
+    }
+}
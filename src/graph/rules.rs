@@ -0,0 +1,358 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ArchitectureEdgeKind, ArchitectureGraph, ArchitectureNodeKind, build_workspace_graph,
+    resolve_workspace_roots,
+};
+use crate::config::AgentSettings;
+use crate::tr;
+
+const RULE_SEPARATOR: &str = " must not depend on ";
+
+/// Forbidden dependencies between graph nodes, loaded from
+/// `GRAPH_DEPENDENCY_RULES_FILE` or `graph check --rules`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DependencyRules {
+    rules: Vec<DependencyRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DependencyRule {
+    from: String,
+    to: String,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DependencyRulesFile {
+    rules: Vec<DependencyRulesFileEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum DependencyRulesFileEntry {
+    Sentence(String),
+    Fields(DependencyRuleFields),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DependencyRuleFields {
+    from: String,
+    to: String,
+    reason: Option<String>,
+}
+
+/// A `Uses` or `Imports` edge that a dependency rule forbids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyViolation {
+    pub from: String,
+    pub to: String,
+    pub relation: ArchitectureEdgeKind,
+    pub rule: String,
+    pub reason: Option<String>,
+}
+
+/// A `mod name;` declaration whose file is missing, typically a typo or an uncommitted file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnresolvedModuleDeclaration {
+    pub module_id: String,
+    /// Source file of the declaring module, when it has one.
+    pub declared_in: Option<String>,
+}
+
+/// Every `UnresolvedModule` node of `graph`, in node order.
+pub fn unresolved_module_declarations(
+    graph: &ArchitectureGraph,
+) -> Vec<UnresolvedModuleDeclaration> {
+    graph
+        .nodes
+        .iter()
+        .filter(|node| node.kind == ArchitectureNodeKind::UnresolvedModule)
+        .map(|node| {
+            let declared_in = graph
+                .edges
+                .iter()
+                .find(|edge| {
+                    edge.to == node.id && edge.relation == ArchitectureEdgeKind::DeclaresModule
+                })
+                .and_then(|edge| graph.nodes.iter().find(|parent| parent.id == edge.from))
+                .and_then(|parent| parent.path.clone());
+            UnresolvedModuleDeclaration {
+                module_id: node.id.clone(),
+                declared_in,
+            }
+        })
+        .collect()
+}
+
+impl DependencyRules {
+    /// Parses `{"rules": [...]}` where each rule is either the sentence
+    /// `"<from> must not depend on <to>"` or `{"from", "to", "reason"?}`. `from` and `to`
+    /// are node ids that also cover their children (`module:crate::tools` matches
+    /// `module:crate::tools::parser`, `file:web/` matches every file under `web/`).
+    pub fn from_rules_json(raw: &str) -> Result<Self> {
+        let file = serde_json::from_str::<DependencyRulesFile>(raw)
+            .context("failed to parse dependency rules as JSON")?;
+        ensure!(
+            !file.rules.is_empty(),
+            "dependency rules file must contain at least one rule"
+        );
+
+        let mut rules = Vec::with_capacity(file.rules.len());
+        for (index, entry) in file.rules.into_iter().enumerate() {
+            let (from, to, reason) = match entry {
+                DependencyRulesFileEntry::Sentence(sentence) => {
+                    let Some((from, to)) = sentence.split_once(RULE_SEPARATOR) else {
+                        bail!("rules[{index}] must read `<from> must not depend on <to>`");
+                    };
+                    (from.to_owned(), to.to_owned(), None)
+                }
+                DependencyRulesFileEntry::Fields(fields) => (fields.from, fields.to, fields.reason),
+            };
+            let (from, to) = (from.trim(), to.trim());
+            ensure!(
+                !from.is_empty() && !to.is_empty(),
+                "rules[{index}] needs non-empty `from` and `to` node ids"
+            );
+            rules.push(DependencyRule {
+                from: from.to_owned(),
+                to: to.to_owned(),
+                reason: reason
+                    .map(|reason| reason.trim().to_owned())
+                    .filter(|reason| !reason.is_empty()),
+            });
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn from_rules_file(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read dependency rules from {}", path.display()))?;
+        Self::from_rules_json(&raw)
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Every `Uses`/`Imports` edge of `graph` matched by a rule, in edge order.
+    pub fn check(&self, graph: &ArchitectureGraph) -> Vec<DependencyViolation> {
+        graph
+            .edges
+            .iter()
+            .filter(|edge| {
+                matches!(
+                    edge.relation,
+                    ArchitectureEdgeKind::Uses | ArchitectureEdgeKind::Imports
+                )
+            })
+            .filter_map(|edge| {
+                let rule = self.rules.iter().find(|rule| {
+                    node_id_matches(&rule.from, &edge.from) && node_id_matches(&rule.to, &edge.to)
+                })?;
+                Some(DependencyViolation {
+                    from: edge.from.clone(),
+                    to: edge.to.clone(),
+                    relation: edge.relation,
+                    rule: format!("{}{RULE_SEPARATOR}{}", rule.from, rule.to),
+                    reason: rule.reason.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Whether `node_id` is `pattern` or nested below it (after `::` or `/`).
+fn node_id_matches(pattern: &str, node_id: &str) -> bool {
+    let Some(rest) = node_id.strip_prefix(pattern) else {
+        return false;
+    };
+    rest.is_empty()
+        || pattern.ends_with("::")
+        || pattern.ends_with('/')
+        || rest.starts_with("::")
+        || rest.starts_with('/')
+}
+
+/// Loads `GRAPH_DEPENDENCY_RULES_FILE`, resolving relative paths against `workspace_root`.
+/// Returns `None` when the variable is unset.
+pub fn load_dependency_rules(
+    settings: &AgentSettings,
+    workspace_root: &Path,
+) -> Result<Option<DependencyRules>> {
+    let Some(path) = settings.graph_dependency_rules_file.as_deref() else {
+        return Ok(None);
+    };
+    let path = workspace_root.join(path);
+    DependencyRules::from_rules_file(&path)
+        .with_context(|| {
+            format!(
+                "failed to load GRAPH_DEPENDENCY_RULES_FILE from {}",
+                path.display()
+            )
+        })
+        .map(Some)
+}
+
+/// `graph check`: reports `mod` declarations without a file and, when rules are configured,
+/// dependency rule violations; fails when it finds either.
+pub fn run_graph_check_command(
+    settings: &AgentSettings,
+    rules_path: Option<&Path>,
+    workspace: Option<&Path>,
+    extra_roots: &[String],
+) -> Result<()> {
+    let workspace_roots = resolve_workspace_roots(workspace, extra_roots)
+        .context("failed to resolve workspace root for graph check")?;
+    let rules = match rules_path {
+        Some(path) => DependencyRules::from_rules_file(path)?,
+        None => match load_dependency_rules(settings, &workspace_roots.primary)? {
+            Some(rules) => rules,
+            None => {
+                println!("{}", tr!("graph.check.no_rules"));
+                DependencyRules::default()
+            }
+        },
+    };
+    let graph = build_workspace_graph(&workspace_roots, 0)?;
+    let unresolved_modules = unresolved_module_declarations(&graph);
+    let violations = rules.check(&graph);
+
+    for unresolved in &unresolved_modules {
+        let name = unresolved
+            .module_id
+            .rsplit("::")
+            .next()
+            .unwrap_or(&unresolved.module_id);
+        println!(
+            "{}",
+            tr!(
+                "graph.check.unresolved_mod",
+                module = unresolved.module_id,
+                name = name,
+                declared_in = unresolved
+                    .declared_in
+                    .as_deref()
+                    .unwrap_or(tr!("graph.check.inline_module"))
+            )
+        );
+    }
+    for violation in &violations {
+        println!(
+            "{} -> {} ({:?}) violates `{}`{}",
+            violation.from,
+            violation.to,
+            violation.relation,
+            violation.rule,
+            violation
+                .reason
+                .as_deref()
+                .map(|reason| format!(": {reason}"))
+                .unwrap_or_default()
+        );
+    }
+    println!(
+        "{}",
+        tr!(
+            "graph.check.summary",
+            rules = rules.rule_count(),
+            edges = graph.edges.len(),
+            violations = violations.len(),
+            unresolved = unresolved_modules.len()
+        )
+    );
+    ensure!(
+        violations.is_empty() && unresolved_modules.is_empty(),
+        "{} dependency rule violation(s), {} unresolved mod declaration(s)",
+        violations.len(),
+        unresolved_modules.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use crate::graph::{ArchitectureEdge, ArchitectureEdgeKind, ArchitectureGraph};
+
+    use super::DependencyRules;
+
+    fn edge(from: &str, to: &str, relation: ArchitectureEdgeKind) -> ArchitectureEdge {
+        ArchitectureEdge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            relation,
+        }
+    }
+
+    #[test]
+    fn rules_flag_dependency_edges_below_matching_nodes() {
+        let rules = DependencyRules::from_rules_json(
+            r#"{
+                "rules": [
+                    "module:crate::tools must not depend on module:crate::studio",
+                    { "from": "file:web/", "to": "file:server/", "reason": "web talks HTTP only" }
+                ]
+            }"#,
+        )
+        .expect("rules should parse");
+        let graph = ArchitectureGraph {
+            nodes: Vec::new(),
+            edges: vec![
+                edge(
+                    "module:crate::tools::parser",
+                    "module:crate::studio::renderer",
+                    ArchitectureEdgeKind::Uses,
+                ),
+                edge(
+                    "module:crate::tools",
+                    "module:crate::studio_ext",
+                    ArchitectureEdgeKind::Uses,
+                ),
+                edge(
+                    "module:crate::tools",
+                    "module:crate::studio",
+                    ArchitectureEdgeKind::DeclaresModule,
+                ),
+                edge(
+                    "file:web/app.ts",
+                    "file:server/api.ts",
+                    ArchitectureEdgeKind::Imports,
+                ),
+            ],
+            revision: 1,
+            generated_at: UNIX_EPOCH,
+        };
+
+        let violations = rules.check(&graph);
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].from, "module:crate::tools::parser");
+        assert_eq!(
+            violations[0].rule,
+            "module:crate::tools must not depend on module:crate::studio"
+        );
+        assert_eq!(violations[1].to, "file:server/api.ts");
+        assert_eq!(violations[1].reason.as_deref(), Some("web talks HTTP only"));
+    }
+
+    #[test]
+    fn rules_reject_malformed_entries() {
+        for raw in [
+            r#"{"rules": []}"#,
+            r#"{"rules": ["module:crate::tools depends on module:crate::studio"]}"#,
+            r#"{"rules": [{"from": "module:crate", "to": " "}]}"#,
+            r#"{"rules": [{"from": "a", "to": "b", "severity": "high"}]}"#,
+        ] {
+            assert!(DependencyRules::from_rules_json(raw).is_err(), "{raw}");
+        }
+    }
+}
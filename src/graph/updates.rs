@@ -0,0 +1,234 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+use tokio::sync::mpsc::error::{SendError, TryRecvError};
+
+use crate::graph::watch::GraphRefreshUpdate;
+
+/// A bounded mailbox from a graph watch worker to the studio. At most one update per
+/// `GraphRefreshTrigger` is pending: a newer update replaces the waiting one with the
+/// same trigger, so a burst of file changes (a large rebase, a branch switch) costs one
+/// rebuilt graph in memory and one canvas refresh instead of a backlog. Keeping one per
+/// trigger, rather than one overall, preserves turn-completion refreshes that finalize
+/// turn snapshots.
+pub fn graph_update_channel() -> (GraphUpdateSender, GraphUpdateReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(MailboxState {
+            pending: Vec::new(),
+            coalesced: 0,
+            senders: 1,
+            receiver_open: true,
+        }),
+        notify: Notify::new(),
+    });
+    (
+        GraphUpdateSender {
+            shared: Arc::clone(&shared),
+        },
+        GraphUpdateReceiver { shared },
+    )
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<MailboxState>,
+    notify: Notify,
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, MailboxState> {
+        self.state
+            .lock()
+            .expect("graph update mailbox lock poisoned")
+    }
+}
+
+#[derive(Debug)]
+struct MailboxState {
+    /// Oldest first; holds at most one update per trigger.
+    pending: Vec<GraphRefreshUpdate>,
+    coalesced: u64,
+    senders: usize,
+    receiver_open: bool,
+}
+
+#[derive(Debug)]
+pub struct GraphUpdateSender {
+    shared: Arc<Shared>,
+}
+
+impl GraphUpdateSender {
+    /// Queues `update`, replacing a pending update with the same trigger. Fails only when
+    /// the receiver is gone.
+    pub fn send(&self, update: GraphRefreshUpdate) -> Result<(), SendError<GraphRefreshUpdate>> {
+        let mut state = self.shared.lock();
+        if !state.receiver_open {
+            return Err(SendError(update));
+        }
+        if let Some(index) = state
+            .pending
+            .iter()
+            .position(|pending| pending.trigger == update.trigger)
+        {
+            state.pending.remove(index);
+            state.coalesced = state.coalesced.saturating_add(1);
+        }
+        state.pending.push(update);
+        drop(state);
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+}
+
+impl Clone for GraphUpdateSender {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Drop for GraphUpdateSender {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        let last = state.senders == 0;
+        drop(state);
+        if last {
+            self.shared.notify.notify_one();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GraphUpdateReceiver {
+    shared: Arc<Shared>,
+}
+
+impl GraphUpdateReceiver {
+    /// The oldest pending update; `Disconnected` once every sender is gone and nothing
+    /// is left.
+    pub fn try_recv(&mut self) -> Result<GraphRefreshUpdate, TryRecvError> {
+        let mut state = self.shared.lock();
+        if !state.pending.is_empty() {
+            return Ok(state.pending.remove(0));
+        }
+        if state.senders == 0 {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Waits for the next update; `None` once every sender is gone.
+    pub async fn recv(&mut self) -> Option<GraphRefreshUpdate> {
+        loop {
+            match self.try_recv() {
+                Ok(update) => return Some(update),
+                Err(TryRecvError::Disconnected) => return None,
+                Err(TryRecvError::Empty) => self.shared.notify.notified().await,
+            }
+        }
+    }
+
+    /// Updates waiting to be received; at most one per trigger.
+    pub fn pending_len(&self) -> usize {
+        self.shared.lock().pending.len()
+    }
+
+    /// Updates replaced by a newer one with the same trigger before being received.
+    pub fn coalesced_count(&self) -> u64 {
+        self.shared.lock().coalesced
+    }
+}
+
+impl Drop for GraphUpdateReceiver {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receiver_open = false;
+        state.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use tokio::sync::mpsc::error::TryRecvError;
+    use tokio::time::{Duration, timeout};
+
+    use super::graph_update_channel;
+    use crate::graph::ArchitectureGraph;
+    use crate::graph::watch::{GraphRefreshTrigger, GraphRefreshUpdate};
+
+    fn update(revision: u64, trigger: GraphRefreshTrigger) -> GraphRefreshUpdate {
+        GraphRefreshUpdate {
+            graph: ArchitectureGraph {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                revision,
+                generated_at: UNIX_EPOCH,
+            },
+            trigger,
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_updates_keep_only_the_latest_per_trigger_in_arrival_order() {
+        let (sender, mut receiver) = graph_update_channel();
+        for revision in 1..=50 {
+            sender
+                .send(update(revision, GraphRefreshTrigger::FilesChanged))
+                .expect("receiver is open");
+        }
+        sender
+            .send(update(51, GraphRefreshTrigger::TurnCompleted))
+            .expect("receiver is open");
+        sender
+            .send(update(52, GraphRefreshTrigger::FilesChanged))
+            .expect("receiver is open");
+
+        let received = std::iter::from_fn(|| receiver.try_recv().ok())
+            .map(|update| (update.graph.revision, update.trigger))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            received,
+            vec![
+                (51, GraphRefreshTrigger::TurnCompleted),
+                (52, GraphRefreshTrigger::FilesChanged),
+            ]
+        );
+        assert_eq!(receiver.coalesced_count(), 50);
+        assert_eq!(receiver.try_recv().err(), Some(TryRecvError::Empty));
+
+        let waiting = tokio::spawn(async move {
+            let next = receiver.recv().await;
+            (
+                next.map(|update| update.graph.revision),
+                receiver.recv().await,
+            )
+        });
+        sender
+            .send(update(53, GraphRefreshTrigger::Manual))
+            .expect("receiver is open");
+        drop(sender);
+        let (next, after_close) = timeout(Duration::from_secs(2), waiting)
+            .await
+            .expect("receiver should wake")
+            .expect("receiver task should not panic");
+        assert_eq!(next, Some(53));
+        assert!(after_close.is_none());
+    }
+
+    #[test]
+    fn send_fails_once_the_receiver_is_dropped() {
+        let (sender, receiver) = graph_update_channel();
+        drop(receiver);
+        assert!(
+            sender
+                .send(update(1, GraphRefreshTrigger::Startup))
+                .is_err()
+        );
+    }
+}
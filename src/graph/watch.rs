@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::UNIX_EPOCH;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use tokio::runtime::Handle;
@@ -8,7 +9,11 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use tokio::time::{Duration, Instant, interval};
 use tracing::{debug, warn};
 
-use crate::graph::{ArchitectureGraph, build_rust_workspace_graph};
+use crate::graph::updates::{GraphUpdateReceiver, GraphUpdateSender, graph_update_channel};
+use crate::graph::{
+    ArchitectureGraph, WorkspaceRoots, build_workspace_graph, collect_source_files,
+    default_graph_sources,
+};
 
 const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(400);
 const DEFAULT_DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
@@ -19,6 +24,7 @@ pub enum GraphRefreshTrigger {
     FilesChanged,
     TurnCompleted,
     TurnCompletedAndFilesChanged,
+    Manual,
 }
 
 impl GraphRefreshTrigger {
@@ -28,6 +34,7 @@ impl GraphRefreshTrigger {
             Self::FilesChanged => "files_changed",
             Self::TurnCompleted => "turn_completed",
             Self::TurnCompletedAndFilesChanged => "turn_completed+files_changed",
+            Self::Manual => "manual",
         }
     }
 }
@@ -53,78 +60,149 @@ impl Default for GraphWatchConfig {
     }
 }
 
+/// Point-in-time watcher status published by the graph watch worker.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphWatchHealth {
+    pub paused: bool,
+    pub last_refresh_at: Option<SystemTime>,
+    pub refresh_count: u64,
+    pub fingerprint_error_count: u64,
+    pub refresh_error_count: u64,
+    pub last_error: Option<String>,
+}
+
+impl GraphWatchHealth {
+    pub fn error_count(&self) -> u64 {
+        self.fingerprint_error_count
+            .saturating_add(self.refresh_error_count)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GraphWatchHandle {
     command_tx: UnboundedSender<GraphWatchCommand>,
+    health: Arc<Mutex<GraphWatchHealth>>,
 }
 
 impl GraphWatchHandle {
+    pub(super) fn new(
+        command_tx: UnboundedSender<GraphWatchCommand>,
+        health: Arc<Mutex<GraphWatchHealth>>,
+    ) -> Self {
+        Self { command_tx, health }
+    }
+
     pub fn notify_turn_completed(&self) {
         let _ = self.command_tx.send(GraphWatchCommand::TurnCompleted);
     }
 
+    /// Refreshes immediately, even while file watching is paused.
+    pub fn request_refresh(&self) {
+        let _ = self.command_tx.send(GraphWatchCommand::RefreshNow);
+    }
+
+    /// Stops file polling and automatic refreshes until `resume` is called.
+    /// Turn completions received while paused are applied on resume.
+    pub fn pause(&self) {
+        let _ = self.command_tx.send(GraphWatchCommand::Pause);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.command_tx.send(GraphWatchCommand::Resume);
+    }
+
+    pub fn health(&self) -> GraphWatchHealth {
+        self.health
+            .lock()
+            .expect("graph watch health lock poisoned")
+            .clone()
+    }
+
     pub fn shutdown(&self) {
         let _ = self.command_tx.send(GraphWatchCommand::Shutdown);
     }
 }
 
+impl PartialEq for GraphWatchHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.command_tx.same_channel(&other.command_tx)
+    }
+}
+
+impl Eq for GraphWatchHandle {}
+
 #[derive(Debug)]
-enum GraphWatchCommand {
+pub(super) enum GraphWatchCommand {
     TurnCompleted,
+    RefreshNow,
+    Pause,
+    Resume,
     Shutdown,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct RustFileFingerprint {
-    relative_path: String,
+pub(crate) struct FileFingerprint {
+    path: String,
     modified_ms: u128,
     byte_len: u64,
 }
 
+/// Watches every root in `workspace_roots` (a single `PathBuf` converts into a one-root set)
+/// and emits rebuilt graphs.
 pub fn spawn_graph_watch_worker(
     handle: &Handle,
-    workspace_root: PathBuf,
-) -> (GraphWatchHandle, UnboundedReceiver<GraphRefreshUpdate>) {
-    spawn_graph_watch_worker_with_config(handle, workspace_root, GraphWatchConfig::default())
+    workspace_roots: impl Into<WorkspaceRoots>,
+) -> (GraphWatchHandle, GraphUpdateReceiver) {
+    spawn_graph_watch_worker_with_config(
+        handle,
+        workspace_roots.into(),
+        GraphWatchConfig::default(),
+    )
 }
 
 fn spawn_graph_watch_worker_with_config(
     handle: &Handle,
-    workspace_root: PathBuf,
+    workspace_roots: WorkspaceRoots,
     config: GraphWatchConfig,
-) -> (GraphWatchHandle, UnboundedReceiver<GraphRefreshUpdate>) {
+) -> (GraphWatchHandle, GraphUpdateReceiver) {
     let (command_tx, command_rx) = unbounded_channel();
-    let (update_tx, update_rx) = unbounded_channel();
-    let watch_handle = GraphWatchHandle { command_tx };
+    let (update_tx, update_rx) = graph_update_channel();
+    let health = Arc::new(Mutex::new(GraphWatchHealth::default()));
+    let watch_handle = GraphWatchHandle::new(command_tx, Arc::clone(&health));
 
     let _task = handle.spawn(run_graph_watch_loop(
-        workspace_root,
+        workspace_roots,
         config,
         command_rx,
         update_tx,
+        health,
     ));
 
     (watch_handle, update_rx)
 }
 
 async fn run_graph_watch_loop(
-    workspace_root: PathBuf,
+    workspace_roots: WorkspaceRoots,
     config: GraphWatchConfig,
     mut command_rx: UnboundedReceiver<GraphWatchCommand>,
-    update_tx: UnboundedSender<GraphRefreshUpdate>,
+    update_tx: GraphUpdateSender,
+    health: Arc<Mutex<GraphWatchHealth>>,
 ) {
     let mut revision: u64 = 0;
+    let mut paused = false;
+    let mut forced_refresh = false;
     let mut ticker = interval(config.poll_interval);
     let mut pending_trigger = Some(GraphRefreshTrigger::Startup);
     let mut refresh_deadline = Some(Instant::now() + config.debounce_interval);
-    let mut last_fingerprint = match collect_workspace_fingerprint(&workspace_root) {
+    let mut last_fingerprint = match collect_workspace_fingerprint(&workspace_roots) {
         Ok(fingerprint) => fingerprint,
         Err(error) => {
             warn!(
-                root = %workspace_root.display(),
+                root = %workspace_roots.primary.display(),
                 error = %error,
                 "failed to compute initial graph watch fingerprint"
             );
+            record_watch_error(&health, WatchErrorKind::Fingerprint, &error);
             Vec::new()
         }
     };
@@ -140,11 +218,27 @@ async fn run_graph_watch_loop(
                         ));
                         refresh_deadline = Some(Instant::now() + config.debounce_interval);
                     }
+                    Some(GraphWatchCommand::RefreshNow) => {
+                        pending_trigger = Some(merge_trigger(
+                            pending_trigger,
+                            GraphRefreshTrigger::Manual
+                        ));
+                        refresh_deadline = Some(Instant::now());
+                        forced_refresh = true;
+                    }
+                    Some(GraphWatchCommand::Pause) => {
+                        paused = true;
+                        update_health(&health, |health| health.paused = true);
+                    }
+                    Some(GraphWatchCommand::Resume) => {
+                        paused = false;
+                        update_health(&health, |health| health.paused = false);
+                    }
                     Some(GraphWatchCommand::Shutdown) | None => break,
                 }
             }
-            _ = ticker.tick() => {
-                match collect_workspace_fingerprint(&workspace_root) {
+            _ = ticker.tick(), if !paused => {
+                match collect_workspace_fingerprint(&workspace_roots) {
                     Ok(fingerprint) => {
                         if fingerprint != last_fingerprint {
                             last_fingerprint = fingerprint;
@@ -157,22 +251,28 @@ async fn run_graph_watch_loop(
                     }
                     Err(error) => {
                         warn!(
-                            root = %workspace_root.display(),
+                            root = %workspace_roots.primary.display(),
                             error = %error,
                             "failed to collect graph watch fingerprint"
                         );
+                        record_watch_error(&health, WatchErrorKind::Fingerprint, &error);
                     }
                 }
             }
         }
 
         if let (Some(deadline), Some(trigger)) = (refresh_deadline, pending_trigger)
+            && (!paused || forced_refresh)
             && Instant::now() >= deadline
         {
             let refresh_succeeded =
-                match build_rust_workspace_graph(&workspace_root, revision.saturating_add(1)) {
+                match build_workspace_graph(&workspace_roots, revision.saturating_add(1)) {
                     Ok(graph) => {
                         revision = graph.revision;
+                        update_health(&health, |health| {
+                            health.last_refresh_at = Some(SystemTime::now());
+                            health.refresh_count = health.refresh_count.saturating_add(1);
+                        });
                         if update_tx
                             .send(GraphRefreshUpdate { graph, trigger })
                             .is_err()
@@ -183,21 +283,23 @@ async fn run_graph_watch_loop(
                     }
                     Err(error) => {
                         warn!(
-                            root = %workspace_root.display(),
+                            root = %workspace_roots.primary.display(),
                             trigger = trigger.label(),
                             error = %error,
                             "graph refresh failed"
                         );
+                        record_watch_error(&health, WatchErrorKind::Refresh, &error);
                         false
                     }
                 };
 
             debug!(
-                root = %workspace_root.display(),
+                root = %workspace_roots.primary.display(),
                 trigger = trigger.label(),
                 revision,
                 "graph refresh completed"
             );
+            forced_refresh = false;
             if refresh_succeeded {
                 pending_trigger = None;
                 refresh_deadline = None;
@@ -208,13 +310,46 @@ async fn run_graph_watch_loop(
     }
 }
 
-fn merge_trigger(
+#[derive(Debug, Clone, Copy)]
+pub(super) enum WatchErrorKind {
+    Fingerprint,
+    Refresh,
+}
+
+pub(super) fn record_watch_error(
+    health: &Mutex<GraphWatchHealth>,
+    kind: WatchErrorKind,
+    error: &anyhow::Error,
+) {
+    update_health(health, |health| {
+        match kind {
+            WatchErrorKind::Fingerprint => {
+                health.fingerprint_error_count = health.fingerprint_error_count.saturating_add(1);
+            }
+            WatchErrorKind::Refresh => {
+                health.refresh_error_count = health.refresh_error_count.saturating_add(1);
+            }
+        }
+        health.last_error = Some(format!("{error:#}"));
+    });
+}
+
+pub(super) fn update_health(
+    health: &Mutex<GraphWatchHealth>,
+    apply: impl FnOnce(&mut GraphWatchHealth),
+) {
+    apply(&mut health.lock().expect("graph watch health lock poisoned"));
+}
+
+pub(super) fn merge_trigger(
     existing: Option<GraphRefreshTrigger>,
     incoming: GraphRefreshTrigger,
 ) -> GraphRefreshTrigger {
     match (existing, incoming) {
         (None, next) => next,
         (Some(GraphRefreshTrigger::Startup), next) => next,
+        (Some(GraphRefreshTrigger::Manual), next) => next,
+        (Some(current), GraphRefreshTrigger::Manual) => current,
         (Some(GraphRefreshTrigger::FilesChanged), GraphRefreshTrigger::TurnCompleted)
         | (Some(GraphRefreshTrigger::TurnCompleted), GraphRefreshTrigger::FilesChanged)
         | (Some(GraphRefreshTrigger::TurnCompletedAndFilesChanged), _)
@@ -227,82 +362,70 @@ fn merge_trigger(
     }
 }
 
-fn collect_workspace_fingerprint(workspace_root: &Path) -> Result<Vec<RustFileFingerprint>> {
-    let mut files = Vec::new();
-    collect_rust_files_recursive(workspace_root, workspace_root, &mut files)?;
-    files.sort_by_key(|path| path_to_slash_string(path.as_path()));
-
-    let mut fingerprint = Vec::with_capacity(files.len());
-    for relative_path in files {
-        let absolute_path = workspace_root.join(&relative_path);
-        let metadata = fs::metadata(&absolute_path).with_context(|| {
-            format!(
-                "failed to read metadata for `{}`",
-                absolute_path.as_path().display()
-            )
-        })?;
-        let modified_ms = metadata
-            .modified()
-            .ok()
-            .and_then(|value| value.duration_since(UNIX_EPOCH).ok())
-            .map(|value| value.as_millis())
-            .unwrap_or(0);
-        fingerprint.push(RustFileFingerprint {
-            relative_path: path_to_slash_string(&relative_path),
-            modified_ms,
-            byte_len: metadata.len(),
-        });
+fn collect_workspace_fingerprint(workspace_roots: &WorkspaceRoots) -> Result<Vec<FileFingerprint>> {
+    let mut fingerprint = Vec::new();
+    for workspace_root in workspace_roots.paths() {
+        for relative_path in collect_source_files(workspace_root, &default_graph_sources())? {
+            let absolute_path = workspace_root.join(&relative_path);
+            let metadata = fs::metadata(&absolute_path).with_context(|| {
+                format!(
+                    "failed to read metadata for `{}`",
+                    absolute_path.as_path().display()
+                )
+            })?;
+            fingerprint.push(file_fingerprint(
+                path_to_slash_string(&absolute_path),
+                &metadata,
+            ));
+        }
     }
 
     Ok(fingerprint)
 }
 
-fn collect_rust_files_recursive(
-    workspace_root: &Path,
-    current_dir: &Path,
-    files: &mut Vec<PathBuf>,
-) -> Result<()> {
-    let mut entries = fs::read_dir(current_dir)
-        .with_context(|| format!("failed to list directory `{}`", current_dir.display()))?
-        .collect::<std::io::Result<Vec<_>>>()
-        .with_context(|| format!("failed to read entries in `{}`", current_dir.display()))?;
-    entries.sort_by_key(|entry| entry.file_name());
-
-    for entry in entries {
-        let path = entry.path();
-        let file_type = entry
-            .file_type()
-            .with_context(|| format!("failed to inspect `{}`", path.display()))?;
-        if file_type.is_dir() {
-            let name = entry.file_name().to_string_lossy().to_string();
-            if should_skip_dir(&name) {
-                continue;
-            }
-            collect_rust_files_recursive(workspace_root, &path, files)?;
+/// Fingerprints explicit files plus the direct file children of explicit directories.
+/// Unreadable or missing paths are skipped, so deleting a watched file still changes the
+/// result.
+pub(crate) fn collect_paths_fingerprint(paths: &[PathBuf]) -> Vec<FileFingerprint> {
+    let mut fingerprint = Vec::new();
+    for path in paths {
+        let Ok(metadata) = fs::metadata(path) else {
             continue;
-        }
-
-        if !file_type.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("rs") {
+        };
+        if metadata.is_file() {
+            fingerprint.push(file_fingerprint(path_to_slash_string(path), &metadata));
             continue;
         }
-        let relative_path = path.strip_prefix(workspace_root).with_context(|| {
-            format!(
-                "failed to strip workspace root `{}` from `{}`",
-                workspace_root.display(),
-                path.display()
-            )
-        })?;
-        files.push(relative_path.to_path_buf());
+        let Ok(entries) = fs::read_dir(path) else {
+            continue;
+        };
+        let mut children = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                metadata
+                    .is_file()
+                    .then(|| file_fingerprint(path_to_slash_string(&entry.path()), &metadata))
+            })
+            .collect::<Vec<_>>();
+        children.sort_by(|left, right| left.path.cmp(&right.path));
+        fingerprint.extend(children);
     }
-
-    Ok(())
+    fingerprint
 }
 
-fn should_skip_dir(name: &str) -> bool {
-    matches!(
-        name,
-        "target" | ".git" | ".idea" | ".vscode" | "node_modules"
-    )
+fn file_fingerprint(path: String, metadata: &fs::Metadata) -> FileFingerprint {
+    let modified_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|value| value.duration_since(UNIX_EPOCH).ok())
+        .map(|value| value.as_millis())
+        .unwrap_or(0);
+    FileFingerprint {
+        path,
+        modified_ms,
+        byte_len: metadata.len(),
+    }
 }
 
 fn path_to_slash_string(path: &Path) -> String {
@@ -320,6 +443,7 @@ mod tests {
     use tokio::runtime::Handle;
     use tokio::time::timeout;
 
+    use crate::graph::{ExtraWorkspaceRoot, WorkspaceRoots};
     use crate::test_support::{remove_dir_if_exists, temp_path};
 
     use super::{
@@ -350,6 +474,17 @@ mod tests {
             ),
             GraphRefreshTrigger::FilesChanged
         );
+        assert_eq!(
+            merge_trigger(
+                Some(GraphRefreshTrigger::TurnCompleted),
+                GraphRefreshTrigger::Manual
+            ),
+            GraphRefreshTrigger::TurnCompleted
+        );
+        assert_eq!(
+            merge_trigger(None, GraphRefreshTrigger::Manual),
+            GraphRefreshTrigger::Manual
+        );
     }
 
     #[test]
@@ -360,16 +495,44 @@ mod tests {
         fs::write(root.join("src/alpha.rs"), "pub fn value() -> u8 { 1 }\n")
             .expect("alpha should be written");
 
-        let before = collect_workspace_fingerprint(&root).expect("fingerprint should work");
+        let roots = WorkspaceRoots::from(root.clone());
+
+        let before = collect_workspace_fingerprint(&roots).expect("fingerprint should work");
         std::thread::sleep(Duration::from_millis(2));
         fs::write(root.join("src/alpha.rs"), "pub fn value() -> u8 { 2 }\n")
             .expect("alpha should be updated");
-        let after = collect_workspace_fingerprint(&root).expect("fingerprint should work");
+        let after = collect_workspace_fingerprint(&roots).expect("fingerprint should work");
         assert_ne!(before, after);
 
         remove_dir_if_exists(&root);
     }
 
+    #[test]
+    fn workspace_fingerprint_covers_extra_roots() {
+        let root = temp_path("graph-watch-primary");
+        let shared = temp_path("graph-watch-shared");
+        for dir in [&root, &shared] {
+            fs::create_dir_all(dir.join("src")).expect("src should be created");
+            fs::write(dir.join("src/lib.rs"), "pub fn value() {}\n")
+                .expect("lib should be written");
+        }
+        let roots = WorkspaceRoots::from(root.clone())
+            .with_extra_roots(vec![ExtraWorkspaceRoot {
+                name: "shared".to_owned(),
+                path: shared.clone(),
+            }])
+            .expect("extra root should be accepted");
+
+        let before = collect_workspace_fingerprint(&roots).expect("fingerprint should work");
+        fs::write(shared.join("src/extra.rs"), "pub fn extra() {}\n")
+            .expect("extra should be written");
+        let after = collect_workspace_fingerprint(&roots).expect("fingerprint should work");
+        assert_eq!(after.len(), before.len() + 1);
+
+        remove_dir_if_exists(&root);
+        remove_dir_if_exists(&shared);
+    }
+
     #[tokio::test]
     async fn watch_worker_emits_startup_and_turn_completion_updates() {
         let root = temp_path("graph-watch-worker");
@@ -380,7 +543,7 @@ mod tests {
 
         let (watch_handle, mut update_rx) = spawn_graph_watch_worker_with_config(
             &Handle::current(),
-            root.clone(),
+            root.clone().into(),
             GraphWatchConfig {
                 poll_interval: Duration::from_millis(25),
                 debounce_interval: Duration::from_millis(40),
@@ -403,4 +566,67 @@ mod tests {
         watch_handle.shutdown();
         remove_dir_if_exists(&root);
     }
+
+    #[tokio::test]
+    async fn watch_worker_pauses_polling_but_honors_manual_refresh() {
+        let root = temp_path("graph-watch-pause");
+        fs::create_dir_all(root.join("src")).expect("src should be created");
+        fs::write(root.join("src/lib.rs"), "mod alpha;\n").expect("lib should be written");
+        fs::write(root.join("src/alpha.rs"), "pub fn value() -> u8 { 1 }\n")
+            .expect("alpha should be written");
+
+        let (watch_handle, mut update_rx) = spawn_graph_watch_worker_with_config(
+            &Handle::current(),
+            root.clone().into(),
+            GraphWatchConfig {
+                poll_interval: Duration::from_millis(25),
+                debounce_interval: Duration::from_millis(40),
+            },
+        );
+        let startup = timeout(Duration::from_secs(2), update_rx.recv())
+            .await
+            .expect("startup update should arrive")
+            .expect("startup update should be present");
+        assert_eq!(startup.trigger, GraphRefreshTrigger::Startup);
+
+        watch_handle.pause();
+        timeout(Duration::from_secs(2), async {
+            while !watch_handle.health().paused {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("watcher should report paused");
+
+        fs::write(root.join("src/beta.rs"), "pub fn beta() {}\n").expect("beta should be written");
+        assert!(
+            timeout(Duration::from_millis(200), update_rx.recv())
+                .await
+                .is_err(),
+            "paused watcher should not refresh on file changes"
+        );
+
+        watch_handle.request_refresh();
+        let manual = timeout(Duration::from_secs(2), update_rx.recv())
+            .await
+            .expect("manual update should arrive")
+            .expect("manual update should be present");
+        assert_eq!(manual.trigger, GraphRefreshTrigger::Manual);
+
+        watch_handle.resume();
+        let resumed = timeout(Duration::from_secs(2), update_rx.recv())
+            .await
+            .expect("resumed update should arrive")
+            .expect("resumed update should be present");
+        assert_eq!(resumed.trigger, GraphRefreshTrigger::FilesChanged);
+
+        let health = watch_handle.health();
+        assert!(!health.paused);
+        assert_eq!(health.refresh_count, 3);
+        assert_eq!(health.error_count(), 0);
+        assert!(health.last_refresh_at.is_some());
+
+        watch_handle.shutdown();
+        remove_dir_if_exists(&root);
+    }
 }
@@ -0,0 +1,116 @@
+use std::net::SocketAddr;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::AgentSettings;
+use crate::tools::{DELETE_NOTE_TOOL_NAME, RUN_COMMAND_TOOL_NAME, SAVE_NOTE_TOOL_NAME};
+
+/// Side-effect policy of a deployment, next to the caps in `CapabilityLimits`: what the
+/// offered tools may write, run, and reach.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Guardrails {
+    /// Hosts `fetch_url` may reach, subdomains included.
+    pub fetch_url_allowed_domains: Vec<String>,
+    /// Programs `run_command` may start; empty when the tool is not offered.
+    pub allowed_commands: Vec<String>,
+    /// `save_note` may replace an existing note with different content.
+    pub save_note_allow_overwrite: bool,
+    /// `delete_note` may remove notes.
+    pub save_note_allow_delete: bool,
+    /// `TOOL_WORKING_DIR` is set; relative tool paths and `run_command` use the launch
+    /// directory while it is not. The path itself is left out of `/capabilities`.
+    pub tool_working_dir_set: bool,
+    /// Note writes stay under a configured root: `TOOL_WORKING_DIR` is set or `NOTES_DIR`
+    /// is absolute.
+    #[serde(skip)]
+    notes_dir_pinned: bool,
+}
+
+impl Guardrails {
+    pub fn from_settings(settings: &AgentSettings) -> Self {
+        let offered = settings.offered_tools();
+        let offers = |name: &str| offered.iter().any(|tool| tool == name);
+        Self {
+            fetch_url_allowed_domains: settings.fetch_url_allowed_domains.clone(),
+            allowed_commands: if offers(RUN_COMMAND_TOOL_NAME) {
+                settings.tool_allowed_commands.clone()
+            } else {
+                Vec::new()
+            },
+            save_note_allow_overwrite: settings.save_note_allow_overwrite
+                && offers(SAVE_NOTE_TOOL_NAME),
+            save_note_allow_delete: settings.save_note_allow_delete
+                && offers(DELETE_NOTE_TOOL_NAME),
+            tool_working_dir_set: settings.tool_working_dir.is_some(),
+            notes_dir_pinned: settings.tool_working_dir.is_some()
+                || Path::new(&settings.notes_dir).is_absolute(),
+        }
+    }
+
+    /// Combinations `serve` refuses to start with unless `--allow-unsafe` is passed, one
+    /// line each. `POST /chat` has no auth, so a non-loopback `bind` hands the tools to
+    /// anyone who can reach the port.
+    pub fn unsafe_combinations(&self, bind: SocketAddr) -> Vec<String> {
+        let mut problems = Vec::new();
+        let destructive_notes = self.save_note_allow_overwrite || self.save_note_allow_delete;
+        let runs_commands = !self.allowed_commands.is_empty();
+        if destructive_notes && !self.notes_dir_pinned {
+            problems.push(
+                "note overwrites or deletes are allowed but TOOL_WORKING_DIR is unset, so a relative NOTES_DIR resolves against whatever directory `serve` was started from".to_owned(),
+            );
+        }
+        if runs_commands && !self.tool_working_dir_set {
+            problems.push(
+                "run_command is offered but TOOL_WORKING_DIR is unset, so commands run in whatever directory `serve` was started from".to_owned(),
+            );
+        }
+        if !bind.ip().is_loopback() && (destructive_notes || runs_commands) {
+            problems.push(format!(
+                "{bind} is not a loopback address and `POST /chat` has no auth, yet note overwrites or deletes or run_command are enabled"
+            ));
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Guardrails;
+    use crate::agent::tests::test_settings;
+
+    #[test]
+    fn destructive_tools_need_a_working_dir_and_a_loopback_bind() {
+        let loopback = "127.0.0.1:8080".parse().expect("address");
+        let public = "0.0.0.0:8080".parse().expect("address");
+        let mut settings = test_settings();
+        settings.tool_working_dir = None;
+        settings.notes_dir = "notes".to_owned();
+        let guardrails = Guardrails::from_settings(&settings);
+        assert!(guardrails.unsafe_combinations(loopback).is_empty());
+        assert!(guardrails.unsafe_combinations(public).is_empty());
+
+        settings.save_note_allow_delete = true;
+        let problems = Guardrails::from_settings(&settings).unsafe_combinations(loopback);
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].contains("TOOL_WORKING_DIR is unset"));
+        settings.notes_dir = "/srv/notes".to_owned();
+        assert!(
+            Guardrails::from_settings(&settings)
+                .unsafe_combinations(loopback)
+                .is_empty()
+        );
+
+        settings.enabled_tools.retain(|tool| tool != "delete_note");
+        assert!(!Guardrails::from_settings(&settings).save_note_allow_delete);
+
+        settings.tool_allowed_commands = vec!["cargo".to_owned()];
+        settings.tool_working_dir = Some("/srv/mjolne".to_owned());
+        let guardrails = Guardrails::from_settings(&settings);
+        assert_eq!(guardrails.allowed_commands, ["cargo"]);
+        assert!(guardrails.unsafe_combinations(loopback).is_empty());
+        let problems = guardrails.unsafe_combinations(public);
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].starts_with("0.0.0.0:8080 is not a loopback address"));
+    }
+}
@@ -0,0 +1,766 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::error::Error as StdError;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use reqwest::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::redirect::Policy;
+use reqwest::{Method, StatusCode, Version};
+use serde::Serialize;
+use tower::{Layer, Service};
+
+use crate::config::AgentSettings;
+
+/// Sent with every request; some APIs, GitHub's among them, reject requests without one.
+const USER_AGENT: &str = concat!("mjolne_vibes/", env!("CARGO_PKG_VERSION"));
+
+pub type HttpFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, HttpError>> + Send + 'a>>;
+
+/// Transport shared by the model client and `fetch_url`. Implementations return
+/// redirects to the caller instead of following them, so `fetch_url` can check its
+/// allowlist on every hop.
+pub trait HttpClient: Send + Sync {
+    fn send(&self, request: HttpRequest) -> HttpFuture<'_>;
+
+    /// Counters since the client was built; all zero for clients that keep none.
+    fn metrics(&self) -> HttpClientMetrics {
+        HttpClientMetrics::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+    /// Deadline for the whole exchange, body included; `None` leaves it to the caller.
+    pub timeout: Option<Duration>,
+}
+
+impl HttpRequest {
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: Method::GET,
+            url: url.into(),
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: None,
+        }
+    }
+
+    pub fn post_json<T: Serialize>(url: impl Into<String>, body: &T) -> Result<Self, HttpError> {
+        let body = serde_json::to_vec(body).map_err(|error| {
+            HttpError::new(
+                HttpErrorKind::Builder,
+                format!("failed to encode request body: {error}"),
+            )
+        })?;
+        let mut request = Self {
+            method: Method::POST,
+            body: Some(body),
+            ..Self::get(url)
+        };
+        request
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(request)
+    }
+
+    pub fn bearer_auth(mut self, token: &str) -> Result<Self, HttpError> {
+        let mut value = HeaderValue::from_str(&format!("Bearer {token}")).map_err(|_| {
+            HttpError::new(
+                HttpErrorKind::Builder,
+                "bearer token contains characters not allowed in a header",
+            )
+        })?;
+        value.set_sensitive(true);
+        self.headers.insert(AUTHORIZATION, value);
+        Ok(self)
+    }
+
+    /// Sends `key` in the `name` header, for APIs that take a key instead of a bearer token.
+    pub fn api_key_header(mut self, name: &'static str, key: &str) -> Result<Self, HttpError> {
+        let mut value = HeaderValue::from_str(key).map_err(|_| {
+            HttpError::new(
+                HttpErrorKind::Builder,
+                "API key contains characters not allowed in a header",
+            )
+        })?;
+        value.set_sensitive(true);
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+pub struct HttpResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: ResponseBody,
+}
+
+enum ResponseBody {
+    Streaming(reqwest::Response),
+    Buffered(VecDeque<Vec<u8>>),
+}
+
+impl HttpResponse {
+    /// A response whose body is already in memory, delivered as `chunks`.
+    pub fn buffered(status: StatusCode, headers: HeaderMap, chunks: Vec<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers,
+            body: ResponseBody::Buffered(chunks.into()),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The announced body size, when the server sent `Content-Length`.
+    pub fn content_length(&self) -> Option<u64> {
+        self.headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+    }
+
+    /// The next piece of the body, or `None` once it has been read completely.
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>, HttpError> {
+        match &mut self.body {
+            ResponseBody::Streaming(response) => Ok(response.chunk().await?.map(Vec::from)),
+            ResponseBody::Buffered(chunks) => Ok(chunks.pop_front()),
+        }
+    }
+
+    pub async fn text(mut self) -> Result<String, HttpError> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            body.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+impl Debug for HttpResponse {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("HttpResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpErrorKind {
+    /// The request could not be built, including a client that failed to start.
+    Builder,
+    Connect,
+    Timeout,
+    /// The request failed after a connection was made.
+    Request,
+    /// Reading or decoding the response body failed.
+    Body,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct HttpError {
+    kind: HttpErrorKind,
+    message: String,
+}
+
+impl HttpError {
+    pub fn new(kind: HttpErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn kind(&self) -> HttpErrorKind {
+        self.kind
+    }
+
+    /// Whether sending the same request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind,
+            HttpErrorKind::Connect | HttpErrorKind::Timeout | HttpErrorKind::Request
+        )
+    }
+}
+
+impl From<reqwest::Error> for HttpError {
+    fn from(error: reqwest::Error) -> Self {
+        let kind = if error.is_timeout() {
+            HttpErrorKind::Timeout
+        } else if error.is_connect() {
+            HttpErrorKind::Connect
+        } else if error.is_builder() {
+            HttpErrorKind::Builder
+        } else if error.is_body() || error.is_decode() {
+            HttpErrorKind::Body
+        } else {
+            HttpErrorKind::Request
+        };
+        Self::new(kind, describe_reqwest_error(&error))
+    }
+}
+
+fn describe_reqwest_error(error: &reqwest::Error) -> String {
+    let mut details = vec![format_error_chain(error)];
+    let mut classes = Vec::new();
+
+    if error.is_builder() {
+        classes.push("builder");
+    }
+    if error.is_connect() {
+        classes.push("connect");
+    }
+    if error.is_timeout() {
+        classes.push("timeout");
+    }
+    if error.is_request() {
+        classes.push("request");
+    }
+    if error.is_body() {
+        classes.push("body");
+    }
+    if error.is_decode() {
+        classes.push("decode");
+    }
+    if error.is_redirect() {
+        classes.push("redirect");
+    }
+    if error.is_status() {
+        classes.push("status");
+    }
+
+    if !classes.is_empty() {
+        details.push(format!("class={}", classes.join(",")));
+    }
+    if let Some(url) = error.url() {
+        details.push(format!("url={url}"));
+    }
+
+    details.join(" | ")
+}
+
+fn format_error_chain(error: &(dyn StdError + 'static)) -> String {
+    let mut chain = error.to_string();
+    let mut source = error.source();
+    while let Some(next) = source {
+        chain.push_str(": ");
+        chain.push_str(&next.to_string());
+        source = next.source();
+    }
+    chain
+}
+
+/// Connection pool, keep-alive, protocol, and proxy options for `ReqwestHttpClient`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HttpClientOptions {
+    /// Idle connections kept per host; `None` keeps reqwest's default.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle connection stays pooled; `None` keeps reqwest's default.
+    pub pool_idle_timeout: Option<Duration>,
+    /// TCP keep-alive probe interval for open connections; `None` sends no probes.
+    pub tcp_keepalive: Option<Duration>,
+    /// Speak HTTP/1.1 only instead of offering HTTP/2 to `https` servers.
+    pub http1_only: bool,
+    /// Proxy for every request; without one, reqwest honors `HTTPS_PROXY` and friends.
+    pub proxy_url: Option<String>,
+}
+
+impl HttpClientOptions {
+    pub fn from_settings(settings: &AgentSettings) -> Self {
+        Self {
+            pool_max_idle_per_host: Some(settings.http_pool_max_idle_per_host as usize),
+            pool_idle_timeout: Some(Duration::from_millis(settings.http_pool_idle_timeout_ms)),
+            tcp_keepalive: (settings.http_tcp_keepalive_ms > 0)
+                .then(|| Duration::from_millis(settings.http_tcp_keepalive_ms)),
+            http1_only: !settings.http2_enabled,
+            proxy_url: settings.http_proxy_url.clone(),
+        }
+    }
+}
+
+/// Traffic counters of one `HttpClient` since it was built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct HttpClientMetrics {
+    pub requests_total: u64,
+    /// Requests still waiting for response headers.
+    pub requests_in_flight: u64,
+    /// Requests that failed before a response arrived (connect, timeout, TLS, ...).
+    pub failures_total: u64,
+    /// New connections dialed; `requests_total` minus this is roughly how many requests
+    /// reused a pooled connection.
+    pub connections_opened_total: u64,
+    pub http2_responses_total: u64,
+}
+
+#[derive(Debug, Default)]
+struct HttpClientCounters {
+    requests: AtomicU64,
+    in_flight: AtomicU64,
+    failures: AtomicU64,
+    connections_opened: AtomicU64,
+    http2_responses: AtomicU64,
+}
+
+/// Decrements the in-flight count when a send finishes or is dropped.
+struct InFlightGuard<'a>(&'a AtomicU64);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// `HttpClient` over one pooled `reqwest::Client`.
+#[derive(Debug, Clone)]
+pub struct ReqwestHttpClient {
+    /// The build error is kept and reported by every request, so a bad proxy fails
+    /// loudly instead of being bypassed.
+    client: Result<reqwest::Client, String>,
+    counters: Arc<HttpClientCounters>,
+}
+
+impl ReqwestHttpClient {
+    pub fn new(options: &HttpClientOptions) -> Self {
+        let counters = Arc::new(HttpClientCounters::default());
+        Self {
+            client: build_reqwest_client(options, &counters)
+                .map_err(|error| describe_reqwest_error(&error)),
+            counters,
+        }
+    }
+}
+
+fn build_reqwest_client(
+    options: &HttpClientOptions,
+    counters: &Arc<HttpClientCounters>,
+) -> Result<reqwest::Client, reqwest::Error> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .redirect(Policy::none())
+        .tcp_keepalive(options.tcp_keepalive)
+        .connector_layer(CountConnectionsLayer(Arc::clone(counters)));
+    if let Some(max_idle) = options.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(idle_timeout) = options.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(idle_timeout);
+    }
+    if options.http1_only {
+        builder = builder.http1_only();
+    }
+    if let Some(proxy_url) = &options.proxy_url {
+        builder = builder.proxy(reqwest::Proxy::all(proxy_url)?);
+    }
+    builder.build()
+}
+
+/// Counts every connection reqwest dials, which only happens when the pool has no idle
+/// connection to reuse.
+#[derive(Clone)]
+struct CountConnectionsLayer(Arc<HttpClientCounters>);
+
+impl<S> Layer<S> for CountConnectionsLayer {
+    type Service = CountConnections<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CountConnections {
+            inner,
+            counters: Arc::clone(&self.0),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct CountConnections<S> {
+    inner: S,
+    counters: Arc<HttpClientCounters>,
+}
+
+impl<S: Service<R>, R> Service<R> for CountConnections<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, context: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(context)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.counters
+            .connections_opened
+            .fetch_add(1, Ordering::Relaxed);
+        self.inner.call(request)
+    }
+}
+
+impl HttpClient for ReqwestHttpClient {
+    fn send(&self, request: HttpRequest) -> HttpFuture<'_> {
+        Box::pin(async move {
+            let client = self.client.as_ref().map_err(|error| {
+                HttpError::new(
+                    HttpErrorKind::Builder,
+                    format!("failed to build HTTP client: {error}"),
+                )
+            })?;
+            let mut builder = client
+                .request(request.method, &request.url)
+                .headers(request.headers);
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let counters = &self.counters;
+            counters.requests.fetch_add(1, Ordering::Relaxed);
+            counters.in_flight.fetch_add(1, Ordering::Relaxed);
+            let _in_flight = InFlightGuard(&counters.in_flight);
+            let response = builder.send().await.inspect_err(|_| {
+                counters.failures.fetch_add(1, Ordering::Relaxed);
+            })?;
+            if response.version() == Version::HTTP_2 {
+                counters.http2_responses.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(HttpResponse {
+                status: response.status(),
+                headers: response.headers().clone(),
+                body: ResponseBody::Streaming(response),
+            })
+        })
+    }
+
+    fn metrics(&self) -> HttpClientMetrics {
+        let counters = &self.counters;
+        HttpClientMetrics {
+            requests_total: counters.requests.load(Ordering::Relaxed),
+            requests_in_flight: counters.in_flight.load(Ordering::Relaxed),
+            failures_total: counters.failures.load(Ordering::Relaxed),
+            connections_opened_total: counters.connections_opened.load(Ordering::Relaxed),
+            http2_responses_total: counters.http2_responses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Cheap-to-clone handle to a `HttpClient`; clones share one connection pool. Two
+/// handles are equal when they share the client.
+#[derive(Clone)]
+pub struct SharedHttpClient(Arc<dyn HttpClient>);
+
+impl SharedHttpClient {
+    pub fn new(client: impl HttpClient + 'static) -> Self {
+        Self(Arc::new(client))
+    }
+
+    /// A reqwest client with the pool, keep-alive, HTTP/2, and proxy settings from the
+    /// environment. Long-lived owners (a REPL session, `serve`, the studio worker, an
+    /// eval run) build one and hand it to every turn so connections are reused.
+    pub fn from_settings(settings: &AgentSettings) -> Self {
+        Self::new(ReqwestHttpClient::new(&HttpClientOptions::from_settings(
+            settings,
+        )))
+    }
+
+    pub async fn send(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        self.0.send(request).await
+    }
+
+    pub fn metrics(&self) -> HttpClientMetrics {
+        self.0.metrics()
+    }
+}
+
+impl Default for SharedHttpClient {
+    fn default() -> Self {
+        Self::new(ReqwestHttpClient::new(&HttpClientOptions::default()))
+    }
+}
+
+impl Debug for SharedHttpClient {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("SharedHttpClient")
+    }
+}
+
+impl PartialEq for SharedHttpClient {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedHttpClient {}
+
+/// Canned reply of a `StubHttpClient` route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StubResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Body chunks, delivered one per `HttpResponse::chunk` call.
+    pub chunks: Vec<Vec<u8>>,
+}
+
+impl StubResponse {
+    pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            chunks: vec![body.into()],
+        }
+    }
+
+    pub fn json(body: &serde_json::Value) -> Self {
+        Self::new(StatusCode::OK, body.to_string()).with_header("content-type", "application/json")
+    }
+
+    /// A redirect to `location`.
+    pub fn redirect(location: &str) -> Self {
+        Self::new(StatusCode::FOUND, Vec::new()).with_header("location", location)
+    }
+
+    /// Panics on a header name or value that HTTP does not allow, which is a bug in the
+    /// test that set it up.
+    pub fn with_header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.insert(
+            name,
+            HeaderValue::from_str(value).expect("stub header value should be valid"),
+        );
+        self
+    }
+}
+
+/// `HttpClient` test double answering from canned responses and recording every request,
+/// so model calls and `fetch_url` can be tested without a network.
+#[derive(Debug, Clone, Default)]
+pub struct StubHttpClient {
+    state: Arc<Mutex<StubState>>,
+}
+
+#[derive(Debug, Default)]
+struct StubState {
+    /// Replies per `METHOD url`, used in order; the last one repeats.
+    routes: BTreeMap<String, VecDeque<Result<StubResponse, HttpErrorKind>>>,
+    requests: Vec<HttpRequest>,
+}
+
+impl StubHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `response` for `method url`. Several queued replies are returned in order,
+    /// and the last one keeps answering after the others are used up.
+    pub fn respond(&self, method: Method, url: &str, response: StubResponse) -> &Self {
+        self.push_route(method, url, Ok(response))
+    }
+
+    /// Queues a transport failure of `kind` for `method url`.
+    pub fn fail(&self, method: Method, url: &str, kind: HttpErrorKind) -> &Self {
+        self.push_route(method, url, Err(kind))
+    }
+
+    fn push_route(
+        &self,
+        method: Method,
+        url: &str,
+        reply: Result<StubResponse, HttpErrorKind>,
+    ) -> &Self {
+        self.lock()
+            .routes
+            .entry(route_key(&method, url))
+            .or_default()
+            .push_back(reply);
+        self
+    }
+
+    /// Every request sent so far, oldest first.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StubState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn reply(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        let mut state = self.lock();
+        let key = route_key(&request.method, &request.url);
+        state.requests.push(request);
+        let replies = state.routes.get_mut(&key).ok_or_else(|| {
+            HttpError::new(
+                HttpErrorKind::Connect,
+                format!("no stub response for `{key}`"),
+            )
+        })?;
+        let reply = if replies.len() > 1 {
+            replies.pop_front()
+        } else {
+            replies.front().cloned()
+        };
+        match reply {
+            Some(Ok(response)) => Ok(HttpResponse::buffered(
+                response.status,
+                response.headers,
+                response.chunks,
+            )),
+            Some(Err(kind)) => Err(HttpError::new(kind, format!("stubbed failure for `{key}`"))),
+            None => Err(HttpError::new(
+                HttpErrorKind::Connect,
+                format!("no stub response for `{key}`"),
+            )),
+        }
+    }
+}
+
+fn route_key(method: &Method, url: &str) -> String {
+    format!("{method} {url}")
+}
+
+impl HttpClient for StubHttpClient {
+    fn send(&self, request: HttpRequest) -> HttpFuture<'_> {
+        Box::pin(std::future::ready(self.reply(request)))
+    }
+
+    fn metrics(&self) -> HttpClientMetrics {
+        HttpClientMetrics {
+            requests_total: self.lock().requests.len() as u64,
+            ..HttpClientMetrics::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Method, StatusCode};
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::{
+        HttpClientOptions, HttpErrorKind, HttpRequest, ReqwestHttpClient, SharedHttpClient,
+        StubHttpClient, StubResponse,
+    };
+
+    #[tokio::test]
+    async fn stub_replies_in_order_repeats_the_last_and_records_requests() {
+        let stub = StubHttpClient::new();
+        stub.fail(Method::GET, "http://stub/a", HttpErrorKind::Timeout)
+            .respond(
+                Method::GET,
+                "http://stub/a",
+                StubResponse::json(&json!({ "ok": true })),
+            );
+        let client = SharedHttpClient::new(stub.clone());
+
+        let error = client
+            .send(HttpRequest::get("http://stub/a"))
+            .await
+            .expect_err("first reply is a failure");
+        assert!(error.is_retryable());
+        for _ in 0..2 {
+            let response = client
+                .send(HttpRequest::get("http://stub/a"))
+                .await
+                .expect("later replies succeed");
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.text().await.expect("body"), r#"{"ok":true}"#);
+        }
+
+        let error = client
+            .send(
+                HttpRequest::post_json("http://stub/b", &json!({}))
+                    .expect("request")
+                    .bearer_auth("secret")
+                    .expect("token"),
+            )
+            .await
+            .expect_err("unrouted requests fail");
+        assert_eq!(error.kind(), HttpErrorKind::Connect);
+
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[3].method, Method::POST);
+        assert_eq!(requests[3].body.as_deref(), Some(&b"{}"[..]));
+        assert_eq!(requests[3].headers["authorization"], "Bearer secret");
+    }
+
+    #[tokio::test]
+    async fn reqwest_client_reports_an_invalid_proxy_on_every_request() {
+        let client = ReqwestHttpClient::new(&HttpClientOptions {
+            proxy_url: Some("not a proxy url".to_owned()),
+            ..HttpClientOptions::default()
+        });
+        let error = SharedHttpClient::new(client)
+            .send(HttpRequest::get("http://example.com/"))
+            .await
+            .expect_err("the client failed to build");
+
+        assert_eq!(error.kind(), HttpErrorKind::Builder);
+        assert!(
+            error.to_string().starts_with("failed to build HTTP client"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn reqwest_client_reuses_one_keep_alive_connection_across_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let url = format!("http://{}/", listener.local_addr().expect("addr"));
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = [0_u8; 4096];
+                    while let Ok(read) = stream.read(&mut buffer).await
+                        && read > 0
+                    {
+                        let reply = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                        if stream.write_all(reply.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        let client = SharedHttpClient::new(ReqwestHttpClient::new(&HttpClientOptions::default()));
+
+        for _ in 0..3 {
+            let response = client
+                .send(HttpRequest::get(&url))
+                .await
+                .expect("request succeeds");
+            assert_eq!(response.text().await.expect("body"), "ok");
+        }
+
+        let metrics = client.metrics();
+        assert_eq!(metrics.requests_total, 3);
+        assert_eq!(metrics.requests_in_flight, 0);
+        assert_eq!(metrics.failures_total, 0);
+        assert_eq!(metrics.connections_opened_total, 1);
+    }
+}
@@ -0,0 +1,739 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use anyhow::{Error, anyhow};
+
+/// Language of user-facing CLI, REPL, and studio strings, selected with `MJOLNE_LANG`.
+/// Machine-read output (JSON, eval and bench reports, logs) stays English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    NorwegianBokmal,
+}
+
+impl Locale {
+    pub const ALL: [Self; 2] = [Self::English, Self::NorwegianBokmal];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::NorwegianBokmal => "nb",
+        }
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::English => EN,
+            Self::NorwegianBokmal => NB,
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = Error;
+
+    /// Accepts codes (`en`, `nb`, `no`) and POSIX-style values such as `nb_NO.UTF-8`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let language = value
+            .trim()
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" | "english" => Ok(Self::English),
+            "nb" | "no" | "norsk" | "norwegian" => Ok(Self::NorwegianBokmal),
+            _ => Err(anyhow!(
+                "invalid MJOLNE_LANG `{value}`; expected one of en, nb"
+            )),
+        }
+    }
+}
+
+static ACTIVE_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// Makes `locale` the language of every later `tr!` lookup in this process.
+pub fn set_locale(locale: Locale) {
+    ACTIVE_LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    match ACTIVE_LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::NorwegianBokmal,
+        _ => Locale::English,
+    }
+}
+
+/// The active locale's text for `key`, falling back to English and then to the key
+/// itself so a missing translation is visible rather than fatal.
+pub fn message(key: &'static str) -> &'static str {
+    message_in(locale(), key)
+}
+
+pub fn message_in(locale: Locale, key: &'static str) -> &'static str {
+    lookup(locale.catalog(), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key)
+}
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog
+        .iter()
+        .find(|(candidate, _)| *candidate == key)
+        .map(|(_, text)| *text)
+}
+
+/// `template` with each `{name}` replaced by the matching argument.
+pub fn format_message(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut formatted = template.to_owned();
+    for (name, value) in args {
+        formatted = formatted.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    formatted
+}
+
+/// Looks up a catalog message in the active locale: `tr!("repl.started")` returns a
+/// `&'static str`; `tr!("repl.session_title", title = summary.title)` fills `{title}`
+/// and returns a `String`.
+#[macro_export]
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::message($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::format_message(
+            $crate::i18n::message($key),
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+
+const EN: &[(&str, &str)] = &[
+    ("cli.error", "error: {error}"),
+    ("cli.opening_path", "Opening {path} with {program}"),
+    (
+        "chat.history_recorded",
+        "Recorded turn {id}; continue with --resume-from {id}",
+    ),
+    (
+        "repl.started",
+        "Interactive mode started. Type /help for commands.",
+    ),
+    ("repl.help.help", "/help   Show commands"),
+    ("repl.help.tools", "/tools  Show available tools"),
+    ("repl.help.reset", "/reset  Reset session history"),
+    (
+        "repl.help.compact",
+        "/compact  Replace earlier turns with a model-written summary",
+    ),
+    (
+        "repl.help.tool",
+        "/tool   Run a tool directly: /tool <name> <json-args>",
+    ),
+    (
+        "repl.help.save_transcript",
+        "/save-transcript <path>  Export the conversation (.md, .html, or .json)",
+    ),
+    (
+        "repl.help.save",
+        "/save [path]  Save the conversation to resume later (default: --session-file)",
+    ),
+    (
+        "repl.help.load",
+        "/load [path]  Replace the conversation with a saved one (default: --session-file)",
+    ),
+    ("repl.help.exit", "/exit   Exit interactive mode"),
+    (
+        "repl.help.multiline",
+        "```     Start or end a multi-line message (also: end a line with \\)",
+    ),
+    (
+        "repl.help.cancel",
+        "/cancel Discard a pending multi-line message",
+    ),
+    (
+        "repl.help.pager",
+        "/pager on|off  Page answers taller than the terminal ($PAGER or built-in)",
+    ),
+    (
+        "repl.help.model",
+        "/model [provider] <name>  Switch model for later turns (/model reset: configured one)",
+    ),
+    ("repl.model.current", "Model: {provider}/{model}"),
+    (
+        "repl.model.reset",
+        "Back to the configured model: {provider}/{model}",
+    ),
+    (
+        "repl.model.usage",
+        "usage: {command} [provider] <name> | {command} reset",
+    ),
+    (
+        "repl.context_trimmed",
+        "Dropped {count} older messages to stay under AGENT_MAX_CONTEXT_TOKENS.",
+    ),
+    (
+        "repl.compact.done",
+        "Compacted {count} messages into a summary.",
+    ),
+    ("repl.compact.nothing", "Nothing to compact yet."),
+    (
+        "repl.context_compacted",
+        "Compacted {count} earlier messages into a summary (AGENT_AUTO_COMPACT).",
+    ),
+    ("repl.pager.enabled", "Pager on."),
+    ("repl.pager.disabled", "Pager off."),
+    (
+        "repl.pager.usage",
+        "usage: {command} on|off (currently {state})",
+    ),
+    (
+        "repl.pager.prompt",
+        "-- lines {first}-{last} of {total}: Enter next, b back, /text search, n next match, q quit -- ",
+    ),
+    ("repl.pager.not_found", "Pattern not found: {pattern}"),
+    ("repl.history_cleared", "Session history cleared."),
+    (
+        "repl.transcript_saved",
+        "Saved {format} transcript to {path}.",
+    ),
+    ("repl.session_title", "Session: {title}"),
+    ("repl.session_saved", "Saved {count} messages to {path}."),
+    (
+        "repl.session_loaded",
+        "Loaded {count} messages from {path}.",
+    ),
+    (
+        "repl.session_usage",
+        "usage: {command} <path> (or start repl with --session-file)",
+    ),
+    ("repl.multiline_discarded", "Multi-line input discarded."),
+    (
+        "repl.pasted_lines",
+        "Pasted {count} lines; press Enter on an empty line to send or type {cancel}.",
+    ),
+    (
+        "repl.save_usage",
+        "usage: {command} <path.md|path.html|path.json>",
+    ),
+    (
+        "repl.save_unknown_format",
+        "cannot infer export format from `{path}`; use a .md, .html, or .json path",
+    ),
+    (
+        "preflight.reachable",
+        "Preflight: {provider} reachable ({count} model(s))",
+    ),
+    (
+        "preflight.model_available",
+        "Preflight: model `{model}` available",
+    ),
+    (
+        "preflight.warmup_completed",
+        "Preflight: warm-up turn completed in {millis}ms",
+    ),
+    (
+        "capabilities.provider",
+        "provider: {provider} (model {model})",
+    ),
+    ("capabilities.tools", "tools: {tools}"),
+    ("capabilities.answer_formats", "answer formats: {formats}"),
+    (
+        "capabilities.guardrails",
+        "guardrails: fetch domains [{domains}], commands [{commands}], note overwrite {overwrite}, note delete {delete}, TOOL_WORKING_DIR set {working_dir}",
+    ),
+    (
+        "capabilities.limits",
+        "limits: {steps} steps, {tool_calls} tool calls ({per_step} per step), {input} input / {output} output chars, tool timeout {tool_timeout}ms, model timeout {model_timeout}ms",
+    ),
+    (
+        "graph.check.no_rules",
+        "No dependency rules configured (pass --rules <PATH> or set GRAPH_DEPENDENCY_RULES_FILE)",
+    ),
+    (
+        "graph.check.unresolved_mod",
+        "{module}: `mod {name};` in {declared_in} has no matching file",
+    ),
+    ("graph.check.inline_module", "an inline module"),
+    (
+        "graph.check.summary",
+        "Checked {rules} rules against {edges} edges: {violations} violations, {unresolved} unresolved mod declarations",
+    ),
+    (
+        "studio.ready",
+        "Studio ready. Send a prompt to run a chat turn.",
+    ),
+    ("studio.history_cleared", "History cleared."),
+    ("studio.title", "Studio"),
+    ("studio.hide_chat", "Hide chat"),
+    ("studio.hide_chat_short", "Hide"),
+    ("studio.show_chat", "Show chat"),
+    ("studio.show_chat_short", "Chat"),
+    ("studio.status.running", "Running"),
+    ("studio.status.reconnecting", "Reconnecting"),
+    ("studio.status.disconnected", "Disconnected"),
+    ("studio.status.ready", "Ready"),
+    ("studio.graph.refresh", "Refresh graph"),
+    (
+        "studio.graph.refresh_hint",
+        "Rebuild the architecture graph now",
+    ),
+    ("studio.graph.refresh_requested", "Graph refresh requested"),
+    ("studio.graph.pause", "Pause watch"),
+    ("studio.graph.pause_hint", "Stop automatic graph refreshes"),
+    ("studio.graph.resume", "Resume watch"),
+    (
+        "studio.graph.resume_hint",
+        "Resume automatic graph refreshes",
+    ),
+    ("studio.chat.heading", "Chat"),
+    ("studio.chat.message_count", "{count} messages"),
+    ("studio.chat.clear_history", "Clear history"),
+    (
+        "studio.chat.clear_history_hint",
+        "Drop chat messages, turn summaries, tool cards, and snapshots",
+    ),
+    ("studio.chat.export", "Export"),
+    ("studio.chat.export_hint", "Export conversation to {dir}/"),
+    ("studio.session_notes.heading", "Session notes ({count})"),
+    (
+        "studio.session_notes.empty",
+        "No notes saved in this session yet.",
+    ),
+    ("studio.session_notes.promote", "Promote selected"),
+    (
+        "studio.session_notes.promote_hint",
+        "Move the selected notes into {dir}/",
+    ),
+    ("studio.history.heading", "Search history"),
+    ("studio.history.hint", "Text in past turns..."),
+    ("studio.history.search", "Search"),
+    ("studio.history.empty", "No recorded turns match."),
+    ("studio.history.copy_id", "Copy id"),
+    (
+        "studio.history.copy_id_hint",
+        "Copy the turn id for chat --resume-from",
+    ),
+    ("studio.prompt.heading", "Prompt"),
+    ("studio.prompt.hint", "Ask the agent..."),
+    ("studio.prompt.send", "Send"),
+    ("studio.prompt.queue", "Queue"),
+    ("studio.prompt.running", "Running..."),
+    ("studio.prompt.runtime_disconnected", "Runtime disconnected"),
+    ("studio.canvas.heading", "Canvas"),
+    ("studio.canvas.fit_view", "Fit View"),
+    ("studio.canvas.fit_view_short", "Fit"),
+    ("studio.canvas.previous_snapshot", "Previous turn snapshot"),
+    ("studio.canvas.next_snapshot", "Next turn snapshot"),
+    ("studio.canvas.before_after", "Before/After"),
+    ("studio.canvas.focus", "Focus"),
+    ("studio.canvas.timeline", "Timeline"),
+    (
+        "studio.canvas.timeline_hint",
+        "Waterfall of the latest turn's model and tool calls",
+    ),
+    ("studio.canvas.notes", "Notes"),
+    (
+        "studio.canvas.notes_hint",
+        "Notes linked by [[wikilinks]] and shared #tags; notes the latest turn touched are highlighted",
+    ),
+    ("studio.canvas.inspector", "Inspector"),
+    (
+        "studio.canvas.inspector_hint",
+        "Model-written architecture summary per subsystem",
+    ),
+    ("studio.canvas.toggle_on", "{label} On"),
+    ("studio.canvas.zoom_in", "Zoom canvas in"),
+    ("studio.canvas.zoom_reset", "Reset canvas zoom"),
+    ("studio.canvas.zoom_out", "Zoom canvas out"),
+    ("studio.display.hint", "Text size and contrast"),
+    ("studio.display.text_size", "Text size {percent}%"),
+    ("studio.display.smaller", "Smaller text (Ctrl+-)"),
+    ("studio.display.reset", "Reset"),
+    ("studio.display.reset_hint", "Default text size (Ctrl+0)"),
+    ("studio.display.larger", "Larger text (Ctrl++)"),
+    ("studio.display.high_contrast", "High contrast"),
+    (
+        "studio.display.high_contrast_hint",
+        "Black-on-white theme with heavier outlines (Ctrl+Shift+H)",
+    ),
+    (
+        "studio.display.keyboard_note",
+        "F6 moves focus between prompt, chat history, and canvas toolbar. Saved to {file}.",
+    ),
+    ("studio.recovery.title", "Restore previous session?"),
+    (
+        "studio.recovery.body",
+        "Studio crashed during the last session in this workspace. Its conversation ({count} messages) was saved.",
+    ),
+    ("studio.recovery.report", "Crash report: {path}"),
+    ("studio.recovery.restore", "Restore conversation"),
+    ("studio.recovery.dismiss", "Start fresh"),
+    (
+        "studio.recovery.restored",
+        "Restored the conversation from before the crash. Crash report: `{path}`.",
+    ),
+    (
+        "update.available",
+        "mjolne_vibes {latest} is available (running {current}): {url}",
+    ),
+    (
+        "update.up_to_date",
+        "mjolne_vibes {current} is up to date (latest release: {latest}).",
+    ),
+    (
+        "context.usage",
+        "{messages} msgs · ~{tokens}/{budget} tokens",
+    ),
+    ("studio.context", "Last turn: {usage}"),
+];
+
+const NB: &[(&str, &str)] = &[
+    ("cli.error", "feil: {error}"),
+    ("cli.opening_path", "Åpner {path} med {program}"),
+    (
+        "chat.history_recorded",
+        "Lagret tur {id}; fortsett med --resume-from {id}",
+    ),
+    (
+        "repl.started",
+        "Interaktiv modus startet. Skriv /help for kommandoer.",
+    ),
+    ("repl.help.help", "/help   Vis kommandoer"),
+    ("repl.help.tools", "/tools  Vis tilgjengelige verktøy"),
+    ("repl.help.reset", "/reset  Nullstill økthistorikken"),
+    (
+        "repl.help.compact",
+        "/compact  Erstatt tidligere turer med et sammendrag skrevet av modellen",
+    ),
+    (
+        "repl.help.tool",
+        "/tool   Kjør et verktøy direkte: /tool <navn> <json-argumenter>",
+    ),
+    (
+        "repl.help.save_transcript",
+        "/save-transcript <sti>  Eksporter samtalen (.md, .html eller .json)",
+    ),
+    (
+        "repl.help.save",
+        "/save [sti]  Lagre samtalen for å fortsette senere (standard: --session-file)",
+    ),
+    (
+        "repl.help.load",
+        "/load [sti]  Erstatt samtalen med en lagret samtale (standard: --session-file)",
+    ),
+    ("repl.help.exit", "/exit   Avslutt interaktiv modus"),
+    (
+        "repl.help.multiline",
+        "```     Start eller avslutt en melding over flere linjer (eller avslutt en linje med \\)",
+    ),
+    (
+        "repl.help.cancel",
+        "/cancel Forkast en påbegynt melding over flere linjer",
+    ),
+    (
+        "repl.help.pager",
+        "/pager on|off  Bla i svar som er høyere enn terminalen ($PAGER eller innebygd)",
+    ),
+    (
+        "repl.help.model",
+        "/model [leverandør] <navn>  Bytt modell for senere turer (/model reset: den konfigurerte)",
+    ),
+    ("repl.model.current", "Modell: {provider}/{model}"),
+    (
+        "repl.model.reset",
+        "Tilbake til den konfigurerte modellen: {provider}/{model}",
+    ),
+    (
+        "repl.model.usage",
+        "bruk: {command} [leverandør] <navn> | {command} reset",
+    ),
+    (
+        "repl.context_trimmed",
+        "Fjernet {count} eldre meldinger for å holde seg under AGENT_MAX_CONTEXT_TOKENS.",
+    ),
+    (
+        "repl.compact.done",
+        "Komprimerte {count} meldinger til et sammendrag.",
+    ),
+    ("repl.compact.nothing", "Ingenting å komprimere ennå."),
+    (
+        "repl.context_compacted",
+        "Komprimerte {count} tidligere meldinger til et sammendrag (AGENT_AUTO_COMPACT).",
+    ),
+    ("repl.pager.enabled", "Bla-visning på."),
+    ("repl.pager.disabled", "Bla-visning av."),
+    ("repl.pager.usage", "bruk: {command} on|off (nå {state})"),
+    (
+        "repl.pager.prompt",
+        "-- linje {first}-{last} av {total}: Enter neste, b tilbake, /tekst søk, n neste treff, q avslutt -- ",
+    ),
+    ("repl.pager.not_found", "Fant ikke mønsteret: {pattern}"),
+    ("repl.history_cleared", "Økthistorikken er tømt."),
+    (
+        "repl.transcript_saved",
+        "Lagret {format}-transkripsjon til {path}.",
+    ),
+    ("repl.session_title", "Økt: {title}"),
+    ("repl.session_saved", "Lagret {count} meldinger til {path}."),
+    (
+        "repl.session_loaded",
+        "Lastet {count} meldinger fra {path}.",
+    ),
+    (
+        "repl.session_usage",
+        "bruk: {command} <sti> (eller start repl med --session-file)",
+    ),
+    (
+        "repl.multiline_discarded",
+        "Melding over flere linjer forkastet.",
+    ),
+    (
+        "repl.pasted_lines",
+        "Limte inn {count} linjer; trykk Enter på en tom linje for å sende, eller skriv {cancel}.",
+    ),
+    (
+        "repl.save_usage",
+        "bruk: {command} <sti.md|sti.html|sti.json>",
+    ),
+    (
+        "repl.save_unknown_format",
+        "kan ikke avgjøre eksportformat fra `{path}`; bruk en .md-, .html- eller .json-sti",
+    ),
+    (
+        "preflight.reachable",
+        "Forhåndssjekk: {provider} svarer ({count} modell(er))",
+    ),
+    (
+        "preflight.model_available",
+        "Forhåndssjekk: modellen `{model}` er tilgjengelig",
+    ),
+    (
+        "preflight.warmup_completed",
+        "Forhåndssjekk: oppvarmingsrunden ble fullført på {millis}ms",
+    ),
+    (
+        "capabilities.provider",
+        "leverandør: {provider} (modell {model})",
+    ),
+    ("capabilities.tools", "verktøy: {tools}"),
+    ("capabilities.answer_formats", "svarformater: {formats}"),
+    (
+        "capabilities.guardrails",
+        "sikkerhetsgrenser: hentedomener [{domains}], kommandoer [{commands}], overskriv notater {overwrite}, slett notater {delete}, TOOL_WORKING_DIR satt {working_dir}",
+    ),
+    (
+        "capabilities.limits",
+        "grenser: {steps} steg, {tool_calls} verktøykall ({per_step} per steg), {input} tegn inn / {output} tegn ut, verktøytidsavbrudd {tool_timeout}ms, modelltidsavbrudd {model_timeout}ms",
+    ),
+    (
+        "graph.check.no_rules",
+        "Ingen avhengighetsregler er satt opp (bruk --rules <STI> eller sett GRAPH_DEPENDENCY_RULES_FILE)",
+    ),
+    (
+        "graph.check.unresolved_mod",
+        "{module}: `mod {name};` i {declared_in} har ingen tilhørende fil",
+    ),
+    ("graph.check.inline_module", "en innebygd modul"),
+    (
+        "graph.check.summary",
+        "Sjekket {rules} regler mot {edges} kanter: {violations} brudd, {unresolved} uløste mod-deklarasjoner",
+    ),
+    (
+        "studio.ready",
+        "Studio er klart. Send en melding for å kjøre en chatrunde.",
+    ),
+    ("studio.history_cleared", "Historikken er tømt."),
+    ("studio.title", "Studio"),
+    ("studio.hide_chat", "Skjul chat"),
+    ("studio.hide_chat_short", "Skjul"),
+    ("studio.show_chat", "Vis chat"),
+    ("studio.show_chat_short", "Chat"),
+    ("studio.status.running", "Kjører"),
+    ("studio.status.reconnecting", "Kobler til igjen"),
+    ("studio.status.disconnected", "Frakoblet"),
+    ("studio.status.ready", "Klar"),
+    ("studio.graph.refresh", "Oppdater graf"),
+    (
+        "studio.graph.refresh_hint",
+        "Bygg arkitekturgrafen på nytt nå",
+    ),
+    ("studio.graph.refresh_requested", "Grafoppdatering bestilt"),
+    ("studio.graph.pause", "Pause overvåking"),
+    (
+        "studio.graph.pause_hint",
+        "Stopp automatiske grafoppdateringer",
+    ),
+    ("studio.graph.resume", "Fortsett overvåking"),
+    (
+        "studio.graph.resume_hint",
+        "Fortsett automatiske grafoppdateringer",
+    ),
+    ("studio.chat.heading", "Chat"),
+    ("studio.chat.message_count", "{count} meldinger"),
+    ("studio.chat.clear_history", "Tøm historikk"),
+    (
+        "studio.chat.clear_history_hint",
+        "Fjern chatmeldinger, rundesammendrag, verktøykort og øyeblikksbilder",
+    ),
+    ("studio.chat.export", "Eksporter"),
+    ("studio.chat.export_hint", "Eksporter samtalen til {dir}/"),
+    ("studio.session_notes.heading", "Øktnotater ({count})"),
+    (
+        "studio.session_notes.empty",
+        "Ingen notater er lagret i denne økten ennå.",
+    ),
+    ("studio.session_notes.promote", "Flytt valgte"),
+    (
+        "studio.session_notes.promote_hint",
+        "Flytt de valgte notatene til {dir}/",
+    ),
+    ("studio.history.heading", "Søk i historikken"),
+    ("studio.history.hint", "Tekst i tidligere turer..."),
+    ("studio.history.search", "Søk"),
+    ("studio.history.empty", "Ingen lagrede turer passer."),
+    ("studio.history.copy_id", "Kopier id"),
+    (
+        "studio.history.copy_id_hint",
+        "Kopier tur-id-en til chat --resume-from",
+    ),
+    ("studio.prompt.heading", "Melding"),
+    ("studio.prompt.hint", "Spør agenten..."),
+    ("studio.prompt.send", "Send"),
+    ("studio.prompt.queue", "Sett i kø"),
+    ("studio.prompt.running", "Kjører..."),
+    (
+        "studio.prompt.runtime_disconnected",
+        "Kjøremiljøet er frakoblet",
+    ),
+    ("studio.canvas.heading", "Lerret"),
+    ("studio.canvas.fit_view", "Tilpass visning"),
+    ("studio.canvas.fit_view_short", "Tilpass"),
+    ("studio.canvas.previous_snapshot", "Forrige rundebilde"),
+    ("studio.canvas.next_snapshot", "Neste rundebilde"),
+    ("studio.canvas.before_after", "Før/etter"),
+    ("studio.canvas.focus", "Fokus"),
+    ("studio.canvas.timeline", "Tidslinje"),
+    (
+        "studio.canvas.timeline_hint",
+        "Fossefall over modell- og verktøykallene i siste runde",
+    ),
+    ("studio.canvas.notes", "Notater"),
+    (
+        "studio.canvas.notes_hint",
+        "Notater koblet med [[wikilenker]] og felles #tagger; notater siste runde rørte er uthevet",
+    ),
+    ("studio.canvas.inspector", "Inspektør"),
+    (
+        "studio.canvas.inspector_hint",
+        "Modellskrevet arkitektursammendrag per delsystem",
+    ),
+    ("studio.canvas.toggle_on", "{label} på"),
+    ("studio.canvas.zoom_in", "Zoom inn på lerretet"),
+    ("studio.canvas.zoom_reset", "Nullstill lerretszoom"),
+    ("studio.canvas.zoom_out", "Zoom ut på lerretet"),
+    ("studio.display.hint", "Tekststørrelse og kontrast"),
+    ("studio.display.text_size", "Tekststørrelse {percent}%"),
+    ("studio.display.smaller", "Mindre tekst (Ctrl+-)"),
+    ("studio.display.reset", "Nullstill"),
+    (
+        "studio.display.reset_hint",
+        "Standard tekststørrelse (Ctrl+0)",
+    ),
+    ("studio.display.larger", "Større tekst (Ctrl++)"),
+    ("studio.display.high_contrast", "Høy kontrast"),
+    (
+        "studio.display.high_contrast_hint",
+        "Svart på hvitt med tydeligere kanter (Ctrl+Shift+H)",
+    ),
+    (
+        "studio.display.keyboard_note",
+        "F6 flytter fokus mellom melding, chathistorikk og lerretsverktøylinjen. Lagres i {file}.",
+    ),
+    ("studio.recovery.title", "Gjenopprette forrige økt?"),
+    (
+        "studio.recovery.body",
+        "Studio krasjet i forrige økt i dette arbeidsområdet. Samtalen ({count} meldinger) ble lagret.",
+    ),
+    ("studio.recovery.report", "Krasjrapport: {path}"),
+    ("studio.recovery.restore", "Gjenopprett samtalen"),
+    ("studio.recovery.dismiss", "Start på nytt"),
+    (
+        "studio.recovery.restored",
+        "Samtalen fra før krasjet er gjenopprettet. Krasjrapport: `{path}`.",
+    ),
+    (
+        "update.available",
+        "mjolne_vibes {latest} er tilgjengelig (du kjører {current}): {url}",
+    ),
+    (
+        "update.up_to_date",
+        "mjolne_vibes {current} er oppdatert (siste utgivelse: {latest}).",
+    ),
+    (
+        "context.usage",
+        "{messages} meld. · ~{tokens}/{budget} tokens",
+    ),
+    ("studio.context", "Siste tur: {usage}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use regex::Regex;
+
+    use super::{EN, Locale, format_message, message_in};
+
+    #[test]
+    fn every_locale_translates_every_key_with_the_same_placeholders() {
+        let placeholder = Regex::new(r"\{([a-z_]+)\}").expect("valid placeholder regex");
+        let placeholders = |text: &str| {
+            placeholder
+                .captures_iter(text)
+                .map(|captures| captures[1].to_owned())
+                .collect::<BTreeSet<_>>()
+        };
+        let english_keys = EN.iter().map(|(key, _)| *key).collect::<BTreeSet<_>>();
+        assert_eq!(english_keys.len(), EN.len(), "duplicate English keys");
+        for locale in Locale::ALL {
+            let keys = locale
+                .catalog()
+                .iter()
+                .map(|(key, _)| *key)
+                .collect::<BTreeSet<_>>();
+            assert_eq!(keys, english_keys, "{} catalog keys", locale.code());
+            for (key, english) in EN {
+                assert_eq!(
+                    placeholders(message_in(locale, key)),
+                    placeholders(english),
+                    "{} placeholders for `{key}`",
+                    locale.code()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn locale_parses_codes_and_fills_placeholders() {
+        assert_eq!(
+            "nb_NO.UTF-8".parse::<Locale>().unwrap(),
+            Locale::NorwegianBokmal
+        );
+        assert_eq!("EN-us".parse::<Locale>().unwrap(), Locale::English);
+        assert!("klingon".parse::<Locale>().is_err());
+        assert_eq!(
+            format_message(
+                message_in(Locale::NorwegianBokmal, "repl.session_title"),
+                &[("title", &"Fredagsfix")]
+            ),
+            "Økt: Fredagsfix"
+        );
+        assert_eq!(
+            message_in(Locale::NorwegianBokmal, "no.such.key"),
+            "no.such.key"
+        );
+    }
+}
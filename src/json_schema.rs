@@ -0,0 +1,412 @@
+use serde_json::{Map, Value};
+
+/// Keywords `schema_violations` checks.
+const CHECKED_KEYWORDS: [&str; 15] = [
+    "type",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "enum",
+    "const",
+    "anyOf",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "minItems",
+    "maxItems",
+    "minProperties",
+];
+/// Keywords that describe a value without constraining it.
+const ANNOTATION_KEYWORDS: [&str; 7] = [
+    "$schema",
+    "$id",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "format",
+];
+const TYPE_NAMES: [&str; 7] = [
+    "object", "array", "string", "number", "integer", "boolean", "null",
+];
+
+/// Rejects a schema the validator cannot fully check, so a keyword it would ignore never
+/// lets an answer pass: only `CHECKED_KEYWORDS` and `ANNOTATION_KEYWORDS` are allowed,
+/// and `$ref`, `oneOf`, `pattern`, and the like fail here.
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    check_schema_at(schema, "")
+}
+
+fn check_schema_at(schema: &Value, path: &str) -> Result<(), String> {
+    let location = display_path(path);
+    let schema = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(schema) => schema,
+        _ => {
+            return Err(format!(
+                "{location}: a schema must be an object or a boolean"
+            ));
+        }
+    };
+    for (keyword, value) in schema {
+        let keyword_path = format!("{path}/{keyword}");
+        match keyword.as_str() {
+            "type" => {
+                let names = match value {
+                    Value::String(name) => vec![name.as_str()],
+                    Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                    _ => Vec::new(),
+                };
+                if names.is_empty() || names.iter().any(|name| !TYPE_NAMES.contains(name)) {
+                    return Err(format!(
+                        "{}: expected one of {} or a list of them",
+                        display_path(&keyword_path),
+                        TYPE_NAMES.join(", ")
+                    ));
+                }
+            }
+            "properties" => {
+                let Value::Object(properties) = value else {
+                    return Err(format!(
+                        "{}: expected an object",
+                        display_path(&keyword_path)
+                    ));
+                };
+                for (name, property) in properties {
+                    check_schema_at(property, &format!("{keyword_path}/{name}"))?;
+                }
+            }
+            "required" => {
+                if !value
+                    .as_array()
+                    .is_some_and(|names| names.iter().all(Value::is_string))
+                {
+                    return Err(format!(
+                        "{}: expected a list of property names",
+                        display_path(&keyword_path)
+                    ));
+                }
+            }
+            "additionalProperties" | "items" => check_schema_at(value, &keyword_path)?,
+            "anyOf" => {
+                let Some(options) = value.as_array().filter(|options| !options.is_empty()) else {
+                    return Err(format!(
+                        "{}: expected a non-empty list of schemas",
+                        display_path(&keyword_path)
+                    ));
+                };
+                for (index, option) in options.iter().enumerate() {
+                    check_schema_at(option, &format!("{keyword_path}/{index}"))?;
+                }
+            }
+            "enum" => {
+                if !value.is_array() {
+                    return Err(format!("{}: expected a list", display_path(&keyword_path)));
+                }
+            }
+            "minimum" | "maximum" => {
+                if !value.is_number() {
+                    return Err(format!(
+                        "{}: expected a number",
+                        display_path(&keyword_path)
+                    ));
+                }
+            }
+            "minLength" | "maxLength" | "minItems" | "maxItems" | "minProperties" => {
+                if !value.is_u64() {
+                    return Err(format!(
+                        "{}: expected a non-negative integer",
+                        display_path(&keyword_path)
+                    ));
+                }
+            }
+            keyword
+                if CHECKED_KEYWORDS.contains(&keyword)
+                    || ANNOTATION_KEYWORDS.contains(&keyword) => {}
+            keyword => {
+                return Err(format!(
+                    "{location}: unsupported keyword `{keyword}`; supported: {}",
+                    CHECKED_KEYWORDS.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Every way `value` breaks `schema`, each prefixed with the JSON pointer of the
+/// offending value (`/` for the root). Empty when it matches. Assumes `check_schema`
+/// accepted the schema; malformed keywords are skipped.
+pub fn schema_violations(schema: &Value, value: &Value) -> Vec<String> {
+    let mut violations = Vec::new();
+    collect_violations(schema, value, "", &mut violations);
+    violations
+}
+
+fn collect_violations(schema: &Value, value: &Value, path: &str, violations: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            violations.push(format!("{}: no value is allowed here", display_path(path)));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+    let location = display_path(path);
+
+    if let Some(expected) = schema.get("type") {
+        let names = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.iter().any(|name| has_type(value, name)) {
+            violations.push(format!(
+                "{location}: expected {}, found {}",
+                names.join(" or "),
+                type_name(value)
+            ));
+            // Further keywords would only restate the type mismatch.
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        violations.push(format!(
+            "{location}: {} is not one of {}",
+            value,
+            allowed
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        violations.push(format!("{location}: expected {expected}, found {value}"));
+    }
+    if let Some(options) = schema.get("anyOf").and_then(Value::as_array)
+        && !options
+            .iter()
+            .any(|option| schema_violations(option, value).is_empty())
+    {
+        violations.push(format!("{location}: matches none of the `anyOf` schemas"));
+    }
+
+    match value {
+        Value::String(text) => {
+            let chars = text.chars().count() as u64;
+            if let Some(min) = bound(schema, "minLength")
+                && chars < min
+            {
+                violations.push(format!(
+                    "{location}: is {chars} characters, expected at least {min}"
+                ));
+            }
+            if let Some(max) = bound(schema, "maxLength")
+                && chars > max
+            {
+                violations.push(format!(
+                    "{location}: is {chars} characters, expected at most {max}"
+                ));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+                && number < min
+            {
+                violations.push(format!("{location}: {number} is below the minimum {min}"));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+                && number > max
+            {
+                violations.push(format!("{location}: {number} is above the maximum {max}"));
+            }
+        }
+        Value::Array(items) => {
+            let count = items.len() as u64;
+            if let Some(min) = bound(schema, "minItems")
+                && count < min
+            {
+                violations.push(format!(
+                    "{location}: has {count} items, expected at least {min}"
+                ));
+            }
+            if let Some(max) = bound(schema, "maxItems")
+                && count > max
+            {
+                violations.push(format!(
+                    "{location}: has {count} items, expected at most {max}"
+                ));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    collect_violations(item_schema, item, &format!("{path}/{index}"), violations);
+                }
+            }
+        }
+        Value::Object(object) => collect_object_violations(schema, object, path, violations),
+        Value::Bool(_) | Value::Null => {}
+    }
+}
+
+fn collect_object_violations(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    violations: &mut Vec<String>,
+) {
+    let location = display_path(path);
+    if let Some(min) = bound(schema, "minProperties")
+        && (object.len() as u64) < min
+    {
+        violations.push(format!(
+            "{location}: has {} properties, expected at least {min}",
+            object.len()
+        ));
+    }
+    let missing = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .filter(|name| !object.contains_key(*name))
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        violations.push(format!(
+            "{location}: missing required {}",
+            missing.join(", ")
+        ));
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let additional = schema.get("additionalProperties");
+    for (name, property) in object {
+        let property_path = format!("{path}/{name}");
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property_schema) => {
+                collect_violations(property_schema, property, &property_path, violations);
+            }
+            None => match additional {
+                Some(Value::Bool(false)) => {
+                    violations.push(format!("{location}: unexpected property `{name}`"))
+                }
+                Some(additional) => {
+                    collect_violations(additional, property, &property_path, violations);
+                }
+                None => {}
+            },
+        }
+    }
+}
+
+fn bound(schema: &Map<String, Value>, keyword: &str) -> Option<u64> {
+    schema.get(keyword).and_then(Value::as_u64)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        "number" => value.is_number(),
+        name => type_name(value) == name,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{check_schema, schema_violations};
+
+    #[test]
+    fn violations_name_the_path_of_each_mismatch() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "releases"],
+            "additionalProperties": false,
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "stable": {"type": "boolean"},
+                "releases": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "type": "object",
+                        "required": ["version"],
+                        "properties": {
+                            "version": {"type": "string"},
+                            "year": {"type": "integer", "minimum": 2010}
+                        }
+                    }
+                },
+                "channel": {"enum": ["stable", "beta"]}
+            }
+        });
+        assert_eq!(check_schema(&schema), Ok(()));
+
+        let matching = json!({"name": "rust", "releases": [{"version": "1.0", "year": 2015}]});
+        assert!(schema_violations(&schema, &matching).is_empty());
+
+        let broken = json!({
+            "name": "",
+            "stable": "yes",
+            "releases": [{"year": 2009.5}, {"version": 1}],
+            "channel": "nightly",
+            "extra": true
+        });
+        assert_eq!(
+            schema_violations(&schema, &broken),
+            [
+                "/channel: \"nightly\" is not one of \"stable\", \"beta\"",
+                "/: unexpected property `extra`",
+                "/name: is 0 characters, expected at least 1",
+                "/releases/0: missing required `version`",
+                "/releases/0/year: expected integer, found number",
+                "/releases/1/version: expected string, found number",
+                "/stable: expected boolean, found string",
+            ]
+        );
+        assert_eq!(
+            schema_violations(&schema, &json!([1])),
+            ["/: expected object, found array"]
+        );
+    }
+
+    #[test]
+    fn unsupported_keywords_are_rejected() {
+        assert_eq!(check_schema(&json!(true)), Ok(()));
+        let error = check_schema(&json!({"properties": {"id": {"pattern": "^a"}}}))
+            .expect_err("pattern is not checked");
+        assert!(
+            error.starts_with("/properties/id: unsupported keyword `pattern`"),
+            "{error}"
+        );
+        assert!(check_schema(&json!({"type": "text"})).is_err());
+        assert!(check_schema(&json!({"required": "name"})).is_err());
+        assert!(check_schema(&json!("object")).is_err());
+    }
+}
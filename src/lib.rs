@@ -1,11 +1,49 @@
+#[cfg(feature = "runtime")]
 pub mod agent;
 pub mod answer_format;
+pub mod api;
+#[cfg(feature = "runtime")]
+pub mod bench;
+#[cfg(feature = "runtime")]
+pub mod build_info;
+#[cfg(feature = "runtime")]
+pub mod capabilities;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "runtime")]
 pub mod config;
+#[cfg(feature = "runtime")]
+pub mod crash;
+#[cfg(feature = "runtime")]
+pub mod digest;
+#[cfg(feature = "runtime")]
+pub mod editor;
+#[cfg(feature = "runtime")]
 pub mod eval;
+#[cfg(feature = "runtime")]
+pub mod export;
 pub mod graph;
+#[cfg(feature = "runtime")]
+pub mod guardrails;
+#[cfg(feature = "runtime")]
+pub mod http;
+#[cfg(feature = "runtime")]
+pub mod i18n;
+pub mod json_schema;
+#[cfg(feature = "runtime")]
+pub mod log_redaction;
+#[cfg(feature = "runtime")]
 pub mod model;
+#[cfg(feature = "runtime")]
+pub mod preflight;
+#[cfg(feature = "runtime")]
 pub mod server;
+#[cfg(feature = "runtime")]
 pub mod studio;
 #[doc(hidden)]
 pub mod test_support;
+pub mod text;
+#[cfg(feature = "runtime")]
 pub mod tools;
+#[cfg(feature = "runtime")]
+pub mod update;
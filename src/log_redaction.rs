@@ -0,0 +1,170 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use ring::digest;
+use tracing::field::Field;
+use tracing_subscriber::field::MakeExt;
+use tracing_subscriber::fmt::FormatFields;
+use tracing_subscriber::fmt::format::{Writer, debug_fn};
+
+use crate::text::{grapheme_count, hex_lower, truncate_graphemes};
+
+/// Log fields that can carry prompt, tool, or answer text: turn and request errors
+/// (which quote tool arguments, note titles, and provider replies) and generated
+/// session summaries.
+pub const CONTENT_LOG_FIELDS: [&str; 3] = ["error", "session_title", "session_summary"];
+/// User-perceived characters `truncate` keeps of a content field.
+const TRUNCATED_FIELD_CHARS: usize = 120;
+/// Digest bytes `hash` shows, enough to match repeated values across log lines.
+const HASHED_FIELD_BYTES: usize = 8;
+
+/// How `MJOLNE_FILE_LOG_REDACTION` rewrites content fields in the file log; the console
+/// log is never redacted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogRedaction {
+    Off,
+    /// Keep the start of each content field and its length.
+    #[default]
+    Truncate,
+    /// Replace each content field with a short SHA-256 prefix and its length.
+    Hash,
+}
+
+impl FromStr for LogRedaction {
+    type Err = String;
+
+    fn from_str(raw: &str) -> std::result::Result<Self, Self::Err> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "truncate" => Ok(Self::Truncate),
+            "hash" => Ok(Self::Hash),
+            other => Err(format!(
+                "unknown log redaction `{other}`; expected `off`, `truncate`, or `hash`"
+            )),
+        }
+    }
+}
+
+impl LogRedaction {
+    /// `MJOLNE_FILE_LOG_REDACTION`, `truncate` when unset.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("MJOLNE_FILE_LOG_REDACTION") {
+            Ok(raw) => raw
+                .parse()
+                .map_err(anyhow::Error::msg)
+                .context("invalid MJOLNE_FILE_LOG_REDACTION"),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// `value` as the file log shows it.
+    pub fn apply(self, value: &str) -> String {
+        let chars = grapheme_count(value);
+        match self {
+            Self::Off => value.to_owned(),
+            Self::Truncate if chars <= TRUNCATED_FIELD_CHARS => value.to_owned(),
+            Self::Truncate => format!(
+                "{} [{chars} chars]",
+                truncate_graphemes(value, TRUNCATED_FIELD_CHARS, "…")
+            ),
+            Self::Hash => {
+                let digest = digest::digest(&digest::SHA256, value.as_bytes());
+                let hashed = hex_lower(&digest.as_ref()[..HASHED_FIELD_BYTES]);
+                format!("sha256:{hashed} [{chars} chars]")
+            }
+        }
+    }
+
+    /// Field formatter for the file log layer: fields print like the default
+    /// `name=value` format, with `CONTENT_LOG_FIELDS` passed through `apply`.
+    pub fn file_log_fields(self) -> impl for<'writer> FormatFields<'writer> + 'static {
+        debug_fn(
+            move |writer: &mut Writer<'_>, field: &Field, value: &dyn fmt::Debug| {
+                let name = field.name();
+                if name == "message" {
+                    write!(writer, "{value:?}")
+                } else if self != Self::Off && CONTENT_LOG_FIELDS.contains(&name) {
+                    write!(writer, "{name}={}", self.apply(&format!("{value:?}")))
+                } else {
+                    write!(writer, "{name}={value:?}")
+                }
+            },
+        )
+        .delimited(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::warn;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::LogRedaction;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0
+                .lock()
+                .expect("capture lock")
+                .extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn log_line(redaction: LogRedaction, error: &str) -> String {
+        let captured = Captured::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .without_time()
+            .with_target(false)
+            .fmt_fields(redaction.file_log_fields())
+            .with_writer(captured.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            warn!(turn_id = 7, error = %error, "turn trace summary (failed)");
+        });
+        let bytes = captured.0.lock().expect("capture lock").clone();
+        String::from_utf8(bytes).expect("utf-8 log line")
+    }
+
+    #[test]
+    fn content_fields_are_truncated_or_hashed_in_the_file_log() {
+        let error = format!("tool `read_note` failed: {}", "secret ".repeat(30));
+
+        let line = log_line(LogRedaction::Truncate, &error);
+        assert!(
+            line.contains("turn trace summary (failed) turn_id=7 error=tool `read_note` failed:"),
+            "{line}"
+        );
+        assert!(line.trim_end().ends_with("… [235 chars]"), "{line}");
+
+        let line = log_line(LogRedaction::Hash, &error);
+        assert!(!line.contains("secret"), "{line}");
+        assert!(line.contains(&LogRedaction::Hash.apply(&error)), "{line}");
+        assert!(line.contains("error=sha256:"), "{line}");
+
+        assert!(log_line(LogRedaction::Off, &error).contains(&error));
+        assert_eq!(LogRedaction::Truncate.apply("short"), "short");
+        assert_eq!("HASH".parse(), Ok(LogRedaction::Hash));
+        assert!("mask".parse::<LogRedaction>().is_err());
+    }
+}
@@ -1,19 +1,45 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 use std::sync::OnceLock;
 use tracing_subscriber::fmt;
 use tracing_subscriber::{EnvFilter, Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
-use mjolne_vibes::agent::{run_chat, run_chat_json, run_repl};
+use mjolne_vibes::agent::{
+    DEFAULT_HISTORY_SEARCH_LIMIT, parse_user, run_audit_verify_command, run_chat, run_chat_json,
+    run_history_search_command, run_repl,
+};
+use mjolne_vibes::bench::{
+    DEFAULT_BENCH_REQUESTS, DEFAULT_LOAD_TEST_CONCURRENCY, DEFAULT_LOAD_TEST_REQUESTS,
+    DEFAULT_MOCK_LATENCY_MS, LoadTestOptions, run_bench_command, run_load_test_command,
+};
+use mjolne_vibes::build_info::LONG_VERSION;
+use mjolne_vibes::capabilities::run_capabilities_command;
 use mjolne_vibes::config::AgentSettings;
-use mjolne_vibes::eval::{DEFAULT_EVAL_CASES_PATH, run_eval_command};
-use mjolne_vibes::server::run_http_server;
+use mjolne_vibes::crash::{install_panic_hook, log_dir_from_env};
+use mjolne_vibes::digest::{DEFAULT_DIGEST_DAYS, run_digest_command};
+use mjolne_vibes::editor::run_graph_open_command;
+use mjolne_vibes::eval::{
+    DEFAULT_EVAL_CASES_PATH, DEFAULT_EVAL_SUMMARY_PATH, run_eval_command, run_eval_watch_command,
+};
+use mjolne_vibes::graph::rules::run_graph_check_command;
+use mjolne_vibes::i18n::set_locale;
+use mjolne_vibes::log_redaction::LogRedaction;
+use mjolne_vibes::server::{ServeOptions, run_http_server};
+use mjolne_vibes::studio::describe::run_graph_describe_command;
 use mjolne_vibes::studio::run_studio;
+use mjolne_vibes::tools::run_tools_list_command;
+use mjolne_vibes::update::{run_update_check_command, spawn_startup_update_check};
 
 static FILE_LOG_GUARD: OnceLock<tracing_appender::non_blocking::WorkerGuard> = OnceLock::new();
 
 #[derive(Debug, Parser)]
-#[command(name = "mjolne_vibes", about = "CLI-first Rust AI agent")]
+#[command(
+    name = "mjolne_vibes",
+    about = "CLI-first Rust AI agent",
+    version,
+    long_version = LONG_VERSION.as_str()
+)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -27,27 +53,223 @@ enum Commands {
         /// Emit a machine-readable JSON payload with final text, trace, and tool calls.
         #[arg(long)]
         json: bool,
+        /// Write a waterfall of the turn's model and tool calls to this HTML file.
+        #[arg(long, value_name = "PATH")]
+        trace_html: Option<PathBuf>,
+        /// With --json, keep tool-call fields longer than JSON_OUTPUT_MAX_FIELD_CHARS intact.
+        #[arg(long, requires = "json")]
+        full: bool,
+        /// Continue the conversation recorded in AGENT_HISTORY_FILE up to this turn id.
+        #[arg(long, value_name = "TURN_ID")]
+        resume_from: Option<String>,
+        /// Run this turn on MODEL instead of the configured one, with the same provider.
+        #[arg(long, value_name = "MODEL")]
+        model: Option<String>,
+        /// Attribute the turn, and the notes it saves, to USER.
+        #[arg(long, value_name = "USER", value_parser = parse_user)]
+        user: Option<String>,
     },
     /// Start an interactive multi-turn REPL session.
     Repl {
         /// Print info/debug logs to terminal during interactive use.
         #[arg(long)]
         verbose: bool,
+        /// Resume the conversation saved in this JSON file and save it back on exit.
+        #[arg(long, value_name = "PATH")]
+        session_file: Option<PathBuf>,
+        /// Attribute every turn, and the notes it saves, to USER.
+        #[arg(long, value_name = "USER", value_parser = parse_user)]
+        user: Option<String>,
     },
     /// Run evaluation cases from YAML.
     Eval {
         /// Path to eval cases YAML file.
         #[arg(long, default_value = DEFAULT_EVAL_CASES_PATH)]
         cases: String,
+        /// Keep running and re-run affected cases when the cases file or notes fixtures change.
+        #[arg(long)]
+        watch: bool,
+        /// Check provider reachability, the configured model, and one warm-up turn first.
+        #[arg(long)]
+        preflight: bool,
+        /// Cases run at once, each in its own notes directory when above 1 (ignored with --watch).
+        #[arg(long, default_value_t = 1)]
+        concurrency: u32,
+        /// Where to write the JSON run summary (ignored with --watch).
+        #[arg(long, default_value = DEFAULT_EVAL_SUMMARY_PATH)]
+        summary: PathBuf,
+        /// Earlier `--summary` JSON to diff against; fails only on cases that passed there
+        /// and no longer pass, instead of on the pass-rate target (ignored with --watch).
+        #[arg(long, value_name = "PREVIOUS_SUMMARY")]
+        baseline: Option<PathBuf>,
+    },
+    /// Measure latency and throughput of the configured provider with fixed prompts.
+    Bench {
+        /// Number of prompts sent to each model.
+        #[arg(long, default_value_t = DEFAULT_BENCH_REQUESTS)]
+        requests: u32,
+        /// Model to benchmark; repeat to compare several. Defaults to MODEL.
+        #[arg(long = "model", value_name = "MODEL")]
+        models: Vec<String>,
+        #[command(subcommand)]
+        mode: Option<BenchMode>,
     },
     /// Start an HTTP server exposing the same one-turn chat loop.
     Serve {
         /// Socket address to bind, for example 127.0.0.1:8080.
         #[arg(long, default_value = "127.0.0.1:8080")]
         bind: String,
+        /// Run the model preflight, then report readiness via sd_notify (`$NOTIFY_SOCKET`).
+        #[arg(long)]
+        daemon_ready_notify: bool,
+        /// Write the server PID here while running; refuses to start if a live server owns it.
+        #[arg(long, value_name = "PATH")]
+        pid_file: Option<PathBuf>,
+        /// Start even when the guardrails combine unsafely (logged as warnings instead).
+        #[arg(long)]
+        allow_unsafe: bool,
     },
     /// Start native studio UI with chat and canvas panes.
-    Studio,
+    Studio {
+        /// Base URL of a `serve` instance to read the workspace graph from instead of watching locally.
+        #[arg(long)]
+        remote_graph: Option<String>,
+        /// Project directory to show instead of the launch directory.
+        #[arg(long, value_name = "PATH")]
+        workspace: Option<PathBuf>,
+        /// Additional directory graphed next to the workspace; repeat for several roots.
+        #[arg(long = "extra-root", value_name = "[NAME=]PATH")]
+        extra_roots: Vec<String>,
+    },
+    /// Inspect the workspace architecture graph.
+    Graph {
+        /// Project directory to inspect instead of the launch directory.
+        #[arg(long, value_name = "PATH", global = true)]
+        workspace: Option<PathBuf>,
+        /// Additional directory graphed next to the workspace; repeat for several roots.
+        #[arg(long = "extra-root", value_name = "[NAME=]PATH", global = true)]
+        extra_roots: Vec<String>,
+        #[command(subcommand)]
+        command: GraphCommand,
+    },
+    /// Summarize recently changed notes with the model and save the summary as a dated note.
+    Digest {
+        /// Include notes modified within this many days.
+        #[arg(long, default_value_t = DEFAULT_DIGEST_DAYS)]
+        days: u32,
+        /// Print the digest without saving it.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Search turns recorded in AGENT_HISTORY_FILE.
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// Check the signed turn ledger for tampering.
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+    /// Inspect the tools exposed to the model.
+    Tools {
+        #[command(subcommand)]
+        command: ToolsCommand,
+    },
+    /// Report the version, provider/model, tools, limits, and answer formats of this deployment.
+    Capabilities {
+        /// Print the full report as JSON (same body as `GET /capabilities`).
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check for newer releases.
+    Update {
+        #[command(subcommand)]
+        command: UpdateCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum UpdateCommand {
+    /// Report whether a newer release than this build is published.
+    Check {
+        /// Print the current and latest version as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum HistoryCommand {
+    /// List the newest turns whose message or answer contains every word of the query.
+    Search {
+        query: String,
+        /// Most turns to list.
+        #[arg(long, default_value_t = DEFAULT_HISTORY_SEARCH_LIMIT)]
+        limit: usize,
+        /// Print the matches as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum AuditCommand {
+    /// Validate every entry's signature and the chain linking them (AGENT_HISTORY_SIGNING_KEY).
+    Verify {
+        /// Ledger to check instead of AGENT_HISTORY_FILE.
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+        /// Print the result as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ToolsCommand {
+    /// List tool signatures and descriptions.
+    List {
+        /// Print the definitions with their JSON parameter schemas.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum GraphCommand {
+    /// Open the source file behind a graph node in the configured editor.
+    Open {
+        /// Node id, for example `module:crate::agent` or `file:src/agent/mod.rs`.
+        node_id: String,
+    },
+    /// Ask the model for an architecture summary of one subsystem.
+    Describe {
+        /// Subsystem key as shown in studio, for example `agent` or `studio`.
+        subsystem: String,
+    },
+    /// Fail when the graph contains a dependency forbidden by the rules file.
+    Check {
+        /// Rules file to use instead of GRAPH_DEPENDENCY_RULES_FILE.
+        #[arg(long, value_name = "PATH")]
+        rules: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum BenchMode {
+    /// Load-test the HTTP server in-process against a mock model.
+    Serve {
+        /// Total `/chat` requests to send.
+        #[arg(long, default_value_t = DEFAULT_LOAD_TEST_REQUESTS)]
+        requests: u32,
+        /// Requests kept in flight at once.
+        #[arg(long, default_value_t = DEFAULT_LOAD_TEST_CONCURRENCY)]
+        concurrency: u32,
+        /// Delay the mock model waits before answering each call.
+        #[arg(long, default_value_t = DEFAULT_MOCK_LATENCY_MS)]
+        mock_latency_ms: u64,
+    },
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -57,15 +279,44 @@ enum LogMode {
     ReplVerbose,
 }
 
+impl Commands {
+    /// Subcommand name recorded in crash reports.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Chat { .. } => "chat",
+            Self::Repl { .. } => "repl",
+            Self::Eval { .. } => "eval",
+            Self::Bench { .. } => "bench",
+            Self::Serve { .. } => "serve",
+            Self::Studio { .. } => "studio",
+            Self::Graph { .. } => "graph",
+            Self::Digest { .. } => "digest",
+            Self::History { .. } => "history",
+            Self::Audit { .. } => "audit",
+            Self::Tools { .. } => "tools",
+            Self::Capabilities { .. } => "capabilities",
+            Self::Update { .. } => "update",
+        }
+    }
+}
+
 impl LogMode {
     fn from_command(command: &Commands) -> Self {
         match command {
-            Commands::Repl { verbose: true } => Self::ReplVerbose,
-            Commands::Repl { verbose: false } => Self::ReplQuiet,
+            Commands::Repl { verbose: true, .. } => Self::ReplVerbose,
+            Commands::Repl { verbose: false, .. } => Self::ReplQuiet,
             Commands::Chat { .. }
             | Commands::Eval { .. }
+            | Commands::Bench { .. }
             | Commands::Serve { .. }
-            | Commands::Studio => Self::Standard,
+            | Commands::Studio { .. }
+            | Commands::Graph { .. }
+            | Commands::Digest { .. }
+            | Commands::History { .. }
+            | Commands::Audit { .. }
+            | Commands::Tools { .. }
+            | Commands::Capabilities { .. }
+            | Commands::Update { .. } => Self::Standard,
         }
     }
 }
@@ -75,22 +326,161 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
     init_tracing(LogMode::from_command(&cli.command))?;
     let settings = AgentSettings::from_env().context("failed to load configuration")?;
+    set_locale(settings.ui_locale);
+    install_panic_hook(log_dir_from_env(), cli.command.name(), &settings);
 
     match cli.command {
         Commands::Chat {
             message,
             json: false,
-        } => run_chat(&settings, &message).await?,
+            trace_html,
+            resume_from,
+            model,
+            user,
+            ..
+        } => {
+            run_chat(
+                &settings,
+                &message,
+                trace_html.as_deref(),
+                resume_from.as_deref(),
+                model.as_deref(),
+                user.as_deref(),
+            )
+            .await?
+        }
         Commands::Chat {
             message,
             json: true,
-        } => run_chat_json(&settings, &message).await?,
-        Commands::Repl { .. } => run_repl(&settings).await?,
-        Commands::Eval { cases } => {
-            run_eval_command(&settings, std::path::Path::new(&cases)).await?
+            trace_html,
+            full,
+            resume_from,
+            model,
+            user,
+        } => {
+            run_chat_json(
+                &settings,
+                &message,
+                trace_html.as_deref(),
+                full,
+                resume_from.as_deref(),
+                model.as_deref(),
+                user.as_deref(),
+            )
+            .await?
+        }
+        Commands::Repl {
+            session_file, user, ..
+        } => run_repl(&settings, session_file.as_deref(), user.as_deref()).await?,
+        Commands::Eval {
+            cases,
+            watch: true,
+            preflight,
+            ..
+        } => run_eval_watch_command(&settings, std::path::Path::new(&cases), preflight).await?,
+        Commands::Eval {
+            cases,
+            watch: false,
+            preflight,
+            concurrency,
+            summary,
+            baseline,
+        } => {
+            run_eval_command(
+                &settings,
+                std::path::Path::new(&cases),
+                preflight,
+                concurrency,
+                &summary,
+                baseline.as_deref(),
+            )
+            .await?
+        }
+        Commands::Bench {
+            mode:
+                Some(BenchMode::Serve {
+                    requests,
+                    concurrency,
+                    mock_latency_ms,
+                }),
+            ..
+        } => {
+            let options = LoadTestOptions {
+                requests,
+                concurrency,
+                mock_latency: std::time::Duration::from_millis(mock_latency_ms),
+            };
+            run_load_test_command(&settings, options).await?
         }
-        Commands::Serve { bind } => run_http_server(&settings, &bind).await?,
-        Commands::Studio => run_studio(&settings)?,
+        Commands::Bench {
+            requests,
+            models,
+            mode: None,
+        } => run_bench_command(&settings, &models, requests).await?,
+        Commands::Serve {
+            bind,
+            daemon_ready_notify,
+            pid_file,
+            allow_unsafe,
+        } => {
+            let options = ServeOptions {
+                daemon_ready_notify,
+                pid_file,
+                allow_unsafe,
+            };
+            spawn_startup_update_check(&settings);
+            run_http_server(&settings, &bind, &options).await?
+        }
+        Commands::Studio {
+            remote_graph,
+            workspace,
+            extra_roots,
+        } => {
+            spawn_startup_update_check(&settings);
+            run_studio(
+                &settings,
+                remote_graph.as_deref(),
+                workspace.as_deref(),
+                &extra_roots,
+            )?
+        }
+        Commands::Graph {
+            workspace,
+            extra_roots,
+            command: GraphCommand::Open { node_id },
+        } => run_graph_open_command(&settings, &node_id, workspace.as_deref(), &extra_roots)?,
+        Commands::Graph {
+            workspace,
+            extra_roots,
+            command: GraphCommand::Describe { subsystem },
+        } => {
+            run_graph_describe_command(&settings, &subsystem, workspace.as_deref(), &extra_roots)
+                .await?
+        }
+        Commands::Graph {
+            workspace,
+            extra_roots,
+            command: GraphCommand::Check { rules },
+        } => run_graph_check_command(
+            &settings,
+            rules.as_deref(),
+            workspace.as_deref(),
+            &extra_roots,
+        )?,
+        Commands::Digest { days, dry_run } => run_digest_command(&settings, days, dry_run).await?,
+        Commands::History {
+            command: HistoryCommand::Search { query, limit, json },
+        } => run_history_search_command(&settings, &query, limit, json)?,
+        Commands::Audit {
+            command: AuditCommand::Verify { file, json },
+        } => run_audit_verify_command(&settings, file.as_deref(), json)?,
+        Commands::Tools {
+            command: ToolsCommand::List { json },
+        } => run_tools_list_command(json)?,
+        Commands::Capabilities { json } => run_capabilities_command(&settings, json)?,
+        Commands::Update {
+            command: UpdateCommand::Check { json },
+        } => run_update_check_command(&settings, json).await?,
     }
 
     Ok(())
@@ -111,9 +501,9 @@ fn init_tracing(mode: LogMode) -> Result<()> {
             .with_context(|| format!("failed to parse MJOLNE_FILE_LOG `{value}`"))?,
         Err(_) => EnvFilter::new("info,mjolne_vibes=debug"),
     };
+    let redaction = LogRedaction::from_env()?;
 
-    let log_dir = std::env::var("MJOLNE_LOG_DIR").unwrap_or_else(|_| "logs".to_owned());
-    let file_appender = tracing_appender::rolling::daily(log_dir, "mjolne_vibes.log");
+    let file_appender = tracing_appender::rolling::daily(log_dir_from_env(), "mjolne_vibes.log");
     let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
     let _ = FILE_LOG_GUARD.set(guard);
 
@@ -125,6 +515,7 @@ fn init_tracing(mode: LogMode) -> Result<()> {
     let file_layer = fmt::layer()
         .with_ansi(false)
         .with_target(true)
+        .fmt_fields(redaction.file_log_fields())
         .with_writer(file_writer)
         .with_filter(file_filter);
 
@@ -137,33 +528,69 @@ fn init_tracing(mode: LogMode) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use clap::Parser;
 
-    use super::{Cli, Commands, LogMode};
+    use super::{
+        AuditCommand, BenchMode, Cli, Commands, GraphCommand, HistoryCommand, LogMode, ToolsCommand,
+    };
 
     #[test]
     fn repl_defaults_to_quiet_mode() {
         let cli = Cli::try_parse_from(["mjolne_vibes", "repl"]).expect("parse should succeed");
         match cli.command {
-            Commands::Repl { verbose } => assert!(!verbose),
+            Commands::Repl {
+                verbose,
+                session_file,
+                user,
+            } => {
+                assert!(!verbose);
+                assert_eq!(session_file, None);
+                assert_eq!(user, None);
+            }
             _ => panic!("expected repl command"),
         }
         assert_eq!(
-            LogMode::from_command(&Commands::Repl { verbose: false }),
+            LogMode::from_command(&Commands::Repl {
+                verbose: false,
+                session_file: None,
+                user: None,
+            }),
             LogMode::ReplQuiet
         );
     }
 
     #[test]
     fn repl_verbose_flag_enables_verbose_mode() {
-        let cli = Cli::try_parse_from(["mjolne_vibes", "repl", "--verbose"])
-            .expect("parse should succeed");
+        let cli = Cli::try_parse_from([
+            "mjolne_vibes",
+            "repl",
+            "--verbose",
+            "--session-file",
+            "investigation.json",
+            "--user",
+            "ada",
+        ])
+        .expect("parse should succeed");
         match cli.command {
-            Commands::Repl { verbose } => assert!(verbose),
+            Commands::Repl {
+                verbose,
+                session_file,
+                user,
+            } => {
+                assert!(verbose);
+                assert_eq!(session_file, Some(PathBuf::from("investigation.json")));
+                assert_eq!(user.as_deref(), Some("ada"));
+            }
             _ => panic!("expected repl command"),
         }
         assert_eq!(
-            LogMode::from_command(&Commands::Repl { verbose: true }),
+            LogMode::from_command(&Commands::Repl {
+                verbose: true,
+                session_file: None,
+                user: None,
+            }),
             LogMode::ReplVerbose
         );
     }
@@ -172,7 +599,50 @@ mod tests {
     fn eval_command_uses_default_cases_path() {
         let cli = Cli::try_parse_from(["mjolne_vibes", "eval"]).expect("parse should succeed");
         match cli.command {
-            Commands::Eval { cases } => assert_eq!(cases, super::DEFAULT_EVAL_CASES_PATH),
+            Commands::Eval {
+                cases,
+                watch,
+                preflight,
+                concurrency,
+                summary,
+                baseline,
+            } => {
+                assert_eq!(cases, super::DEFAULT_EVAL_CASES_PATH);
+                assert!(!watch);
+                assert!(!preflight);
+                assert_eq!(concurrency, 1);
+                assert_eq!(
+                    summary,
+                    super::PathBuf::from(super::DEFAULT_EVAL_SUMMARY_PATH)
+                );
+                assert_eq!(baseline, None);
+            }
+            _ => panic!("expected eval command"),
+        }
+    }
+
+    #[test]
+    fn eval_command_supports_watch_and_preflight_flags() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "eval", "--watch", "--preflight"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Eval {
+                watch, preflight, ..
+            } => assert!(watch && preflight),
+            _ => panic!("expected eval command"),
+        }
+        let cli = Cli::try_parse_from(["mjolne_vibes", "eval", "--concurrency", "4"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Eval { concurrency, .. } => assert_eq!(concurrency, 4),
+            _ => panic!("expected eval command"),
+        }
+        let cli = Cli::try_parse_from(["mjolne_vibes", "eval", "--baseline", "main.json"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Eval { baseline, .. } => {
+                assert_eq!(baseline, Some(super::PathBuf::from("main.json")))
+            }
             _ => panic!("expected eval command"),
         }
     }
@@ -182,19 +652,319 @@ mod tests {
         let cli = Cli::try_parse_from(["mjolne_vibes", "chat", "hello", "--json"])
             .expect("parse should succeed");
         match cli.command {
-            Commands::Chat { message, json } => {
+            Commands::Chat {
+                message,
+                json,
+                trace_html,
+                full,
+                resume_from,
+                model,
+                user,
+            } => {
                 assert_eq!(message, "hello");
                 assert!(json);
+                assert_eq!(trace_html, None);
+                assert!(!full);
+                assert_eq!(resume_from, None);
+                assert_eq!(model, None);
+                assert_eq!(user, None);
             }
             _ => panic!("expected chat command"),
         }
     }
 
+    #[test]
+    fn chat_full_flag_requires_json() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "chat", "hello", "--json", "--full"])
+            .expect("parse should succeed");
+        assert!(matches!(cli.command, Commands::Chat { full: true, .. }));
+        assert!(Cli::try_parse_from(["mjolne_vibes", "chat", "hello", "--full"]).is_err());
+    }
+
+    #[test]
+    fn chat_turn_flags_and_history_search_parse() {
+        let cli = Cli::try_parse_from([
+            "mjolne_vibes",
+            "chat",
+            "and then?",
+            "--resume-from",
+            "19a2b-3f1a-1",
+            "--model",
+            "llama3",
+            "--user",
+            "ada@example.com",
+        ])
+        .expect("parse should succeed");
+        match cli.command {
+            Commands::Chat {
+                resume_from,
+                model,
+                user,
+                ..
+            } => {
+                assert_eq!(resume_from.as_deref(), Some("19a2b-3f1a-1"));
+                assert_eq!(model.as_deref(), Some("llama3"));
+                assert_eq!(user.as_deref(), Some("ada@example.com"));
+            }
+            _ => panic!("expected chat command"),
+        }
+        assert!(Cli::try_parse_from(["mjolne_vibes", "chat", "hello", "--user", "a b"]).is_err());
+
+        let cli = Cli::try_parse_from(["mjolne_vibes", "history", "search", "tokio migration"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::History {
+                command: HistoryCommand::Search { query, limit, json },
+            } => {
+                assert_eq!(query, "tokio migration");
+                assert_eq!(limit, super::DEFAULT_HISTORY_SEARCH_LIMIT);
+                assert!(!json);
+            }
+            _ => panic!("expected history search command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["mjolne_vibes", "audit", "verify", "--file", "ledger.jsonl"])
+                .expect("parse should succeed");
+        match cli.command {
+            Commands::Audit {
+                command: AuditCommand::Verify { file, json },
+            } => {
+                assert_eq!(file, Some(PathBuf::from("ledger.jsonl")));
+                assert!(!json);
+            }
+            _ => panic!("expected audit verify command"),
+        }
+    }
+
+    #[test]
+    fn chat_command_accepts_trace_html_path() {
+        let cli =
+            Cli::try_parse_from(["mjolne_vibes", "chat", "hello", "--trace-html", "out.html"])
+                .expect("parse should succeed");
+        match cli.command {
+            Commands::Chat { trace_html, .. } => {
+                assert_eq!(trace_html, Some(std::path::PathBuf::from("out.html")))
+            }
+            _ => panic!("expected chat command"),
+        }
+    }
+
+    #[test]
+    fn bench_command_collects_repeated_models() {
+        let cli = Cli::try_parse_from([
+            "mjolne_vibes",
+            "bench",
+            "--model",
+            "llama3",
+            "--model",
+            "qwen2.5:7b",
+        ])
+        .expect("parse should succeed");
+        match cli.command {
+            Commands::Bench {
+                requests,
+                models,
+                mode,
+            } => {
+                assert_eq!(requests, super::DEFAULT_BENCH_REQUESTS);
+                assert_eq!(models, vec!["llama3", "qwen2.5:7b"]);
+                assert!(mode.is_none());
+            }
+            _ => panic!("expected bench command"),
+        }
+    }
+
+    #[test]
+    fn bench_serve_parses_load_test_options() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "bench", "serve", "--concurrency", "4"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Bench {
+                mode:
+                    Some(BenchMode::Serve {
+                        requests,
+                        concurrency,
+                        mock_latency_ms,
+                    }),
+                ..
+            } => {
+                assert_eq!(requests, super::DEFAULT_LOAD_TEST_REQUESTS);
+                assert_eq!(concurrency, 4);
+                assert_eq!(mock_latency_ms, super::DEFAULT_MOCK_LATENCY_MS);
+            }
+            _ => panic!("expected bench serve command"),
+        }
+    }
+
+    #[test]
+    fn graph_open_parses_node_id() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "graph", "open", "module:crate::agent"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Graph {
+                command: GraphCommand::Open { node_id },
+                ..
+            } => assert_eq!(node_id, "module:crate::agent"),
+            _ => panic!("expected graph open command"),
+        }
+    }
+
+    #[test]
+    fn graph_describe_parses_subsystem() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "graph", "describe", "studio"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Graph {
+                workspace,
+                command: GraphCommand::Describe { subsystem },
+                ..
+            } => {
+                assert_eq!(subsystem, "studio");
+                assert_eq!(workspace, None);
+            }
+            _ => panic!("expected graph describe command"),
+        }
+    }
+
+    #[test]
+    fn graph_check_parses_optional_rules_path() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "graph", "check", "--rules", "rules.json"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Graph {
+                command: GraphCommand::Check { rules },
+                ..
+            } => assert_eq!(rules, Some(PathBuf::from("rules.json"))),
+            _ => panic!("expected graph check command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["mjolne_vibes", "graph", "check"]).expect("parse should succeed");
+        assert!(matches!(
+            cli.command,
+            Commands::Graph {
+                command: GraphCommand::Check { rules: None },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn graph_workspace_flag_is_accepted_before_or_after_the_subcommand() {
+        for args in [
+            [
+                "mjolne_vibes",
+                "graph",
+                "--workspace",
+                "../other",
+                "describe",
+                "agent",
+            ],
+            [
+                "mjolne_vibes",
+                "graph",
+                "describe",
+                "agent",
+                "--workspace",
+                "../other",
+            ],
+        ] {
+            let cli = Cli::try_parse_from(args).expect("parse should succeed");
+            match cli.command {
+                Commands::Graph { workspace, .. } => {
+                    assert_eq!(workspace, Some(PathBuf::from("../other")))
+                }
+                _ => panic!("expected graph command"),
+            }
+        }
+    }
+
+    #[test]
+    fn digest_command_defaults_to_one_day() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "digest"]).expect("parse should succeed");
+        match cli.command {
+            Commands::Digest { days, dry_run } => {
+                assert_eq!(days, super::DEFAULT_DIGEST_DAYS);
+                assert!(!dry_run);
+            }
+            _ => panic!("expected digest command"),
+        }
+
+        let cli = Cli::try_parse_from(["mjolne_vibes", "digest", "--days", "7", "--dry-run"])
+            .expect("parse should succeed");
+        assert!(matches!(
+            cli.command,
+            Commands::Digest {
+                days: 7,
+                dry_run: true
+            }
+        ));
+    }
+
+    #[test]
+    fn tools_list_supports_json_flag() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "tools", "list", "--json"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Tools {
+                command: ToolsCommand::List { json },
+            } => assert!(json),
+            _ => panic!("expected tools list command"),
+        }
+    }
+
+    #[test]
+    fn capabilities_supports_json_flag() {
+        let cli = Cli::try_parse_from(["mjolne_vibes", "capabilities", "--json"])
+            .expect("parse should succeed");
+        match cli.command {
+            Commands::Capabilities { json } => assert!(json),
+            _ => panic!("expected capabilities command"),
+        }
+    }
+
     #[test]
     fn serve_command_uses_default_bind_address() {
         let cli = Cli::try_parse_from(["mjolne_vibes", "serve"]).expect("parse should succeed");
         match cli.command {
-            Commands::Serve { bind } => assert_eq!(bind, "127.0.0.1:8080"),
+            Commands::Serve {
+                bind,
+                daemon_ready_notify,
+                pid_file,
+                allow_unsafe,
+            } => {
+                assert_eq!(bind, "127.0.0.1:8080");
+                assert!(!daemon_ready_notify);
+                assert_eq!(pid_file, None);
+                assert!(!allow_unsafe);
+            }
+            _ => panic!("expected serve command"),
+        }
+    }
+
+    #[test]
+    fn serve_command_supports_daemon_flags() {
+        let cli = Cli::try_parse_from([
+            "mjolne_vibes",
+            "serve",
+            "--daemon-ready-notify",
+            "--pid-file",
+            "/run/mjolne/serve.pid",
+            "--allow-unsafe",
+        ])
+        .expect("parse should succeed");
+        match cli.command {
+            Commands::Serve {
+                daemon_ready_notify,
+                pid_file,
+                allow_unsafe,
+                ..
+            } => {
+                assert!(daemon_ready_notify);
+                assert_eq!(pid_file, Some(PathBuf::from("/run/mjolne/serve.pid")));
+                assert!(allow_unsafe);
+            }
             _ => panic!("expected serve command"),
         }
     }
@@ -203,7 +973,44 @@ mod tests {
     fn studio_command_is_available() {
         let cli = Cli::try_parse_from(["mjolne_vibes", "studio"]).expect("parse should succeed");
         match cli.command {
-            Commands::Studio => {}
+            Commands::Studio {
+                remote_graph,
+                workspace,
+                extra_roots,
+            } => {
+                assert_eq!(remote_graph, None);
+                assert!(extra_roots.is_empty());
+                assert_eq!(workspace, None);
+            }
+            _ => panic!("expected studio command"),
+        }
+    }
+
+    #[test]
+    fn studio_command_accepts_remote_graph_url() {
+        let cli = Cli::try_parse_from([
+            "mjolne_vibes",
+            "studio",
+            "--remote-graph",
+            "http://devbox:8080",
+            "--workspace",
+            "/srv/project",
+            "--extra-root",
+            "shared=../shared-lib",
+            "--extra-root",
+            "../protocol",
+        ])
+        .expect("parse should succeed");
+        match cli.command {
+            Commands::Studio {
+                remote_graph,
+                workspace,
+                extra_roots,
+            } => {
+                assert_eq!(extra_roots, ["shared=../shared-lib", "../protocol"]);
+                assert_eq!(remote_graph.as_deref(), Some("http://devbox:8080"));
+                assert_eq!(workspace, Some(PathBuf::from("/srv/project")));
+            }
             _ => panic!("expected studio command"),
         }
     }
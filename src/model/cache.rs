@@ -0,0 +1,105 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+/// Dev-mode store of raw provider response bodies, enabled by `MODEL_CACHE_DIR`.
+///
+/// Entries are keyed by a hash of the provider, URL, and full request payload, and keep
+/// the payload so a hash collision is treated as a miss instead of a wrong answer.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    provider: String,
+    url: String,
+    request: Value,
+    response: String,
+}
+
+impl ResponseCache {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the cached response body for this exact request, if any.
+    pub fn get(&self, provider: &str, url: &str, request: &Value) -> Option<String> {
+        let raw = fs::read_to_string(self.entry_path(provider, url, request)).ok()?;
+        let entry = serde_json::from_str::<CacheEntry>(&raw).ok()?;
+        (entry.provider == provider && entry.url == url && entry.request == *request)
+            .then_some(entry.response)
+    }
+
+    /// Stores a response body; failures are logged and otherwise ignored.
+    pub fn put(&self, provider: &str, url: &str, request: &Value, response: &str) {
+        let path = self.entry_path(provider, url, request);
+        let entry = CacheEntry {
+            provider: provider.to_owned(),
+            url: url.to_owned(),
+            request: request.clone(),
+            response: response.to_owned(),
+        };
+        let result = serde_json::to_vec_pretty(&entry)
+            .map_err(io::Error::other)
+            .and_then(|bytes| fs::write(&path, bytes));
+        if let Err(error) = result {
+            warn!(
+                path = %path.display(),
+                error = %error,
+                "failed to write model response cache entry"
+            );
+        }
+    }
+
+    fn entry_path(&self, provider: &str, url: &str, request: &Value) -> PathBuf {
+        let key = format!("{provider}\n{url}\n{request}");
+        self.dir
+            .join(format!("{:016x}.json", fnv1a_64(key.as_bytes())))
+    }
+}
+
+/// FNV-1a; stable across Rust releases, unlike `DefaultHasher`.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ResponseCache;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn cache_returns_responses_only_for_identical_requests() {
+        let dir = temp_path("model_cache");
+        let cache = ResponseCache::open(&dir).expect("cache dir should be created");
+        let request =
+            json!({ "model": "llama3", "messages": [{ "role": "user", "content": "hi" }] });
+        let other =
+            json!({ "model": "llama3", "messages": [{ "role": "user", "content": "bye" }] });
+
+        assert_eq!(cache.get("ollama", "http://x/api/chat", &request), None);
+        cache.put("ollama", "http://x/api/chat", &request, r#"{"done":true}"#);
+
+        assert_eq!(
+            cache
+                .get("ollama", "http://x/api/chat", &request)
+                .as_deref(),
+            Some(r#"{"done":true}"#)
+        );
+        assert_eq!(cache.get("ollama", "http://x/api/chat", &other), None);
+        assert_eq!(cache.get("openai", "http://x/api/chat", &request), None);
+        remove_dir_if_exists(&dir);
+    }
+}
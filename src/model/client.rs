@@ -1,15 +1,25 @@
+use std::future::Future;
 use std::time::Duration;
 
 use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio::time::{sleep, timeout};
+use tokio::time::{Instant, sleep, sleep_until, timeout};
 use tracing::{debug, warn};
 
 use crate::config::{AgentSettings, ModelProvider};
+use crate::http::{HttpError, HttpRequest, HttpResponse, SharedHttpClient};
+use crate::model::cache::ResponseCache;
+use crate::model::debug_stream::{DebugStreamContext, DebugStreamWriter};
 
 const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+const GEMINI_API_KEY_HEADER: &str = "x-goog-api-key";
+/// Schema keywords outside the OpenAPI subset Gemini function declarations accept.
+const GEMINI_UNSUPPORTED_SCHEMA_KEYS: [&str; 3] = ["additionalProperties", "$schema", "title"];
 const RETRY_BASE_DELAY_MS: u64 = 250;
+/// Longest `Retry-After` or rate-limit reset honored, so a bogus header cannot stall a run.
+const MAX_RATE_LIMIT_PAUSE: Duration = Duration::from_secs(60);
 
 #[derive(Debug, thiserror::Error)]
 pub enum ModelClientError {
@@ -17,10 +27,15 @@ pub enum ModelClientError {
     Timeout { timeout_ms: u64 },
 
     #[error("HTTP request failed: {0}")]
-    Transport(#[from] reqwest::Error),
+    Transport(#[from] HttpError),
 
     #[error("provider returned HTTP {status}: {body}")]
-    HttpStatus { status: StatusCode, body: String },
+    HttpStatus {
+        status: StatusCode,
+        body: String,
+        /// The provider's `Retry-After`, used instead of the backoff delay.
+        retry_after: Option<Duration>,
+    },
 
     #[error("response missing field: {field}")]
     MissingField { field: &'static str },
@@ -36,18 +51,24 @@ impl ModelClientError {
     pub fn is_retryable(&self) -> bool {
         match self {
             Self::Timeout { .. } => true,
-            Self::Transport(error) => {
-                error.is_timeout() || error.is_connect() || error.is_request()
-            }
+            Self::Transport(error) => error.is_retryable(),
             Self::HttpStatus { status, .. } => {
                 *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
             }
             Self::MissingField { .. } | Self::ResponseFormat(_) | Self::Configuration(_) => false,
         }
     }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::HttpStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum MessageRole {
     System,
     User,
@@ -73,12 +94,15 @@ pub struct ModelToolCall {
     pub arguments: Value,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelMessage {
     pub role: MessageRole,
     pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tool_calls: Vec<ModelToolCall>,
 }
 
@@ -145,6 +169,15 @@ pub struct ChatRequest {
     pub model: String,
     pub messages: Vec<ModelMessage>,
     pub tools: Vec<ModelToolDefinition>,
+    /// Provider default when `None`.
+    pub temperature: Option<f32>,
+}
+
+/// Per-call overrides of the configured request parameters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChatCallOptions {
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
 }
 
 impl ChatRequest {
@@ -157,6 +190,7 @@ impl ChatRequest {
             model,
             messages,
             tools,
+            temperature: None,
         }
     }
 
@@ -168,6 +202,7 @@ impl ChatRequest {
                 ModelMessage::user(user_prompt),
             ],
             tools: Vec::new(),
+            temperature: None,
         }
     }
 }
@@ -183,17 +218,66 @@ pub enum ChatResponse {
     },
 }
 
+/// A chat response with the provider-reported completion token count, when available.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasuredChatResponse {
+    pub response: ChatResponse,
+    pub output_tokens: Option<u64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ModelClient {
-    http_client: reqwest::Client,
+    http_client: SharedHttpClient,
     settings: AgentSettings,
+    debug_stream: Option<DebugStreamWriter>,
+    response_cache: Option<ResponseCache>,
+}
+
+/// Debug-stream position of one provider request attempt.
+#[derive(Debug, Clone, Copy)]
+struct DebugStreamTap {
+    context: DebugStreamContext,
+    attempt: u32,
 }
 
 impl ModelClient {
     pub fn new(settings: AgentSettings) -> Self {
+        let http_client = SharedHttpClient::from_settings(&settings);
+        Self::with_http_client(settings, http_client)
+    }
+
+    /// Like `new`, sending provider requests through `http_client`.
+    pub fn with_http_client(settings: AgentSettings, http_client: SharedHttpClient) -> Self {
+        let debug_stream = settings
+            .model_debug_stream_file
+            .as_deref()
+            .and_then(|path| {
+                DebugStreamWriter::open(path)
+                .map_err(|error| {
+                    warn!(
+                        path,
+                        error = %error,
+                        "failed to open model debug stream file; raw deltas will not be recorded"
+                    );
+                })
+                .ok()
+            });
+        let response_cache = settings.model_cache_dir.as_deref().and_then(|dir| {
+            ResponseCache::open(dir)
+                .map_err(|error| {
+                    warn!(
+                        dir,
+                        error = %error,
+                        "failed to open model response cache; responses will not be cached"
+                    );
+                })
+                .ok()
+        });
         Self {
-            http_client: reqwest::Client::new(),
+            http_client,
             settings,
+            debug_stream,
+            response_cache,
         }
     }
 
@@ -202,30 +286,90 @@ impl ModelClient {
         system_prompt: &str,
         user_prompt: &str,
     ) -> Result<ChatResponse, ModelClientError> {
+        self.chat_measured(system_prompt, user_prompt)
+            .await
+            .map(|measured| measured.response)
+    }
+
+    /// Like `chat`, also returning the completion token count reported by the provider.
+    pub async fn chat_measured(
+        &self,
+        system_prompt: &str,
+        user_prompt: &str,
+    ) -> Result<MeasuredChatResponse, ModelClientError> {
         let request = ChatRequest::from_prompts(&self.settings.model, system_prompt, user_prompt);
-        self.chat_request(&request).await
+        self.chat_request(&request, None).await
     }
 
     pub async fn chat_with_messages(
         &self,
         messages: &[ModelMessage],
         tools: &[ModelToolDefinition],
+        options: &ChatCallOptions,
     ) -> Result<ChatResponse, ModelClientError> {
-        let request = ChatRequest::new(
-            self.settings.model.clone(),
-            messages.to_vec(),
-            tools.to_vec(),
-        );
-        self.chat_request(&request).await
+        let request = self.messages_request(messages, tools, options);
+        self.chat_request(&request, None)
+            .await
+            .map(|measured| measured.response)
+    }
+
+    /// Like `chat_with_messages`, tagging raw output in the debug stream with turn and step.
+    pub async fn chat_with_messages_in_step(
+        &self,
+        messages: &[ModelMessage],
+        tools: &[ModelToolDefinition],
+        options: &ChatCallOptions,
+        context: DebugStreamContext,
+    ) -> Result<ChatResponse, ModelClientError> {
+        let request = self.messages_request(messages, tools, options);
+        self.chat_request(&request, Some(context))
+            .await
+            .map(|measured| measured.response)
+    }
+
+    fn messages_request(
+        &self,
+        messages: &[ModelMessage],
+        tools: &[ModelToolDefinition],
+        options: &ChatCallOptions,
+    ) -> ChatRequest {
+        ChatRequest {
+            temperature: options.temperature,
+            ..ChatRequest::new(
+                options
+                    .model
+                    .clone()
+                    .unwrap_or_else(|| self.settings.model.clone()),
+                messages.to_vec(),
+                tools.to_vec(),
+            )
+        }
+    }
+
+    async fn chat_request(
+        &self,
+        request: &ChatRequest,
+        context: Option<DebugStreamContext>,
+    ) -> Result<MeasuredChatResponse, ModelClientError> {
+        self.with_retries(|attempt| {
+            let tap = context.map(|context| DebugStreamTap { context, attempt });
+            self.chat_once(request, tap)
+        })
+        .await
     }
 
-    async fn chat_request(&self, request: &ChatRequest) -> Result<ChatResponse, ModelClientError> {
+    /// Runs `call` up to `MODEL_MAX_RETRIES + 1` times while it fails with a retryable
+    /// error, waiting out the backoff or the provider's `Retry-After` in between.
+    async fn with_retries<T, F, Fut>(&self, mut call: F) -> Result<T, ModelClientError>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, ModelClientError>>,
+    {
         let total_attempts = self.settings.model_max_retries.saturating_add(1);
         let mut attempt: u32 = 1;
 
         loop {
-            let result = self.chat_once(request).await;
-            match result {
+            match call(attempt).await {
                 Ok(response) => return Ok(response),
                 Err(error) => {
                     let should_retry = attempt < total_attempts && error.is_retryable();
@@ -233,7 +377,9 @@ impl ModelClient {
                         return Err(error);
                     }
 
-                    let delay_ms = retry_delay_ms(attempt);
+                    let delay_ms = error
+                        .retry_after()
+                        .map_or_else(|| retry_delay_ms(attempt), |delay| delay.as_millis() as u64);
                     warn!(
                         attempt,
                         total_attempts,
@@ -249,9 +395,13 @@ impl ModelClient {
         }
     }
 
-    async fn chat_once(&self, request: &ChatRequest) -> Result<ChatResponse, ModelClientError> {
+    async fn chat_once(
+        &self,
+        request: &ChatRequest,
+        tap: Option<DebugStreamTap>,
+    ) -> Result<MeasuredChatResponse, ModelClientError> {
         let timeout_duration = Duration::from_millis(self.settings.model_timeout_ms);
-        match timeout(timeout_duration, self.chat_by_provider(request)).await {
+        match timeout(timeout_duration, self.chat_by_provider(request, tap)).await {
             Ok(result) => result,
             Err(_) => Err(ModelClientError::Timeout {
                 timeout_ms: self.settings.model_timeout_ms,
@@ -262,14 +412,22 @@ impl ModelClient {
     async fn chat_by_provider(
         &self,
         request: &ChatRequest,
-    ) -> Result<ChatResponse, ModelClientError> {
+        tap: Option<DebugStreamTap>,
+    ) -> Result<MeasuredChatResponse, ModelClientError> {
         match self.settings.model_provider {
-            ModelProvider::Ollama => self.chat_ollama(request).await,
-            ModelProvider::OpenAi => self.chat_openai(request).await,
+            ModelProvider::Ollama => self.chat_ollama(request, tap).await,
+            ModelProvider::OpenAi | ModelProvider::Compatible => {
+                self.chat_openai(request, tap).await
+            }
+            ModelProvider::Gemini => self.chat_gemini(request, tap).await,
         }
     }
 
-    async fn chat_ollama(&self, request: &ChatRequest) -> Result<ChatResponse, ModelClientError> {
+    async fn chat_ollama(
+        &self,
+        request: &ChatRequest,
+        tap: Option<DebugStreamTap>,
+    ) -> Result<MeasuredChatResponse, ModelClientError> {
         let url = format!(
             "{}/api/chat",
             self.settings.ollama_base_url.trim_end_matches('/')
@@ -284,12 +442,20 @@ impl ModelClient {
             "sending chat request to ollama"
         );
 
-        let response = self.post_json(&url, None, &provider_request).await?;
-        let payload: OllamaChatResponse = response.json().await?;
+        let payload: OllamaChatResponse = self
+            .post_chat_json(
+                &url,
+                ProviderAuth::None,
+                &provider_request,
+                tap,
+                |payload: &OllamaChatResponse| payload.error.is_none(),
+            )
+            .await?;
         if let Some(error_message) = payload.error {
             return Err(ModelClientError::ResponseFormat(error_message));
         }
 
+        let output_tokens = payload.eval_count;
         let message = payload
             .message
             .ok_or(ModelClientError::MissingField { field: "message" })?;
@@ -297,9 +463,12 @@ impl ModelClient {
         if !message.tool_calls.is_empty() {
             let calls = parse_ollama_tool_calls(message.tool_calls)?;
             let assistant_content = normalize_optional_text(message.content);
-            return Ok(ChatResponse::ToolCalls {
-                assistant_content,
-                calls,
+            return Ok(MeasuredChatResponse {
+                response: ChatResponse::ToolCalls {
+                    assistant_content,
+                    calls,
+                },
+                output_tokens,
             });
         }
 
@@ -307,29 +476,34 @@ impl ModelClient {
             normalize_optional_text(message.content).ok_or(ModelClientError::MissingField {
                 field: "message.content",
             })?;
-        Ok(ChatResponse::FinalText { text })
+        Ok(MeasuredChatResponse {
+            response: ChatResponse::FinalText { text },
+            output_tokens,
+        })
     }
 
-    async fn chat_openai(&self, request: &ChatRequest) -> Result<ChatResponse, ModelClientError> {
-        let api_key = self.settings.openai_api_key.as_deref().ok_or_else(|| {
-            ModelClientError::Configuration("OPENAI_API_KEY is required".to_owned())
-        })?;
-
-        let url = format!("{OPENAI_BASE_URL}/chat/completions");
+    async fn chat_openai(
+        &self,
+        request: &ChatRequest,
+        tap: Option<DebugStreamTap>,
+    ) -> Result<MeasuredChatResponse, ModelClientError> {
+        let (base_url, auth) = self.openai_endpoint()?;
+        let url = format!("{base_url}/chat/completions");
         let provider_request = OpenAiChatRequest::from_common_request(request);
 
         debug!(
             url = %url,
+            provider = %self.settings.model_provider,
             model = %request.model,
             message_count = request.messages.len(),
             tool_count = request.tools.len(),
-            "sending chat request to openai"
+            "sending chat request in openai format"
         );
 
-        let response = self
-            .post_json(&url, Some(api_key), &provider_request)
+        let payload: OpenAiChatResponse = self
+            .post_chat_json(&url, auth, &provider_request, tap, |_| true)
             .await?;
-        let payload: OpenAiChatResponse = response.json().await?;
+        let output_tokens = payload.usage.and_then(|usage| usage.completion_tokens);
         let choice = payload
             .choices
             .first()
@@ -346,9 +520,12 @@ impl ModelClient {
                 .and_then(extract_openai_content_text)
                 .and_then(normalize_text);
 
-            return Ok(ChatResponse::ToolCalls {
-                assistant_content,
-                calls,
+            return Ok(MeasuredChatResponse {
+                response: ChatResponse::ToolCalls {
+                    assistant_content,
+                    calls,
+                },
+                output_tokens,
             });
         }
 
@@ -364,43 +541,353 @@ impl ModelClient {
                 )
             })?;
 
-        Ok(ChatResponse::FinalText { text: content })
+        Ok(MeasuredChatResponse {
+            response: ChatResponse::FinalText { text: content },
+            output_tokens,
+        })
+    }
+
+    /// Base URL and credentials for the OpenAI wire format: OpenAI itself, or the server at
+    /// `OPENAI_COMPAT_BASE_URL`, which only gets a bearer token when a key is configured.
+    fn openai_endpoint(&self) -> Result<(&str, ProviderAuth<'_>), ModelClientError> {
+        if self.settings.model_provider == ModelProvider::Compatible {
+            let base_url = self
+                .settings
+                .openai_compat_base_url
+                .as_deref()
+                .ok_or_else(|| {
+                    ModelClientError::Configuration("OPENAI_COMPAT_BASE_URL is required".to_owned())
+                })?;
+            let auth = self
+                .settings
+                .openai_compat_api_key
+                .as_deref()
+                .map_or(ProviderAuth::None, ProviderAuth::Bearer);
+            return Ok((base_url.trim_end_matches('/'), auth));
+        }
+        let api_key = self.settings.openai_api_key.as_deref().ok_or_else(|| {
+            ModelClientError::Configuration("OPENAI_API_KEY is required".to_owned())
+        })?;
+        Ok((OPENAI_BASE_URL, ProviderAuth::Bearer(api_key)))
+    }
+
+    async fn chat_gemini(
+        &self,
+        request: &ChatRequest,
+        tap: Option<DebugStreamTap>,
+    ) -> Result<MeasuredChatResponse, ModelClientError> {
+        let api_key = self.gemini_api_key()?;
+        let url = format!("{}:generateContent", self.gemini_model_url(&request.model));
+        let provider_request = GeminiChatRequest::from_common_request(request);
+
+        debug!(
+            url = %url,
+            model = %request.model,
+            message_count = request.messages.len(),
+            tool_count = request.tools.len(),
+            "sending chat request to gemini"
+        );
+
+        let payload: GeminiChatResponse = self
+            .post_chat_json(
+                &url,
+                ProviderAuth::GeminiApiKey(api_key),
+                &provider_request,
+                tap,
+                |payload: &GeminiChatResponse| !payload.candidates.is_empty(),
+            )
+            .await?;
+        parse_gemini_response(payload)
+    }
+
+    fn gemini_api_key(&self) -> Result<&str, ModelClientError> {
+        self.settings
+            .gemini_api_key
+            .as_deref()
+            .ok_or_else(|| ModelClientError::Configuration("GEMINI_API_KEY is required".to_owned()))
+    }
+
+    /// `{GEMINI_BASE_URL}/models/{model}`; a `models/` prefix on the name is accepted.
+    fn gemini_model_url(&self, model: &str) -> String {
+        format!(
+            "{}/models/{}",
+            self.settings.gemini_base_url.trim_end_matches('/'),
+            model.trim_start_matches("models/")
+        )
+    }
+
+    /// Names of the models the provider reports as available, within `MODEL_TIMEOUT_MS`.
+    pub async fn list_models(&self) -> Result<Vec<String>, ModelClientError> {
+        let timeout_duration = Duration::from_millis(self.settings.model_timeout_ms);
+        match timeout(timeout_duration, self.list_models_by_provider()).await {
+            Ok(result) => result,
+            Err(_) => Err(ModelClientError::Timeout {
+                timeout_ms: self.settings.model_timeout_ms,
+            }),
+        }
+    }
+
+    async fn list_models_by_provider(&self) -> Result<Vec<String>, ModelClientError> {
+        match self.settings.model_provider {
+            ModelProvider::Ollama => {
+                let url = format!(
+                    "{}/api/tags",
+                    self.settings.ollama_base_url.trim_end_matches('/')
+                );
+                let response = self.send(HttpRequest::get(url)).await?;
+                let payload: OllamaTagsResponse = decode_provider_body(&response.text().await?)?;
+                Ok(payload.models.into_iter().map(|model| model.name).collect())
+            }
+            ModelProvider::OpenAi | ModelProvider::Compatible => {
+                let (base_url, auth) = self.openai_endpoint()?;
+                let url = format!("{base_url}/models");
+                let response = self.send(auth.apply(HttpRequest::get(url))?).await?;
+                let payload: OpenAiModelsResponse = decode_provider_body(&response.text().await?)?;
+                Ok(payload.data.into_iter().map(|model| model.id).collect())
+            }
+            ModelProvider::Gemini => {
+                let api_key = self.gemini_api_key()?;
+                let url = format!(
+                    "{}/models?pageSize=1000",
+                    self.settings.gemini_base_url.trim_end_matches('/')
+                );
+                let request = ProviderAuth::GeminiApiKey(api_key).apply(HttpRequest::get(url))?;
+                let response = self.send(request).await?;
+                let payload: GeminiModelsResponse = decode_provider_body(&response.text().await?)?;
+                Ok(payload
+                    .models
+                    .into_iter()
+                    .map(|model| {
+                        model
+                            .name
+                            .strip_prefix("models/")
+                            .map(str::to_owned)
+                            .unwrap_or(model.name)
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Embeddings for `inputs`, in order, from `EMBEDDING_MODEL`. Inputs are sent in
+    /// requests of at most `MODEL_BATCH_SIZE`, paced by `MODEL_BATCH_REQUESTS_PER_MINUTE`
+    /// and the provider's rate-limit headers; each request retries like a chat call.
+    pub async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, ModelClientError> {
+        let batch_size = self.settings.model_batch_size.max(1) as usize;
+        let mut pacer = RequestPacer::new(self.settings.model_batch_requests_per_minute);
+        let mut embeddings = Vec::with_capacity(inputs.len());
+        for (batch_index, batch) in inputs.chunks(batch_size).enumerate() {
+            pacer.wait().await;
+            let (vectors, pause) = self.with_retries(|_| self.embed_once(batch)).await?;
+            if vectors.len() != batch.len() {
+                return Err(ModelClientError::ResponseFormat(format!(
+                    "provider returned {} embeddings for {} inputs",
+                    vectors.len(),
+                    batch.len()
+                )));
+            }
+            if let Some(pause) = pause {
+                debug!(
+                    batch_index,
+                    pause_ms = pause.as_millis() as u64,
+                    "provider request limit reached; pausing batch"
+                );
+                pacer.pause_for(pause);
+            }
+            embeddings.extend(vectors);
+        }
+        Ok(embeddings)
+    }
+
+    /// One embedding request within `MODEL_TIMEOUT_MS`, with the pause the provider's
+    /// rate-limit headers ask for before the next one.
+    async fn embed_once(
+        &self,
+        inputs: &[String],
+    ) -> Result<(Vec<Vec<f32>>, Option<Duration>), ModelClientError> {
+        let timeout_duration = Duration::from_millis(self.settings.model_timeout_ms);
+        match timeout(timeout_duration, self.embed_by_provider(inputs)).await {
+            Ok(result) => result,
+            Err(_) => Err(ModelClientError::Timeout {
+                timeout_ms: self.settings.model_timeout_ms,
+            }),
+        }
+    }
+
+    async fn embed_by_provider(
+        &self,
+        inputs: &[String],
+    ) -> Result<(Vec<Vec<f32>>, Option<Duration>), ModelClientError> {
+        let request = EmbeddingRequest {
+            model: &self.settings.embedding_model,
+            input: inputs,
+        };
+        debug!(
+            model = %request.model,
+            input_count = inputs.len(),
+            provider = %self.settings.model_provider,
+            "sending embedding request"
+        );
+        match self.settings.model_provider {
+            ModelProvider::Ollama => {
+                let url = format!(
+                    "{}/api/embed",
+                    self.settings.ollama_base_url.trim_end_matches('/')
+                );
+                let response = self.post_json(&url, ProviderAuth::None, &request).await?;
+                let payload: OllamaEmbedResponse = decode_provider_body(&response.text().await?)?;
+                Ok((payload.embeddings, None))
+            }
+            ModelProvider::OpenAi | ModelProvider::Compatible => {
+                let (base_url, auth) = self.openai_endpoint()?;
+                let url = format!("{base_url}/embeddings");
+                let response = self.post_json(&url, auth, &request).await?;
+                let pause = rate_limit_pause(response.headers());
+                let mut payload: OpenAiEmbeddingsResponse =
+                    decode_provider_body(&response.text().await?)?;
+                payload.data.sort_by_key(|entry| entry.index);
+                let vectors = payload
+                    .data
+                    .into_iter()
+                    .map(|entry| entry.embedding)
+                    .collect();
+                Ok((vectors, pause))
+            }
+            ModelProvider::Gemini => {
+                let api_key = self.gemini_api_key()?;
+                let url = format!(
+                    "{}:batchEmbedContents",
+                    self.gemini_model_url(request.model)
+                );
+                let body = GeminiBatchEmbedRequest::new(request.model, inputs);
+                let response = self
+                    .post_json(&url, ProviderAuth::GeminiApiKey(api_key), &body)
+                    .await?;
+                let payload: GeminiBatchEmbedResponse =
+                    decode_provider_body(&response.text().await?)?;
+                let vectors = payload
+                    .embeddings
+                    .into_iter()
+                    .map(|embedding| embedding.values)
+                    .collect();
+                Ok((vectors, None))
+            }
+        }
+    }
+
+    /// Posts a chat request and decodes the reply. With `MODEL_CACHE_DIR` set, an identical
+    /// earlier request is answered from disk, and replies passing `cacheable` are stored.
+    async fn post_chat_json<B: Serialize, T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        auth: ProviderAuth<'_>,
+        body: &B,
+        tap: Option<DebugStreamTap>,
+        cacheable: impl Fn(&T) -> bool,
+    ) -> Result<T, ModelClientError> {
+        let Some(cache) = &self.response_cache else {
+            let response = self.post_json(url, auth, body).await?;
+            return decode_provider_body(&self.read_body(response, tap).await?);
+        };
+
+        let provider = self.settings.model_provider.as_str();
+        let request = serde_json::to_value(body).map_err(|error| {
+            ModelClientError::ResponseFormat(format!("failed to encode provider request: {error}"))
+        })?;
+        if let Some(cached) = cache.get(provider, url, &request) {
+            debug!(url = %url, "serving chat response from model cache");
+            return decode_provider_body(&cached);
+        }
+
+        let response = self.post_json(url, auth, body).await?;
+        let raw = self.read_body(response, tap).await?;
+        let payload = decode_provider_body(&raw)?;
+        if cacheable(&payload) {
+            cache.put(provider, url, &request, &raw);
+        }
+        Ok(payload)
+    }
+
+    /// Reads the raw body and mirrors it to the debug stream.
+    async fn read_body(
+        &self,
+        response: HttpResponse,
+        tap: Option<DebugStreamTap>,
+    ) -> Result<String, ModelClientError> {
+        let body = response.text().await?;
+        if let (Some(writer), Some(tap)) = (&self.debug_stream, tap) {
+            writer.append_delta(
+                tap.context,
+                tap.attempt,
+                self.settings.model_provider.as_str(),
+                &body,
+            );
+        }
+        Ok(body)
     }
 
     async fn post_json<T: Serialize>(
         &self,
         url: &str,
-        bearer_token: Option<&str>,
+        auth: ProviderAuth<'_>,
         body: &T,
-    ) -> Result<reqwest::Response, ModelClientError> {
-        let mut request = self.http_client.post(url).json(body);
-        if let Some(token) = bearer_token {
-            request = request.bearer_auth(token);
-        }
+    ) -> Result<HttpResponse, ModelClientError> {
+        let request = auth.apply(HttpRequest::post_json(url, body)?)?;
+        self.send(request).await
+    }
 
-        let response = request.send().await?;
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, ModelClientError> {
+        let response = self.http_client.send(request).await?;
         ensure_success(response).await
     }
 }
 
+/// Credentials a provider request carries.
+#[derive(Debug, Clone, Copy)]
+enum ProviderAuth<'a> {
+    None,
+    Bearer(&'a str),
+    /// Gemini takes its key in `x-goog-api-key` rather than `Authorization`.
+    GeminiApiKey(&'a str),
+}
+
+impl ProviderAuth<'_> {
+    fn apply(self, request: HttpRequest) -> Result<HttpRequest, HttpError> {
+        match self {
+            Self::None => Ok(request),
+            Self::Bearer(token) => request.bearer_auth(token),
+            Self::GeminiApiKey(key) => request.api_key_header(GEMINI_API_KEY_HEADER, key),
+        }
+    }
+}
+
+fn decode_provider_body<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, ModelClientError> {
+    serde_json::from_str(body).map_err(|error| {
+        ModelClientError::ResponseFormat(format!("failed to decode provider response: {error}"))
+    })
+}
+
 fn retry_delay_ms(attempt: u32) -> u64 {
     let exponent = attempt.saturating_sub(1).min(5);
     RETRY_BASE_DELAY_MS.saturating_mul(1_u64 << exponent)
 }
 
-async fn ensure_success(
-    response: reqwest::Response,
-) -> Result<reqwest::Response, ModelClientError> {
+async fn ensure_success(response: HttpResponse) -> Result<HttpResponse, ModelClientError> {
     let status = response.status();
     if status.is_success() {
         return Ok(response);
     }
 
+    let retry_after = retry_after(response.headers());
     let body = response
         .text()
         .await
         .unwrap_or_else(|_| "<failed to read error response body>".to_owned());
-    Err(ModelClientError::HttpStatus { status, body })
+    Err(ModelClientError::HttpStatus {
+        status,
+        body,
+        retry_after,
+    })
 }
 
 fn normalize_text(content: String) -> Option<String> {
@@ -440,8 +927,14 @@ fn extract_openai_content_text(value: &serde_json::Value) -> Option<String> {
     }
 }
 
+/// Tool-call arguments as JSON. Missing, `null`, or blank arguments, which Gemini and some
+/// OpenAI-compatible servers send for tools without parameters, are an empty object.
 fn parse_tool_arguments(raw: Value, field: &str) -> Result<Value, ModelClientError> {
     match raw {
+        Value::Null => Ok(Value::Object(serde_json::Map::new())),
+        Value::String(arguments) if arguments.trim().is_empty() => {
+            Ok(Value::Object(serde_json::Map::new()))
+        }
         Value::String(arguments) => serde_json::from_str::<Value>(&arguments).map_err(|error| {
             ModelClientError::ResponseFormat(format!("failed to parse {field} as JSON: {error}"))
         }),
@@ -466,7 +959,9 @@ where
             let arguments = parse_tool_arguments(raw_arguments, arguments_field)?;
 
             Ok(ModelToolCall {
-                id: id.unwrap_or_else(|| format!("{default_id_prefix}{}", index + 1)),
+                id: id
+                    .filter(|id| !id.trim().is_empty())
+                    .unwrap_or_else(|| format!("{default_id_prefix}{}", index + 1)),
                 name,
                 arguments,
             })
@@ -496,11 +991,83 @@ fn parse_ollama_tool_calls(
     )
 }
 
+fn parse_gemini_tool_calls(
+    raw_calls: Vec<GeminiFunctionCall>,
+) -> Result<Vec<ModelToolCall>, ModelClientError> {
+    parse_provider_tool_calls(
+        raw_calls,
+        "candidates[0].content.parts[].functionCall.args",
+        "gemini-tool-call-",
+        |call| (call.id, call.name, call.args),
+    )
+}
+
+/// Text and function calls of the first candidate; a prompt Gemini blocked has none.
+fn parse_gemini_response(
+    payload: GeminiChatResponse,
+) -> Result<MeasuredChatResponse, ModelClientError> {
+    let output_tokens = payload
+        .usage_metadata
+        .and_then(|usage| usage.candidates_token_count);
+    let Some(candidate) = payload.candidates.into_iter().next() else {
+        if let Some(reason) = payload
+            .prompt_feedback
+            .and_then(|feedback| feedback.block_reason)
+        {
+            return Err(ModelClientError::ResponseFormat(format!(
+                "gemini blocked the prompt: {reason}"
+            )));
+        }
+        return Err(ModelClientError::MissingField {
+            field: "candidates[0]",
+        });
+    };
+
+    let mut text = String::new();
+    let mut function_calls = Vec::new();
+    for part in candidate
+        .content
+        .map(|content| content.parts)
+        .unwrap_or_default()
+    {
+        if part.thought {
+            continue;
+        }
+        if let Some(call) = part.function_call {
+            function_calls.push(call);
+        } else if let Some(part_text) = part.text {
+            text.push_str(&part_text);
+        }
+    }
+
+    if !function_calls.is_empty() {
+        return Ok(MeasuredChatResponse {
+            response: ChatResponse::ToolCalls {
+                assistant_content: normalize_text(text),
+                calls: parse_gemini_tool_calls(function_calls)?,
+            },
+            output_tokens,
+        });
+    }
+
+    let text = normalize_text(text).ok_or_else(|| {
+        ModelClientError::ResponseFormat(format!(
+            "unable to extract assistant content from Gemini response (finish reason: {})",
+            candidate.finish_reason.as_deref().unwrap_or("none")
+        ))
+    })?;
+    Ok(MeasuredChatResponse {
+        response: ChatResponse::FinalText { text },
+        output_tokens,
+    })
+}
+
 #[derive(Debug)]
 struct ProviderRequestBase<M, T> {
     model: String,
     messages: Vec<M>,
     tools: Vec<T>,
+    temperature: Option<f32>,
 }
 
 fn build_provider_request_base<M, T, FM, FT>(
@@ -516,6 +1083,7 @@ where
         model: request.model.clone(),
         messages: request.messages.iter().map(map_message).collect(),
         tools: request.tools.iter().map(map_tool).collect(),
+        temperature: request.temperature,
     }
 }
 
@@ -614,6 +1182,8 @@ struct OpenAiChatRequest {
     messages: Vec<OpenAiMessage>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<OpenAiToolDefinition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
 }
 
 impl OpenAiChatRequest {
@@ -627,29 +1197,44 @@ impl OpenAiChatRequest {
             model: base.model,
             messages: base.messages,
             tools: base.tools,
+            temperature: base.temperature,
         }
     }
 }
 
+// OpenAI-compatible servers (vLLM, LM Studio, llama.cpp, OpenRouter) leave out or null
+// fields OpenAI always sends, so only what an answer cannot do without is required.
 #[derive(Debug, Deserialize)]
 struct OpenAiChatResponse {
+    #[serde(default)]
     choices: Vec<OpenAiChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiUsage {
+    #[serde(default)]
+    completion_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct OpenAiChoice {
+    #[serde(default)]
     message: OpenAiResponseMessage,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 struct OpenAiResponseMessage {
-    content: Option<serde_json::Value>,
     #[serde(default)]
+    content: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "null_as_default")]
     tool_calls: Vec<OpenAiToolCallResponse>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct OpenAiToolCallResponse {
+    #[serde(default)]
     id: Option<String>,
     function: OpenAiToolCallFunctionResponse,
 }
@@ -657,9 +1242,18 @@ struct OpenAiToolCallResponse {
 #[derive(Debug, Clone, Deserialize)]
 struct OpenAiToolCallFunctionResponse {
     name: String,
+    #[serde(default)]
     arguments: Value,
 }
 
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 #[derive(Debug, Clone, Serialize, PartialEq)]
 struct OllamaMessage {
     role: String,
@@ -743,6 +1337,13 @@ struct OllamaChatRequest {
     messages: Vec<OllamaMessage>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<OllamaToolDefinition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<OllamaRequestOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct OllamaRequestOptions {
+    temperature: f32,
 }
 
 impl OllamaChatRequest {
@@ -757,6 +1358,9 @@ impl OllamaChatRequest {
             stream: false,
             messages: base.messages,
             tools: base.tools,
+            options: base
+                .temperature
+                .map(|temperature| OllamaRequestOptions { temperature }),
         }
     }
 }
@@ -765,6 +1369,8 @@ impl OllamaChatRequest {
 struct OllamaChatResponse {
     message: Option<OllamaResponseMessage>,
     error: Option<String>,
+    #[serde(default)]
+    eval_count: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -786,6 +1392,460 @@ struct OllamaToolCallFunctionResponse {
     arguments: Value,
 }
 
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaTagsModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTagsModel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModelsResponse {
+    #[serde(default)]
+    data: Vec<OpenAiModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModelEntry {
+    id: String,
+}
+
+/// Body of both Ollama `/api/embed` and OpenAI `/embeddings`, which take a list of inputs.
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbeddingsResponse {
+    #[serde(default)]
+    data: Vec<OpenAiEmbeddingEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbeddingEntry {
+    /// Missing from some compatible servers, which return entries in input order.
+    #[serde(default)]
+    index: Option<usize>,
+    embedding: Vec<f32>,
+}
+
+/// `generateContent` body. Gemini has no system or tool roles: system messages become
+/// `systemInstruction`, tool results are `user` turns of `functionResponse` parts, and
+/// consecutive turns of the same role are merged.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct GeminiChatRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<GeminiTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    generation_config: Option<GeminiGenerationConfig>,
+}
+
+impl GeminiChatRequest {
+    fn from_common_request(request: &ChatRequest) -> Self {
+        let mut system_parts = Vec::new();
+        let mut contents: Vec<GeminiContent> = Vec::new();
+        for message in &request.messages {
+            if message.role == MessageRole::System {
+                system_parts.push(GeminiPart::text(&message.content));
+                continue;
+            }
+            let content = GeminiContent::from(message);
+            match contents.last_mut() {
+                Some(last) if last.role == content.role => last.parts.extend(content.parts),
+                _ => contents.push(content),
+            }
+        }
+
+        let function_declarations = request
+            .tools
+            .iter()
+            .map(GeminiFunctionDeclaration::from)
+            .collect::<Vec<_>>();
+        Self {
+            system_instruction: (!system_parts.is_empty()).then_some(GeminiContent {
+                role: None,
+                parts: system_parts,
+            }),
+            contents,
+            tools: if function_declarations.is_empty() {
+                Vec::new()
+            } else {
+                vec![GeminiTool {
+                    function_declarations,
+                }]
+            },
+            generation_config: request
+                .temperature
+                .map(|temperature| GeminiGenerationConfig { temperature }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct GeminiContent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'static str>,
+    parts: Vec<GeminiPart>,
+}
+
+impl From<&ModelMessage> for GeminiContent {
+    fn from(message: &ModelMessage) -> Self {
+        let (role, parts) = match message.role {
+            MessageRole::Assistant => {
+                let mut parts = Vec::new();
+                if !message.content.trim().is_empty() || message.tool_calls.is_empty() {
+                    parts.push(GeminiPart::text(&message.content));
+                }
+                parts.extend(message.tool_calls.iter().map(|call| GeminiPart {
+                    function_call: Some(GeminiFunctionCall {
+                        id: None,
+                        name: call.name.clone(),
+                        args: call.arguments.clone(),
+                    }),
+                    ..GeminiPart::default()
+                }));
+                ("model", parts)
+            }
+            MessageRole::Tool => {
+                // `response` must be an object; other tool output is wrapped.
+                let response = match serde_json::from_str::<Value>(&message.content) {
+                    Ok(value @ Value::Object(_)) => value,
+                    _ => serde_json::json!({ "content": message.content }),
+                };
+                let part = GeminiPart {
+                    function_response: Some(GeminiFunctionResponse {
+                        name: message.tool_name.clone().unwrap_or_default(),
+                        response,
+                    }),
+                    ..GeminiPart::default()
+                };
+                ("user", vec![part])
+            }
+            MessageRole::System | MessageRole::User => {
+                ("user", vec![GeminiPart::text(&message.content)])
+            }
+        };
+        Self {
+            role: Some(role),
+            parts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPart {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function_call: Option<GeminiFunctionCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function_response: Option<GeminiFunctionResponse>,
+}
+
+impl GeminiPart {
+    fn text(text: &str) -> Self {
+        Self {
+            text: Some(text.to_owned()),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct GeminiFunctionCall {
+    #[serde(default, skip_serializing)]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct GeminiFunctionResponse {
+    name: String,
+    response: Value,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct GeminiTool {
+    function_declarations: Vec<GeminiFunctionDeclaration>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct GeminiFunctionDeclaration {
+    name: String,
+    description: String,
+    /// Left out for tools without parameters; Gemini rejects an object with none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<Value>,
+}
+
+impl From<&ModelToolDefinition> for GeminiFunctionDeclaration {
+    fn from(tool: &ModelToolDefinition) -> Self {
+        let has_properties = tool
+            .parameters
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|properties| !properties.is_empty());
+        Self {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            parameters: has_properties.then(|| gemini_schema(&tool.parameters)),
+        }
+    }
+}
+
+/// `schema` without the keywords Gemini rejects, applied through `properties` and `items`.
+fn gemini_schema(schema: &Value) -> Value {
+    let Value::Object(fields) = schema else {
+        return schema.clone();
+    };
+    fields
+        .iter()
+        .filter(|(key, _)| !GEMINI_UNSUPPORTED_SCHEMA_KEYS.contains(&key.as_str()))
+        .map(|(key, value)| {
+            let value = match (key.as_str(), value) {
+                ("properties", Value::Object(properties)) => Value::Object(
+                    properties
+                        .iter()
+                        .map(|(name, property)| (name.clone(), gemini_schema(property)))
+                        .collect(),
+                ),
+                ("items", items) => gemini_schema(items),
+                _ => value.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct GeminiGenerationConfig {
+    temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiChatResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    prompt_feedback: Option<GeminiPromptFeedback>,
+    #[serde(default)]
+    usage_metadata: Option<GeminiUsageMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    #[serde(default)]
+    content: Option<GeminiResponseContent>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiResponseContent {
+    #[serde(default)]
+    parts: Vec<GeminiResponsePart>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponsePart {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    function_call: Option<GeminiFunctionCall>,
+    /// Thinking models mark their reasoning parts; those are not the answer.
+    #[serde(default)]
+    thought: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPromptFeedback {
+    #[serde(default)]
+    block_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsageMetadata {
+    #[serde(default)]
+    candidates_token_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiModelsResponse {
+    #[serde(default)]
+    models: Vec<GeminiModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiModelEntry {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiBatchEmbedRequest {
+    requests: Vec<GeminiEmbedContentRequest>,
+}
+
+impl GeminiBatchEmbedRequest {
+    fn new(model: &str, inputs: &[String]) -> Self {
+        let model = format!("models/{}", model.trim_start_matches("models/"));
+        Self {
+            requests: inputs
+                .iter()
+                .map(|input| GeminiEmbedContentRequest {
+                    model: model.clone(),
+                    content: GeminiContent {
+                        role: None,
+                        parts: vec![GeminiPart::text(input)],
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiEmbedContentRequest {
+    model: String,
+    content: GeminiContent,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiBatchEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<GeminiEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbedding {
+    values: Vec<f32>,
+}
+
+/// Spaces consecutive batched requests by `MODEL_BATCH_REQUESTS_PER_MINUTE` and by any
+/// pause the provider's rate-limit headers asked for.
+#[derive(Debug)]
+struct RequestPacer {
+    interval: Option<Duration>,
+    next_at: Option<Instant>,
+}
+
+impl RequestPacer {
+    fn new(requests_per_minute: u32) -> Self {
+        Self {
+            interval: (requests_per_minute > 0)
+                .then(|| Duration::from_secs(60) / requests_per_minute),
+            next_at: None,
+        }
+    }
+
+    /// Waits until the next request may start and reserves its slot.
+    async fn wait(&mut self) {
+        if let Some(next_at) = self.next_at {
+            sleep_until(next_at).await;
+        }
+        self.next_at = self.interval.map(|interval| Instant::now() + interval);
+    }
+
+    fn pause_for(&mut self, pause: Duration) {
+        let resume_at = Instant::now() + pause;
+        self.next_at = Some(
+            self.next_at
+                .map_or(resume_at, |next_at| next_at.max(resume_at)),
+        );
+    }
+}
+
+/// How long to hold the next request when OpenAI reports no requests left in the window.
+fn rate_limit_pause(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok();
+    if header("x-ratelimit-remaining-requests")?.trim() != "0" {
+        return None;
+    }
+    parse_rate_limit_reset(header("x-ratelimit-reset-requests")?)
+}
+
+/// Parses OpenAI rate-limit reset durations such as `20ms`, `1.5s`, or `6m0s`.
+fn parse_rate_limit_reset(raw: &str) -> Option<Duration> {
+    let mut rest = raw.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut seconds = 0.0_f64;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value = rest[..number_len].parse::<f64>().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_seconds = match &rest[..unit_len] {
+            "h" => 3_600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        seconds += value * unit_seconds;
+        rest = &rest[unit_len..];
+    }
+    Some(
+        Duration::try_from_secs_f64(seconds)
+            .ok()?
+            .min(MAX_RATE_LIMIT_PAUSE),
+    )
+}
+
+/// A `Retry-After` given in seconds; HTTP dates fall back to the usual backoff.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RATE_LIMIT_PAUSE))
+}
+
+/// Whether `model` appears in a provider model list. Ollama reports untagged models with
+/// an explicit `:latest` suffix, so `llama3` matches `llama3:latest`.
+pub fn model_is_listed(available: &[String], model: &str) -> bool {
+    available.iter().any(|name| {
+        name == model
+            || (!model.contains(':')
+                && name
+                    .strip_suffix(":latest")
+                    .is_some_and(|base| base == model))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -867,6 +1927,145 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gemini_requests_map_roles_tools_and_responses() {
+        let request = ChatRequest::new(
+            "m".to_owned(),
+            vec![
+                ModelMessage::system("s"),
+                ModelMessage::user("u"),
+                ModelMessage::assistant_tool_calls(
+                    "",
+                    vec![ModelToolCall {
+                        id: "call-1".to_owned(),
+                        name: "search_notes".to_owned(),
+                        arguments: json!({"query": "rust"}),
+                    }],
+                ),
+                ModelMessage::tool_result(
+                    "{\"results\":[]}",
+                    Some("call-1".to_owned()),
+                    Some("search_notes".to_owned()),
+                ),
+                ModelMessage::user("more"),
+            ],
+            vec![ModelToolDefinition {
+                name: "search_notes".to_owned(),
+                description: "Search notes".to_owned(),
+                parameters: json!({
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "query": {"type": "string"},
+                        "tags": {"type": "array", "items": {"type": "object", "additionalProperties": false}}
+                    }
+                }),
+            }],
+        );
+
+        let gemini = serde_json::to_value(GeminiChatRequest::from_common_request(&request))
+            .expect("request should serialize");
+        assert_eq!(
+            gemini["systemInstruction"],
+            json!({"parts": [{"text": "s"}]})
+        );
+        assert_eq!(
+            gemini["contents"],
+            json!([
+                {"role": "user", "parts": [{"text": "u"}]},
+                {"role": "model", "parts": [
+                    {"functionCall": {"name": "search_notes", "args": {"query": "rust"}}}
+                ]},
+                {"role": "user", "parts": [
+                    {"functionResponse": {"name": "search_notes", "response": {"results": []}}},
+                    {"text": "more"}
+                ]}
+            ])
+        );
+        assert_eq!(
+            gemini["tools"][0]["functionDeclarations"][0]["parameters"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string"},
+                    "tags": {"type": "array", "items": {"type": "object"}}
+                }
+            })
+        );
+
+        let response: GeminiChatResponse = serde_json::from_value(json!({
+            "candidates": [{"content": {"role": "model", "parts": [
+                {"text": "thinking", "thought": true},
+                {"text": "Looking."},
+                {"functionCall": {"name": "search_notes", "args": {"query": "rust"}}},
+                {"functionCall": {"name": "list_notes"}}
+            ]}}],
+            "usageMetadata": {"candidatesTokenCount": 7}
+        }))
+        .expect("response should decode");
+        let parsed = parse_gemini_response(response).expect("response should parse");
+        assert_eq!(parsed.output_tokens, Some(7));
+        assert_eq!(
+            parsed.response,
+            ChatResponse::ToolCalls {
+                assistant_content: Some("Looking.".to_owned()),
+                calls: vec![
+                    ModelToolCall {
+                        id: "gemini-tool-call-1".to_owned(),
+                        name: "search_notes".to_owned(),
+                        arguments: json!({"query": "rust"}),
+                    },
+                    ModelToolCall {
+                        id: "gemini-tool-call-2".to_owned(),
+                        name: "list_notes".to_owned(),
+                        arguments: json!({}),
+                    },
+                ],
+            }
+        );
+
+        let blocked: GeminiChatResponse =
+            serde_json::from_value(json!({"promptFeedback": {"blockReason": "SAFETY"}}))
+                .expect("blocked response should decode");
+        let error = parse_gemini_response(blocked).expect_err("blocked prompts have no answer");
+        assert!(error.to_string().contains("SAFETY"), "{error}");
+    }
+
+    #[test]
+    fn call_options_override_model_and_temperature() {
+        let mut settings = crate::agent::tests::test_settings();
+        settings.model = "base-model".to_owned();
+        let client = ModelClient::new(settings);
+        let messages = [ModelMessage::user("u")];
+
+        let default_request = client.messages_request(&messages, &[], &ChatCallOptions::default());
+        let repair_request = client.messages_request(
+            &messages,
+            &[],
+            &ChatCallOptions {
+                model: Some("repair-model".to_owned()),
+                temperature: Some(0.25),
+            },
+        );
+
+        let default_ollama =
+            serde_json::to_value(OllamaChatRequest::from_common_request(&default_request))
+                .expect("request should serialize");
+        assert_eq!(default_ollama["model"], "base-model");
+        assert!(default_ollama.get("options").is_none());
+
+        let repair_ollama =
+            serde_json::to_value(OllamaChatRequest::from_common_request(&repair_request))
+                .expect("request should serialize");
+        let repair_openai =
+            serde_json::to_value(OpenAiChatRequest::from_common_request(&repair_request))
+                .expect("request should serialize");
+        assert_eq!(repair_ollama["model"], "repair-model");
+        assert_eq!(repair_ollama["options"], json!({ "temperature": 0.25 }));
+        assert_eq!(repair_openai["model"], "repair-model");
+        assert_eq!(repair_openai["temperature"], 0.25);
+    }
+
     #[test]
     fn parse_tool_arguments_from_json_string() {
         let parsed = parse_tool_arguments(
@@ -1005,4 +2204,355 @@ mod tests {
         assert_eq!(retry_delay_ms(6), 8_000);
         assert_eq!(retry_delay_ms(99), 8_000);
     }
+
+    #[test]
+    fn model_listing_responses_decode_and_match_latest_tag() {
+        let ollama: OllamaTagsResponse = serde_json::from_value(json!({
+            "models": [{"name": "llama3:latest", "size": 1}, {"name": "qwen2.5:7b"}]
+        }))
+        .expect("ollama tags should decode");
+        let ollama_names = ollama
+            .models
+            .into_iter()
+            .map(|model| model.name)
+            .collect::<Vec<_>>();
+        let openai: OpenAiModelsResponse = serde_json::from_value(json!({
+            "object": "list",
+            "data": [{"id": "gpt-4o-mini", "object": "model"}]
+        }))
+        .expect("openai models should decode");
+
+        assert!(model_is_listed(&ollama_names, "llama3"));
+        assert!(model_is_listed(&ollama_names, "qwen2.5:7b"));
+        assert!(!model_is_listed(&ollama_names, "qwen2.5"));
+        assert!(!model_is_listed(&ollama_names, "llama3:8b"));
+        assert_eq!(openai.data[0].id, "gpt-4o-mini");
+    }
+
+    #[tokio::test]
+    async fn cached_response_answers_without_contacting_the_provider() {
+        let cache_dir = crate::test_support::temp_path("model_client_cache");
+        let mut settings = crate::agent::tests::test_settings();
+        settings.ollama_base_url = "http://127.0.0.1:9".to_owned();
+        settings.model_max_retries = 0;
+        settings.model_cache_dir = Some(cache_dir.display().to_string());
+        let client = ModelClient::new(settings.clone());
+
+        let request = ChatRequest::from_prompts(&settings.model, "system", "hello");
+        let provider_request =
+            serde_json::to_value(OllamaChatRequest::from_common_request(&request))
+                .expect("request should serialize");
+        ResponseCache::open(&cache_dir)
+            .expect("cache should open")
+            .put(
+                "ollama",
+                "http://127.0.0.1:9/api/chat",
+                &provider_request,
+                r#"{"message":{"role":"assistant","content":"cached hello"},"eval_count":2}"#,
+            );
+
+        let response = client
+            .chat("system", "hello")
+            .await
+            .expect("cached response should be served");
+        assert_eq!(
+            response,
+            ChatResponse::FinalText {
+                text: "cached hello".to_owned()
+            }
+        );
+        assert!(client.chat("system", "other prompt").await.is_err());
+        crate::test_support::remove_dir_if_exists(&cache_dir);
+    }
+
+    #[tokio::test]
+    async fn provider_requests_go_through_the_http_client_and_retry_server_errors() {
+        use crate::http::{StubHttpClient, StubResponse};
+
+        let mut settings = crate::agent::tests::test_settings();
+        settings.ollama_base_url = "http://ollama.test/".to_owned();
+        settings.model_max_retries = 1;
+        let stub = StubHttpClient::new();
+        stub.respond(
+            reqwest::Method::POST,
+            "http://ollama.test/api/chat",
+            StubResponse::new(StatusCode::SERVICE_UNAVAILABLE, "loading model"),
+        )
+        .respond(
+            reqwest::Method::POST,
+            "http://ollama.test/api/chat",
+            StubResponse::json(&json!({
+                "message": { "role": "assistant", "content": "hello" },
+                "eval_count": 1
+            })),
+        );
+        let client = ModelClient::with_http_client(settings, SharedHttpClient::new(stub.clone()));
+
+        let response = client
+            .chat("system", "hi")
+            .await
+            .expect("the retry should succeed");
+        assert_eq!(
+            response,
+            ChatResponse::FinalText {
+                text: "hello".to_owned()
+            }
+        );
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 2);
+        let body: Value =
+            serde_json::from_slice(requests[1].body.as_deref().expect("request body"))
+                .expect("request body should be JSON");
+        assert_eq!(body["messages"][1]["content"], "hi");
+
+        let mut settings = crate::agent::tests::test_settings();
+        settings.model_provider = ModelProvider::OpenAi;
+        settings.openai_api_key = Some("sk-test".to_owned());
+        let stub = StubHttpClient::new();
+        stub.respond(
+            reqwest::Method::GET,
+            &format!("{OPENAI_BASE_URL}/models"),
+            StubResponse::json(&json!({ "data": [{ "id": "gpt-4o-mini" }] })),
+        );
+        let client = ModelClient::with_http_client(settings, SharedHttpClient::new(stub.clone()));
+
+        assert_eq!(
+            client.list_models().await.expect("models should list"),
+            ["gpt-4o-mini"]
+        );
+        assert_eq!(
+            stub.requests()[0].headers["authorization"],
+            "Bearer sk-test"
+        );
+
+        let mut settings = crate::agent::tests::test_settings();
+        settings.model_provider = ModelProvider::Gemini;
+        settings.model = "gemini-2.0-flash".to_owned();
+        settings.gemini_api_key = Some("gemini-test".to_owned());
+        settings.gemini_base_url = "http://gemini.test/v1beta/".to_owned();
+        let stub = StubHttpClient::new();
+        stub.respond(
+            reqwest::Method::POST,
+            "http://gemini.test/v1beta/models/gemini-2.0-flash:generateContent",
+            StubResponse::json(&json!({
+                "candidates": [{"content": {"role": "model", "parts": [{"text": "hello"}]}}]
+            })),
+        );
+        let client = ModelClient::with_http_client(settings, SharedHttpClient::new(stub.clone()));
+
+        assert_eq!(
+            client
+                .chat("system", "hi")
+                .await
+                .expect("gemini should answer"),
+            ChatResponse::FinalText {
+                text: "hello".to_owned()
+            }
+        );
+        let requests = stub.requests();
+        assert_eq!(requests[0].headers["x-goog-api-key"], "gemini-test");
+        assert!(!requests[0].headers.contains_key("authorization"));
+    }
+
+    #[tokio::test]
+    async fn compatible_servers_use_the_openai_format_with_relaxed_responses() {
+        use crate::http::{StubHttpClient, StubResponse};
+
+        let mut settings = crate::agent::tests::test_settings();
+        settings.model_provider = ModelProvider::Compatible;
+        settings.model = "qwen2.5-7b-instruct".to_owned();
+        settings.openai_compat_base_url = Some("http://compat.test/v1/".to_owned());
+        let stub = StubHttpClient::new();
+        stub.respond(
+            reqwest::Method::POST,
+            "http://compat.test/v1/chat/completions",
+            StubResponse::json(&json!({
+                "choices": [{"message": {
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "",
+                        "type": "function",
+                        "function": { "name": "list_notes", "arguments": "" }
+                    }]
+                }}]
+            })),
+        )
+        .respond(
+            reqwest::Method::POST,
+            "http://compat.test/v1/chat/completions",
+            StubResponse::json(&json!({
+                "choices": [{"message": { "content": "done", "tool_calls": null }}],
+                "usage": { "prompt_tokens": 3 }
+            })),
+        );
+        let client = ModelClient::with_http_client(settings, SharedHttpClient::new(stub.clone()));
+
+        assert_eq!(
+            client
+                .chat("system", "hi")
+                .await
+                .expect("tool calls should parse"),
+            ChatResponse::ToolCalls {
+                assistant_content: None,
+                calls: vec![ModelToolCall {
+                    id: "openai-tool-call-1".to_owned(),
+                    name: "list_notes".to_owned(),
+                    arguments: json!({}),
+                }],
+            }
+        );
+        let measured = client
+            .chat_measured("system", "hi")
+            .await
+            .expect("final text should parse");
+        assert_eq!(
+            measured.response,
+            ChatResponse::FinalText {
+                text: "done".to_owned()
+            }
+        );
+        assert_eq!(measured.output_tokens, None);
+        let requests = stub.requests();
+        assert!(!requests[0].headers.contains_key("authorization"));
+        let body: Value =
+            serde_json::from_slice(requests[0].body.as_deref().expect("request body"))
+                .expect("request body should be JSON");
+        assert_eq!(body["model"], "qwen2.5-7b-instruct");
+
+        let mut settings = crate::agent::tests::test_settings();
+        settings.model_provider = ModelProvider::Compatible;
+        settings.openai_compat_base_url = Some("http://compat.test/v1".to_owned());
+        settings.openai_compat_api_key = Some("or-test".to_owned());
+        let stub = StubHttpClient::new();
+        stub.respond(
+            reqwest::Method::GET,
+            "http://compat.test/v1/models",
+            StubResponse::json(&json!({ "data": [{ "id": "local-model" }] })),
+        )
+        .respond(
+            reqwest::Method::POST,
+            "http://compat.test/v1/embeddings",
+            StubResponse::json(
+                &json!({ "data": [{ "embedding": [0.5] }, { "embedding": [1.5] }] }),
+            ),
+        );
+        let client = ModelClient::with_http_client(settings, SharedHttpClient::new(stub.clone()));
+
+        assert_eq!(
+            client.list_models().await.expect("models should list"),
+            ["local-model"]
+        );
+        assert_eq!(
+            client
+                .embed(&["a".to_owned(), "b".to_owned()])
+                .await
+                .expect("embeddings without indexes should keep order"),
+            [vec![0.5], vec![1.5]]
+        );
+        assert_eq!(
+            stub.requests()[0].headers["authorization"],
+            "Bearer or-test"
+        );
+    }
+
+    #[tokio::test]
+    async fn embed_chunks_inputs_keeps_order_and_honors_retry_after() {
+        use crate::http::{StubHttpClient, StubResponse};
+
+        let mut settings = crate::agent::tests::test_settings();
+        settings.ollama_base_url = "http://ollama.test".to_owned();
+        settings.model_batch_size = 2;
+        let stub = StubHttpClient::new();
+        for batch in [json!([[1.0], [2.0]]), json!([[3.0], [4.0]]), json!([[5.0]])] {
+            stub.respond(
+                reqwest::Method::POST,
+                "http://ollama.test/api/embed",
+                StubResponse::json(&json!({ "embeddings": batch })),
+            );
+        }
+        let client = ModelClient::with_http_client(settings, SharedHttpClient::new(stub.clone()));
+        let inputs = ["a", "b", "c", "d", "e"].map(str::to_owned);
+
+        let embeddings = client.embed(&inputs).await.expect("embeddings");
+        assert_eq!(
+            embeddings,
+            [[1.0], [2.0], [3.0], [4.0], [5.0]].map(|vector| vector.to_vec())
+        );
+        let bodies = stub
+            .requests()
+            .iter()
+            .map(|request| {
+                serde_json::from_slice::<Value>(request.body.as_deref().expect("body"))
+                    .expect("request body should be JSON")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(bodies.len(), 3);
+        assert_eq!(
+            bodies[0],
+            json!({ "model": "nomic-embed-text", "input": ["a", "b"] })
+        );
+        assert_eq!(bodies[2]["input"], json!(["e"]));
+
+        let mut settings = crate::agent::tests::test_settings();
+        settings.model_provider = ModelProvider::OpenAi;
+        settings.openai_api_key = Some("sk-test".to_owned());
+        settings.model_max_retries = 1;
+        let url = format!("{OPENAI_BASE_URL}/embeddings");
+        let stub = StubHttpClient::new();
+        stub.respond(
+            reqwest::Method::POST,
+            &url,
+            StubResponse::new(StatusCode::TOO_MANY_REQUESTS, "slow down")
+                .with_header("retry-after", "0"),
+        )
+        .respond(
+            reqwest::Method::POST,
+            &url,
+            StubResponse::json(&json!({ "data": [
+                { "index": 1, "embedding": [2.0] },
+                { "index": 0, "embedding": [1.0] }
+            ] })),
+        );
+        let client = ModelClient::with_http_client(settings, SharedHttpClient::new(stub.clone()));
+
+        let embeddings = client
+            .embed(&["a".to_owned(), "b".to_owned()])
+            .await
+            .expect("the retry should succeed");
+        assert_eq!(embeddings, [vec![1.0], vec![2.0]]);
+        assert_eq!(stub.requests().len(), 2);
+    }
+
+    #[test]
+    fn rate_limit_headers_pause_only_when_no_requests_remain() {
+        assert_eq!(
+            parse_rate_limit_reset("6m0s"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_rate_limit_reset("1.5s"),
+            Some(Duration::from_millis(1_500))
+        );
+        assert_eq!(
+            parse_rate_limit_reset("20ms"),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(parse_rate_limit_reset("soon"), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-ratelimit-remaining-requests",
+            "3".parse().expect("value"),
+        );
+        headers.insert("x-ratelimit-reset-requests", "2s".parse().expect("value"));
+        assert_eq!(rate_limit_pause(&headers), None);
+        headers.insert(
+            "x-ratelimit-remaining-requests",
+            "0".parse().expect("value"),
+        );
+        assert_eq!(rate_limit_pause(&headers), Some(Duration::from_secs(2)));
+
+        headers.insert(RETRY_AFTER, "7".parse().expect("value"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+    }
 }
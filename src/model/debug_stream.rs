@@ -0,0 +1,124 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tracing::warn;
+
+/// Identifies the agent turn and loop step a model call belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugStreamContext {
+    pub turn_id: u64,
+    pub step: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct DebugStreamRecord<'a> {
+    timestamp_ms: u128,
+    turn_id: u64,
+    step: u32,
+    attempt: u32,
+    provider: &'a str,
+    delta: &'a str,
+}
+
+/// Append-only JSONL sink for raw model output, enabled by `MODEL_DEBUG_STREAM_FILE`.
+///
+/// Responses are not streamed yet, so each provider response body is written as a
+/// single delta; streamed chunks will be appended through the same record shape.
+#[derive(Debug, Clone)]
+pub struct DebugStreamWriter {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+}
+
+impl DebugStreamWriter {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Appends one raw delta. Write failures are logged and never fail the model call.
+    pub fn append_delta(
+        &self,
+        context: DebugStreamContext,
+        attempt: u32,
+        provider: &str,
+        delta: &str,
+    ) {
+        let record = DebugStreamRecord {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis())
+                .unwrap_or_default(),
+            turn_id: context.turn_id,
+            step: context.step,
+            attempt,
+            provider,
+            delta,
+        };
+        if let Err(error) = self.write_record(&record) {
+            warn!(
+                path = %self.path.display(),
+                error = %error,
+                "failed to append model debug stream record"
+            );
+        }
+    }
+
+    fn write_record(&self, record: &DebugStreamRecord<'_>) -> io::Result<()> {
+        let mut line = serde_json::to_string(record).map_err(io::Error::other)?;
+        line.push('\n');
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| io::Error::other("debug stream file lock poisoned"))?;
+        file.write_all(line.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::{DebugStreamContext, DebugStreamWriter};
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn append_delta_writes_one_json_line_per_delta() {
+        let dir = temp_path("debug_stream");
+        let path = dir.join("nested").join("stream.jsonl");
+        let writer = DebugStreamWriter::open(&path).expect("open debug stream");
+        let context = DebugStreamContext {
+            turn_id: 7,
+            step: 2,
+        };
+
+        writer.append_delta(context, 1, "ollama", "{\"message\":{\"tool_calls\":[{");
+        writer.append_delta(context, 2, "ollama", "{\"message\":{}}");
+
+        let contents = std::fs::read_to_string(&path).expect("read debug stream");
+        let records = contents
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).expect("json line"))
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["turn_id"], 7);
+        assert_eq!(records[0]["step"], 2);
+        assert_eq!(records[0]["delta"], "{\"message\":{\"tool_calls\":[{");
+        assert_eq!(records[1]["attempt"], 2);
+
+        remove_dir_if_exists(&dir);
+    }
+}
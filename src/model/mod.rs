@@ -1 +1,3 @@
+pub mod cache;
 pub mod client;
+pub mod debug_stream;
@@ -0,0 +1,133 @@
+use std::time::Instant;
+
+use anyhow::{Result, anyhow};
+
+use crate::agent::run_chat_turn;
+use crate::config::{AgentSettings, ModelProvider};
+use crate::http::SharedHttpClient;
+use crate::model::client::{ModelClient, model_is_listed};
+use crate::tr;
+
+const PREFLIGHT_PROMPT: &str = "Reply with the single word: ready";
+const MAX_LISTED_MODELS: usize = 10;
+
+/// Checks the provider is reachable, the configured model exists, and one trivial turn
+/// completes, so a broken setup fails once instead of once per eval case (or before
+/// `serve --daemon-ready-notify` reports readiness).
+pub async fn run_model_preflight(settings: &AgentSettings) -> Result<()> {
+    let mut preflight_settings = settings.clone();
+    // One attempt: a flaky provider should surface here, not be papered over.
+    preflight_settings.model_max_retries = 0;
+    // The listing and the warm-up turn share one client, so the turn reuses the connection.
+    let http_client = SharedHttpClient::from_settings(&preflight_settings);
+    let client = ModelClient::with_http_client(preflight_settings.clone(), http_client.clone());
+
+    let available = client.list_models().await.map_err(|error| {
+        anyhow!(
+            "preflight: {} is unreachable: {error}",
+            provider_label(&preflight_settings)
+        )
+    })?;
+    println!(
+        "{}",
+        tr!(
+            "preflight.reachable",
+            provider = provider_label(&preflight_settings),
+            count = available.len()
+        )
+    );
+
+    if !model_is_listed(&available, &settings.model) {
+        return Err(anyhow!(
+            "preflight: model `{}` not found on {}; {}",
+            settings.model,
+            provider_label(&preflight_settings),
+            describe_available_models(&available)
+        ));
+    }
+    println!(
+        "{}",
+        tr!("preflight.model_available", model = settings.model)
+    );
+
+    let started_at = Instant::now();
+    run_chat_turn(&preflight_settings, PREFLIGHT_PROMPT, &http_client)
+        .await
+        .map_err(|error| anyhow!("preflight: warm-up turn failed: {}", error.details()))?;
+    println!(
+        "{}",
+        tr!(
+            "preflight.warmup_completed",
+            millis = started_at.elapsed().as_millis()
+        )
+    );
+    Ok(())
+}
+
+fn provider_label(settings: &AgentSettings) -> String {
+    match settings.model_provider {
+        ModelProvider::Ollama => format!("ollama at {}", settings.ollama_base_url),
+        ModelProvider::OpenAi => "openai".to_owned(),
+        ModelProvider::Gemini => format!("gemini at {}", settings.gemini_base_url),
+        ModelProvider::Compatible => format!(
+            "compatible server at {}",
+            settings
+                .openai_compat_base_url
+                .as_deref()
+                .unwrap_or("<unset>")
+        ),
+    }
+}
+
+fn describe_available_models(available: &[String]) -> String {
+    if available.is_empty() {
+        return "the provider reports no models".to_owned();
+    }
+    let mut listed = available
+        .iter()
+        .take(MAX_LISTED_MODELS)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if available.len() > MAX_LISTED_MODELS {
+        listed.push_str(&format!(", … {} more", available.len() - MAX_LISTED_MODELS));
+    }
+    format!("available: {listed}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe_available_models, provider_label, run_model_preflight};
+    use crate::agent::tests::test_settings;
+
+    #[test]
+    fn describe_available_models_caps_the_list() {
+        let models = (1..=12)
+            .map(|index| format!("m{index}"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            describe_available_models(&[]),
+            "the provider reports no models"
+        );
+        assert_eq!(
+            describe_available_models(&models),
+            "available: m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, … 2 more"
+        );
+    }
+
+    #[tokio::test]
+    async fn preflight_fails_fast_when_provider_is_unreachable() {
+        let mut settings = test_settings();
+        settings.ollama_base_url = "http://127.0.0.1:9".to_owned();
+
+        let error = run_model_preflight(&settings)
+            .await
+            .expect_err("unreachable provider should fail");
+
+        assert!(error.to_string().starts_with(&format!(
+            "preflight: {} is unreachable",
+            provider_label(&settings)
+        )));
+    }
+}
@@ -0,0 +1,207 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use tracing::{info, warn};
+
+const NOTIFY_SOCKET_ENV: &str = "NOTIFY_SOCKET";
+
+/// `serve` supervision options from the command line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServeOptions {
+    /// Run the model preflight, then send `READY=1` to `$NOTIFY_SOCKET` (systemd
+    /// `Type=notify`) once the server accepts connections.
+    pub daemon_ready_notify: bool,
+    pub pid_file: Option<PathBuf>,
+    /// Start even when `Guardrails::unsafe_combinations` reports a problem.
+    pub allow_unsafe: bool,
+}
+
+/// Holds the `--pid-file` for the server's lifetime and removes it on drop.
+#[derive(Debug)]
+pub(super) struct PidFileGuard {
+    path: PathBuf,
+}
+
+impl PidFileGuard {
+    /// Writes this process id to `path`. Fails when the file names another live process;
+    /// a stale file from a crashed run is replaced.
+    pub fn create(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(existing) => {
+                if let Ok(pid) = existing.trim().parse::<u32>()
+                    && pid != std::process::id()
+                    && process_is_running(pid)
+                {
+                    bail!(
+                        "PID file {} belongs to running process {pid}; is another server running?",
+                        path.display()
+                    );
+                }
+                warn!(path = %path.display(), "replacing stale PID file");
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read PID file {}", path.display()));
+            }
+        }
+        fs::write(path, format!("{}\n", std::process::id()))
+            .with_context(|| format!("failed to write PID file {}", path.display()))?;
+        Ok(Self {
+            path: path.to_owned(),
+        })
+    }
+}
+
+impl Drop for PidFileGuard {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_file(&self.path)
+            && error.kind() != ErrorKind::NotFound
+        {
+            warn!(path = %self.path.display(), error = %error, "failed to remove PID file");
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn process_is_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Without procfs there is no cheap liveness check; treat the file as live so two
+/// servers never share one PID file.
+#[cfg(not(target_os = "linux"))]
+fn process_is_running(_pid: u32) -> bool {
+    true
+}
+
+/// Sends an `sd_notify` state (for example `READY=1`) to the supervisor. Returns
+/// whether a message was sent; without `$NOTIFY_SOCKET` this is a no-op.
+pub(super) fn notify_supervisor(state: &str) -> Result<bool> {
+    let Some(socket_path) = std::env::var_os(NOTIFY_SOCKET_ENV) else {
+        return Ok(false);
+    };
+    send_notify_datagram(Path::new(&socket_path), state)
+        .with_context(|| format!("failed to send `{state}` to {NOTIFY_SOCKET_ENV}"))?;
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn send_notify_datagram(socket_path: &Path, state: &str) -> Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let socket = UnixDatagram::unbound().context("failed to open notify socket")?;
+    // A leading `@` names a Linux abstract socket.
+    #[cfg(target_os = "linux")]
+    if let Some(name) = socket_path
+        .as_os_str()
+        .as_encoded_bytes()
+        .strip_prefix(b"@")
+    {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::net::SocketAddr;
+
+        let address = SocketAddr::from_abstract_name(name)?;
+        socket.send_to_addr(state.as_bytes(), &address)?;
+        return Ok(());
+    }
+    socket.send_to(state.as_bytes(), socket_path)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_notify_datagram(_socket_path: &Path, _state: &str) -> Result<()> {
+    bail!("sd_notify is only supported on Unix")
+}
+
+/// Resolves on SIGTERM or Ctrl-C and returns the signal name for the shutdown log.
+pub(super) async fn shutdown_signal() -> &'static str {
+    let ctrl_c = async {
+        if let Err(error) = tokio::signal::ctrl_c().await {
+            warn!(error = %error, "failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(error) => {
+                warn!(error = %error, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    let signal = tokio::select! {
+        () = ctrl_c => "SIGINT",
+        () = terminate => "SIGTERM",
+    };
+    info!(signal, "shutdown requested; draining in-flight requests");
+    signal
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{PidFileGuard, send_notify_datagram};
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn pid_file_is_written_replaced_when_stale_and_removed_on_drop() {
+        let dir = temp_path("pid-file");
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let path = dir.join("serve.pid");
+        // u32::MAX is never a live pid.
+        fs::write(&path, format!("{}\n", u32::MAX)).expect("stale PID file");
+
+        let guard = PidFileGuard::create(&path).expect("stale PID file should be replaced");
+        assert_eq!(
+            fs::read_to_string(&path).expect("PID file should exist"),
+            format!("{}\n", std::process::id())
+        );
+        drop(guard);
+        assert!(!path.exists());
+        remove_dir_if_exists(&dir);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pid_file_owned_by_a_live_process_is_rejected() {
+        let dir = temp_path("pid-file-live");
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let path = dir.join("serve.pid");
+        // pid 1 is always running.
+        fs::write(&path, "1\n").expect("live PID file");
+
+        let error = PidFileGuard::create(&path).expect_err("live PID file should be rejected");
+        assert!(error.to_string().contains("running process 1"));
+        assert_eq!(fs::read_to_string(&path).expect("file kept"), "1\n");
+        remove_dir_if_exists(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn notify_datagram_reaches_the_socket() {
+        use std::os::unix::net::UnixDatagram;
+
+        let dir = temp_path("notify-socket");
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let socket_path = dir.join("notify.sock");
+        let receiver = UnixDatagram::bind(&socket_path).expect("notify socket should bind");
+
+        send_notify_datagram(&socket_path, "READY=1\nSTATUS=serving").expect("notify");
+
+        let mut buffer = [0_u8; 64];
+        let len = receiver.recv(&mut buffer).expect("datagram should arrive");
+        assert_eq!(&buffer[..len], b"READY=1\nSTATUS=serving");
+        remove_dir_if_exists(&dir);
+    }
+}
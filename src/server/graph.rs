@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use tokio::runtime::Handle;
+use tracing::debug;
+
+use crate::graph::ArchitectureGraph;
+use crate::graph::watch::{GraphWatchHandle, spawn_graph_watch_worker};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GraphLookup {
+    NotReady,
+    Unchanged,
+    Updated(ArchitectureGraph),
+}
+
+/// Latest workspace graph published by the server's graph watch worker.
+#[derive(Debug, Default)]
+pub(crate) struct SharedGraph {
+    latest: RwLock<Option<ArchitectureGraph>>,
+}
+
+impl SharedGraph {
+    pub(crate) fn publish(&self, graph: ArchitectureGraph) {
+        *self.latest.write().expect("shared graph lock poisoned") = Some(graph);
+    }
+
+    /// Returns the latest graph unless the caller already has its revision. Any other
+    /// revision gets the graph, since revisions restart from 1 when `serve` restarts and a
+    /// client's higher revision then belongs to the previous process.
+    pub(crate) fn lookup(&self, after_revision: Option<u64>) -> GraphLookup {
+        let latest = self.latest.read().expect("shared graph lock poisoned");
+        match (latest.as_ref(), after_revision) {
+            (None, _) => GraphLookup::NotReady,
+            (Some(graph), Some(known)) if graph.revision == known => GraphLookup::Unchanged,
+            (Some(graph), _) => GraphLookup::Updated(graph.clone()),
+        }
+    }
+}
+
+/// Watches `workspace_root` and mirrors each refresh into the returned `SharedGraph`.
+pub(crate) fn spawn_server_graph_watch(
+    handle: &Handle,
+    workspace_root: PathBuf,
+) -> (GraphWatchHandle, Arc<SharedGraph>) {
+    let shared = Arc::new(SharedGraph::default());
+    let (watch_handle, mut update_rx) = spawn_graph_watch_worker(handle, workspace_root);
+    let publisher = Arc::clone(&shared);
+    let _task = handle.spawn(async move {
+        while let Some(update) = update_rx.recv().await {
+            debug!(
+                revision = update.graph.revision,
+                trigger = update.trigger.label(),
+                "published server workspace graph"
+            );
+            publisher.publish(update.graph);
+        }
+    });
+    (watch_handle, shared)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use crate::graph::ArchitectureGraph;
+
+    use super::{GraphLookup, SharedGraph};
+
+    #[test]
+    fn lookup_reports_not_ready_unchanged_and_updated() {
+        let shared = SharedGraph::default();
+        assert_eq!(shared.lookup(None), GraphLookup::NotReady);
+
+        let graph = ArchitectureGraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            revision: 3,
+            generated_at: UNIX_EPOCH,
+        };
+        shared.publish(graph.clone());
+
+        assert_eq!(shared.lookup(None), GraphLookup::Updated(graph.clone()));
+        assert_eq!(shared.lookup(Some(2)), GraphLookup::Updated(graph.clone()));
+        assert_eq!(shared.lookup(Some(3)), GraphLookup::Unchanged);
+        // A client that saw revision 9 before a server restart still gets the new graph.
+        assert_eq!(shared.lookup(Some(9)), GraphLookup::Updated(graph));
+    }
+}
@@ -1,81 +1,1041 @@
-use anyhow::{Context, Result};
-use axum::extract::State;
-use axum::http::StatusCode;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::unbounded_channel;
 use tracing::{info, warn};
 
-use crate::agent::{ChatTurnError, ChatTurnErrorKind, run_chat_turn};
+use crate::agent::{
+    ArchivedSession, ChatSession, ChatTurnError, ChatTurnErrorKind, ChatTurnOutcome, ErrorCode,
+    HistorySource, ModelOverride, TurnEventSink, TurnHistory, list_archived_sessions,
+    load_archived_transcript, outcome_json, parse_user,
+};
+use crate::build_info::{BuildInfo, VERSION};
+use crate::capabilities::Capabilities;
 use crate::config::AgentSettings;
+use crate::export::{ExportFormat, Transcript};
+use crate::graph::watch::GraphWatchHandle;
+use crate::guardrails::Guardrails;
+use crate::http::HttpClientMetrics;
+use crate::model::client::{ModelClient, model_is_listed};
+use crate::preflight::run_model_preflight;
+use crate::tools::{ToolSchema, tool_schemas};
+
+mod daemon;
+mod graph;
+mod models;
+mod policy;
+mod schedule;
+mod sessions;
+mod stream;
+mod webhooks;
+
+pub use self::daemon::ServeOptions;
+use self::daemon::{PidFileGuard, notify_supervisor, shutdown_signal};
+use self::graph::{GraphLookup, SharedGraph, spawn_server_graph_watch};
+use self::models::{ModelPool, ModelSwapRequest, candidate_settings, is_authorized};
+use self::policy::{
+    PolicyAuditEntry, PolicyPatchRequest, PolicyStore, parse_policy_patch, policy_changes,
+};
+use self::schedule::{
+    ScheduleBoard, ScheduleRunContext, ScheduleStatus, load_schedules, spawn_schedule_runners,
+};
+use self::sessions::{SessionMetrics, SessionStore, SessionStoreError};
+use self::stream::{StreamEvent, sse_response};
+use self::webhooks::{TurnOutcomeEvent, TurnOutcomeStatus, WebhookNotifier, unix_time_ms};
+pub(crate) use crate::api::GraphBody;
+pub use crate::api::{ChatRequest, ErrorBody};
+
+/// Users `/metrics` counts turns for by name; turns from any later user are counted
+/// under `OTHER_USERS_KEY`, so self-asserted ids cannot grow the counters without bound.
+const MAX_COUNTED_USERS: usize = 100;
+const OTHER_USERS_KEY: &str = "<other>";
 
 #[derive(Clone)]
 struct AppState {
     settings: AgentSettings,
+    sessions: Arc<SessionStore>,
+    graph: Arc<SharedGraph>,
+    graph_watch: GraphWatchHandle,
+    webhooks: Option<Arc<WebhookNotifier>>,
+    schedules: Arc<ScheduleBoard>,
+    models: Arc<ModelPool>,
+    policy: Arc<PolicyStore>,
+    turn_errors: Arc<Mutex<BTreeMap<ErrorCode, u64>>>,
+    turns_by_user: Arc<Mutex<BTreeMap<String, u64>>>,
+    /// Address the guardrail check ran against; `None` when the listener has none.
+    local_addr: Option<SocketAddr>,
+    /// `serve --allow-unsafe`: policy changes may also combine guardrails unsafely.
+    allow_unsafe: bool,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(deny_unknown_fields)]
-struct ChatRequest {
-    message: String,
+#[derive(Debug, Serialize)]
+struct ChatResponseBody {
+    /// `ChatTurnOutcome` as rendered by `outcome_json`.
+    #[serde(flatten)]
+    outcome: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
 }
 
+/// Body of a `/chat` response: the outcome, or an error (with its moderation decision
+/// when one blocked the turn).
 #[derive(Debug, Serialize)]
-struct ErrorBody {
-    error: String,
+#[serde(untagged)]
+enum ChatReply {
+    Outcome(ChatResponseBody),
+    Error(ErrorBody),
 }
 
 #[derive(Debug, Serialize)]
 struct HealthBody {
     status: &'static str,
+    version: &'static str,
+    build: BuildInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GraphQuery {
+    #[serde(default)]
+    after_revision: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptQuery {
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolsBody {
+    tools: Vec<ToolSchema>,
+}
+
+#[derive(Debug, Serialize)]
+struct MetricsBody {
+    sessions: SessionMetrics,
+    /// Failed chat turns per error code since startup.
+    turn_errors_total: BTreeMap<ErrorCode, u64>,
+    /// Chat turns that ran per request `user` since startup, the first
+    /// `MAX_COUNTED_USERS` users by name and the rest under `other`; turns without a user
+    /// are not counted.
+    turns_total_by_user: BTreeMap<String, u64>,
+    /// Provider and `fetch_url` traffic through the shared HTTP client.
+    http_client: HttpClientMetrics,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionsBody {
+    /// Ids of the sessions still in memory.
+    live: Vec<String>,
+    /// Expired sessions in `SESSION_ARCHIVE_DIR`, most recent first.
+    archived: Vec<ArchivedSession>,
+}
+
+#[derive(Debug, Serialize)]
+struct SchedulesBody {
+    schedules: Vec<ScheduleStatus>,
+}
+
+/// `serve`: binds `bind` and serves until SIGTERM or Ctrl-C, then drains in-flight requests.
+pub async fn run_http_server(
+    settings: &AgentSettings,
+    bind: &str,
+    options: &ServeOptions,
+) -> Result<()> {
+    let started_at = Instant::now();
+    let _pid_file = options
+        .pid_file
+        .as_deref()
+        .map(PidFileGuard::create)
+        .transpose()?;
+    let listener = tokio::net::TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("failed to bind HTTP server to `{bind}`"))?;
+    let notify_stopping = options.daemon_ready_notify;
+    let shutdown = async move {
+        shutdown_signal().await;
+        if notify_stopping && let Err(error) = notify_supervisor("STOPPING=1") {
+            warn!(error = %error, "failed to report shutdown to supervisor");
+        }
+    };
+    serve_with_lifecycle(settings, listener, bind, options, shutdown).await?;
+    info!(
+        uptime_ms = started_at.elapsed().as_millis() as u64,
+        "HTTP server stopped"
+    );
+    Ok(())
+}
+
+/// Logs the effective guardrails and fails when they combine unsafely, unless
+/// `allow_unsafe` (`serve --allow-unsafe`) downgrades each problem to a warning.
+fn check_guardrails(
+    settings: &AgentSettings,
+    local_addr: Option<SocketAddr>,
+    allow_unsafe: bool,
+) -> Result<()> {
+    let guardrails = Guardrails::from_settings(settings);
+    info!(
+        tools = %settings.offered_tools().join(","),
+        max_steps = settings.max_steps,
+        max_tool_calls = settings.max_tool_calls,
+        max_input_chars = settings.max_input_chars,
+        max_output_chars = settings.max_output_chars,
+        tool_timeout_ms = settings.tool_timeout_ms,
+        fetch_url_allowed_domains = %guardrails.fetch_url_allowed_domains.join(","),
+        allowed_commands = %guardrails.allowed_commands.join(","),
+        save_note_allow_overwrite = guardrails.save_note_allow_overwrite,
+        save_note_allow_delete = guardrails.save_note_allow_delete,
+        notes_dir = %settings.notes_dir,
+        tool_working_dir = settings.tool_working_dir.as_deref(),
+        "effective guardrails"
+    );
+    let Some(local_addr) = local_addr else {
+        return Ok(());
+    };
+    let problems = guardrails.unsafe_combinations(local_addr);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if !allow_unsafe {
+        bail!(
+            "refusing to start with unsafe guardrails: {}; fix the configuration or pass --allow-unsafe",
+            problems.join("; ")
+        );
+    }
+    for problem in &problems {
+        warn!(problem = %problem, "starting with unsafe guardrails (--allow-unsafe)");
+    }
+    Ok(())
+}
+
+/// Why a `PATCH /admin/policy` leading to `candidate` is refused: the combinations `serve`
+/// refuses to start with, unless it was started with `--allow-unsafe`.
+fn unsafe_policy_change(
+    candidate: &AgentSettings,
+    local_addr: Option<SocketAddr>,
+    allow_unsafe: bool,
+) -> Option<String> {
+    let local_addr = local_addr.filter(|_| !allow_unsafe)?;
+    let problems = Guardrails::from_settings(candidate).unsafe_combinations(local_addr);
+    (!problems.is_empty()).then(|| {
+        format!(
+            "policy not changed; it would combine guardrails unsafely: {}; restart serve with --allow-unsafe to allow this",
+            problems.join("; ")
+        )
+    })
+}
+
+/// Serves the HTTP API on an already-bound listener until the task is dropped; used by
+/// `bench serve`.
+pub(crate) async fn serve_http_on_listener(
+    settings: &AgentSettings,
+    listener: tokio::net::TcpListener,
+    bind: &str,
+) -> Result<()> {
+    // Load tests bind a random loopback port and their mock model never calls tools.
+    let options = ServeOptions {
+        allow_unsafe: true,
+        ..ServeOptions::default()
+    };
+    serve_with_lifecycle(settings, listener, bind, &options, std::future::pending()).await
 }
 
-pub async fn run_http_server(settings: &AgentSettings, bind: &str) -> Result<()> {
+async fn serve_with_lifecycle(
+    settings: &AgentSettings,
+    listener: tokio::net::TcpListener,
+    bind: &str,
+    options: &ServeOptions,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let workspace_root =
+        std::env::current_dir().context("failed to resolve workspace root for HTTP server")?;
+    let policy = Arc::new(PolicyStore::from_settings(settings, &workspace_root));
+    let settings = &policy.apply_persisted(settings)?;
+    let local_addr = listener.local_addr().ok();
+    check_guardrails(settings, local_addr, options.allow_unsafe)?;
+    if options.daemon_ready_notify {
+        run_model_preflight(settings)
+            .await
+            .context("model provider preflight failed; not reporting readiness")?;
+    }
+    let models = Arc::new(ModelPool::new(settings));
+    let sessions = Arc::new(SessionStore::new(settings, models.http_client().clone()));
+    spawn_session_sweeper(
+        Arc::clone(&sessions),
+        settings.session_archive_dir.as_deref().map(PathBuf::from),
+    );
+    let scheduled_prompts = load_schedules(settings, &workspace_root)?;
+    let (graph_watch, graph) =
+        spawn_server_graph_watch(&tokio::runtime::Handle::current(), workspace_root);
+    let webhooks = WebhookNotifier::from_settings(settings)?;
+    let schedules = Arc::new(ScheduleBoard::new(&scheduled_prompts));
+    let schedule_count = scheduled_prompts.len();
+    let policy_file = policy.file().map(|path| path.display().to_string());
+    spawn_schedule_runners(
+        ScheduleRunContext {
+            models: Arc::clone(&models),
+            board: Arc::clone(&schedules),
+            graph_watch: graph_watch.clone(),
+            webhooks: webhooks.clone(),
+        },
+        scheduled_prompts,
+    );
     let state = AppState {
         settings: settings.clone(),
+        sessions,
+        graph,
+        graph_watch,
+        webhooks,
+        schedules,
+        models,
+        policy,
+        turn_errors: Arc::new(Mutex::new(BTreeMap::new())),
+        turns_by_user: Arc::new(Mutex::new(BTreeMap::new())),
+        local_addr,
+        allow_unsafe: options.allow_unsafe,
     };
     let app = Router::new()
         .route("/health", get(handle_health))
+        .route("/metrics", get(handle_metrics))
+        .route("/graph", get(handle_graph))
+        .route("/tools", get(handle_tools))
+        .route("/capabilities", get(handle_capabilities))
+        .route("/schedules", get(handle_schedules))
         .route("/chat", post(handle_chat))
+        .route("/chat/stream", post(handle_chat_stream))
+        .route("/sessions", get(handle_sessions))
+        .route(
+            "/sessions/:session_id/transcript",
+            get(handle_session_transcript),
+        )
+        .route("/admin/model", post(handle_admin_model))
+        .route(
+            "/admin/policy",
+            get(handle_admin_policy).patch(handle_admin_policy_patch),
+        )
         .with_state(state);
 
-    let listener = tokio::net::TcpListener::bind(bind)
-        .await
-        .with_context(|| format!("failed to bind HTTP server to `{bind}`"))?;
-    let local_addr = listener.local_addr().ok();
-
     info!(
+        pid = std::process::id(),
         provider = %settings.model_provider,
         model = %settings.model,
         requested_bind = %bind,
         bound_addr = local_addr.map(|addr| addr.to_string()),
+        session_idle_timeout_ms = settings.server_session_idle_timeout_ms,
+        session_max_messages = settings.server_session_max_messages,
+        max_sessions = settings.server_max_sessions,
+        webhook_urls = settings.server_webhook_urls.len(),
+        schedules = schedule_count,
+        policy_file,
         "starting HTTP server"
     );
 
+    if options.daemon_ready_notify {
+        let status = format!(
+            "READY=1\nMAINPID={}\nSTATUS=serving on {}",
+            std::process::id(),
+            local_addr.map_or_else(|| bind.to_owned(), |addr| addr.to_string())
+        );
+        match notify_supervisor(&status) {
+            Ok(true) => info!("reported readiness to supervisor"),
+            Ok(false) => warn!("NOTIFY_SOCKET is unset; --daemon-ready-notify has no supervisor"),
+            Err(error) => warn!(error = %error, "failed to report readiness to supervisor"),
+        }
+    }
+
     axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
         .await
         .context("HTTP server exited with an error")
 }
 
 async fn handle_health() -> Json<HealthBody> {
-    Json(HealthBody { status: "ok" })
+    Json(HealthBody {
+        status: "ok",
+        version: VERSION,
+        build: BuildInfo::current(),
+    })
+}
+
+async fn handle_tools() -> Json<ToolsBody> {
+    Json(ToolsBody {
+        tools: tool_schemas(),
+    })
+}
+
+async fn handle_capabilities(State(state): State<AppState>) -> Json<Capabilities> {
+    Json(Capabilities::from_settings(
+        &state.models.current().settings,
+    ))
+}
+
+/// Expires idle sessions; with `SESSION_ARCHIVE_DIR` set, each one is summarized and
+/// archived in the background.
+fn spawn_session_sweeper(sessions: Arc<SessionStore>, archive_dir: Option<PathBuf>) {
+    let sweep_interval = sessions.sweep_interval();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(sweep_interval);
+        loop {
+            ticker.tick().await;
+            let expired = sessions.sweep_expired(Instant::now());
+            if expired.is_empty() {
+                continue;
+            }
+            info!(
+                expired_sessions = expired.len(),
+                live_sessions = sessions.metrics().live_sessions,
+                "expired idle HTTP chat sessions"
+            );
+            let Some(archive_dir) = &archive_dir else {
+                continue;
+            };
+            for (session_id, session) in expired {
+                let archive_dir = archive_dir.clone();
+                tokio::spawn(async move {
+                    match session
+                        .lock()
+                        .await
+                        .archive(&archive_dir, &session_id)
+                        .await
+                    {
+                        Ok(Some(path)) => {
+                            info!(session_id, path = %path.display(), "archived expired HTTP chat session");
+                        }
+                        Ok(None) => {}
+                        Err(error) => warn!(
+                            session_id,
+                            error = %format!("{error:#}"),
+                            "failed to archive expired HTTP chat session"
+                        ),
+                    }
+                });
+            }
+        }
+    });
 }
 
-async fn handle_chat(State(state): State<AppState>, Json(req): Json<ChatRequest>) -> Response {
-    match run_chat_turn(&state.settings, &req.message).await {
-        Ok(outcome) => (StatusCode::OK, Json(outcome)).into_response(),
+async fn handle_metrics(State(state): State<AppState>) -> Json<MetricsBody> {
+    Json(MetricsBody {
+        sessions: state.sessions.metrics(),
+        turn_errors_total: state
+            .turn_errors
+            .lock()
+            .expect("turn error counter lock poisoned")
+            .clone(),
+        turns_total_by_user: state
+            .turns_by_user
+            .lock()
+            .expect("user turn counter lock poisoned")
+            .clone(),
+        http_client: state.models.http_client().metrics(),
+    })
+}
+
+async fn handle_schedules(State(state): State<AppState>) -> Json<SchedulesBody> {
+    Json(SchedulesBody {
+        schedules: state.schedules.snapshot(),
+    })
+}
+
+async fn handle_graph(State(state): State<AppState>, Query(query): Query<GraphQuery>) -> Response {
+    match state.graph.lookup(query.after_revision) {
+        GraphLookup::Updated(graph) => (StatusCode::OK, Json(GraphBody { graph })).into_response(),
+        GraphLookup::Unchanged => StatusCode::NO_CONTENT.into_response(),
+        GraphLookup::NotReady => {
+            let body = ErrorBody::new("workspace graph is not built yet; retry shortly".to_owned());
+            (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response()
+        }
+    }
+}
+
+/// `GET /sessions`: live session ids and the titles of archived sessions.
+async fn handle_sessions(State(state): State<AppState>) -> Response {
+    let archived = match state.settings.session_archive_dir.as_deref() {
+        Some(dir) => match list_archived_sessions(std::path::Path::new(dir)) {
+            Ok(archived) => archived,
+            Err(error) => {
+                return error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#}"));
+            }
+        },
+        None => Vec::new(),
+    };
+    Json(SessionsBody {
+        live: state.sessions.live_ids(),
+        archived,
+    })
+    .into_response()
+}
+
+/// `GET /sessions/{id}/transcript?format=markdown|html|json` (JSON by default). Waits for
+/// a turn in flight on that session and does not count as session activity. Expired
+/// sessions are served from `SESSION_ARCHIVE_DIR` when archived there.
+async fn handle_session_transcript(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<TranscriptQuery>,
+) -> Response {
+    let format = match query.format.as_deref().map(str::parse::<ExportFormat>) {
+        None => ExportFormat::Json,
+        Some(Ok(format)) => format,
+        Some(Err(error)) => return error_response(StatusCode::BAD_REQUEST, format!("{error}")),
+    };
+    let session = match state.sessions.get(&session_id) {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            let archived = state
+                .settings
+                .session_archive_dir
+                .as_deref()
+                .map(|dir| load_archived_transcript(std::path::Path::new(dir), &session_id));
+            return match archived {
+                Some(Ok(Some(transcript))) => (
+                    [(header::CONTENT_TYPE, format.content_type())],
+                    transcript.render(format),
+                )
+                    .into_response(),
+                Some(Err(error)) => {
+                    error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#}"))
+                }
+                Some(Ok(None)) | None => error_response(
+                    StatusCode::NOT_FOUND,
+                    format!("no session `{session_id}`; it may have expired"),
+                ),
+            };
+        }
         Err(error) => {
+            return error_response(status_code_for_session_error(&error), error.to_string());
+        }
+    };
+    let transcript = Transcript::from_conversation(
+        format!("Session {session_id}"),
+        session.lock().await.conversation(),
+    );
+    (
+        [(header::CONTENT_TYPE, format.content_type())],
+        transcript.render(format),
+    )
+        .into_response()
+}
+
+async fn handle_chat(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ChatRequest>,
+) -> Response {
+    let admin = is_admin_request(&state.settings, &headers);
+    let result = run_chat_request(&state, &req, admin, None).await;
+    let (status, reply) = chat_reply(&state, req.session_id, req.full, result);
+    (status, Json(reply)).into_response()
+}
+
+/// `POST /chat/stream`: runs the turn like `POST /chat`, reporting tool activity, the
+/// answer text, and the final outcome as Server-Sent Events.
+async fn handle_chat_stream(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ChatRequest>,
+) -> Response {
+    let admin = is_admin_request(&state.settings, &headers);
+    let (events_tx, events_rx) = unbounded_channel();
+    let tool_events_tx = events_tx.clone();
+    let sink: TurnEventSink = Arc::new(move |event| {
+        let _ = tool_events_tx.send(StreamEvent::from_turn_event(&event));
+    });
+    // Detached so a client that disconnects mid-turn still leaves a consistent session.
+    tokio::spawn(async move {
+        let result = run_chat_request(&state, &req, admin, Some(sink)).await;
+        if let Ok(outcome) = &result {
+            let _ = events_tx.send(StreamEvent::answer(&outcome.final_text));
+        }
+        let (status, reply) = chat_reply(&state, req.session_id, req.full, result);
+        let _ = events_tx.send(StreamEvent::finished(status, &reply));
+    });
+    sse_response(events_rx)
+}
+
+/// The parts of a `/chat` body that shape the turn beyond its message.
+struct ChatTurnRequest {
+    /// Active settings with the request's `model`/`provider` applied.
+    settings: AgentSettings,
+    model_override: ModelOverride,
+    user: Option<String>,
+}
+
+impl ChatTurnRequest {
+    /// Validates `user`, `model`, and `provider` against the active `settings`. Without
+    /// the admin token (`admin`), a turn may only switch to a `SERVER_CHAT_MODEL_ALLOWLIST`
+    /// entry, since it runs on the server's provider keys.
+    fn resolve(
+        req: &ChatRequest,
+        settings: &AgentSettings,
+        admin: bool,
+    ) -> Result<Self, ChatHandlerError> {
+        let user = req
+            .user
+            .as_deref()
+            .map(parse_user)
+            .transpose()
+            .map_err(ChatHandlerError::InvalidRequest)?;
+        let model_override = ModelOverride::parse(req.provider.as_deref(), req.model.as_deref())
+            .map_err(ChatHandlerError::InvalidRequest)?;
+        let resolved = model_override
+            .resolve(settings)
+            .map_err(ChatHandlerError::InvalidRequest)?;
+        let unchanged =
+            resolved.model_provider == settings.model_provider && resolved.model == settings.model;
+        let target = format!("{}/{}", resolved.model_provider, resolved.model);
+        if !admin && !unchanged && !settings.server_chat_model_allowlist.contains(&target) {
+            return Err(ChatHandlerError::Forbidden(format!(
+                "model override `{target}` is not in SERVER_CHAT_MODEL_ALLOWLIST; list it there or send the admin bearer token"
+            )));
+        }
+        Ok(Self {
+            settings: resolved,
+            model_override,
+            user,
+        })
+    }
+}
+
+/// Runs one `/chat` or `/chat/stream` turn, then refreshes the served graph and notifies
+/// webhooks.
+async fn run_chat_request(
+    state: &AppState,
+    req: &ChatRequest,
+    admin: bool,
+    events: Option<TurnEventSink>,
+) -> std::result::Result<ChatTurnOutcome, ChatHandlerError> {
+    let started_at = Instant::now();
+    // Held for the whole turn so a concurrent model swap only affects later turns.
+    let active = state.models.current();
+    let result = match (
+        ChatTurnRequest::resolve(req, &active.settings, admin),
+        req.session_id.as_deref(),
+    ) {
+        (Err(error), _) => Err(error),
+        (Ok(turn), Some(session_id)) => {
+            run_session_chat_turn(
+                state,
+                &active.settings,
+                session_id,
+                &req.message,
+                turn,
+                events,
+            )
+            .await
+        }
+        (Ok(turn), None) => {
+            let mut session =
+                ChatSession::with_http_client(&turn.settings, state.models.http_client().clone())
+                    .with_user(turn.user);
+            if let Some(history) = TurnHistory::from_settings(&turn.settings) {
+                session = session.with_history(history, HistorySource::Server);
+            }
+            session.set_event_sink(events);
+            session
+                .chat_turn(&req.message)
+                .await
+                .map_err(ChatHandlerError::Turn)
+        }
+    };
+    if matches!(result, Ok(_) | Err(ChatHandlerError::Turn(_))) {
+        // Turns may have edited workspace files; refresh the served graph.
+        state.graph_watch.notify_turn_completed();
+        if let Some(user) = req.user.as_deref() {
+            count_user_turn(
+                &mut state
+                    .turns_by_user
+                    .lock()
+                    .expect("user turn counter lock poisoned"),
+                user.trim(),
+            );
+        }
+    }
+    if let Some(webhooks) = &state.webhooks
+        && let Some((status, error_kind)) = turn_outcome_status(&result)
+    {
+        webhooks.notify(TurnOutcomeEvent {
+            turn_id: webhooks.next_turn_id(),
+            status,
+            latency_ms: started_at.elapsed().as_millis() as u64,
+            error_kind,
+            session_id: req.session_id.clone(),
+            schedule: None,
+            finished_at_ms: unix_time_ms(SystemTime::now()),
+        });
+    }
+    result
+}
+
+/// Status and body for a chat turn result, shared by `/chat` and the final
+/// `/chat/stream` event.
+fn chat_reply(
+    state: &AppState,
+    session_id: Option<String>,
+    full: bool,
+    result: std::result::Result<ChatTurnOutcome, ChatHandlerError>,
+) -> (StatusCode, ChatReply) {
+    match result {
+        Ok(outcome) => {
+            let max_field_chars =
+                (!full).then_some(state.settings.json_output_max_field_chars as usize);
+            match outcome_json(&outcome, max_field_chars) {
+                Ok(outcome) => (
+                    StatusCode::OK,
+                    ChatReply::Outcome(ChatResponseBody {
+                        outcome,
+                        session_id,
+                    }),
+                ),
+                Err(error) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ChatReply::Error(ErrorBody::new(format!(
+                        "failed to encode chat turn outcome: {error}"
+                    ))),
+                ),
+            }
+        }
+        Err(ChatHandlerError::Session(error)) => {
+            let status = status_code_for_session_error(&error);
+            warn!(
+                status = status.as_u16(),
+                error = %error,
+                "HTTP chat session request rejected"
+            );
+            (status, ChatReply::Error(ErrorBody::new(error.to_string())))
+        }
+        Err(ChatHandlerError::InvalidRequest(error)) => {
+            warn!(error = %error, "HTTP chat request rejected");
+            (
+                StatusCode::BAD_REQUEST,
+                ChatReply::Error(ErrorBody::new(error)),
+            )
+        }
+        Err(ChatHandlerError::Forbidden(error)) => {
+            warn!(error = %error, "HTTP chat request refused");
+            (
+                StatusCode::FORBIDDEN,
+                ChatReply::Error(ErrorBody::new(error)),
+            )
+        }
+        Err(ChatHandlerError::Turn(error)) => {
             let details = error_details(&error);
-            let status = status_code_for_error_kind(error.kind());
+            let code = error.code();
+            let status = status_code_for_error(code);
             warn!(
                 status = status.as_u16(),
+                code = code.as_str(),
                 error = %details,
                 "HTTP chat request failed"
             );
-            let body = ErrorBody { error: details };
-            (status, Json(body)).into_response()
+            *state
+                .turn_errors
+                .lock()
+                .expect("turn error counter lock poisoned")
+                .entry(code)
+                .or_insert(0) += 1;
+            let body = ErrorBody {
+                error: details,
+                code: Some(code),
+                moderation: error.moderation_decision().cloned(),
+            };
+            (status, ChatReply::Error(body))
+        }
+    }
+}
+
+/// `POST /admin/model`: checks the provider lists the model, then makes it the model
+/// for every later turn. Disabled (`404`) without `SERVER_ADMIN_TOKEN`.
+async fn handle_admin_model(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Some(response) = reject_unauthorized_admin(&state.settings, &headers) {
+        return response;
+    }
+    let request = match serde_json::from_slice::<ModelSwapRequest>(&body) {
+        Ok(request) => request,
+        Err(error) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                format!("invalid model swap request: {error}"),
+            );
+        }
+    };
+    let candidate = match candidate_settings(&state.models.current().settings, &request) {
+        Ok(candidate) => candidate,
+        Err(error) => return error_response(StatusCode::BAD_REQUEST, error),
+    };
+    match ModelClient::with_http_client(candidate.clone(), state.models.http_client().clone())
+        .list_models()
+        .await
+    {
+        Ok(available) if model_is_listed(&available, &candidate.model) => {}
+        Ok(_) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "model `{}` is not listed by {}",
+                    candidate.model, candidate.model_provider
+                ),
+            );
+        }
+        Err(error) => {
+            return error_response(
+                StatusCode::BAD_GATEWAY,
+                format!("{} is unreachable: {error}", candidate.model_provider),
+            );
+        }
+    }
+
+    let swapped = state.models.swap(&candidate);
+    info!(
+        provider = swapped.active.provider,
+        model = %swapped.active.model,
+        previous_provider = swapped.previous.provider,
+        previous_model = %swapped.previous.model,
+        generation = swapped.generation,
+        "swapped active model"
+    );
+    (StatusCode::OK, Json(swapped)).into_response()
+}
+
+/// `GET /admin/policy`: every runtime-adjustable setting in env syntax.
+async fn handle_admin_policy(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(response) = reject_unauthorized_admin(&state.settings, &headers) {
+        return response;
+    }
+    let active = state.models.current();
+    let body = serde_json::json!({
+        "policy": active.settings.override_values(),
+        "generation": active.generation,
+        "persisted_to": state.policy.file().map(|path| path.display().to_string()),
+    });
+    (StatusCode::OK, Json(body)).into_response()
+}
+
+/// `PATCH /admin/policy`: validates `set`, persists it to `SERVER_POLICY_FILE`, installs
+/// it for later turns, and writes one audit entry.
+async fn handle_admin_policy_patch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Some(response) = reject_unauthorized_admin(&state.settings, &headers) {
+        return response;
+    }
+    let request = match serde_json::from_slice::<PolicyPatchRequest>(&body) {
+        Ok(request) => request,
+        Err(error) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                format!("invalid policy request: {error}"),
+            );
+        }
+    };
+    let overrides = match parse_policy_patch(&request) {
+        Ok(overrides) => overrides,
+        Err(error) => return error_response(StatusCode::BAD_REQUEST, error),
+    };
+
+    let _patch_guard = state
+        .policy
+        .patch_lock
+        .lock()
+        .expect("policy patch lock poisoned");
+    let current = state.models.current();
+    let mut candidate = current.settings.clone();
+    for (_, setting) in &overrides {
+        candidate.apply_override(setting);
+    }
+    let changes = policy_changes(&current.settings, &candidate);
+    if changes.is_empty() {
+        let body = serde_json::json!({
+            "changes": changes,
+            "generation": current.generation,
+            "persisted": false,
+        });
+        return (StatusCode::OK, Json(body)).into_response();
+    }
+    if let Some(error) = unsafe_policy_change(&candidate, state.local_addr, state.allow_unsafe) {
+        warn!(error = %error, "refused admin policy change");
+        return error_response(StatusCode::BAD_REQUEST, error);
+    }
+    let persisted = match state.policy.persist(&candidate, &changes) {
+        Ok(persisted) => persisted,
+        Err(error) => {
+            warn!(error = %format!("{error:#}"), "failed to persist policy change");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("policy not changed; failed to persist it: {error:#}"),
+            );
         }
+    };
+    let (_, active) = state.models.update(|settings| {
+        for (_, setting) in &overrides {
+            settings.apply_override(setting);
+        }
+    });
+
+    let reason = request
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty());
+    for change in &changes {
+        info!(
+            setting = %change.setting,
+            before = %change.before,
+            after = %change.after,
+            reason,
+            generation = active.generation,
+            "admin policy change"
+        );
+    }
+    let audit = PolicyAuditEntry {
+        at_ms: unix_time_ms(SystemTime::now()),
+        generation: active.generation,
+        changes: &changes,
+        reason,
+        persisted,
+    };
+    if let Err(error) = state.policy.append_audit(&audit) {
+        warn!(error = %format!("{error:#}"), "failed to append policy audit entry");
+    }
+    let body = serde_json::json!({
+        "policy": active.settings.override_values(),
+        "changes": changes,
+        "generation": active.generation,
+        "persisted": persisted,
+    });
+    (StatusCode::OK, Json(body)).into_response()
+}
+
+/// Admin routes answer `404` without `SERVER_ADMIN_TOKEN` and `401` without its bearer.
+fn reject_unauthorized_admin(settings: &AgentSettings, headers: &HeaderMap) -> Option<Response> {
+    let Some(token) = settings.server_admin_token.as_deref() else {
+        return Some(error_response(
+            StatusCode::NOT_FOUND,
+            "admin endpoints are disabled; set SERVER_ADMIN_TOKEN".to_owned(),
+        ));
+    };
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !is_authorized(authorization, token) {
+        warn!("rejected admin request without a valid bearer token");
+        return Some(error_response(
+            StatusCode::UNAUTHORIZED,
+            "missing or invalid bearer token".to_owned(),
+        ));
+    }
+    None
+}
+
+/// The request carries the `SERVER_ADMIN_TOKEN` bearer token.
+fn is_admin_request(settings: &AgentSettings, headers: &HeaderMap) -> bool {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    settings
+        .server_admin_token
+        .as_deref()
+        .is_some_and(|token| is_authorized(authorization, token))
+}
+
+fn error_response(status: StatusCode, error: String) -> Response {
+    (status, Json(ErrorBody::new(error))).into_response()
+}
+
+enum ChatHandlerError {
+    Session(SessionStoreError),
+    /// The request's `user`, `model`, or `provider` cannot be used; the turn did not run.
+    InvalidRequest(String),
+    /// The request's model override needs the admin token; the turn did not run.
+    Forbidden(String),
+    Turn(ChatTurnError),
+}
+
+/// Webhook status for a `/chat` result; `None` when a session or request error kept the
+/// turn from running.
+fn turn_outcome_status(
+    result: &std::result::Result<ChatTurnOutcome, ChatHandlerError>,
+) -> Option<(TurnOutcomeStatus, Option<&'static str>)> {
+    match result {
+        Ok(_) => Some((TurnOutcomeStatus::Completed, None)),
+        Err(ChatHandlerError::Turn(error)) => {
+            Some((TurnOutcomeStatus::Failed, Some(error.kind().as_str())))
+        }
+        Err(
+            ChatHandlerError::Session(_)
+            | ChatHandlerError::InvalidRequest(_)
+            | ChatHandlerError::Forbidden(_),
+        ) => None,
+    }
+}
+
+async fn run_session_chat_turn(
+    state: &AppState,
+    settings: &AgentSettings,
+    session_id: &str,
+    message: &str,
+    turn: ChatTurnRequest,
+    events: Option<TurnEventSink>,
+) -> std::result::Result<ChatTurnOutcome, ChatHandlerError> {
+    let session = state
+        .sessions
+        .checkout(session_id, settings, Instant::now())
+        .map_err(ChatHandlerError::Session)?;
+    let mut session = session.lock().await;
+    if session.refresh_settings(settings) {
+        info!(
+            session_id,
+            provider = %settings.model_provider,
+            model = %settings.model,
+            "moved HTTP chat session to the latest server settings"
+        );
+    }
+    // The override only lasts for this turn; later turns of the session use the active model.
+    session
+        .set_model_override(Some(turn.model_override))
+        .map_err(ChatHandlerError::InvalidRequest)?;
+    session.set_user(turn.user);
+    session.set_event_sink(events);
+    let result = session.chat_turn(message).await;
+    session.set_event_sink(None);
+    let _ = session.set_model_override(None);
+    let dropped = session.trim_history(state.sessions.max_messages());
+    if dropped > 0 {
+        info!(
+            session_id,
+            dropped_messages = dropped,
+            retained_messages = session.history_len(),
+            "trimmed HTTP chat session history"
+        );
+    }
+    drop(session);
+    state.sessions.touch(session_id, Instant::now());
+    result.map_err(ChatHandlerError::Turn)
+}
+
+fn status_code_for_session_error(error: &SessionStoreError) -> StatusCode {
+    match error {
+        SessionStoreError::InvalidSessionId { .. } => StatusCode::BAD_REQUEST,
+        SessionStoreError::CapacityReached { .. } => StatusCode::SERVICE_UNAVAILABLE,
     }
 }
 
@@ -83,42 +1043,166 @@ fn error_details(error: &ChatTurnError) -> String {
     error.details()
 }
 
-fn status_code_for_error_kind(kind: ChatTurnErrorKind) -> StatusCode {
-    match kind {
+/// Adds a turn for `user`, or for `other` once `MAX_COUNTED_USERS` users are counted.
+fn count_user_turn(counts: &mut BTreeMap<String, u64>, user: &str) {
+    let key = if counts.contains_key(user) || counts.len() < MAX_COUNTED_USERS {
+        user
+    } else {
+        OTHER_USERS_KEY
+    };
+    *counts.entry(key.to_owned()).or_insert(0) += 1;
+}
+
+fn status_code_for_error(code: ErrorCode) -> StatusCode {
+    match code.kind() {
         ChatTurnErrorKind::BadRequest => StatusCode::BAD_REQUEST,
         ChatTurnErrorKind::Upstream => StatusCode::BAD_GATEWAY,
         ChatTurnErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        ChatTurnErrorKind::Blocked => StatusCode::UNPROCESSABLE_ENTITY,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use axum::http::StatusCode;
 
-    use super::status_code_for_error_kind;
-    use crate::agent::ChatTurnErrorKind;
+    use super::sessions::SessionStoreError;
+    use super::{
+        ChatHandlerError, ChatRequest, ChatTurnRequest, ErrorBody, MAX_COUNTED_USERS,
+        count_user_turn, status_code_for_error, status_code_for_session_error,
+        unsafe_policy_change,
+    };
+    use crate::agent::ErrorCode;
+    use crate::agent::tests::test_settings;
+    use crate::config::SettingsOverride;
 
     #[test]
-    fn status_code_classifies_bad_request_kind() {
+    fn user_turn_counts_group_users_past_the_cap_under_other() {
+        let mut counts = BTreeMap::new();
+        count_user_turn(&mut counts, "other");
+        for index in 1..MAX_COUNTED_USERS {
+            count_user_turn(&mut counts, &format!("user-{index}"));
+        }
+        count_user_turn(&mut counts, "other");
+        count_user_turn(&mut counts, "late-1");
+        count_user_turn(&mut counts, "late-2");
+        assert_eq!(counts.len(), MAX_COUNTED_USERS + 1);
         assert_eq!(
-            status_code_for_error_kind(ChatTurnErrorKind::BadRequest),
+            counts["other"], 2,
+            "a user named `other` keeps its own counter"
+        );
+        assert_eq!(counts["<other>"], 2);
+        assert!(!counts.contains_key("late-1"));
+    }
+
+    #[test]
+    fn policy_changes_are_held_to_the_startup_guardrail_check() {
+        let loopback = Some("127.0.0.1:8080".parse().expect("address"));
+        let mut candidate = test_settings();
+        candidate.tool_working_dir = None;
+        candidate.notes_dir = "notes".to_owned();
+        assert_eq!(unsafe_policy_change(&candidate, loopback, false), None);
+
+        candidate.apply_override(&SettingsOverride::SaveNoteAllowDelete(true));
+        let error = unsafe_policy_change(&candidate, loopback, false).expect("refused");
+        assert!(error.contains("TOOL_WORKING_DIR is unset"), "{error}");
+        assert_eq!(unsafe_policy_change(&candidate, loopback, true), None);
+        assert_eq!(unsafe_policy_change(&candidate, None, false), None);
+    }
+
+    #[test]
+    fn model_overrides_need_the_allowlist_or_the_admin_token() {
+        let mut settings = test_settings();
+        settings.openai_api_key = Some("sk-test".to_owned());
+        settings.server_chat_model_allowlist = vec!["ollama/llama3".to_owned()];
+        let request = |provider: Option<&str>, model: &str| ChatRequest {
+            provider: provider.map(str::to_owned),
+            model: Some(model.to_owned()),
+            ..ChatRequest::new("hi")
+        };
+
+        let allowed = ChatTurnRequest::resolve(&request(None, "llama3"), &settings, false)
+            .unwrap_or_else(|_| panic!("allowlisted override"));
+        assert_eq!(allowed.settings.model, "llama3");
+        let active = request(None, &settings.model);
+        assert!(ChatTurnRequest::resolve(&active, &settings, false).is_ok());
+
+        let hosted = request(Some("openai"), "gpt-4o");
+        let Err(ChatHandlerError::Forbidden(error)) =
+            ChatTurnRequest::resolve(&hosted, &settings, false)
+        else {
+            panic!("unlisted override should be forbidden");
+        };
+        assert!(error.contains("`openai/gpt-4o`"), "{error}");
+        assert!(ChatTurnRequest::resolve(&hosted, &settings, true).is_ok());
+    }
+
+    #[test]
+    fn status_code_classifies_bad_request_codes() {
+        assert_eq!(
+            status_code_for_error(ErrorCode::GuardrailToolCap),
             StatusCode::BAD_REQUEST
         );
     }
 
     #[test]
-    fn status_code_classifies_upstream_kind() {
+    fn status_code_classifies_upstream_codes() {
         assert_eq!(
-            status_code_for_error_kind(ChatTurnErrorKind::Upstream),
+            status_code_for_error(ErrorCode::UpstreamModel),
             StatusCode::BAD_GATEWAY
         );
     }
 
     #[test]
-    fn status_code_classifies_internal_kind() {
+    fn status_code_classifies_internal_codes() {
         assert_eq!(
-            status_code_for_error_kind(ChatTurnErrorKind::Internal),
+            status_code_for_error(ErrorCode::ToolTimeout),
             StatusCode::INTERNAL_SERVER_ERROR
         );
     }
+
+    #[test]
+    fn status_code_classifies_blocked_codes() {
+        assert_eq!(
+            status_code_for_error(ErrorCode::ModerationBlocked),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[test]
+    fn error_body_carries_the_code_only_for_turn_errors() {
+        let turn = ErrorBody {
+            error: "agent stopped after reaching max_steps=8".to_owned(),
+            code: Some(ErrorCode::GuardrailMaxSteps),
+            moderation: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&turn).expect("serialize"),
+            serde_json::json!({
+                "error": "agent stopped after reaching max_steps=8",
+                "code": "E_GUARDRAIL_MAX_STEPS",
+            })
+        );
+        let other = ErrorBody::new("unauthorized".to_owned());
+        assert_eq!(
+            serde_json::to_value(&other).expect("serialize"),
+            serde_json::json!({ "error": "unauthorized" })
+        );
+    }
+
+    #[test]
+    fn status_code_classifies_session_errors() {
+        assert_eq!(
+            status_code_for_session_error(&SessionStoreError::InvalidSessionId {
+                session_id: "bad id".to_owned(),
+            }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status_code_for_session_error(&SessionStoreError::CapacityReached { max_sessions: 1 }),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
 }
@@ -0,0 +1,214 @@
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::ModelOverride;
+use crate::config::AgentSettings;
+use crate::http::SharedHttpClient;
+
+/// Settings each new turn runs with. `POST /admin/model` and `PATCH /admin/policy`
+/// install new generations; turns already running keep the `Arc` they started with, so
+/// they finish on the old client and policy.
+#[derive(Debug)]
+pub(super) struct ModelPool {
+    active: RwLock<Arc<ActiveModel>>,
+    /// Built once at startup and kept across swaps, so every turn, session, and schedule
+    /// reuses the same pooled (keep-alive, HTTP/2) provider connections.
+    http_client: SharedHttpClient,
+}
+
+#[derive(Debug)]
+pub(super) struct ActiveModel {
+    pub settings: AgentSettings,
+    /// Starts at 1 and grows with every model swap or policy change.
+    pub generation: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct ModelSwapRequest {
+    /// Defaults to the active provider.
+    #[serde(default)]
+    pub provider: Option<String>,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(super) struct ModelSelection {
+    pub provider: &'static str,
+    pub model: String,
+}
+
+impl ModelSelection {
+    fn of(settings: &AgentSettings) -> Self {
+        Self {
+            provider: settings.model_provider.as_str(),
+            model: settings.model.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct ModelSwapBody {
+    pub active: ModelSelection,
+    pub previous: ModelSelection,
+    pub generation: u64,
+}
+
+impl ModelPool {
+    pub fn new(settings: &AgentSettings) -> Self {
+        Self {
+            active: RwLock::new(Arc::new(ActiveModel {
+                settings: settings.clone(),
+                generation: 1,
+            })),
+            http_client: SharedHttpClient::from_settings(settings),
+        }
+    }
+
+    pub fn http_client(&self) -> &SharedHttpClient {
+        &self.http_client
+    }
+
+    pub fn current(&self) -> Arc<ActiveModel> {
+        Arc::clone(&self.active.read().expect("model pool lock poisoned"))
+    }
+
+    /// Installs the provider and model of `candidate` (from `candidate_settings`) as the
+    /// next generation.
+    pub fn swap(&self, candidate: &AgentSettings) -> ModelSwapBody {
+        let (previous, active) = self.update(|settings| {
+            settings.model_provider = candidate.model_provider;
+            settings.model = candidate.model.clone();
+        });
+        ModelSwapBody {
+            active: ModelSelection::of(&active.settings),
+            previous: ModelSelection::of(&previous.settings),
+            generation: active.generation,
+        }
+    }
+
+    /// Applies `change` to a copy of the latest settings and installs it as the next
+    /// generation. Returns the previous and the new generation.
+    pub fn update(
+        &self,
+        change: impl FnOnce(&mut AgentSettings),
+    ) -> (Arc<ActiveModel>, Arc<ActiveModel>) {
+        let mut active = self.active.write().expect("model pool lock poisoned");
+        let mut settings = active.settings.clone();
+        change(&mut settings);
+        let next = Arc::new(ActiveModel {
+            settings,
+            generation: active.generation + 1,
+        });
+        let previous = std::mem::replace(&mut *active, Arc::clone(&next));
+        (previous, next)
+    }
+}
+
+/// Settings for `request` on top of `current`, or a client-facing reason it is invalid.
+pub(super) fn candidate_settings(
+    current: &AgentSettings,
+    request: &ModelSwapRequest,
+) -> Result<AgentSettings, String> {
+    ModelOverride::parse(request.provider.as_deref(), Some(&request.model))?.resolve(current)
+}
+
+/// Whether `authorization` carries `Bearer <token>`, compared in constant time.
+pub(super) fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let Some(presented) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let (presented, token) = (presented.trim().as_bytes(), token.as_bytes());
+    presented.len() == token.len()
+        && presented
+            .iter()
+            .zip(token)
+            .fold(0_u8, |diff, (left, right)| diff | (left ^ right))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{ModelPool, ModelSwapRequest, candidate_settings, is_authorized};
+    use crate::agent::tests::test_settings;
+    use crate::config::ModelProvider;
+
+    fn request(provider: Option<&str>, model: &str) -> ModelSwapRequest {
+        ModelSwapRequest {
+            provider: provider.map(str::to_owned),
+            model: model.to_owned(),
+        }
+    }
+
+    #[test]
+    fn swap_installs_a_new_generation_without_touching_held_snapshots() {
+        let settings = test_settings();
+        let pool = ModelPool::new(&settings);
+        let in_flight = pool.current();
+
+        let candidate =
+            candidate_settings(&in_flight.settings, &request(None, " llama3 ")).expect("valid");
+        let swapped = pool.swap(&candidate);
+
+        assert_eq!(swapped.generation, 2);
+        assert_eq!(swapped.previous.model, settings.model);
+        assert_eq!(swapped.active.model, "llama3");
+        assert_eq!(in_flight.settings.model, settings.model);
+        assert_eq!(in_flight.generation, 1);
+        assert_eq!(pool.current().settings.model, "llama3");
+        assert!(!Arc::ptr_eq(&in_flight, &pool.current()));
+    }
+
+    #[test]
+    fn candidate_settings_validate_provider_model_and_credentials() {
+        let mut settings = test_settings();
+        settings.model_provider = ModelProvider::Ollama;
+        settings.openai_api_key = None;
+
+        assert!(candidate_settings(&settings, &request(Some("anthropic"), "x")).is_err());
+        assert!(candidate_settings(&settings, &request(None, "  ")).is_err());
+        let error = candidate_settings(&settings, &request(Some("openai"), "gpt-4o-mini"))
+            .expect_err("openai without a key should be rejected");
+        assert!(error.contains("OPENAI_API_KEY"));
+
+        settings.openai_api_key = Some("sk-test".to_owned());
+        let candidate = candidate_settings(&settings, &request(Some("OpenAI"), "gpt-4o-mini"))
+            .expect("openai with a key should be accepted");
+        assert_eq!(candidate.model_provider, ModelProvider::OpenAi);
+        assert_eq!(candidate.model, "gpt-4o-mini");
+
+        settings.gemini_api_key = None;
+        let error = candidate_settings(&settings, &request(Some("gemini"), "gemini-2.0-flash"))
+            .expect_err("gemini without a key should be rejected");
+        assert!(error.contains("GEMINI_API_KEY"));
+        settings.gemini_api_key = Some("gemini-test".to_owned());
+        settings.offline = true;
+        let error = candidate_settings(&settings, &request(Some("gemini"), "gemini-2.0-flash"))
+            .expect_err("gemini is unavailable offline");
+        assert!(error.contains("AGENT_OFFLINE"));
+
+        settings.offline = false;
+        let error = candidate_settings(&settings, &request(Some("compatible"), "qwen2.5"))
+            .expect_err("compatible without a base URL should be rejected");
+        assert!(error.contains("OPENAI_COMPAT_BASE_URL"));
+        settings.offline = true;
+        settings.openai_compat_base_url = Some("http://127.0.0.1:8000/v1".to_owned());
+        let candidate = candidate_settings(&settings, &request(Some("compatible"), "qwen2.5"))
+            .expect("a local compatible server stays available offline");
+        assert_eq!(candidate.model_provider, ModelProvider::Compatible);
+        settings.openai_compat_base_url = Some("https://openrouter.ai/api/v1".to_owned());
+        assert!(candidate_settings(&settings, &request(Some("compatible"), "qwen2.5")).is_err());
+    }
+
+    #[test]
+    fn authorization_requires_the_exact_bearer_token() {
+        assert!(is_authorized(Some("Bearer s3cret"), "s3cret"));
+        assert!(!is_authorized(Some("Bearer s3cre"), "s3cret"));
+        assert!(!is_authorized(Some("Bearer s3creT"), "s3cret"));
+        assert!(!is_authorized(Some("s3cret"), "s3cret"));
+        assert!(!is_authorized(None, "s3cret"));
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{AgentSettings, SettingsOverride};
+
+const POLICY_FILE_HEADER: &str =
+    "# Written by `serve` (PATCH /admin/policy). Applied over the environment at startup.\n";
+
+/// `PATCH /admin/policy` body: env-style setting names mapped to new values.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct PolicyPatchRequest {
+    pub set: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(super) struct PolicyChange {
+    pub setting: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// One line of `SERVER_POLICY_AUDIT_FILE`.
+#[derive(Debug, Serialize)]
+pub(super) struct PolicyAuditEntry<'a> {
+    pub at_ms: u64,
+    pub generation: u64,
+    pub changes: &'a [PolicyChange],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'a str>,
+    pub persisted: bool,
+}
+
+/// Parses every `set` entry with the same rules the environment uses. Values may be JSON
+/// strings, numbers, booleans, or string arrays (joined with `,`).
+pub(super) fn parse_policy_patch(
+    request: &PolicyPatchRequest,
+) -> Result<Vec<(String, SettingsOverride)>, String> {
+    if request.set.is_empty() {
+        return Err("`set` must name at least one setting".to_owned());
+    }
+    request
+        .set
+        .iter()
+        .map(|(name, value)| {
+            let raw = match value {
+                Value::String(raw) => raw.clone(),
+                Value::Number(number) => number.to_string(),
+                Value::Bool(flag) => flag.to_string(),
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().map(str::to_owned))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| format!("{name} must be a list of strings"))?
+                    .join(","),
+                _ => return Err(format!("{name} must be a string, number, boolean, or list")),
+            };
+            let setting =
+                SettingsOverride::parse(name, &raw).map_err(|error| format!("{error:#}"))?;
+            Ok((name.clone(), setting))
+        })
+        .collect()
+}
+
+/// Settings whose env-style value differs between `before` and `after`.
+pub(super) fn policy_changes(before: &AgentSettings, after: &AgentSettings) -> Vec<PolicyChange> {
+    let after_values = after.override_values();
+    before
+        .override_values()
+        .into_iter()
+        .filter_map(|(name, before)| {
+            let after = after_values.get(name)?;
+            (before != *after).then(|| PolicyChange {
+                setting: name.to_owned(),
+                before,
+                after: after.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Where policy changes are persisted and audited. Also serializes `PATCH /admin/policy`
+/// so concurrent changes cannot interleave their file writes.
+#[derive(Debug)]
+pub(super) struct PolicyStore {
+    file: Option<PathBuf>,
+    audit_file: Option<PathBuf>,
+    pub patch_lock: Mutex<()>,
+}
+
+impl PolicyStore {
+    /// Resolves `SERVER_POLICY_FILE` and `SERVER_POLICY_AUDIT_FILE` against `workspace_root`.
+    pub fn from_settings(settings: &AgentSettings, workspace_root: &Path) -> Self {
+        Self {
+            file: settings
+                .server_policy_file
+                .as_deref()
+                .map(|path| workspace_root.join(path)),
+            audit_file: settings
+                .server_policy_audit_file
+                .as_deref()
+                .map(|path| workspace_root.join(path)),
+            patch_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    /// `settings` with the persisted overrides applied; unchanged when there is no file yet.
+    pub fn apply_persisted(&self, settings: &AgentSettings) -> Result<AgentSettings> {
+        let mut settings = settings.clone();
+        let Some(path) = self.file.as_deref() else {
+            return Ok(settings);
+        };
+        for (name, raw) in read_policy_entries(path)? {
+            let setting = SettingsOverride::parse(&name, &raw).with_context(|| {
+                format!("invalid {name} in SERVER_POLICY_FILE {}", path.display())
+            })?;
+            settings.apply_override(&setting);
+        }
+        Ok(settings)
+    }
+
+    /// Writes the current value of each changed setting into the policy file, keeping
+    /// entries from earlier changes. Returns `false` without `SERVER_POLICY_FILE`.
+    pub fn persist(&self, settings: &AgentSettings, changes: &[PolicyChange]) -> Result<bool> {
+        let Some(path) = self.file.as_deref() else {
+            return Ok(false);
+        };
+        let mut entries = read_policy_entries(path)?;
+        let values = settings.override_values();
+        for change in changes {
+            if let Some(value) = values.get(change.setting.as_str()) {
+                entries.insert(change.setting.clone(), value.clone());
+            }
+        }
+        let mut contents = POLICY_FILE_HEADER.to_owned();
+        for (name, value) in &entries {
+            contents.push_str(&format!("{name}={value}\n"));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        // Write-then-rename so a crash never leaves a half-written policy file.
+        let staging = path.with_extension("tmp");
+        fs::write(&staging, contents)
+            .with_context(|| format!("failed to write {}", staging.display()))?;
+        fs::rename(&staging, path)
+            .with_context(|| format!("failed to replace {}", path.display()))?;
+        Ok(true)
+    }
+
+    /// Appends `entry` as one JSON line; a no-op without `SERVER_POLICY_AUDIT_FILE`.
+    pub fn append_audit(&self, entry: &PolicyAuditEntry<'_>) -> Result<()> {
+        let Some(path) = self.audit_file.as_deref() else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_string(entry).context("failed to encode audit entry")?;
+        line.push('\n');
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("failed to append to {}", path.display()))
+    }
+}
+
+fn read_policy_entries(path: &Path) -> Result<BTreeMap<String, String>> {
+    let iter = match dotenvy::from_path_iter(path) {
+        Ok(iter) => iter,
+        Err(dotenvy::Error::Io(error)) if error.kind() == ErrorKind::NotFound => {
+            return Ok(BTreeMap::new());
+        }
+        Err(error) => {
+            return Err(error)
+                .with_context(|| format!("failed to read SERVER_POLICY_FILE {}", path.display()));
+        }
+    };
+    iter.map(|entry| {
+        entry.with_context(|| format!("failed to parse SERVER_POLICY_FILE {}", path.display()))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use super::{
+        PolicyAuditEntry, PolicyPatchRequest, PolicyStore, parse_policy_patch, policy_changes,
+    };
+    use crate::agent::tests::test_settings;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    fn patch(set: serde_json::Value) -> PolicyPatchRequest {
+        serde_json::from_value(json!({ "set": set })).expect("patch should decode")
+    }
+
+    #[test]
+    fn patch_values_use_env_parsing_rules() {
+        let parsed = parse_policy_patch(&patch(json!({
+            "AGENT_MAX_STEPS": 3,
+            "FETCH_URL_ALLOWED_DOMAINS": ["Docs.rs", "example.com"],
+            "FETCH_URL_FOLLOW_REDIRECTS": false,
+            "ENABLED_TOOLS": "search_notes",
+        })))
+        .expect("patch should parse");
+        let mut settings = test_settings();
+        for (_, setting) in &parsed {
+            settings.apply_override(setting);
+        }
+
+        assert_eq!(settings.max_steps, 3);
+        assert_eq!(
+            settings.fetch_url_allowed_domains,
+            vec!["docs.rs".to_owned(), "example.com".to_owned()]
+        );
+        assert!(!settings.fetch_url_follow_redirects);
+        assert_eq!(settings.enabled_tools, vec!["search_notes".to_owned()]);
+
+        for set in [
+            json!({}),
+            json!({"AGENT_MAX_STEPS": 0}),
+            json!({"MODEL": "llama3"}),
+            json!({"ENABLED_TOOLS": ["shell"]}),
+            json!({"AGENT_MAX_STEPS": {"value": 3}}),
+        ] {
+            assert!(parse_policy_patch(&patch(set.clone())).is_err(), "{set}");
+        }
+    }
+
+    #[test]
+    fn persisted_policy_round_trips_and_audit_appends_lines() {
+        let dir = temp_path("policy-store");
+        let mut settings = test_settings();
+        settings.server_policy_file = Some("policy.env".to_owned());
+        settings.server_policy_audit_file = Some("audit/policy.jsonl".to_owned());
+        let store = PolicyStore::from_settings(&settings, &dir);
+        assert_eq!(
+            store
+                .apply_persisted(&settings)
+                .expect("missing file is fine"),
+            settings
+        );
+
+        let mut tightened = settings.clone();
+        tightened.max_tool_calls = 1;
+        tightened.enabled_tools = Vec::new();
+        let changes = policy_changes(&settings, &tightened);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.setting.as_str())
+                .collect::<Vec<_>>(),
+            vec!["AGENT_MAX_TOOL_CALLS", "ENABLED_TOOLS"]
+        );
+        assert!(store.persist(&tightened, &changes).expect("persist"));
+        assert_eq!(store.apply_persisted(&settings).expect("reload"), tightened);
+
+        for _ in 0..2 {
+            store
+                .append_audit(&PolicyAuditEntry {
+                    at_ms: 1,
+                    generation: 2,
+                    changes: &changes,
+                    reason: Some("incident 42"),
+                    persisted: true,
+                })
+                .expect("audit");
+        }
+        let audit = fs::read_to_string(dir.join("audit/policy.jsonl")).expect("audit file");
+        let lines = audit.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let entry = serde_json::from_str::<serde_json::Value>(lines[0]).expect("json line");
+        assert_eq!(entry["changes"][1]["after"], "none");
+        assert_eq!(entry["reason"], "incident 42");
+
+        let entries = fs::read_to_string(dir.join("policy.env"))
+            .expect("policy file")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec!["AGENT_MAX_TOOL_CALLS=1", "ENABLED_TOOLS=none"]
+        );
+        remove_dir_if_exists(&dir);
+    }
+}
@@ -0,0 +1,639 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result, bail, ensure};
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime, Time};
+use tracing::{info, warn};
+
+use super::models::ModelPool;
+use super::webhooks::{TurnOutcomeEvent, TurnOutcomeStatus, WebhookNotifier, unix_time_ms};
+use crate::agent::{ChatSession, HistorySource, TurnHistory};
+use crate::config::AgentSettings;
+use crate::digest::{MAX_DIGEST_DAYS, run_digest};
+use crate::graph::watch::GraphWatchHandle;
+use crate::text::truncate_graphemes;
+
+const MAX_SCHEDULE_NAME_CHARS: usize = 64;
+const ANSWER_PREVIEW_CHARS: usize = 200;
+/// `next_after` gives up after this many days; covers leap-day-only schedules.
+const MAX_CRON_SEARCH_DAYS: u32 = 5 * 366;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScheduleFile {
+    schedules: Vec<ScheduleFileEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScheduleFileEntry {
+    name: String,
+    cron: String,
+    #[serde(default)]
+    prompt: Option<String>,
+    #[serde(default)]
+    digest_days: Option<u32>,
+}
+
+/// One `SERVER_SCHEDULE_FILE` entry: `task` runs whenever `cron` fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ScheduledPrompt {
+    pub name: String,
+    pub cron: CronSchedule,
+    pub task: ScheduledTask,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ScheduledTask {
+    /// A one-shot chat turn.
+    Prompt(String),
+    /// A notes digest over the last `days` days, saved as a dated note.
+    Digest { days: u32 },
+}
+
+/// Five-field cron expression (`minute hour day-of-month month day-of-week`), evaluated
+/// in UTC. Fields accept `*`, numbers, `a-b` ranges, `,` lists, and `/n` steps; day of
+/// week is 0-7 with both 0 and 7 meaning Sunday. As in Vixie cron, when both day
+/// fields are restricted a day matching either one fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        let expanded = match expression {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields = expanded.split_whitespace().collect::<Vec<_>>();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            bail!("cron expression `{expression}` must have 5 fields or be a @macro");
+        };
+
+        let mut days_of_week = parse_cron_field(day_of_week, "day-of-week", 0, 7)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+        let schedule = Self {
+            expression: expression.to_owned(),
+            minutes: parse_cron_field(minute, "minute", 0, 59)?,
+            hours: parse_cron_field(hour, "hour", 0, 23)?,
+            days_of_month: parse_cron_field(day_of_month, "day-of-month", 1, 31)?,
+            months: parse_cron_field(month, "month", 1, 12)?,
+            days_of_week,
+            day_of_month_restricted: !day_of_month.starts_with('*'),
+            day_of_week_restricted: !day_of_week.starts_with('*'),
+        };
+        ensure!(
+            schedule.next_after(OffsetDateTime::UNIX_EPOCH).is_some(),
+            "cron expression `{expression}` never fires"
+        );
+        Ok(schedule)
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// First whole minute strictly after `after` that matches, or `None` when nothing
+    /// matches within `MAX_CRON_SEARCH_DAYS`.
+    pub fn next_after(&self, after: OffsetDateTime) -> Option<OffsetDateTime> {
+        let after = after.to_offset(time::UtcOffset::UTC);
+        let start =
+            after.replace_second(0).ok()?.replace_nanosecond(0).ok()? + time::Duration::minutes(1);
+        let mut date = start.date();
+        for day in 0..MAX_CRON_SEARCH_DAYS {
+            if self.matches_date(date) {
+                let earliest = if day == 0 {
+                    start.time()
+                } else {
+                    Time::MIDNIGHT
+                };
+                if let Some(time) = self.first_time_from(earliest) {
+                    return Some(date.with_time(time).assume_utc());
+                }
+            }
+            date = date.next_day()?;
+        }
+        None
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        if !has_bit(self.months, u8::from(date.month())) {
+            return false;
+        }
+        let day_of_month = has_bit(self.days_of_month, date.day());
+        let day_of_week = has_bit(self.days_of_week, date.weekday().number_days_from_sunday());
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+
+    fn first_time_from(&self, earliest: Time) -> Option<Time> {
+        for hour in earliest.hour()..24 {
+            if !has_bit(self.hours, hour) {
+                continue;
+            }
+            let first_minute = if hour == earliest.hour() {
+                earliest.minute()
+            } else {
+                0
+            };
+            if let Some(minute) = (first_minute..60).find(|&minute| has_bit(self.minutes, minute)) {
+                return Time::from_hms(hour, minute, 0).ok();
+            }
+        }
+        None
+    }
+}
+
+fn has_bit(mask: u64, value: u8) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Bitmask of the values `field` selects within `min..=max`.
+fn parse_cron_field(field: &str, label: &str, min: u8, max: u8) -> Result<u64> {
+    let mut mask = 0_u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .with_context(|| format!("invalid {label} step in `{field}`"))?;
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_cron_value(start, label, min, max)?,
+                parse_cron_value(end, label, min, max)?,
+            )
+        } else {
+            let value = parse_cron_value(range, label, min, max)?;
+            // `5/15` means "from 5 to the end, every 15".
+            (value, if step.is_some() { max } else { value })
+        };
+        ensure!(start <= end, "{label} range `{range}` is reversed");
+        for value in (start..=end).step_by(usize::from(step.unwrap_or(1))) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_cron_value(raw: &str, label: &str, min: u8, max: u8) -> Result<u8> {
+    raw.parse::<u8>()
+        .ok()
+        .filter(|value| (min..=max).contains(value))
+        .with_context(|| format!("{label} value `{raw}` must be a number in {min}-{max}"))
+}
+
+/// Parses `{"schedules": [{"name", "cron", "prompt" | "digest_days"}, ...]}`.
+pub(super) fn parse_schedule_json(raw: &str) -> Result<Vec<ScheduledPrompt>> {
+    let file = serde_json::from_str::<ScheduleFile>(raw)
+        .context("failed to parse schedule file as JSON")?;
+    let mut schedules = Vec::<ScheduledPrompt>::with_capacity(file.schedules.len());
+    for (index, entry) in file.schedules.into_iter().enumerate() {
+        let name = entry.name.trim();
+        ensure!(
+            !name.is_empty()
+                && name.chars().count() <= MAX_SCHEDULE_NAME_CHARS
+                && name
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_'),
+            "schedules[{index}].name `{name}` must be 1-{MAX_SCHEDULE_NAME_CHARS} ASCII letters, digits, `-`, or `_`"
+        );
+        ensure!(
+            schedules.iter().all(|schedule| schedule.name != name),
+            "schedules[{index}].name `{name}` is used more than once"
+        );
+        let task = match (entry.prompt.as_deref().map(str::trim), entry.digest_days) {
+            (Some(prompt), None) => {
+                ensure!(
+                    !prompt.is_empty(),
+                    "schedules[{index}].prompt must not be empty"
+                );
+                ScheduledTask::Prompt(prompt.to_owned())
+            }
+            (None, Some(days)) => {
+                ensure!(
+                    (1..=MAX_DIGEST_DAYS).contains(&days),
+                    "schedules[{index}].digest_days must be between 1 and {MAX_DIGEST_DAYS}"
+                );
+                ScheduledTask::Digest { days }
+            }
+            _ => bail!("schedules[{index}] must set exactly one of `prompt` and `digest_days`"),
+        };
+        let cron = CronSchedule::parse(&entry.cron)
+            .with_context(|| format!("schedules[{index}] (`{name}`) has an invalid cron"))?;
+        schedules.push(ScheduledPrompt {
+            name: name.to_owned(),
+            cron,
+            task,
+        });
+    }
+    Ok(schedules)
+}
+
+/// Loads `SERVER_SCHEDULE_FILE`, resolving relative paths against `workspace_root`.
+/// Returns no schedules when the variable is unset.
+pub(super) fn load_schedules(
+    settings: &AgentSettings,
+    workspace_root: &Path,
+) -> Result<Vec<ScheduledPrompt>> {
+    let Some(path) = settings.server_schedule_file.as_deref() else {
+        return Ok(Vec::new());
+    };
+    let path = workspace_root.join(path);
+    fs::read_to_string(&path)
+        .context("failed to read file")
+        .and_then(|raw| parse_schedule_json(&raw))
+        .with_context(|| {
+            format!(
+                "failed to load SERVER_SCHEDULE_FILE from {}",
+                path.display()
+            )
+        })
+}
+
+/// Last-run status of one schedule, as served by `GET /schedules`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(super) struct ScheduleStatus {
+    pub name: String,
+    pub cron: String,
+    /// Unix time in milliseconds; `None` while a run is in progress.
+    pub next_run_at_ms: Option<u64>,
+    pub running: bool,
+    pub run_count: u64,
+    pub failure_count: u64,
+    pub last_run: Option<ScheduledRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(super) struct ScheduledRun {
+    pub started_at_ms: u64,
+    pub finished_at_ms: u64,
+    pub latency_ms: u64,
+    pub status: TurnOutcomeStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// First characters of the final answer of a completed run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer_preview: Option<String>,
+}
+
+/// Per-schedule status shared between the runner tasks and `GET /schedules`, in file order.
+#[derive(Debug, Default)]
+pub(super) struct ScheduleBoard {
+    statuses: RwLock<Vec<ScheduleStatus>>,
+}
+
+impl ScheduleBoard {
+    pub fn new(schedules: &[ScheduledPrompt]) -> Self {
+        let statuses = schedules
+            .iter()
+            .map(|schedule| ScheduleStatus {
+                name: schedule.name.clone(),
+                cron: schedule.cron.expression().to_owned(),
+                next_run_at_ms: None,
+                running: false,
+                run_count: 0,
+                failure_count: 0,
+                last_run: None,
+            })
+            .collect();
+        Self {
+            statuses: RwLock::new(statuses),
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<ScheduleStatus> {
+        self.statuses
+            .read()
+            .expect("schedule board lock poisoned")
+            .clone()
+    }
+
+    fn update(&self, name: &str, apply: impl FnOnce(&mut ScheduleStatus)) {
+        let mut statuses = self.statuses.write().expect("schedule board lock poisoned");
+        if let Some(status) = statuses.iter_mut().find(|status| status.name == name) {
+            apply(status);
+        }
+    }
+
+    fn record_next_run(&self, name: &str, next_run_at: Option<OffsetDateTime>) {
+        self.update(name, |status| {
+            status.next_run_at_ms = next_run_at.map(|at| unix_time_ms(SystemTime::from(at)));
+        });
+    }
+
+    fn record_started(&self, name: &str) {
+        self.update(name, |status| {
+            status.running = true;
+            status.next_run_at_ms = None;
+        });
+    }
+
+    fn record_finished(&self, name: &str, run: ScheduledRun) {
+        self.update(name, |status| {
+            status.running = false;
+            status.run_count += 1;
+            if run.status == TurnOutcomeStatus::Failed {
+                status.failure_count += 1;
+            }
+            status.last_run = Some(run);
+        });
+    }
+}
+
+/// Everything a schedule runner needs from the server besides its own entry.
+#[derive(Clone)]
+pub(super) struct ScheduleRunContext {
+    pub models: Arc<ModelPool>,
+    pub board: Arc<ScheduleBoard>,
+    pub graph_watch: GraphWatchHandle,
+    pub webhooks: Option<Arc<WebhookNotifier>>,
+}
+
+/// Spawns one task per schedule. A task sleeps until its next fire time, runs the
+/// prompt or digest, and only then computes the following fire time, so runs of one schedule
+/// never overlap and fires missed during a slow run are skipped.
+pub(super) fn spawn_schedule_runners(context: ScheduleRunContext, schedules: Vec<ScheduledPrompt>) {
+    for schedule in schedules {
+        let context = context.clone();
+        tokio::spawn(async move {
+            loop {
+                let now = OffsetDateTime::now_utc();
+                let next_run_at = schedule.cron.next_after(now);
+                context.board.record_next_run(&schedule.name, next_run_at);
+                let Some(next_run_at) = next_run_at else {
+                    warn!(schedule = %schedule.name, "schedule has no future runs; stopping");
+                    return;
+                };
+                let wait = Duration::try_from(next_run_at - now).unwrap_or_default();
+                tokio::time::sleep(wait).await;
+                run_scheduled_prompt(&context, &schedule).await;
+            }
+        });
+    }
+}
+
+async fn run_scheduled_prompt(context: &ScheduleRunContext, schedule: &ScheduledPrompt) {
+    context.board.record_started(&schedule.name);
+    let started_at = Instant::now();
+    let started_at_ms = unix_time_ms(SystemTime::now());
+    info!(schedule = %schedule.name, "running scheduled prompt");
+
+    let active = context.models.current();
+    let result = match &schedule.task {
+        ScheduledTask::Prompt(prompt) => {
+            let mut session = ChatSession::with_http_client(
+                &active.settings,
+                context.models.http_client().clone(),
+            );
+            if let Some(history) = TurnHistory::from_settings(&active.settings) {
+                session = session.with_history(history, HistorySource::Schedule);
+            }
+            session
+                .chat_turn(prompt)
+                .await
+                .map(|outcome| outcome.final_text)
+                .map_err(|error| (error.kind().as_str(), error.details()))
+        }
+        ScheduledTask::Digest { days } => run_digest(
+            &active.settings,
+            *days,
+            context.models.http_client().clone(),
+            true,
+        )
+        .await
+        .map(|outcome| match outcome {
+            Some(outcome) => outcome.text,
+            None => format!("No notes changed in the last {days} day(s); no digest written."),
+        })
+        .map_err(|error| (error.kind().as_str(), error.to_string())),
+    };
+    // Scheduled turns may edit workspace files just like `/chat` turns.
+    context.graph_watch.notify_turn_completed();
+
+    let latency_ms = started_at.elapsed().as_millis() as u64;
+    let finished_at_ms = unix_time_ms(SystemTime::now());
+    let run = match &result {
+        Ok(answer) => ScheduledRun {
+            started_at_ms,
+            finished_at_ms,
+            latency_ms,
+            status: TurnOutcomeStatus::Completed,
+            error_kind: None,
+            error: None,
+            answer_preview: Some(truncate_graphemes(answer, ANSWER_PREVIEW_CHARS, "")),
+        },
+        Err((error_kind, error)) => {
+            warn!(
+                schedule = %schedule.name,
+                error = %error,
+                "scheduled prompt failed"
+            );
+            ScheduledRun {
+                started_at_ms,
+                finished_at_ms,
+                latency_ms,
+                status: TurnOutcomeStatus::Failed,
+                error_kind: Some(error_kind),
+                error: Some(error.clone()),
+                answer_preview: None,
+            }
+        }
+    };
+    if let Some(webhooks) = &context.webhooks {
+        webhooks.notify(TurnOutcomeEvent {
+            turn_id: webhooks.next_turn_id(),
+            status: run.status,
+            latency_ms,
+            error_kind: run.error_kind,
+            session_id: None,
+            schedule: Some(schedule.name.clone()),
+            finished_at_ms,
+        });
+    }
+    info!(
+        schedule = %schedule.name,
+        latency_ms,
+        status = ?run.status,
+        "finished scheduled prompt"
+    );
+    context.board.record_finished(&schedule.name, run);
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Month, OffsetDateTime, Time};
+
+    use super::{CronSchedule, ScheduleBoard, ScheduledRun, ScheduledTask, parse_schedule_json};
+    use crate::server::webhooks::TurnOutcomeStatus;
+
+    fn utc(year: i32, month: Month, day: u8, hour: u8, minute: u8) -> OffsetDateTime {
+        Date::from_calendar_date(year, month, day)
+            .expect("valid date")
+            .with_time(Time::from_hms(hour, minute, 0).expect("valid time"))
+            .assume_utc()
+    }
+
+    fn next(expression: &str, after: OffsetDateTime) -> OffsetDateTime {
+        CronSchedule::parse(expression)
+            .expect("cron should parse")
+            .next_after(after)
+            .expect("cron should fire")
+    }
+
+    #[test]
+    fn next_after_finds_the_following_matching_minute() {
+        let after = utc(2026, Month::March, 14, 2, 0);
+        assert_eq!(next("0 2 * * *", after), utc(2026, Month::March, 15, 2, 0));
+        assert_eq!(next("@daily", after), utc(2026, Month::March, 15, 0, 0));
+        assert_eq!(
+            next("*/15 * * * *", after),
+            utc(2026, Month::March, 14, 2, 15)
+        );
+        assert_eq!(
+            next("5/20 9-10 * * *", after),
+            utc(2026, Month::March, 14, 9, 5)
+        );
+        assert_eq!(next("0 0 1 */3 *", after), utc(2026, Month::April, 1, 0, 0));
+        // 2026-03-14 is a Saturday; 7 and 0 both mean Sunday.
+        assert_eq!(
+            next("30 8 * * 7", after),
+            utc(2026, Month::March, 15, 8, 30)
+        );
+        assert_eq!(next("@weekly", after), utc(2026, Month::March, 15, 0, 0));
+        assert_eq!(
+            next("0 0 29 2 *", after),
+            utc(2028, Month::February, 29, 0, 0)
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either_day() {
+        // The 20th, or any Monday: Monday 2026-03-16 comes first.
+        let after = utc(2026, Month::March, 14, 12, 0);
+        assert_eq!(next("0 6 20 * 1", after), utc(2026, Month::March, 16, 6, 0));
+        assert_eq!(next("0 6 20 * *", after), utc(2026, Month::March, 20, 6, 0));
+    }
+
+    #[test]
+    fn cron_rejects_malformed_or_impossible_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "10-5 * * * *",
+            "*/0 * * * *",
+            "a * * * *",
+            "@yearly",
+            "0 0 30 2 *",
+        ] {
+            assert!(CronSchedule::parse(expression).is_err(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn schedule_file_validates_names_and_prompts() {
+        let schedules = parse_schedule_json(
+            r#"{"schedules": [
+                {"name": "nightly-notes", "cron": "0 2 * * *", "prompt": " Summarize new notes. "}
+            ]}"#,
+        )
+        .expect("schedule file should parse");
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].name, "nightly-notes");
+        assert_eq!(
+            schedules[0].task,
+            ScheduledTask::Prompt("Summarize new notes.".to_owned())
+        );
+        assert_eq!(schedules[0].cron.expression(), "0 2 * * *");
+
+        let schedules = parse_schedule_json(
+            r#"{"schedules": [{"name": "weekly-digest", "cron": "0 18 * * 5", "digest_days": 7}]}"#,
+        )
+        .expect("digest schedule should parse");
+        assert_eq!(schedules[0].task, ScheduledTask::Digest { days: 7 });
+
+        for raw in [
+            r#"{"schedules": [{"name": "bad name", "cron": "@daily", "prompt": "hi"}]}"#,
+            r#"{"schedules": [{"name": "a", "cron": "@daily", "prompt": "  "}]}"#,
+            r#"{"schedules": [{"name": "a", "cron": "@daily", "prompt": "hi"}, {"name": "a", "cron": "@hourly", "prompt": "hi"}]}"#,
+            r#"{"schedules": [{"name": "a", "cron": "@daily", "prompt": "hi", "model": "x"}]}"#,
+            r#"{"schedules": [{"name": "a", "cron": "0 0 * *", "prompt": "hi"}]}"#,
+            r#"{"schedules": [{"name": "a", "cron": "@daily"}]}"#,
+            r#"{"schedules": [{"name": "a", "cron": "@daily", "prompt": "hi", "digest_days": 1}]}"#,
+            r#"{"schedules": [{"name": "a", "cron": "@daily", "digest_days": 0}]}"#,
+        ] {
+            assert!(parse_schedule_json(raw).is_err(), "{raw}");
+        }
+    }
+
+    #[test]
+    fn board_tracks_runs_and_failures() {
+        let schedules = parse_schedule_json(
+            r#"{"schedules": [{"name": "hourly", "cron": "@hourly", "prompt": "hi"}]}"#,
+        )
+        .expect("schedule file should parse");
+        let board = ScheduleBoard::new(&schedules);
+        board.record_next_run("hourly", Some(OffsetDateTime::UNIX_EPOCH));
+        assert_eq!(board.snapshot()[0].next_run_at_ms, Some(0));
+
+        board.record_started("hourly");
+        let status = &board.snapshot()[0];
+        assert!(status.running);
+        assert_eq!(status.next_run_at_ms, None);
+
+        board.record_finished(
+            "hourly",
+            ScheduledRun {
+                started_at_ms: 1,
+                finished_at_ms: 2,
+                latency_ms: 1,
+                status: TurnOutcomeStatus::Failed,
+                error_kind: Some("upstream"),
+                error: Some("provider unavailable".to_owned()),
+                answer_preview: None,
+            },
+        );
+        let status = &board.snapshot()[0];
+        assert!(!status.running);
+        assert_eq!((status.run_count, status.failure_count), (1, 1));
+        assert_eq!(
+            status.last_run.as_ref().map(|run| run.status),
+            Some(TurnOutcomeStatus::Failed)
+        );
+    }
+}
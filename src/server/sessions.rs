@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::agent::{ChatSession, HistorySource, TurnHistory};
+use crate::config::AgentSettings;
+use crate::http::SharedHttpClient;
+
+const MAX_SESSION_ID_CHARS: usize = 64;
+const MIN_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum SessionStoreError {
+    #[error(
+        "invalid session_id `{session_id}`; expected 1-{MAX_SESSION_ID_CHARS} ASCII letters, digits, `-`, or `_`"
+    )]
+    InvalidSessionId { session_id: String },
+    #[error("session capacity reached (SERVER_MAX_SESSIONS={max_sessions}); retry later")]
+    CapacityReached { max_sessions: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub(crate) struct SessionMetrics {
+    pub live_sessions: usize,
+    pub max_sessions: usize,
+    pub sessions_created_total: u64,
+    pub sessions_expired_total: u64,
+}
+
+struct SessionSlot {
+    session: Arc<AsyncMutex<ChatSession>>,
+    last_active: Instant,
+}
+
+/// In-memory store for HTTP chat sessions keyed by client-provided ids.
+pub(crate) struct SessionStore {
+    slots: Mutex<HashMap<String, SessionSlot>>,
+    idle_timeout: Duration,
+    max_sessions: usize,
+    max_messages: usize,
+    sessions_created_total: AtomicU64,
+    sessions_expired_total: AtomicU64,
+    /// Shared by every session so their turns reuse pooled provider connections.
+    http_client: SharedHttpClient,
+}
+
+impl SessionStore {
+    pub(crate) fn new(settings: &AgentSettings, http_client: SharedHttpClient) -> Self {
+        Self {
+            slots: Mutex::new(HashMap::new()),
+            idle_timeout: Duration::from_millis(settings.server_session_idle_timeout_ms),
+            max_sessions: settings.server_max_sessions as usize,
+            max_messages: settings.server_session_max_messages as usize,
+            sessions_created_total: AtomicU64::new(0),
+            sessions_expired_total: AtomicU64::new(0),
+            http_client,
+        }
+    }
+
+    pub(crate) fn max_messages(&self) -> usize {
+        self.max_messages
+    }
+
+    /// Sweep cadence: a quarter of the idle timeout, clamped to 1s..=60s.
+    pub(crate) fn sweep_interval(&self) -> Duration {
+        (self.idle_timeout / 4).clamp(MIN_SWEEP_INTERVAL, MAX_SWEEP_INTERVAL)
+    }
+
+    /// Returns the session for `session_id`, creating it when absent. New sessions record
+    /// their turns to `AGENT_HISTORY_FILE`, chained within the session.
+    pub(crate) fn checkout(
+        &self,
+        session_id: &str,
+        settings: &AgentSettings,
+        now: Instant,
+    ) -> Result<Arc<AsyncMutex<ChatSession>>, SessionStoreError> {
+        validate_session_id(session_id)?;
+        let mut slots = self.slots.lock().expect("session store lock poisoned");
+
+        if let Some(slot) = slots.get_mut(session_id) {
+            slot.last_active = now;
+            return Ok(Arc::clone(&slot.session));
+        }
+
+        if slots.len() >= self.max_sessions {
+            return Err(SessionStoreError::CapacityReached {
+                max_sessions: self.max_sessions,
+            });
+        }
+
+        let mut session = ChatSession::with_http_client(settings, self.http_client.clone());
+        if let Some(history) = TurnHistory::from_settings(settings) {
+            session = session.with_history(history, HistorySource::Server);
+        }
+        let session = Arc::new(AsyncMutex::new(session));
+        slots.insert(
+            session_id.to_owned(),
+            SessionSlot {
+                session: Arc::clone(&session),
+                last_active: now,
+            },
+        );
+        self.sessions_created_total.fetch_add(1, Ordering::Relaxed);
+        Ok(session)
+    }
+
+    /// Returns an existing session without creating it or extending its idle time.
+    pub(crate) fn get(
+        &self,
+        session_id: &str,
+    ) -> Result<Option<Arc<AsyncMutex<ChatSession>>>, SessionStoreError> {
+        validate_session_id(session_id)?;
+        let slots = self.slots.lock().expect("session store lock poisoned");
+        Ok(slots.get(session_id).map(|slot| Arc::clone(&slot.session)))
+    }
+
+    /// Marks a session active after a turn so long turns do not count as idle time.
+    pub(crate) fn touch(&self, session_id: &str, now: Instant) {
+        let mut slots = self.slots.lock().expect("session store lock poisoned");
+        if let Some(slot) = slots.get_mut(session_id) {
+            slot.last_active = now;
+        }
+    }
+
+    /// Drops sessions idle past the timeout and returns them with their ids, so they can
+    /// be archived; sessions with a turn in flight are kept.
+    pub(crate) fn sweep_expired(
+        &self,
+        now: Instant,
+    ) -> Vec<(String, Arc<AsyncMutex<ChatSession>>)> {
+        let mut slots = self.slots.lock().expect("session store lock poisoned");
+        let expired_ids = slots
+            .iter()
+            .filter(|(_, slot)| {
+                Arc::strong_count(&slot.session) == 1
+                    && now.saturating_duration_since(slot.last_active) >= self.idle_timeout
+            })
+            .map(|(session_id, _)| session_id.clone())
+            .collect::<Vec<_>>();
+        let expired = expired_ids
+            .into_iter()
+            .filter_map(|session_id| {
+                let slot = slots.remove(&session_id)?;
+                Some((session_id, slot.session))
+            })
+            .collect::<Vec<_>>();
+        self.sessions_expired_total
+            .fetch_add(expired.len() as u64, Ordering::Relaxed);
+        expired
+    }
+
+    /// Ids of the live sessions, sorted.
+    pub(crate) fn live_ids(&self) -> Vec<String> {
+        let slots = self.slots.lock().expect("session store lock poisoned");
+        let mut ids = slots.keys().cloned().collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    pub(crate) fn metrics(&self) -> SessionMetrics {
+        let live_sessions = self
+            .slots
+            .lock()
+            .expect("session store lock poisoned")
+            .len();
+        SessionMetrics {
+            live_sessions,
+            max_sessions: self.max_sessions,
+            sessions_created_total: self.sessions_created_total.load(Ordering::Relaxed),
+            sessions_expired_total: self.sessions_expired_total.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn validate_session_id(session_id: &str) -> Result<(), SessionStoreError> {
+    let valid = !session_id.is_empty()
+        && session_id.chars().count() <= MAX_SESSION_ID_CHARS
+        && session_id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(SessionStoreError::InvalidSessionId {
+            session_id: session_id.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use crate::config::AgentSettings;
+    use crate::http::SharedHttpClient;
+
+    use super::{SessionStore, SessionStoreError};
+
+    #[test]
+    fn checkout_reuses_existing_session_and_counts_creations() {
+        let settings = test_settings(60_000, 4);
+        let store = SessionStore::new(&settings, SharedHttpClient::default());
+        let now = Instant::now();
+
+        assert!(store.get("abc").expect("valid id").is_none());
+        let first = store.checkout("abc", &settings, now).expect("checkout");
+        let second = store.checkout("abc", &settings, now).expect("checkout");
+
+        assert!(Arc::ptr_eq(&first, &second));
+        let existing = store.get("abc").expect("valid id").expect("session exists");
+        assert!(Arc::ptr_eq(&first, &existing));
+        let metrics = store.metrics();
+        assert_eq!(metrics.live_sessions, 1);
+        assert_eq!(metrics.sessions_created_total, 1);
+    }
+
+    #[test]
+    fn checked_out_sessions_record_to_the_turn_history() {
+        let settings = AgentSettings {
+            history_file: Some(
+                std::env::temp_dir()
+                    .join("mjolne-vibes-session-history-missing.jsonl")
+                    .display()
+                    .to_string(),
+            ),
+            ..test_settings(60_000, 4)
+        };
+        let store = SessionStore::new(&settings, SharedHttpClient::default());
+        let session = store
+            .checkout("abc", &settings, Instant::now())
+            .expect("checkout");
+        let mut session = session.try_lock().expect("unlocked");
+
+        let error = session.resume_from("unknown").expect_err("no such turn");
+        assert!(!error.to_string().contains("AGENT_HISTORY_FILE is not set"));
+    }
+
+    #[test]
+    fn checkout_rejects_invalid_ids_and_capacity_overflow() {
+        let settings = test_settings(60_000, 1);
+        let store = SessionStore::new(&settings, SharedHttpClient::default());
+        let now = Instant::now();
+
+        assert!(matches!(
+            store.checkout("bad id", &settings, now),
+            Err(SessionStoreError::InvalidSessionId { .. })
+        ));
+        store.checkout("one", &settings, now).expect("checkout");
+        assert_eq!(
+            store.checkout("two", &settings, now).err(),
+            Some(SessionStoreError::CapacityReached { max_sessions: 1 })
+        );
+    }
+
+    #[test]
+    fn sweep_expired_drops_idle_sessions_but_keeps_in_use_ones() {
+        let settings = test_settings(1_000, 4);
+        let store = SessionStore::new(&settings, SharedHttpClient::default());
+        let start = Instant::now();
+
+        let held = store.checkout("busy", &settings, start).expect("checkout");
+        store.checkout("idle", &settings, start).expect("checkout");
+
+        let expired = store.sweep_expired(start + Duration::from_millis(1_500));
+
+        assert_eq!(
+            expired
+                .iter()
+                .map(|(session_id, _)| session_id.as_str())
+                .collect::<Vec<_>>(),
+            ["idle"]
+        );
+        let metrics = store.metrics();
+        assert_eq!(metrics.live_sessions, 1);
+        assert_eq!(metrics.sessions_expired_total, 1);
+        assert_eq!(store.live_ids(), ["busy"]);
+        drop(held);
+        assert_eq!(
+            store
+                .sweep_expired(start + Duration::from_millis(1_500))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn sweep_interval_is_clamped() {
+        let store = SessionStore::new(&test_settings(1_000, 4), SharedHttpClient::default());
+        assert_eq!(store.sweep_interval(), Duration::from_secs(1));
+        let store = SessionStore::new(&test_settings(3_600_000, 4), SharedHttpClient::default());
+        assert_eq!(store.sweep_interval(), Duration::from_secs(60));
+    }
+
+    fn test_settings(idle_timeout_ms: u64, max_sessions: u32) -> AgentSettings {
+        AgentSettings {
+            server_session_idle_timeout_ms: idle_timeout_ms,
+            server_max_sessions: max_sessions,
+            ..crate::agent::tests::test_settings()
+        }
+    }
+}
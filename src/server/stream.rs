@@ -0,0 +1,165 @@
+use std::convert::Infallible;
+
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::agent::TurnEvent;
+
+/// One `POST /chat/stream` event: the SSE `event:` name and its JSON `data:`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct StreamEvent {
+    pub name: &'static str,
+    pub data: Value,
+}
+
+impl StreamEvent {
+    /// `tool_started`, `tool_progress`, or `tool_finished` for a live tool call.
+    pub fn from_turn_event(event: &TurnEvent) -> Self {
+        match event {
+            TurnEvent::ToolStarted {
+                tool_call_index,
+                step,
+                tool_name,
+                arguments,
+            } => Self {
+                name: "tool_started",
+                data: json!({
+                    "tool_call_index": tool_call_index,
+                    "step": step,
+                    "tool_name": tool_name,
+                    "arguments": arguments,
+                }),
+            },
+            TurnEvent::ToolProgress {
+                tool_call_index,
+                tool_name,
+                progress,
+            } => Self {
+                name: "tool_progress",
+                data: json!({
+                    "tool_call_index": tool_call_index,
+                    "tool_name": tool_name,
+                    "elapsed_ms": progress.elapsed.as_millis() as u64,
+                    "bytes_received": progress.bytes_received,
+                    "bytes_total": progress.bytes_total,
+                }),
+            },
+            TurnEvent::ToolFinished {
+                tool_call_index,
+                tool_name,
+                latency,
+                succeeded,
+            } => Self {
+                name: "tool_finished",
+                data: json!({
+                    "tool_call_index": tool_call_index,
+                    "tool_name": tool_name,
+                    "latency_ms": latency.as_millis() as u64,
+                    "succeeded": succeeded,
+                }),
+            },
+        }
+    }
+
+    /// The whole final answer, after moderation, format repair, and post-processing.
+    pub fn answer(text: &str) -> Self {
+        Self {
+            name: "answer",
+            data: json!({ "text": text }),
+        }
+    }
+
+    /// The last event of a stream: `done` with the `POST /chat` body on success, or
+    /// `error` with that body plus the HTTP `status` `POST /chat` would have returned.
+    pub fn finished(status: StatusCode, body: &impl Serialize) -> Self {
+        let mut data = serde_json::to_value(body).unwrap_or_else(
+            |error| json!({ "error": format!("failed to encode chat reply: {error}") }),
+        );
+        if status.is_success() {
+            return Self { name: "done", data };
+        }
+        if let Some(fields) = data.as_object_mut() {
+            fields.insert("status".to_owned(), Value::from(status.as_u16()));
+        }
+        Self {
+            name: "error",
+            data,
+        }
+    }
+
+    fn into_sse(self) -> Event {
+        Event::default()
+            .event(self.name)
+            .data(self.data.to_string())
+    }
+}
+
+/// Streams `events` as Server-Sent Events until the sender side is dropped.
+pub(super) fn sse_response(events: UnboundedReceiver<StreamEvent>) -> Response {
+    let stream = futures_util::stream::unfold(events, |mut events| async move {
+        let event = events.recv().await?;
+        Some((Ok::<_, Infallible>(event.into_sse()), events))
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    use super::StreamEvent;
+    use crate::agent::TurnEvent;
+    use crate::tools::ToolProgress;
+
+    #[test]
+    fn turn_events_and_replies_map_to_named_stream_events() {
+        let progress = StreamEvent::from_turn_event(&TurnEvent::ToolProgress {
+            tool_call_index: 1,
+            tool_name: "fetch_url".to_owned(),
+            progress: ToolProgress {
+                elapsed: Duration::from_millis(250),
+                bytes_received: 512,
+                bytes_total: None,
+            },
+        });
+        assert_eq!(progress.name, "tool_progress");
+        assert_eq!(
+            progress.data,
+            json!({
+                "tool_call_index": 1,
+                "tool_name": "fetch_url",
+                "elapsed_ms": 250,
+                "bytes_received": 512,
+                "bytes_total": null,
+            })
+        );
+
+        let finished = StreamEvent::from_turn_event(&TurnEvent::ToolFinished {
+            tool_call_index: 1,
+            tool_name: "fetch_url".to_owned(),
+            latency: Duration::from_millis(300),
+            succeeded: false,
+        });
+        assert_eq!(
+            (finished.name, &finished.data["latency_ms"]),
+            ("tool_finished", &json!(300))
+        );
+
+        let done = StreamEvent::finished(StatusCode::OK, &json!({ "final_text": "hi" }));
+        assert_eq!(done.name, "done");
+        assert_eq!(done.data, json!({ "final_text": "hi" }));
+
+        let error = StreamEvent::finished(StatusCode::BAD_GATEWAY, &json!({ "error": "down" }));
+        assert_eq!(error.name, "error");
+        assert_eq!(error.data, json!({ "error": "down", "status": 502 }));
+    }
+}
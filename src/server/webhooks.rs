@@ -0,0 +1,326 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use reqwest::redirect::Policy;
+use ring::hmac;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::config::AgentSettings;
+use crate::text::hex_lower;
+
+pub(super) const SIGNATURE_HEADER: &str = "X-Mjolne-Signature";
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum TurnOutcomeStatus {
+    Completed,
+    Failed,
+}
+
+/// JSON body posted to every `SERVER_WEBHOOK_URLS` endpoint when a `/chat` or scheduled
+/// turn ends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(super) struct TurnOutcomeEvent {
+    pub turn_id: u64,
+    pub status: TurnOutcomeStatus,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// `SERVER_SCHEDULE_FILE` entry name, for turns started by the scheduler.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// Unix time in milliseconds; part of the signed body, so receivers can reject replays.
+    pub finished_at_ms: u64,
+}
+
+/// Fire-and-forget turn outcome delivery with per-URL retries. Built only when
+/// `SERVER_WEBHOOK_URLS` is set.
+pub(super) struct WebhookNotifier {
+    client: reqwest::Client,
+    urls: Vec<String>,
+    signing_key: Option<hmac::Key>,
+    max_retries: u32,
+    initial_backoff: Duration,
+    next_turn_id: AtomicU64,
+}
+
+impl WebhookNotifier {
+    pub fn from_settings(settings: &AgentSettings) -> Result<Option<Arc<Self>>> {
+        if settings.server_webhook_urls.is_empty() {
+            return Ok(None);
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(settings.server_webhook_timeout_ms))
+            // A redirect would forward the signed payload to a host nobody configured.
+            .redirect(Policy::none())
+            .build()
+            .context("failed to build webhook HTTP client")?;
+        Ok(Some(Arc::new(Self {
+            client,
+            urls: settings.server_webhook_urls.clone(),
+            signing_key: settings
+                .server_webhook_secret
+                .as_deref()
+                .map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())),
+            max_retries: settings.server_webhook_max_retries,
+            initial_backoff: INITIAL_RETRY_BACKOFF,
+            next_turn_id: AtomicU64::new(1),
+        })))
+    }
+
+    /// Server-assigned id for the next turn, unique within this process.
+    pub fn next_turn_id(&self) -> u64 {
+        self.next_turn_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Delivers `event` to every URL in the background; never delays the chat response.
+    pub fn notify(self: &Arc<Self>, event: TurnOutcomeEvent) {
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => body,
+            Err(error) => {
+                warn!(
+                    error = %error,
+                    turn_id = event.turn_id,
+                    "failed to encode webhook payload"
+                );
+                return;
+            }
+        };
+        let signature = self
+            .signing_key
+            .as_ref()
+            .map(|key| sign_payload(key, &body));
+        for url in self.urls.clone() {
+            let notifier = Arc::clone(self);
+            let body = body.clone();
+            let signature = signature.clone();
+            let turn_id = event.turn_id;
+            tokio::spawn(async move {
+                notifier
+                    .deliver(&url, &body, signature.as_deref(), turn_id)
+                    .await;
+            });
+        }
+    }
+
+    /// Posts `body` until a 2xx response, a non-retryable 4xx, or `max_retries` retries,
+    /// doubling the backoff after each failure. Returns whether delivery succeeded.
+    async fn deliver(&self, url: &str, body: &[u8], signature: Option<&str>, turn_id: u64) -> bool {
+        let mut backoff = self.initial_backoff;
+        for attempt in 1..=self.max_retries.saturating_add(1) {
+            let mut request = self
+                .client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_vec());
+            if let Some(signature) = signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+            let failure = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    info!(url, turn_id, attempt, "delivered turn outcome webhook");
+                    return true;
+                }
+                Ok(response) if !is_retryable_status(response.status()) => {
+                    warn!(
+                        url,
+                        turn_id,
+                        status = response.status().as_u16(),
+                        "turn outcome webhook rejected; not retrying"
+                    );
+                    return false;
+                }
+                Ok(response) => format!("HTTP {}", response.status().as_u16()),
+                Err(error) => error.to_string(),
+            };
+            if attempt > self.max_retries {
+                warn!(
+                    url,
+                    turn_id,
+                    attempt,
+                    error = %failure,
+                    "turn outcome webhook failed; giving up"
+                );
+                return false;
+            }
+            warn!(
+                url,
+                turn_id,
+                attempt,
+                error = %failure,
+                retry_in_ms = backoff.as_millis() as u64,
+                "turn outcome webhook failed; retrying"
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+        }
+        false
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// `sha256=<hex HMAC-SHA256 of body>`, the `X-Mjolne-Signature` header value.
+fn sign_payload(key: &hmac::Key, body: &[u8]) -> String {
+    format!("sha256={}", hex_lower(hmac::sign(key, body).as_ref()))
+}
+
+pub(super) fn unix_time_ms(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use axum::Router;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use ring::hmac;
+
+    use super::{
+        SIGNATURE_HEADER, TurnOutcomeEvent, TurnOutcomeStatus, WebhookNotifier, sign_payload,
+    };
+
+    struct Delivery {
+        signature: Option<String>,
+        body: String,
+    }
+
+    #[derive(Clone, Default)]
+    struct Receiver {
+        deliveries: Arc<Mutex<Vec<Delivery>>>,
+        statuses: Arc<Mutex<Vec<StatusCode>>>,
+    }
+
+    async fn receive(
+        State(receiver): State<Receiver>,
+        headers: HeaderMap,
+        body: String,
+    ) -> StatusCode {
+        let signature = headers
+            .get(SIGNATURE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        receiver
+            .deliveries
+            .lock()
+            .expect("deliveries lock")
+            .push(Delivery { signature, body });
+        let mut statuses = receiver.statuses.lock().expect("statuses lock");
+        if statuses.is_empty() {
+            StatusCode::OK
+        } else {
+            statuses.remove(0)
+        }
+    }
+
+    async fn spawn_receiver(statuses: Vec<StatusCode>) -> (String, Receiver) {
+        let receiver = Receiver::default();
+        *receiver.statuses.lock().expect("statuses lock") = statuses;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("receiver should bind");
+        let url = format!("http://{}/hook", listener.local_addr().expect("local addr"));
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(receiver.clone());
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        (url, receiver)
+    }
+
+    fn notifier(url: &str, secret: Option<&str>, max_retries: u32) -> WebhookNotifier {
+        WebhookNotifier {
+            client: reqwest::Client::new(),
+            urls: vec![url.to_owned()],
+            signing_key: secret.map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())),
+            max_retries,
+            initial_backoff: Duration::from_millis(5),
+            next_turn_id: AtomicU64::new(1),
+        }
+    }
+
+    #[test]
+    fn signature_is_hex_hmac_sha256_of_the_body() {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"key");
+        assert_eq!(
+            sign_payload(&key, b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn delivery_retries_server_errors_and_sends_signed_payload() {
+        let (url, receiver) = spawn_receiver(vec![
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::TOO_MANY_REQUESTS,
+        ])
+        .await;
+        let notifier = notifier(&url, Some("s3cret"), 2);
+        let event = TurnOutcomeEvent {
+            turn_id: notifier.next_turn_id(),
+            status: TurnOutcomeStatus::Failed,
+            latency_ms: 42,
+            error_kind: Some("upstream"),
+            session_id: None,
+            schedule: None,
+            finished_at_ms: 1_700_000_000_000,
+        };
+        let body = serde_json::to_vec(&event).expect("event should encode");
+        let signature = sign_payload(
+            notifier.signing_key.as_ref().expect("key should be set"),
+            &body,
+        );
+
+        assert!(notifier.deliver(&url, &body, Some(&signature), 1).await);
+
+        let deliveries = receiver.deliveries.lock().expect("deliveries lock");
+        assert_eq!(deliveries.len(), 3);
+        assert_eq!(deliveries[2].signature.as_deref(), Some(signature.as_str()));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&deliveries[2].body).expect("json body"),
+            serde_json::json!({
+                "turn_id": 1,
+                "status": "failed",
+                "latency_ms": 42,
+                "error_kind": "upstream",
+                "finished_at_ms": 1_700_000_000_000_u64,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn delivery_stops_on_client_errors_and_after_max_retries() {
+        let (url, receiver) = spawn_receiver(vec![StatusCode::BAD_REQUEST]).await;
+        assert!(!notifier(&url, None, 3).deliver(&url, b"{}", None, 1).await);
+        assert_eq!(
+            receiver.deliveries.lock().expect("deliveries lock").len(),
+            1
+        );
+
+        let (url, receiver) = spawn_receiver(vec![StatusCode::BAD_GATEWAY; 3]).await;
+        assert!(!notifier(&url, None, 1).deliver(&url, b"{}", None, 1).await);
+        let deliveries = receiver.deliveries.lock().expect("deliveries lock");
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].signature, None);
+    }
+}
@@ -0,0 +1,266 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Workspace-relative file holding the studio display preferences.
+pub(super) const PREFERENCES_FILE: &str = ".mjolne/studio.json";
+const MIN_UI_SCALE: f32 = 0.8;
+const MAX_UI_SCALE: f32 = 2.0;
+const UI_SCALE_STEP: f32 = 0.1;
+
+/// Read by the palette functions, which are called from every render path without
+/// access to the app state.
+static HIGH_CONTRAST: AtomicBool = AtomicBool::new(false);
+
+pub(super) fn high_contrast_enabled() -> bool {
+    HIGH_CONTRAST.load(Ordering::Relaxed)
+}
+
+/// `normal`, or `high_contrast` while the high-contrast theme is active.
+pub(super) fn themed(normal: egui::Color32, high_contrast: egui::Color32) -> egui::Color32 {
+    if high_contrast_enabled() {
+        high_contrast
+    } else {
+        normal
+    }
+}
+
+/// Display preferences persisted under `PREFERENCES_FILE` so they survive restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct StudioPreferences {
+    /// Multiplier applied to every studio text style.
+    pub ui_scale: f32,
+    pub high_contrast: bool,
+}
+
+impl Default for StudioPreferences {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            high_contrast: false,
+        }
+    }
+}
+
+impl StudioPreferences {
+    /// Preferences saved for `workspace_root`; defaults when the file is missing or
+    /// unreadable.
+    pub fn load(workspace_root: &Path) -> Self {
+        let path = workspace_root.join(PREFERENCES_FILE);
+        let payload = match fs::read(&path) {
+            Ok(payload) => payload,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!(path = %path.display(), error = %error, "failed to read studio preferences");
+                return Self::default();
+            }
+        };
+        match serde_json::from_slice::<Self>(&payload) {
+            Ok(preferences) => preferences.normalized(),
+            Err(error) => {
+                warn!(path = %path.display(), error = %error, "ignoring invalid studio preferences");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, workspace_root: &Path) -> Result<()> {
+        let path = workspace_root.join(PREFERENCES_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create `{}`", parent.display()))?;
+        }
+        let payload =
+            serde_json::to_vec_pretty(self).context("failed to encode studio preferences")?;
+        fs::write(&path, payload).with_context(|| format!("failed to write `{}`", path.display()))
+    }
+
+    /// Makes these preferences the active theme for the palette functions.
+    pub fn activate(&self) {
+        HIGH_CONTRAST.store(self.high_contrast, Ordering::Relaxed);
+    }
+
+    /// Steps the scale by `steps` increments of 10%, within 80%–200%.
+    pub fn step_ui_scale(&mut self, steps: i32) {
+        self.ui_scale += steps as f32 * UI_SCALE_STEP;
+        *self = self.normalized();
+    }
+
+    pub fn reset_ui_scale(&mut self) {
+        self.ui_scale = 1.0;
+    }
+
+    pub fn scale_percent(&self) -> u32 {
+        (self.ui_scale * 100.0).round() as u32
+    }
+
+    /// `size` in points after applying the UI scale.
+    pub fn scaled(&self, size: f32) -> f32 {
+        size * self.ui_scale
+    }
+
+    fn normalized(mut self) -> Self {
+        self.ui_scale = if self.ui_scale.is_finite() {
+            ((self.ui_scale / UI_SCALE_STEP).round() * UI_SCALE_STEP)
+                .clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+        } else {
+            1.0
+        };
+        self
+    }
+}
+
+/// Keyboard shortcuts handled by the studio rather than individual widgets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum StudioShortcut {
+    ScaleUp,
+    ScaleDown,
+    ResetScale,
+    ToggleHighContrast,
+    /// `F6` / `Shift+F6`: move keyboard focus to the next or previous region.
+    CycleFocus {
+        backwards: bool,
+    },
+}
+
+/// Consumes the first studio shortcut pressed this frame, so egui's built-in
+/// `Ctrl`+`+`/`-`/`0` zoom does not also run.
+pub(super) fn consume_shortcut(input: &mut egui::InputState) -> Option<StudioShortcut> {
+    let command = egui::Modifiers::COMMAND;
+    let shortcuts = [
+        (command, egui::Key::Plus, StudioShortcut::ScaleUp),
+        (command, egui::Key::Equals, StudioShortcut::ScaleUp),
+        (command, egui::Key::Minus, StudioShortcut::ScaleDown),
+        (command, egui::Key::Num0, StudioShortcut::ResetScale),
+        (
+            command | egui::Modifiers::SHIFT,
+            egui::Key::H,
+            StudioShortcut::ToggleHighContrast,
+        ),
+        (
+            egui::Modifiers::SHIFT,
+            egui::Key::F6,
+            StudioShortcut::CycleFocus { backwards: true },
+        ),
+        (
+            egui::Modifiers::NONE,
+            egui::Key::F6,
+            StudioShortcut::CycleFocus { backwards: false },
+        ),
+    ];
+    shortcuts
+        .into_iter()
+        .find(|(modifiers, key, _)| input.consume_key(*modifiers, *key))
+        .map(|(_, _, shortcut)| shortcut)
+}
+
+/// Parts of the studio reachable with `F6`, in cycle order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FocusRegion {
+    Prompt,
+    ChatHistory,
+    CanvasToolbar,
+}
+
+impl FocusRegion {
+    const ORDER: [Self; 3] = [Self::Prompt, Self::ChatHistory, Self::CanvasToolbar];
+
+    /// The region after (or before) `current`, skipping the chat regions while the chat
+    /// pane is collapsed.
+    pub fn cycle(current: Option<Self>, backwards: bool, chat_visible: bool) -> Self {
+        let available = Self::ORDER
+            .into_iter()
+            .filter(|region| chat_visible || *region == Self::CanvasToolbar)
+            .collect::<Vec<_>>();
+        let Some(index) =
+            current.and_then(|current| available.iter().position(|region| *region == current))
+        else {
+            return if backwards {
+                available[available.len() - 1]
+            } else {
+                available[0]
+            };
+        };
+        let next = if backwards {
+            index.checked_sub(1).unwrap_or(available.len() - 1)
+        } else {
+            (index + 1) % available.len()
+        };
+        available[next]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Prompt => "prompt",
+            Self::ChatHistory => "chat history",
+            Self::CanvasToolbar => "canvas toolbar",
+        }
+    }
+}
+
+/// Outlines `response` while it has keyboard focus. Buttons with a custom stroke do not
+/// pick up egui's focused-widget style, so keyboard users would otherwise lose track.
+pub(super) fn paint_focus_ring(ui: &egui::Ui, response: &egui::Response, color: egui::Color32) {
+    if response.has_focus() {
+        let width = if high_contrast_enabled() { 3.0 } else { 2.0 };
+        ui.painter().rect_stroke(
+            response.rect.expand(2.0),
+            8.0,
+            egui::Stroke::new(width, color),
+            egui::StrokeKind::Outside,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{FocusRegion, PREFERENCES_FILE, StudioPreferences};
+
+    #[test]
+    fn preferences_round_trip_with_clamped_scale() {
+        let root = std::env::temp_dir().join(format!(
+            "mjolne_studio_prefs_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        assert_eq!(StudioPreferences::load(&root), StudioPreferences::default());
+
+        let mut preferences = StudioPreferences::default();
+        preferences.step_ui_scale(3);
+        assert_eq!(preferences.scale_percent(), 130);
+        preferences.step_ui_scale(20);
+        assert_eq!(preferences.scale_percent(), 200);
+        preferences.high_contrast = true;
+        preferences.save(&root).expect("preferences should save");
+        assert_eq!(StudioPreferences::load(&root), preferences);
+
+        fs::write(root.join(PREFERENCES_FILE), r#"{"ui_scale": 0.1}"#).expect("write prefs");
+        let loaded = StudioPreferences::load(&root);
+        assert_eq!((loaded.scale_percent(), loaded.high_contrast), (80, false));
+
+        fs::write(root.join(PREFERENCES_FILE), "not json").expect("write prefs");
+        assert_eq!(StudioPreferences::load(&root), StudioPreferences::default());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn focus_cycle_wraps_and_skips_hidden_chat() {
+        use FocusRegion::{CanvasToolbar, ChatHistory, Prompt};
+        assert_eq!(FocusRegion::cycle(None, false, true), Prompt);
+        assert_eq!(FocusRegion::cycle(Some(Prompt), false, true), ChatHistory);
+        assert_eq!(FocusRegion::cycle(Some(CanvasToolbar), false, true), Prompt);
+        assert_eq!(FocusRegion::cycle(Some(Prompt), true, true), CanvasToolbar);
+        assert_eq!(
+            FocusRegion::cycle(Some(ChatHistory), false, false),
+            CanvasToolbar
+        );
+    }
+}
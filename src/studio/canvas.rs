@@ -2,18 +2,22 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use eframe::egui;
 
+use crate::agent::{TimelineSpan, TimelineSpanKind, timeline_extent};
 use crate::graph::{ArchitectureGraph, ArchitectureNode, ArchitectureNodeKind};
+use crate::text::truncate_graphemes;
 
 use super::events::{
     CanvasConnectorObject, CanvasDrawCommand, CanvasDrawCommandBatch, CanvasGroupObject, CanvasOp,
-    CanvasSceneData, CanvasShapeKind, CanvasShapeObject, CanvasViewportHint,
+    CanvasSceneData, CanvasSelectionEvent, CanvasShapeKind, CanvasShapeObject, CanvasViewportHint,
 };
+use super::notes_graph::NotesGraph;
 
 const MIN_CANVAS_SURFACE_WIDTH: f32 = 320.0;
 const MIN_CANVAS_SURFACE_HEIGHT: f32 = 240.0;
 const CANVAS_FRAME_INSET: f32 = 8.0;
 const CANVAS_CONTENT_INSET_X: f32 = 24.0;
 const CANVAS_CONTENT_INSET_Y: f32 = 24.0;
+const NODE_SHAPE_ID_PREFIX: &str = "node:";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CanvasAnnotation {
@@ -34,10 +38,25 @@ pub struct CanvasDrawScene {
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct CanvasState {
     graph: Option<ArchitectureGraph>,
+    notes_graph: Option<NotesGraph>,
     highlighted_target_ids: Vec<String>,
     focused_target_id: Option<String>,
+    selected_target_ids: Vec<String>,
+    hovered_target_id: Option<String>,
     annotations: Vec<CanvasAnnotation>,
     draw_scene: CanvasDrawScene,
+    /// Draw commands applied since startup, for the profiling overlay's per-frame count.
+    applied_draw_commands: u64,
+}
+
+/// Pointer state for one frame, in scene coordinates (see `CanvasViewport::scene_position`).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CanvasPointerInput {
+    pub hover_pos: Option<egui::Pos2>,
+    pub primary_click: Option<egui::Pos2>,
+    pub secondary_click: Option<egui::Pos2>,
+    /// Shift/command held: primary clicks toggle targets instead of replacing the selection.
+    pub additive: bool,
 }
 
 impl CanvasState {
@@ -45,6 +64,10 @@ impl CanvasState {
         self.graph.as_ref()
     }
 
+    pub fn notes_graph(&self) -> Option<&NotesGraph> {
+        self.notes_graph.as_ref()
+    }
+
     pub fn highlighted_target_ids(&self) -> &[String] {
         &self.highlighted_target_ids
     }
@@ -61,6 +84,18 @@ impl CanvasState {
         self.focused_target_id()
     }
 
+    pub fn selected_target_ids(&self) -> &[String] {
+        &self.selected_target_ids
+    }
+
+    pub fn is_selected(&self, target_id: &str) -> bool {
+        self.selected_target_ids.iter().any(|id| id == target_id)
+    }
+
+    pub fn hovered_target_id(&self) -> Option<&str> {
+        self.hovered_target_id.as_deref()
+    }
+
     pub fn annotations(&self) -> &[CanvasAnnotation] {
         &self.annotations
     }
@@ -69,6 +104,10 @@ impl CanvasState {
         &self.draw_scene
     }
 
+    pub fn applied_draw_command_count(&self) -> u64 {
+        self.applied_draw_commands
+    }
+
     pub fn apply(&mut self, op: CanvasOp) {
         match op {
             CanvasOp::SetSceneData { scene } => {
@@ -80,6 +119,9 @@ impl CanvasState {
             CanvasOp::SetFocusedTarget { target_id } => {
                 self.apply_set_focused_target(target_id);
             }
+            CanvasOp::SetSelectedTargets { target_ids } => {
+                self.selected_target_ids = self.known_unique_targets(target_ids);
+            }
             CanvasOp::UpsertAnnotation {
                 id,
                 text,
@@ -112,10 +154,92 @@ impl CanvasState {
                 self.graph = Some(graph);
                 self.prune_unknown_target_references();
             }
+            CanvasSceneData::NotesGraph { graph } => {
+                self.notes_graph = Some(graph);
+                self.prune_unknown_target_references();
+            }
         }
     }
 
+    /// Topmost graph node whose draw-scene rectangle contains `scene_pos`.
+    pub fn hit_test(&self, scene_pos: egui::Pos2) -> Option<&str> {
+        self.draw_scene
+            .shapes
+            .values()
+            .filter(|shape| shape.kind == CanvasShapeKind::Rectangle)
+            .filter_map(|shape| {
+                let target_id = shape.id.strip_prefix(NODE_SHAPE_ID_PREFIX)?;
+                let rect = scene_shape_rect(shape)?;
+                (rect.contains(scene_pos) && self.contains_target(target_id))
+                    .then_some((shape.layer, target_id))
+            })
+            .max_by_key(|(layer, _)| *layer)
+            .map(|(_, target_id)| target_id)
+    }
+
+    /// Updates hover and selection from one frame of pointer input and reports what changed.
+    ///
+    /// A primary click selects the hit node (or clears the selection on empty space); with
+    /// `additive` it toggles the hit node. A secondary click on a node selects it if needed and
+    /// requests a context menu.
+    pub fn handle_pointer(&mut self, input: CanvasPointerInput) -> Vec<CanvasSelectionEvent> {
+        let mut events = Vec::new();
+
+        let hovered = input
+            .hover_pos
+            .and_then(|pos| self.hit_test(pos))
+            .map(str::to_owned);
+        if hovered != self.hovered_target_id {
+            self.hovered_target_id = hovered.clone();
+            events.push(CanvasSelectionEvent::HoverChanged { target_id: hovered });
+        }
+
+        if let Some(pos) = input.primary_click {
+            let hit = self.hit_test(pos).map(str::to_owned);
+            let next = match hit {
+                Some(target_id) if input.additive => {
+                    let mut next = self.selected_target_ids.clone();
+                    if let Some(index) = next.iter().position(|id| *id == target_id) {
+                        next.remove(index);
+                    } else {
+                        next.push(target_id);
+                    }
+                    next
+                }
+                Some(target_id) => vec![target_id],
+                None if input.additive => self.selected_target_ids.clone(),
+                None => Vec::new(),
+            };
+            self.replace_selection(next, &mut events);
+        }
+
+        if let Some(pos) = input.secondary_click
+            && let Some(target_id) = self.hit_test(pos).map(str::to_owned)
+        {
+            if !self.is_selected(&target_id) {
+                self.replace_selection(vec![target_id.clone()], &mut events);
+            }
+            events.push(CanvasSelectionEvent::ContextMenuRequested { target_id });
+        }
+
+        events
+    }
+
+    fn replace_selection(&mut self, next: Vec<String>, events: &mut Vec<CanvasSelectionEvent>) {
+        if next == self.selected_target_ids {
+            return;
+        }
+        self.selected_target_ids = next;
+        events.push(CanvasSelectionEvent::SelectionChanged {
+            target_ids: self.selected_target_ids.clone(),
+        });
+    }
+
     fn apply_set_highlighted_targets(&mut self, target_ids: Vec<String>) {
+        self.highlighted_target_ids = self.known_unique_targets(target_ids);
+    }
+
+    fn known_unique_targets(&self, target_ids: Vec<String>) -> Vec<String> {
         let mut seen = BTreeSet::new();
         let mut filtered = Vec::with_capacity(target_ids.len());
         for target_id in target_ids {
@@ -124,7 +248,7 @@ impl CanvasState {
             }
             filtered.push(target_id);
         }
-        self.highlighted_target_ids = filtered;
+        filtered
     }
 
     fn apply_set_focused_target(&mut self, target_id: Option<String>) {
@@ -160,6 +284,9 @@ impl CanvasState {
             return;
         }
 
+        self.applied_draw_commands = self
+            .applied_draw_commands
+            .saturating_add(batch.commands.len() as u64);
         for command in batch.commands {
             match command {
                 CanvasDrawCommand::UpsertShape { shape } => {
@@ -198,23 +325,34 @@ impl CanvasState {
         self.graph
             .as_ref()
             .is_some_and(|graph| graph.nodes.iter().any(|node| node.id == target_id))
+            || self
+                .notes_graph
+                .as_ref()
+                .is_some_and(|graph| graph.nodes.iter().any(|node| node.id == target_id))
     }
 
     fn prune_unknown_target_references(&mut self) {
-        let known_node_ids = self
+        let graph_node_ids = self
             .graph
-            .as_ref()
-            .map(|graph| {
-                graph
-                    .nodes
-                    .iter()
-                    .map(|node| node.id.as_str())
-                    .collect::<BTreeSet<_>>()
-            })
-            .unwrap_or_default();
+            .iter()
+            .flat_map(|graph| graph.nodes.iter().map(|node| node.id.as_str()));
+        let note_node_ids = self
+            .notes_graph
+            .iter()
+            .flat_map(|graph| graph.nodes.iter().map(|node| node.id.as_str()));
+        let known_node_ids = graph_node_ids.chain(note_node_ids).collect::<BTreeSet<_>>();
 
         self.highlighted_target_ids
             .retain(|node_id| known_node_ids.contains(node_id.as_str()));
+        self.selected_target_ids
+            .retain(|node_id| known_node_ids.contains(node_id.as_str()));
+        if self
+            .hovered_target_id
+            .as_ref()
+            .is_some_and(|node_id| !known_node_ids.contains(node_id.as_str()))
+        {
+            self.hovered_target_id = None;
+        }
 
         if self
             .focused_target_id
@@ -250,6 +388,10 @@ impl CanvasDrawScene {
         self.groups.values().collect()
     }
 
+    pub fn object_count(&self) -> usize {
+        self.shapes.len() + self.connectors.len() + self.groups.len()
+    }
+
     pub fn viewport_hint(&self) -> Option<&CanvasViewportHint> {
         self.viewport_hint.as_ref()
     }
@@ -359,6 +501,17 @@ impl CanvasViewport {
         self.transformed_position(scene_position, canvas_center)
     }
 
+    /// Inverse of `transformed_position_in_scene`: maps a screen position to scene coordinates.
+    pub fn scene_position(
+        &self,
+        screen_position: egui::Pos2,
+        canvas_center: egui::Pos2,
+        scene_origin: egui::Pos2,
+    ) -> egui::Pos2 {
+        let unzoomed = canvas_center + ((screen_position - canvas_center - self.pan) / self.zoom);
+        (unzoomed - scene_origin).to_pos2()
+    }
+
     fn zoom_clamped(&self, min: f32, max: f32) -> f32 {
         self.zoom.clamp(min, max)
     }
@@ -381,18 +534,27 @@ impl CanvasViewport {
 pub struct CanvasToolCard {
     pub id: String,
     pub title: String,
+    /// Latency/attempt summary shown right-aligned on the title line.
+    pub meta: String,
     pub body: String,
+    pub citation: Option<String>,
+    /// Download progress of a tool call that is still running, drawn as a bar.
+    pub progress_percent: Option<u8>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CanvasSurfaceAdapterKind {
     ArchitectureGraph,
+    TurnTimeline,
+    NotesGraph,
 }
 
 impl CanvasSurfaceAdapterKind {
     pub fn label(self) -> &'static str {
         match self {
             Self::ArchitectureGraph => "Architecture graph",
+            Self::TurnTimeline => "Turn timeline",
+            Self::NotesGraph => "Notes graph",
         }
     }
 }
@@ -411,6 +573,11 @@ pub enum CanvasSurfaceAdapter<'a> {
     ArchitectureGraph {
         options: GraphSurfaceAdapterOptions<'a>,
     },
+    TurnTimeline {
+        spans: &'a [TimelineSpan],
+    },
+    /// Draws the notes scene of the `CanvasState` it is rendered with.
+    NotesGraph,
 }
 
 impl<'a> CanvasSurfaceAdapter<'a> {
@@ -418,19 +585,26 @@ impl<'a> CanvasSurfaceAdapter<'a> {
         Self::ArchitectureGraph { options }
     }
 
+    pub fn turn_timeline(spans: &'a [TimelineSpan]) -> Self {
+        Self::TurnTimeline { spans }
+    }
+
     pub fn kind(&self) -> CanvasSurfaceAdapterKind {
         match self {
             Self::ArchitectureGraph { .. } => CanvasSurfaceAdapterKind::ArchitectureGraph,
+            Self::TurnTimeline { .. } => CanvasSurfaceAdapterKind::TurnTimeline,
+            Self::NotesGraph => CanvasSurfaceAdapterKind::NotesGraph,
         }
     }
 
+    /// Renders the surface and returns pointer-driven selection events for the app.
     pub fn render(
         self,
         ui: &mut egui::Ui,
-        state: &CanvasState,
+        state: &mut CanvasState,
         viewport: &mut CanvasViewport,
         surface_height: f32,
-    ) {
+    ) -> Vec<CanvasSelectionEvent> {
         match self {
             Self::ArchitectureGraph { options } => {
                 let _ = (
@@ -445,8 +619,13 @@ impl<'a> CanvasSurfaceAdapter<'a> {
                     viewport,
                     surface_height,
                     options.show_graph_legend,
-                );
+                )
+            }
+            Self::TurnTimeline { spans } => {
+                render_turn_timeline(ui, spans, viewport, surface_height);
+                Vec::new()
             }
+            Self::NotesGraph => render_draw_scene(ui, state, viewport, surface_height, false),
         }
     }
 }
@@ -486,7 +665,7 @@ fn render_canvas_surface_frame(
     surface_height: f32,
 ) -> CanvasSurfaceFrame {
     let desired_size = canvas_desired_size(ui.available_width(), surface_height);
-    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::drag());
+    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::click_and_drag());
     let frame = response.rect.shrink(CANVAS_FRAME_INSET);
     painter.rect_filled(frame, 14.0, egui::Color32::from_rgb(250, 253, 255));
     painter.rect_stroke(
@@ -710,6 +889,7 @@ fn render_graph_snapshot(
             match node.kind {
                 ArchitectureNodeKind::Module => egui::Color32::from_rgb(77, 125, 158),
                 ArchitectureNodeKind::File => egui::Color32::from_rgb(84, 143, 106),
+                ArchitectureNodeKind::UnresolvedModule => egui::Color32::from_rgb(242, 193, 78),
             }
         };
         let stroke = if is_focused || is_hovered {
@@ -720,7 +900,7 @@ fn render_graph_snapshot(
         let scaled_node_radius = MODULE_NODE_RADIUS * viewport.zoom_clamped(0.72, 1.8);
         let scaled_file_node_size = FILE_NODE_SIZE * viewport.zoom_clamped(0.72, 1.8);
         match node.kind {
-            ArchitectureNodeKind::Module => {
+            ArchitectureNodeKind::Module | ArchitectureNodeKind::UnresolvedModule => {
                 surface
                     .painter
                     .circle_filled(*position, scaled_node_radius, fill);
@@ -760,6 +940,7 @@ fn render_graph_snapshot(
         let kind = match node.kind {
             ArchitectureNodeKind::Module => "module",
             ArchitectureNodeKind::File => "file",
+            ArchitectureNodeKind::UnresolvedModule => "unresolved module",
         };
         let hint = format!("{kind}: {}", node.display_label);
         surface.painter.text(
@@ -774,18 +955,17 @@ fn render_graph_snapshot(
 
 fn render_draw_scene(
     ui: &mut egui::Ui,
-    state: &CanvasState,
+    state: &mut CanvasState,
     viewport: &mut CanvasViewport,
     surface_height: f32,
     show_legend: bool,
-) {
+) -> Vec<CanvasSelectionEvent> {
     let surface = render_canvas_surface_frame(ui, viewport, surface_height);
     let canvas_center = surface.frame.center();
     let scene_origin = surface.frame.min;
     let scene_painter = surface.painter.with_clip_rect(surface.frame);
-    let scene = state.draw_scene();
 
-    if scene.ordered_object_ids().is_empty() {
+    if state.draw_scene().ordered_object_ids().is_empty() {
         surface.painter.text(
             surface.frame.center(),
             egui::Align2::CENTER_CENTER,
@@ -793,8 +973,23 @@ fn render_draw_scene(
             egui::FontId::proportional(13.0),
             ui.visuals().weak_text_color(),
         );
-        return;
-    }
+        return Vec::new();
+    }
+
+    let to_scene = |pos: egui::Pos2| viewport.scene_position(pos, canvas_center, scene_origin);
+    let pointer_pos = surface.response.interact_pointer_pos();
+    let selection_events = state.handle_pointer(CanvasPointerInput {
+        hover_pos: surface.response.hover_pos().map(to_scene),
+        primary_click: pointer_pos
+            .filter(|_| surface.response.clicked())
+            .map(to_scene),
+        secondary_click: pointer_pos
+            .filter(|_| surface.response.secondary_clicked())
+            .map(to_scene),
+        additive: ui.input(|input| input.modifiers.shift || input.modifiers.command),
+    });
+    let state = &*state;
+    let scene = state.draw_scene();
 
     let shape_centers = scene
         .shapes()
@@ -859,14 +1054,25 @@ fn render_draw_scene(
         }
     }
 
-    if let Some(pointer) = surface.response.hover_pos()
-        && let Some((shape_id, _)) = rect_shapes.iter().find(|(_, rect)| rect.contains(pointer))
-        && let Some(full_id) = shape_id.strip_prefix("node:")
-    {
+    for (shape_id, rect) in &rect_shapes {
+        if shape_id
+            .strip_prefix(NODE_SHAPE_ID_PREFIX)
+            .is_some_and(|target_id| state.is_selected(target_id))
+        {
+            scene_painter.rect_stroke(
+                rect.expand(3.0),
+                5.0,
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(39, 110, 196)),
+                egui::StrokeKind::Outside,
+            );
+        }
+    }
+
+    if let Some(hovered_id) = state.hovered_target_id() {
         surface.painter.text(
             surface.frame.left_top() + egui::vec2(16.0, 16.0),
             egui::Align2::LEFT_TOP,
-            clipped_label(full_id, 64),
+            clipped_label(hovered_id, 64),
             egui::FontId::proportional(11.0),
             egui::Color32::from_rgb(43, 57, 76),
         );
@@ -875,6 +1081,8 @@ fn render_draw_scene(
     if show_legend {
         render_legend(ui, &surface.painter, surface.frame, viewport.zoom_percent());
     }
+
+    selection_events
 }
 
 fn draw_shape_center(shape: &CanvasShapeObject) -> egui::Pos2 {
@@ -1087,6 +1295,13 @@ fn render_tool_cards(painter: &egui::Painter, frame: egui::Rect, tool_cards: &[C
             egui::FontId::proportional(10.8),
             egui::Color32::from_rgb(43, 89, 144),
         );
+        painter.text(
+            rect.right_top() + egui::vec2(-10.0, 8.0),
+            egui::Align2::RIGHT_TOP,
+            clipped_label(&card.meta, 24),
+            egui::FontId::proportional(9.6),
+            egui::Color32::from_rgb(96, 118, 146),
+        );
         painter.text(
             rect.left_top() + egui::vec2(12.0, 26.0),
             egui::Align2::LEFT_TOP,
@@ -1094,9 +1309,126 @@ fn render_tool_cards(painter: &egui::Painter, frame: egui::Rect, tool_cards: &[C
             egui::FontId::proportional(10.0),
             egui::Color32::from_rgb(54, 71, 95),
         );
+        if let Some(citation) = &card.citation {
+            painter.text(
+                rect.left_top() + egui::vec2(12.0, 40.0),
+                egui::Align2::LEFT_TOP,
+                clipped_label(citation, 44),
+                egui::FontId::proportional(9.6),
+                egui::Color32::from_rgb(68, 126, 188),
+            );
+        }
+        if let Some(percent) = card.progress_percent {
+            let track = egui::Rect::from_min_size(
+                rect.left_bottom() + egui::vec2(12.0, -7.0),
+                egui::vec2(CARD_WIDTH - 24.0, 3.0),
+            );
+            painter.rect_filled(track, 1.5, egui::Color32::from_rgb(206, 222, 240));
+            let filled = track.width() * f32::from(percent.min(100)) / 100.0;
+            painter.rect_filled(
+                egui::Rect::from_min_size(track.left_top(), egui::vec2(filled, track.height())),
+                1.5,
+                egui::Color32::from_rgb(68, 126, 188),
+            );
+        }
+    }
+}
+
+const TIMELINE_LABEL_WIDTH: f32 = 170.0;
+const TIMELINE_ROW_HEIGHT: f32 = 22.0;
+const TIMELINE_BAR_HEIGHT: f32 = 14.0;
+
+fn render_turn_timeline(
+    ui: &mut egui::Ui,
+    spans: &[TimelineSpan],
+    viewport: &mut CanvasViewport,
+    surface_height: f32,
+) {
+    let surface = render_canvas_surface_frame(ui, viewport, surface_height);
+    let painter = surface.painter.with_clip_rect(surface.frame);
+    if spans.is_empty() {
+        painter.text(
+            surface.frame.center(),
+            egui::Align2::CENTER_CENTER,
+            "Complete a turn to see its model and tool call timeline",
+            egui::FontId::proportional(13.0),
+            ui.visuals().weak_text_color(),
+        );
+        return;
+    }
+
+    let content = surface.content_rect;
+    let extent = timeline_extent(spans);
+    painter.text(
+        content.left_top(),
+        egui::Align2::LEFT_TOP,
+        format!(
+            "Latest turn · {} calls · {}ms",
+            spans.len(),
+            extent.as_millis()
+        ),
+        egui::FontId::proportional(11.5),
+        egui::Color32::from_rgb(43, 89, 144),
+    );
+
+    let rows_rect = egui::Rect::from_min_max(content.min + egui::vec2(0.0, 22.0), content.max);
+    for (span, bar) in spans.iter().zip(timeline_bar_rects(spans, rows_rect)) {
+        let (fill, text_color) = match span.kind {
+            TimelineSpanKind::ModelCall => (
+                egui::Color32::from_rgb(68, 126, 188),
+                egui::Color32::from_rgb(43, 89, 144),
+            ),
+            TimelineSpanKind::ToolCall => (
+                egui::Color32::from_rgb(224, 160, 70),
+                egui::Color32::from_rgb(150, 96, 27),
+            ),
+        };
+        let track = egui::Rect::from_min_max(
+            egui::pos2(rows_rect.left() + TIMELINE_LABEL_WIDTH, bar.top()),
+            egui::pos2(rows_rect.right(), bar.bottom()),
+        );
+        painter.rect_filled(track, 3.0, egui::Color32::from_rgb(236, 243, 250));
+        painter.rect_filled(bar, 3.0, fill);
+        painter.text(
+            egui::pos2(rows_rect.left(), bar.center().y),
+            egui::Align2::LEFT_CENTER,
+            format!("{} · step {}", clipped_label(&span.label, 20), span.step),
+            egui::FontId::proportional(10.4),
+            text_color,
+        );
+        painter.text(
+            egui::pos2(bar.right() + 4.0, bar.center().y),
+            egui::Align2::LEFT_CENTER,
+            format!("{}ms", span.duration().as_millis()),
+            egui::FontId::proportional(9.6),
+            egui::Color32::from_rgb(96, 118, 146),
+        );
     }
 }
 
+/// Bar rect per span: x scaled to the turn extent, one row per span.
+fn timeline_bar_rects(spans: &[TimelineSpan], rows_rect: egui::Rect) -> Vec<egui::Rect> {
+    let extent = timeline_extent(spans).as_secs_f32().max(f32::EPSILON);
+    let track_left = rows_rect.left() + TIMELINE_LABEL_WIDTH;
+    let track_width = (rows_rect.right() - track_left - 48.0).max(1.0);
+
+    spans
+        .iter()
+        .enumerate()
+        .map(|(row, span)| {
+            let left = track_left + span.start.as_secs_f32() / extent * track_width;
+            let width = (span.duration().as_secs_f32() / extent * track_width).max(2.0);
+            let top = rows_rect.top()
+                + row as f32 * TIMELINE_ROW_HEIGHT
+                + (TIMELINE_ROW_HEIGHT - TIMELINE_BAR_HEIGHT) / 2.0;
+            egui::Rect::from_min_size(
+                egui::pos2(left, top),
+                egui::vec2(width, TIMELINE_BAR_HEIGHT),
+            )
+        })
+        .collect()
+}
+
 #[allow(dead_code)]
 fn compute_node_positions(
     graph: &ArchitectureGraph,
@@ -1106,7 +1438,9 @@ fn compute_node_positions(
     let mut file_nodes = Vec::new();
     for node in &graph.nodes {
         match node.kind {
-            ArchitectureNodeKind::Module => module_nodes.push(node),
+            ArchitectureNodeKind::Module | ArchitectureNodeKind::UnresolvedModule => {
+                module_nodes.push(node)
+            }
             ArchitectureNodeKind::File => file_nodes.push(node),
         }
     }
@@ -1179,16 +1513,17 @@ fn place_nodes_in_rect(
 }
 
 fn clipped_label(label: &str, max_chars: usize) -> String {
-    if label.chars().count() <= max_chars {
-        return label.to_owned();
-    }
+    truncate_graphemes(label, max_chars, "...")
+}
 
-    let mut clipped = label
-        .chars()
-        .take(max_chars.saturating_sub(3))
-        .collect::<String>();
-    clipped.push_str("...");
-    clipped
+fn scene_shape_rect(shape: &CanvasShapeObject) -> Option<egui::Rect> {
+    let [first, second, ..] = shape.points.as_slice() else {
+        return None;
+    };
+    Some(egui::Rect::from_two_pos(
+        egui::pos2(first.x as f32, first.y as f32),
+        egui::pos2(second.x as f32, second.y as f32),
+    ))
 }
 
 fn rectangle_shape_rect(
@@ -1216,21 +1551,25 @@ fn rectangle_shape_rect(
 
 #[cfg(test)]
 mod tests {
-    use std::time::UNIX_EPOCH;
+    use std::time::{Duration, UNIX_EPOCH};
 
     use eframe::egui;
 
+    use crate::agent::{TimelineSpan, TimelineSpanKind};
+
     use crate::graph::{
         ArchitectureEdge, ArchitectureEdgeKind, ArchitectureGraph, ArchitectureNode,
         ArchitectureNodeKind,
     };
-    use crate::studio::events::{CanvasPoint, CanvasShapeKind, CanvasStyle};
+    use crate::studio::events::{CanvasPoint, CanvasSelectionEvent, CanvasShapeKind, CanvasStyle};
+    use crate::studio::notes_graph::{NoteNode, NotesGraph};
 
     use super::{
-        CanvasDrawCommand, CanvasDrawCommandBatch, CanvasGroupObject, CanvasOp, CanvasShapeObject,
-        CanvasState, CanvasSurfaceAdapter, CanvasSurfaceAdapterKind, CanvasToolCard,
-        CanvasViewportHint, GraphSurfaceAdapterOptions, canvas_content_rect, canvas_desired_size,
-        clipped_label, compute_node_positions,
+        CanvasDrawCommand, CanvasDrawCommandBatch, CanvasGroupObject, CanvasOp, CanvasPointerInput,
+        CanvasShapeObject, CanvasState, CanvasSurfaceAdapter, CanvasSurfaceAdapterKind,
+        CanvasToolCard, CanvasViewport, CanvasViewportHint, GraphSurfaceAdapterOptions,
+        canvas_content_rect, canvas_desired_size, clipped_label, compute_node_positions,
+        timeline_bar_rects,
     };
 
     #[test]
@@ -1288,6 +1627,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn notes_scene_targets_share_highlight_and_pruning() {
+        let note = |id: &str| NoteNode {
+            id: id.to_owned(),
+            title: id.to_owned(),
+            tags: Default::default(),
+            backlinks: Vec::new(),
+        };
+        let mut state = CanvasState::default();
+        state.apply(CanvasOp::set_scene_notes(NotesGraph {
+            nodes: vec![note("a.md"), note("b.md")],
+            edges: Vec::new(),
+        }));
+        state.apply(CanvasOp::set_highlighted_targets(vec![
+            "b.md".to_owned(),
+            "gone.md".to_owned(),
+        ]));
+        assert_eq!(state.highlighted_target_ids(), ["b.md"]);
+
+        state.apply(CanvasOp::set_scene_notes(NotesGraph {
+            nodes: vec![note("a.md")],
+            edges: Vec::new(),
+        }));
+        assert!(state.highlighted_target_ids().is_empty());
+        assert_eq!(
+            CanvasSurfaceAdapter::NotesGraph.kind().label(),
+            "Notes graph"
+        );
+    }
+
     #[test]
     fn focus_node_requires_valid_id_and_supports_clear() {
         let mut state = CanvasState::default();
@@ -1624,6 +1993,106 @@ mod tests {
         assert_eq!(transformed, egui::pos2(120.0, 132.0));
     }
 
+    #[test]
+    fn hit_test_returns_topmost_known_node_rectangle() {
+        let state = state_with_node_rects();
+
+        assert_eq!(state.hit_test(egui::pos2(15.0, 15.0)), Some("module:crate"));
+        assert_eq!(
+            state.hit_test(egui::pos2(45.0, 45.0)),
+            Some("module:crate::tools")
+        );
+        assert_eq!(state.hit_test(egui::pos2(200.0, 200.0)), None);
+        // `node:module:missing` overlaps but is not in the graph.
+        assert_eq!(state.hit_test(egui::pos2(95.0, 95.0)), None);
+    }
+
+    #[test]
+    fn handle_pointer_selects_toggles_and_requests_context_menu() {
+        let mut state = state_with_node_rects();
+        let click = |x: f32, y: f32, additive: bool| CanvasPointerInput {
+            primary_click: Some(egui::pos2(x, y)),
+            additive,
+            ..CanvasPointerInput::default()
+        };
+
+        let events = state.handle_pointer(CanvasPointerInput {
+            hover_pos: Some(egui::pos2(15.0, 15.0)),
+            ..click(15.0, 15.0, false)
+        });
+        assert_eq!(
+            events,
+            vec![
+                CanvasSelectionEvent::HoverChanged {
+                    target_id: Some("module:crate".to_owned())
+                },
+                CanvasSelectionEvent::SelectionChanged {
+                    target_ids: vec!["module:crate".to_owned()]
+                },
+            ]
+        );
+
+        state.handle_pointer(click(45.0, 45.0, true));
+        assert_eq!(
+            state.selected_target_ids(),
+            ["module:crate", "module:crate::tools"]
+        );
+        state.handle_pointer(click(15.0, 15.0, true));
+        assert_eq!(state.selected_target_ids(), ["module:crate::tools"]);
+        assert!(state.handle_pointer(click(200.0, 200.0, true)).is_empty());
+
+        let events = state.handle_pointer(CanvasPointerInput {
+            secondary_click: Some(egui::pos2(15.0, 15.0)),
+            ..CanvasPointerInput::default()
+        });
+        assert_eq!(state.selected_target_ids(), ["module:crate"]);
+        assert_eq!(
+            events.last(),
+            Some(&CanvasSelectionEvent::ContextMenuRequested {
+                target_id: "module:crate".to_owned()
+            })
+        );
+
+        state.handle_pointer(click(200.0, 200.0, false));
+        assert!(state.selected_target_ids().is_empty());
+        assert_eq!(state.hovered_target_id(), None);
+    }
+
+    #[test]
+    fn selection_is_validated_and_pruned_with_graph_updates() {
+        let mut state = state_with_node_rects();
+        state.apply(CanvasOp::set_selected_targets(vec![
+            "module:crate".to_owned(),
+            "module:missing".to_owned(),
+            "module:crate::tools".to_owned(),
+        ]));
+        assert_eq!(
+            state.selected_target_ids(),
+            ["module:crate", "module:crate::tools"]
+        );
+
+        state.apply(CanvasOp::set_scene_graph(graph_with_nodes(
+            2,
+            &["module:crate::tools"],
+        )));
+
+        assert_eq!(state.selected_target_ids(), ["module:crate::tools"]);
+    }
+
+    #[test]
+    fn viewport_scene_position_inverts_scene_transform() {
+        let mut viewport = CanvasViewport::default();
+        viewport.zoom_with_anchor(egui::pos2(140.0, 90.0), egui::pos2(200.0, 150.0), 1.6);
+        let center = egui::pos2(200.0, 150.0);
+        let origin = egui::pos2(12.0, 8.0);
+        let scene_point = egui::pos2(37.0, 64.0);
+
+        let screen = viewport.transformed_position_in_scene(scene_point, center, origin);
+        let roundtrip = viewport.scene_position(screen, center, origin);
+
+        assert!((roundtrip - scene_point).length() < 0.001);
+    }
+
     #[test]
     fn canvas_surface_adapter_reports_graph_kind() {
         let changed = vec!["module:crate".to_owned()];
@@ -1631,7 +2100,10 @@ mod tests {
         let cards = vec![CanvasToolCard {
             id: "card-1".to_owned(),
             title: "Tool".to_owned(),
+            meta: "12ms".to_owned(),
             body: "details".to_owned(),
+            citation: None,
+            progress_percent: Some(40),
         }];
         let adapter = CanvasSurfaceAdapter::architecture_graph(GraphSurfaceAdapterOptions {
             changed_node_ids: &changed,
@@ -1645,6 +2117,38 @@ mod tests {
         assert_eq!(adapter.kind().label(), "Architecture graph");
     }
 
+    #[test]
+    fn timeline_bar_rects_scale_spans_to_turn_extent() {
+        let spans = vec![
+            TimelineSpan {
+                kind: TimelineSpanKind::ModelCall,
+                label: "model".to_owned(),
+                step: 1,
+                start: Duration::ZERO,
+                end: Duration::from_millis(100),
+            },
+            TimelineSpan {
+                kind: TimelineSpanKind::ToolCall,
+                label: "fetch_url".to_owned(),
+                step: 1,
+                start: Duration::from_millis(100),
+                end: Duration::from_millis(200),
+            },
+        ];
+        let rows_rect = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(418.0, 200.0));
+
+        let bars = timeline_bar_rects(&spans, rows_rect);
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].left(), 170.0);
+        assert_eq!(bars[0].width(), 100.0);
+        assert_eq!(bars[1].left(), 270.0);
+        assert!(bars[1].top() > bars[0].bottom());
+
+        let adapter = CanvasSurfaceAdapter::turn_timeline(&spans);
+        assert_eq!(adapter.kind().label(), "Turn timeline");
+    }
+
     fn graph_with_nodes(revision: u64, node_ids: &[&str]) -> ArchitectureGraph {
         ArchitectureGraph {
             nodes: node_ids.iter().copied().map(graph_node).collect(),
@@ -1690,6 +2194,43 @@ mod tests {
         }
     }
 
+    fn state_with_node_rects() -> CanvasState {
+        let rect = |id: &str, layer: u16, min: i32, max: i32| CanvasShapeObject {
+            id: id.to_owned(),
+            layer,
+            kind: CanvasShapeKind::Rectangle,
+            points: vec![
+                CanvasPoint { x: min, y: min },
+                CanvasPoint { x: max, y: max },
+            ],
+            text: None,
+            style: basic_style(),
+        };
+        let mut state = CanvasState::default();
+        state.apply(CanvasOp::set_scene_graph(graph_with_nodes(
+            1,
+            &["module:crate", "module:crate::tools"],
+        )));
+        state.apply(CanvasOp::apply_draw_command_batch(CanvasDrawCommandBatch {
+            sequence: 1,
+            commands: vec![
+                CanvasDrawCommand::UpsertShape {
+                    shape: rect("lane:modules", 0, 0, 120),
+                },
+                CanvasDrawCommand::UpsertShape {
+                    shape: rect("node:module:crate", 2, 10, 50),
+                },
+                CanvasDrawCommand::UpsertShape {
+                    shape: rect("node:module:crate::tools", 3, 40, 60),
+                },
+                CanvasDrawCommand::UpsertShape {
+                    shape: rect("node:module:missing", 4, 90, 100),
+                },
+            ],
+        }));
+        state
+    }
+
     fn basic_style() -> CanvasStyle {
         CanvasStyle {
             fill_color: Some("#ffffff".to_owned()),
@@ -0,0 +1,400 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+
+use super::load_subsystem_mapper;
+use super::renderer::SubsystemMapper;
+use crate::config::AgentSettings;
+use crate::editor::node_source_path;
+use crate::graph::{
+    ArchitectureEdgeKind, ArchitectureGraph, build_workspace_graph, resolve_workspace_roots,
+};
+use crate::model::client::{ChatResponse, ModelClient};
+
+const SUBSYSTEM_SUMMARY_PROMPT: &str = "You are a software architect describing one subsystem of a Rust codebase. You are given its modules and files, the graph edges touching it, and short source excerpts. Write a concise architecture summary in plain prose: what the subsystem is responsible for, its main parts, and how it relates to the rest of the crate. Use at most three short paragraphs and do not invent items that are not in the input.";
+const MAX_DESCRIBE_NODES: usize = 60;
+const MAX_DESCRIBE_EDGES: usize = 80;
+const MAX_EXCERPT_FILES: usize = 8;
+const MAX_EXCERPT_LINES: usize = 40;
+const MAX_EXCERPT_CHARS: usize = 2_000;
+
+/// Graph slice and source excerpts handed to the model for one subsystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsystemContext {
+    pub subsystem: String,
+    pub revision: u64,
+    pub node_ids: Vec<String>,
+    pub edges: Vec<String>,
+    pub excerpts: Vec<FileExcerpt>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileExcerpt {
+    pub path: String,
+    pub text: String,
+}
+
+/// Sorted, de-duplicated subsystem keys present in `graph`.
+pub fn subsystem_names(graph: &ArchitectureGraph, mapper: &SubsystemMapper) -> Vec<String> {
+    graph
+        .nodes
+        .iter()
+        .map(|node| mapper.resolve_subsystem(node))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+pub fn collect_subsystem_context(
+    graph: &ArchitectureGraph,
+    mapper: &SubsystemMapper,
+    workspace_root: &Path,
+    subsystem: &str,
+) -> Result<SubsystemContext> {
+    let subsystem_by_node = graph
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), mapper.resolve_subsystem(node)))
+        .collect::<HashMap<_, _>>();
+    let mut node_ids = graph
+        .nodes
+        .iter()
+        .filter(|node| {
+            subsystem_by_node.get(node.id.as_str()).map(String::as_str) == Some(subsystem)
+        })
+        .map(|node| node.id.clone())
+        .collect::<Vec<_>>();
+    if node_ids.is_empty() {
+        return Err(anyhow!(
+            "subsystem `{subsystem}` not found; available: {}",
+            subsystem_names(graph, mapper).join(", ")
+        ));
+    }
+    node_ids.sort();
+
+    let mut edges = graph
+        .edges
+        .iter()
+        .filter(|edge| {
+            node_ids.binary_search(&edge.from).is_ok() || node_ids.binary_search(&edge.to).is_ok()
+        })
+        .map(|edge| {
+            let mut line = format!(
+                "{} -> {} [{}]",
+                edge.from,
+                edge.to,
+                relation_label(edge.relation)
+            );
+            let other = if node_ids.binary_search(&edge.from).is_ok() {
+                &edge.to
+            } else {
+                &edge.from
+            };
+            if let Some(other_subsystem) = subsystem_by_node.get(other.as_str())
+                && other_subsystem != subsystem
+            {
+                line.push_str(&format!(" (subsystem {other_subsystem})"));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    edges.sort();
+
+    let mut excerpt_paths = Vec::new();
+    for node_id in &node_ids {
+        if let Some(path) = node_source_path(graph, node_id)
+            && !excerpt_paths.iter().any(|existing| existing == path)
+        {
+            excerpt_paths.push(path.to_owned());
+        }
+    }
+    // Shallow files (`mod.rs`, top-level modules) describe a subsystem best.
+    excerpt_paths.sort_by_key(|path| (path.matches('/').count(), path.clone()));
+    let excerpts = excerpt_paths
+        .into_iter()
+        .take(MAX_EXCERPT_FILES)
+        .filter_map(|path| {
+            let text = read_excerpt(&workspace_root.join(&path))?;
+            Some(FileExcerpt { path, text })
+        })
+        .collect();
+
+    Ok(SubsystemContext {
+        subsystem: subsystem.to_owned(),
+        revision: graph.revision,
+        node_ids,
+        edges,
+        excerpts,
+    })
+}
+
+fn relation_label(relation: ArchitectureEdgeKind) -> &'static str {
+    match relation {
+        ArchitectureEdgeKind::DefinesModule => "defines module",
+        ArchitectureEdgeKind::DeclaresModule => "declares module",
+        ArchitectureEdgeKind::ResolvesToFile => "resolves to file",
+        ArchitectureEdgeKind::Imports => "imports",
+        ArchitectureEdgeKind::Uses => "uses",
+    }
+}
+
+/// First lines of a source file, bounded in lines and characters. Unreadable files are
+/// skipped so a remote graph without a local checkout still gets a summary.
+fn read_excerpt(path: &Path) -> Option<String> {
+    let source = fs::read_to_string(path).ok()?;
+    let mut excerpt = String::new();
+    for line in source.lines().take(MAX_EXCERPT_LINES) {
+        if excerpt.len() + line.len() + 1 > MAX_EXCERPT_CHARS {
+            break;
+        }
+        excerpt.push_str(line);
+        excerpt.push('\n');
+    }
+    (!excerpt.trim().is_empty()).then_some(excerpt)
+}
+
+pub fn build_subsystem_prompt(context: &SubsystemContext) -> String {
+    let mut prompt = format!(
+        "Subsystem: {}\n\nNodes ({}):\n",
+        context.subsystem,
+        context.node_ids.len()
+    );
+    for node_id in context.node_ids.iter().take(MAX_DESCRIBE_NODES) {
+        prompt.push_str(&format!("- {node_id}\n"));
+    }
+    if context.node_ids.len() > MAX_DESCRIBE_NODES {
+        prompt.push_str(&format!(
+            "- … {} more\n",
+            context.node_ids.len() - MAX_DESCRIBE_NODES
+        ));
+    }
+
+    prompt.push_str(&format!("\nEdges ({}):\n", context.edges.len()));
+    for edge in context.edges.iter().take(MAX_DESCRIBE_EDGES) {
+        prompt.push_str(&format!("- {edge}\n"));
+    }
+    if context.edges.len() > MAX_DESCRIBE_EDGES {
+        prompt.push_str(&format!(
+            "- … {} more\n",
+            context.edges.len() - MAX_DESCRIBE_EDGES
+        ));
+    }
+
+    for excerpt in &context.excerpts {
+        prompt.push_str(&format!(
+            "\nExcerpt from {}:\n```rust\n{}```\n",
+            excerpt.path, excerpt.text
+        ));
+    }
+    prompt
+}
+
+pub async fn generate_subsystem_summary(
+    client: &ModelClient,
+    context: &SubsystemContext,
+) -> Result<String> {
+    let response = client
+        .chat(SUBSYSTEM_SUMMARY_PROMPT, &build_subsystem_prompt(context))
+        .await
+        .map_err(|error| anyhow!("subsystem summary model call failed: {error}"))?;
+    let ChatResponse::FinalText { text } = response else {
+        return Err(anyhow!(
+            "subsystem summary model call returned tool calls instead of text"
+        ));
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow!("subsystem summary model call returned empty text"));
+    }
+    Ok(text.to_owned())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum SubsystemSummaryState {
+    Pending,
+    Ready(String),
+    Failed(String),
+}
+
+/// Latest summary per subsystem, valid only for the graph revision it was generated from.
+#[derive(Debug, Default)]
+pub(super) struct SubsystemSummaryCache {
+    entries: HashMap<String, (u64, SubsystemSummaryState)>,
+}
+
+impl SubsystemSummaryCache {
+    pub(super) fn get(&self, subsystem: &str, revision: u64) -> Option<&SubsystemSummaryState> {
+        self.entries
+            .get(subsystem)
+            .filter(|(cached_revision, _)| *cached_revision == revision)
+            .map(|(_, state)| state)
+    }
+
+    pub(super) fn mark_pending(&mut self, subsystem: &str, revision: u64) {
+        self.entries.insert(
+            subsystem.to_owned(),
+            (revision, SubsystemSummaryState::Pending),
+        );
+    }
+
+    /// Stores a finished summary unless a newer revision was requested meanwhile.
+    pub(super) fn complete(
+        &mut self,
+        subsystem: &str,
+        revision: u64,
+        result: Result<String, String>,
+    ) {
+        if let Some((cached_revision, _)) = self.entries.get(subsystem)
+            && *cached_revision > revision
+        {
+            return;
+        }
+        let state = match result {
+            Ok(summary) => SubsystemSummaryState::Ready(summary),
+            Err(error) => SubsystemSummaryState::Failed(error),
+        };
+        self.entries.insert(subsystem.to_owned(), (revision, state));
+    }
+}
+
+/// `graph describe <subsystem>`: builds the workspace graph and prints a model-written
+/// summary of one subsystem.
+pub async fn run_graph_describe_command(
+    settings: &AgentSettings,
+    subsystem: &str,
+    workspace: Option<&Path>,
+    extra_roots: &[String],
+) -> Result<()> {
+    let workspace_roots = resolve_workspace_roots(workspace, extra_roots)
+        .context("failed to resolve workspace root for graph describe")?;
+    let workspace_root = &workspace_roots.primary;
+    let mapper = load_subsystem_mapper(settings, workspace_root)?;
+    let graph = build_workspace_graph(&workspace_roots, 0)?;
+    let context = collect_subsystem_context(&graph, &mapper, workspace_root, subsystem)?;
+
+    let client = ModelClient::new(settings.clone());
+    let summary = generate_subsystem_summary(&client, &context).await?;
+    println!(
+        "{} ({} nodes, {} edges)\n\n{summary}",
+        context.subsystem,
+        context.node_ids.len(),
+        context.edges.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::UNIX_EPOCH;
+
+    use super::{
+        SubsystemSummaryCache, SubsystemSummaryState, build_subsystem_prompt,
+        collect_subsystem_context, subsystem_names,
+    };
+    use crate::graph::{
+        ArchitectureEdge, ArchitectureEdgeKind, ArchitectureGraph, ArchitectureNode,
+        ArchitectureNodeKind,
+    };
+    use crate::studio::renderer::SubsystemMapper;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    fn graph() -> ArchitectureGraph {
+        let node = |id: &str, kind, path: Option<&str>| ArchitectureNode {
+            id: id.to_owned(),
+            display_label: id.to_owned(),
+            kind,
+            path: path.map(str::to_owned),
+        };
+        ArchitectureGraph {
+            nodes: vec![
+                node(
+                    "module:crate",
+                    ArchitectureNodeKind::Module,
+                    Some("src/lib.rs"),
+                ),
+                node(
+                    "module:crate::agent",
+                    ArchitectureNodeKind::Module,
+                    Some("src/agent/mod.rs"),
+                ),
+                node(
+                    "file:src/agent/mod.rs",
+                    ArchitectureNodeKind::File,
+                    Some("src/agent/mod.rs"),
+                ),
+            ],
+            edges: vec![
+                ArchitectureEdge {
+                    from: "module:crate".to_owned(),
+                    to: "module:crate::agent".to_owned(),
+                    relation: ArchitectureEdgeKind::DeclaresModule,
+                },
+                ArchitectureEdge {
+                    from: "module:crate::agent".to_owned(),
+                    to: "file:src/agent/mod.rs".to_owned(),
+                    relation: ArchitectureEdgeKind::ResolvesToFile,
+                },
+            ],
+            revision: 7,
+            generated_at: UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn collect_subsystem_context_gathers_nodes_edges_and_excerpts() {
+        let root = temp_path("studio_describe_context");
+        fs::create_dir_all(root.join("src/agent")).expect("create workspace");
+        fs::write(
+            root.join("src/agent/mod.rs"),
+            "//! Agent loop.\npub fn run() {}\n",
+        )
+        .expect("write source");
+        let mapper = SubsystemMapper::default();
+
+        let context =
+            collect_subsystem_context(&graph(), &mapper, &root, "agent").expect("context");
+
+        assert_eq!(context.revision, 7);
+        assert_eq!(
+            context.node_ids,
+            ["file:src/agent/mod.rs", "module:crate::agent"]
+        );
+        assert_eq!(
+            context.edges,
+            [
+                "module:crate -> module:crate::agent [declares module] (subsystem crate)",
+                "module:crate::agent -> file:src/agent/mod.rs [resolves to file]",
+            ]
+        );
+        assert_eq!(context.excerpts.len(), 1);
+        let prompt = build_subsystem_prompt(&context);
+        assert!(prompt.starts_with("Subsystem: agent\n"));
+        assert!(prompt.contains("Excerpt from src/agent/mod.rs:\n```rust\n//! Agent loop."));
+
+        let error = collect_subsystem_context(&graph(), &mapper, &root, "missing")
+            .expect_err("unknown subsystem");
+        assert!(error.to_string().contains("available: agent, crate"));
+        assert_eq!(subsystem_names(&graph(), &mapper), ["agent", "crate"]);
+        remove_dir_if_exists(&root);
+    }
+
+    #[test]
+    fn summary_cache_is_keyed_by_revision_and_ignores_stale_results() {
+        let mut cache = SubsystemSummaryCache::default();
+        cache.mark_pending("agent", 1);
+        cache.mark_pending("agent", 2);
+
+        cache.complete("agent", 1, Ok("old".to_owned()));
+        assert_eq!(cache.get("agent", 2), Some(&SubsystemSummaryState::Pending));
+
+        cache.complete("agent", 2, Ok("new".to_owned()));
+        assert_eq!(
+            cache.get("agent", 2),
+            Some(&SubsystemSummaryState::Ready("new".to_owned()))
+        );
+        assert_eq!(cache.get("agent", 3), None);
+    }
+}
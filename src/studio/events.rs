@@ -2,32 +2,67 @@ use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
-use crate::agent::{ChatTurnOutcome, ExecutedToolCall, TurnTraceSummary};
+use crate::agent::{ChatTurnOutcome, ExecutedToolCall, TurnCitation, TurnEvent, TurnTraceSummary};
 use crate::graph::ArchitectureGraph;
+use crate::graph::watch::GraphWatchHandle;
+
+use super::describe::SubsystemContext;
+use super::notes_graph::NotesGraph;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StudioCommand {
-    SubmitUserMessage { message: String },
+    SubmitUserMessage {
+        turn_id: u64,
+        message: String,
+    },
+    CancelTurn {
+        turn_id: u64,
+    },
+    /// Points completed-turn notifications at a restarted graph watch worker.
+    ReplaceGraphWatch {
+        handle: GraphWatchHandle,
+    },
+    /// Generates a model-written architecture summary outside the turn queue.
+    DescribeSubsystem {
+        context: SubsystemContext,
+    },
     Shutdown,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StudioEvent {
     TurnStarted {
+        turn_id: u64,
         message: String,
         started_at: SystemTime,
     },
     TurnCompleted {
+        turn_id: u64,
         message: String,
-        result: StudioTurnResult,
+        result: Box<StudioTurnResult>,
     },
     TurnFailed {
+        turn_id: u64,
         message: String,
         error: String,
     },
+    TurnCancelled {
+        turn_id: u64,
+        message: String,
+    },
+    /// Live tool activity of a running turn, before its result arrives.
+    TurnToolActivity {
+        turn_id: u64,
+        event: TurnEvent,
+    },
     CanvasUpdate {
         op: CanvasOp,
     },
+    SubsystemSummaryReady {
+        subsystem: String,
+        revision: u64,
+        result: Result<String, String>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -35,6 +70,7 @@ pub struct StudioTurnResult {
     pub final_text: String,
     pub trace: TurnTraceSummary,
     pub tool_calls: Vec<ExecutedToolCall>,
+    pub citations: Vec<TurnCitation>,
 }
 
 impl From<ChatTurnOutcome> for StudioTurnResult {
@@ -43,6 +79,7 @@ impl From<ChatTurnOutcome> for StudioTurnResult {
             final_text: outcome.final_text,
             trace: outcome.trace,
             tool_calls: outcome.tool_calls,
+            citations: outcome.citations,
         }
     }
 }
@@ -50,6 +87,7 @@ impl From<ChatTurnOutcome> for StudioTurnResult {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CanvasSceneData {
     ArchitectureGraph { graph: ArchitectureGraph },
+    NotesGraph { graph: NotesGraph },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -145,6 +183,9 @@ pub enum CanvasOp {
     SetFocusedTarget {
         target_id: Option<String>,
     },
+    SetSelectedTargets {
+        target_ids: Vec<String>,
+    },
     UpsertAnnotation {
         id: String,
         text: String,
@@ -178,6 +219,12 @@ impl CanvasOp {
         }
     }
 
+    pub fn set_scene_notes(graph: NotesGraph) -> Self {
+        Self::SetSceneData {
+            scene: CanvasSceneData::NotesGraph { graph },
+        }
+    }
+
     pub fn set_highlighted_targets(target_ids: Vec<String>) -> Self {
         Self::SetHighlightedTargets { target_ids }
     }
@@ -186,6 +233,10 @@ impl CanvasOp {
         Self::SetFocusedTarget { target_id }
     }
 
+    pub fn set_selected_targets(target_ids: Vec<String>) -> Self {
+        Self::SetSelectedTargets { target_ids }
+    }
+
     pub fn upsert_annotation(
         id: impl Into<String>,
         text: impl Into<String>,
@@ -203,11 +254,19 @@ impl CanvasOp {
     }
 }
 
+/// Pointer-driven canvas changes reported by `CanvasState::handle_pointer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanvasSelectionEvent {
+    HoverChanged { target_id: Option<String> },
+    SelectionChanged { target_ids: Vec<String> },
+    ContextMenuRequested { target_id: String },
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::{Duration, UNIX_EPOCH};
 
-    use crate::agent::{ChatTurnOutcome, TurnTraceSummary};
+    use crate::agent::{ChatTurnOutcome, TurnCitation, TurnTraceSummary};
     use crate::graph::{ArchitectureGraph, ArchitectureNode, ArchitectureNodeKind};
     use serde_json::json;
 
@@ -218,6 +277,8 @@ mod tests {
         let outcome = ChatTurnOutcome {
             final_text: "final response".to_owned(),
             trace: TurnTraceSummary {
+                version: crate::build_info::VERSION.to_owned(),
+                git_commit: crate::build_info::GIT_COMMIT.to_owned(),
                 input_chars: 5,
                 output_chars: Some(14),
                 steps_executed: 1,
@@ -226,14 +287,30 @@ mod tests {
                 total_model_latency: Duration::from_millis(5),
                 total_tool_latency: Duration::from_millis(0),
                 tool_names: Vec::new(),
+                stripped_tool_call_blocks: 0,
+                trimmed_messages: 0,
+                compacted_messages: 0,
+                user: None,
+                injection_detections: Vec::new(),
+                timeline: Vec::new(),
+                context: Default::default(),
             },
             tool_calls: Vec::new(),
+            citations: vec![TurnCitation {
+                marker: 1,
+                tool_call_index: 0,
+                tool_name: "fetch_url".to_owned(),
+                sources: vec!["https://example.com/".to_owned()],
+            }],
+            moderation: None,
+            was_repaired_locally: false,
         };
 
         let studio_result = StudioTurnResult::from(outcome.clone());
         assert_eq!(studio_result.final_text, outcome.final_text);
         assert_eq!(studio_result.trace, outcome.trace);
         assert_eq!(studio_result.tool_calls, outcome.tool_calls);
+        assert_eq!(studio_result.citations, outcome.citations);
     }
 
     #[test]
@@ -249,12 +326,16 @@ mod tests {
             }
             CanvasOp::SetHighlightedTargets { .. }
             | CanvasOp::SetFocusedTarget { .. }
+            | CanvasOp::SetSelectedTargets { .. }
             | CanvasOp::UpsertAnnotation { .. }
             | CanvasOp::HighlightNodes { .. }
             | CanvasOp::FocusNode { .. }
             | CanvasOp::AddAnnotation { .. }
             | CanvasOp::SetGraph { .. }
             | CanvasOp::ApplyDrawCommandBatch { .. }
+            | CanvasOp::SetSceneData {
+                scene: CanvasSceneData::NotesGraph { .. },
+            }
             | CanvasOp::ClearAnnotations => panic!("unexpected canvas op"),
         }
     }
@@ -280,6 +361,7 @@ mod tests {
             CanvasOp::SetSceneData { .. }
             | CanvasOp::SetHighlightedTargets { .. }
             | CanvasOp::SetFocusedTarget { .. }
+            | CanvasOp::SetSelectedTargets { .. }
             | CanvasOp::SetGraph { .. }
             | CanvasOp::HighlightNodes { .. }
             | CanvasOp::FocusNode { .. }
@@ -303,6 +385,7 @@ mod tests {
             }
             CanvasOp::SetSceneData { .. }
             | CanvasOp::SetFocusedTarget { .. }
+            | CanvasOp::SetSelectedTargets { .. }
             | CanvasOp::UpsertAnnotation { .. }
             | CanvasOp::SetGraph { .. }
             | CanvasOp::FocusNode { .. }
@@ -318,6 +401,7 @@ mod tests {
             }
             CanvasOp::SetSceneData { .. }
             | CanvasOp::SetHighlightedTargets { .. }
+            | CanvasOp::SetSelectedTargets { .. }
             | CanvasOp::UpsertAnnotation { .. }
             | CanvasOp::SetGraph { .. }
             | CanvasOp::HighlightNodes { .. }
@@ -1,110 +1,239 @@
-use std::collections::{BTreeMap, BTreeSet};
-use std::path::PathBuf;
-use std::time::{Duration, SystemTime};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use eframe::egui;
-use tokio::runtime::Handle;
+use reqwest::Url;
+use tokio::runtime::{Handle, RuntimeFlavor};
 use tokio::sync::mpsc::error::TryRecvError;
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::sync::mpsc::{Receiver, UnboundedSender, channel, unbounded_channel};
 use tracing::{info, warn};
 
-use crate::agent::{ExecutedToolCall, run_chat_turn};
+use crate::agent::{
+    ContextLevel, ContextUsage, DEFAULT_HISTORY_SEARCH_LIMIT, ExecutedToolCall, HistoryMatch,
+    TimelineSpan, TurnCitation, TurnEvent, TurnHistory, archive_transcript, format_recorded_at,
+    generate_session_summary, resolve_tool_working_dir,
+};
 use crate::config::AgentSettings;
-use crate::graph::ArchitectureGraph;
+use crate::crash::{
+    StudioRecovery, discard_own_studio_recovery, log_dir_from_env, set_studio_session,
+    take_studio_recovery,
+};
+use crate::editor::{node_source_path, open_workspace_path_detached};
+use crate::export::{
+    ExportFormat, Transcript, TranscriptEntry, TranscriptRole, TranscriptToolCall,
+};
+use crate::graph::delta::graph_change_delta;
+use crate::graph::remote::{parse_remote_graph_base_url, spawn_remote_graph_worker};
+use crate::graph::rules::{DependencyRules, load_dependency_rules};
+use crate::graph::updates::GraphUpdateReceiver;
 use crate::graph::watch::{
-    GraphRefreshTrigger, GraphRefreshUpdate, GraphWatchHandle, spawn_graph_watch_worker,
+    GraphRefreshTrigger, GraphRefreshUpdate, GraphWatchHandle, GraphWatchHealth,
+    spawn_graph_watch_worker,
+};
+use crate::graph::{
+    ArchitectureGraph, ExtraWorkspaceRoot, WorkspaceRoots, resolve_workspace_roots,
 };
+use crate::model::client::{ModelClient, ModelMessage};
+use crate::text::truncate_graphemes;
+use crate::tr;
 
+mod accessibility;
 pub mod canvas;
+pub mod describe;
 pub mod events;
+pub mod notes_graph;
+mod profiler;
+mod remote_model;
 pub mod renderer;
-
+mod runtime;
+mod session_notes;
+mod snapshots;
+mod supervisor;
+
+use self::accessibility::{
+    FocusRegion, PREFERENCES_FILE, StudioPreferences, StudioShortcut, consume_shortcut,
+    high_contrast_enabled, paint_focus_ring, themed,
+};
 use self::canvas::{
     CanvasState, CanvasSurfaceAdapter, CanvasSurfaceAdapterKind, CanvasToolCard, CanvasViewport,
     GraphSurfaceAdapterOptions,
 };
-use self::events::{CanvasOp, StudioCommand, StudioEvent, StudioTurnResult};
+use self::describe::{SubsystemSummaryCache, SubsystemSummaryState, collect_subsystem_context};
+use self::events::{CanvasOp, CanvasSelectionEvent, StudioCommand, StudioEvent};
+use self::notes_graph::{NotesGraph, NotesGraphRenderInput, NotesGraphRenderer, touched_note_ids};
+use self::profiler::{FrameProfiler, FrameSample};
+use self::remote_model::{RemoteModelStatus, spawn_remote_model_poller};
 use self::renderer::{
     ArchitectureActivitySummary, ArchitectureOverviewRenderInput, ArchitectureOverviewRenderer,
     SubsystemMapper,
 };
+use self::runtime::spawn_runtime_worker;
+use self::session_notes::SessionNotes;
+use self::snapshots::{SnapshotGraphs, TurnSnapshotStore};
+use self::supervisor::{WorkerHealth, WorkerSupervisor};
 
 const APP_TITLE: &str = "mjolne_vibes studio";
 const MAX_CANVAS_SUMMARIES: usize = 24;
 const MAX_CANVAS_TOOL_CARDS: usize = 16;
-const MAX_TURN_SNAPSHOTS: usize = 24;
 const CANVAS_PREVIEW_CHAR_LIMIT: usize = 180;
 const MAX_IMPACT_NODE_ANNOTATIONS: usize = 12;
 const MAX_GRAPH_UPDATES_PER_FRAME: usize = 4;
+/// Runtime events the UI has not drained yet. When full the runtime worker waits, and
+/// live tool progress is dropped, instead of queueing without limit behind a stalled frame.
+const STUDIO_EVENT_CHANNEL_CAPACITY: usize = 256;
+const MAX_QUEUED_TURNS: usize = 8;
+/// Workspace-relative directory for "Export" conversation files.
+const EXPORT_DIR: &str = ".mjolne/exports";
 
 fn studio_text() -> egui::Color32 {
-    egui::Color32::from_rgb(19, 29, 40)
+    themed(
+        egui::Color32::from_rgb(19, 29, 40),
+        egui::Color32::from_rgb(0, 0, 0),
+    )
 }
 
 fn studio_muted_text() -> egui::Color32 {
-    egui::Color32::from_rgb(94, 109, 127)
+    themed(
+        egui::Color32::from_rgb(94, 109, 127),
+        egui::Color32::from_rgb(38, 45, 54),
+    )
 }
 
 fn studio_app_bg() -> egui::Color32 {
-    egui::Color32::from_rgb(219, 231, 243)
+    themed(
+        egui::Color32::from_rgb(219, 231, 243),
+        egui::Color32::from_rgb(255, 255, 255),
+    )
 }
 
 fn studio_panel_surface() -> egui::Color32 {
-    egui::Color32::from_rgb(238, 246, 252)
+    themed(
+        egui::Color32::from_rgb(238, 246, 252),
+        egui::Color32::from_rgb(255, 255, 255),
+    )
 }
 
 fn studio_panel_surface_alt() -> egui::Color32 {
-    egui::Color32::from_rgb(246, 251, 255)
+    themed(
+        egui::Color32::from_rgb(246, 251, 255),
+        egui::Color32::from_rgb(255, 255, 255),
+    )
 }
 
 fn studio_stage_surface() -> egui::Color32 {
-    egui::Color32::from_rgb(230, 240, 249)
+    themed(
+        egui::Color32::from_rgb(230, 240, 249),
+        egui::Color32::from_rgb(250, 250, 250),
+    )
 }
 
 fn studio_panel_tint() -> egui::Color32 {
-    egui::Color32::from_rgb(210, 226, 242)
+    themed(
+        egui::Color32::from_rgb(210, 226, 242),
+        egui::Color32::from_rgb(236, 236, 236),
+    )
 }
 
 fn studio_border() -> egui::Color32 {
-    egui::Color32::from_rgb(153, 179, 208)
+    themed(
+        egui::Color32::from_rgb(153, 179, 208),
+        egui::Color32::from_rgb(40, 40, 40),
+    )
 }
 
 fn studio_border_strong() -> egui::Color32 {
-    egui::Color32::from_rgb(98, 137, 176)
+    themed(
+        egui::Color32::from_rgb(98, 137, 176),
+        egui::Color32::from_rgb(0, 0, 0),
+    )
 }
 
 fn studio_accent() -> egui::Color32 {
-    egui::Color32::from_rgb(16, 112, 165)
+    themed(
+        egui::Color32::from_rgb(16, 112, 165),
+        egui::Color32::from_rgb(0, 72, 145),
+    )
 }
 
 fn studio_accent_soft() -> egui::Color32 {
-    egui::Color32::from_rgb(206, 229, 247)
+    themed(
+        egui::Color32::from_rgb(206, 229, 247),
+        egui::Color32::from_rgb(214, 232, 250),
+    )
 }
 
 fn studio_mode_active() -> egui::Color32 {
-    egui::Color32::from_rgb(24, 129, 187)
+    themed(
+        egui::Color32::from_rgb(24, 129, 187),
+        egui::Color32::from_rgb(0, 72, 145),
+    )
 }
 
 fn studio_mode_inactive() -> egui::Color32 {
-    egui::Color32::from_rgb(226, 236, 246)
+    themed(
+        egui::Color32::from_rgb(226, 236, 246),
+        egui::Color32::from_rgb(255, 255, 255),
+    )
+}
+
+fn studio_focus_ring() -> egui::Color32 {
+    themed(
+        egui::Color32::from_rgb(214, 118, 24),
+        egui::Color32::from_rgb(196, 72, 0),
+    )
 }
 
-pub fn run_studio(settings: &AgentSettings) -> Result<()> {
+/// Starts the studio UI on `workspace` (default: the launch directory) plus any
+/// `extra_roots`. With `remote_graph_url`, the architecture graph is read from a remote
+/// `serve` instance instead of watching the local workspace.
+pub fn run_studio(
+    settings: &AgentSettings,
+    remote_graph_url: Option<&str>,
+    workspace: Option<&std::path::Path>,
+    extra_roots: &[String],
+) -> Result<()> {
     let runtime_handle = Handle::try_current().context("studio requires a tokio runtime")?;
-    let workspace_root =
-        std::env::current_dir().context("failed to resolve workspace root for studio")?;
+    let workspace_roots = resolve_workspace_roots(workspace, extra_roots)
+        .context("failed to resolve workspace root for studio")?;
+    let workspace_root = workspace_roots.primary.clone();
     let subsystem_mapper = load_subsystem_mapper(settings, &workspace_root)?;
+    let dependency_rules = load_dependency_rules(settings, &workspace_root)?.unwrap_or_default();
+    let remote_graph_url = remote_graph_url
+        .map(parse_remote_graph_base_url)
+        .transpose()?;
 
+    // Commands follow user actions and queued turns are capped at `MAX_QUEUED_TURNS`, so
+    // only the worker-to-UI directions need backpressure.
     let (command_tx, command_rx) = unbounded_channel::<StudioCommand>();
-    let (event_tx, event_rx) = unbounded_channel::<StudioEvent>();
-    let (graph_watch_handle, graph_update_rx) =
-        spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
+    let (event_tx, event_rx) = channel::<StudioEvent>(STUDIO_EVENT_CHANNEL_CAPACITY);
+    let (graph_watch_handle, graph_update_rx) = spawn_graph_source(
+        &runtime_handle,
+        workspace_roots.clone(),
+        remote_graph_url.as_ref(),
+    );
+    let remote_model = remote_graph_url
+        .as_ref()
+        .map(|url| spawn_remote_model_poller(&runtime_handle, url.clone()));
     let app_settings = settings.clone();
+    let pending_recovery = take_studio_recovery(&log_dir_from_env(), &workspace_root);
 
+    let session_notes = settings.studio_session_notes.then(|| {
+        SessionNotes::start(
+            resolve_tool_working_dir(settings).join(&settings.notes_dir),
+            settings.notes_quota(),
+        )
+    });
+    let mut runtime_settings = settings.clone();
+    runtime_settings.workspace_root = Some(workspace_root.clone());
+    if let Some(session_notes) = &session_notes {
+        runtime_settings.notes_dir = session_notes.session_dir().display().to_string();
+    }
     spawn_runtime_worker(
         &runtime_handle,
-        settings.clone(),
+        runtime_settings,
         command_rx,
         event_tx,
         graph_watch_handle.clone(),
@@ -113,6 +242,12 @@ pub fn run_studio(settings: &AgentSettings) -> Result<()> {
         provider = %settings.model_provider,
         model = %settings.model,
         workspace_root = %workspace_root.display(),
+        extra_roots = workspace_roots.extra.len(),
+        dependency_rules = dependency_rules.rule_count(),
+        remote_graph_url = remote_graph_url.as_ref().map(Url::as_str),
+        session_notes_dir = session_notes
+            .as_ref()
+            .map(|notes| notes.session_dir().display().to_string()),
         "starting native studio shell"
     );
 
@@ -120,20 +255,42 @@ pub fn run_studio(settings: &AgentSettings) -> Result<()> {
         APP_TITLE,
         eframe::NativeOptions::default(),
         Box::new(move |_cc| {
-            Ok(Box::new(StudioApp::new(
-                app_settings,
-                subsystem_mapper,
-                command_tx,
-                event_rx,
-                graph_update_rx,
-                graph_watch_handle,
-                workspace_root,
-            )))
+            Ok(Box::new(
+                StudioApp::new(
+                    app_settings,
+                    subsystem_mapper,
+                    StudioWorkerChannels {
+                        command_tx,
+                        event_rx,
+                        graph_update_rx,
+                        graph_watch_handle,
+                    },
+                    workspace_root,
+                    runtime_handle,
+                )
+                .with_remote_graph_url(remote_graph_url)
+                .with_remote_model(remote_model)
+                .with_extra_workspace_roots(workspace_roots.extra)
+                .with_dependency_rules(dependency_rules)
+                .with_session_notes(session_notes)
+                .with_pending_recovery(pending_recovery),
+            ))
         }),
     )
     .map_err(|error| anyhow::anyhow!("studio UI exited with error: {error}"))
 }
 
+fn spawn_graph_source(
+    handle: &Handle,
+    workspace_roots: WorkspaceRoots,
+    remote_graph_url: Option<&Url>,
+) -> (GraphWatchHandle, GraphUpdateReceiver) {
+    match remote_graph_url {
+        Some(url) => spawn_remote_graph_worker(handle, url.clone()),
+        None => spawn_graph_watch_worker(handle, workspace_roots),
+    }
+}
+
 fn load_subsystem_mapper(
     settings: &AgentSettings,
     workspace_root: &std::path::Path,
@@ -162,64 +319,6 @@ fn load_subsystem_mapper(
     Ok(mapper)
 }
 
-fn spawn_runtime_worker(
-    handle: &Handle,
-    settings: AgentSettings,
-    mut command_rx: UnboundedReceiver<StudioCommand>,
-    event_tx: UnboundedSender<StudioEvent>,
-    graph_watch_handle: GraphWatchHandle,
-) {
-    let _task = handle.spawn(async move {
-        while let Some(command) = command_rx.recv().await {
-            match command {
-                StudioCommand::SubmitUserMessage { message } => {
-                    if event_tx
-                        .send(StudioEvent::TurnStarted {
-                            message: message.clone(),
-                            started_at: SystemTime::now(),
-                        })
-                        .is_err()
-                    {
-                        break;
-                    }
-
-                    match run_chat_turn(&settings, &message).await {
-                        Ok(outcome) => {
-                            let result = StudioTurnResult::from(outcome);
-
-                            if event_tx
-                                .send(StudioEvent::TurnCompleted {
-                                    message: message.clone(),
-                                    result,
-                                })
-                                .is_err()
-                            {
-                                break;
-                            }
-                        }
-                        Err(error) => {
-                            let details = error.details();
-                            if event_tx
-                                .send(StudioEvent::TurnFailed {
-                                    message: message.clone(),
-                                    error: details.clone(),
-                                })
-                                .is_err()
-                            {
-                                break;
-                            }
-                        }
-                    }
-
-                    // Graph refreshes are decoupled from turn success/failure.
-                    graph_watch_handle.notify_turn_completed();
-                }
-                StudioCommand::Shutdown => break,
-            }
-        }
-    });
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ChatSpeaker {
     User,
@@ -241,31 +340,97 @@ impl ChatSpeaker {
 struct ChatEntry {
     speaker: ChatSpeaker,
     text: String,
+    /// Tool calls behind an assistant answer; kept for conversation exports.
+    tool_calls: Vec<TranscriptToolCall>,
 }
 
 impl ChatEntry {
     fn user(text: impl Into<String>) -> Self {
-        Self {
-            speaker: ChatSpeaker::User,
-            text: text.into(),
-        }
+        Self::new(ChatSpeaker::User, text)
     }
 
-    fn assistant(text: impl Into<String>) -> Self {
+    fn assistant(text: impl Into<String>, tool_calls: Vec<TranscriptToolCall>) -> Self {
         Self {
-            speaker: ChatSpeaker::Assistant,
-            text: text.into(),
+            tool_calls,
+            ..Self::new(ChatSpeaker::Assistant, text)
         }
     }
 
     fn system(text: impl Into<String>) -> Self {
+        Self::new(ChatSpeaker::System, text)
+    }
+
+    fn new(speaker: ChatSpeaker, text: impl Into<String>) -> Self {
         Self {
-            speaker: ChatSpeaker::System,
+            speaker,
             text: text.into(),
+            tool_calls: Vec::new(),
         }
     }
 }
 
+/// Chat entries for a transcript saved by `studio_transcript`, used to restore a
+/// conversation after a crash.
+fn chat_history_from_transcript(transcript: &Transcript) -> Vec<ChatEntry> {
+    transcript
+        .entries
+        .iter()
+        .map(|entry| ChatEntry {
+            speaker: match entry.role {
+                TranscriptRole::User => ChatSpeaker::User,
+                TranscriptRole::Assistant => ChatSpeaker::Assistant,
+                TranscriptRole::System => ChatSpeaker::System,
+            },
+            text: entry.text.clone(),
+            tool_calls: entry.tool_calls.clone(),
+        })
+        .collect()
+}
+
+/// Studio chat history as an export transcript titled after the workspace.
+fn studio_transcript(chat_history: &[ChatEntry], workspace_root: &Path) -> Transcript {
+    let workspace = workspace_root.file_name().map_or_else(
+        || workspace_root.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    Transcript {
+        title: format!("Studio conversation ({workspace})"),
+        summary: None,
+        entries: chat_history
+            .iter()
+            .map(|entry| TranscriptEntry {
+                role: match entry.speaker {
+                    ChatSpeaker::User => TranscriptRole::User,
+                    ChatSpeaker::Assistant => TranscriptRole::Assistant,
+                    ChatSpeaker::System => TranscriptRole::System,
+                },
+                text: entry.text.clone(),
+                tool_calls: entry.tool_calls.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// The user and assistant text of the chat history, for the session summary call.
+fn studio_model_conversation(chat_history: &[ChatEntry]) -> Vec<ModelMessage> {
+    chat_history
+        .iter()
+        .filter_map(|entry| match entry.speaker {
+            ChatSpeaker::User => Some(ModelMessage::user(entry.text.clone())),
+            ChatSpeaker::Assistant => Some(ModelMessage::assistant_text(entry.text.clone())),
+            ChatSpeaker::System => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StudioQueuedTurn {
+    turn_id: u64,
+    message: String,
+    running: bool,
+    cancel_requested: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct CanvasTurnSummary {
     user_message: String,
@@ -298,12 +463,18 @@ struct CanvasTurnSnapshot {
     changed_target_ids: Vec<String>,
     impact_target_ids: Vec<String>,
     intent_target_ids: Vec<String>,
-    baseline_graph: Option<ArchitectureGraph>,
-    outcome_graph: ArchitectureGraph,
+    graphs: SnapshotGraphs,
 }
 
 type CanvasSurfaceKind = CanvasSurfaceAdapterKind;
 
+/// Right-click menu for a canvas node, anchored where it was requested.
+#[derive(Debug, Clone, PartialEq)]
+struct CanvasContextMenu {
+    target_id: String,
+    anchor: egui::Pos2,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 struct GraphSurfaceState {
     changed_target_ids: Vec<String>,
@@ -398,13 +569,27 @@ impl GraphSurfaceState {
     }
 }
 
+/// Endpoints the studio app holds for its runtime and graph watch workers.
+struct StudioWorkerChannels {
+    command_tx: UnboundedSender<StudioCommand>,
+    event_rx: Receiver<StudioEvent>,
+    graph_update_rx: GraphUpdateReceiver,
+    graph_watch_handle: GraphWatchHandle,
+}
+
 struct StudioApp {
     settings: AgentSettings,
     workspace_root: PathBuf,
+    extra_workspace_roots: Vec<ExtraWorkspaceRoot>,
+    runtime_handle: Handle,
+    remote_graph_url: Option<Url>,
+    /// Active model of the `--remote-graph` server, shown next to the local model.
+    remote_model: Option<Arc<RemoteModelStatus>>,
     subsystem_mapper: SubsystemMapper,
+    dependency_rules: DependencyRules,
     command_tx: UnboundedSender<StudioCommand>,
-    event_rx: UnboundedReceiver<StudioEvent>,
-    graph_update_rx: UnboundedReceiver<GraphRefreshUpdate>,
+    event_rx: Receiver<StudioEvent>,
+    graph_update_rx: GraphUpdateReceiver,
     graph_watch_handle: GraphWatchHandle,
     input_buffer: String,
     chat_history: Vec<ChatEntry>,
@@ -414,71 +599,171 @@ struct StudioApp {
     active_canvas_surface: CanvasSurfaceKind,
     chat_panel_expanded: bool,
     canvas_viewport: CanvasViewport,
+    canvas_context_menu: Option<CanvasContextMenu>,
+    inspector_subsystem: Option<String>,
+    subsystem_summaries: SubsystemSummaryCache,
     canvas_tool_cards: Vec<CanvasToolCard>,
+    last_turn_timeline: Vec<TimelineSpan>,
+    /// Notes surface scene; its highlights are the notes the latest turn touched.
+    notes_canvas: CanvasState,
+    last_turn_touched_notes: Vec<String>,
+    /// Context size of the last completed turn; studio turns do not share history.
+    last_turn_context: Option<ContextUsage>,
     next_draw_command_sequence: u64,
     next_tool_card_id: u64,
     next_turn_snapshot_id: u64,
     pending_turn_snapshot: Option<PendingTurnSnapshot>,
-    turn_snapshots: Vec<CanvasTurnSnapshot>,
+    turn_snapshots: TurnSnapshotStore,
     selected_snapshot_index: Option<usize>,
     snapshot_transition_pulse: bool,
     canvas_diff_mode: CanvasDiffMode,
     turn_summaries: Vec<CanvasTurnSummary>,
     theme_applied: bool,
     turn_in_flight: bool,
+    queued_turns: Vec<StudioQueuedTurn>,
+    next_queued_turn_id: u64,
     runtime_disconnected: bool,
     graph_watch_disconnected: bool,
+    runtime_supervisor: WorkerSupervisor,
+    graph_watch_supervisor: WorkerSupervisor,
+    profiler: FrameProfiler,
+    preferences: StudioPreferences,
+    /// Region last reached with `F6`; `pending_focus` is focused on the next render.
+    focus_region: Option<FocusRegion>,
+    pending_focus: Option<FocusRegion>,
+    /// Chat history length last handed to the panic hook's recovery snapshot.
+    crash_session_len: usize,
+    /// Conversation left by a crashed studio, offered back until the user decides.
+    pending_recovery: Option<StudioRecovery>,
+    /// This session's own notes directory when `STUDIO_SESSION_NOTES` is on.
+    session_notes: Option<SessionNotes>,
+    /// The `AGENT_HISTORY_FILE` ledger searched from the chat pane, when configured.
+    turn_history: Option<TurnHistory>,
+    history_query: String,
+    history_results: Option<Result<Vec<HistoryMatch>, String>>,
 }
 
 impl StudioApp {
     fn new(
         settings: AgentSettings,
         subsystem_mapper: SubsystemMapper,
-        command_tx: UnboundedSender<StudioCommand>,
-        event_rx: UnboundedReceiver<StudioEvent>,
-        graph_update_rx: UnboundedReceiver<GraphRefreshUpdate>,
-        graph_watch_handle: GraphWatchHandle,
+        channels: StudioWorkerChannels,
         workspace_root: PathBuf,
+        runtime_handle: Handle,
     ) -> Self {
+        let StudioWorkerChannels {
+            command_tx,
+            event_rx,
+            graph_update_rx,
+            graph_watch_handle,
+        } = channels;
+        let turn_snapshots = TurnSnapshotStore::from_settings(&settings);
+        let profiler = FrameProfiler::new(settings.studio_profile_overlay);
+        let preferences = StudioPreferences::load(&workspace_root);
+        preferences.activate();
+        let turn_history = TurnHistory::from_settings(&settings);
         Self {
             settings,
             workspace_root,
+            extra_workspace_roots: Vec::new(),
+            runtime_handle,
+            remote_graph_url: None,
+            remote_model: None,
             subsystem_mapper,
+            dependency_rules: DependencyRules::default(),
             command_tx,
             event_rx,
             graph_update_rx,
             graph_watch_handle,
             input_buffer: String::new(),
-            chat_history: vec![ChatEntry::system(
-                "Studio ready. Send a prompt to run a chat turn.",
-            )],
+            chat_history: vec![ChatEntry::system(tr!("studio.ready"))],
             canvas: CanvasState::default(),
             canvas_status: "Idle".to_owned(),
             graph_surface: GraphSurfaceState::default(),
             active_canvas_surface: CanvasSurfaceKind::ArchitectureGraph,
             chat_panel_expanded: true,
             canvas_viewport: CanvasViewport::default(),
+            canvas_context_menu: None,
+            inspector_subsystem: None,
+            subsystem_summaries: SubsystemSummaryCache::default(),
             canvas_tool_cards: Vec::new(),
+            last_turn_timeline: Vec::new(),
+            notes_canvas: CanvasState::default(),
+            last_turn_touched_notes: Vec::new(),
+            last_turn_context: None,
             next_draw_command_sequence: 0,
             next_tool_card_id: 0,
             next_turn_snapshot_id: 1,
             pending_turn_snapshot: None,
-            turn_snapshots: Vec::new(),
+            turn_snapshots,
             selected_snapshot_index: None,
             snapshot_transition_pulse: false,
             canvas_diff_mode: CanvasDiffMode::Live,
             turn_summaries: Vec::new(),
             theme_applied: false,
             turn_in_flight: false,
+            queued_turns: Vec::new(),
+            next_queued_turn_id: 1,
             runtime_disconnected: false,
             graph_watch_disconnected: false,
+            runtime_supervisor: WorkerSupervisor::default(),
+            graph_watch_supervisor: WorkerSupervisor::default(),
+            profiler,
+            preferences,
+            focus_region: None,
+            pending_focus: None,
+            crash_session_len: 0,
+            pending_recovery: None,
+            session_notes: None,
+            turn_history,
+            history_query: String::new(),
+            history_results: None,
         }
     }
 
+    fn with_remote_graph_url(mut self, remote_graph_url: Option<Url>) -> Self {
+        self.remote_graph_url = remote_graph_url;
+        self
+    }
+
+    fn with_remote_model(mut self, remote_model: Option<Arc<RemoteModelStatus>>) -> Self {
+        self.remote_model = remote_model;
+        self
+    }
+
+    fn with_extra_workspace_roots(
+        mut self,
+        extra_workspace_roots: Vec<ExtraWorkspaceRoot>,
+    ) -> Self {
+        self.extra_workspace_roots = extra_workspace_roots;
+        self
+    }
+
+    fn with_dependency_rules(mut self, dependency_rules: DependencyRules) -> Self {
+        self.dependency_rules = dependency_rules;
+        self
+    }
+
+    fn with_pending_recovery(mut self, pending_recovery: Option<StudioRecovery>) -> Self {
+        self.pending_recovery = pending_recovery;
+        self
+    }
+
+    fn with_session_notes(mut self, session_notes: Option<SessionNotes>) -> Self {
+        self.session_notes = session_notes;
+        self.refresh_session_notes();
+        self
+    }
+
+    /// Applies the studio style, scaled and themed by `preferences`. Re-runs after a
+    /// preference change clears `theme_applied`.
     fn ensure_theme(&mut self, ctx: &egui::Context) {
         if self.theme_applied {
             return;
         }
+        // The studio scales its text styles itself; egui's keyboard zoom would fight it
+        // and forget the setting on restart.
+        ctx.options_mut(|options| options.zoom_with_keyboard = false);
 
         let mut style = (*ctx.style()).clone();
         style.spacing.item_spacing = egui::vec2(9.0, 9.0);
@@ -491,23 +776,35 @@ impl StudioApp {
 
         style.text_styles.insert(
             egui::TextStyle::Heading,
-            egui::FontId::new(24.0, egui::FontFamily::Proportional),
+            egui::FontId::new(
+                self.preferences.scaled(24.0),
+                egui::FontFamily::Proportional,
+            ),
         );
         style.text_styles.insert(
             egui::TextStyle::Button,
-            egui::FontId::new(13.4, egui::FontFamily::Proportional),
+            egui::FontId::new(
+                self.preferences.scaled(13.4),
+                egui::FontFamily::Proportional,
+            ),
         );
         style.text_styles.insert(
             egui::TextStyle::Body,
-            egui::FontId::new(14.1, egui::FontFamily::Proportional),
+            egui::FontId::new(
+                self.preferences.scaled(14.1),
+                egui::FontFamily::Proportional,
+            ),
         );
         style.text_styles.insert(
             egui::TextStyle::Small,
-            egui::FontId::new(11.2, egui::FontFamily::Proportional),
+            egui::FontId::new(
+                self.preferences.scaled(11.2),
+                egui::FontFamily::Proportional,
+            ),
         );
         style.text_styles.insert(
             egui::TextStyle::Monospace,
-            egui::FontId::new(11.5, egui::FontFamily::Monospace),
+            egui::FontId::new(self.preferences.scaled(11.5), egui::FontFamily::Monospace),
         );
 
         let mut visuals = egui::Visuals::light();
@@ -542,12 +839,142 @@ impl StudioApp {
         visuals.hyperlink_color = egui::Color32::from_rgb(39, 110, 214);
         visuals.warn_fg_color = egui::Color32::from_rgb(184, 121, 34);
         visuals.error_fg_color = egui::Color32::from_rgb(193, 67, 67);
+        if self.preferences.high_contrast {
+            visuals.extreme_bg_color = egui::Color32::WHITE;
+            visuals.code_bg_color = egui::Color32::from_rgb(240, 240, 240);
+            visuals.window_stroke = egui::Stroke::new(2.0, studio_border_strong());
+            visuals.selection.bg_fill = studio_accent();
+            visuals.selection.stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+            visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(1.0, studio_border());
+            visuals.widgets.inactive.bg_fill = egui::Color32::WHITE;
+            visuals.widgets.inactive.weak_bg_fill = egui::Color32::WHITE;
+            visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(226, 226, 226);
+            visuals.widgets.active.bg_fill = egui::Color32::from_rgb(204, 204, 204);
+            visuals.widgets.inactive.bg_stroke = egui::Stroke::new(2.0, studio_border_strong());
+            visuals.widgets.hovered.bg_stroke = egui::Stroke::new(2.5, studio_border_strong());
+            visuals.widgets.active.bg_stroke = egui::Stroke::new(3.0, studio_focus_ring());
+            visuals.hyperlink_color = egui::Color32::from_rgb(0, 56, 168);
+            visuals.warn_fg_color = egui::Color32::from_rgb(122, 68, 0);
+            visuals.error_fg_color = egui::Color32::from_rgb(158, 0, 0);
+        }
         style.visuals = visuals;
 
         ctx.set_style(style);
         self.theme_applied = true;
     }
 
+    fn handle_shortcut(&mut self, shortcut: StudioShortcut) {
+        match shortcut {
+            StudioShortcut::ScaleUp => self.update_preferences(|prefs| prefs.step_ui_scale(1)),
+            StudioShortcut::ScaleDown => self.update_preferences(|prefs| prefs.step_ui_scale(-1)),
+            StudioShortcut::ResetScale => {
+                self.update_preferences(StudioPreferences::reset_ui_scale)
+            }
+            StudioShortcut::ToggleHighContrast => {
+                self.update_preferences(|prefs| prefs.high_contrast = !prefs.high_contrast);
+            }
+            StudioShortcut::CycleFocus { backwards } => {
+                let region =
+                    FocusRegion::cycle(self.focus_region, backwards, self.chat_panel_expanded);
+                self.focus_region = Some(region);
+                self.pending_focus = Some(region);
+                self.canvas_status = format!("Focus: {}", region.label());
+            }
+        }
+    }
+
+    /// Applies `change`, re-themes on the next frame, and saves the result to
+    /// `PREFERENCES_FILE`.
+    fn update_preferences(&mut self, change: impl FnOnce(&mut StudioPreferences)) {
+        let previous = self.preferences;
+        change(&mut self.preferences);
+        if self.preferences == previous {
+            return;
+        }
+        self.preferences.activate();
+        self.theme_applied = false;
+        self.canvas_status = match self.preferences.save(&self.workspace_root) {
+            Ok(()) => format!(
+                "Display: text {}%{}",
+                self.preferences.scale_percent(),
+                if self.preferences.high_contrast {
+                    ", high contrast"
+                } else {
+                    ""
+                }
+            ),
+            Err(error) => {
+                warn!(error = %format!("{error:#}"), "failed to save studio preferences");
+                format!("Display preferences not saved: {error:#}")
+            }
+        };
+    }
+
+    fn render_display_menu(&mut self, ui: &mut egui::Ui) {
+        ui.label(
+            egui::RichText::new(tr!(
+                "studio.display.text_size",
+                percent = self.preferences.scale_percent()
+            ))
+            .small()
+            .strong()
+            .color(studio_muted_text()),
+        );
+        ui.horizontal(|ui| {
+            if ui
+                .button("−")
+                .on_hover_text(tr!("studio.display.smaller"))
+                .clicked()
+            {
+                self.update_preferences(|prefs| prefs.step_ui_scale(-1));
+            }
+            if ui
+                .button(tr!("studio.display.reset"))
+                .on_hover_text(tr!("studio.display.reset_hint"))
+                .clicked()
+            {
+                self.update_preferences(StudioPreferences::reset_ui_scale);
+            }
+            if ui
+                .button("+")
+                .on_hover_text(tr!("studio.display.larger"))
+                .clicked()
+            {
+                self.update_preferences(|prefs| prefs.step_ui_scale(1));
+            }
+        });
+        let mut high_contrast = self.preferences.high_contrast;
+        if ui
+            .checkbox(&mut high_contrast, tr!("studio.display.high_contrast"))
+            .on_hover_text(tr!("studio.display.high_contrast_hint"))
+            .changed()
+        {
+            self.update_preferences(|prefs| prefs.high_contrast = high_contrast);
+        }
+        ui.label(
+            egui::RichText::new(tr!("studio.display.keyboard_note", file = PREFERENCES_FILE))
+                .small()
+                .color(studio_muted_text()),
+        );
+    }
+
+    /// Adds a toolbar widget with a visible keyboard-focus ring; the first toolbar widget
+    /// also takes focus when `F6` lands on the toolbar.
+    fn add_toolbar_widget(
+        &mut self,
+        ui: &mut egui::Ui,
+        enabled: bool,
+        widget: impl egui::Widget,
+    ) -> egui::Response {
+        let response = ui.add_enabled(enabled, widget);
+        if self.pending_focus == Some(FocusRegion::CanvasToolbar) {
+            response.request_focus();
+            self.pending_focus = None;
+        }
+        paint_focus_ring(ui, &response, studio_focus_ring());
+        response
+    }
+
     fn card_frame(_ui: &egui::Ui) -> egui::Frame {
         egui::Frame::new()
             .fill(studio_panel_surface_alt())
@@ -581,21 +1008,33 @@ impl StudioApp {
     fn session_status(&self) -> (&'static str, egui::Color32, egui::Color32, egui::Color32) {
         if self.turn_in_flight {
             (
-                "Running",
+                tr!("studio.status.running"),
+                egui::Color32::from_rgb(255, 241, 220),
+                egui::Color32::from_rgb(224, 175, 117),
+                egui::Color32::from_rgb(150, 96, 27),
+            )
+        } else if self.runtime_disconnected
+            && matches!(
+                self.runtime_supervisor.health(),
+                WorkerHealth::RestartScheduled { .. }
+            )
+        {
+            (
+                tr!("studio.status.reconnecting"),
                 egui::Color32::from_rgb(255, 241, 220),
                 egui::Color32::from_rgb(224, 175, 117),
                 egui::Color32::from_rgb(150, 96, 27),
             )
         } else if self.runtime_disconnected {
             (
-                "Disconnected",
+                tr!("studio.status.disconnected"),
                 egui::Color32::from_rgb(253, 232, 232),
                 egui::Color32::from_rgb(226, 160, 160),
                 egui::Color32::from_rgb(163, 61, 61),
             )
         } else {
             (
-                "Ready",
+                tr!("studio.status.ready"),
                 egui::Color32::from_rgb(223, 244, 234),
                 egui::Color32::from_rgb(153, 205, 182),
                 egui::Color32::from_rgb(34, 118, 88),
@@ -614,11 +1053,15 @@ impl StudioApp {
             ui.add_space(8.0);
             let toggle_button = egui::Button::new(
                 egui::RichText::new(if self.chat_panel_expanded {
-                    if compact_header { "Hide" } else { "Hide chat" }
+                    if compact_header {
+                        tr!("studio.hide_chat_short")
+                    } else {
+                        tr!("studio.hide_chat")
+                    }
                 } else if compact_header {
-                    "Chat"
+                    tr!("studio.show_chat_short")
                 } else {
-                    "Show chat"
+                    tr!("studio.show_chat")
                 })
                 .small()
                 .strong()
@@ -632,12 +1075,19 @@ impl StudioApp {
             }
             ui.add_space(6.0);
             ui.label(
-                egui::RichText::new(if compact_header { "mjolne" } else { "Studio" })
-                    .heading()
-                    .strong()
-                    .color(studio_text()),
+                egui::RichText::new(if compact_header {
+                    "mjolne"
+                } else {
+                    tr!("studio.title")
+                })
+                .heading()
+                .strong()
+                .color(studio_text()),
             );
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.menu_button("Aa", |ui| self.render_display_menu(ui))
+                    .response
+                    .on_hover_text(tr!("studio.display.hint"));
                 if self.turn_in_flight {
                     ui.add(egui::Spinner::new());
                 }
@@ -648,6 +1098,15 @@ impl StudioApp {
                     status_stroke,
                     status_text_color,
                 );
+                if let Some(label) = self.worker_health_label() {
+                    Self::chip(
+                        ui,
+                        label,
+                        egui::Color32::from_rgb(255, 241, 220),
+                        egui::Color32::from_rgb(224, 175, 117),
+                        egui::Color32::from_rgb(150, 96, 27),
+                    );
+                }
             });
         });
         ui.add_space(2.0);
@@ -663,20 +1122,52 @@ impl StudioApp {
                 studio_border(),
                 studio_muted_text(),
             );
+            if let Some(remote) = self
+                .remote_model
+                .as_ref()
+                .and_then(|status| status.latest())
+            {
+                Self::chip(
+                    ui,
+                    if compact_header {
+                        format!("serve {}", remote.provider)
+                    } else {
+                        format!("serve {} / {}", remote.provider, remote.model)
+                    },
+                    egui::Color32::from_rgb(231, 243, 252),
+                    studio_border(),
+                    studio_muted_text(),
+                );
+            }
             if !compact_header {
                 let refresh = self
                     .graph_surface
                     .last_refresh_trigger
                     .as_deref()
                     .unwrap_or("not yet refreshed");
+                let graph_label = match &self.remote_graph_url {
+                    Some(url) => format!("graph @{} {refresh}", url.host_str().unwrap_or("remote")),
+                    None => format!("graph {refresh}"),
+                };
                 Self::chip(
                     ui,
-                    format!("graph {refresh}"),
+                    graph_label,
                     egui::Color32::from_rgb(235, 242, 250),
                     studio_border(),
                     studio_muted_text(),
                 );
             }
+            self.render_graph_watch_controls(ui, compact_header);
+            if let Some(context) = self.last_turn_context {
+                let (fill, stroke, text_color) = context_chip_colors(context.level());
+                Self::chip(
+                    ui,
+                    tr!("studio.context", usage = context.label()),
+                    fill,
+                    stroke,
+                    text_color,
+                );
+            }
             Self::chip(
                 ui,
                 truncate_ui_text(&self.canvas_status, if compact_header { 34 } else { 52 }),
@@ -687,43 +1178,113 @@ impl StudioApp {
         });
     }
 
-    fn drain_events(&mut self) {
+    fn render_graph_watch_controls(&mut self, ui: &mut egui::Ui, compact_header: bool) {
+        let health = self.graph_watch_handle.health();
+        if !compact_header {
+            let (fill, stroke, text_color) = if health.error_count() > 0 {
+                (
+                    egui::Color32::from_rgb(253, 232, 232),
+                    egui::Color32::from_rgb(226, 160, 160),
+                    egui::Color32::from_rgb(163, 61, 61),
+                )
+            } else {
+                (
+                    egui::Color32::from_rgb(235, 242, 250),
+                    studio_border(),
+                    studio_muted_text(),
+                )
+            };
+            Self::chip(
+                ui,
+                graph_watch_health_label(&health, SystemTime::now()),
+                fill,
+                stroke,
+                text_color,
+            );
+            if let Some(last_error) = &health.last_error {
+                ui.label(egui::RichText::new("ⓘ").small().color(text_color))
+                    .on_hover_text(format!("Last watch error: {last_error}"));
+            }
+        }
+
+        let watch_available = !self.graph_watch_disconnected;
+        if ui
+            .add_enabled(
+                watch_available,
+                egui::Button::new(tr!("studio.graph.refresh")).small(),
+            )
+            .on_hover_text(tr!("studio.graph.refresh_hint"))
+            .clicked()
+        {
+            self.graph_watch_handle.request_refresh();
+            self.canvas_status = tr!("studio.graph.refresh_requested").to_owned();
+        }
+        let (toggle_label, toggle_hint) = if health.paused {
+            (tr!("studio.graph.resume"), tr!("studio.graph.resume_hint"))
+        } else {
+            (tr!("studio.graph.pause"), tr!("studio.graph.pause_hint"))
+        };
+        if ui
+            .add_enabled(watch_available, egui::Button::new(toggle_label).small())
+            .on_hover_text(toggle_hint)
+            .clicked()
+        {
+            if health.paused {
+                self.graph_watch_handle.resume();
+            } else {
+                self.graph_watch_handle.pause();
+            }
+        }
+    }
+
+    /// Applies every queued runtime event; returns how many were applied.
+    fn drain_events(&mut self) -> usize {
+        let mut drained = 0;
         loop {
             match self.event_rx.try_recv() {
-                Ok(event) => self.apply_event(event),
+                Ok(event) => {
+                    self.apply_event(event);
+                    drained += 1;
+                }
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => {
                     if !self.runtime_disconnected {
                         warn!("studio runtime worker disconnected");
-                        self.chat_history.push(ChatEntry::system(
-                            "Runtime worker disconnected. Restart studio to continue.",
-                        ));
+                        self.mark_runtime_disconnected("Runtime worker disconnected.".to_owned());
                     }
-                    self.runtime_disconnected = true;
-                    self.turn_in_flight = false;
                     break;
                 }
             }
         }
+        drained
     }
 
-    fn drain_graph_updates(&mut self) {
+    /// Applies up to `MAX_GRAPH_UPDATES_PER_FRAME` graph updates; returns how many.
+    fn drain_graph_updates(&mut self) -> usize {
+        let mut drained = 0;
         for _ in 0..MAX_GRAPH_UPDATES_PER_FRAME {
             match self.graph_update_rx.try_recv() {
-                Ok(update) => self.apply_graph_update(update),
+                Ok(update) => {
+                    self.apply_graph_update(update);
+                    drained += 1;
+                }
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => {
                     if !self.graph_watch_disconnected {
                         warn!("graph watch worker disconnected");
-                        self.chat_history.push(ChatEntry::system(
-                            "Graph watch worker disconnected; graph updates stopped.",
-                        ));
+                        self.graph_watch_disconnected = true;
+                        let now = Instant::now();
+                        let health = self.graph_watch_supervisor.on_disconnected(now);
+                        self.chat_history.push(ChatEntry::system(format!(
+                            "Graph watch worker disconnected; {}",
+                            restart_plan_text(health, now, "graph updates stopped")
+                        )));
                     }
-                    self.graph_watch_disconnected = true;
                     break;
                 }
             }
         }
+        drained
     }
 
     fn apply_graph_update(&mut self, update: GraphRefreshUpdate) {
@@ -781,6 +1342,7 @@ impl StudioApp {
                 tool_call_count: summary.tool_call_count,
             })
             .collect::<Vec<_>>();
+        let dependency_violations = self.dependency_rules.check(&graph);
 
         self.next_draw_command_sequence = self.next_draw_command_sequence.saturating_add(1);
         let batch = ArchitectureOverviewRenderer::render(ArchitectureOverviewRenderInput {
@@ -789,7 +1351,7 @@ impl StudioApp {
             changed_target_ids: effective_changed,
             impact_target_ids: effective_impact,
             show_impact_overlay: self.graph_surface.impact_overlay_enabled,
-            before_graph: overlay_snapshot.and_then(|snapshot| snapshot.baseline_graph.as_ref()),
+            before_graph: overlay_snapshot.and_then(|snapshot| snapshot.graphs.baseline()),
             show_before_after_overlay: overlay_snapshot.is_some(),
             show_focus_mode: self.canvas_diff_mode == CanvasDiffMode::FocusLatestTurn
                 && mode_snapshot.is_some(),
@@ -797,6 +1359,7 @@ impl StudioApp {
             turn_in_flight: self.turn_in_flight,
             canvas_status: &self.canvas_status,
             recent_activity: &recent_activity,
+            dependency_violations: &dependency_violations,
             sequence: self.next_draw_command_sequence,
         });
         self.canvas.apply(CanvasOp::apply_draw_command_batch(batch));
@@ -805,39 +1368,94 @@ impl StudioApp {
     fn apply_event(&mut self, event: StudioEvent) {
         match event {
             StudioEvent::TurnStarted {
+                turn_id,
                 message,
                 started_at,
             } => {
-                self.turn_in_flight = true;
+                if let Some(turn) = self
+                    .queued_turns
+                    .iter_mut()
+                    .find(|turn| turn.turn_id == turn_id)
+                {
+                    turn.running = true;
+                }
+                self.sync_turn_in_flight();
                 self.canvas_status =
                     format!("Running turn for: {}", summarize_for_canvas(&message));
-                self.pending_turn_snapshot = Some(PendingTurnSnapshot {
-                    turn_id: self.next_turn_snapshot_id,
-                    started_at,
-                    baseline_graph: self.canvas.graph().cloned(),
-                    intent_target_ids: Vec::new(),
-                });
-                self.next_turn_snapshot_id = self.next_turn_snapshot_id.saturating_add(1);
+                // Parallel turns share one snapshot baseline: the earliest started turn.
+                if self.pending_turn_snapshot.is_none() {
+                    self.pending_turn_snapshot = Some(PendingTurnSnapshot {
+                        turn_id: self.next_turn_snapshot_id,
+                        started_at,
+                        baseline_graph: self.canvas.graph().cloned(),
+                        intent_target_ids: Vec::new(),
+                    });
+                    self.next_turn_snapshot_id = self.next_turn_snapshot_id.saturating_add(1);
+                }
             }
-            StudioEvent::TurnCompleted { message, result } => {
-                self.turn_in_flight = false;
+            StudioEvent::TurnCompleted {
+                turn_id,
+                message,
+                result,
+            } => {
+                self.finish_queued_turn(turn_id);
+                self.drop_live_tool_cards(turn_id);
                 let assistant_preview = summarize_for_canvas(&result.final_text);
                 self.record_turn_summary(message, assistant_preview, result.trace.tool_calls);
-                self.record_tool_cards(&result.tool_calls);
+                self.record_tool_cards(&result.tool_calls, &result.citations);
+                self.last_turn_timeline = result.trace.timeline;
+                self.last_turn_context = Some(result.trace.context);
+                self.last_turn_touched_notes =
+                    touched_note_ids(&result.tool_calls, &self.notes_graph_dir());
+                let tool_calls = result.tool_calls.iter().map(Into::into).collect();
                 self.chat_history
-                    .push(ChatEntry::assistant(result.final_text));
-                self.canvas_status = "Idle".to_owned();
+                    .push(ChatEntry::assistant(result.final_text, tool_calls));
+                self.canvas_status = self.idle_or_running_status();
+                self.refresh_session_notes();
+                self.refresh_notes_graph_if_active();
             }
-            StudioEvent::TurnFailed { message, error } => {
-                self.turn_in_flight = false;
-                self.pending_turn_snapshot = None;
+            StudioEvent::TurnFailed {
+                turn_id,
+                message,
+                error,
+            } => {
+                self.finish_queued_turn(turn_id);
+                self.drop_live_tool_cards(turn_id);
+                if !self.has_running_turns() {
+                    self.pending_turn_snapshot = None;
+                }
                 self.chat_history.push(ChatEntry::system(format!(
                     "Turn failed for `{}`: {error}",
                     summarize_for_canvas(&message)
                 )));
                 self.canvas_status = format!("Turn failed: {error}");
+                self.refresh_session_notes();
+                self.refresh_notes_graph_if_active();
+            }
+            StudioEvent::TurnCancelled { turn_id, message } => {
+                self.finish_queued_turn(turn_id);
+                self.drop_live_tool_cards(turn_id);
+                if !self.has_running_turns() {
+                    self.pending_turn_snapshot = None;
+                }
+                self.chat_history.push(ChatEntry::system(format!(
+                    "Cancelled turn `{}`.",
+                    summarize_for_canvas(&message)
+                )));
+                self.canvas_status = self.idle_or_running_status();
+            }
+            StudioEvent::TurnToolActivity { turn_id, event } => {
+                self.apply_tool_activity(turn_id, event);
             }
             StudioEvent::CanvasUpdate { op } => self.canvas.apply(op),
+            StudioEvent::SubsystemSummaryReady {
+                subsystem,
+                revision,
+                result,
+            } => {
+                self.subsystem_summaries
+                    .complete(&subsystem, revision, result);
+            }
         }
     }
 
@@ -858,13 +1476,16 @@ impl StudioApp {
         }
     }
 
-    fn record_tool_cards(&mut self, tool_calls: &[ExecutedToolCall]) {
-        for call in tool_calls {
-            let preview = summarize_for_canvas(&call.output);
+    fn record_tool_cards(&mut self, tool_calls: &[ExecutedToolCall], citations: &[TurnCitation]) {
+        for (index, call) in tool_calls.iter().enumerate() {
+            let preview = summarize_for_canvas(&format!("{} → {}", call.arguments, call.output));
             self.canvas_tool_cards.push(CanvasToolCard {
                 id: format!("tool-card-{}", self.next_tool_card_id),
                 title: call.tool_name.clone(),
+                meta: tool_card_meta_label(call),
                 body: preview,
+                citation: tool_card_citation_label(index, citations),
+                progress_percent: None,
             });
             self.next_tool_card_id = self.next_tool_card_id.saturating_add(1);
         }
@@ -877,48 +1498,287 @@ impl StudioApp {
         self.render_architecture_overview_scene();
     }
 
-    fn submit_prompt(&mut self) {
-        let message = self.input_buffer.trim().to_owned();
-        if message.is_empty() {
+    /// Shows a running tool call as a card that tracks its progress until the turn ends.
+    fn apply_tool_activity(&mut self, turn_id: u64, event: TurnEvent) {
+        // Activity can trail a cancellation that already removed the turn.
+        if !self.queued_turns.iter().any(|turn| turn.turn_id == turn_id) {
             return;
         }
+        match event {
+            TurnEvent::ToolStarted {
+                tool_call_index,
+                tool_name,
+                arguments,
+                ..
+            } => {
+                self.canvas_tool_cards.push(CanvasToolCard {
+                    id: live_tool_card_id(turn_id, tool_call_index),
+                    title: tool_name,
+                    meta: "running".to_owned(),
+                    body: summarize_for_canvas(&arguments.to_string()),
+                    citation: None,
+                    progress_percent: None,
+                });
+            }
+            TurnEvent::ToolProgress {
+                tool_call_index,
+                progress,
+                ..
+            } => {
+                let id = live_tool_card_id(turn_id, tool_call_index);
+                let Some(card) = self.canvas_tool_cards.iter_mut().find(|card| card.id == id)
+                else {
+                    return;
+                };
+                card.meta = progress.label();
+                card.progress_percent = progress.percent();
+            }
+            TurnEvent::ToolFinished {
+                tool_call_index,
+                latency,
+                succeeded,
+                ..
+            } => {
+                let id = live_tool_card_id(turn_id, tool_call_index);
+                let Some(card) = self.canvas_tool_cards.iter_mut().find(|card| card.id == id)
+                else {
+                    return;
+                };
+                card.meta = format!("{}ms", latency.as_millis());
+                if !succeeded {
+                    card.meta.push_str(" · failed");
+                }
+                card.progress_percent = None;
+            }
+        }
+        self.render_architecture_overview_scene();
+    }
 
-        self.input_buffer.clear();
-        self.chat_history.push(ChatEntry::user(message.clone()));
-        self.turn_in_flight = true;
-        self.canvas_status = "Queued turn...".to_owned();
+    /// Live cards give way to the cards recorded from the turn result.
+    fn drop_live_tool_cards(&mut self, turn_id: u64) {
+        let prefix = live_tool_card_id_prefix(turn_id);
+        self.canvas_tool_cards
+            .retain(|card| !card.id.starts_with(&prefix));
+    }
 
-        if let Err(error) = self
-            .command_tx
-            .send(StudioCommand::SubmitUserMessage { message })
-        {
-            self.turn_in_flight = false;
-            self.runtime_disconnected = true;
-            self.canvas_status = "Runtime disconnected".to_owned();
-            self.chat_history.push(ChatEntry::system(format!(
-                "Failed to submit turn to runtime worker: {error}"
-            )));
-        }
+    fn has_running_turns(&self) -> bool {
+        self.queued_turns.iter().any(|turn| turn.running)
     }
 
-    fn render_chat_pane(&mut self, ui: &mut egui::Ui) {
-        let compact_width = ui.available_width() < 320.0;
-        let composer_section_height = if compact_width { 170.0 } else { 188.0 };
+    fn waiting_turn_count(&self) -> usize {
+        self.queued_turns
+            .iter()
+            .filter(|turn| !turn.running)
+            .count()
+    }
 
-        ui.horizontal_wrapped(|ui| {
-            ui.label(
-                egui::RichText::new("Chat")
-                    .heading()
-                    .strong()
-                    .color(studio_text()),
-            );
+    fn can_queue_turn(&self) -> bool {
+        !self.runtime_disconnected && self.waiting_turn_count() < MAX_QUEUED_TURNS
+    }
+
+    fn sync_turn_in_flight(&mut self) {
+        self.turn_in_flight = !self.queued_turns.is_empty();
+    }
+
+    fn finish_queued_turn(&mut self, turn_id: u64) {
+        self.queued_turns.retain(|turn| turn.turn_id != turn_id);
+        self.sync_turn_in_flight();
+    }
+
+    fn idle_or_running_status(&self) -> String {
+        match self.queued_turns.len() {
+            0 => "Idle".to_owned(),
+            count => format!("{count} turn{} pending", if count == 1 { "" } else { "s" }),
+        }
+    }
+
+    fn cancel_turn(&mut self, turn_id: u64) {
+        let Some(turn) = self
+            .queued_turns
+            .iter_mut()
+            .find(|turn| turn.turn_id == turn_id && !turn.cancel_requested)
+        else {
+            return;
+        };
+        turn.cancel_requested = true;
+
+        if let Err(error) = self.command_tx.send(StudioCommand::CancelTurn { turn_id }) {
+            self.mark_runtime_disconnected(format!(
+                "Failed to cancel turn in runtime worker: {error}"
+            ));
+        }
+    }
+
+    fn mark_runtime_disconnected(&mut self, reason: String) {
+        self.runtime_disconnected = true;
+        self.canvas_status = "Runtime disconnected".to_owned();
+        let now = Instant::now();
+        let health = self.runtime_supervisor.on_disconnected(now);
+        self.chat_history.push(ChatEntry::system(format!(
+            "{reason} {}",
+            restart_plan_text(health, now, "restart studio to continue")
+        )));
+        let dropped = self.queued_turns.len();
+        if dropped > 0 {
+            self.chat_history.push(ChatEntry::system(format!(
+                "Dropped {dropped} pending turn{} after runtime disconnect.",
+                if dropped == 1 { "" } else { "s" }
+            )));
+        }
+        self.queued_turns.clear();
+        self.pending_turn_snapshot = None;
+        self.sync_turn_in_flight();
+    }
+
+    /// Respawns disconnected workers once their restart backoff has elapsed.
+    fn supervise_workers(&mut self, now: Instant) {
+        if self.runtime_disconnected && self.runtime_supervisor.restart_due(now) {
+            self.restart_runtime_worker(now);
+        }
+        if self.graph_watch_disconnected && self.graph_watch_supervisor.restart_due(now) {
+            self.restart_graph_watch_worker(now);
+        }
+    }
+
+    fn restart_runtime_worker(&mut self, now: Instant) {
+        let (command_tx, command_rx) = unbounded_channel::<StudioCommand>();
+        let (event_tx, event_rx) = channel::<StudioEvent>(STUDIO_EVENT_CHANNEL_CAPACITY);
+        spawn_runtime_worker(
+            &self.runtime_handle,
+            self.settings.clone(),
+            command_rx,
+            event_tx,
+            self.graph_watch_handle.clone(),
+        );
+        self.command_tx = command_tx;
+        self.event_rx = event_rx;
+        self.runtime_disconnected = false;
+        self.runtime_supervisor.on_restarted(now);
+        self.canvas_status = self.idle_or_running_status();
+        info!("studio runtime worker restarted");
+        self.chat_history
+            .push(ChatEntry::system("Runtime worker restarted."));
+    }
+
+    fn restart_graph_watch_worker(&mut self, now: Instant) {
+        let workspace_roots = WorkspaceRoots {
+            primary: self.workspace_root.clone(),
+            extra: self.extra_workspace_roots.clone(),
+        };
+        let (graph_watch_handle, graph_update_rx) = spawn_graph_source(
+            &self.runtime_handle,
+            workspace_roots,
+            self.remote_graph_url.as_ref(),
+        );
+        if !self.runtime_disconnected
+            && let Err(error) = self.command_tx.send(StudioCommand::ReplaceGraphWatch {
+                handle: graph_watch_handle.clone(),
+            })
+        {
+            self.mark_runtime_disconnected(format!(
+                "Failed to hand restarted graph watch to runtime worker: {error}"
+            ));
+        }
+        self.graph_watch_handle = graph_watch_handle;
+        self.graph_update_rx = graph_update_rx;
+        self.graph_watch_disconnected = false;
+        self.graph_watch_supervisor.on_restarted(now);
+        info!("graph watch worker restarted");
+        self.chat_history
+            .push(ChatEntry::system("Graph watch worker restarted."));
+    }
+
+    fn worker_health_label(&self) -> Option<String> {
+        let labels = [
+            self.runtime_supervisor.status_label("Runtime"),
+            self.graph_watch_supervisor.status_label("Graph watch"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        (!labels.is_empty()).then(|| labels.join(" · "))
+    }
+
+    fn submit_prompt(&mut self) {
+        let message = self.input_buffer.trim().to_owned();
+        if message.is_empty() {
+            return;
+        }
+        if self.runtime_disconnected {
+            self.chat_history.push(ChatEntry::system(
+                "Runtime worker is disconnected; prompt was not sent.",
+            ));
+            return;
+        }
+        if !self.can_queue_turn() {
+            self.canvas_status = format!("Turn queue full ({MAX_QUEUED_TURNS} waiting)");
+            return;
+        }
+
+        self.input_buffer.clear();
+        self.chat_history.push(ChatEntry::user(message.clone()));
+        let turn_id = self.next_queued_turn_id;
+        self.next_queued_turn_id = self.next_queued_turn_id.saturating_add(1);
+        self.queued_turns.push(StudioQueuedTurn {
+            turn_id,
+            message: message.clone(),
+            running: false,
+            cancel_requested: false,
+        });
+        self.sync_turn_in_flight();
+        self.canvas_status = "Queued turn...".to_owned();
+
+        if let Err(error) = self
+            .command_tx
+            .send(StudioCommand::SubmitUserMessage { turn_id, message })
+        {
+            self.queued_turns.retain(|turn| turn.turn_id != turn_id);
+            self.mark_runtime_disconnected(format!(
+                "Failed to submit turn to runtime worker: {error}"
+            ));
+        }
+    }
+
+    fn render_chat_pane(&mut self, ui: &mut egui::Ui) {
+        let compact_width = ui.available_width() < 320.0;
+        let composer_section_height = if compact_width { 170.0 } else { 188.0 }
+            + if self.queued_turns.is_empty() {
+                0.0
+            } else {
+                40.0
+            };
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(
+                egui::RichText::new(tr!("studio.chat.heading"))
+                    .heading()
+                    .strong()
+                    .color(studio_text()),
+            );
             Self::chip(
                 ui,
-                format!("{} messages", self.chat_history.len()),
+                tr!("studio.chat.message_count", count = self.chat_history.len()),
                 studio_accent_soft(),
                 studio_border(),
                 studio_muted_text(),
             );
+            if ui
+                .small_button(tr!("studio.chat.clear_history"))
+                .on_hover_text(tr!("studio.chat.clear_history_hint"))
+                .clicked()
+            {
+                self.clear_history();
+            }
+            ui.menu_button(tr!("studio.chat.export"), |ui| {
+                for format in ExportFormat::ALL {
+                    if ui.button(format.label()).clicked() {
+                        self.export_conversation(format);
+                        ui.close_menu();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(tr!("studio.chat.export_hint", dir = EXPORT_DIR));
         });
         ui.horizontal_wrapped(|ui| {
             Self::chip(
@@ -940,39 +1800,66 @@ impl StudioApp {
                 );
             }
         });
+        self.render_session_notes(ui);
+        self.render_history_search(ui);
 
+        let focus_latest = self.pending_focus == Some(FocusRegion::ChatHistory);
         Self::card_frame(ui).show(ui, |ui| {
             egui::ScrollArea::vertical()
                 .stick_to_bottom(true)
                 .max_height((ui.available_height() - composer_section_height).max(140.0))
                 .show(ui, |ui| {
-                    for entry in &self.chat_history {
-                        self.render_chat_entry(ui, entry);
+                    let last_index = self.chat_history.len().saturating_sub(1);
+                    for (index, entry) in self.chat_history.iter().enumerate() {
+                        // Focusable without being clickable, so Up/Down walk the history
+                        // and the focused message scrolls into view.
+                        let rect = self.render_chat_entry(ui, entry);
+                        let response = ui.interact(
+                            rect,
+                            ui.id().with(("chat-entry", index)),
+                            egui::Sense::focusable_noninteractive(),
+                        );
+                        if focus_latest && index == last_index {
+                            response.request_focus();
+                        }
+                        if response.gained_focus() {
+                            response.scroll_to_me(Some(egui::Align::Center));
+                        }
+                        paint_focus_ring(ui, &response, studio_focus_ring());
                     }
                 });
         });
+        if focus_latest {
+            self.pending_focus = None;
+        }
 
         Self::card_frame(ui).show(ui, |ui| {
             ui.label(
-                egui::RichText::new("Prompt")
+                egui::RichText::new(tr!("studio.prompt.heading"))
                     .small()
                     .strong()
                     .color(studio_muted_text()),
             );
-            ui.add(
+            let prompt = ui.add(
                 egui::TextEdit::multiline(&mut self.input_buffer)
-                    .hint_text("Ask the agent...")
+                    .hint_text(tr!("studio.prompt.hint"))
                     .desired_rows(4),
             );
+            if self.pending_focus == Some(FocusRegion::Prompt) {
+                prompt.request_focus();
+                self.pending_focus = None;
+            }
 
-            let can_send = !self.turn_in_flight
-                && !self.runtime_disconnected
-                && !self.input_buffer.trim().is_empty();
+            let can_send = self.can_queue_turn() && !self.input_buffer.trim().is_empty();
             ui.horizontal(|ui| {
                 let send_button = egui::Button::new(
-                    egui::RichText::new("Send")
-                        .strong()
-                        .color(egui::Color32::from_rgb(250, 253, 255)),
+                    egui::RichText::new(if self.turn_in_flight {
+                        tr!("studio.prompt.queue")
+                    } else {
+                        tr!("studio.prompt.send")
+                    })
+                    .strong()
+                    .color(egui::Color32::from_rgb(250, 253, 255)),
                 )
                 .fill(studio_accent())
                 .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(27, 84, 136)))
@@ -984,7 +1871,7 @@ impl StudioApp {
                 if self.turn_in_flight {
                     Self::chip(
                         ui,
-                        "Running...",
+                        tr!("studio.prompt.running"),
                         egui::Color32::from_rgb(255, 241, 220),
                         egui::Color32::from_rgb(224, 175, 117),
                         egui::Color32::from_rgb(150, 96, 27),
@@ -992,14 +1879,223 @@ impl StudioApp {
                 } else if self.runtime_disconnected {
                     Self::chip(
                         ui,
-                        "Runtime disconnected",
+                        tr!("studio.prompt.runtime_disconnected"),
                         egui::Color32::from_rgb(253, 232, 232),
                         egui::Color32::from_rgb(226, 160, 160),
                         egui::Color32::from_rgb(163, 61, 61),
                     );
                 }
             });
+            self.render_turn_queue(ui);
+        });
+    }
+
+    /// Lists this session's notes with checkboxes and a button that promotes the checked
+    /// ones into the shared notes directory.
+    fn render_session_notes(&mut self, ui: &mut egui::Ui) {
+        let Some(session_notes) = self.session_notes.as_mut() else {
+            return;
+        };
+        let mut promote = false;
+        egui::CollapsingHeader::new(tr!(
+            "studio.session_notes.heading",
+            count = session_notes.notes().len()
+        ))
+        .id_salt("studio-session-notes")
+        .show(ui, |ui| {
+            if session_notes.notes().is_empty() {
+                ui.label(
+                    egui::RichText::new(tr!("studio.session_notes.empty"))
+                        .small()
+                        .color(studio_muted_text()),
+                );
+                return;
+            }
+            for note in session_notes.notes().to_vec() {
+                let mut selected = session_notes.is_selected(&note);
+                if ui.checkbox(&mut selected, &note).changed() {
+                    session_notes.set_selected(&note, selected);
+                }
+            }
+            promote = ui
+                .add_enabled(
+                    session_notes.has_selection(),
+                    egui::Button::new(tr!("studio.session_notes.promote")),
+                )
+                .on_hover_text(tr!(
+                    "studio.session_notes.promote_hint",
+                    dir = session_notes.shared_dir().display()
+                ))
+                .clicked();
         });
+        if promote {
+            self.promote_session_notes();
+        }
+    }
+
+    fn render_history_search(&mut self, ui: &mut egui::Ui) {
+        if self.turn_history.is_none() {
+            return;
+        }
+        let mut search = false;
+        egui::CollapsingHeader::new(tr!("studio.history.heading"))
+            .id_salt("studio-history-search")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.history_query)
+                            .hint_text(tr!("studio.history.hint")),
+                    );
+                    let submitted = response.lost_focus()
+                        && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                    search = ui
+                        .add_enabled(
+                            !self.history_query.trim().is_empty(),
+                            egui::Button::new(tr!("studio.history.search")),
+                        )
+                        .clicked()
+                        || (submitted && !self.history_query.trim().is_empty());
+                });
+                match &self.history_results {
+                    None => {}
+                    Some(Err(error)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                    Some(Ok(matches)) if matches.is_empty() => {
+                        ui.label(
+                            egui::RichText::new(tr!("studio.history.empty"))
+                                .small()
+                                .color(studio_muted_text()),
+                        );
+                    }
+                    Some(Ok(matches)) => {
+                        for entry in matches {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} · {} · {}",
+                                        format_recorded_at(entry.recorded_at_ms),
+                                        entry.source.as_str(),
+                                        entry.id
+                                    ))
+                                    .small()
+                                    .color(studio_muted_text()),
+                                );
+                                if ui
+                                    .small_button(tr!("studio.history.copy_id"))
+                                    .on_hover_text(tr!("studio.history.copy_id_hint"))
+                                    .clicked()
+                                {
+                                    ui.ctx().copy_text(entry.id.clone());
+                                }
+                            });
+                            ui.label(truncate_ui_text(&entry.message, 120));
+                            ui.label(
+                                egui::RichText::new(truncate_ui_text(&entry.snippet, 160)).small(),
+                            );
+                            ui.separator();
+                        }
+                    }
+                }
+            });
+        if search {
+            self.search_turn_history();
+        }
+    }
+
+    fn search_turn_history(&mut self) {
+        let Some(turn_history) = &self.turn_history else {
+            return;
+        };
+        self.history_results = Some(
+            turn_history
+                .search(&self.history_query, DEFAULT_HISTORY_SEARCH_LIMIT)
+                .map_err(|error| format!("{error:#}")),
+        );
+    }
+
+    fn refresh_session_notes(&mut self) {
+        if let Some(session_notes) = self.session_notes.as_mut()
+            && let Err(error) = session_notes.refresh()
+        {
+            warn!(error = %format!("{error:#}"), "failed to list studio session notes");
+        }
+    }
+
+    fn promote_session_notes(&mut self) {
+        let Some(session_notes) = self.session_notes.as_mut() else {
+            return;
+        };
+        let promotion = session_notes.promote_selected();
+        let shared_dir = session_notes.shared_dir().display().to_string();
+        if !promotion.promoted.is_empty() {
+            info!(notes = ?promotion.promoted, shared_dir = %shared_dir, "promoted studio session notes");
+            self.chat_history.push(ChatEntry::system(format!(
+                "Promoted {} to `{shared_dir}`.",
+                promotion.promoted.join(", ")
+            )));
+        }
+        for failure in promotion.failed {
+            warn!(failure = %failure, "failed to promote studio session note");
+            self.chat_history
+                .push(ChatEntry::system(format!("Could not promote {failure}")));
+        }
+    }
+
+    fn render_turn_queue(&mut self, ui: &mut egui::Ui) {
+        if self.queued_turns.is_empty() {
+            return;
+        }
+
+        let mut cancel_turn_id = None;
+        ui.horizontal_wrapped(|ui| {
+            for turn in &self.queued_turns {
+                let (state_label, fill, stroke, text_color) = if turn.cancel_requested {
+                    (
+                        "Cancelling",
+                        egui::Color32::from_rgb(253, 232, 232),
+                        egui::Color32::from_rgb(226, 160, 160),
+                        egui::Color32::from_rgb(163, 61, 61),
+                    )
+                } else if turn.running {
+                    (
+                        "Running",
+                        egui::Color32::from_rgb(255, 241, 220),
+                        egui::Color32::from_rgb(224, 175, 117),
+                        egui::Color32::from_rgb(150, 96, 27),
+                    )
+                } else {
+                    (
+                        "Queued",
+                        studio_accent_soft(),
+                        studio_border(),
+                        studio_muted_text(),
+                    )
+                };
+                Self::chip(
+                    ui,
+                    format!(
+                        "#{} {state_label}: {}",
+                        turn.turn_id,
+                        truncate_ui_text(&turn.message, 24)
+                    ),
+                    fill,
+                    stroke,
+                    text_color,
+                );
+                if ui
+                    .add_enabled(!turn.cancel_requested, egui::Button::new("×").small())
+                    .on_hover_text("Cancel this turn")
+                    .clicked()
+                {
+                    cancel_turn_id = Some(turn.turn_id);
+                }
+            }
+        });
+
+        if let Some(turn_id) = cancel_turn_id {
+            self.cancel_turn(turn_id);
+        }
     }
 
     fn render_chat_rail(&mut self, ui: &mut egui::Ui) {
@@ -1052,6 +2148,7 @@ impl StudioApp {
 
     fn render_canvas_surface(&mut self, ui: &mut egui::Ui, surface_height: f32) {
         // Canvas surface dispatch point for future renderers (timeline, diffs, notes).
+        let notes_surface = self.active_canvas_surface == CanvasSurfaceKind::NotesGraph;
         let surface_adapter = Self::build_canvas_surface_adapter(
             self.active_canvas_surface,
             &self.graph_surface.changed_target_ids,
@@ -1059,8 +2156,283 @@ impl StudioApp {
             self.graph_surface.impact_overlay_enabled,
             self.graph_surface.legend_enabled,
             &self.canvas_tool_cards,
+            &self.last_turn_timeline,
         );
-        surface_adapter.render(ui, &self.canvas, &mut self.canvas_viewport, surface_height);
+        let canvas = if notes_surface {
+            &mut self.notes_canvas
+        } else {
+            &mut self.canvas
+        };
+        let selection_events =
+            surface_adapter.render(ui, canvas, &mut self.canvas_viewport, surface_height);
+        // The context menu acts on architecture nodes; notes are only selectable.
+        if !notes_surface {
+            let pointer_pos = ui.ctx().pointer_interact_pos();
+            self.apply_canvas_selection_events(selection_events, pointer_pos);
+        }
+        self.render_canvas_context_menu(ui.ctx());
+    }
+
+    /// The directory the notes tools of this studio's turns use.
+    fn notes_graph_dir(&self) -> PathBuf {
+        self.session_notes.as_ref().map_or_else(
+            || PathBuf::from(&self.settings.notes_dir),
+            |session_notes| session_notes.session_dir().to_path_buf(),
+        )
+    }
+
+    /// Re-reads the notes directory into the notes surface, highlighting the notes the
+    /// latest turn touched.
+    fn refresh_notes_graph(&mut self) {
+        let graph = match NotesGraph::build(&self.notes_graph_dir()) {
+            Ok(graph) => graph,
+            Err(error) => {
+                self.canvas_status = format!("Notes graph refresh failed: {error:#}");
+                return;
+            }
+        };
+        self.notes_canvas.apply(CanvasOp::set_scene_notes(graph));
+        self.notes_canvas.apply(CanvasOp::set_highlighted_targets(
+            self.last_turn_touched_notes.clone(),
+        ));
+        let Some(graph) = self.notes_canvas.notes_graph() else {
+            return;
+        };
+        self.next_draw_command_sequence = self.next_draw_command_sequence.saturating_add(1);
+        let batch = NotesGraphRenderer::render(NotesGraphRenderInput {
+            graph,
+            highlighted_target_ids: self.notes_canvas.highlighted_target_ids(),
+            sequence: self.next_draw_command_sequence,
+        });
+        self.notes_canvas
+            .apply(CanvasOp::apply_draw_command_batch(batch));
+    }
+
+    fn refresh_notes_graph_if_active(&mut self) {
+        if self.active_canvas_surface == CanvasSurfaceKind::NotesGraph {
+            self.refresh_notes_graph();
+        }
+    }
+
+    fn apply_canvas_selection_events(
+        &mut self,
+        events: Vec<CanvasSelectionEvent>,
+        pointer_pos: Option<egui::Pos2>,
+    ) {
+        for event in events {
+            match event {
+                CanvasSelectionEvent::HoverChanged { .. } => {}
+                CanvasSelectionEvent::SelectionChanged { .. } => {
+                    self.canvas_context_menu = None;
+                }
+                CanvasSelectionEvent::ContextMenuRequested { target_id } => {
+                    self.canvas_context_menu =
+                        pointer_pos.map(|anchor| CanvasContextMenu { target_id, anchor });
+                }
+            }
+        }
+    }
+
+    fn render_canvas_context_menu(&mut self, ctx: &egui::Context) {
+        let Some(menu) = self.canvas_context_menu.clone() else {
+            return;
+        };
+        let selected_count = self.canvas.selected_target_ids().len();
+        let source_path = self
+            .canvas
+            .graph()
+            .and_then(|graph| node_source_path(graph, &menu.target_id))
+            .map(str::to_owned);
+        let target_subsystem = self.canvas.graph().and_then(|graph| {
+            graph
+                .nodes
+                .iter()
+                .find(|node| node.id == menu.target_id)
+                .map(|node| self.subsystem_mapper.resolve_subsystem(node))
+        });
+        let mut close = ctx.input(|input| input.key_pressed(egui::Key::Escape));
+        egui::Area::new(egui::Id::new("studio-canvas-context-menu"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(menu.anchor)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(truncate_ui_text(&menu.target_id, 48))
+                            .small()
+                            .strong()
+                            .color(studio_muted_text()),
+                    );
+                    if ui.button("Copy id").clicked() {
+                        ctx.copy_text(menu.target_id.clone());
+                        close = true;
+                    }
+                    if let Some(relative_path) = source_path.as_deref()
+                        && ui.button("Open in editor").clicked()
+                    {
+                        self.open_in_editor(relative_path);
+                        close = true;
+                    }
+                    if let Some(subsystem) = target_subsystem.as_deref()
+                        && ui
+                            .button(format!("Describe subsystem `{subsystem}`"))
+                            .clicked()
+                    {
+                        self.request_subsystem_summary(subsystem.to_owned(), false);
+                        close = true;
+                    }
+                    if selected_count > 1 && ui.button("Copy selected ids").clicked() {
+                        ctx.copy_text(self.canvas.selected_target_ids().join("\n"));
+                        close = true;
+                    }
+                    if ui.button("Clear selection").clicked() {
+                        self.canvas
+                            .apply(CanvasOp::set_selected_targets(Vec::new()));
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.canvas_context_menu = None;
+        }
+    }
+
+    /// Shows `subsystem` in the inspector, asking the runtime for a summary unless one is
+    /// cached (or pending) for the current graph revision.
+    fn request_subsystem_summary(&mut self, subsystem: String, regenerate: bool) {
+        self.graph_surface.inspector_enabled = true;
+        self.inspector_subsystem = Some(subsystem.clone());
+        let Some(graph) = self.canvas.graph() else {
+            self.canvas_status = "No graph loaded to describe".to_owned();
+            return;
+        };
+        if !regenerate
+            && self
+                .subsystem_summaries
+                .get(&subsystem, graph.revision)
+                .is_some()
+        {
+            return;
+        }
+        let context = match collect_subsystem_context(
+            graph,
+            &self.subsystem_mapper,
+            &self.workspace_root,
+            &subsystem,
+        ) {
+            Ok(context) => context,
+            Err(error) => {
+                self.canvas_status = format!("Describe failed: {error:#}");
+                return;
+            }
+        };
+        self.subsystem_summaries
+            .mark_pending(&subsystem, context.revision);
+        if let Err(error) = self
+            .command_tx
+            .send(StudioCommand::DescribeSubsystem { context })
+        {
+            warn!(error = %error, "failed to send subsystem summary request");
+            self.subsystem_summaries.complete(
+                &subsystem,
+                graph.revision,
+                Err("runtime worker disconnected".to_owned()),
+            );
+        }
+    }
+
+    fn render_inspector_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(graph) = self.canvas.graph() else {
+            return;
+        };
+        let revision = graph.revision;
+        let subsystems = describe::subsystem_names(graph, &self.subsystem_mapper);
+        let mut selected = self
+            .inspector_subsystem
+            .clone()
+            .filter(|subsystem| subsystems.contains(subsystem))
+            .or_else(|| subsystems.first().cloned());
+        let mut describe_requested = None;
+
+        egui::Frame::new()
+            .fill(egui::Color32::from_rgb(236, 245, 253))
+            .stroke(egui::Stroke::new(1.0, studio_border()))
+            .corner_radius(10)
+            .inner_margin(egui::Margin::symmetric(10, 8))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new("Inspector")
+                            .strong()
+                            .color(studio_text()),
+                    );
+                    egui::ComboBox::from_id_salt("studio-inspector-subsystem")
+                        .selected_text(selected.as_deref().unwrap_or("no subsystems"))
+                        .show_ui(ui, |ui| {
+                            for subsystem in &subsystems {
+                                ui.selectable_value(
+                                    &mut selected,
+                                    Some(subsystem.clone()),
+                                    subsystem,
+                                );
+                            }
+                        });
+                    let state = selected
+                        .as_deref()
+                        .and_then(|subsystem| self.subsystem_summaries.get(subsystem, revision));
+                    let pending = state == Some(&SubsystemSummaryState::Pending);
+                    let label = if state.is_some() {
+                        "Regenerate"
+                    } else {
+                        "Describe"
+                    };
+                    if ui
+                        .add_enabled(selected.is_some() && !pending, egui::Button::new(label))
+                        .clicked()
+                    {
+                        describe_requested = selected.clone();
+                    }
+                });
+                let state = selected
+                    .as_deref()
+                    .and_then(|subsystem| self.subsystem_summaries.get(subsystem, revision));
+                let (text, color) = match state {
+                    None => (
+                        "Describe to generate an architecture summary for this revision."
+                            .to_owned(),
+                        studio_muted_text(),
+                    ),
+                    Some(SubsystemSummaryState::Pending) => {
+                        ("Generating summary…".to_owned(), studio_muted_text())
+                    }
+                    Some(SubsystemSummaryState::Ready(summary)) => (summary.clone(), studio_text()),
+                    Some(SubsystemSummaryState::Failed(error)) => (
+                        format!("Summary failed: {error}"),
+                        egui::Color32::from_rgb(153, 60, 46),
+                    ),
+                };
+                egui::ScrollArea::vertical()
+                    .id_salt("studio-inspector-summary")
+                    .max_height(160.0)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(text).color(color));
+                    });
+            });
+        ui.add_space(6.0);
+
+        if let Some(subsystem) = describe_requested {
+            self.request_subsystem_summary(subsystem, true);
+        } else {
+            self.inspector_subsystem = selected;
+        }
+    }
+
+    fn open_in_editor(&mut self, relative_path: &str) {
+        self.canvas_status =
+            match open_workspace_path_detached(&self.settings, &self.workspace_root, relative_path)
+            {
+                Ok(()) => format!("Opened {relative_path} in editor"),
+                Err(error) => format!("Open in editor failed: {error:#}"),
+            };
     }
 
     fn build_canvas_surface_adapter<'a>(
@@ -1070,8 +2442,11 @@ impl StudioApp {
         show_impact_overlay: bool,
         show_graph_legend: bool,
         tool_cards: &'a [CanvasToolCard],
+        timeline: &'a [TimelineSpan],
     ) -> CanvasSurfaceAdapter<'a> {
         match active_surface {
+            CanvasSurfaceKind::TurnTimeline => CanvasSurfaceAdapter::turn_timeline(timeline),
+            CanvasSurfaceKind::NotesGraph => CanvasSurfaceAdapter::NotesGraph,
             CanvasSurfaceKind::ArchitectureGraph => {
                 CanvasSurfaceAdapter::architecture_graph(GraphSurfaceAdapterOptions {
                     changed_node_ids,
@@ -1088,7 +2463,7 @@ impl StudioApp {
         let compact_toolbar = ui.available_width() < 840.0;
         ui.horizontal(|ui| {
             ui.label(
-                egui::RichText::new("Canvas")
+                egui::RichText::new(tr!("studio.canvas.heading"))
                     .heading()
                     .strong()
                     .color(studio_text()),
@@ -1101,16 +2476,19 @@ impl StudioApp {
                     .inner_margin(egui::Margin::symmetric(7, 4))
                     .show(ui, |ui| {
                         ui.horizontal_wrapped(|ui| {
-                            if ui
-                                .button(if compact_toolbar { "Fit" } else { "Fit View" })
-                                .clicked()
-                            {
+                            let fit_button = egui::Button::new(if compact_toolbar {
+                                tr!("studio.canvas.fit_view_short")
+                            } else {
+                                tr!("studio.canvas.fit_view")
+                            });
+                            if self.add_toolbar_widget(ui, true, fit_button).clicked() {
                                 self.canvas_viewport.fit_to_view();
                             }
                             let has_snapshots = !self.turn_snapshots.is_empty();
                             let selected_index = self.selected_snapshot_index();
-                            if ui
-                                .add_enabled(has_snapshots, egui::Button::new("←"))
+                            if self
+                                .add_toolbar_widget(ui, has_snapshots, egui::Button::new("←"))
+                                .on_hover_text(tr!("studio.canvas.previous_snapshot"))
                                 .clicked()
                             {
                                 self.select_previous_snapshot();
@@ -1127,8 +2505,9 @@ impl StudioApp {
                                     .strong()
                                     .color(studio_muted_text()),
                             );
-                            if ui
-                                .add_enabled(has_snapshots, egui::Button::new("→"))
+                            if self
+                                .add_toolbar_widget(ui, has_snapshots, egui::Button::new("→"))
+                                .on_hover_text(tr!("studio.canvas.next_snapshot"))
                                 .clicked()
                             {
                                 self.select_next_snapshot();
@@ -1136,19 +2515,14 @@ impl StudioApp {
                             }
                             let before_after_selected =
                                 self.canvas_diff_mode == CanvasDiffMode::BeforeAfterLatestTurn;
-                            let before_after_label = if before_after_selected {
-                                if compact_toolbar {
-                                    "B/A On"
-                                } else {
-                                    "Before/After On"
-                                }
-                            } else if compact_toolbar {
-                                "B/A"
-                            } else {
-                                "Before/After"
-                            };
+                            let before_after_label = toolbar_toggle_label(
+                                tr!("studio.canvas.before_after"),
+                                "B/A",
+                                before_after_selected,
+                                compact_toolbar,
+                            );
                             if self
-                                .mode_toggle_button(ui, before_after_label, before_after_selected)
+                                .mode_toggle_button(ui, &before_after_label, before_after_selected)
                                 .clicked()
                             {
                                 self.canvas_diff_mode = if before_after_selected {
@@ -1160,13 +2534,18 @@ impl StudioApp {
                             }
                             let focus_selected =
                                 self.canvas_diff_mode == CanvasDiffMode::FocusLatestTurn;
-                            let focus_label = if focus_selected {
-                                if compact_toolbar { "F On" } else { "Focus On" }
-                            } else {
-                                "Focus"
-                            };
+                            let focus_label = toolbar_toggle_label(
+                                tr!("studio.canvas.focus"),
+                                if focus_selected {
+                                    "F"
+                                } else {
+                                    tr!("studio.canvas.focus")
+                                },
+                                focus_selected,
+                                compact_toolbar,
+                            );
                             if self
-                                .mode_toggle_button(ui, focus_label, focus_selected)
+                                .mode_toggle_button(ui, &focus_label, focus_selected)
                                 .clicked()
                             {
                                 self.canvas_diff_mode = if focus_selected {
@@ -1176,16 +2555,82 @@ impl StudioApp {
                                 };
                                 self.render_architecture_overview_scene();
                             }
-                            if ui.button("+").clicked() {
+                            let timeline_selected =
+                                self.active_canvas_surface == CanvasSurfaceKind::TurnTimeline;
+                            let timeline_label = toolbar_toggle_label(
+                                tr!("studio.canvas.timeline"),
+                                "T",
+                                timeline_selected,
+                                compact_toolbar,
+                            );
+                            if self
+                                .mode_toggle_button(ui, &timeline_label, timeline_selected)
+                                .on_hover_text(tr!("studio.canvas.timeline_hint"))
+                                .clicked()
+                            {
+                                self.active_canvas_surface = if timeline_selected {
+                                    CanvasSurfaceKind::ArchitectureGraph
+                                } else {
+                                    CanvasSurfaceKind::TurnTimeline
+                                };
+                            }
+                            let notes_selected =
+                                self.active_canvas_surface == CanvasSurfaceKind::NotesGraph;
+                            let notes_label = toolbar_toggle_label(
+                                tr!("studio.canvas.notes"),
+                                "N",
+                                notes_selected,
+                                compact_toolbar,
+                            );
+                            if self
+                                .mode_toggle_button(ui, &notes_label, notes_selected)
+                                .on_hover_text(tr!("studio.canvas.notes_hint"))
+                                .clicked()
+                            {
+                                if notes_selected {
+                                    self.active_canvas_surface =
+                                        CanvasSurfaceKind::ArchitectureGraph;
+                                } else {
+                                    self.active_canvas_surface = CanvasSurfaceKind::NotesGraph;
+                                    self.refresh_notes_graph();
+                                }
+                            }
+                            let inspector_selected = self.graph_surface.inspector_enabled;
+                            let inspector_label = toolbar_toggle_label(
+                                tr!("studio.canvas.inspector"),
+                                "I",
+                                inspector_selected,
+                                compact_toolbar,
+                            );
+                            if self
+                                .mode_toggle_button(ui, &inspector_label, inspector_selected)
+                                .on_hover_text(tr!("studio.canvas.inspector_hint"))
+                                .clicked()
+                            {
+                                self.graph_surface.inspector_enabled = !inspector_selected;
+                            }
+                            if self
+                                .add_toolbar_widget(ui, true, egui::Button::new("+"))
+                                .on_hover_text(tr!("studio.canvas.zoom_in"))
+                                .clicked()
+                            {
                                 self.canvas_viewport.zoom_in();
                             }
-                            if ui
-                                .button(format!("{}%", self.canvas_viewport.zoom_percent()))
+                            let zoom_label = format!("{}%", self.canvas_viewport.zoom_percent());
+                            if self
+                                .add_toolbar_widget(ui, true, egui::Button::new(zoom_label))
+                                .on_hover_text(tr!("studio.canvas.zoom_reset"))
                                 .clicked()
                             {
                                 self.canvas_viewport.reset();
                             }
-                            if ui.button(if compact_toolbar { "-" } else { "−" }).clicked() {
+                            let zoom_out =
+                                egui::Button::new(if compact_toolbar { "-" } else { "−" });
+                            if self
+                                .add_toolbar_widget(ui, true, zoom_out)
+                                .on_hover_text(tr!("studio.canvas.zoom_out"))
+                                .clicked()
+                            {
                                 self.canvas_viewport.zoom_out();
                             }
                         });
@@ -1240,6 +2685,10 @@ impl StudioApp {
             });
         }
 
+        if self.graph_surface.inspector_enabled {
+            self.render_inspector_panel(ui);
+        }
+
         let surface_height = ui.available_height().max(240.0);
         egui::Frame::new()
             .fill(studio_stage_surface())
@@ -1249,7 +2698,12 @@ impl StudioApp {
             .show(ui, |ui| self.render_canvas_surface(ui, surface_height));
     }
 
-    fn mode_toggle_button(&self, ui: &mut egui::Ui, label: &str, selected: bool) -> egui::Response {
+    fn mode_toggle_button(
+        &mut self,
+        ui: &mut egui::Ui,
+        label: &str,
+        selected: bool,
+    ) -> egui::Response {
         let anim = ui
             .ctx()
             .animate_bool(ui.id().with(format!("mode-{label}")), selected);
@@ -1268,16 +2722,16 @@ impl StudioApp {
         } else {
             studio_border_strong()
         };
-        ui.add(
-            egui::Button::new(
-                egui::RichText::new(label)
-                    .small()
-                    .strong()
-                    .color(text_color),
-            )
-            .fill(fill)
-            .stroke(egui::Stroke::new(1.0, stroke)),
+        let stroke_width = if high_contrast_enabled() { 2.0 } else { 1.0 };
+        let button = egui::Button::new(
+            egui::RichText::new(label)
+                .small()
+                .strong()
+                .color(text_color),
         )
+        .fill(fill)
+        .stroke(egui::Stroke::new(stroke_width, stroke));
+        self.add_toolbar_widget(ui, true, button)
     }
 
     fn maybe_finalize_turn_snapshot(
@@ -1297,14 +2751,12 @@ impl StudioApp {
             changed_target_ids: self.graph_surface.changed_target_ids.clone(),
             impact_target_ids: self.graph_surface.impact_target_ids.clone(),
             intent_target_ids: pending.intent_target_ids,
-            baseline_graph: pending.baseline_graph,
-            outcome_graph,
+            graphs: SnapshotGraphs::Resident {
+                baseline: pending.baseline_graph,
+                outcome: outcome_graph,
+            },
         };
         self.turn_snapshots.push(snapshot);
-        if self.turn_snapshots.len() > MAX_TURN_SNAPSHOTS {
-            let extra = self.turn_snapshots.len() - MAX_TURN_SNAPSHOTS;
-            self.turn_snapshots.drain(0..extra);
-        }
         self.selected_snapshot_index = self.turn_snapshots.len().checked_sub(1);
         self.bump_snapshot_transition();
     }
@@ -1330,6 +2782,7 @@ impl StudioApp {
         let next = current.saturating_sub(1);
         if next != current {
             self.selected_snapshot_index = Some(next);
+            self.turn_snapshots.ensure_resident(next);
             self.bump_snapshot_transition();
         }
     }
@@ -1342,6 +2795,7 @@ impl StudioApp {
         let next = (current + 1).min(last_index);
         if next != current {
             self.selected_snapshot_index = Some(next);
+            self.turn_snapshots.ensure_resident(next);
             self.bump_snapshot_transition();
         }
     }
@@ -1350,7 +2804,164 @@ impl StudioApp {
         self.snapshot_transition_pulse = !self.snapshot_transition_pulse;
     }
 
-    fn render_chat_entry(&self, ui: &mut egui::Ui, entry: &ChatEntry) {
+    /// Drops chat entries, turn summaries, tool cards, and turn snapshots (including spilled
+    /// files). Queued and running turns are left alone.
+    /// Writes the chat history to `.mjolne/exports/` and reports the path in the chat.
+    fn export_conversation(&mut self, format: ExportFormat) {
+        let transcript = studio_transcript(&self.chat_history, &self.workspace_root);
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = self
+            .workspace_root
+            .join(EXPORT_DIR)
+            .join(format!("conversation-{stamp}.{}", format.extension()));
+        let notice = match transcript.write_to(&path, format) {
+            Ok(()) => {
+                info!(path = %path.display(), format = format.as_str(), "exported studio conversation");
+                format!("Exported conversation to `{}`.", path.display())
+            }
+            Err(error) => {
+                warn!(error = %format!("{error:#}"), "failed to export studio conversation");
+                format!("Export failed: {error:#}")
+            }
+        };
+        self.chat_history.push(ChatEntry::system(notice));
+    }
+
+    /// Archives the conversation to `SESSION_ARCHIVE_DIR` as the window closes, titled by a
+    /// generated session summary with `AGENT_SESSION_SUMMARY` on. Closing waits for it.
+    fn archive_conversation(&self) {
+        let Some(dir) = self.settings.session_archive_dir.as_deref() else {
+            return;
+        };
+        if !self
+            .chat_history
+            .iter()
+            .any(|entry| entry.speaker == ChatSpeaker::User)
+        {
+            return;
+        }
+        if self.runtime_handle.runtime_flavor() != RuntimeFlavor::MultiThread {
+            warn!("studio session archive needs a multi-threaded runtime; skipping");
+            return;
+        }
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let session_id = format!("studio-{stamp}");
+        let dir = PathBuf::from(dir);
+        let transcript = studio_transcript(&self.chat_history, &self.workspace_root);
+        let conversation = studio_model_conversation(&self.chat_history);
+        let summarize = self.settings.session_summary_enabled;
+        let client = ModelClient::new(self.settings.clone());
+        let archive = async move {
+            let session_summary = if summarize {
+                generate_session_summary(&client, &conversation)
+                    .await
+                    .unwrap_or_else(|error| {
+                        warn!(error = %error, "failed to generate studio session summary");
+                        None
+                    })
+            } else {
+                None
+            };
+            archive_transcript(&dir, &session_id, transcript, session_summary.as_ref())
+        };
+        match tokio::task::block_in_place(|| self.runtime_handle.block_on(archive)) {
+            Ok(path) => info!(path = %path.display(), "archived studio session"),
+            Err(error) => warn!(error = %format!("{error:#}"), "failed to archive studio session"),
+        }
+    }
+
+    /// Keeps the conversation the panic hook would save in step with the chat history.
+    /// Nothing is saved until the user has sent a message.
+    fn sync_crash_session(&mut self) {
+        if self.chat_history.len() == self.crash_session_len {
+            return;
+        }
+        self.crash_session_len = self.chat_history.len();
+        let has_user_entries = self
+            .chat_history
+            .iter()
+            .any(|entry| entry.speaker == ChatSpeaker::User);
+        set_studio_session(
+            &self.workspace_root,
+            has_user_entries.then(|| studio_transcript(&self.chat_history, &self.workspace_root)),
+        );
+    }
+
+    /// Replaces the chat history with the conversation a crashed studio left behind.
+    fn restore_recovery(&mut self, recovery: StudioRecovery) {
+        info!(
+            crash_report = %recovery.crash_report.display(),
+            entries = recovery.transcript.entries.len(),
+            "restoring studio conversation after crash"
+        );
+        self.chat_history = chat_history_from_transcript(&recovery.transcript);
+        self.chat_history.push(ChatEntry::system(tr!(
+            "studio.recovery.restored",
+            path = recovery.crash_report.display()
+        )));
+    }
+
+    fn render_recovery_dialog(&mut self, ctx: &egui::Context) {
+        let Some(recovery) = &self.pending_recovery else {
+            return;
+        };
+        let mut restore = None;
+        egui::Modal::new(egui::Id::new("studio_recovery_dialog")).show(ctx, |ui| {
+            ui.set_max_width(self.preferences.scaled(420.0));
+            ui.heading(tr!("studio.recovery.title"));
+            ui.label(tr!(
+                "studio.recovery.body",
+                count = recovery.transcript.entries.len()
+            ));
+            ui.label(
+                egui::RichText::new(tr!(
+                    "studio.recovery.report",
+                    path = recovery.crash_report.display()
+                ))
+                .small()
+                .color(studio_muted_text()),
+            );
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr!("studio.recovery.restore")).clicked() {
+                    restore = Some(true);
+                }
+                if ui.button(tr!("studio.recovery.dismiss")).clicked() {
+                    restore = Some(false);
+                }
+            });
+        });
+        match restore {
+            Some(true) => {
+                if let Some(recovery) = self.pending_recovery.take() {
+                    self.restore_recovery(recovery);
+                }
+            }
+            Some(false) => self.pending_recovery = None,
+            None => {}
+        }
+    }
+
+    fn clear_history(&mut self) {
+        self.chat_history = vec![ChatEntry::system(tr!("studio.history_cleared"))];
+        self.turn_summaries.clear();
+        self.canvas_tool_cards.clear();
+        self.last_turn_timeline.clear();
+        self.last_turn_context = None;
+        self.turn_snapshots.clear();
+        self.selected_snapshot_index = None;
+        self.canvas_diff_mode = CanvasDiffMode::Live;
+        self.render_architecture_overview_scene();
+    }
+
+    /// Draws one chat message and returns the area it covers.
+    fn render_chat_entry(&self, ui: &mut egui::Ui, entry: &ChatEntry) -> egui::Rect {
         let (fill, stroke, label_color, text_color) = match entry.speaker {
             ChatSpeaker::User => (
                 egui::Color32::from_rgb(233, 243, 253),
@@ -1372,9 +2983,19 @@ impl StudioApp {
             ),
         };
 
-        egui::Frame::new()
+        let (stroke, label_color, text_color) = if high_contrast_enabled() {
+            (
+                egui::Stroke::new(2.0, studio_border_strong()),
+                studio_text(),
+                studio_text(),
+            )
+        } else {
+            (egui::Stroke::new(1.0, stroke), label_color, text_color)
+        };
+
+        let rect = egui::Frame::new()
             .fill(fill)
-            .stroke(egui::Stroke::new(1.0, stroke))
+            .stroke(stroke)
             .corner_radius(10)
             .inner_margin(egui::Margin::symmetric(10, 8))
             .show(ui, |ui| {
@@ -1386,91 +3007,11 @@ impl StudioApp {
                 );
                 ui.add_space(1.0);
                 ui.label(egui::RichText::new(&entry.text).color(text_color));
-            });
+            })
+            .response
+            .rect;
         ui.add_space(5.0);
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
-struct GraphChangeDelta {
-    changed_node_ids: Vec<String>,
-    impact_node_ids: Vec<String>,
-}
-
-fn graph_change_delta(
-    previous: Option<&ArchitectureGraph>,
-    current: &ArchitectureGraph,
-) -> GraphChangeDelta {
-    let Some(previous_graph) = previous else {
-        return GraphChangeDelta::default();
-    };
-
-    let previous_nodes_by_id = previous_graph
-        .nodes
-        .iter()
-        .map(|node| (node.id.as_str(), node))
-        .collect::<BTreeMap<_, _>>();
-    let current_nodes_by_id = current
-        .nodes
-        .iter()
-        .map(|node| (node.id.as_str(), node))
-        .collect::<BTreeMap<_, _>>();
-
-    let mut changed_node_ids = BTreeSet::new();
-
-    for node in &current.nodes {
-        match previous_nodes_by_id.get(node.id.as_str()) {
-            None => {
-                changed_node_ids.insert(node.id.clone());
-            }
-            Some(previous_node) if *previous_node != node => {
-                changed_node_ids.insert(node.id.clone());
-            }
-            Some(_) => {}
-        }
-    }
-
-    let previous_edges = previous_graph
-        .edges
-        .iter()
-        .cloned()
-        .collect::<BTreeSet<_>>();
-    let current_edges = current.edges.iter().cloned().collect::<BTreeSet<_>>();
-
-    for edge in current_edges.difference(&previous_edges) {
-        if current_nodes_by_id.contains_key(edge.from.as_str()) {
-            changed_node_ids.insert(edge.from.clone());
-        }
-        if current_nodes_by_id.contains_key(edge.to.as_str()) {
-            changed_node_ids.insert(edge.to.clone());
-        }
-    }
-
-    for edge in previous_edges.difference(&current_edges) {
-        if current_nodes_by_id.contains_key(edge.from.as_str()) {
-            changed_node_ids.insert(edge.from.clone());
-        }
-        if current_nodes_by_id.contains_key(edge.to.as_str()) {
-            changed_node_ids.insert(edge.to.clone());
-        }
-    }
-
-    let mut impact_node_ids = BTreeSet::new();
-    if !changed_node_ids.is_empty() {
-        for edge in &current.edges {
-            let from_changed = changed_node_ids.contains(edge.from.as_str());
-            let to_changed = changed_node_ids.contains(edge.to.as_str());
-            if from_changed && !to_changed {
-                impact_node_ids.insert(edge.to.clone());
-            } else if to_changed && !from_changed {
-                impact_node_ids.insert(edge.from.clone());
-            }
-        }
-    }
-
-    GraphChangeDelta {
-        changed_node_ids: changed_node_ids.into_iter().collect(),
-        impact_node_ids: impact_node_ids.into_iter().collect(),
+        rect
     }
 }
 
@@ -1488,19 +3029,34 @@ fn build_highlight_node_ids(
 
 impl Drop for StudioApp {
     fn drop(&mut self) {
+        self.archive_conversation();
         let _ = self.command_tx.send(StudioCommand::Shutdown);
         self.graph_watch_handle.shutdown();
+        set_studio_session(&self.workspace_root, None);
+        discard_own_studio_recovery();
     }
 }
 
 impl eframe::App for StudioApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let frame_start = Instant::now();
+        if ctx.input(|input| input.key_pressed(egui::Key::F12)) {
+            self.profiler.toggle();
+        }
+        if let Some(shortcut) = ctx.input_mut(consume_shortcut) {
+            self.handle_shortcut(shortcut);
+        }
+        let frame_interval = self.profiler.begin_frame(frame_start);
+        let draw_commands_before = self.canvas.applied_draw_command_count();
+
         self.ensure_theme(ctx);
-        self.drain_events();
-        self.drain_graph_updates();
+        let events_drained = self.drain_events();
+        let graph_updates_drained = self.drain_graph_updates();
+        self.supervise_workers(Instant::now());
+        self.sync_crash_session();
 
         egui::TopBottomPanel::top("studio_header")
-            .exact_height(78.0)
+            .exact_height(self.preferences.scaled(78.0).max(78.0))
             .frame(
                 egui::Frame::new()
                     .fill(studio_panel_tint())
@@ -1512,9 +3068,9 @@ impl eframe::App for StudioApp {
         if self.chat_panel_expanded {
             egui::SidePanel::left("studio_chat_pane")
                 .resizable(true)
-                .default_width(305.0)
+                .default_width(self.preferences.scaled(305.0))
                 .min_width(250.0)
-                .max_width(420.0)
+                .max_width(self.preferences.scaled(420.0).max(420.0))
                 .frame(
                     egui::Frame::new()
                         .fill(studio_panel_surface())
@@ -1542,108 +3098,240 @@ impl eframe::App for StudioApp {
                     .inner_margin(egui::Margin::symmetric(12, 10)),
             )
             .show(ctx, |ui| self.render_canvas_pane(ui));
+        self.render_recovery_dialog(ctx);
+
+        if self.profiler.is_visible() {
+            let (graph_nodes, graph_edges) = self
+                .canvas
+                .graph()
+                .map_or((0, 0), |graph| (graph.nodes.len(), graph.edges.len()));
+            self.profiler.record(FrameSample {
+                update_time: frame_start.elapsed(),
+                frame_interval,
+                events_drained,
+                event_queue_depth: self.event_rx.len(),
+                graph_updates_drained,
+                graph_update_queue_depth: self.graph_update_rx.pending_len(),
+                graph_nodes,
+                graph_edges,
+                draw_commands: self.canvas.applied_draw_command_count() - draw_commands_before,
+                scene_objects: self.canvas.draw_scene().object_count(),
+            });
+            self.profiler.render(ctx);
+        }
 
         ctx.request_repaint_after(Duration::from_millis(120));
     }
 }
 
+/// Chip colors for the context indicator: neutral, amber near the budget, red over it.
+fn context_chip_colors(level: ContextLevel) -> (egui::Color32, egui::Color32, egui::Color32) {
+    match level {
+        ContextLevel::Normal => (
+            egui::Color32::from_rgb(235, 242, 250),
+            studio_border(),
+            studio_muted_text(),
+        ),
+        ContextLevel::NearLimit => (
+            egui::Color32::from_rgb(255, 241, 220),
+            egui::Color32::from_rgb(224, 175, 117),
+            egui::Color32::from_rgb(150, 96, 27),
+        ),
+        ContextLevel::OverBudget => (
+            egui::Color32::from_rgb(253, 232, 232),
+            egui::Color32::from_rgb(226, 160, 160),
+            egui::Color32::from_rgb(163, 61, 61),
+        ),
+    }
+}
+
+/// Canvas toolbar toggle text: `short` on a narrow toolbar, with the localized "On"
+/// marker while selected.
+fn toolbar_toggle_label(full: &str, short: &str, selected: bool, compact: bool) -> String {
+    let label = if compact { short } else { full };
+    if selected {
+        tr!("studio.canvas.toggle_on", label = label)
+    } else {
+        label.to_owned()
+    }
+}
+
 fn truncate_ui_text(text: &str, max_chars: usize) -> String {
-    if text.chars().count() <= max_chars {
-        return text.to_owned();
+    truncate_graphemes(text, max_chars, "…")
+}
+
+fn graph_watch_health_label(health: &GraphWatchHealth, now: SystemTime) -> String {
+    let mut label = if health.paused {
+        "watch paused".to_owned()
+    } else {
+        "watch on".to_owned()
+    };
+    match health.last_refresh_at {
+        Some(refreshed_at) => {
+            let age_secs = now
+                .duration_since(refreshed_at)
+                .unwrap_or_default()
+                .as_secs();
+            label.push_str(&format!(" · refreshed {age_secs}s ago"));
+        }
+        None => label.push_str(" · no refresh yet"),
     }
+    match health.error_count() {
+        0 => {}
+        1 => label.push_str(" · 1 error"),
+        count => label.push_str(&format!(" · {count} errors")),
+    }
+    label
+}
 
-    let mut clipped = text
-        .chars()
-        .take(max_chars.saturating_sub(1))
-        .collect::<String>();
-    clipped.push('…');
-    clipped
+fn restart_plan_text(health: WorkerHealth, now: Instant, gave_up_hint: &str) -> String {
+    match health {
+        WorkerHealth::RestartScheduled {
+            attempt,
+            restart_at,
+        } => format!(
+            "restarting in {}ms (attempt {attempt}/{}).",
+            restart_at.saturating_duration_since(now).as_millis(),
+            supervisor::MAX_WORKER_RESTART_ATTEMPTS
+        ),
+        WorkerHealth::Healthy | WorkerHealth::GaveUp => {
+            format!("restart attempts exhausted; {gave_up_hint}.")
+        }
+    }
 }
 
-fn summarize_for_canvas(text: &str) -> String {
-    let trimmed = text.trim();
-    if trimmed.chars().count() <= CANVAS_PREVIEW_CHAR_LIMIT {
-        return trimmed.to_owned();
+fn live_tool_card_id_prefix(turn_id: u64) -> String {
+    format!("live-tool-{turn_id}-")
+}
+
+fn live_tool_card_id(turn_id: u64, tool_call_index: usize) -> String {
+    format!("{}{tool_call_index}", live_tool_card_id_prefix(turn_id))
+}
+
+fn tool_card_meta_label(call: &ExecutedToolCall) -> String {
+    let mut label = format!("{}ms", call.latency.as_millis());
+    if call.attempts > 1 {
+        label.push_str(&format!(" · {} attempts", call.attempts));
+    }
+    if call.error.is_some() {
+        label.push_str(" · recovered");
+    }
+    label
+}
+
+fn tool_card_citation_label(tool_call_index: usize, citations: &[TurnCitation]) -> Option<String> {
+    let citation = citations
+        .iter()
+        .find(|citation| citation.tool_call_index == tool_call_index)?;
+    if citation.sources.is_empty() {
+        return Some(format!("Cited as [{}]", citation.marker));
     }
 
-    let mut preview = trimmed
-        .chars()
-        .take(CANVAS_PREVIEW_CHAR_LIMIT.saturating_sub(1))
-        .collect::<String>();
-    preview.push('…');
-    preview
+    Some(format!(
+        "Cited as [{}] · {}",
+        citation.marker,
+        citation.sources.join(", ")
+    ))
+}
+
+fn summarize_for_canvas(text: &str) -> String {
+    truncate_graphemes(text.trim(), CANVAS_PREVIEW_CHAR_LIMIT, "…")
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs;
     use std::path::PathBuf;
-    use std::time::UNIX_EPOCH;
+    use std::time::{Instant, UNIX_EPOCH};
 
     use tokio::runtime::Handle;
-    use tokio::sync::mpsc::unbounded_channel;
+    use tokio::sync::mpsc::{channel, unbounded_channel};
     use tokio::time::{Duration, timeout};
 
-    use crate::config::{AgentSettings, ModelProvider};
-    use crate::graph::watch::{GraphRefreshTrigger, GraphRefreshUpdate, spawn_graph_watch_worker};
+    use crate::config::{AgentSettings, ModelProvider, ModerationMode, ModerationProvider};
+    use crate::graph::updates::graph_update_channel;
+    use crate::graph::watch::{
+        GraphRefreshTrigger, GraphRefreshUpdate, GraphWatchHealth, spawn_graph_watch_worker,
+    };
     use crate::graph::{
         ArchitectureEdge, ArchitectureEdgeKind, ArchitectureGraph, ArchitectureNode,
         ArchitectureNodeKind,
     };
+    use crate::i18n::Locale;
     use crate::test_support::{remove_dir_if_exists, temp_path};
 
     use super::{
-        CanvasDiffMode, CanvasOp, CanvasState, CanvasTurnSnapshot, GraphSurfaceState,
-        MAX_GRAPH_UPDATES_PER_FRAME, PendingTurnSnapshot, StudioApp, StudioCommand, StudioEvent,
-        SubsystemMapper, build_highlight_node_ids, graph_change_delta, spawn_runtime_worker,
-        summarize_for_canvas,
+        CanvasDiffMode, CanvasOp, CanvasState, CanvasTurnSnapshot, ChatEntry, ExecutedToolCall,
+        GraphSurfaceState, MAX_GRAPH_UPDATES_PER_FRAME, PendingTurnSnapshot,
+        STUDIO_EVENT_CHANNEL_CAPACITY, SnapshotGraphs, StudioApp, StudioCommand, StudioEvent,
+        StudioWorkerChannels, SubsystemMapper, TurnCitation, build_highlight_node_ids,
+        chat_history_from_transcript, graph_watch_health_label, spawn_runtime_worker,
+        studio_transcript, summarize_for_canvas, tool_card_citation_label, tool_card_meta_label,
     };
-
-    #[test]
-    fn summarize_for_canvas_truncates_long_text() {
-        let long_text = "x".repeat(260);
-        let summary = summarize_for_canvas(&long_text);
-        assert_eq!(summary.chars().count(), 180);
-        assert!(summary.ends_with('…'));
-    }
-
-    #[test]
-    fn graph_change_delta_is_empty_without_previous_graph() {
-        let current = graph_for_test(2, &["module:crate"], &[("module:crate", "module:crate")]);
-        let delta = graph_change_delta(None, &current);
-        assert!(delta.changed_node_ids.is_empty());
-        assert!(delta.impact_node_ids.is_empty());
-    }
-
-    #[test]
-    fn graph_change_delta_detects_added_nodes_and_one_hop_impact() {
-        let previous = graph_for_test(
-            1,
-            &["module:crate", "module:crate::tools"],
-            &[("module:crate", "module:crate::tools")],
-        );
-        let current = graph_for_test(
-            2,
-            &[
-                "module:crate",
-                "module:crate::tools",
-                "module:crate::tools::parser",
-            ],
-            &[
-                ("module:crate", "module:crate::tools"),
-                ("module:crate::tools", "module:crate::tools::parser"),
-            ],
+    use crate::agent::TurnEvent;
+    use crate::export::{ExportFormat, TranscriptRole};
+    use crate::tools::ToolProgress;
+
+    #[test]
+    fn tool_card_citation_label_lists_cited_sources() {
+        let citations = vec![TurnCitation {
+            marker: 2,
+            tool_call_index: 1,
+            tool_name: "fetch_url".to_owned(),
+            sources: vec!["https://example.com/".to_owned()],
+        }];
+
+        assert_eq!(tool_card_citation_label(0, &citations), None);
+        assert_eq!(
+            tool_card_citation_label(1, &citations).as_deref(),
+            Some("Cited as [2] · https://example.com/")
         );
+    }
+
+    #[test]
+    fn tool_card_meta_label_reports_latency_retries_and_recovery() {
+        let mut call = ExecutedToolCall {
+            tool_name: "fetch_url".to_owned(),
+            arguments: serde_json::json!({ "url": "https://example.com" }),
+            output: "{}".to_owned(),
+            latency: Duration::from_millis(42),
+            attempts: 1,
+            error: None,
+        };
+        assert_eq!(tool_card_meta_label(&call), "42ms");
+
+        call.attempts = 2;
+        call.error = Some("timed out after 5000ms".to_owned());
+        assert_eq!(tool_card_meta_label(&call), "42ms · 2 attempts · recovered");
+    }
 
-        let delta = graph_change_delta(Some(&previous), &current);
+    #[test]
+    fn graph_watch_health_label_reports_pause_age_and_errors() {
+        let now = UNIX_EPOCH + Duration::from_secs(100);
         assert_eq!(
-            delta.changed_node_ids,
-            vec![
-                "module:crate::tools".to_owned(),
-                "module:crate::tools::parser".to_owned()
-            ]
+            graph_watch_health_label(&GraphWatchHealth::default(), now),
+            "watch on · no refresh yet"
+        );
+        let health = GraphWatchHealth {
+            paused: true,
+            last_refresh_at: Some(UNIX_EPOCH + Duration::from_secs(88)),
+            refresh_count: 3,
+            fingerprint_error_count: 1,
+            refresh_error_count: 1,
+            last_error: Some("boom".to_owned()),
+        };
+        assert_eq!(
+            graph_watch_health_label(&health, now),
+            "watch paused · refreshed 12s ago · 2 errors"
         );
-        assert_eq!(delta.impact_node_ids, vec!["module:crate".to_owned()]);
+    }
+
+    #[test]
+    fn summarize_for_canvas_truncates_long_text() {
+        let long_text = "x".repeat(260);
+        let summary = summarize_for_canvas(&long_text);
+        assert_eq!(summary.chars().count(), 180);
+        assert!(summary.ends_with('…'));
     }
 
     #[test]
@@ -1765,22 +3453,25 @@ mod tests {
     async fn snapshot_navigation_moves_selection_within_bounds() {
         let workspace_root = create_workspace_root("studio-snapshot-selection");
         let (command_tx, _command_rx) = unbounded_channel();
-        let (_event_tx, event_rx) = unbounded_channel();
-        let (_graph_update_tx, graph_update_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (_graph_update_tx, graph_update_rx) = graph_update_channel();
         let runtime_handle = Handle::current();
         let (graph_watch_handle, _graph_watch_rx) =
             spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
         let mut app = StudioApp::new(
             studio_test_settings(8),
             SubsystemMapper::default(),
-            command_tx,
-            event_rx,
-            graph_update_rx,
-            graph_watch_handle.clone(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
             workspace_root.clone(),
+            runtime_handle.clone(),
         );
         app.selected_snapshot_index = Some(1);
-        app.turn_snapshots = vec![
+        for snapshot in [
             CanvasTurnSnapshot {
                 turn_id: 1,
                 started_at: UNIX_EPOCH,
@@ -1790,8 +3481,10 @@ mod tests {
                 changed_target_ids: vec![],
                 impact_target_ids: vec![],
                 intent_target_ids: vec![],
-                baseline_graph: None,
-                outcome_graph: graph_for_test(2, &["module:crate"], &[]),
+                graphs: SnapshotGraphs::Resident {
+                    baseline: None,
+                    outcome: graph_for_test(2, &["module:crate"], &[]),
+                },
             },
             CanvasTurnSnapshot {
                 turn_id: 2,
@@ -1802,10 +3495,14 @@ mod tests {
                 changed_target_ids: vec![],
                 impact_target_ids: vec![],
                 intent_target_ids: vec![],
-                baseline_graph: None,
-                outcome_graph: graph_for_test(3, &["module:crate"], &[]),
+                graphs: SnapshotGraphs::Resident {
+                    baseline: None,
+                    outcome: graph_for_test(3, &["module:crate"], &[]),
+                },
             },
-        ];
+        ] {
+            app.turn_snapshots.push(snapshot);
+        }
 
         app.select_previous_snapshot();
         assert_eq!(app.selected_snapshot_index(), Some(0));
@@ -1823,24 +3520,120 @@ mod tests {
         remove_dir_if_exists(&workspace_root);
     }
 
+    #[test]
+    fn studio_transcript_keeps_speakers_and_turn_tool_calls_for_recovery() {
+        let call = ExecutedToolCall {
+            tool_name: "search_notes".to_owned(),
+            arguments: serde_json::json!({"query": "rust"}),
+            output: r#"{"results":[]}"#.to_owned(),
+            latency: Duration::from_millis(3),
+            attempts: 1,
+            error: None,
+        };
+        let history = vec![
+            ChatEntry::system("Studio ready."),
+            ChatEntry::user("find rust notes"),
+            ChatEntry::assistant("None found.", vec![(&call).into()]),
+        ];
+
+        let transcript = studio_transcript(&history, &PathBuf::from("/work/demo"));
+
+        assert_eq!(transcript.title, "Studio conversation (demo)");
+        let roles = transcript
+            .entries
+            .iter()
+            .map(|entry| entry.role)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            roles,
+            [
+                TranscriptRole::System,
+                TranscriptRole::User,
+                TranscriptRole::Assistant
+            ]
+        );
+        let markdown = transcript.render(ExportFormat::Markdown);
+        assert!(markdown.contains("<summary>Tool call: <code>search_notes</code></summary>"));
+        assert!(markdown.ends_with("None found.\n"));
+        assert_eq!(chat_history_from_transcript(&transcript), history);
+    }
+
+    #[tokio::test]
+    async fn clear_history_drops_chat_and_turn_snapshots() {
+        let workspace_root = create_workspace_root("studio-clear-history");
+        let (command_tx, _command_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (_graph_update_tx, graph_update_rx) = graph_update_channel();
+        let runtime_handle = Handle::current();
+        let (graph_watch_handle, _graph_watch_rx) =
+            spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
+        let mut app = StudioApp::new(
+            studio_test_settings(8),
+            SubsystemMapper::default(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
+            workspace_root.clone(),
+            runtime_handle.clone(),
+        );
+        app.chat_history.push(super::ChatEntry::user("hello"));
+        app.turn_summaries.push(super::CanvasTurnSummary {
+            user_message: "hello".to_owned(),
+            assistant_preview: "hi".to_owned(),
+            tool_call_count: 0,
+        });
+        app.turn_snapshots.push(CanvasTurnSnapshot {
+            turn_id: 1,
+            started_at: UNIX_EPOCH,
+            completed_at: UNIX_EPOCH,
+            baseline_revision: None,
+            outcome_revision: 1,
+            changed_target_ids: vec![],
+            impact_target_ids: vec![],
+            intent_target_ids: vec![],
+            graphs: SnapshotGraphs::Resident {
+                baseline: None,
+                outcome: graph_for_test(1, &["module:crate"], &[]),
+            },
+        });
+        app.canvas_diff_mode = CanvasDiffMode::FocusLatestTurn;
+
+        app.clear_history();
+
+        assert_eq!(app.chat_history.len(), 1);
+        assert!(app.turn_summaries.is_empty());
+        assert!(app.turn_snapshots.is_empty());
+        assert_eq!(app.selected_snapshot_index(), None);
+        assert_eq!(app.canvas_diff_mode, CanvasDiffMode::Live);
+
+        graph_watch_handle.shutdown();
+        remove_dir_if_exists(&workspace_root);
+    }
+
     #[tokio::test]
     async fn maybe_finalize_turn_snapshot_records_baseline_and_outcome() {
         let workspace_root = create_workspace_root("studio-snapshot-record");
         let settings = studio_test_settings(8);
         let (command_tx, _command_rx) = unbounded_channel();
-        let (_event_tx, event_rx) = unbounded_channel();
-        let (_graph_update_tx, graph_update_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (_graph_update_tx, graph_update_rx) = graph_update_channel();
         let runtime_handle = Handle::current();
         let (graph_watch_handle, _graph_watch_rx) =
             spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
         let mut app = StudioApp::new(
             settings,
             SubsystemMapper::default(),
-            command_tx,
-            event_rx,
-            graph_update_rx,
-            graph_watch_handle.clone(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
             workspace_root.clone(),
+            runtime_handle.clone(),
         );
 
         let baseline = graph_for_test(1, &["module:crate"], &[]);
@@ -1857,15 +3650,20 @@ mod tests {
         app.maybe_finalize_turn_snapshot(outcome.clone(), UNIX_EPOCH);
 
         assert_eq!(app.turn_snapshots.len(), 1);
-        let snapshot = &app.turn_snapshots[0];
+        let snapshot = app.turn_snapshots.get(0).expect("snapshot");
         assert_eq!(snapshot.turn_id, 9);
         assert_eq!(snapshot.baseline_revision, Some(1));
         assert_eq!(snapshot.outcome_revision, 2);
         assert_eq!(snapshot.changed_target_ids, ["module:crate::tools"]);
         assert_eq!(snapshot.impact_target_ids, ["module:crate"]);
         assert_eq!(snapshot.intent_target_ids, ["module:crate::tools"]);
-        assert_eq!(snapshot.baseline_graph.as_ref(), Some(&baseline));
-        assert_eq!(snapshot.outcome_graph, outcome);
+        assert_eq!(
+            snapshot.graphs,
+            SnapshotGraphs::Resident {
+                baseline: Some(baseline),
+                outcome,
+            }
+        );
 
         graph_watch_handle.shutdown();
         remove_dir_if_exists(&workspace_root);
@@ -1876,19 +3674,22 @@ mod tests {
         let workspace_root = create_workspace_root("studio-overlay-mode");
         let settings = studio_test_settings(8);
         let (command_tx, _command_rx) = unbounded_channel();
-        let (_event_tx, event_rx) = unbounded_channel();
-        let (_graph_update_tx, graph_update_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (_graph_update_tx, graph_update_rx) = graph_update_channel();
         let runtime_handle = Handle::current();
         let (graph_watch_handle, _graph_watch_rx) =
             spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
         let mut app = StudioApp::new(
             settings,
             SubsystemMapper::default(),
-            command_tx,
-            event_rx,
-            graph_update_rx,
-            graph_watch_handle.clone(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
             workspace_root.clone(),
+            runtime_handle.clone(),
         );
 
         let baseline = graph_for_test(1, &["module:crate"], &[]);
@@ -1906,8 +3707,10 @@ mod tests {
             changed_target_ids: vec!["module:crate::tools".to_owned()],
             impact_target_ids: vec!["module:crate".to_owned()],
             intent_target_ids: Vec::new(),
-            baseline_graph: Some(baseline),
-            outcome_graph: graph_for_test(2, &["module:crate", "module:crate::tools"], &[]),
+            graphs: SnapshotGraphs::Resident {
+                baseline: Some(baseline),
+                outcome: graph_for_test(2, &["module:crate", "module:crate::tools"], &[]),
+            },
         });
 
         app.render_architecture_overview_scene();
@@ -1929,19 +3732,22 @@ mod tests {
         let workspace_root = create_workspace_root("studio-focus-mode");
         let settings = studio_test_settings(8);
         let (command_tx, _command_rx) = unbounded_channel();
-        let (_event_tx, event_rx) = unbounded_channel();
-        let (_graph_update_tx, graph_update_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (_graph_update_tx, graph_update_rx) = graph_update_channel();
         let runtime_handle = Handle::current();
         let (graph_watch_handle, _graph_watch_rx) =
             spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
         let mut app = StudioApp::new(
             settings,
             SubsystemMapper::default(),
-            command_tx,
-            event_rx,
-            graph_update_rx,
-            graph_watch_handle.clone(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
             workspace_root.clone(),
+            runtime_handle.clone(),
         );
 
         app.canvas.apply(CanvasOp::set_scene_graph(graph_for_test(
@@ -1959,8 +3765,10 @@ mod tests {
             changed_target_ids: vec!["module:crate::tools".to_owned()],
             impact_target_ids: Vec::new(),
             intent_target_ids: Vec::new(),
-            baseline_graph: Some(graph_for_test(2, &["module:crate"], &[])),
-            outcome_graph: graph_for_test(3, &["module:crate", "module:crate::tools"], &[]),
+            graphs: SnapshotGraphs::Resident {
+                baseline: Some(graph_for_test(2, &["module:crate"], &[])),
+                outcome: graph_for_test(3, &["module:crate", "module:crate::tools"], &[]),
+            },
         });
 
         app.render_architecture_overview_scene();
@@ -1983,7 +3791,7 @@ mod tests {
         let workspace_root = create_workspace_root("studio-runtime-flow");
         let settings = studio_test_settings(1);
         let (command_tx, command_rx) = unbounded_channel();
-        let (event_tx, mut event_rx) = unbounded_channel();
+        let (event_tx, mut event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
         let runtime_handle = Handle::current();
         let (graph_watch_handle, mut graph_update_rx) =
             spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
@@ -1998,6 +3806,7 @@ mod tests {
 
         command_tx
             .send(StudioCommand::SubmitUserMessage {
+                turn_id: 1,
                 message: "hello".to_owned(),
             })
             .expect("command send should succeed");
@@ -2007,7 +3816,12 @@ mod tests {
             .expect("turn started should arrive within timeout")
             .expect("event channel should remain open");
         match started {
-            StudioEvent::TurnStarted { message, .. } => assert_eq!(message, "hello"),
+            StudioEvent::TurnStarted {
+                turn_id, message, ..
+            } => {
+                assert_eq!(turn_id, 1);
+                assert_eq!(message, "hello");
+            }
             other => panic!("expected TurnStarted event, got {other:?}"),
         }
 
@@ -2016,7 +3830,7 @@ mod tests {
             .expect("turn failed should arrive within timeout")
             .expect("event channel should remain open");
         match failed {
-            StudioEvent::TurnFailed { message, error } => {
+            StudioEvent::TurnFailed { message, error, .. } => {
                 assert_eq!(message, "hello");
                 assert!(error.contains("AGENT_MAX_INPUT_CHARS"));
             }
@@ -2050,54 +3864,330 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn drain_graph_updates_processes_bounded_batch_per_frame() {
+    async fn runtime_worker_cancels_queued_and_running_turns() {
+        // A listener that never accepts keeps model calls pending until cancelled.
+        let Ok(stalled_model) = std::net::TcpListener::bind("127.0.0.1:0") else {
+            eprintln!("skipping: local TCP bind is not permitted in this environment");
+            return;
+        };
+        let workspace_root = create_workspace_root("studio-runtime-cancel");
+        let mut settings = studio_test_settings(1_000);
+        settings.ollama_base_url = format!(
+            "http://{}",
+            stalled_model
+                .local_addr()
+                .expect("listener should have an address")
+        );
+        settings.model_timeout_ms = 30_000;
+        let (command_tx, command_rx) = unbounded_channel();
+        let (event_tx, mut event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let runtime_handle = Handle::current();
+        let (graph_watch_handle, _graph_update_rx) =
+            spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
+
+        spawn_runtime_worker(
+            &runtime_handle,
+            settings,
+            command_rx,
+            event_tx,
+            graph_watch_handle.clone(),
+        );
+
+        for (turn_id, message) in [(1, "first"), (2, "second")] {
+            command_tx
+                .send(StudioCommand::SubmitUserMessage {
+                    turn_id,
+                    message: message.to_owned(),
+                })
+                .expect("command send should succeed");
+        }
+        let started = timeout(Duration::from_secs(2), event_rx.recv())
+            .await
+            .expect("turn started should arrive within timeout")
+            .expect("event channel should remain open");
+        assert!(matches!(
+            started,
+            StudioEvent::TurnStarted { turn_id: 1, .. }
+        ));
+
+        for turn_id in [2, 1] {
+            command_tx
+                .send(StudioCommand::CancelTurn { turn_id })
+                .expect("command send should succeed");
+            let cancelled = timeout(Duration::from_secs(2), event_rx.recv())
+                .await
+                .expect("turn cancellation should arrive within timeout")
+                .expect("event channel should remain open");
+            match cancelled {
+                StudioEvent::TurnCancelled {
+                    turn_id: cancelled_id,
+                    ..
+                } => assert_eq!(cancelled_id, turn_id),
+                other => panic!("expected TurnCancelled event, got {other:?}"),
+            }
+        }
+
+        command_tx
+            .send(StudioCommand::Shutdown)
+            .expect("command send should succeed");
+        graph_watch_handle.shutdown();
+        remove_dir_if_exists(&workspace_root);
+    }
+
+    #[tokio::test]
+    async fn studio_app_tracks_turn_queue_and_blocks_disconnected_submits() {
+        let workspace_root = create_workspace_root("studio-turn-queue");
+        let (command_tx, mut command_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (_graph_update_tx, graph_update_rx) = graph_update_channel();
+        let runtime_handle = Handle::current();
+        let (graph_watch_handle, _graph_watch_rx) =
+            spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
+        let mut app = StudioApp::new(
+            studio_test_settings(100),
+            SubsystemMapper::default(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
+            workspace_root.clone(),
+            runtime_handle.clone(),
+        );
+
+        for prompt in ["one", "two"] {
+            app.input_buffer = prompt.to_owned();
+            app.submit_prompt();
+        }
+        assert_eq!(app.queued_turns.len(), 2);
+        assert!(app.turn_in_flight);
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(StudioCommand::SubmitUserMessage { turn_id: 1, .. })
+        ));
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(StudioCommand::SubmitUserMessage { turn_id: 2, .. })
+        ));
+
+        app.cancel_turn(2);
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(StudioCommand::CancelTurn { turn_id: 2 })
+        ));
+        app.apply_event(StudioEvent::TurnCancelled {
+            turn_id: 2,
+            message: "two".to_owned(),
+        });
+        assert_eq!(app.queued_turns.len(), 1);
+        assert!(app.turn_in_flight);
+
+        drop(command_rx);
+        app.input_buffer = "three".to_owned();
+        app.submit_prompt();
+        assert!(app.runtime_disconnected);
+        assert!(app.queued_turns.is_empty());
+        assert!(!app.turn_in_flight);
+
+        app.input_buffer = "four".to_owned();
+        app.submit_prompt();
+        assert_eq!(app.input_buffer, "four");
+        assert!(app.queued_turns.is_empty());
+
+        graph_watch_handle.shutdown();
+        remove_dir_if_exists(&workspace_root);
+    }
+
+    #[tokio::test]
+    async fn live_tool_cards_track_progress_until_the_turn_ends() {
+        let workspace_root = create_workspace_root("studio-live-tool-cards");
+        let (command_tx, _command_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (_graph_update_tx, graph_update_rx) = graph_update_channel();
+        let runtime_handle = Handle::current();
+        let (graph_watch_handle, _graph_watch_rx) =
+            spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
+        let mut app = StudioApp::new(
+            studio_test_settings(100),
+            SubsystemMapper::default(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
+            workspace_root.clone(),
+            runtime_handle.clone(),
+        );
+        app.input_buffer = "fetch the docs".to_owned();
+        app.submit_prompt();
+
+        app.apply_event(StudioEvent::TurnToolActivity {
+            turn_id: 1,
+            event: TurnEvent::ToolStarted {
+                tool_call_index: 0,
+                step: 1,
+                tool_name: "fetch_url".to_owned(),
+                arguments: serde_json::json!({"url": "https://docs.rs"}),
+            },
+        });
+        assert_eq!(app.canvas_tool_cards.len(), 1);
+        assert_eq!(app.canvas_tool_cards[0].meta, "running");
+
+        app.apply_event(StudioEvent::TurnToolActivity {
+            turn_id: 1,
+            event: TurnEvent::ToolProgress {
+                tool_call_index: 0,
+                tool_name: "fetch_url".to_owned(),
+                progress: ToolProgress {
+                    elapsed: Duration::from_millis(1500),
+                    bytes_received: 512,
+                    bytes_total: Some(2048),
+                },
+            },
+        });
+        assert_eq!(app.canvas_tool_cards[0].meta, "1.5s · 512 B / 2.0 KB");
+        assert_eq!(app.canvas_tool_cards[0].progress_percent, Some(25));
+
+        app.apply_event(StudioEvent::TurnToolActivity {
+            turn_id: 1,
+            event: TurnEvent::ToolFinished {
+                tool_call_index: 0,
+                tool_name: "fetch_url".to_owned(),
+                latency: Duration::from_millis(1800),
+                succeeded: false,
+            },
+        });
+        assert_eq!(app.canvas_tool_cards[0].meta, "1800ms · failed");
+        assert_eq!(app.canvas_tool_cards[0].progress_percent, None);
+
+        app.apply_event(StudioEvent::TurnFailed {
+            turn_id: 1,
+            message: "fetch the docs".to_owned(),
+            error: "fetch failed".to_owned(),
+        });
+        assert!(app.canvas_tool_cards.is_empty());
+
+        // Activity trailing the end of a turn does not resurrect its cards.
+        app.apply_event(StudioEvent::TurnToolActivity {
+            turn_id: 1,
+            event: TurnEvent::ToolStarted {
+                tool_call_index: 1,
+                step: 2,
+                tool_name: "search_notes".to_owned(),
+                arguments: serde_json::json!({}),
+            },
+        });
+        assert!(app.canvas_tool_cards.is_empty());
+
+        graph_watch_handle.shutdown();
+        remove_dir_if_exists(&workspace_root);
+    }
+
+    #[tokio::test]
+    async fn studio_app_restarts_disconnected_workers_after_backoff() {
+        let workspace_root = create_workspace_root("studio-worker-restart");
+        let (command_tx, command_rx) = unbounded_channel();
+        let (event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (graph_update_tx, graph_update_rx) = graph_update_channel();
+        let runtime_handle = Handle::current();
+        let (graph_watch_handle, _graph_watch_rx) =
+            spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
+        let mut app = StudioApp::new(
+            studio_test_settings(100),
+            SubsystemMapper::default(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
+            workspace_root.clone(),
+            runtime_handle.clone(),
+        );
+
+        drop(command_rx);
+        drop(event_tx);
+        drop(graph_update_tx);
+        app.drain_events();
+        app.drain_graph_updates();
+        assert!(app.runtime_disconnected);
+        assert!(app.graph_watch_disconnected);
+        assert_eq!(
+            app.worker_health_label().as_deref(),
+            Some("Runtime restarting (1/5) · Graph watch restarting (1/5)")
+        );
+
+        let now = Instant::now();
+        app.supervise_workers(now);
+        assert!(app.runtime_disconnected);
+
+        app.supervise_workers(now + Duration::from_secs(1));
+        assert!(!app.runtime_disconnected);
+        assert!(!app.graph_watch_disconnected);
+        assert_eq!(app.worker_health_label(), None);
+        assert_ne!(app.graph_watch_handle, graph_watch_handle);
+
+        app.input_buffer = "after restart".to_owned();
+        app.submit_prompt();
+        assert!(!app.runtime_disconnected);
+        assert_eq!(app.queued_turns.len(), 1);
+
+        graph_watch_handle.shutdown();
+        remove_dir_if_exists(&workspace_root);
+    }
+
+    #[tokio::test]
+    async fn drain_graph_updates_coalesces_bursts_and_processes_bounded_batch_per_frame() {
         let workspace_root = create_workspace_root("studio-bounded-drain");
         let settings = studio_test_settings(8);
         let (command_tx, _command_rx) = unbounded_channel();
-        let (_event_tx, event_rx) = unbounded_channel();
-        let (graph_update_tx, graph_update_rx) = unbounded_channel();
+        let (_event_tx, event_rx) = channel(STUDIO_EVENT_CHANNEL_CAPACITY);
+        let (graph_update_tx, graph_update_rx) = graph_update_channel();
         let runtime_handle = Handle::current();
         let (graph_watch_handle, _graph_watch_rx) =
             spawn_graph_watch_worker(&runtime_handle, workspace_root.clone());
         let mut app = StudioApp::new(
             settings,
             SubsystemMapper::default(),
-            command_tx,
-            event_rx,
-            graph_update_rx,
-            graph_watch_handle.clone(),
+            StudioWorkerChannels {
+                command_tx,
+                event_rx,
+                graph_update_rx,
+                graph_watch_handle: graph_watch_handle.clone(),
+            },
             workspace_root.clone(),
+            runtime_handle.clone(),
         );
 
-        let total_updates = MAX_GRAPH_UPDATES_PER_FRAME + 2;
-        for revision in 1..=(total_updates as u64) {
+        // A rebase-sized burst of file changes, interleaved with every other trigger.
+        let mut triggers = vec![GraphRefreshTrigger::FilesChanged; 40];
+        triggers.extend([
+            GraphRefreshTrigger::TurnCompleted,
+            GraphRefreshTrigger::Manual,
+            GraphRefreshTrigger::Startup,
+            GraphRefreshTrigger::TurnCompletedAndFilesChanged,
+            GraphRefreshTrigger::FilesChanged,
+        ]);
+        for (revision, trigger) in (1..).zip(triggers) {
             graph_update_tx
                 .send(GraphRefreshUpdate {
                     graph: graph_for_test(revision, &["module:crate"], &[]),
-                    trigger: GraphRefreshTrigger::TurnCompleted,
+                    trigger,
                 })
                 .expect("graph update send should succeed");
         }
 
-        app.drain_graph_updates();
-        assert_eq!(
-            app.canvas.graph().map(|graph| graph.revision),
-            Some(MAX_GRAPH_UPDATES_PER_FRAME as u64)
-        );
-        assert_eq!(
-            app.canvas.draw_scene().last_sequence(),
-            Some(MAX_GRAPH_UPDATES_PER_FRAME as u64)
-        );
+        assert_eq!(app.graph_update_rx.pending_len(), 5);
+        assert_eq!(app.drain_graph_updates(), MAX_GRAPH_UPDATES_PER_FRAME);
+        assert_eq!(MAX_GRAPH_UPDATES_PER_FRAME, 4);
+        assert_eq!(app.canvas.graph().map(|graph| graph.revision), Some(44));
+        assert_eq!(app.canvas.draw_scene().last_sequence(), Some(4));
 
-        app.drain_graph_updates();
-        assert_eq!(
-            app.canvas.graph().map(|graph| graph.revision),
-            Some(total_updates as u64)
-        );
-        assert_eq!(
-            app.canvas.draw_scene().last_sequence(),
-            Some(total_updates as u64)
-        );
+        assert_eq!(app.drain_graph_updates(), 1);
+        assert_eq!(app.canvas.graph().map(|graph| graph.revision), Some(45));
+        assert_eq!(app.canvas.draw_scene().last_sequence(), Some(5));
 
         graph_watch_handle.shutdown();
         remove_dir_if_exists(&workspace_root);
@@ -2137,7 +4227,14 @@ mod tests {
             model_provider: ModelProvider::Ollama,
             model: "qwen2.5:3b".to_owned(),
             ollama_base_url: "http://127.0.0.1:9".to_owned(),
+            offline: false,
             openai_api_key: None,
+            gemini_api_key: None,
+            gemini_base_url: "http://127.0.0.1:9".to_owned(),
+            history_file: None,
+            history_signing_key: None,
+            openai_compat_base_url: None,
+            openai_compat_api_key: None,
             max_steps: 4,
             max_tool_calls: 4,
             max_tool_calls_per_step: 2,
@@ -2145,14 +4242,79 @@ mod tests {
             max_input_chars,
             max_output_chars: 2000,
             tool_timeout_ms: 100,
+            tool_timeout_overrides_ms: Default::default(),
             fetch_url_max_bytes: 4096,
             fetch_url_follow_redirects: false,
             fetch_url_allowed_domains: vec!["example.com".to_owned()],
+            enabled_tools: crate::tools::all_tool_names(),
             notes_dir: "notes".to_owned(),
             save_note_allow_overwrite: false,
+            save_note_allow_delete: false,
+            notes_max_files: 10_000,
+            notes_max_total_bytes: 104_857_600,
+            notes_max_note_bytes: 1_048_576,
             model_timeout_ms: 100,
             model_max_retries: 0,
+            embedding_model: "nomic-embed-text".to_owned(),
+            model_batch_size: 64,
+            model_batch_requests_per_minute: 0,
             studio_subsystem_rules_file: None,
+            graph_dependency_rules_file: None,
+            session_summary_enabled: false,
+            session_archive_dir: None,
+            server_session_idle_timeout_ms: 900_000,
+            server_session_max_messages: 64,
+            server_max_sessions: 256,
+            server_webhook_urls: Vec::new(),
+            server_webhook_secret: None,
+            server_webhook_max_retries: 3,
+            server_webhook_timeout_ms: 5_000,
+            server_schedule_file: None,
+            server_admin_token: None,
+            server_chat_model_allowlist: Vec::new(),
+            server_policy_file: None,
+            server_policy_audit_file: None,
+            studio_max_parallel_turns: 1,
+            studio_snapshot_memory_budget_mb: 64,
+            studio_snapshot_spill_dir: None,
+            update_check_url: crate::config::DEFAULT_UPDATE_CHECK_URL.to_owned(),
+            update_check_on_start: false,
+            repl_pager: true,
+            context_budget_tokens: 8_192,
+            max_context_tokens: 0,
+            auto_compact_tokens: 0,
+            http_pool_max_idle_per_host: 32,
+            http_pool_idle_timeout_ms: 90_000,
+            http_tcp_keepalive_ms: 60_000,
+            http2_enabled: true,
+            http_proxy_url: None,
+            ui_locale: Locale::English,
+            studio_profile_overlay: false,
+            studio_session_notes: false,
+            tool_working_dir: None,
+            workspace_root: None,
+            read_workspace_file_extensions: vec!["md".to_owned(), "rs".to_owned()],
+            read_workspace_file_max_bytes: 4_096,
+            search_workspace_max_results: 20,
+            search_workspace_max_bytes: 65_536,
+            tool_allowed_commands: Vec::new(),
+            run_command_max_output_bytes: 4_096,
+            tool_env_allowlist: Vec::new(),
+            injection_detection_enabled: true,
+            parallel_tool_calls: false,
+            moderation_mode: ModerationMode::Off,
+            moderation_provider: ModerationProvider::Local,
+            moderation_keywords: Vec::new(),
+            moderation_patterns: Vec::new(),
+            response_language: None,
+            model_debug_stream_file: None,
+            editor_command: None,
+            model_cache_dir: None,
+            format_repair_model: None,
+            format_repair_temperature: None,
+            answer_post_processors: crate::answer_format::DEFAULT_ANSWER_POST_PROCESSORS.to_vec(),
+            json_fence_tolerance: true,
+            json_output_max_field_chars: 4_000,
         }
     }
 
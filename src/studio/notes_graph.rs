@@ -0,0 +1,507 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::f32::consts::TAU;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::agent::ExecutedToolCall;
+use crate::text::truncate_graphemes;
+use crate::tools::{
+    BacklinksIndex, DELETE_NOTE_TOOL_NAME, READ_NOTE_TOOL_NAME, SAVE_NOTE_TOOL_NAME,
+    SEARCH_NOTES_TOOL_NAME, extract_note_title, is_searchable_note_extension,
+};
+
+use super::events::{
+    CanvasConnectorObject, CanvasDrawCommand, CanvasDrawCommandBatch, CanvasPoint, CanvasShapeKind,
+    CanvasShapeObject, CanvasStyle,
+};
+
+const NOTE_NODE_WIDTH: i32 = 168;
+const NOTE_NODE_HEIGHT: i32 = 40;
+const NOTE_LABEL_MAX_CHARS: usize = 24;
+
+/// A note in the notes directory; `id` is its file name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteNode {
+    pub id: String,
+    pub title: String,
+    pub tags: BTreeSet<String>,
+    /// File names of the notes linking here, by their `[[wikilinks]]`.
+    pub backlinks: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteEdgeKind {
+    /// `from` has a `[[wikilink]]` to `to`.
+    WikiLink,
+    /// Both notes carry these `#tags`; `from` sorts before `to`.
+    SharedTags(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: NoteEdgeKind,
+}
+
+/// Notes at the top level of a notes directory, linked by `[[wikilinks]]` and shared `#tags`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NotesGraph {
+    pub nodes: Vec<NoteNode>,
+    pub edges: Vec<NoteEdge>,
+}
+
+impl NotesGraph {
+    /// Reads the notes the notes tools see: regular `.md`, `.markdown`, and `.txt` files
+    /// directly in `notes_dir`. A missing directory is an empty graph.
+    pub fn build(notes_dir: &Path) -> Result<Self> {
+        let entries = match fs::read_dir(notes_dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to list {}", notes_dir.display()));
+            }
+        };
+        let mut notes = Vec::new();
+        for entry in entries {
+            let entry = entry.with_context(|| format!("failed to list {}", notes_dir.display()))?;
+            let path = entry.path();
+            if !is_searchable_note_extension(&path)
+                || !entry.file_type().is_ok_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+            let Some(id) = entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            let raw =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            notes.push((id, path, String::from_utf8_lossy(&raw).into_owned()));
+        }
+        notes.sort_by(|a, b| a.0.cmp(&b.0));
+        // Every note was just read, so the links come from that content rather than the
+        // stored index.
+        let index = BacklinksIndex::from_notes(
+            notes
+                .iter()
+                .map(|(id, _, content)| (id.as_str(), content.as_str())),
+        );
+        Ok(Self::from_notes(
+            notes.iter().map(|(id, path, content)| {
+                (
+                    id.clone(),
+                    extract_note_title(content, path),
+                    content.as_str(),
+                )
+            }),
+            &index,
+        ))
+    }
+
+    fn from_notes<'a>(
+        notes: impl IntoIterator<Item = (String, String, &'a str)>,
+        index: &BacklinksIndex,
+    ) -> Self {
+        let mut nodes = notes
+            .into_iter()
+            .map(|(id, title, content)| NoteNode {
+                id,
+                title,
+                tags: parse_tags(content),
+                backlinks: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let mut backlinks = index.backlinks(
+            &nodes
+                .iter()
+                .map(|node| (node.id.clone(), node.title.clone()))
+                .collect::<Vec<_>>(),
+        );
+        for node in &mut nodes {
+            node.backlinks = backlinks.remove(&node.id).unwrap_or_default();
+        }
+
+        let mut edges = nodes
+            .iter()
+            .flat_map(|node| {
+                node.backlinks.iter().map(|source| NoteEdge {
+                    from: source.clone(),
+                    to: node.id.clone(),
+                    kind: NoteEdgeKind::WikiLink,
+                })
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+
+        let mut shared_tags = BTreeMap::<(&str, &str), Vec<String>>::new();
+        for (index, node) in nodes.iter().enumerate() {
+            for other in &nodes[index + 1..] {
+                let tags = node
+                    .tags
+                    .intersection(&other.tags)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !tags.is_empty() {
+                    shared_tags.insert((node.id.as_str(), other.id.as_str()), tags);
+                }
+            }
+        }
+        edges.extend(shared_tags.into_iter().map(|((from, to), tags)| NoteEdge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            kind: NoteEdgeKind::SharedTags(tags),
+        }));
+
+        Self { nodes, edges }
+    }
+}
+
+/// Lowercase `#tags` that start a word and contain a letter, so `# Heading` and `#42` are
+/// not tags.
+fn parse_tags(content: &str) -> BTreeSet<String> {
+    let mut tags = BTreeSet::new();
+    for word in content.split(|ch: char| ch.is_whitespace() || ch == '(' || ch == ',') {
+        let Some(rest) = word.strip_prefix('#') else {
+            continue;
+        };
+        let tag = rest
+            .split(|ch: char| !(ch.is_alphanumeric() || matches!(ch, '_' | '-' | '/')))
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(['-', '/']);
+        if tag.chars().any(char::is_alphabetic) {
+            tags.insert(tag.to_lowercase());
+        }
+    }
+    tags
+}
+
+/// File names of the notes in `notes_dir` that `tool_calls` saved, read, deleted, or found.
+pub fn touched_note_ids(tool_calls: &[ExecutedToolCall], notes_dir: &Path) -> Vec<String> {
+    let mut touched = BTreeSet::new();
+    for call in tool_calls {
+        if !matches!(
+            call.tool_name.as_str(),
+            SAVE_NOTE_TOOL_NAME
+                | READ_NOTE_TOOL_NAME
+                | DELETE_NOTE_TOOL_NAME
+                | SEARCH_NOTES_TOOL_NAME
+        ) {
+            continue;
+        }
+        let Ok(payload) = serde_json::from_str::<Value>(&call.output) else {
+            continue;
+        };
+        let results = payload
+            .get("results")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for path in std::iter::once(&payload)
+            .chain(results)
+            .filter_map(|value| value.get("path").and_then(Value::as_str))
+        {
+            let path = Path::new(path);
+            if path.parent() == Some(notes_dir)
+                && let Some(name) = path.file_name().and_then(|name| name.to_str())
+            {
+                touched.insert(name.to_owned());
+            }
+        }
+    }
+    touched.into_iter().collect()
+}
+
+pub struct NotesGraphRenderInput<'a> {
+    pub graph: &'a NotesGraph,
+    /// Notes drawn in the highlight style, normally those touched by the latest turn.
+    pub highlighted_target_ids: &'a [String],
+    pub sequence: u64,
+}
+
+pub struct NotesGraphRenderer;
+
+impl NotesGraphRenderer {
+    /// Lays the notes out on a circle so every link stays visible; wikilinks are drawn
+    /// stronger than shared-tag edges, and each label carries the note's backlink count.
+    pub fn render(input: NotesGraphRenderInput<'_>) -> CanvasDrawCommandBatch {
+        let highlighted = input
+            .highlighted_target_ids
+            .iter()
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+        let node_count = input.graph.nodes.len();
+        let mut commands = vec![
+            CanvasDrawCommand::ClearScene,
+            CanvasDrawCommand::UpsertShape {
+                shape: CanvasShapeObject {
+                    id: "notes:header".to_owned(),
+                    layer: 10,
+                    kind: CanvasShapeKind::Text,
+                    points: vec![CanvasPoint { x: 24, y: 16 }],
+                    text: Some(notes_header(node_count, highlighted.len())),
+                    style: CanvasStyle {
+                        fill_color: None,
+                        stroke_color: None,
+                        stroke_width_px: None,
+                        text_color: Some("#38506b".to_owned()),
+                    },
+                },
+            },
+        ];
+
+        let radius = if node_count <= 1 {
+            0.0
+        } else {
+            (node_count as f32 * (NOTE_NODE_WIDTH as f32 + 24.0) / TAU).max(140.0)
+        };
+        let center_x = radius as i32 + NOTE_NODE_WIDTH / 2 + 48;
+        let center_y = radius as i32 + NOTE_NODE_HEIGHT / 2 + 64;
+        for (index, node) in input.graph.nodes.iter().enumerate() {
+            let angle = TAU * index as f32 / node_count as f32 - TAU / 4.0;
+            let x = center_x + (radius * angle.cos()) as i32 - NOTE_NODE_WIDTH / 2;
+            let y = center_y + (radius * angle.sin()) as i32 - NOTE_NODE_HEIGHT / 2;
+            let (fill_color, stroke_color) = if highlighted.contains(node.id.as_str()) {
+                ("#dc7e35", "#88451b")
+            } else {
+                ("#5e7fa8", "#3a5677")
+            };
+            commands.push(CanvasDrawCommand::UpsertShape {
+                shape: CanvasShapeObject {
+                    id: format!("node:{}", node.id),
+                    layer: 40,
+                    kind: CanvasShapeKind::Rectangle,
+                    points: vec![
+                        CanvasPoint { x, y },
+                        CanvasPoint {
+                            x: x + NOTE_NODE_WIDTH,
+                            y: y + NOTE_NODE_HEIGHT,
+                        },
+                    ],
+                    text: Some(note_label(node)),
+                    style: CanvasStyle {
+                        fill_color: Some(fill_color.to_owned()),
+                        stroke_color: Some(stroke_color.to_owned()),
+                        stroke_width_px: Some(2),
+                        text_color: Some("#ffffff".to_owned()),
+                    },
+                },
+            });
+        }
+
+        for edge in &input.graph.edges {
+            let (id, label, stroke_color, stroke_width_px) = match &edge.kind {
+                NoteEdgeKind::WikiLink => (
+                    format!("edge:link:{}->{}", edge.from, edge.to),
+                    None,
+                    "#3e6f9a",
+                    2,
+                ),
+                NoteEdgeKind::SharedTags(tags) => (
+                    format!("edge:tags:{}|{}", edge.from, edge.to),
+                    Some(
+                        tags.iter()
+                            .map(|tag| format!("#{tag}"))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    "#a7b8ca",
+                    1,
+                ),
+            };
+            commands.push(CanvasDrawCommand::UpsertConnector {
+                connector: CanvasConnectorObject {
+                    id,
+                    from_id: format!("node:{}", edge.from),
+                    to_id: format!("node:{}", edge.to),
+                    label,
+                    style: CanvasStyle {
+                        fill_color: None,
+                        stroke_color: Some(stroke_color.to_owned()),
+                        stroke_width_px: Some(stroke_width_px),
+                        text_color: None,
+                    },
+                },
+            });
+        }
+
+        CanvasDrawCommandBatch {
+            sequence: input.sequence,
+            commands,
+        }
+    }
+}
+
+/// The note's title, followed by its backlink count when other notes link to it.
+fn note_label(node: &NoteNode) -> String {
+    let title = truncate_graphemes(&node.title, NOTE_LABEL_MAX_CHARS, "…");
+    match node.backlinks.len() {
+        0 => title,
+        count => format!("{title} ←{count}"),
+    }
+}
+
+fn notes_header(node_count: usize, touched_count: usize) -> String {
+    match (node_count, touched_count) {
+        (0, _) => "No notes yet".to_owned(),
+        (_, 0) => format!("Notes · {node_count}"),
+        _ => format!("Notes · {node_count} · {touched_count} touched by the last turn"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use super::{
+        NoteEdge, NoteEdgeKind, NotesGraph, NotesGraphRenderInput, NotesGraphRenderer, parse_tags,
+        touched_note_ids,
+    };
+    use crate::agent::ExecutedToolCall;
+    use crate::studio::events::CanvasDrawCommand;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+    use crate::tools::BacklinksIndex;
+
+    #[test]
+    fn tags_are_parsed_from_note_bodies() {
+        assert_eq!(
+            parse_tags("# Heading\n#Rust and #async-io, (#rust) #42 issue#7 ## Sub"),
+            ["async-io", "rust"].map(str::to_owned).into()
+        );
+    }
+
+    #[test]
+    fn notes_graph_links_wikilinks_and_shared_tags() {
+        let notes_dir = temp_path("notes-graph");
+        fs::create_dir_all(notes_dir.join("nested")).expect("notes dir");
+        fs::write(
+            notes_dir.join("rust-tips.md"),
+            "# Rust Tips\n#rust #async\nSee [[Daily Log]] and [[missing]] and [[rust-tips]].",
+        )
+        .expect("rust tips");
+        fs::write(notes_dir.join("daily-log.md"), "# Daily Log\n#rust\n").expect("daily log");
+        fs::write(notes_dir.join("todo.txt"), "[[rust-tips.md]] #async").expect("todo");
+        fs::write(notes_dir.join("image.png"), "png").expect("image");
+        fs::write(notes_dir.join("nested/deep.md"), "#rust").expect("nested");
+
+        let graph = NotesGraph::build(&notes_dir).expect("build notes graph");
+        remove_dir_if_exists(&notes_dir);
+
+        let ids = graph
+            .nodes
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["daily-log.md", "rust-tips.md", "todo.txt"]);
+        assert_eq!(graph.nodes[1].title, "Rust Tips");
+        assert_eq!(graph.nodes[0].backlinks, ["rust-tips.md"]);
+        assert_eq!(graph.nodes[1].backlinks, ["todo.txt"]);
+        let edge = |from: &str, to: &str, kind| NoteEdge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            kind,
+        };
+        assert_eq!(
+            graph.edges,
+            [
+                edge("rust-tips.md", "daily-log.md", NoteEdgeKind::WikiLink),
+                edge("todo.txt", "rust-tips.md", NoteEdgeKind::WikiLink),
+                edge(
+                    "daily-log.md",
+                    "rust-tips.md",
+                    NoteEdgeKind::SharedTags(vec!["rust".to_owned()])
+                ),
+                edge(
+                    "rust-tips.md",
+                    "todo.txt",
+                    NoteEdgeKind::SharedTags(vec!["async".to_owned()])
+                ),
+            ]
+        );
+        assert_eq!(
+            NotesGraph::build(&notes_dir).expect("missing dir"),
+            NotesGraph::default()
+        );
+    }
+
+    #[test]
+    fn touched_notes_come_from_notes_tool_outputs_in_the_notes_dir() {
+        let call = |tool_name: &str, output: &str| ExecutedToolCall {
+            tool_name: tool_name.to_owned(),
+            arguments: serde_json::Value::Null,
+            output: output.to_owned(),
+            latency: Duration::ZERO,
+            attempts: 1,
+            error: None,
+        };
+        let calls = [
+            call("save_note", r#"{"title":"a","path":"notes/a.md"}"#),
+            call(
+                "search_notes",
+                r#"{"results":[{"path":"notes/b.md"},{"path":"elsewhere/c.md"}]}"#,
+            ),
+            call("read_workspace_file", r#"{"path":"notes/d.md"}"#),
+            call("read_note", "not json"),
+        ];
+        assert_eq!(
+            touched_note_ids(&calls, Path::new("notes")),
+            ["a.md", "b.md"]
+        );
+    }
+
+    #[test]
+    fn renderer_highlights_touched_notes_and_connects_edges() {
+        let index = BacklinksIndex::from_notes([("a.md", "[[b]]")]);
+        let graph = NotesGraph::from_notes(
+            [
+                ("a.md".to_owned(), "A".to_owned(), "[[b]] #x"),
+                ("b.md".to_owned(), "B".to_owned(), "#x"),
+            ],
+            &index,
+        );
+        let batch = NotesGraphRenderer::render(NotesGraphRenderInput {
+            graph: &graph,
+            highlighted_target_ids: &["b.md".to_owned()],
+            sequence: 3,
+        });
+        assert_eq!(batch.sequence, 3);
+
+        let fill_of = |id: &str| {
+            batch.commands.iter().find_map(|command| match command {
+                CanvasDrawCommand::UpsertShape { shape } if shape.id == id => {
+                    shape.style.fill_color.clone()
+                }
+                _ => None,
+            })
+        };
+        assert_eq!(fill_of("node:a.md").as_deref(), Some("#5e7fa8"));
+        assert_eq!(fill_of("node:b.md").as_deref(), Some("#dc7e35"));
+        let label_of = |id: &str| {
+            batch.commands.iter().find_map(|command| match command {
+                CanvasDrawCommand::UpsertShape { shape } if shape.id == id => shape.text.clone(),
+                _ => None,
+            })
+        };
+        assert_eq!(label_of("node:a.md").as_deref(), Some("A"));
+        assert_eq!(label_of("node:b.md").as_deref(), Some("B ←1"));
+        let connectors = batch
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                CanvasDrawCommand::UpsertConnector { connector } => {
+                    Some((connector.from_id.as_str(), connector.label.as_deref()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(connectors, [("node:a.md", None), ("node:a.md", Some("#x"))]);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+/// Frames kept for the overlay's average and maximum.
+const FRAME_HISTORY: usize = 120;
+
+/// Counters gathered during one `StudioApp::update` call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct FrameSample {
+    /// Time spent inside `update`, excluding the overlay itself.
+    pub update_time: Duration,
+    /// Wall-clock gap since the previous frame started; `None` for the first frame.
+    pub frame_interval: Option<Duration>,
+    pub events_drained: usize,
+    pub event_queue_depth: usize,
+    pub graph_updates_drained: usize,
+    pub graph_update_queue_depth: usize,
+    pub graph_nodes: usize,
+    pub graph_edges: usize,
+    pub draw_commands: u64,
+    pub scene_objects: usize,
+}
+
+/// Frame-time profiling overlay toggled with `F12` or started by
+/// `STUDIO_PROFILE_OVERLAY=true`. Samples are only kept while it is visible.
+#[derive(Debug, Default)]
+pub(super) struct FrameProfiler {
+    visible: bool,
+    samples: VecDeque<FrameSample>,
+    last_frame_start: Option<Instant>,
+}
+
+impl FrameProfiler {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.samples.clear();
+        self.last_frame_start = None;
+    }
+
+    /// Marks the start of a frame and returns the gap since the previous one.
+    pub fn begin_frame(&mut self, now: Instant) -> Option<Duration> {
+        let interval = self
+            .last_frame_start
+            .map(|previous| now.saturating_duration_since(previous));
+        self.last_frame_start = Some(now);
+        interval
+    }
+
+    pub fn record(&mut self, sample: FrameSample) {
+        if !self.visible {
+            return;
+        }
+        if self.samples.len() == FRAME_HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn overlay_lines(&self) -> Vec<String> {
+        let Some(latest) = self.samples.back() else {
+            return vec!["profiling: waiting for a frame".to_owned()];
+        };
+        let update_times = self.samples.iter().map(|sample| sample.update_time);
+        let intervals = self
+            .samples
+            .iter()
+            .filter_map(|sample| sample.frame_interval)
+            .collect::<Vec<_>>();
+        let mut lines = vec![format!(
+            "update {} · avg {} · max {} ({} frames)",
+            millis(latest.update_time),
+            millis(average(update_times.clone())),
+            millis(update_times.max().unwrap_or_default()),
+            self.samples.len()
+        )];
+        if let Some(interval) = latest.frame_interval {
+            lines.push(format!(
+                "frame interval {} · avg {}",
+                millis(interval),
+                millis(average(intervals.into_iter()))
+            ));
+        }
+        lines.push(format!(
+            "events {} queued · {} drained",
+            latest.event_queue_depth, latest.events_drained
+        ));
+        lines.push(format!(
+            "graph updates {} queued · {} drained",
+            latest.graph_update_queue_depth, latest.graph_updates_drained
+        ));
+        lines.push(format!(
+            "graph {} nodes · {} edges",
+            latest.graph_nodes, latest.graph_edges
+        ));
+        lines.push(format!(
+            "draw {} commands · {} scene objects",
+            latest.draw_commands, latest.scene_objects
+        ));
+        lines
+    }
+
+    pub fn render(&self, ctx: &egui::Context) {
+        egui::Area::new(egui::Id::new("studio_profile_overlay"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::new()
+                    .fill(egui::Color32::from_black_alpha(200))
+                    .corner_radius(6.0)
+                    .inner_margin(egui::Margin::symmetric(8, 6))
+                    .show(ui, |ui| {
+                        for line in self.overlay_lines() {
+                            ui.label(
+                                egui::RichText::new(line)
+                                    .monospace()
+                                    .small()
+                                    .color(egui::Color32::from_gray(230)),
+                            );
+                        }
+                    });
+            });
+    }
+}
+
+fn average(durations: impl Iterator<Item = Duration>) -> Duration {
+    let (total, count) = durations.fold((Duration::ZERO, 0_u32), |(total, count), duration| {
+        (total + duration, count + 1)
+    });
+    total.checked_div(count).unwrap_or_default()
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{FRAME_HISTORY, FrameProfiler, FrameSample};
+
+    #[test]
+    fn overlay_reports_latest_counters_with_bounded_history() {
+        let mut hidden = FrameProfiler::new(false);
+        hidden.record(FrameSample::default());
+        hidden.toggle();
+        assert_eq!(hidden.overlay_lines(), ["profiling: waiting for a frame"]);
+
+        let mut profiler = FrameProfiler::new(true);
+        let start = Instant::now();
+        assert_eq!(profiler.begin_frame(start), None);
+        assert_eq!(
+            profiler.begin_frame(start + Duration::from_millis(16)),
+            Some(Duration::from_millis(16))
+        );
+        for millis in 1..=(FRAME_HISTORY as u64 + 10) {
+            profiler.record(FrameSample {
+                update_time: Duration::from_millis(millis % 4),
+                frame_interval: Some(Duration::from_millis(16)),
+                ..FrameSample::default()
+            });
+        }
+        profiler.record(FrameSample {
+            update_time: Duration::from_millis(9),
+            frame_interval: Some(Duration::from_millis(32)),
+            events_drained: 3,
+            event_queue_depth: 1,
+            graph_updates_drained: 2,
+            graph_update_queue_depth: 0,
+            graph_nodes: 412,
+            graph_edges: 988,
+            draw_commands: 24,
+            scene_objects: 1400,
+        });
+
+        let lines = profiler.overlay_lines();
+        assert!(
+            lines[0].starts_with("update 9.0 ms · avg ")
+                && lines[0].ends_with("max 9.0 ms (120 frames)"),
+            "{}",
+            lines[0]
+        );
+        assert_eq!(
+            &lines[1..],
+            [
+                "frame interval 32.0 ms · avg 16.1 ms",
+                "events 1 queued · 3 drained",
+                "graph updates 0 queued · 2 drained",
+                "graph 412 nodes · 988 edges",
+                "draw 24 commands · 1400 scene objects",
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::Url;
+use serde::Deserialize;
+use tokio::runtime::Handle;
+use tokio::time::interval;
+use tracing::debug;
+
+const REMOTE_MODEL_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const REMOTE_MODEL_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Provider and model a `--remote-graph` server is answering with, from its
+/// `GET /capabilities`. Tracks `POST /admin/model` swaps within one poll interval.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(super) struct RemoteActiveModel {
+    pub provider: String,
+    pub model: String,
+}
+
+/// Latest poll result; `None` until the first successful poll or after a failed one.
+#[derive(Debug, Default)]
+pub(super) struct RemoteModelStatus {
+    latest: Mutex<Option<RemoteActiveModel>>,
+}
+
+impl RemoteModelStatus {
+    pub fn latest(&self) -> Option<RemoteActiveModel> {
+        self.latest
+            .lock()
+            .expect("remote model lock poisoned")
+            .clone()
+    }
+
+    fn set(&self, model: Option<RemoteActiveModel>) {
+        *self.latest.lock().expect("remote model lock poisoned") = model;
+    }
+}
+
+pub(super) fn spawn_remote_model_poller(handle: &Handle, base_url: Url) -> Arc<RemoteModelStatus> {
+    let status = Arc::new(RemoteModelStatus::default());
+    let publisher = Arc::clone(&status);
+    let _task = handle.spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(REMOTE_MODEL_REQUEST_TIMEOUT)
+            .build()
+        {
+            Ok(client) => client,
+            Err(error) => {
+                debug!(error = %error, "failed to build remote model HTTP client");
+                return;
+            }
+        };
+        let mut ticker = interval(REMOTE_MODEL_POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match fetch_remote_active_model(&client, &base_url).await {
+                Ok(model) => publisher.set(Some(model)),
+                Err(error) => {
+                    debug!(error = %format!("{error:#}"), "failed to poll remote active model");
+                    publisher.set(None);
+                }
+            }
+        }
+    });
+    status
+}
+
+async fn fetch_remote_active_model(
+    client: &reqwest::Client,
+    base_url: &Url,
+) -> Result<RemoteActiveModel> {
+    let url = base_url
+        .join("capabilities")
+        .context("invalid remote capabilities endpoint")?;
+    client
+        .get(url)
+        .send()
+        .await
+        .context("remote capabilities request failed")?
+        .error_for_status()
+        .context("remote capabilities request was rejected")?
+        .json::<RemoteActiveModel>()
+        .await
+        .context("failed to decode remote capabilities")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Json;
+    use axum::Router;
+    use axum::routing::get;
+    use reqwest::Url;
+
+    use super::{RemoteActiveModel, fetch_remote_active_model};
+
+    #[tokio::test]
+    async fn fetch_reads_provider_and_model_from_capabilities() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let base_url = Url::parse(&format!(
+            "http://{}/",
+            listener.local_addr().expect("local addr")
+        ))
+        .expect("base url");
+        let app = Router::new().route(
+            "/capabilities",
+            get(|| async {
+                Json(serde_json::json!({
+                    "version": "0.1.0",
+                    "provider": "ollama",
+                    "model": "llama3",
+                    "tools": ["search_notes"],
+                }))
+            }),
+        );
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let model = fetch_remote_active_model(&reqwest::Client::new(), &base_url)
+            .await
+            .expect("capabilities should decode");
+
+        assert_eq!(
+            model,
+            RemoteActiveModel {
+                provider: "ollama".to_owned(),
+                model: "llama3".to_owned(),
+            }
+        );
+    }
+}
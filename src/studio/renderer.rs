@@ -2,9 +2,15 @@ use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::Path;
 
-use crate::graph::{ArchitectureGraph, ArchitectureNode, ArchitectureNodeKind};
+use crate::graph::rules::DependencyViolation;
+use crate::graph::{
+    ArchitectureGraph, ArchitectureNode, ArchitectureNodeKind, EXTRA_ROOT_MARKER,
+    split_extra_root_node_id,
+};
+use crate::text::{grapheme_count, truncate_graphemes};
 use anyhow::{Context, Result, ensure};
 use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
 
 use super::canvas::CanvasToolCard;
 use super::events::{
@@ -25,6 +31,8 @@ pub struct ArchitectureOverviewRenderInput<'a> {
     pub turn_in_flight: bool,
     pub canvas_status: &'a str,
     pub recent_activity: &'a [ArchitectureActivitySummary<'a>],
+    /// Dependency-rule violations; their edges and endpoint nodes are outlined in red.
+    pub dependency_violations: &'a [DependencyViolation],
     pub sequence: u64,
 }
 
@@ -36,6 +44,16 @@ pub struct ArchitectureActivitySummary<'a> {
 
 pub struct ArchitectureOverviewRenderer;
 
+/// Label prefix for `mod` declarations without a file.
+const UNRESOLVED_MODULE_BADGE: &str = "⚠";
+
+/// Stroke for edges and nodes that break a dependency rule.
+const VIOLATION_COLOR: &str = "#c62828";
+
+/// Root label for primary-workspace subsystems when the graph has extra roots.
+const PRIMARY_ROOT_KEY: &str = "workspace";
+const ROOT_GROUP_GAP: i32 = 60;
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SubsystemMapper {
     rules: Vec<SubsystemMappingRule>,
@@ -111,7 +129,25 @@ impl SubsystemMapper {
         Self::from_rules_json(&raw)
     }
 
+    /// Subsystem key for `node`. Nodes from an extra workspace root are matched by their
+    /// root-local id and get a root-qualified key such as `@shared/agent`.
     pub fn resolve_subsystem(&self, node: &ArchitectureNode) -> String {
+        let Some((root, local_id)) = split_extra_root_node_id(&node.id) else {
+            return self.resolve_local_subsystem(node);
+        };
+        let local_node = ArchitectureNode {
+            path: local_id.strip_prefix("file:").map(str::to_owned),
+            id: local_id,
+            display_label: node.display_label.clone(),
+            kind: node.kind,
+        };
+        format!(
+            "{EXTRA_ROOT_MARKER}{root}/{}",
+            self.resolve_local_subsystem(&local_node)
+        )
+    }
+
+    fn resolve_local_subsystem(&self, node: &ArchitectureNode) -> String {
         for rule in &self.rules {
             if rule.matches(node) {
                 return rule.subsystem.clone();
@@ -183,6 +219,15 @@ impl ArchitectureOverviewRenderer {
             .iter()
             .map(|node| node.id.as_str())
             .collect::<BTreeSet<_>>();
+        let violating_edges = input
+            .dependency_violations
+            .iter()
+            .map(|violation| (violation.from.as_str(), violation.to.as_str()))
+            .collect::<BTreeSet<_>>();
+        let violating_nodes = violating_edges
+            .iter()
+            .flat_map(|(from, to)| [*from, *to])
+            .collect::<BTreeSet<_>>();
         let added_count = current_node_ids.difference(&before_node_ids).count();
         let removed_count = before_node_ids.difference(&current_node_ids).count();
         let changed_count = changed
@@ -201,7 +246,9 @@ impl ArchitectureOverviewRenderer {
             node_subsystems.insert(node.id.as_str(), subsystem.clone());
             let bucket = subsystem_buckets.entry(subsystem).or_default();
             match node.kind {
-                ArchitectureNodeKind::Module => bucket.modules.push(node),
+                ArchitectureNodeKind::Module | ArchitectureNodeKind::UnresolvedModule => {
+                    bucket.modules.push(node)
+                }
                 ArchitectureNodeKind::File => bucket.files.push(node),
             }
         }
@@ -244,17 +291,102 @@ impl ArchitectureOverviewRenderer {
                 },
             });
         }
+        let unresolved_module_count = input
+            .graph
+            .nodes
+            .iter()
+            .filter(|node| node.kind == ArchitectureNodeKind::UnresolvedModule)
+            .count();
+        if unresolved_module_count > 0 {
+            commands.push(CanvasDrawCommand::UpsertShape {
+                shape: CanvasShapeObject {
+                    id: "overlay:unresolved-modules".to_owned(),
+                    layer: 7,
+                    kind: CanvasShapeKind::Text,
+                    points: vec![CanvasPoint { x: 600, y: 36 }],
+                    text: Some(format!(
+                        "{UNRESOLVED_MODULE_BADGE} {unresolved_module_count} unresolved mod declaration(s)"
+                    )),
+                    style: CanvasStyle {
+                        fill_color: None,
+                        stroke_color: None,
+                        stroke_width_px: None,
+                        text_color: Some("#9a6700".to_owned()),
+                    },
+                },
+            });
+        }
+        if !input.dependency_violations.is_empty() {
+            commands.push(CanvasDrawCommand::UpsertShape {
+                shape: CanvasShapeObject {
+                    id: "overlay:dependency-violations".to_owned(),
+                    layer: 7,
+                    kind: CanvasShapeKind::Text,
+                    points: vec![CanvasPoint { x: 360, y: 36 }],
+                    text: Some(format!(
+                        "{} dependency rule violation(s)",
+                        input.dependency_violations.len()
+                    )),
+                    style: CanvasStyle {
+                        fill_color: None,
+                        stroke_color: None,
+                        stroke_width_px: None,
+                        text_color: Some(VIOLATION_COLOR.to_owned()),
+                    },
+                },
+            });
+        }
+
+        // Primary-root subsystems first; with extra roots, each root's subsystems form one
+        // labelled group, separated by a wider gap.
+        let mut ordered_buckets = subsystem_buckets.iter().collect::<Vec<_>>();
+        ordered_buckets
+            .sort_by_key(|(subsystem, _)| (subsystem.starts_with(EXTRA_ROOT_MARKER), *subsystem));
+        let multi_root = ordered_buckets
+            .iter()
+            .any(|(subsystem, _)| subsystem_root(subsystem).is_some());
+        let mut root_groups: Vec<(String, Vec<String>)> = Vec::new();
 
         let mut subsystem_group_ids = Vec::new();
         let mut x_cursor = 92;
-        for (subsystem, bucket) in &subsystem_buckets {
+        for (subsystem, bucket) in ordered_buckets {
+            let (root_key, local_subsystem) = match subsystem_root(subsystem) {
+                Some((root, local)) => (root, local),
+                None => (PRIMARY_ROOT_KEY, subsystem.as_str()),
+            };
+            if multi_root
+                && root_groups
+                    .last()
+                    .is_none_or(|(current_root, _)| current_root != root_key)
+            {
+                if !root_groups.is_empty() {
+                    x_cursor += ROOT_GROUP_GAP;
+                }
+                commands.push(CanvasDrawCommand::UpsertShape {
+                    shape: CanvasShapeObject {
+                        id: format!("root-label:{root_key}"),
+                        layer: 6,
+                        kind: CanvasShapeKind::Text,
+                        points: vec![CanvasPoint { x: x_cursor, y: 16 }],
+                        text: Some(root_key.to_owned()),
+                        style: CanvasStyle {
+                            fill_color: None,
+                            stroke_color: None,
+                            stroke_width_px: None,
+                            text_color: Some("#1f4a6e".to_owned()),
+                        },
+                    },
+                });
+                root_groups.push((root_key.to_owned(), Vec::new()));
+            }
+
             commands.push(CanvasDrawCommand::UpsertShape {
                 shape: CanvasShapeObject {
                     id: format!("system-label:{subsystem}"),
                     layer: 6,
                     kind: CanvasShapeKind::Text,
                     points: vec![CanvasPoint { x: x_cursor, y: 62 }],
-                    text: Some(format!("{} system", clipped_system_label(subsystem))),
+                    text: Some(format!("{} system", clipped_system_label(local_subsystem))),
                     style: CanvasStyle {
                         fill_color: None,
                         stroke_color: None,
@@ -267,7 +399,7 @@ impl ArchitectureOverviewRenderer {
             let module_layout = layout_column(&bucket.modules, &node_labels, 104, x_cursor, 28);
             let mut module_shape_ids = Vec::new();
             for (node, x, y) in &module_layout {
-                let shape = build_node_shape(
+                let mut shape = build_node_shape(
                     node,
                     node_labels
                         .get(node.id.as_str())
@@ -278,6 +410,9 @@ impl ArchitectureOverviewRenderer {
                     node_delta_kind(node.id.as_str(), &before_node_ids, &changed, &impact),
                     input.show_focus_mode,
                 );
+                if violating_nodes.contains(node.id.as_str()) {
+                    highlight_violation(&mut shape);
+                }
                 fit_ids.push(shape.id.clone());
                 module_shape_ids.push(shape.id.clone());
                 commands.push(CanvasDrawCommand::UpsertShape { shape });
@@ -293,7 +428,7 @@ impl ArchitectureOverviewRenderer {
                 layout_column(&bucket.files, &node_labels, file_start_y, x_cursor, 22);
             let mut file_shape_ids = Vec::new();
             for (node, x, y) in &file_layout {
-                let shape = build_node_shape(
+                let mut shape = build_node_shape(
                     node,
                     node_labels
                         .get(node.id.as_str())
@@ -304,6 +439,9 @@ impl ArchitectureOverviewRenderer {
                     node_delta_kind(node.id.as_str(), &before_node_ids, &changed, &impact),
                     input.show_focus_mode,
                 );
+                if violating_nodes.contains(node.id.as_str()) {
+                    highlight_violation(&mut shape);
+                }
                 fit_ids.push(shape.id.clone());
                 file_shape_ids.push(shape.id.clone());
                 commands.push(CanvasDrawCommand::UpsertShape { shape });
@@ -312,6 +450,9 @@ impl ArchitectureOverviewRenderer {
             let mut object_ids = module_shape_ids;
             object_ids.extend(file_shape_ids);
             subsystem_group_ids.push(format!("group:system:{subsystem}"));
+            if let Some((_, root_subsystem_ids)) = root_groups.last_mut() {
+                root_subsystem_ids.push(format!("group:system:{subsystem}"));
+            }
             commands.push(CanvasDrawCommand::UpsertGroup {
                 group: CanvasGroupObject {
                     id: format!("group:system:{subsystem}"),
@@ -323,6 +464,21 @@ impl ArchitectureOverviewRenderer {
 
             x_cursor += node_shape_width() + 86;
         }
+        if multi_root {
+            subsystem_group_ids = Vec::with_capacity(root_groups.len());
+            for (root_key, object_ids) in root_groups {
+                let group_id = format!("group:root:{root_key}");
+                subsystem_group_ids.push(group_id.clone());
+                commands.push(CanvasDrawCommand::UpsertGroup {
+                    group: CanvasGroupObject {
+                        id: group_id,
+                        layer: 27,
+                        label: Some(format!("root:{root_key}")),
+                        object_ids,
+                    },
+                });
+            }
+        }
         commands.push(CanvasDrawCommand::UpsertGroup {
             group: CanvasGroupObject {
                 id: "group:systems".to_owned(),
@@ -365,43 +521,49 @@ impl ArchitectureOverviewRenderer {
                 .get(edge.from.as_str())
                 .zip(node_subsystems.get(edge.to.as_str()))
                 .is_some_and(|(from, to)| from == to);
-            let style =
-                if changed.contains(edge.from.as_str()) || changed.contains(edge.to.as_str()) {
-                    CanvasStyle {
-                        fill_color: None,
-                        stroke_color: Some("#cf6c2a".to_owned()),
-                        stroke_width_px: Some(2),
-                        text_color: None,
-                    }
-                } else if impact.contains(edge.from.as_str()) || impact.contains(edge.to.as_str()) {
-                    CanvasStyle {
-                        fill_color: None,
-                        stroke_color: Some("#3f89b2".to_owned()),
-                        stroke_width_px: Some(2),
-                        text_color: None,
-                    }
-                } else if input.show_focus_mode && !edge_has_focus_target {
-                    CanvasStyle {
-                        fill_color: None,
-                        stroke_color: Some("#d8e0ea".to_owned()),
-                        stroke_width_px: Some(1),
-                        text_color: None,
-                    }
-                } else if same_subsystem {
-                    CanvasStyle {
-                        fill_color: None,
-                        stroke_color: Some("#6f8da9".to_owned()),
-                        stroke_width_px: Some(1),
-                        text_color: None,
-                    }
-                } else {
-                    CanvasStyle {
-                        fill_color: None,
-                        stroke_color: Some("#b5c5d6".to_owned()),
-                        stroke_width_px: Some(1),
-                        text_color: None,
-                    }
-                };
+            let style = if violating_edges.contains(&(edge.from.as_str(), edge.to.as_str())) {
+                CanvasStyle {
+                    fill_color: None,
+                    stroke_color: Some(VIOLATION_COLOR.to_owned()),
+                    stroke_width_px: Some(3),
+                    text_color: None,
+                }
+            } else if changed.contains(edge.from.as_str()) || changed.contains(edge.to.as_str()) {
+                CanvasStyle {
+                    fill_color: None,
+                    stroke_color: Some("#cf6c2a".to_owned()),
+                    stroke_width_px: Some(2),
+                    text_color: None,
+                }
+            } else if impact.contains(edge.from.as_str()) || impact.contains(edge.to.as_str()) {
+                CanvasStyle {
+                    fill_color: None,
+                    stroke_color: Some("#3f89b2".to_owned()),
+                    stroke_width_px: Some(2),
+                    text_color: None,
+                }
+            } else if input.show_focus_mode && !edge_has_focus_target {
+                CanvasStyle {
+                    fill_color: None,
+                    stroke_color: Some("#d8e0ea".to_owned()),
+                    stroke_width_px: Some(1),
+                    text_color: None,
+                }
+            } else if same_subsystem {
+                CanvasStyle {
+                    fill_color: None,
+                    stroke_color: Some("#6f8da9".to_owned()),
+                    stroke_width_px: Some(1),
+                    text_color: None,
+                }
+            } else {
+                CanvasStyle {
+                    fill_color: None,
+                    stroke_color: Some("#b5c5d6".to_owned()),
+                    stroke_width_px: Some(1),
+                    text_color: None,
+                }
+            };
 
             commands.push(CanvasDrawCommand::UpsertConnector {
                 connector: super::events::CanvasConnectorObject {
@@ -444,15 +606,21 @@ fn build_node_shape(
     delta_kind: NodeDeltaKind,
     show_focus_mode: bool,
 ) -> CanvasShapeObject {
-    let (fill_color, stroke_color, text_color) = match delta_kind {
-        NodeDeltaKind::Added => ("#3aa66a", "#1f6642", "#ffffff"),
-        NodeDeltaKind::Changed => ("#dc7e35", "#88451b", "#ffffff"),
-        NodeDeltaKind::Impact => ("#4f98bf", "#2d6687", "#ffffff"),
-        NodeDeltaKind::Unchanged if show_focus_mode => ("#d9e2ec", "#b5c4d3", "#536577"),
-        NodeDeltaKind::Unchanged => match node.kind {
-            ArchitectureNodeKind::Module => ("#3e7faa", "#22577a", "#ffffff"),
-            ArchitectureNodeKind::File => ("#4e9164", "#2f6543", "#ffffff"),
-        },
+    // Unresolved declarations keep their warning style in every overlay mode.
+    let (fill_color, stroke_color, text_color) = match (delta_kind, node.kind) {
+        (_, ArchitectureNodeKind::UnresolvedModule) => ("#f2c14e", "#9a6700", "#3d2b00"),
+        (NodeDeltaKind::Added, _) => ("#3aa66a", "#1f6642", "#ffffff"),
+        (NodeDeltaKind::Changed, _) => ("#dc7e35", "#88451b", "#ffffff"),
+        (NodeDeltaKind::Impact, _) => ("#4f98bf", "#2d6687", "#ffffff"),
+        (NodeDeltaKind::Unchanged, _) if show_focus_mode => ("#d9e2ec", "#b5c4d3", "#536577"),
+        (NodeDeltaKind::Unchanged, ArchitectureNodeKind::Module) => {
+            ("#3e7faa", "#22577a", "#ffffff")
+        }
+        (NodeDeltaKind::Unchanged, ArchitectureNodeKind::File) => ("#4e9164", "#2f6543", "#ffffff"),
+    };
+    let text = match node.kind {
+        ArchitectureNodeKind::UnresolvedModule => format!("{UNRESOLVED_MODULE_BADGE} {label}"),
+        ArchitectureNodeKind::Module | ArchitectureNodeKind::File => label.to_owned(),
     };
 
     let width = node_shape_width();
@@ -461,7 +629,7 @@ fn build_node_shape(
     CanvasShapeObject {
         id: format!("node:{}", node.id),
         layer: match node.kind {
-            ArchitectureNodeKind::Module => 40,
+            ArchitectureNodeKind::Module | ArchitectureNodeKind::UnresolvedModule => 40,
             ArchitectureNodeKind::File => 60,
         },
         kind: CanvasShapeKind::Rectangle,
@@ -472,7 +640,7 @@ fn build_node_shape(
                 y: y + height,
             },
         ],
-        text: Some(label.to_owned()),
+        text: Some(text),
         style: CanvasStyle {
             fill_color: Some(fill_color.to_owned()),
             stroke_color: Some(stroke_color.to_owned()),
@@ -482,6 +650,11 @@ fn build_node_shape(
     }
 }
 
+fn highlight_violation(shape: &mut CanvasShapeObject) {
+    shape.style.stroke_color = Some(VIOLATION_COLOR.to_owned());
+    shape.style.stroke_width_px = Some(3);
+}
+
 fn node_delta_kind<'a>(
     node_id: &'a str,
     before_node_ids: &BTreeSet<&'a str>,
@@ -537,7 +710,7 @@ fn layout_column<'a>(
 }
 
 fn module_path_for_matching(node: &ArchitectureNode) -> Option<&str> {
-    if node.kind != ArchitectureNodeKind::Module {
+    if node.kind == ArchitectureNodeKind::File {
         return None;
     }
     Some(
@@ -563,7 +736,7 @@ fn file_path_for_matching(node: &ArchitectureNode) -> Option<&str> {
 
 fn default_subsystem_key(node: &ArchitectureNode) -> String {
     match node.kind {
-        ArchitectureNodeKind::Module => {
+        ArchitectureNodeKind::Module | ArchitectureNodeKind::UnresolvedModule => {
             let raw = node.id.strip_prefix("module:").unwrap_or(node.id.as_str());
             let parts = raw.split("::").collect::<Vec<_>>();
             if parts.first() == Some(&"crate") && parts.len() >= 2 {
@@ -596,6 +769,14 @@ fn default_subsystem_key(node: &ArchitectureNode) -> String {
     }
 }
 
+/// Splits a root-qualified subsystem key (`@shared/agent`) into `("@shared", "agent")`.
+fn subsystem_root(subsystem: &str) -> Option<(&str, &str)> {
+    if !subsystem.starts_with(EXTRA_ROOT_MARKER) {
+        return None;
+    }
+    subsystem.split_once('/')
+}
+
 fn normalize_module_prefix(prefix: String) -> String {
     prefix
         .strip_prefix("module:")
@@ -629,13 +810,7 @@ fn clipped_system_label(label: &str) -> String {
     if trimmed.is_empty() {
         return "root".to_owned();
     }
-    if trimmed.chars().count() <= 16 {
-        trimmed.to_owned()
-    } else {
-        let mut short = trimmed.chars().take(13).collect::<String>();
-        short.push_str("...");
-        short
-    }
+    truncate_graphemes(trimmed, 16, "...")
 }
 
 fn build_semantic_node_labels(nodes: &[ArchitectureNode]) -> HashMap<&str, String> {
@@ -712,7 +887,7 @@ fn wrap_identifier_lines(text: &str, max_chars_per_line: usize) -> Vec<String> {
             format!("::{part}")
         };
 
-        if current.chars().count() + segment.chars().count() <= max_chars_per_line {
+        if grapheme_count(&current) + grapheme_count(&segment) <= max_chars_per_line {
             current.push_str(&segment);
             continue;
         }
@@ -722,12 +897,12 @@ fn wrap_identifier_lines(text: &str, max_chars_per_line: usize) -> Vec<String> {
             current = String::new();
         }
 
-        if part.chars().count() <= max_chars_per_line {
+        if grapheme_count(part) <= max_chars_per_line {
             current.push_str(part);
         } else {
-            let chunks = part.chars().collect::<Vec<_>>();
-            for chunk in chunks.chunks(max_chars_per_line) {
-                lines.push(chunk.iter().collect::<String>());
+            let graphemes = part.graphemes(true).collect::<Vec<_>>();
+            for chunk in graphemes.chunks(max_chars_per_line) {
+                lines.push(chunk.concat());
             }
         }
     }
@@ -745,6 +920,7 @@ fn wrap_identifier_lines(text: &str, max_chars_per_line: usize) -> Vec<String> {
 mod tests {
     use std::time::UNIX_EPOCH;
 
+    use crate::graph::rules::DependencyViolation;
     use crate::graph::{
         ArchitectureEdge, ArchitectureEdgeKind, ArchitectureGraph, ArchitectureNode,
         ArchitectureNodeKind,
@@ -762,7 +938,10 @@ mod tests {
         let cards = vec![CanvasToolCard {
             id: "1".to_owned(),
             title: "search_notes".to_owned(),
+            meta: "12ms".to_owned(),
             body: "found 3".to_owned(),
+            citation: None,
+            progress_percent: None,
         }];
         let mapper = SubsystemMapper::default();
 
@@ -779,6 +958,7 @@ mod tests {
             turn_in_flight: false,
             canvas_status: "Idle",
             recent_activity: &[],
+            dependency_violations: &[],
             sequence: 10,
         });
         let two = ArchitectureOverviewRenderer::render(ArchitectureOverviewRenderInput {
@@ -794,6 +974,7 @@ mod tests {
             turn_in_flight: false,
             canvas_status: "Idle",
             recent_activity: &[],
+            dependency_violations: &[],
             sequence: 10,
         });
 
@@ -817,6 +998,7 @@ mod tests {
             turn_in_flight: false,
             canvas_status: "Idle",
             recent_activity: &[],
+            dependency_violations: &[],
             sequence: 1,
         });
 
@@ -835,6 +1017,105 @@ mod tests {
         assert_eq!(changed_shape.style.fill_color.as_deref(), Some("#dc7e35"));
     }
 
+    #[test]
+    fn architecture_renderer_outlines_dependency_violations_in_red() {
+        let graph = graph_fixture();
+        let mapper = SubsystemMapper::default();
+        let violations = [DependencyViolation {
+            from: "module:crate".to_owned(),
+            to: "module:crate::tools".to_owned(),
+            relation: ArchitectureEdgeKind::Uses,
+            rule: "module:crate must not depend on module:crate::tools".to_owned(),
+            reason: None,
+        }];
+        let batch = ArchitectureOverviewRenderer::render(ArchitectureOverviewRenderInput {
+            graph: &graph,
+            subsystem_mapper: &mapper,
+            changed_target_ids: &["module:crate::tools".to_owned()],
+            impact_target_ids: &[],
+            show_impact_overlay: false,
+            before_graph: None,
+            show_before_after_overlay: false,
+            show_focus_mode: false,
+            tool_cards: &[],
+            turn_in_flight: false,
+            canvas_status: "Idle",
+            recent_activity: &[],
+            dependency_violations: &violations,
+            sequence: 1,
+        });
+
+        let stroke_of = |id: &str| {
+            batch.commands.iter().find_map(|command| match command {
+                super::CanvasDrawCommand::UpsertShape { shape } if shape.id == id => {
+                    shape.style.stroke_color.clone()
+                }
+                super::CanvasDrawCommand::UpsertConnector { connector } if connector.id == id => {
+                    connector.style.stroke_color.clone()
+                }
+                _ => None,
+            })
+        };
+        assert_eq!(
+            stroke_of("edge:module:crate->module:crate::tools").as_deref(),
+            Some("#c62828")
+        );
+        assert_eq!(
+            stroke_of("node:module:crate::tools").as_deref(),
+            Some("#c62828")
+        );
+        assert_eq!(
+            stroke_of("node:file:src/tools.rs").as_deref(),
+            Some("#2f6543")
+        );
+        assert!(batch.commands.iter().any(|command| matches!(
+            command,
+            super::CanvasDrawCommand::UpsertShape { shape }
+                if shape.id == "overlay:dependency-violations"
+        )));
+    }
+
+    #[test]
+    fn architecture_renderer_badges_unresolved_modules_as_warnings() {
+        let mut graph = graph_fixture();
+        graph.nodes[1].kind = ArchitectureNodeKind::UnresolvedModule;
+        let mapper = SubsystemMapper::default();
+        let batch = ArchitectureOverviewRenderer::render(ArchitectureOverviewRenderInput {
+            graph: &graph,
+            subsystem_mapper: &mapper,
+            changed_target_ids: &["module:crate::tools".to_owned()],
+            impact_target_ids: &[],
+            show_impact_overlay: false,
+            before_graph: None,
+            show_before_after_overlay: false,
+            show_focus_mode: false,
+            tool_cards: &[],
+            turn_in_flight: false,
+            canvas_status: "Idle",
+            recent_activity: &[],
+            dependency_violations: &[],
+            sequence: 1,
+        });
+
+        let shape = |id: &str| {
+            batch.commands.iter().find_map(|command| match command {
+                super::CanvasDrawCommand::UpsertShape { shape } if shape.id == id => Some(shape),
+                _ => None,
+            })
+        };
+        let node = shape("node:module:crate::tools").expect("unresolved node should render");
+        assert_eq!(node.style.fill_color.as_deref(), Some("#f2c14e"));
+        assert!(
+            node.text
+                .as_deref()
+                .is_some_and(|text| text.starts_with("⚠ "))
+        );
+        assert_eq!(
+            shape("overlay:unresolved-modules").and_then(|overlay| overlay.text.as_deref()),
+            Some("⚠ 1 unresolved mod declaration(s)")
+        );
+    }
+
     #[test]
     fn architecture_renderer_places_files_below_module_block() {
         let graph = ArchitectureGraph {
@@ -895,6 +1176,7 @@ mod tests {
             turn_in_flight: false,
             canvas_status: "Idle",
             recent_activity: &[],
+            dependency_violations: &[],
             sequence: 1,
         });
 
@@ -952,6 +1234,7 @@ mod tests {
             turn_in_flight: true,
             canvas_status: "Running turn for: inspect parser",
             recent_activity: &activity,
+            dependency_violations: &[],
             sequence: 3,
         });
 
@@ -1013,6 +1296,7 @@ mod tests {
             turn_in_flight: false,
             canvas_status: "Idle",
             recent_activity: &[],
+            dependency_violations: &[],
             sequence: 2,
         });
 
@@ -1071,6 +1355,7 @@ mod tests {
             turn_in_flight: false,
             canvas_status: "Idle",
             recent_activity: &[],
+            dependency_violations: &[],
             sequence: 4,
         });
 
@@ -1170,6 +1455,7 @@ mod tests {
             turn_in_flight: false,
             canvas_status: "Idle",
             recent_activity: &[],
+            dependency_violations: &[],
             sequence: 5,
         });
 
@@ -1182,6 +1468,85 @@ mod tests {
         assert!(has_runtime_group);
     }
 
+    #[test]
+    fn extra_root_subsystems_render_under_their_own_root_group() {
+        let mut graph = graph_fixture();
+        graph.nodes.extend([
+            ArchitectureNode {
+                id: "module:@shared::crate::codec".to_owned(),
+                display_label: "crate::codec".to_owned(),
+                kind: ArchitectureNodeKind::Module,
+                path: Some("../shared/src/codec.rs".to_owned()),
+            },
+            ArchitectureNode {
+                id: "file:@shared/src/codec.rs".to_owned(),
+                display_label: "codec.rs".to_owned(),
+                kind: ArchitectureNodeKind::File,
+                path: Some("../shared/src/codec.rs".to_owned()),
+            },
+        ]);
+        let mapper = SubsystemMapper::from_rules_json(
+            r#"{ "rules": [ { "subsystem": "wire", "module_prefix": "crate::codec" } ] }"#,
+        )
+        .expect("valid rules should parse");
+        assert_eq!(mapper.resolve_subsystem(&graph.nodes[3]), "@shared/wire");
+        assert_eq!(mapper.resolve_subsystem(&graph.nodes[4]), "@shared/root");
+        assert_eq!(mapper.resolve_subsystem(&graph.nodes[1]), "tools");
+
+        let batch = ArchitectureOverviewRenderer::render(ArchitectureOverviewRenderInput {
+            graph: &graph,
+            subsystem_mapper: &mapper,
+            changed_target_ids: &[],
+            impact_target_ids: &[],
+            show_impact_overlay: false,
+            before_graph: None,
+            show_before_after_overlay: false,
+            show_focus_mode: false,
+            tool_cards: &[],
+            turn_in_flight: false,
+            canvas_status: "Idle",
+            recent_activity: &[],
+            dependency_violations: &[],
+            sequence: 6,
+        });
+
+        let groups = batch
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                super::CanvasDrawCommand::UpsertGroup { group } => {
+                    Some((group.id.as_str(), group.object_ids.clone()))
+                }
+                _ => None,
+            })
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(
+            groups["group:systems"],
+            ["group:root:workspace", "group:root:@shared"]
+        );
+        assert_eq!(
+            groups["group:root:workspace"],
+            [
+                "group:system:crate",
+                "group:system:root",
+                "group:system:tools"
+            ]
+        );
+        assert_eq!(
+            groups["group:root:@shared"],
+            ["group:system:@shared/root", "group:system:@shared/wire"]
+        );
+        let label_x = |id: &str| {
+            batch.commands.iter().find_map(|command| match command {
+                super::CanvasDrawCommand::UpsertShape { shape } if shape.id == id => {
+                    Some(shape.points[0].x)
+                }
+                _ => None,
+            })
+        };
+        assert!(label_x("root-label:@shared") > label_x("system-label:tools"));
+    }
+
     #[test]
     fn subsystem_mapper_rejects_unknown_fields() {
         let error = SubsystemMapper::from_rules_json(
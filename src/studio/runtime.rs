@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{Sender, UnboundedReceiver};
+use tokio::task::{AbortHandle, JoinSet};
+use tracing::warn;
+
+use crate::agent::{
+    ChatSession, ChatTurnError, ChatTurnOutcome, HistorySource, TurnEventSink, TurnHistory,
+};
+use crate::config::AgentSettings;
+use crate::graph::watch::GraphWatchHandle;
+use crate::http::SharedHttpClient;
+use crate::model::client::ModelClient;
+
+use super::describe::generate_subsystem_summary;
+use super::events::{StudioCommand, StudioEvent, StudioTurnResult};
+
+#[derive(Debug)]
+struct QueuedTurn {
+    turn_id: u64,
+    message: String,
+}
+
+#[derive(Debug)]
+struct RunningTurn {
+    message: String,
+    abort_handle: AbortHandle,
+}
+
+type TurnTaskOutput = (u64, Result<ChatTurnOutcome, ChatTurnError>);
+
+/// Runs submitted turns in FIFO order with at most `STUDIO_MAX_PARALLEL_TURNS`
+/// in flight. Every turn uses its own independent agent session, recorded in
+/// `AGENT_HISTORY_FILE` when set; all of them share one HTTP client so turns reuse pooled
+/// provider connections.
+pub(super) fn spawn_runtime_worker(
+    handle: &Handle,
+    settings: AgentSettings,
+    mut command_rx: UnboundedReceiver<StudioCommand>,
+    event_tx: Sender<StudioEvent>,
+    mut graph_watch_handle: GraphWatchHandle,
+) {
+    let max_parallel_turns = settings.studio_max_parallel_turns.max(1) as usize;
+    let http_client = SharedHttpClient::from_settings(&settings);
+    let history = TurnHistory::from_settings(&settings);
+    let _task = handle.spawn(async move {
+        let mut queued = VecDeque::<QueuedTurn>::new();
+        let mut running = BTreeMap::<u64, RunningTurn>::new();
+        let mut tasks = JoinSet::<TurnTaskOutput>::new();
+
+        'worker: loop {
+            while running.len() < max_parallel_turns
+                && let Some(turn) = queued.pop_front()
+            {
+                if event_tx
+                    .send(StudioEvent::TurnStarted {
+                        turn_id: turn.turn_id,
+                        message: turn.message.clone(),
+                        started_at: SystemTime::now(),
+                    })
+                    .await
+                    .is_err()
+                {
+                    break 'worker;
+                }
+
+                let turn_settings = settings.clone();
+                let turn_http_client = http_client.clone();
+                let turn_history = history.clone();
+                let turn_message = turn.message.clone();
+                let turn_id = turn.turn_id;
+                let activity_tx = event_tx.clone();
+                // The sink cannot wait for space, so live activity is dropped while the
+                // UI is behind; the turn's recorded tool cards still arrive with its result.
+                let activity: TurnEventSink = Arc::new(move |event| {
+                    let _ = activity_tx.try_send(StudioEvent::TurnToolActivity { turn_id, event });
+                });
+                let abort_handle = tasks.spawn(async move {
+                    let mut session =
+                        ChatSession::with_http_client(&turn_settings, turn_http_client)
+                            .with_event_sink(activity);
+                    if let Some(history) = turn_history {
+                        session = session.with_history(history, HistorySource::Studio);
+                    }
+                    (turn_id, session.chat_turn(&turn_message).await)
+                });
+                running.insert(
+                    turn.turn_id,
+                    RunningTurn {
+                        message: turn.message,
+                        abort_handle,
+                    },
+                );
+            }
+
+            tokio::select! {
+                command = command_rx.recv() => match command {
+                    Some(StudioCommand::SubmitUserMessage { turn_id, message }) => {
+                        queued.push_back(QueuedTurn { turn_id, message });
+                    }
+                    Some(StudioCommand::CancelTurn { turn_id }) => {
+                        let cancelled_message = if let Some(index) =
+                            queued.iter().position(|turn| turn.turn_id == turn_id)
+                        {
+                            queued.remove(index).map(|turn| turn.message)
+                        } else if let Some(turn) = running.remove(&turn_id) {
+                            turn.abort_handle.abort();
+                            Some(turn.message)
+                        } else {
+                            None
+                        };
+
+                        if let Some(message) = cancelled_message
+                            && event_tx
+                                .send(StudioEvent::TurnCancelled { turn_id, message })
+                                .await
+                                .is_err()
+                        {
+                            break 'worker;
+                        }
+                    }
+                    Some(StudioCommand::ReplaceGraphWatch { handle }) => {
+                        graph_watch_handle = handle;
+                    }
+                    Some(StudioCommand::DescribeSubsystem { context }) => {
+                        // Summaries do not occupy a turn slot or trigger graph refreshes.
+                        let client =
+                            ModelClient::with_http_client(settings.clone(), http_client.clone());
+                        let event_tx = event_tx.clone();
+                        tokio::spawn(async move {
+                            let result = generate_subsystem_summary(&client, &context)
+                                .await
+                                .map_err(|error| format!("{error:#}"));
+                            let _ = event_tx
+                                .send(StudioEvent::SubsystemSummaryReady {
+                                    subsystem: context.subsystem,
+                                    revision: context.revision,
+                                    result,
+                                })
+                                .await;
+                        });
+                    }
+                    Some(StudioCommand::Shutdown) | None => break 'worker,
+                },
+                Some(joined) = tasks.join_next(), if !tasks.is_empty() => {
+                    let event = match joined {
+                        Ok((turn_id, result)) => {
+                            // Cancelled turns were already reported and removed.
+                            let Some(turn) = running.remove(&turn_id) else {
+                                continue;
+                            };
+                            turn_result_event(turn_id, turn.message, result)
+                        }
+                        Err(error) if error.is_cancelled() => continue,
+                        Err(error) => {
+                            let Some(turn_id) = running
+                                .iter()
+                                .find(|(_, turn)| turn.abort_handle.id() == error.id())
+                                .map(|(turn_id, _)| *turn_id)
+                            else {
+                                continue;
+                            };
+                            let turn = running.remove(&turn_id).expect("running turn exists");
+                            warn!(turn_id, error = %error, "studio turn task failed");
+                            StudioEvent::TurnFailed {
+                                turn_id,
+                                message: turn.message,
+                                error: format!("turn task failed: {error}"),
+                            }
+                        }
+                    };
+
+                    if event_tx.send(event).await.is_err() {
+                        break 'worker;
+                    }
+
+                    // Graph refreshes are decoupled from turn success/failure.
+                    graph_watch_handle.notify_turn_completed();
+                }
+            }
+        }
+
+        tasks.abort_all();
+    });
+}
+
+fn turn_result_event(
+    turn_id: u64,
+    message: String,
+    result: Result<ChatTurnOutcome, ChatTurnError>,
+) -> StudioEvent {
+    match result {
+        Ok(outcome) => StudioEvent::TurnCompleted {
+            turn_id,
+            message,
+            result: Box::new(StudioTurnResult::from(outcome)),
+        },
+        Err(error) => StudioEvent::TurnFailed {
+            turn_id,
+            message,
+            error: error.details(),
+        },
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail, ensure};
+
+use crate::tools::{NotesQuota, check_notes_quota, forget_note_links, record_note_links};
+
+/// Subdirectory of the notes directory that holds one directory per studio session. The
+/// notes tools only read the top level of `NOTES_DIR`, so session notes stay out of shared
+/// searches until promoted.
+pub(super) const SESSION_NOTES_DIR: &str = ".studio-sessions";
+
+/// The notes directory of one studio session (`STUDIO_SESSION_NOTES=true`) and the notes
+/// the user picked for promotion into the shared notes directory.
+#[derive(Debug)]
+pub(super) struct SessionNotes {
+    shared_dir: PathBuf,
+    session_dir: PathBuf,
+    /// `NOTES_MAX_*` limits the shared notes directory keeps after a promotion.
+    notes_quota: NotesQuota,
+    notes: Vec<String>,
+    selected: BTreeSet<String>,
+}
+
+/// What `SessionNotes::promote_selected` moved, and why the rest stayed behind.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct Promotion {
+    pub promoted: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl SessionNotes {
+    /// A fresh session under `shared_dir`, named after the launch time and process id.
+    /// Nothing is created until `save_note` writes the first note.
+    pub(super) fn start(shared_dir: PathBuf, notes_quota: NotesQuota) -> Self {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        Self::for_session(
+            shared_dir,
+            &format!("{stamp}-{}", std::process::id()),
+            notes_quota,
+        )
+    }
+
+    pub(super) fn for_session(
+        shared_dir: PathBuf,
+        session_id: &str,
+        notes_quota: NotesQuota,
+    ) -> Self {
+        let session_dir = shared_dir.join(SESSION_NOTES_DIR).join(session_id);
+        Self {
+            shared_dir,
+            session_dir,
+            notes_quota,
+            notes: Vec::new(),
+            selected: BTreeSet::new(),
+        }
+    }
+
+    pub(super) fn shared_dir(&self) -> &Path {
+        &self.shared_dir
+    }
+
+    pub(super) fn session_dir(&self) -> &Path {
+        &self.session_dir
+    }
+
+    /// File names of the session's notes, sorted, as of the last `refresh`.
+    pub(super) fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub(super) fn is_selected(&self, note: &str) -> bool {
+        self.selected.contains(note)
+    }
+
+    pub(super) fn set_selected(&mut self, note: &str, selected: bool) {
+        if selected {
+            self.selected.insert(note.to_owned());
+        } else {
+            self.selected.remove(note);
+        }
+    }
+
+    pub(super) fn has_selection(&self) -> bool {
+        !self.selected.is_empty()
+    }
+
+    /// Re-lists the session directory; selections of notes that are gone are dropped.
+    pub(super) fn refresh(&mut self) -> Result<()> {
+        self.notes = list_notes(&self.session_dir)?;
+        let notes = &self.notes;
+        self.selected.retain(|note| notes.contains(note));
+        Ok(())
+    }
+
+    /// Moves every selected note into the shared notes directory, carrying its links over to
+    /// the shared backlinks index. A note whose name is already taken there, or that would
+    /// put the shared directory over its `NOTES_MAX_*` quota, is left in the session.
+    pub(super) fn promote_selected(&mut self) -> Promotion {
+        let mut promotion = Promotion::default();
+        for note in std::mem::take(&mut self.selected) {
+            match self.promote(&note) {
+                Ok(()) => promotion.promoted.push(note),
+                Err(error) => promotion.failed.push(format!("{note}: {error:#}")),
+            }
+        }
+        if let Err(error) = self.refresh() {
+            promotion.failed.push(format!("{error:#}"));
+        }
+        promotion
+    }
+
+    fn promote(&self, note: &str) -> Result<()> {
+        ensure!(
+            Path::new(note).file_name() == Some(note.as_ref()),
+            "not a note file name"
+        );
+        let source = self.session_dir.join(note);
+        let metadata = fs::symlink_metadata(&source)
+            .with_context(|| format!("failed to inspect {}", source.display()))?;
+        ensure!(
+            metadata.is_file() && !metadata.file_type().is_symlink(),
+            "not a regular file"
+        );
+        let target = self.shared_dir.join(note);
+        if fs::symlink_metadata(&target).is_ok() {
+            bail!("the shared notes already have a note with this name");
+        }
+        check_notes_quota(
+            self.notes_quota,
+            &self.shared_dir,
+            None,
+            metadata.len() as usize,
+        )?;
+        fs::rename(&source, &target)
+            .with_context(|| format!("failed to move to {}", target.display()))?;
+        forget_note_links(&self.session_dir, note);
+        if let Ok(content) = fs::read_to_string(&target) {
+            record_note_links(&self.shared_dir, note, &content);
+        }
+        Ok(())
+    }
+}
+
+fn list_notes(session_dir: &Path) -> Result<Vec<String>> {
+    let entries = match fs::read_dir(session_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| format!("failed to list {}", session_dir.display()));
+        }
+    };
+    let mut notes = Vec::new();
+    for entry in entries {
+        let entry = entry.with_context(|| format!("failed to list {}", session_dir.display()))?;
+        let is_note = Path::new(&entry.file_name())
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                matches!(
+                    extension.to_ascii_lowercase().as_str(),
+                    "md" | "markdown" | "txt"
+                )
+            });
+        if is_note
+            && entry.file_type().is_ok_and(|file_type| file_type.is_file())
+            && let Some(name) = entry.file_name().to_str()
+        {
+            notes.push(name.to_owned());
+        }
+    }
+    notes.sort();
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Promotion, SESSION_NOTES_DIR, SessionNotes};
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+    use crate::tools::NotesQuota;
+
+    #[test]
+    fn promoting_moves_selected_notes_without_overwriting_shared_ones() {
+        let shared_dir = temp_path("studio-session-notes");
+        let mut session =
+            SessionNotes::for_session(shared_dir.clone(), "s1", NotesQuota::UNLIMITED);
+        assert_eq!(
+            session.session_dir(),
+            shared_dir.join(SESSION_NOTES_DIR).join("s1")
+        );
+        session
+            .refresh()
+            .expect("a missing session dir lists no notes");
+        assert!(session.notes().is_empty());
+
+        fs::create_dir_all(session.session_dir()).expect("session dir");
+        fs::write(session.session_dir().join("draft.md"), "draft").expect("draft");
+        fs::write(session.session_dir().join("taken.md"), "session").expect("taken");
+        fs::write(session.session_dir().join("keep.md"), "keep").expect("keep");
+        fs::write(session.session_dir().join("image.png"), "png").expect("png");
+        fs::write(shared_dir.join("taken.md"), "shared").expect("shared note");
+        session.refresh().expect("refresh");
+        assert_eq!(session.notes(), ["draft.md", "keep.md", "taken.md"]);
+
+        session.set_selected("draft.md", true);
+        session.set_selected("taken.md", true);
+        let promotion = session.promote_selected();
+        assert_eq!(promotion.promoted, ["draft.md"]);
+        assert_eq!(promotion.failed.len(), 1, "{promotion:?}");
+        assert!(promotion.failed[0].starts_with("taken.md: "));
+
+        assert_eq!(
+            fs::read_to_string(shared_dir.join("draft.md")).expect("promoted"),
+            "draft"
+        );
+        assert_eq!(
+            fs::read_to_string(shared_dir.join("taken.md")).expect("shared"),
+            "shared"
+        );
+        assert_eq!(session.notes(), ["keep.md", "taken.md"]);
+        assert!(!session.has_selection());
+        assert_eq!(session.promote_selected(), Promotion::default());
+        remove_dir_if_exists(&shared_dir);
+    }
+    #[test]
+    fn promoting_keeps_the_shared_notes_within_their_quota() {
+        let shared_dir = temp_path("studio-session-notes-quota");
+        let quota = NotesQuota {
+            max_files: 1,
+            ..NotesQuota::UNLIMITED
+        };
+        let mut session = SessionNotes::for_session(shared_dir.clone(), "s1", quota);
+        fs::create_dir_all(session.session_dir()).expect("session dir");
+        fs::write(shared_dir.join("existing.md"), "shared").expect("shared note");
+        fs::write(session.session_dir().join("draft.md"), "draft").expect("draft");
+        session.refresh().expect("refresh");
+
+        session.set_selected("draft.md", true);
+        let promotion = session.promote_selected();
+        assert!(promotion.promoted.is_empty());
+        assert_eq!(promotion.failed.len(), 1, "{promotion:?}");
+        assert!(
+            promotion.failed[0].contains("NOTES_MAX_FILES"),
+            "{promotion:?}"
+        );
+        assert!(!shared_dir.join("draft.md").exists());
+        assert_eq!(session.notes(), ["draft.md"]);
+        remove_dir_if_exists(&shared_dir);
+    }
+}
@@ -0,0 +1,321 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::CanvasTurnSnapshot;
+use crate::config::AgentSettings;
+use crate::graph::ArchitectureGraph;
+
+const MAX_TURN_SNAPSHOTS: usize = 24;
+/// Rough in-memory cost of one graph node (id, label, path strings plus vec slot).
+const ESTIMATED_NODE_BYTES: usize = 256;
+/// Rough in-memory cost of one graph edge (two id strings plus vec slot).
+const ESTIMATED_EDGE_BYTES: usize = 160;
+
+/// Where a snapshot's before/after graphs currently live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum SnapshotGraphs {
+    Resident {
+        baseline: Option<ArchitectureGraph>,
+        outcome: ArchitectureGraph,
+    },
+    /// Written to disk to stay under the memory budget; reloaded when selected.
+    Spilled(PathBuf),
+    /// Evicted without a spill directory; the before/after overlay is unavailable.
+    Discarded,
+}
+
+impl SnapshotGraphs {
+    pub(super) fn baseline(&self) -> Option<&ArchitectureGraph> {
+        match self {
+            Self::Resident { baseline, .. } => baseline.as_ref(),
+            Self::Spilled(_) | Self::Discarded => None,
+        }
+    }
+
+    fn estimated_bytes(&self) -> usize {
+        match self {
+            Self::Resident { baseline, outcome } => {
+                baseline.as_ref().map_or(0, estimate_graph_bytes) + estimate_graph_bytes(outcome)
+            }
+            Self::Spilled(_) | Self::Discarded => 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SpilledGraphs {
+    baseline: Option<ArchitectureGraph>,
+    outcome: ArchitectureGraph,
+}
+
+pub(super) fn estimate_graph_bytes(graph: &ArchitectureGraph) -> usize {
+    graph.nodes.len() * ESTIMATED_NODE_BYTES + graph.edges.len() * ESTIMATED_EDGE_BYTES
+}
+
+/// Turn snapshots kept within a count cap and an estimated memory budget.
+///
+/// When the budget is exceeded, the oldest snapshots lose their resident graphs first,
+/// spilling them to `STUDIO_SNAPSHOT_SPILL_DIR` when configured. The latest and the
+/// selected snapshot always stay resident.
+#[derive(Debug)]
+pub(super) struct TurnSnapshotStore {
+    snapshots: Vec<CanvasTurnSnapshot>,
+    budget_bytes: usize,
+    spill_dir: Option<PathBuf>,
+}
+
+impl TurnSnapshotStore {
+    pub(super) fn new(budget_bytes: usize, spill_root: Option<PathBuf>) -> Self {
+        // A per-process subdirectory keeps concurrent studio instances apart.
+        let spill_dir = spill_root.map(|root| {
+            let started_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis())
+                .unwrap_or_default();
+            root.join(format!("studio-{}-{started_ms}", std::process::id()))
+        });
+        Self {
+            snapshots: Vec::new(),
+            budget_bytes,
+            spill_dir,
+        }
+    }
+
+    pub(super) fn from_settings(settings: &AgentSettings) -> Self {
+        let budget_bytes = (settings.studio_snapshot_memory_budget_mb as usize) * 1024 * 1024;
+        Self::new(
+            budget_bytes,
+            settings
+                .studio_snapshot_spill_dir
+                .as_ref()
+                .map(PathBuf::from),
+        )
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub(super) fn get(&self, index: usize) -> Option<&CanvasTurnSnapshot> {
+        self.snapshots.get(index)
+    }
+
+    pub(super) fn resident_bytes(&self) -> usize {
+        self.snapshots
+            .iter()
+            .map(|snapshot| snapshot.graphs.estimated_bytes())
+            .sum()
+    }
+
+    /// Appends a snapshot, dropping the oldest past the count cap and enforcing the budget.
+    pub(super) fn push(&mut self, snapshot: CanvasTurnSnapshot) {
+        self.snapshots.push(snapshot);
+        if self.snapshots.len() > MAX_TURN_SNAPSHOTS {
+            let extra = self.snapshots.len() - MAX_TURN_SNAPSHOTS;
+            for removed in self.snapshots.drain(0..extra) {
+                remove_spill_file(&removed.graphs);
+            }
+        }
+        let latest = self.snapshots.len().checked_sub(1);
+        self.enforce_budget(latest);
+    }
+
+    /// Reloads spilled graphs for `index` so the before/after overlay can use them.
+    pub(super) fn ensure_resident(&mut self, index: usize) {
+        let Some(snapshot) = self.snapshots.get_mut(index) else {
+            return;
+        };
+        let SnapshotGraphs::Spilled(path) = &snapshot.graphs else {
+            return;
+        };
+        let path = path.clone();
+        snapshot.graphs = match load_spilled_graphs(&path) {
+            Ok(spilled) => SnapshotGraphs::Resident {
+                baseline: spilled.baseline,
+                outcome: spilled.outcome,
+            },
+            Err(error) => {
+                warn!(
+                    path = %path.display(),
+                    error = %error,
+                    "failed to reload spilled studio snapshot; overlay unavailable"
+                );
+                SnapshotGraphs::Discarded
+            }
+        };
+        let _ = fs::remove_file(&path);
+        self.enforce_budget(Some(index));
+    }
+
+    /// Drops every snapshot and removes spilled files.
+    pub(super) fn clear(&mut self) {
+        for snapshot in self.snapshots.drain(..) {
+            remove_spill_file(&snapshot.graphs);
+        }
+    }
+
+    fn enforce_budget(&mut self, keep_index: Option<usize>) {
+        let latest = self.snapshots.len().checked_sub(1);
+        let mut resident_bytes = self.resident_bytes();
+        for index in 0..self.snapshots.len() {
+            if resident_bytes <= self.budget_bytes {
+                break;
+            }
+            if Some(index) == keep_index || Some(index) == latest {
+                continue;
+            }
+            let freed = self.snapshots[index].graphs.estimated_bytes();
+            if freed == 0 {
+                continue;
+            }
+            self.evict(index);
+            resident_bytes = resident_bytes.saturating_sub(freed);
+        }
+    }
+
+    fn evict(&mut self, index: usize) {
+        let snapshot = &mut self.snapshots[index];
+        let graphs = std::mem::replace(&mut snapshot.graphs, SnapshotGraphs::Discarded);
+        let SnapshotGraphs::Resident { baseline, outcome } = graphs else {
+            snapshot.graphs = graphs;
+            return;
+        };
+        let Some(spill_dir) = &self.spill_dir else {
+            return;
+        };
+        let path = spill_dir.join(format!("turn-{}.json", snapshot.turn_id));
+        match write_spilled_graphs(&path, &SpilledGraphs { baseline, outcome }) {
+            Ok(()) => snapshot.graphs = SnapshotGraphs::Spilled(path),
+            Err(error) => warn!(
+                path = %path.display(),
+                error = %error,
+                "failed to spill studio snapshot; discarding its graphs"
+            ),
+        }
+    }
+}
+
+impl Drop for TurnSnapshotStore {
+    fn drop(&mut self) {
+        self.clear();
+        if let Some(spill_dir) = &self.spill_dir {
+            let _ = fs::remove_dir(spill_dir);
+        }
+    }
+}
+
+fn write_spilled_graphs(path: &Path, graphs: &SpilledGraphs) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create `{}`", parent.display()))?;
+    }
+    let payload = serde_json::to_vec(graphs).context("failed to encode snapshot graphs")?;
+    fs::write(path, payload).with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+fn load_spilled_graphs(path: &Path) -> Result<SpilledGraphs> {
+    let payload = fs::read(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    serde_json::from_slice(&payload).context("failed to decode snapshot graphs")
+}
+
+fn remove_spill_file(graphs: &SnapshotGraphs) {
+    if let SnapshotGraphs::Spilled(path) = graphs {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::{SnapshotGraphs, TurnSnapshotStore, estimate_graph_bytes};
+    use crate::graph::{ArchitectureGraph, ArchitectureNode, ArchitectureNodeKind};
+    use crate::studio::CanvasTurnSnapshot;
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    fn graph(node_count: usize, revision: u64) -> ArchitectureGraph {
+        ArchitectureGraph {
+            nodes: (0..node_count)
+                .map(|index| ArchitectureNode {
+                    id: format!("file:src/n{index}.rs"),
+                    display_label: format!("n{index}.rs"),
+                    kind: ArchitectureNodeKind::File,
+                    path: Some(format!("src/n{index}.rs")),
+                })
+                .collect(),
+            edges: Vec::new(),
+            revision,
+            generated_at: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn snapshot(turn_id: u64, node_count: usize) -> CanvasTurnSnapshot {
+        CanvasTurnSnapshot {
+            turn_id,
+            started_at: SystemTime::UNIX_EPOCH,
+            completed_at: SystemTime::UNIX_EPOCH,
+            baseline_revision: Some(turn_id),
+            outcome_revision: turn_id + 1,
+            changed_target_ids: Vec::new(),
+            impact_target_ids: Vec::new(),
+            intent_target_ids: Vec::new(),
+            graphs: SnapshotGraphs::Resident {
+                baseline: Some(graph(node_count, turn_id)),
+                outcome: graph(node_count, turn_id + 1),
+            },
+        }
+    }
+
+    #[test]
+    fn push_discards_oldest_graphs_when_over_budget_without_spill_dir() {
+        let per_snapshot = estimate_graph_bytes(&graph(10, 0)) * 2;
+        let mut store = TurnSnapshotStore::new(per_snapshot * 2, None);
+
+        for turn_id in 1..=3 {
+            store.push(snapshot(turn_id, 10));
+        }
+
+        assert_eq!(store.len(), 3);
+        assert_eq!(
+            store.get(0).map(|s| &s.graphs),
+            Some(&SnapshotGraphs::Discarded)
+        );
+        assert!(store.get(1).and_then(|s| s.graphs.baseline()).is_some());
+        assert!(store.get(2).and_then(|s| s.graphs.baseline()).is_some());
+        assert_eq!(store.resident_bytes(), per_snapshot * 2);
+    }
+
+    #[test]
+    fn spilled_snapshots_reload_when_selected_and_clear_removes_files() {
+        let root = temp_path("studio_snapshot_spill");
+        let per_snapshot = estimate_graph_bytes(&graph(10, 0)) * 2;
+        let mut store = TurnSnapshotStore::new(per_snapshot, Some(root.clone()));
+
+        store.push(snapshot(1, 10));
+        store.push(snapshot(2, 10));
+        let SnapshotGraphs::Spilled(path) = store.get(0).expect("snapshot").graphs.clone() else {
+            panic!("oldest snapshot should be spilled");
+        };
+        assert!(path.exists());
+
+        store.ensure_resident(0);
+        let reloaded = store.get(0).expect("snapshot");
+        assert_eq!(reloaded.graphs.baseline().map(|g| g.nodes.len()), Some(10));
+        assert!(!path.exists());
+        assert!(store.get(1).expect("latest").graphs.baseline().is_some());
+
+        store.clear();
+        assert!(store.is_empty());
+        drop(store);
+        remove_dir_if_exists(&root);
+    }
+}
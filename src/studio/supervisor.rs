@@ -0,0 +1,164 @@
+use std::time::{Duration, Instant};
+
+pub(super) const MAX_WORKER_RESTART_ATTEMPTS: u32 = 5;
+const BASE_RESTART_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(30);
+/// A worker that stayed up this long after a restart gets a fresh attempt budget.
+const STABLE_WORKER_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum WorkerHealth {
+    Healthy,
+    RestartScheduled { attempt: u32, restart_at: Instant },
+    GaveUp,
+}
+
+/// Restart bookkeeping for one studio background worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct WorkerSupervisor {
+    health: WorkerHealth,
+    attempts: u32,
+    last_restart_at: Option<Instant>,
+}
+
+impl Default for WorkerSupervisor {
+    fn default() -> Self {
+        Self {
+            health: WorkerHealth::Healthy,
+            attempts: 0,
+            last_restart_at: None,
+        }
+    }
+}
+
+impl WorkerSupervisor {
+    pub(super) fn health(&self) -> WorkerHealth {
+        self.health
+    }
+
+    /// Schedules the next restart with exponential backoff, or gives up once
+    /// `MAX_WORKER_RESTART_ATTEMPTS` consecutive restarts have failed.
+    pub(super) fn on_disconnected(&mut self, now: Instant) -> WorkerHealth {
+        if self.last_restart_at.is_some_and(|restarted_at| {
+            now.saturating_duration_since(restarted_at) >= STABLE_WORKER_WINDOW
+        }) {
+            self.attempts = 0;
+        }
+
+        self.health = if self.attempts >= MAX_WORKER_RESTART_ATTEMPTS {
+            WorkerHealth::GaveUp
+        } else {
+            self.attempts += 1;
+            WorkerHealth::RestartScheduled {
+                attempt: self.attempts,
+                restart_at: now + worker_restart_backoff(self.attempts),
+            }
+        };
+        self.health
+    }
+
+    pub(super) fn restart_due(&self, now: Instant) -> bool {
+        matches!(
+            self.health,
+            WorkerHealth::RestartScheduled { restart_at, .. } if now >= restart_at
+        )
+    }
+
+    pub(super) fn on_restarted(&mut self, now: Instant) {
+        self.health = WorkerHealth::Healthy;
+        self.last_restart_at = Some(now);
+    }
+
+    pub(super) fn status_label(&self, worker_name: &str) -> Option<String> {
+        match self.health {
+            WorkerHealth::Healthy => None,
+            WorkerHealth::RestartScheduled { attempt, .. } => Some(format!(
+                "{worker_name} restarting ({attempt}/{MAX_WORKER_RESTART_ATTEMPTS})"
+            )),
+            WorkerHealth::GaveUp => Some(format!("{worker_name} down")),
+        }
+    }
+}
+
+fn worker_restart_backoff(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    BASE_RESTART_BACKOFF
+        .saturating_mul(1_u32 << exponent)
+        .min(MAX_RESTART_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{
+        MAX_WORKER_RESTART_ATTEMPTS, WorkerHealth, WorkerSupervisor, worker_restart_backoff,
+    };
+
+    #[test]
+    fn worker_restart_backoff_doubles_and_caps() {
+        assert_eq!(worker_restart_backoff(1), Duration::from_millis(500));
+        assert_eq!(worker_restart_backoff(2), Duration::from_secs(1));
+        assert_eq!(worker_restart_backoff(3), Duration::from_secs(2));
+        assert_eq!(worker_restart_backoff(12), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn supervisor_schedules_restart_then_reports_due() {
+        let mut supervisor = WorkerSupervisor::default();
+        let now = Instant::now();
+
+        let health = supervisor.on_disconnected(now);
+
+        assert_eq!(
+            health,
+            WorkerHealth::RestartScheduled {
+                attempt: 1,
+                restart_at: now + Duration::from_millis(500),
+            }
+        );
+        assert!(!supervisor.restart_due(now));
+        assert!(supervisor.restart_due(now + Duration::from_millis(500)));
+        assert_eq!(
+            supervisor.status_label("runtime").as_deref(),
+            Some("runtime restarting (1/5)")
+        );
+
+        supervisor.on_restarted(now + Duration::from_millis(500));
+        assert_eq!(supervisor.health(), WorkerHealth::Healthy);
+        assert_eq!(supervisor.status_label("runtime"), None);
+    }
+
+    #[test]
+    fn supervisor_gives_up_after_repeated_fast_failures() {
+        let mut supervisor = WorkerSupervisor::default();
+        let now = Instant::now();
+
+        for _ in 0..MAX_WORKER_RESTART_ATTEMPTS {
+            supervisor.on_disconnected(now);
+            supervisor.on_restarted(now);
+        }
+
+        assert_eq!(supervisor.on_disconnected(now), WorkerHealth::GaveUp);
+        assert_eq!(
+            supervisor.status_label("graph watch").as_deref(),
+            Some("graph watch down")
+        );
+    }
+
+    #[test]
+    fn supervisor_resets_attempts_after_stable_window() {
+        let mut supervisor = WorkerSupervisor::default();
+        let now = Instant::now();
+        for _ in 0..MAX_WORKER_RESTART_ATTEMPTS {
+            supervisor.on_disconnected(now);
+            supervisor.on_restarted(now);
+        }
+
+        let later = now + Duration::from_secs(61);
+        assert!(matches!(
+            supervisor.on_disconnected(later),
+            WorkerHealth::RestartScheduled { attempt: 1, .. }
+        ));
+    }
+}
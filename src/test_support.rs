@@ -39,6 +39,7 @@ pub fn apply_ollama_test_env(
     command.env("FETCH_URL_ALLOWED_DOMAINS", "example.com");
     command.env("NOTES_DIR", notes_dir.as_os_str());
     command.env("SAVE_NOTE_ALLOW_OVERWRITE", "false");
+    command.env("SAVE_NOTE_ALLOW_DELETE", "false");
     command.env("MODEL_TIMEOUT_MS", "100");
     command.env("MODEL_MAX_RETRIES", "0");
     command.env("RUST_LOG", "error");
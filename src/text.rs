@@ -0,0 +1,78 @@
+use std::fmt::Write as _;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// User-perceived characters (extended grapheme clusters) in `text`, so `é` written as
+/// `e` plus a combining accent, or a flag emoji, counts once.
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// `text` cut to at most `max_graphemes` user-perceived characters, `marker` included,
+/// never splitting a grapheme cluster. Returned unchanged when it already fits.
+pub fn truncate_graphemes(text: &str, max_graphemes: usize, marker: &str) -> String {
+    if grapheme_count(text) <= max_graphemes {
+        return text.to_owned();
+    }
+    let kept = max_graphemes.saturating_sub(grapheme_count(marker));
+    let mut truncated = text.graphemes(true).take(kept).collect::<String>();
+    truncated.push_str(marker);
+    truncated
+}
+
+/// Lowercased words of `text` with their `char` offsets, using Unicode word boundaries
+/// and full Unicode case mapping rather than ASCII-only rules.
+pub fn words_with_char_offsets(text: &str) -> Vec<(String, usize, usize)> {
+    let mut words = Vec::new();
+    let mut offset = 0;
+    for segment in text.split_word_bounds() {
+        let length = segment.chars().count();
+        if segment.chars().any(char::is_alphanumeric) {
+            words.push((segment.to_lowercase(), offset, offset + length));
+        }
+        offset += length;
+    }
+    words
+}
+
+/// Lowercase hex of `bytes`, two digits per byte, for digests and HMAC tags.
+pub fn hex_lower(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{grapheme_count, hex_lower, truncate_graphemes, words_with_char_offsets};
+
+    #[test]
+    fn hex_is_two_lowercase_digits_per_byte() {
+        assert_eq!(hex_lower(&[0x00, 0x0f, 0xab, 0xff]), "000fabff");
+        assert_eq!(hex_lower(&[]), "");
+    }
+
+    #[test]
+    fn truncation_counts_and_keeps_whole_grapheme_clusters() {
+        let decomposed = "Cafe\u{301} cre\u{300}me";
+        assert_eq!(grapheme_count(decomposed), 10);
+        assert_eq!(truncate_graphemes(decomposed, 5, "…"), "Cafe\u{301}…");
+        assert_eq!(truncate_graphemes("🇳🇴🇸🇪🇩🇰", 2, "…"), "🇳🇴…");
+        assert_eq!(truncate_graphemes("👩‍💻 koder", 4, "..."), "👩‍💻...");
+        assert_eq!(truncate_graphemes("kort", 4, "…"), "kort");
+    }
+
+    #[test]
+    fn words_use_unicode_case_mapping_and_char_offsets() {
+        assert_eq!(
+            words_with_char_offsets("Ærlig ΣΟΦΊΑ, cafe\u{301}!"),
+            vec![
+                ("ærlig".to_owned(), 0, 5),
+                ("σοφία".to_owned(), 6, 11),
+                ("cafe\u{301}".to_owned(), 13, 18),
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::ErrorKind;
+use std::path::{Component, Path};
+use std::process::Stdio;
+
+use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::{
+    PolicyRule, RUN_COMMAND_TOOL_NAME, RunCommandArgs, ToolDispatchError, ToolRuntimeConfig,
+};
+
+/// Runs an allowlisted program with `args` (no shell) in the workspace root, with the
+/// runtime's cleared environment. stdout and stderr are each cut to
+/// `run_command_max_output_bytes`; a non-zero exit is a result, not an error, so the model
+/// can read compiler or test failures. The caller's tool timeout kills the process.
+pub(super) async fn run_command(
+    args: RunCommandArgs,
+    runtime: &ToolRuntimeConfig,
+) -> Result<Value, ToolDispatchError> {
+    let program = args.command.trim();
+    if program.is_empty() {
+        return Err(ToolDispatchError::invalid_args(
+            RUN_COMMAND_TOOL_NAME,
+            "command cannot be empty",
+        ));
+    }
+    if !runtime
+        .allowed_commands
+        .iter()
+        .any(|allowed| allowed == program)
+    {
+        return Err(ToolDispatchError::policy_violation(
+            RUN_COMMAND_TOOL_NAME,
+            PolicyRule::Command,
+            format!("`{program}` is not in TOOL_ALLOWED_COMMANDS"),
+        ));
+    }
+    if let Some(arg) = args.args.iter().find(|arg| arg_leaves_workspace(arg)) {
+        return Err(ToolDispatchError::policy_violation(
+            RUN_COMMAND_TOOL_NAME,
+            PolicyRule::WorkspacePath,
+            format!("argument `{arg}` points outside the workspace root"),
+        ));
+    }
+
+    let mut command = runtime.command(program);
+    command
+        .args(&args.args)
+        .current_dir(runtime.workspace_root_path())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = command.spawn().map_err(|error| {
+        let reason = if error.kind() == ErrorKind::NotFound {
+            format!("`{program}` was not found on the tool PATH")
+        } else {
+            format!("failed to start `{program}`: {error}")
+        };
+        ToolDispatchError::execution_failed(RUN_COMMAND_TOOL_NAME, reason)
+    })?;
+
+    let cap = runtime.run_command_max_output_bytes;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let (stdout, stderr, status) = tokio::join!(
+        read_capped(stdout, cap),
+        read_capped(stderr, cap),
+        child.wait()
+    );
+    let read_failed = |error: std::io::Error| {
+        ToolDispatchError::execution_failed(
+            RUN_COMMAND_TOOL_NAME,
+            format!("failed to collect output of `{program}`: {error}"),
+        )
+    };
+    let (stdout, stdout_truncated) = stdout.map_err(read_failed)?;
+    let (stderr, stderr_truncated) = stderr.map_err(read_failed)?;
+    let status = status.map_err(read_failed)?;
+
+    Ok(json!({
+        "command": program,
+        "args": args.args,
+        "exit_code": status.code(),
+        "success": status.success(),
+        "stdout": stdout,
+        "stdout_truncated": stdout_truncated,
+        "stderr": stderr,
+        "stderr_truncated": stderr_truncated
+    }))
+}
+
+/// Absolute paths, `~` paths, and `..` components, checked on the whole argument, on
+/// the value after `=` (`--manifest-path=/elsewhere/Cargo.toml`), and on every value a
+/// joined short option could carry after its flag letters (`-C/etc`, `-p../x`,
+/// `-xvf/tmp/a`). The last check also refuses some relative values such as
+/// `-ofoo/bar`; those pass as a separate argument (`-o`, `foo/bar`).
+fn arg_leaves_workspace(arg: &str) -> bool {
+    let value = arg.split_once('=').map(|(_, value)| value);
+    [Some(arg), value]
+        .into_iter()
+        .flatten()
+        .chain(joined_short_option_values(arg))
+        .any(|candidate| {
+            let path = Path::new(candidate);
+            candidate.starts_with('~')
+                || path.is_absolute()
+                || path
+                    .components()
+                    .any(|component| component == Component::ParentDir)
+        })
+}
+
+/// What follows each flag letter of a single-dash argument: `-C/etc` gives `/etc`, and
+/// `-xvf/tmp` gives `vf/tmp`, `f/tmp`, and `/tmp`.
+fn joined_short_option_values(arg: &str) -> impl Iterator<Item = &str> {
+    let flags = arg
+        .strip_prefix('-')
+        .filter(|flags| !flags.starts_with('-'))
+        .unwrap_or_default();
+    let letters = flags
+        .find(|ch: char| !ch.is_ascii_alphanumeric())
+        .unwrap_or(flags.len());
+    (1..=letters).map(move |end| &flags[end..])
+}
+
+/// The first `cap` bytes of `reader` as text, and whether more followed. The rest is
+/// drained so the child never blocks on a full pipe.
+async fn read_capped(
+    mut reader: impl AsyncRead + Unpin,
+    cap: usize,
+) -> std::io::Result<(String, bool)> {
+    let mut kept = Vec::new();
+    (&mut reader)
+        .take(cap as u64)
+        .read_to_end(&mut kept)
+        .await?;
+    let dropped = tokio::io::copy(&mut reader, &mut tokio::io::sink()).await?;
+    if dropped > 0
+        && let Err(error) = std::str::from_utf8(&kept)
+        && error.error_len().is_none()
+    {
+        kept.truncate(error.valid_up_to());
+    }
+    Ok((String::from_utf8_lossy(&kept).into_owned(), dropped > 0))
+}
@@ -1,19 +1,49 @@
-use std::error::Error as StdError;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
 use std::fs;
 use std::io::{ErrorKind, Write};
-use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use icu_normalizer::ComposingNormalizerBorrowed;
 use reqwest::Url;
 use reqwest::header::{CONTENT_TYPE, HeaderMap, LOCATION};
-use reqwest::redirect::Policy;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
+use crate::http::{HttpRequest, SharedHttpClient};
+use crate::json_schema::schema_violations;
+
+mod command;
+mod note_links;
+mod notes_index;
+mod notes_quota;
+mod workspace_file;
+mod workspace_search;
+
+use self::command::run_command;
+pub(crate) use self::note_links::{BacklinksIndex, forget_note_links, record_note_links};
+use self::notes_index::{NoteDocument, NotesIndex, NotesQuery};
+pub use self::notes_quota::NotesQuota;
+pub(crate) use self::notes_quota::check_notes_quota;
+use self::workspace_file::run_read_workspace_file;
+use self::workspace_search::{WorkspaceSearchLimits, run_search_workspace};
+
 pub const SEARCH_NOTES_TOOL_NAME: &str = "search_notes";
 pub const FETCH_URL_TOOL_NAME: &str = "fetch_url";
 pub const SAVE_NOTE_TOOL_NAME: &str = "save_note";
+pub const READ_NOTE_TOOL_NAME: &str = "read_note";
+pub const DELETE_NOTE_TOOL_NAME: &str = "delete_note";
+pub const READ_WORKSPACE_FILE_TOOL_NAME: &str = "read_workspace_file";
+pub const SEARCH_WORKSPACE_TOOL_NAME: &str = "search_workspace";
+pub const RUN_COMMAND_TOOL_NAME: &str = "run_command";
+
+/// Minimum gap between `fetch_url` progress updates while a body downloads.
+const FETCH_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct ToolDefinition {
@@ -22,7 +52,7 @@ pub struct ToolDefinition {
     pub description: &'static str,
 }
 
-const TOOL_DEFINITIONS: [ToolDefinition; 3] = [
+const TOOL_DEFINITIONS: [ToolDefinition; 8] = [
     ToolDefinition {
         name: SEARCH_NOTES_TOOL_NAME,
         signature: "search_notes(query: string, limit: u8)",
@@ -35,15 +65,102 @@ const TOOL_DEFINITIONS: [ToolDefinition; 3] = [
     },
     ToolDefinition {
         name: SAVE_NOTE_TOOL_NAME,
-        signature: "save_note(title: string, body: string)",
+        signature: "save_note(title: string, body: string, if_absent?: bool, mode?: create|overwrite|append)",
         description: "Save a note with a title and body.",
     },
+    ToolDefinition {
+        name: READ_NOTE_TOOL_NAME,
+        signature: "read_note(identifier: string)",
+        description: "Read the full body of a note by slug or path.",
+    },
+    ToolDefinition {
+        name: DELETE_NOTE_TOOL_NAME,
+        signature: "delete_note(title: string, confirm: bool)",
+        description: "Delete a saved note by title.",
+    },
+    ToolDefinition {
+        name: READ_WORKSPACE_FILE_TOOL_NAME,
+        signature: "read_workspace_file(path: string, max_bytes: u32)",
+        description: "Read a source or text file inside the workspace root.",
+    },
+    ToolDefinition {
+        name: SEARCH_WORKSPACE_TOOL_NAME,
+        signature: "search_workspace(pattern: string, glob: string, max_results: u16)",
+        description: "Search workspace source files for a substring or /regex/.",
+    },
+    ToolDefinition {
+        name: RUN_COMMAND_TOOL_NAME,
+        signature: "run_command(command: string, args: [string])",
+        description: "Run an allowlisted program in the workspace root and return its output.",
+    },
 ];
 
+/// A tool definition with its JSON parameter and output schemas, as served by `GET /tools`
+/// and `tools list --json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolSchema {
+    #[serde(flatten)]
+    pub definition: ToolDefinition,
+    pub parameters: Value,
+    pub output: Value,
+}
+
 pub fn tool_definitions() -> &'static [ToolDefinition] {
     &TOOL_DEFINITIONS
 }
 
+/// Tools that reach beyond the machine; `AGENT_OFFLINE` refuses them.
+const NETWORK_TOOL_NAMES: [&str; 1] = [FETCH_URL_TOOL_NAME];
+
+pub fn is_network_tool(name: &str) -> bool {
+    NETWORK_TOOL_NAMES.contains(&name)
+}
+
+/// Tools that change nothing on disk, so calls to them can run in any order.
+const READ_ONLY_TOOL_NAMES: [&str; 5] = [
+    SEARCH_NOTES_TOOL_NAME,
+    FETCH_URL_TOOL_NAME,
+    READ_NOTE_TOOL_NAME,
+    READ_WORKSPACE_FILE_TOOL_NAME,
+    SEARCH_WORKSPACE_TOOL_NAME,
+];
+
+pub fn is_read_only_tool(name: &str) -> bool {
+    READ_ONLY_TOOL_NAMES.contains(&name)
+}
+
+/// Every registered tool name, the default for `ENABLED_TOOLS`.
+pub fn all_tool_names() -> Vec<String> {
+    tool_definitions()
+        .iter()
+        .map(|tool| tool.name.to_owned())
+        .collect()
+}
+
+pub fn tool_schemas() -> Vec<ToolSchema> {
+    tool_definitions()
+        .iter()
+        .map(|definition| ToolSchema {
+            definition: *definition,
+            parameters: tool_parameters_schema(definition.name),
+            output: tool_output_schema(definition.name),
+        })
+        .collect()
+}
+
+/// `tools list`: prints each tool's signature, or the full schemas with `json`.
+pub fn run_tools_list_command(json: bool) -> anyhow::Result<()> {
+    if json {
+        let body = json!({ "tools": tool_schemas() });
+        println!("{}", serde_json::to_string_pretty(&body)?);
+        return Ok(());
+    }
+    for tool in tool_definitions() {
+        println!("{}: {}", tool.signature, tool.description);
+    }
+    Ok(())
+}
+
 pub fn tool_parameters_schema(tool_name: &str) -> Value {
     match tool_name {
         SEARCH_NOTES_TOOL_NAME => json!({
@@ -67,11 +184,58 @@ pub fn tool_parameters_schema(tool_name: &str) -> Value {
             "type": "object",
             "properties": {
                 "title": {"type": "string"},
-                "body": {"type": "string"}
+                "body": {"type": "string"},
+                "if_absent": {"type": "boolean"},
+                "mode": {"type": "string", "enum": ["create", "overwrite", "append"]}
             },
             "required": ["title", "body"],
             "additionalProperties": false
         }),
+        READ_NOTE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "identifier": {"type": "string"}
+            },
+            "required": ["identifier"],
+            "additionalProperties": false
+        }),
+        DELETE_NOTE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "confirm": {"type": "boolean"}
+            },
+            "required": ["title", "confirm"],
+            "additionalProperties": false
+        }),
+        READ_WORKSPACE_FILE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "max_bytes": {"type": "integer", "minimum": 1, "maximum": 4294967295u64}
+            },
+            "required": ["path", "max_bytes"],
+            "additionalProperties": false
+        }),
+        SEARCH_WORKSPACE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "pattern": {"type": "string"},
+                "glob": {"type": "string"},
+                "max_results": {"type": "integer", "minimum": 1, "maximum": 65535}
+            },
+            "required": ["pattern", "glob", "max_results"],
+            "additionalProperties": false
+        }),
+        RUN_COMMAND_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "command": {"type": "string"},
+                "args": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["command", "args"],
+            "additionalProperties": false
+        }),
         _ => json!({
             "type": "object",
             "properties": {},
@@ -80,6 +244,156 @@ pub fn tool_parameters_schema(tool_name: &str) -> Value {
     }
 }
 
+/// Schema every successful payload of `tool_name` is validated against in
+/// `dispatch_tool_call`.
+pub fn tool_output_schema(tool_name: &str) -> Value {
+    match tool_name {
+        SEARCH_NOTES_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string"},
+                "limit": {"type": "integer", "minimum": 0, "maximum": 255},
+                "total_matches": {"type": "integer", "minimum": 0},
+                "results": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "title": {"type": "string"},
+                            "path": {"type": "string"},
+                            "score": {"type": "number", "minimum": 0},
+                            "snippet": {"type": "string"},
+                            "highlights": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "start": {"type": "integer", "minimum": 0},
+                                        "end": {"type": "integer", "minimum": 0}
+                                    },
+                                    "required": ["start", "end"],
+                                    "additionalProperties": false
+                                }
+                            },
+                            "backlinks": {"type": "array", "items": {"type": "string"}}
+                        },
+                        "required": ["title", "path", "score", "snippet", "highlights", "backlinks"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["query", "limit", "total_matches", "results"],
+            "additionalProperties": false
+        }),
+        FETCH_URL_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "url": {"type": "string"},
+                "final_url": {"type": "string"},
+                "status_code": {"type": "integer", "minimum": 100, "maximum": 599},
+                "content_type": {"type": ["string", "null"]},
+                "bytes": {"type": "integer", "minimum": 0},
+                "content": {"type": "string"}
+            },
+            "required": ["url", "final_url", "status_code", "content_type", "bytes", "content"],
+            "additionalProperties": false
+        }),
+        SAVE_NOTE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "path": {"type": "string"},
+                "bytes": {"type": "integer", "minimum": 0},
+                "status": {"type": "string", "enum": ["created", "overwritten", "appended", "unchanged"]}
+            },
+            "required": ["title", "path", "bytes", "status"],
+            "additionalProperties": false
+        }),
+        READ_NOTE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "path": {"type": "string"},
+                "bytes": {"type": "integer", "minimum": 0},
+                "content": {"type": "string"}
+            },
+            "required": ["title", "path", "bytes", "content"],
+            "additionalProperties": false
+        }),
+        DELETE_NOTE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "path": {"type": "string"},
+                "status": {"type": "string", "enum": ["deleted", "absent"]}
+            },
+            "required": ["title", "path", "status"],
+            "additionalProperties": false
+        }),
+        READ_WORKSPACE_FILE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "bytes": {"type": "integer", "minimum": 0},
+                "total_bytes": {"type": "integer", "minimum": 0},
+                "truncated": {"type": "boolean"},
+                "content": {"type": "string"}
+            },
+            "required": ["path", "bytes", "total_bytes", "truncated", "content"],
+            "additionalProperties": false
+        }),
+        SEARCH_WORKSPACE_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "pattern": {"type": "string"},
+                "glob": {"type": "string"},
+                "files_scanned": {"type": "integer", "minimum": 0},
+                "truncated": {"type": "boolean"},
+                "results": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": {"type": "string"},
+                            "line": {"type": "integer", "minimum": 1},
+                            "snippet": {"type": "string"}
+                        },
+                        "required": ["path", "line", "snippet"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["pattern", "glob", "files_scanned", "truncated", "results"],
+            "additionalProperties": false
+        }),
+        RUN_COMMAND_TOOL_NAME => json!({
+            "type": "object",
+            "properties": {
+                "command": {"type": "string"},
+                "args": {"type": "array", "items": {"type": "string"}},
+                "exit_code": {"type": ["integer", "null"]},
+                "success": {"type": "boolean"},
+                "stdout": {"type": "string"},
+                "stdout_truncated": {"type": "boolean"},
+                "stderr": {"type": "string"},
+                "stderr_truncated": {"type": "boolean"}
+            },
+            "required": [
+                "command",
+                "args",
+                "exit_code",
+                "success",
+                "stdout",
+                "stdout_truncated",
+                "stderr",
+                "stderr_truncated"
+            ],
+            "additionalProperties": false
+        }),
+        _ => json!({"type": "object"}),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct SearchNotesArgs {
@@ -98,6 +412,77 @@ pub struct FetchUrlArgs {
 pub struct SaveNoteArgs {
     pub title: String,
     pub body: String,
+    /// Never replace an existing note, even with `SAVE_NOTE_ALLOW_OVERWRITE=true`.
+    #[serde(default)]
+    pub if_absent: bool,
+    /// How an existing note is treated; `overwrite` when unset.
+    #[serde(default)]
+    pub mode: Option<SaveNoteMode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveNoteMode {
+    /// Write a new note; an existing one with different content is never replaced.
+    Create,
+    /// Replace an existing note, which needs `SAVE_NOTE_ALLOW_OVERWRITE=true`.
+    Overwrite,
+    /// Add the body after the existing note's content, or create the note.
+    Append,
+}
+
+impl SaveNoteMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Overwrite => "overwrite",
+            Self::Append => "append",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReadNoteArgs {
+    /// Note slug (`daily-note`) or path relative to `notes_dir` (`daily-note.md`).
+    pub identifier: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteNoteArgs {
+    pub title: String,
+    /// Must be `true`; the model has to ask for the deletion explicitly.
+    pub confirm: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReadWorkspaceFileArgs {
+    /// Relative to the workspace root, or absolute under it.
+    pub path: String,
+    /// Bytes to return; clamped to `READ_WORKSPACE_FILE_MAX_BYTES`.
+    pub max_bytes: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchWorkspaceArgs {
+    /// Case-insensitive substring, or a regex when wrapped in slashes (`/fn \w+_test/`).
+    pub pattern: String,
+    /// Workspace-relative path glob (`src/**/*.rs`, `*.toml`); empty searches every file.
+    pub glob: String,
+    /// Matches to return; clamped to `SEARCH_WORKSPACE_MAX_RESULTS`.
+    pub max_results: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunCommandArgs {
+    /// Bare program name from `TOOL_ALLOWED_COMMANDS`, looked up on the tool `PATH`.
+    pub command: String,
+    /// Passed to the program as-is; there is no shell.
+    pub args: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -106,14 +491,116 @@ pub struct ToolDispatchOutput {
     pub payload: Value,
 }
 
+/// Intermediate progress of a running tool call, currently `fetch_url` body downloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolProgress {
+    pub elapsed: Duration,
+    pub bytes_received: u64,
+    /// `Content-Length` of the response, when the server sent one.
+    pub bytes_total: Option<u64>,
+}
+
+impl ToolProgress {
+    /// Whole percent of `bytes_total` received, when the total is known.
+    pub fn percent(&self) -> Option<u8> {
+        let total = self.bytes_total.filter(|total| *total > 0)?;
+        Some((self.bytes_received.min(total) * 100 / total) as u8)
+    }
+
+    /// Short status such as `2.4s · 340.0 KB / 1.0 MB`.
+    pub fn label(&self) -> String {
+        let received = format_bytes(self.bytes_received);
+        match self.bytes_total {
+            Some(total) => format!(
+                "{:.1}s · {received} / {}",
+                self.elapsed.as_secs_f64(),
+                format_bytes(total)
+            ),
+            None => format!("{:.1}s · {received}", self.elapsed.as_secs_f64()),
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f < KB {
+        format!("{bytes} B")
+    } else if bytes_f < KB * KB {
+        format!("{:.1} KB", bytes_f / KB)
+    } else {
+        format!("{:.1} MB", bytes_f / (KB * KB))
+    }
+}
+
+/// Receives `ToolProgress` from a running tool call. Clones share the callback.
+#[derive(Clone)]
+pub struct ToolProgressSink(Arc<dyn Fn(ToolProgress) + Send + Sync>);
+
+impl ToolProgressSink {
+    pub fn new(callback: impl Fn(ToolProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    pub fn emit(&self, progress: ToolProgress) {
+        (self.0)(progress);
+    }
+}
+
+impl fmt::Debug for ToolProgressSink {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("ToolProgressSink")
+    }
+}
+
+impl PartialEq for ToolProgressSink {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ToolProgressSink {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ToolRuntimeConfig {
     pub fetch_url_allowed_domains: Vec<String>,
     pub notes_dir: PathBuf,
     pub save_note_allow_overwrite: bool,
+    pub save_note_allow_delete: bool,
+    pub notes_quota: NotesQuota,
     pub tool_timeout_ms: u64,
+    /// Per-tool deadlines that replace `tool_timeout_ms`, keyed by tool name.
+    pub tool_timeout_overrides_ms: BTreeMap<String, u64>,
     pub fetch_url_max_bytes: usize,
     pub fetch_url_follow_redirects: bool,
+    /// Base for relative tool paths; empty means the process working directory.
+    pub working_dir: PathBuf,
+    /// Complete environment for process-based tools; nothing else is inherited.
+    pub env: BTreeMap<String, String>,
+    /// The only directory `read_workspace_file` may read; empty means `working_dir`.
+    pub workspace_root: PathBuf,
+    /// Lowercase extensions `read_workspace_file` accepts; empty refuses every file.
+    pub read_workspace_file_extensions: Vec<String>,
+    pub read_workspace_file_max_bytes: usize,
+    /// Most matches one `search_workspace` call returns.
+    pub search_workspace_max_results: usize,
+    /// File bytes one `search_workspace` call scans; `read_workspace_file_extensions`
+    /// picks the files.
+    pub search_workspace_max_bytes: usize,
+    /// Programs `run_command` may start; empty refuses every command.
+    pub allowed_commands: Vec<String>,
+    /// Bytes of stdout and of stderr one `run_command` call returns.
+    pub run_command_max_output_bytes: usize,
+    /// Tools that may run; `None` allows every registered tool.
+    pub enabled_tools: Option<BTreeSet<String>>,
+    /// Refuses network tools (`AGENT_OFFLINE`).
+    pub offline: bool,
+    /// Where long-running tools report intermediate progress.
+    pub progress: Option<ToolProgressSink>,
+    /// Transport for `fetch_url`; `None` builds a default client per call.
+    pub http_client: Option<SharedHttpClient>,
+    /// User recorded as the author of the notes `save_note` writes.
+    pub note_author: Option<String>,
 }
 
 impl ToolRuntimeConfig {
@@ -129,11 +616,193 @@ impl ToolRuntimeConfig {
             fetch_url_allowed_domains,
             notes_dir,
             save_note_allow_overwrite,
+            save_note_allow_delete: false,
+            notes_quota: NotesQuota::UNLIMITED,
             tool_timeout_ms,
+            tool_timeout_overrides_ms: BTreeMap::new(),
             fetch_url_max_bytes,
             fetch_url_follow_redirects,
+            working_dir: PathBuf::new(),
+            env: BTreeMap::new(),
+            workspace_root: PathBuf::new(),
+            read_workspace_file_extensions: Vec::new(),
+            read_workspace_file_max_bytes: 0,
+            search_workspace_max_results: 0,
+            search_workspace_max_bytes: 0,
+            allowed_commands: Vec::new(),
+            run_command_max_output_bytes: 0,
+            enabled_tools: None,
+            offline: false,
+            progress: None,
+            http_client: None,
+            note_author: None,
+        }
+    }
+
+    pub fn with_progress(mut self, progress: ToolProgressSink) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_enabled_tools(mut self, enabled_tools: &[String]) -> Self {
+        self.enabled_tools = Some(enabled_tools.iter().cloned().collect());
+        self
+    }
+
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn with_save_note_allow_delete(mut self, save_note_allow_delete: bool) -> Self {
+        self.save_note_allow_delete = save_note_allow_delete;
+        self
+    }
+
+    pub fn with_note_author(mut self, note_author: Option<String>) -> Self {
+        self.note_author = note_author;
+        self
+    }
+
+    pub fn with_http_client(mut self, http_client: SharedHttpClient) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    pub fn with_working_dir(mut self, working_dir: PathBuf) -> Self {
+        self.working_dir = working_dir;
+        self
+    }
+
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn with_notes_quota(mut self, notes_quota: NotesQuota) -> Self {
+        self.notes_quota = notes_quota;
+        self
+    }
+
+    pub fn with_workspace_root(mut self, workspace_root: PathBuf) -> Self {
+        self.workspace_root = workspace_root;
+        self
+    }
+
+    pub fn with_read_workspace_file_limits(
+        mut self,
+        extensions: Vec<String>,
+        max_bytes: usize,
+    ) -> Self {
+        self.read_workspace_file_extensions = extensions;
+        self.read_workspace_file_max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_search_workspace_limits(mut self, max_results: usize, max_bytes: usize) -> Self {
+        self.search_workspace_max_results = max_results;
+        self.search_workspace_max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_run_command_policy(
+        mut self,
+        allowed_commands: Vec<String>,
+        max_output_bytes: usize,
+    ) -> Self {
+        self.allowed_commands = allowed_commands;
+        self.run_command_max_output_bytes = max_output_bytes;
+        self
+    }
+
+    pub fn with_tool_timeout_overrides(mut self, overrides_ms: BTreeMap<String, u64>) -> Self {
+        self.tool_timeout_overrides_ms = overrides_ms;
+        self
+    }
+
+    /// Deadline for one call of `tool_name`: its override, else `tool_timeout_ms`.
+    pub fn timeout_ms_for(&self, tool_name: &str) -> u64 {
+        self.tool_timeout_overrides_ms
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.tool_timeout_ms)
+    }
+
+    /// Resolves `path` against `working_dir` unless it is already absolute.
+    pub fn resolve_path(&self, path: &Path) -> PathBuf {
+        if path.is_absolute() || self.working_dir.as_os_str().is_empty() {
+            path.to_path_buf()
+        } else {
+            self.working_dir.join(path)
+        }
+    }
+
+    pub fn notes_dir_path(&self) -> PathBuf {
+        self.resolve_path(&self.notes_dir)
+    }
+
+    /// `workspace_root` resolved like other tool paths, falling back to the process
+    /// working directory when neither it nor `working_dir` is set.
+    pub fn workspace_root_path(&self) -> PathBuf {
+        let root = self.resolve_path(&self.workspace_root);
+        if root.as_os_str().is_empty() {
+            env::current_dir().unwrap_or_default()
+        } else {
+            root
         }
     }
+
+    /// Builds a child process that runs in `working_dir` with only `env` set.
+    pub fn command(&self, program: impl AsRef<OsStr>) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(program);
+        command.env_clear().envs(&self.env);
+        if !self.working_dir.as_os_str().is_empty() {
+            command.current_dir(&self.working_dir);
+        }
+        command
+    }
+}
+
+/// Snapshots the allowlisted variables from the current process environment.
+pub fn collect_tool_env(allowlist: &[String]) -> BTreeMap<String, String> {
+    allowlist
+        .iter()
+        .filter_map(|name| env::var(name).ok().map(|value| (name.clone(), value)))
+        .collect()
+}
+
+/// Which tool policy blocked a call, so callers can react without matching on the reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyRule {
+    /// Left out of `ENABLED_TOOLS`.
+    Disabled,
+    /// Network tool called while `AGENT_OFFLINE` is on.
+    Offline,
+    /// URL or redirect host outside `FETCH_URL_ALLOWED_DOMAINS`.
+    Domain,
+    Scheme,
+    ContentType,
+    /// Response body over `FETCH_URL_MAX_BYTES`.
+    Size,
+    /// Note path is a symlink, not a regular file, or not a `.md`/`.markdown`/`.txt` note.
+    NoteTarget,
+    /// Note path reaches outside `notes_dir`.
+    NotePath,
+    /// Replacing an existing note without confirmation, against `if_absent`, or in
+    /// `create` mode.
+    Overwrite,
+    /// Deleting a note without `SAVE_NOTE_ALLOW_DELETE` or `confirm: true`.
+    Delete,
+    /// Note write over `NOTES_MAX_FILES`, `NOTES_MAX_TOTAL_BYTES`, or
+    /// `NOTES_MAX_NOTE_BYTES`.
+    NoteQuota,
+    /// Workspace file path or `run_command` argument reaches outside the workspace root.
+    WorkspacePath,
+    /// Workspace file is a symlink, not a regular file, or outside
+    /// `READ_WORKSPACE_FILE_EXTENSIONS`.
+    WorkspaceFileType,
+    /// Program outside `TOOL_ALLOWED_COMMANDS`.
+    Command,
 }
 
 #[derive(Debug, thiserror::Error, PartialEq)]
@@ -145,10 +814,17 @@ pub enum ToolDispatchError {
     InvalidArgs { tool_name: String, reason: String },
 
     #[error("policy block for tool `{tool_name}`: {reason}")]
-    PolicyViolation { tool_name: String, reason: String },
+    PolicyViolation {
+        tool_name: String,
+        rule: PolicyRule,
+        reason: String,
+    },
 
     #[error("execution failed for tool `{tool_name}`: {reason}")]
     ExecutionFailed { tool_name: String, reason: String },
+
+    #[error("tool `{tool_name}` returned output that does not match its schema: {reason}")]
+    InvalidOutput { tool_name: String, reason: String },
 }
 
 impl ToolDispatchError {
@@ -165,9 +841,10 @@ impl ToolDispatchError {
         }
     }
 
-    fn policy_violation(tool_name: &str, reason: impl Into<String>) -> Self {
+    fn policy_violation(tool_name: &str, rule: PolicyRule, reason: impl Into<String>) -> Self {
         Self::PolicyViolation {
             tool_name: tool_name.to_owned(),
+            rule,
             reason: reason.into(),
         }
     }
@@ -185,36 +862,93 @@ pub async fn dispatch_tool_call(
     raw_args: Value,
     runtime: &ToolRuntimeConfig,
 ) -> Result<ToolDispatchOutput, ToolDispatchError> {
+    if let Some(enabled_tools) = &runtime.enabled_tools
+        && !enabled_tools.contains(tool_name)
+        && tool_definitions().iter().any(|tool| tool.name == tool_name)
+    {
+        return Err(ToolDispatchError::policy_violation(
+            tool_name,
+            PolicyRule::Disabled,
+            "tool is disabled by ENABLED_TOOLS",
+        ));
+    }
+    if runtime.offline && is_network_tool(tool_name) {
+        return Err(ToolDispatchError::policy_violation(
+            tool_name,
+            PolicyRule::Offline,
+            "network access is disabled by AGENT_OFFLINE; answer from local notes instead",
+        ));
+    }
     let payload = match tool_name {
         SEARCH_NOTES_TOOL_NAME => {
-            run_search_notes(parse_args(tool_name, raw_args)?, &runtime.notes_dir)
+            run_search_notes(parse_args(tool_name, raw_args)?, &runtime.notes_dir_path())
         }
         FETCH_URL_TOOL_NAME => {
             run_fetch_url(
                 parse_args(tool_name, raw_args)?,
                 &runtime.fetch_url_allowed_domains,
-                runtime.tool_timeout_ms,
+                runtime.timeout_ms_for(FETCH_URL_TOOL_NAME),
                 runtime.fetch_url_max_bytes,
                 runtime.fetch_url_follow_redirects,
+                runtime.progress.as_ref(),
+                runtime.http_client.clone().unwrap_or_default(),
             )
             .await
         }
         SAVE_NOTE_TOOL_NAME => run_save_note(
             parse_args(tool_name, raw_args)?,
-            &runtime.notes_dir,
+            &runtime.notes_dir_path(),
             runtime.save_note_allow_overwrite,
+            runtime.notes_quota,
+            runtime.note_author.as_deref(),
+        ),
+        READ_NOTE_TOOL_NAME => {
+            run_read_note(parse_args(tool_name, raw_args)?, &runtime.notes_dir_path())
+        }
+        DELETE_NOTE_TOOL_NAME => run_delete_note(
+            parse_args(tool_name, raw_args)?,
+            &runtime.notes_dir_path(),
+            runtime.save_note_allow_delete,
+        ),
+        READ_WORKSPACE_FILE_TOOL_NAME => run_read_workspace_file(
+            parse_args(tool_name, raw_args)?,
+            &runtime.workspace_root_path(),
+            &runtime.read_workspace_file_extensions,
+            runtime.read_workspace_file_max_bytes,
+        ),
+        SEARCH_WORKSPACE_TOOL_NAME => run_search_workspace(
+            parse_args(tool_name, raw_args)?,
+            &runtime.workspace_root_path(),
+            WorkspaceSearchLimits {
+                extensions: &runtime.read_workspace_file_extensions,
+                max_results: runtime.search_workspace_max_results,
+                max_bytes: runtime.search_workspace_max_bytes,
+            },
         ),
+        RUN_COMMAND_TOOL_NAME => run_command(parse_args(tool_name, raw_args)?, runtime).await,
         _ => {
             return Err(ToolDispatchError::unknown_tool(tool_name));
         }
     }?;
 
+    validate_tool_output(tool_name, &payload)?;
     Ok(ToolDispatchOutput {
         tool_name: tool_name.to_owned(),
         payload,
     })
 }
 
+fn validate_tool_output(tool_name: &str, payload: &Value) -> Result<(), ToolDispatchError> {
+    let violations = schema_violations(&tool_output_schema(tool_name), payload);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(ToolDispatchError::InvalidOutput {
+        tool_name: tool_name.to_owned(),
+        reason: violations.join("; "),
+    })
+}
+
 fn parse_args<T: for<'de> Deserialize<'de>>(
     tool_name: &str,
     raw_args: Value,
@@ -223,17 +957,10 @@ fn parse_args<T: for<'de> Deserialize<'de>>(
         .map_err(|error| ToolDispatchError::invalid_args(tool_name, error.to_string()))
 }
 
-#[derive(Debug)]
-struct SearchNoteMatch {
-    title: String,
-    path: String,
-    score: u32,
-    snippet: String,
-}
-
 fn run_search_notes(args: SearchNotesArgs, notes_dir: &Path) -> Result<Value, ToolDispatchError> {
     let query = args.query.trim();
-    if query.is_empty() {
+    let parsed_query = NotesQuery::parse(query);
+    if parsed_query.is_empty() {
         return Err(ToolDispatchError::invalid_args(
             SEARCH_NOTES_TOOL_NAME,
             "query cannot be empty",
@@ -250,9 +977,8 @@ fn run_search_notes(args: SearchNotesArgs, notes_dir: &Path) -> Result<Value, To
         }));
     }
 
-    let query_lower = query.to_ascii_lowercase();
-    let mut matches = Vec::new();
-
+    let mut documents = Vec::new();
+    let mut notes = Vec::new();
     for path in list_searchable_note_paths(notes_dir)? {
         let raw = fs::read(&path).map_err(|error| {
             ToolDispatchError::execution_failed(
@@ -262,47 +988,61 @@ fn run_search_notes(args: SearchNotesArgs, notes_dir: &Path) -> Result<Value, To
         })?;
         let content = String::from_utf8_lossy(&raw).to_string();
         let title = extract_note_title(&content, &path);
-        let score = count_occurrences_case_insensitive(&title, &query_lower)
-            .saturating_mul(2)
-            .saturating_add(count_occurrences_case_insensitive(&content, &query_lower));
-        if score == 0 {
-            continue;
+        if let Some(file) = path.file_name().and_then(OsStr::to_str) {
+            notes.push((file.to_owned(), title.clone(), content.clone()));
         }
-
-        matches.push(SearchNoteMatch {
+        documents.push(NoteDocument {
             title,
             path: path.display().to_string(),
-            score,
-            snippet: extract_note_snippet(&content, &query_lower),
+            content,
         });
     }
 
-    matches.sort_by(|left, right| {
-        right
-            .score
-            .cmp(&left.score)
-            .then_with(|| left.title.cmp(&right.title))
-            .then_with(|| left.path.cmp(&right.path))
-    });
-    let total_matches = matches.len();
-    matches.truncate(limit);
+    let mut backlinks = BacklinksIndex::load_or_build(
+        notes_dir,
+        notes
+            .iter()
+            .map(|(file, _, content)| (file.as_str(), content.as_str())),
+    )
+    .backlinks(
+        &notes
+            .into_iter()
+            .map(|(file, title, _)| (file, title))
+            .collect::<Vec<_>>(),
+    );
+    let mut hits = NotesIndex::build(documents).search(&parsed_query);
+    let total_matches = hits.len();
+    hits.truncate(limit);
 
     Ok(json!({
         "query": query,
         "limit": args.limit,
         "total_matches": total_matches,
-        "results": matches.into_iter().map(|matched| {
+        "results": hits.into_iter().map(|hit| {
             json!({
-                "title": matched.title,
-                "path": matched.path,
-                "score": matched.score,
-                "snippet": matched.snippet,
+                "title": hit.title,
+                "path": hit.path,
+                "score": hit.score,
+                "snippet": hit.snippet,
+                "highlights": hit.highlights.into_iter().map(|(start, end)| {
+                    json!({"start": start, "end": end})
+                }).collect::<Vec<_>>(),
+                "backlinks": Path::new(&hit.path)
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .and_then(|file| backlinks.remove(file))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|source| notes_dir.join(source).display().to_string())
+                    .collect::<Vec<_>>(),
             })
         }).collect::<Vec<_>>()
     }))
 }
 
-fn list_searchable_note_paths(notes_dir: &Path) -> Result<Vec<PathBuf>, ToolDispatchError> {
+pub(crate) fn list_searchable_note_paths(
+    notes_dir: &Path,
+) -> Result<Vec<PathBuf>, ToolDispatchError> {
     if !notes_dir.exists() {
         return Ok(Vec::new());
     }
@@ -347,7 +1087,7 @@ fn list_searchable_note_paths(notes_dir: &Path) -> Result<Vec<PathBuf>, ToolDisp
     Ok(paths)
 }
 
-fn is_searchable_note_extension(path: &Path) -> bool {
+pub(crate) fn is_searchable_note_extension(path: &Path) -> bool {
     let Some(extension) = path.extension().and_then(|value| value.to_str()) else {
         return false;
     };
@@ -355,7 +1095,7 @@ fn is_searchable_note_extension(path: &Path) -> bool {
     normalized == "md" || normalized == "markdown" || normalized == "txt"
 }
 
-fn extract_note_title(content: &str, path: &Path) -> String {
+pub(crate) fn extract_note_title(content: &str, path: &Path) -> String {
     for line in content.lines() {
         let trimmed = line.trim();
         if let Some(stripped) = trimmed.strip_prefix("# ") {
@@ -372,48 +1112,53 @@ fn extract_note_title(content: &str, path: &Path) -> String {
         .unwrap_or_else(|| "untitled".to_owned())
 }
 
-fn extract_note_snippet(content: &str, query_lower: &str) -> String {
-    let mut fallback: Option<String> = None;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        if fallback.is_none() {
-            fallback = Some(trimmed.to_owned());
-        }
-        if trimmed.to_ascii_lowercase().contains(query_lower) {
-            return truncate_chars(trimmed, 160);
-        }
-    }
-
-    fallback
-        .map(|value| truncate_chars(&value, 160))
-        .unwrap_or_default()
+/// Fetches `url` under the `fetch_url` policy (host allowlist checked on every redirect
+/// hop, size cap, text/JSON content types) for requests made outside a turn, such as
+/// `update check`. Returns the `fetch_url` output object.
+pub async fn fetch_url_outside_turn(
+    url: &str,
+    fetch_url_allowed_domains: &[String],
+    fetch_url_follow_redirects: bool,
+    timeout_ms: u64,
+    fetch_url_max_bytes: usize,
+    http_client: SharedHttpClient,
+) -> Result<Value, ToolDispatchError> {
+    run_fetch_url(
+        FetchUrlArgs {
+            url: url.to_owned(),
+        },
+        fetch_url_allowed_domains,
+        timeout_ms,
+        fetch_url_max_bytes,
+        fetch_url_follow_redirects,
+        None,
+        http_client,
+    )
+    .await
 }
 
-fn truncate_chars(text: &str, max_chars: usize) -> String {
-    if text.chars().count() <= max_chars {
-        return text.to_owned();
-    }
-    text.chars().take(max_chars).collect()
-}
-
-fn count_occurrences_case_insensitive(haystack: &str, needle_lower: &str) -> u32 {
-    if needle_lower.is_empty() {
-        return 0;
-    }
-
-    let haystack_lower = haystack.to_ascii_lowercase();
-    let mut count = 0_u32;
-    let mut offset = 0_usize;
-    while let Some(index) = haystack_lower[offset..].find(needle_lower) {
-        count = count.saturating_add(1);
-        offset = offset.saturating_add(index + needle_lower.len());
-    }
-
-    count
+/// Writes a note under the `save_note` policy (no symlink targets, overwrites only with
+/// `SAVE_NOTE_ALLOW_OVERWRITE`, within `notes_quota`) for notes written outside a turn,
+/// such as `digest`. Returns the `save_note` output object.
+pub fn save_note_outside_turn(
+    title: &str,
+    body: &str,
+    notes_dir: &Path,
+    save_note_allow_overwrite: bool,
+    notes_quota: NotesQuota,
+) -> Result<Value, ToolDispatchError> {
+    run_save_note(
+        SaveNoteArgs {
+            title: title.to_owned(),
+            body: body.to_owned(),
+            if_absent: false,
+            mode: None,
+        },
+        notes_dir,
+        save_note_allow_overwrite,
+        notes_quota,
+        None,
+    )
 }
 
 async fn run_fetch_url(
@@ -422,6 +1167,8 @@ async fn run_fetch_url(
     tool_timeout_ms: u64,
     fetch_url_max_bytes: usize,
     fetch_url_follow_redirects: bool,
+    progress: Option<&ToolProgressSink>,
+    http_client: SharedHttpClient,
 ) -> Result<Value, ToolDispatchError> {
     run_fetch_url_with_fetcher(
         args,
@@ -429,7 +1176,17 @@ async fn run_fetch_url(
         fetch_url_follow_redirects,
         tool_timeout_ms,
         fetch_url_max_bytes,
-        fetch_url_over_http,
+        |url, allowed_domains, follow_redirects, timeout_ms, max_bytes| {
+            fetch_url_over_http(
+                url,
+                allowed_domains,
+                follow_redirects,
+                timeout_ms,
+                max_bytes,
+                progress.cloned(),
+                http_client.clone(),
+            )
+        },
     )
     .await
 }
@@ -479,6 +1236,7 @@ where
     {
         return Err(ToolDispatchError::policy_violation(
             FETCH_URL_TOOL_NAME,
+            PolicyRule::ContentType,
             format!("content type `{value}` is not allowed"),
         ));
     }
@@ -486,6 +1244,7 @@ where
     if fetched.body.len() > fetch_url_max_bytes {
         return Err(ToolDispatchError::policy_violation(
             FETCH_URL_TOOL_NAME,
+            PolicyRule::Size,
             format!(
                 "response body exceeded FETCH_URL_MAX_BYTES limit: {} bytes (max {fetch_url_max_bytes})",
                 fetched.body.len()
@@ -510,39 +1269,23 @@ async fn fetch_url_over_http(
     fetch_url_follow_redirects: bool,
     tool_timeout_ms: u64,
     fetch_url_max_bytes: usize,
+    progress: Option<ToolProgressSink>,
+    http_client: SharedHttpClient,
 ) -> Result<FetchResponse, ToolDispatchError> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_millis(tool_timeout_ms))
-        // Redirects are handled explicitly below so we can enforce allowlist policy per hop.
-        .redirect(Policy::none())
-        .build()
-        .map_err(|error| {
+    let started_at = Instant::now();
+    let mut current_url = parsed_url.clone();
+    let mut redirects_followed = 0_usize;
+    loop {
+        // The client never follows redirects itself, so the allowlist is enforced per hop below.
+        let request = HttpRequest::get(current_url.as_str())
+            .with_timeout(Duration::from_millis(tool_timeout_ms));
+        let response = http_client.send(request).await.map_err(|error| {
             ToolDispatchError::execution_failed(
                 FETCH_URL_TOOL_NAME,
-                format!(
-                    "failed to build HTTP client: {}",
-                    describe_reqwest_error(&error)
-                ),
+                format!("failed to fetch `{current_url}`: {error}"),
             )
         })?;
 
-    let mut current_url = parsed_url.clone();
-    let mut redirects_followed = 0_usize;
-    loop {
-        let response = client
-            .get(current_url.clone())
-            .send()
-            .await
-            .map_err(|error| {
-                ToolDispatchError::execution_failed(
-                    FETCH_URL_TOOL_NAME,
-                    format!(
-                        "failed to fetch `{current_url}`: {}",
-                        describe_reqwest_error(&error)
-                    ),
-                )
-            })?;
-
         if fetch_url_follow_redirects && response.status().is_redirection() {
             if redirects_followed >= 10 {
                 return Err(ToolDispatchError::execution_failed(
@@ -562,15 +1305,14 @@ async fn fetch_url_over_http(
 
         let status_code = response.status().as_u16();
         let content_type = extract_content_type(response.headers())?;
+        let bytes_total = response.content_length();
+        let mut last_progress_at = started_at;
         let mut body = Vec::new();
         let mut response = response;
         while let Some(chunk) = response.chunk().await.map_err(|error| {
             ToolDispatchError::execution_failed(
                 FETCH_URL_TOOL_NAME,
-                format!(
-                    "failed to read response body from `{current_url}`: {}",
-                    describe_reqwest_error(&error)
-                ),
+                format!("failed to read response body from `{current_url}`: {error}"),
             )
         })? {
             let next_len = body.len().checked_add(chunk.len()).ok_or_else(|| {
@@ -582,12 +1324,23 @@ async fn fetch_url_over_http(
             if next_len > fetch_url_max_bytes {
                 return Err(ToolDispatchError::policy_violation(
                     FETCH_URL_TOOL_NAME,
+                    PolicyRule::Size,
                     format!(
                         "response body exceeded FETCH_URL_MAX_BYTES limit: {next_len} bytes (max {fetch_url_max_bytes})"
                     ),
                 ));
             }
             body.extend_from_slice(&chunk);
+            if let Some(progress) = &progress
+                && last_progress_at.elapsed() >= FETCH_PROGRESS_INTERVAL
+            {
+                last_progress_at = Instant::now();
+                progress.emit(ToolProgress {
+                    elapsed: started_at.elapsed(),
+                    bytes_received: body.len() as u64,
+                    bytes_total,
+                });
+            }
         }
 
         return Ok(FetchResponse {
@@ -603,56 +1356,6 @@ fn status_is_success(status_code: u16) -> bool {
     (200..300).contains(&status_code)
 }
 
-fn describe_reqwest_error(error: &reqwest::Error) -> String {
-    let mut details = vec![format_error_chain(error)];
-    let mut classes = Vec::new();
-
-    if error.is_builder() {
-        classes.push("builder");
-    }
-    if error.is_connect() {
-        classes.push("connect");
-    }
-    if error.is_timeout() {
-        classes.push("timeout");
-    }
-    if error.is_request() {
-        classes.push("request");
-    }
-    if error.is_body() {
-        classes.push("body");
-    }
-    if error.is_decode() {
-        classes.push("decode");
-    }
-    if error.is_redirect() {
-        classes.push("redirect");
-    }
-    if error.is_status() {
-        classes.push("status");
-    }
-
-    if !classes.is_empty() {
-        details.push(format!("class={}", classes.join(",")));
-    }
-    if let Some(url) = error.url() {
-        details.push(format!("url={url}"));
-    }
-
-    details.join(" | ")
-}
-
-fn format_error_chain(error: &(dyn StdError + 'static)) -> String {
-    let mut chain = error.to_string();
-    let mut source = error.source();
-    while let Some(next) = source {
-        chain.push_str(": ");
-        chain.push_str(&next.to_string());
-        source = next.source();
-    }
-    chain
-}
-
 fn parse_fetch_url(
     url: &str,
     fetch_url_allowed_domains: &[String],
@@ -674,6 +1377,7 @@ fn parse_fetch_url(
     if parsed.scheme() != "http" && parsed.scheme() != "https" {
         return Err(ToolDispatchError::policy_violation(
             FETCH_URL_TOOL_NAME,
+            PolicyRule::Scheme,
             format!("url scheme `{}` is not allowed", parsed.scheme()),
         ));
     }
@@ -682,6 +1386,7 @@ fn parse_fetch_url(
     if !host_allowed(&host, fetch_url_allowed_domains) {
         return Err(ToolDispatchError::policy_violation(
             FETCH_URL_TOOL_NAME,
+            PolicyRule::Domain,
             format!("url host `{host}` is not in allowlist"),
         ));
     }
@@ -722,6 +1427,7 @@ fn resolve_redirect_target(
     if target.scheme() != "http" && target.scheme() != "https" {
         return Err(ToolDispatchError::policy_violation(
             FETCH_URL_TOOL_NAME,
+            PolicyRule::Scheme,
             format!(
                 "redirect target scheme `{}` is not allowed",
                 target.scheme()
@@ -734,6 +1440,7 @@ fn resolve_redirect_target(
         .ok_or_else(|| {
             ToolDispatchError::policy_violation(
                 FETCH_URL_TOOL_NAME,
+                PolicyRule::Domain,
                 format!("redirect target `{target}` must include a host"),
             )
         })?
@@ -742,6 +1449,7 @@ fn resolve_redirect_target(
     if !host_allowed(&host, fetch_url_allowed_domains) {
         return Err(ToolDispatchError::policy_violation(
             FETCH_URL_TOOL_NAME,
+            PolicyRule::Domain,
             format!("redirect target host `{host}` is not in allowlist"),
         ));
     }
@@ -757,6 +1465,7 @@ fn extract_content_type(headers: &HeaderMap) -> Result<Option<String>, ToolDispa
     let raw = value.to_str().map_err(|error| {
         ToolDispatchError::policy_violation(
             FETCH_URL_TOOL_NAME,
+            PolicyRule::ContentType,
             format!("invalid response content type header: {error}"),
         )
     })?;
@@ -793,6 +1502,8 @@ fn run_save_note(
     args: SaveNoteArgs,
     notes_dir: &Path,
     save_note_allow_overwrite: bool,
+    notes_quota: NotesQuota,
+    author: Option<&str>,
 ) -> Result<Value, ToolDispatchError> {
     let title = args.title.trim();
     if title.is_empty() {
@@ -801,8 +1512,22 @@ fn run_save_note(
             "title cannot be empty",
         ));
     }
+    let mode = match (args.mode, args.if_absent) {
+        (None, false) => SaveNoteMode::Overwrite,
+        (None | Some(SaveNoteMode::Create), true) => SaveNoteMode::Create,
+        (Some(mode), false) => mode,
+        (Some(mode), true) => {
+            return Err(ToolDispatchError::invalid_args(
+                SAVE_NOTE_TOOL_NAME,
+                format!(
+                    "`if_absent` cannot be combined with mode `{}`",
+                    mode.as_str()
+                ),
+            ));
+        }
+    };
 
-    let note_slug = normalize_note_title(title).ok_or_else(|| {
+    let note_slug = note_slug_in_dir(notes_dir, title).ok_or_else(|| {
         ToolDispatchError::invalid_args(
             SAVE_NOTE_TOOL_NAME,
             "title must include at least one alphanumeric character",
@@ -836,10 +1561,15 @@ fn run_save_note(
         )
     })?;
 
+    let mut file_content = match author {
+        Some(author) => format!("# {title}\n{}\n\n{}\n", note_author_line(author), args.body),
+        None => format!("# {title}\n\n{}\n", args.body),
+    };
     if let Some(metadata) = existing_metadata.as_ref() {
         if metadata.file_type().is_symlink() {
             return Err(ToolDispatchError::policy_violation(
                 SAVE_NOTE_TOOL_NAME,
+                PolicyRule::NoteTarget,
                 format!(
                     "refusing to write note `{}` because target is a symlink",
                     note_path.display()
@@ -850,6 +1580,7 @@ fn run_save_note(
         if !metadata.is_file() {
             return Err(ToolDispatchError::policy_violation(
                 SAVE_NOTE_TOOL_NAME,
+                PolicyRule::NoteTarget,
                 format!(
                     "refusing to overwrite non-file note path `{}`",
                     note_path.display()
@@ -857,9 +1588,45 @@ fn run_save_note(
             ));
         }
 
-        if !save_note_allow_overwrite {
+        let existing_content = fs::read_to_string(&note_path).map_err(|error| {
+            ToolDispatchError::execution_failed(
+                SAVE_NOTE_TOOL_NAME,
+                format!(
+                    "failed to read existing note `{}`: {error}",
+                    note_path.display()
+                ),
+            )
+        })?;
+        if mode == SaveNoteMode::Append {
+            // Appending keeps every earlier line, so it needs no overwrite confirmation.
+            file_content = append_note_body(&existing_content, &args.body, author);
+        } else if existing_content == file_content {
+            // Retried turns and duplicate model calls write the same note again; treat
+            // that as success instead of an overwrite.
+            return Ok(json!({
+                "title": title,
+                "path": note_path.display().to_string(),
+                "bytes": file_content.len(),
+                "status": "unchanged"
+            }));
+        } else if mode == SaveNoteMode::Create {
+            let reason = if args.if_absent {
+                "`if_absent` is set"
+            } else {
+                "mode is `create`"
+            };
             return Err(ToolDispatchError::policy_violation(
                 SAVE_NOTE_TOOL_NAME,
+                PolicyRule::Overwrite,
+                format!(
+                    "refusing to replace existing note `{}` with different content because {reason}",
+                    note_path.display()
+                ),
+            ));
+        } else if !save_note_allow_overwrite {
+            return Err(ToolDispatchError::policy_violation(
+                SAVE_NOTE_TOOL_NAME,
+                PolicyRule::Overwrite,
                 format!(
                     "refusing to overwrite existing note `{}` without confirmation; set SAVE_NOTE_ALLOW_OVERWRITE=true to confirm overwrite",
                     note_path.display()
@@ -868,7 +1635,13 @@ fn run_save_note(
         }
     }
 
-    let file_content = format!("# {title}\n\n{}\n", args.body);
+    check_notes_quota(
+        notes_quota,
+        notes_dir,
+        existing_metadata.as_ref().map(fs::Metadata::len),
+        file_content.len(),
+    )?;
+
     let temp_path = create_temp_note_path(notes_dir, &note_slug);
     write_new_file(&temp_path, &file_content).map_err(|error| {
         ToolDispatchError::execution_failed(
@@ -903,15 +1676,250 @@ fn run_save_note(
             ),
         )
     })?;
+    record_note_links(notes_dir, &note_filename, &file_content);
 
     Ok(json!({
         "title": title,
         "path": note_path.display().to_string(),
         "bytes": file_content.len(),
-        "status": if existing_metadata.is_some() { "overwritten" } else { "created" }
+        "status": match (existing_metadata.is_some(), mode) {
+            (false, _) => "created",
+            (true, SaveNoteMode::Append) => "appended",
+            (true, _) => "overwritten",
+        }
+    }))
+}
+
+/// `existing` followed by a blank line and `body` (under an author line when there is an
+/// author), ending in one newline.
+fn append_note_body(existing: &str, body: &str, author: Option<&str>) -> String {
+    let existing = existing.trim_end_matches(['\r', '\n']);
+    let body = body.trim_end_matches(['\r', '\n']);
+    match author {
+        Some(author) => format!("{existing}\n\n{}\n{body}\n", note_author_line(author)),
+        None => format!("{existing}\n\n{body}\n"),
+    }
+}
+
+/// Hidden Markdown comment naming who saved the section below it.
+fn note_author_line(author: &str) -> String {
+    format!("<!-- author: {author} -->")
+}
+
+fn run_delete_note(
+    args: DeleteNoteArgs,
+    notes_dir: &Path,
+    save_note_allow_delete: bool,
+) -> Result<Value, ToolDispatchError> {
+    let title = args.title.trim();
+    if title.is_empty() {
+        return Err(ToolDispatchError::invalid_args(
+            DELETE_NOTE_TOOL_NAME,
+            "title cannot be empty",
+        ));
+    }
+
+    let note_slug = note_slug_in_dir(notes_dir, title).ok_or_else(|| {
+        ToolDispatchError::invalid_args(
+            DELETE_NOTE_TOOL_NAME,
+            "title must include at least one alphanumeric character",
+        )
+    })?;
+    let note_path = notes_dir.join(format!("{note_slug}.md"));
+    let existing_metadata = fs::symlink_metadata(&note_path)
+        .map(Some)
+        .or_else(|error| match error.kind() {
+            ErrorKind::NotFound => Ok(None),
+            _ => Err(error),
+        });
+    let existing_metadata = existing_metadata.map_err(|error| {
+        ToolDispatchError::execution_failed(
+            DELETE_NOTE_TOOL_NAME,
+            format!(
+                "failed to inspect existing note `{}`: {error}",
+                note_path.display()
+            ),
+        )
+    })?;
+
+    // Retried turns and duplicate model calls delete the same note again; treat an
+    // already missing note as success.
+    let Some(metadata) = existing_metadata else {
+        return Ok(json!({
+            "title": title,
+            "path": note_path.display().to_string(),
+            "status": "absent"
+        }));
+    };
+
+    if metadata.file_type().is_symlink() {
+        return Err(ToolDispatchError::policy_violation(
+            DELETE_NOTE_TOOL_NAME,
+            PolicyRule::NoteTarget,
+            format!(
+                "refusing to delete note `{}` because target is a symlink",
+                note_path.display()
+            ),
+        ));
+    }
+
+    if !metadata.is_file() {
+        return Err(ToolDispatchError::policy_violation(
+            DELETE_NOTE_TOOL_NAME,
+            PolicyRule::NoteTarget,
+            format!(
+                "refusing to delete non-file note path `{}`",
+                note_path.display()
+            ),
+        ));
+    }
+
+    if !save_note_allow_delete {
+        return Err(ToolDispatchError::policy_violation(
+            DELETE_NOTE_TOOL_NAME,
+            PolicyRule::Delete,
+            format!(
+                "refusing to delete note `{}`; set SAVE_NOTE_ALLOW_DELETE=true to allow deletes",
+                note_path.display()
+            ),
+        ));
+    }
+
+    if !args.confirm {
+        return Err(ToolDispatchError::policy_violation(
+            DELETE_NOTE_TOOL_NAME,
+            PolicyRule::Delete,
+            format!(
+                "refusing to delete note `{}` without confirmation; call again with `confirm: true`",
+                note_path.display()
+            ),
+        ));
+    }
+
+    fs::remove_file(&note_path).map_err(|error| {
+        ToolDispatchError::execution_failed(
+            DELETE_NOTE_TOOL_NAME,
+            format!("failed to delete note `{}`: {error}", note_path.display()),
+        )
+    })?;
+    if let Some(file) = note_path.file_name().and_then(OsStr::to_str) {
+        forget_note_links(notes_dir, file);
+    }
+
+    Ok(json!({
+        "title": title,
+        "path": note_path.display().to_string(),
+        "status": "deleted"
+    }))
+}
+
+fn run_read_note(args: ReadNoteArgs, notes_dir: &Path) -> Result<Value, ToolDispatchError> {
+    let identifier = args.identifier.trim();
+    if identifier.is_empty() {
+        return Err(ToolDispatchError::invalid_args(
+            READ_NOTE_TOOL_NAME,
+            "identifier cannot be empty",
+        ));
+    }
+
+    let note_path = notes_dir.join(resolve_note_identifier(identifier, notes_dir)?);
+    let metadata = match fs::symlink_metadata(&note_path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            return Err(ToolDispatchError::invalid_args(
+                READ_NOTE_TOOL_NAME,
+                format!("no note matches `{identifier}`"),
+            ));
+        }
+        Err(error) => {
+            return Err(ToolDispatchError::execution_failed(
+                READ_NOTE_TOOL_NAME,
+                format!("failed to inspect note `{}`: {error}", note_path.display()),
+            ));
+        }
+    };
+    if metadata.file_type().is_symlink() || !metadata.is_file() {
+        return Err(ToolDispatchError::policy_violation(
+            READ_NOTE_TOOL_NAME,
+            PolicyRule::NoteTarget,
+            format!(
+                "refusing to read `{}` because it is not a regular note file",
+                note_path.display()
+            ),
+        ));
+    }
+    // A symlinked directory inside `notes_dir` could still lead elsewhere.
+    let canonicalize = |path: &Path| {
+        fs::canonicalize(path).map_err(|error| {
+            ToolDispatchError::execution_failed(
+                READ_NOTE_TOOL_NAME,
+                format!("failed to resolve `{}`: {error}", path.display()),
+            )
+        })
+    };
+    if !canonicalize(&note_path)?.starts_with(canonicalize(notes_dir)?) {
+        return Err(ToolDispatchError::policy_violation(
+            READ_NOTE_TOOL_NAME,
+            PolicyRule::NotePath,
+            format!("note `{identifier}` resolves outside the notes directory"),
+        ));
+    }
+
+    let raw = fs::read(&note_path).map_err(|error| {
+        ToolDispatchError::execution_failed(
+            READ_NOTE_TOOL_NAME,
+            format!("failed to read note `{}`: {error}", note_path.display()),
+        )
+    })?;
+    let content = String::from_utf8_lossy(&raw).to_string();
+    Ok(json!({
+        "title": extract_note_title(&content, &note_path),
+        "path": note_path.display().to_string(),
+        "bytes": raw.len(),
+        "content": content
     }))
 }
 
+/// Path of the note `identifier` names, relative to `notes_dir`. A bare slug becomes
+/// `<slug>.md` the way `save_note` names files; anything else is a path, optionally
+/// prefixed with `notes_dir` as `search_notes` reports it, that must stay inside
+/// `notes_dir` and end in a searchable note extension.
+fn resolve_note_identifier(
+    identifier: &str,
+    notes_dir: &Path,
+) -> Result<PathBuf, ToolDispatchError> {
+    let path = Path::new(identifier);
+    let path = path.strip_prefix(notes_dir).unwrap_or(path);
+    if path.extension().is_none() && path.components().count() == 1 {
+        let slug = note_slug_in_dir(notes_dir, identifier).ok_or_else(|| {
+            ToolDispatchError::invalid_args(
+                READ_NOTE_TOOL_NAME,
+                "identifier must include at least one alphanumeric character",
+            )
+        })?;
+        return Ok(PathBuf::from(format!("{slug}.md")));
+    }
+
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(ToolDispatchError::policy_violation(
+            READ_NOTE_TOOL_NAME,
+            PolicyRule::NotePath,
+            format!("note path `{identifier}` must stay inside the notes directory"),
+        ));
+    }
+    if !is_searchable_note_extension(path) {
+        return Err(ToolDispatchError::policy_violation(
+            READ_NOTE_TOOL_NAME,
+            PolicyRule::NoteTarget,
+            format!("`{identifier}` is not a .md, .markdown, or .txt note"),
+        ));
+    }
+    Ok(path.to_path_buf())
+}
+
 fn create_temp_note_path(notes_dir: &Path, note_slug: &str) -> PathBuf {
     let now_ns = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -931,13 +1939,42 @@ fn write_new_file(path: &Path, content: &str) -> std::io::Result<()> {
     file.write_all(content.as_bytes())
 }
 
-fn normalize_note_title(title: &str) -> Option<String> {
+/// Slug `save_note` names a note file with: the NFC form of `title`, so precomposed and
+/// combining-accent spellings match, cut down to lowercased letters and digits of any
+/// script joined by single `-`.
+pub(crate) fn normalize_note_title(title: &str) -> Option<String> {
+    let title = ComposingNormalizerBorrowed::new_nfc().normalize(title);
+    slugify_note_title(&title, char::is_alphanumeric)
+}
+
+/// Slug older builds named notes with: ASCII letters and digits only.
+fn legacy_note_slug(title: &str) -> Option<String> {
+    slugify_note_title(title, |ch| ch.is_ascii_alphanumeric())
+}
+
+/// Slug of the note file `title` names in `notes_dir`: the current slug, unless only a
+/// note saved under the legacy ASCII slug exists, so older notes are still read,
+/// updated, and deleted instead of duplicated.
+fn note_slug_in_dir(notes_dir: &Path, title: &str) -> Option<String> {
+    let slug = normalize_note_title(title)?;
+    let exists = |slug: &str| fs::symlink_metadata(notes_dir.join(format!("{slug}.md"))).is_ok();
+    if let Some(legacy) = legacy_note_slug(title)
+        && legacy != slug
+        && !exists(&slug)
+        && exists(&legacy)
+    {
+        return Some(legacy);
+    }
+    Some(slug)
+}
+
+fn slugify_note_title(title: &str, keep: impl Fn(char) -> bool) -> Option<String> {
     let mut output = String::new();
     let mut previous_was_dash = false;
 
     for ch in title.chars() {
-        if ch.is_ascii_alphanumeric() {
-            output.push(ch.to_ascii_lowercase());
+        if keep(ch) {
+            output.extend(ch.to_lowercase());
             previous_was_dash = false;
             continue;
         }
@@ -967,17 +2004,25 @@ mod tests {
     use std::fs;
     use std::future::Future;
     use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
-    use reqwest::Url;
     use reqwest::header::{HeaderMap, HeaderValue, LOCATION};
+    use reqwest::{Method, Url};
     use serde_json::{Value, json};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     use super::{
-        FETCH_URL_TOOL_NAME, FetchResponse, FetchUrlArgs, SAVE_NOTE_TOOL_NAME,
-        SEARCH_NOTES_TOOL_NAME, ToolDispatchError, ToolDispatchOutput, ToolRuntimeConfig,
-        dispatch_tool_call as dispatch_tool_call_async, host_allowed, normalize_note_title,
-        resolve_redirect_target, run_fetch_url_with_fetcher, tool_definitions,
+        DELETE_NOTE_TOOL_NAME, FETCH_URL_TOOL_NAME, FetchResponse, FetchUrlArgs, NotesQuota,
+        PolicyRule, READ_NOTE_TOOL_NAME, READ_WORKSPACE_FILE_TOOL_NAME, RUN_COMMAND_TOOL_NAME,
+        SAVE_NOTE_TOOL_NAME, SEARCH_NOTES_TOOL_NAME, SEARCH_WORKSPACE_TOOL_NAME, ToolDispatchError,
+        ToolDispatchOutput, ToolProgress, ToolProgressSink, ToolRuntimeConfig, collect_tool_env,
+        dispatch_tool_call as dispatch_tool_call_async, fetch_url_over_http, host_allowed,
+        normalize_note_title, resolve_redirect_target, run_fetch_url_with_fetcher,
+        tool_definitions, tool_output_schema, validate_tool_output,
     };
+    use crate::http::{SharedHttpClient, StubHttpClient, StubResponse};
+    use crate::json_schema::check_schema;
     use crate::test_support::{remove_dir_if_exists, temp_path};
 
     fn dispatch_tool_call(
@@ -997,7 +2042,7 @@ mod tests {
     }
 
     #[test]
-    fn registry_contains_three_v1_tools() {
+    fn registry_contains_the_v1_tools() {
         let definitions = tool_definitions();
         let names: Vec<_> = definitions.iter().map(|tool| tool.name).collect();
         assert_eq!(
@@ -1005,7 +2050,12 @@ mod tests {
             vec![
                 SEARCH_NOTES_TOOL_NAME,
                 FETCH_URL_TOOL_NAME,
-                SAVE_NOTE_TOOL_NAME
+                SAVE_NOTE_TOOL_NAME,
+                READ_NOTE_TOOL_NAME,
+                DELETE_NOTE_TOOL_NAME,
+                READ_WORKSPACE_FILE_TOOL_NAME,
+                SEARCH_WORKSPACE_TOOL_NAME,
+                RUN_COMMAND_TOOL_NAME
             ]
         );
 
@@ -1024,7 +2074,7 @@ mod tests {
         );
         assert_eq!(
             definitions[2].signature,
-            "save_note(title: string, body: string)"
+            "save_note(title: string, body: string, if_absent?: bool, mode?: create|overwrite|append)"
         );
         assert_eq!(
             definitions[2].description,
@@ -1032,6 +2082,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tool_output_validation_rejects_payloads_that_break_the_schema() {
+        for tool in tool_definitions() {
+            assert_eq!(
+                check_schema(&tool_output_schema(tool.name)),
+                Ok(()),
+                "{}",
+                tool.name
+            );
+        }
+        assert_eq!(
+            validate_tool_output(
+                FETCH_URL_TOOL_NAME,
+                &json!({
+                    "url": "https://example.com",
+                    "final_url": "https://example.com/",
+                    "status_code": 200,
+                    "content_type": null,
+                    "bytes": 5,
+                    "content": "hello"
+                })
+            ),
+            Ok(())
+        );
+
+        let error = validate_tool_output(
+            SAVE_NOTE_TOOL_NAME,
+            &json!({"title": "t", "path": "notes/t.md", "bytes": 1, "status": "deleted"}),
+        )
+        .expect_err("unknown status should fail");
+        assert_eq!(
+            error,
+            ToolDispatchError::InvalidOutput {
+                tool_name: SAVE_NOTE_TOOL_NAME.to_owned(),
+                reason: "/status: \"deleted\" is not one of \"created\", \"overwritten\", \"appended\", \"unchanged\""
+                    .to_owned()
+            }
+        );
+    }
+
     #[test]
     fn dispatch_rejects_unknown_tool_name() {
         let runtime = test_runtime_config("unknown_tool", false);
@@ -1045,6 +2135,120 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tool_progress_reports_percent_and_label() {
+        let progress = ToolProgress {
+            elapsed: Duration::from_millis(2_400),
+            bytes_received: 348_160,
+            bytes_total: Some(1_048_576),
+        };
+        assert_eq!(progress.percent(), Some(33));
+        assert_eq!(progress.label(), "2.4s · 340.0 KB / 1.0 MB");
+
+        let unknown_total = ToolProgress {
+            bytes_total: None,
+            bytes_received: 512,
+            ..progress
+        };
+        assert_eq!(unknown_total.percent(), None);
+        assert_eq!(unknown_total.label(), "2.4s · 512 B");
+    }
+
+    #[test]
+    fn fetch_reports_download_progress_while_the_body_streams() {
+        block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .expect("listener should bind");
+            let url = Url::parse(&format!(
+                "http://{}/large.txt",
+                listener.local_addr().expect("local addr")
+            ))
+            .expect("url");
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.expect("accept");
+                let mut request = [0_u8; 1024];
+                let _ = stream.read(&mut request).await;
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: 3000\r\n\r\n",
+                    )
+                    .await
+                    .expect("headers");
+                for _ in 0..3 {
+                    stream.write_all(&[b'x'; 1000]).await.expect("chunk");
+                    stream.flush().await.expect("flush");
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                }
+            });
+
+            let updates = Arc::new(Mutex::new(Vec::new()));
+            let sink_updates = Arc::clone(&updates);
+            let sink = ToolProgressSink::new(move |progress| {
+                sink_updates.lock().expect("updates lock").push(progress);
+            });
+            let fetched = fetch_url_over_http(
+                url,
+                Vec::new(),
+                false,
+                5_000,
+                10_000,
+                Some(sink),
+                SharedHttpClient::default(),
+            )
+            .await
+            .expect("fetch should succeed");
+
+            assert_eq!(fetched.body.len(), 3000);
+            let updates = updates.lock().expect("updates lock");
+            assert!(!updates.is_empty(), "expected progress while streaming");
+            assert!(
+                updates
+                    .iter()
+                    .all(|update| update.bytes_total == Some(3000))
+            );
+            assert!(
+                updates
+                    .windows(2)
+                    .all(|pair| pair[0].bytes_received <= pair[1].bytes_received)
+            );
+        });
+    }
+
+    #[test]
+    fn dispatch_rejects_tools_disabled_by_enabled_tools() {
+        let runtime = test_runtime_config("disabled_tool", false)
+            .with_enabled_tools(&[SEARCH_NOTES_TOOL_NAME.to_owned()]);
+        let error = dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({"title": "t", "body": "b"}),
+            &runtime,
+        )
+        .expect_err("disabled tool should fail");
+
+        let ToolDispatchError::PolicyViolation { reason, .. } = error else {
+            panic!("expected policy violation");
+        };
+        assert!(reason.contains("ENABLED_TOOLS"));
+    }
+
+    #[test]
+    fn dispatch_refuses_network_tools_offline() {
+        let runtime = test_runtime_config("offline_tool", false).with_offline(true);
+        let error = dispatch_tool_call(
+            FETCH_URL_TOOL_NAME,
+            json!({"url": "https://example.com/"}),
+            &runtime,
+        )
+        .expect_err("fetch_url should be refused offline");
+
+        let ToolDispatchError::PolicyViolation { rule, reason, .. } = error else {
+            panic!("expected policy violation");
+        };
+        assert_eq!(rule, PolicyRule::Offline);
+        assert!(reason.contains("AGENT_OFFLINE"), "{reason}");
+    }
+
     #[test]
     fn dispatch_search_notes_returns_ranked_results_with_limit() {
         let runtime = test_runtime_config("search_notes_ranked", false);
@@ -1092,16 +2296,47 @@ mod tests {
         assert!(
             results[0]
                 .get("score")
-                .and_then(|value| value.as_u64())
-                .expect("score should be u64")
+                .and_then(|value| value.as_f64())
+                .expect("score should be a number")
                 >= results[1]
                     .get("score")
-                    .and_then(|value| value.as_u64())
-                    .expect("score should be u64")
+                    .and_then(|value| value.as_f64())
+                    .expect("score should be a number")
+        );
+        assert_eq!(
+            results[1].get("snippet"),
+            Some(&json!("Tokio helps with rust async workflows."))
         );
+        assert_eq!(
+            results[1].get("highlights"),
+            Some(&json!([{"start": 17, "end": 21}]))
+        );
+        assert_eq!(results[0].get("backlinks"), Some(&json!([])));
 
-        cleanup_dir(&runtime.notes_dir);
-    }
+        dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({"title": "Links", "body": "See [[Rust Guide]] for ownership."}),
+            &runtime,
+        )
+        .expect("linking note should save");
+        let output = dispatch_tool_call(
+            SEARCH_NOTES_TOOL_NAME,
+            json!({"query": "ownership", "limit": 5}),
+            &runtime,
+        )
+        .expect("should dispatch");
+        let backlinks_of = |title: &str| {
+            output.payload["results"]
+                .as_array()
+                .and_then(|results| results.iter().find(|result| result["title"] == title))
+                .map(|result| result["backlinks"].clone())
+        };
+        let linked_from = runtime.notes_dir.join("links.md").display().to_string();
+        assert_eq!(backlinks_of("Rust Guide"), Some(json!([linked_from])));
+        assert_eq!(backlinks_of("Links"), Some(json!([])));
+
+        cleanup_dir(&runtime.notes_dir);
+    }
 
     #[test]
     fn dispatch_search_notes_returns_empty_when_notes_dir_is_missing() {
@@ -1237,6 +2472,196 @@ mod tests {
         cleanup_dir(&runtime.notes_dir);
     }
 
+    #[test]
+    fn dispatch_save_note_reports_identical_rewrite_as_unchanged() {
+        let runtime = test_runtime_config("save_note_unchanged", false);
+        cleanup_dir(&runtime.notes_dir);
+        let args = json!({
+            "title": "daily note",
+            "body": "version one"
+        });
+
+        let first = dispatch_tool_call(SAVE_NOTE_TOOL_NAME, args.clone(), &runtime)
+            .expect("initial write should succeed");
+        let retry = dispatch_tool_call(SAVE_NOTE_TOOL_NAME, args, &runtime)
+            .expect("identical rewrite should succeed without overwrite confirmation");
+
+        assert_eq!(first.payload.get("status"), Some(&json!("created")));
+        assert_eq!(retry.payload.get("status"), Some(&json!("unchanged")));
+        assert_eq!(retry.payload.get("path"), first.payload.get("path"));
+        assert_eq!(retry.payload.get("bytes"), first.payload.get("bytes"));
+
+        cleanup_dir(&runtime.notes_dir);
+    }
+
+    #[test]
+    fn dispatch_save_note_records_the_author_of_each_saved_section() {
+        let runtime =
+            test_runtime_config("save_note_author", false).with_note_author(Some("ada".to_owned()));
+        cleanup_dir(&runtime.notes_dir);
+        dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({ "title": "Findings", "body": "- first lead" }),
+            &runtime,
+        )
+        .expect("save should succeed");
+        let runtime = runtime.with_note_author(Some("grace".to_owned()));
+        dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({ "title": "Findings", "body": "- second lead", "mode": "append" }),
+            &runtime,
+        )
+        .expect("append should succeed");
+
+        let content = fs::read_to_string(runtime.notes_dir.join("findings.md")).expect("note");
+        assert_eq!(
+            content,
+            "# Findings\n<!-- author: ada -->\n\n- first lead\n\n<!-- author: grace -->\n- second lead\n"
+        );
+        assert_eq!(
+            super::extract_note_title(&content, Path::new("findings.md")),
+            "Findings"
+        );
+
+        cleanup_dir(&runtime.notes_dir);
+    }
+
+    #[test]
+    fn dispatch_save_note_append_mode_accumulates_without_overwrite_confirmation() {
+        let runtime = test_runtime_config("save_note_append", false);
+        cleanup_dir(&runtime.notes_dir);
+        let append = |body: &str| {
+            dispatch_tool_call(
+                SAVE_NOTE_TOOL_NAME,
+                json!({ "title": "Findings", "body": body, "mode": "append" }),
+                &runtime,
+            )
+        };
+
+        let first = append("- first lead").expect("append should create a missing note");
+        assert_eq!(first.payload.get("status"), Some(&json!("created")));
+        let second = append("- second lead\n").expect("append should not need overwrite");
+        assert_eq!(second.payload.get("status"), Some(&json!("appended")));
+
+        let path = runtime.notes_dir.join("findings.md");
+        let content = fs::read_to_string(&path).expect("note should exist");
+        assert_eq!(content, "# Findings\n\n- first lead\n\n- second lead\n");
+        assert_eq!(second.payload.get("bytes"), Some(&json!(content.len())));
+
+        let error = dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({ "title": "Findings", "body": "fresh", "mode": "create" }),
+            &runtime,
+        )
+        .expect_err("create mode should not replace the note");
+        let ToolDispatchError::PolicyViolation { rule, reason, .. } = error else {
+            panic!("expected policy violation");
+        };
+        assert_eq!(rule, PolicyRule::Overwrite);
+        assert!(reason.contains("mode is `create`"), "{reason}");
+
+        let error = dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({ "title": "Findings", "body": "x", "mode": "append", "if_absent": true }),
+            &runtime,
+        )
+        .expect_err("if_absent conflicts with append");
+        assert!(
+            matches!(error, ToolDispatchError::InvalidArgs { .. }),
+            "{error:?}"
+        );
+        assert_eq!(
+            fs::read_to_string(&path).expect("note should exist"),
+            content
+        );
+
+        cleanup_dir(&runtime.notes_dir);
+    }
+
+    #[test]
+    fn dispatch_save_note_enforces_notes_quota() {
+        let runtime = test_runtime_config("save_note_quota", true).with_notes_quota(NotesQuota {
+            max_files: 2,
+            max_total_bytes: 50,
+            max_note_bytes: 40,
+        });
+        cleanup_dir(&runtime.notes_dir);
+        let save = |title: &str, body: &str, mode: &str| {
+            dispatch_tool_call(
+                SAVE_NOTE_TOOL_NAME,
+                json!({ "title": title, "body": body, "mode": mode }),
+                &runtime,
+            )
+        };
+        let quota_reason = |error: ToolDispatchError| {
+            let ToolDispatchError::PolicyViolation { rule, reason, .. } = error else {
+                panic!("expected policy violation, got {error:?}");
+            };
+            assert_eq!(rule, PolicyRule::NoteQuota);
+            reason
+        };
+
+        save("One", "first", "create").expect("first note fits");
+        let reason = quota_reason(
+            save("One", &"x".repeat(40), "append").expect_err("append past the note cap"),
+        );
+        assert!(reason.contains("NOTES_MAX_NOTE_BYTES (40)"), "{reason}");
+        save("Two", "second", "create").expect("second note fits");
+        let reason = quota_reason(save("Three", "third", "create").expect_err("third note"));
+        assert!(reason.contains("NOTES_MAX_FILES"), "{reason}");
+        save("Two", "more", "append").expect("appending needs no new file");
+        let reason = quota_reason(
+            save("Two", &"y".repeat(30), "overwrite").expect_err("total past the cap"),
+        );
+        assert!(reason.contains("NOTES_MAX_TOTAL_BYTES (50)"), "{reason}");
+        assert_eq!(
+            fs::read_to_string(runtime.notes_dir.join("two.md")).expect("note"),
+            "# Two\n\nsecond\n\nmore\n"
+        );
+
+        cleanup_dir(&runtime.notes_dir);
+    }
+
+    #[test]
+    fn dispatch_save_note_if_absent_never_replaces_different_content() {
+        let runtime = test_runtime_config("save_note_if_absent", true);
+        cleanup_dir(&runtime.notes_dir);
+
+        let created = dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({
+                "title": "daily note",
+                "body": "version one",
+                "if_absent": true
+            }),
+            &runtime,
+        )
+        .expect("initial write should succeed");
+        assert_eq!(created.payload.get("status"), Some(&json!("created")));
+
+        let error = dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({
+                "title": "daily note",
+                "body": "version two",
+                "if_absent": true
+            }),
+            &runtime,
+        )
+        .expect_err("if_absent should block replacing different content");
+
+        let ToolDispatchError::PolicyViolation { reason, .. } = error else {
+            panic!("expected policy violation");
+        };
+        assert!(reason.contains("if_absent"));
+
+        let note_path = runtime.notes_dir.join("daily-note.md");
+        let file_contents = fs::read_to_string(note_path).expect("note should exist");
+        assert!(file_contents.contains("version one"));
+
+        cleanup_dir(&runtime.notes_dir);
+    }
+
     #[test]
     fn dispatch_save_note_allows_overwrite_when_confirmed() {
         let runtime = test_runtime_config("save_note_overwrite_allowed", true);
@@ -1311,6 +2736,378 @@ mod tests {
         cleanup_dir(&target_dir);
     }
 
+    #[test]
+    fn dispatch_delete_note_requires_the_flag_and_confirmation() {
+        let runtime = test_runtime_config("delete_note", false);
+        cleanup_dir(&runtime.notes_dir);
+        fs::create_dir_all(&runtime.notes_dir).expect("notes dir should be creatable");
+        let note_path = runtime.notes_dir.join("daily-note.md");
+        fs::write(&note_path, "# daily note\n\nhello\n").expect("note should be written");
+
+        let error = dispatch_tool_call(
+            DELETE_NOTE_TOOL_NAME,
+            json!({ "title": "daily note", "confirm": true }),
+            &runtime,
+        )
+        .expect_err("deletes are off by default");
+        let ToolDispatchError::PolicyViolation { rule, reason, .. } = error else {
+            panic!("expected policy violation");
+        };
+        assert_eq!(rule, PolicyRule::Delete);
+        assert!(reason.contains("SAVE_NOTE_ALLOW_DELETE"));
+
+        let runtime = runtime.with_save_note_allow_delete(true);
+        let error = dispatch_tool_call(
+            DELETE_NOTE_TOOL_NAME,
+            json!({ "title": "daily note", "confirm": false }),
+            &runtime,
+        )
+        .expect_err("unconfirmed delete should be blocked");
+        let ToolDispatchError::PolicyViolation { reason, .. } = error else {
+            panic!("expected policy violation");
+        };
+        assert!(reason.contains("without confirmation"));
+        assert!(note_path.exists());
+
+        let output = dispatch_tool_call(
+            DELETE_NOTE_TOOL_NAME,
+            json!({ "title": "daily note", "confirm": true }),
+            &runtime,
+        )
+        .expect("confirmed delete should succeed");
+        assert_eq!(output.payload["status"], json!("deleted"));
+        assert!(!note_path.exists());
+
+        let output = dispatch_tool_call(
+            DELETE_NOTE_TOOL_NAME,
+            json!({ "title": "daily note", "confirm": true }),
+            &runtime,
+        )
+        .expect("repeated delete should succeed");
+        assert_eq!(output.payload["status"], json!("absent"));
+
+        cleanup_dir(&runtime.notes_dir);
+    }
+
+    #[test]
+    fn dispatch_delete_note_rejects_symlink_and_non_file_targets() {
+        use std::os::unix::fs::symlink;
+
+        let runtime =
+            test_runtime_config("delete_note_targets", false).with_save_note_allow_delete(true);
+        cleanup_dir(&runtime.notes_dir);
+        fs::create_dir_all(runtime.notes_dir.join("folder.md")).expect("dir should be creatable");
+        let target_dir = temp_notes_dir("delete_note_symlink_target");
+        cleanup_dir(&target_dir);
+        fs::create_dir_all(&target_dir).expect("target dir should be creatable");
+        let target_file = target_dir.join("outside.md");
+        fs::write(&target_file, "keep me").expect("target file should be writable");
+        symlink(&target_file, runtime.notes_dir.join("linked.md")).expect("symlink");
+
+        for (title, expected) in [("linked", "symlink"), ("folder", "non-file")] {
+            let error = dispatch_tool_call(
+                DELETE_NOTE_TOOL_NAME,
+                json!({ "title": title, "confirm": true }),
+                &runtime,
+            )
+            .expect_err("unsafe target should be rejected");
+            let ToolDispatchError::PolicyViolation { rule, reason, .. } = error else {
+                panic!("expected policy violation");
+            };
+            assert_eq!(rule, PolicyRule::NoteTarget);
+            assert!(reason.contains(expected), "{reason}");
+        }
+        assert!(target_file.exists());
+
+        cleanup_dir(&runtime.notes_dir);
+        cleanup_dir(&target_dir);
+    }
+
+    #[test]
+    fn dispatch_read_note_accepts_slugs_and_search_paths() {
+        let runtime = test_runtime_config("read_note", false);
+        cleanup_dir(&runtime.notes_dir);
+        fs::create_dir_all(&runtime.notes_dir).expect("notes dir should be creatable");
+        let body = "# Daily Note\n\nfirst line\nsecond line\n";
+        fs::write(runtime.notes_dir.join("daily-note.md"), body).expect("note should be written");
+
+        let by_slug = dispatch_tool_call(
+            READ_NOTE_TOOL_NAME,
+            json!({ "identifier": "Daily Note" }),
+            &runtime,
+        )
+        .expect("slug should resolve");
+        assert_eq!(by_slug.payload["title"], json!("Daily Note"));
+        assert_eq!(by_slug.payload["content"], json!(body));
+        assert_eq!(by_slug.payload["bytes"], json!(body.len()));
+
+        let search_path = runtime.notes_dir.join("daily-note.md");
+        let by_path = dispatch_tool_call(
+            READ_NOTE_TOOL_NAME,
+            json!({ "identifier": search_path.display().to_string() }),
+            &runtime,
+        )
+        .expect("search_notes path should resolve");
+        assert_eq!(by_path.payload, by_slug.payload);
+
+        let missing = dispatch_tool_call(
+            READ_NOTE_TOOL_NAME,
+            json!({ "identifier": "weekly.md" }),
+            &runtime,
+        )
+        .expect_err("missing note should fail");
+        assert!(matches!(missing, ToolDispatchError::InvalidArgs { .. }));
+
+        cleanup_dir(&runtime.notes_dir);
+    }
+
+    #[test]
+    fn dispatch_read_note_stays_inside_notes_dir() {
+        use std::os::unix::fs::symlink;
+
+        let runtime = test_runtime_config("read_note_confined", false);
+        cleanup_dir(&runtime.notes_dir);
+        fs::create_dir_all(&runtime.notes_dir).expect("notes dir should be creatable");
+        let outside_dir = temp_notes_dir("read_note_outside");
+        cleanup_dir(&outside_dir);
+        fs::create_dir_all(&outside_dir).expect("outside dir should be creatable");
+        fs::write(outside_dir.join("secret.md"), "secret").expect("outside note");
+        symlink(&outside_dir, runtime.notes_dir.join("linked")).expect("symlink");
+
+        for (identifier, expected_rule) in [
+            ("../read_note_outside/secret.md", PolicyRule::NotePath),
+            ("/etc/passwd.md", PolicyRule::NotePath),
+            ("linked/secret.md", PolicyRule::NotePath),
+            ("config.toml", PolicyRule::NoteTarget),
+        ] {
+            let error = dispatch_tool_call(
+                READ_NOTE_TOOL_NAME,
+                json!({ "identifier": identifier }),
+                &runtime,
+            )
+            .expect_err("read outside the notes dir should fail");
+            let ToolDispatchError::PolicyViolation { rule, .. } = error else {
+                panic!("expected policy violation for `{identifier}`, got {error:?}");
+            };
+            assert_eq!(rule, expected_rule, "{identifier}");
+        }
+
+        cleanup_dir(&runtime.notes_dir);
+        cleanup_dir(&outside_dir);
+    }
+
+    #[test]
+    fn dispatch_read_workspace_file_caps_bytes_and_stays_inside_the_root() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_notes_dir("read_workspace_file");
+        let outside_dir = temp_notes_dir("read_workspace_file_outside");
+        cleanup_dir(&root);
+        cleanup_dir(&outside_dir);
+        fs::create_dir_all(root.join("src")).expect("workspace should be creatable");
+        fs::create_dir_all(root.join(".git")).expect("hidden dir should be creatable");
+        fs::create_dir_all(&outside_dir).expect("outside dir should be creatable");
+        fs::write(root.join("src/main.rs"), "fn main() {}\n// é\n").expect("source");
+        fs::write(root.join(".git/config.md"), "hidden").expect("hidden file");
+        fs::write(root.join("build.sh"), "echo hi").expect("script");
+        fs::write(outside_dir.join("secret.rs"), "secret").expect("outside file");
+        symlink(&outside_dir, root.join("linked")).expect("symlink");
+        let runtime = test_runtime_config("read_workspace_file_notes", false)
+            .with_workspace_root(root.clone())
+            .with_read_workspace_file_limits(vec!["md".to_owned(), "rs".to_owned()], 17);
+
+        let full = dispatch_tool_call(
+            READ_WORKSPACE_FILE_TOOL_NAME,
+            json!({ "path": "./src/main.rs", "max_bytes": 1_000 }),
+            &runtime,
+        )
+        .expect("read should be capped, not refused");
+        assert_eq!(full.payload["path"], json!("src/main.rs"));
+        assert_eq!(full.payload["total_bytes"], json!(19));
+        assert_eq!(full.payload["truncated"], json!(true));
+        // The 17-byte cap splits `é`, so the partial character is dropped.
+        assert_eq!(full.payload["bytes"], json!(16));
+        assert_eq!(full.payload["content"], json!("fn main() {}\n// "));
+
+        let absolute = root.join("src/main.rs").display().to_string();
+        let short = dispatch_tool_call(
+            READ_WORKSPACE_FILE_TOOL_NAME,
+            json!({ "path": absolute, "max_bytes": 2 }),
+            &runtime,
+        )
+        .expect("absolute paths under the root are accepted");
+        assert_eq!(short.payload["content"], json!("fn"));
+
+        for (path, expected_rule) in [
+            (
+                "../read_workspace_file_outside/secret.rs",
+                PolicyRule::WorkspacePath,
+            ),
+            ("/etc/hosts.md", PolicyRule::WorkspacePath),
+            (".git/config.md", PolicyRule::WorkspacePath),
+            ("linked/secret.rs", PolicyRule::WorkspacePath),
+            ("build.sh", PolicyRule::WorkspaceFileType),
+            ("src", PolicyRule::WorkspaceFileType),
+        ] {
+            let error = dispatch_tool_call(
+                READ_WORKSPACE_FILE_TOOL_NAME,
+                json!({ "path": path, "max_bytes": 100 }),
+                &runtime,
+            )
+            .expect_err("read outside the allowlist should fail");
+            let ToolDispatchError::PolicyViolation { rule, .. } = error else {
+                panic!("expected policy violation for `{path}`, got {error:?}");
+            };
+            assert_eq!(rule, expected_rule, "{path}");
+        }
+
+        cleanup_dir(&root);
+        cleanup_dir(&outside_dir);
+    }
+
+    #[test]
+    fn dispatch_search_workspace_matches_substrings_and_regexes_within_caps() {
+        let root = temp_notes_dir("search_workspace");
+        cleanup_dir(&root);
+        fs::create_dir_all(root.join("src/tools")).expect("workspace should be creatable");
+        fs::create_dir_all(root.join("target")).expect("build dir should be creatable");
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    run_Agent();\n}\n",
+        )
+        .expect("main");
+        fs::write(root.join("src/tools/mod.rs"), "pub fn run_agent() {}\n").expect("tools");
+        fs::write(root.join("README.md"), "Run the agent.\n").expect("readme");
+        fs::write(root.join("target/out.rs"), "run_agent").expect("build output");
+        fs::write(root.join(".hidden.rs"), "run_agent").expect("hidden file");
+        let runtime = test_runtime_config("search_workspace_notes", false)
+            .with_workspace_root(root.clone())
+            .with_read_workspace_file_limits(vec!["md".to_owned(), "rs".to_owned()], 1_024)
+            .with_search_workspace_limits(10, 1_024);
+
+        let substring = dispatch_tool_call(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            json!({ "pattern": "RUN_agent", "glob": "", "max_results": 50 }),
+            &runtime,
+        )
+        .expect("substring search should succeed");
+        assert_eq!(substring.payload["files_scanned"], json!(3));
+        assert_eq!(substring.payload["truncated"], json!(false));
+        assert_eq!(
+            substring.payload["results"],
+            json!([
+                {"path": "src/main.rs", "line": 2, "snippet": "run_Agent();"},
+                {"path": "src/tools/mod.rs", "line": 1, "snippet": "pub fn run_agent() {}"}
+            ])
+        );
+
+        let regex = dispatch_tool_call(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            json!({ "pattern": "/^pub fn \\w+/", "glob": "src/**/*.rs", "max_results": 5 }),
+            &runtime,
+        )
+        .expect("regex search should succeed");
+        assert_eq!(
+            regex.payload["results"][0]["path"],
+            json!("src/tools/mod.rs")
+        );
+        assert_eq!(regex.payload["results"].as_array().map(Vec::len), Some(1));
+
+        let capped = dispatch_tool_call(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            json!({ "pattern": "run", "glob": "*.rs", "max_results": 1 }),
+            &runtime,
+        )
+        .expect("capped search should succeed");
+        assert_eq!(capped.payload["truncated"], json!(true));
+        assert_eq!(capped.payload["results"].as_array().map(Vec::len), Some(1));
+
+        let byte_capped = dispatch_tool_call(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            json!({ "pattern": "run", "glob": "", "max_results": 10 }),
+            &runtime.clone().with_search_workspace_limits(10, 20),
+        )
+        .expect("byte-capped search should succeed");
+        assert_eq!(byte_capped.payload["files_scanned"], json!(1));
+        assert_eq!(byte_capped.payload["truncated"], json!(true));
+
+        let error = dispatch_tool_call(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            json!({ "pattern": "/(/", "glob": "", "max_results": 10 }),
+            &runtime,
+        )
+        .expect_err("invalid regex should fail");
+        assert!(matches!(error, ToolDispatchError::InvalidArgs { .. }));
+
+        cleanup_dir(&root);
+    }
+
+    #[test]
+    fn dispatch_run_command_runs_allowlisted_programs_in_the_workspace_root() {
+        let root = temp_notes_dir("run_command");
+        cleanup_dir(&root);
+        fs::create_dir_all(&root).expect("workspace should be creatable");
+        let root = fs::canonicalize(&root).expect("workspace should resolve");
+        let runtime = test_runtime_config("run_command_notes", false)
+            .with_env(collect_tool_env(&["PATH".to_owned()]))
+            .with_workspace_root(root.clone())
+            .with_run_command_policy(vec!["sh".to_owned()], 8);
+
+        let output = dispatch_tool_call(
+            RUN_COMMAND_TOOL_NAME,
+            json!({
+                "command": "sh",
+                "args": ["-c", "pwd; echo oops >&2; exit 3"]
+            }),
+            &runtime,
+        )
+        .expect("allowlisted command should run");
+        assert_eq!(output.payload["exit_code"], json!(3));
+        assert_eq!(output.payload["success"], json!(false));
+        assert_eq!(output.payload["stderr"], json!("oops\n"));
+        assert_eq!(output.payload["stderr_truncated"], json!(false));
+        let root_display = format!("{}\n", root.display());
+        let expected_stdout = &root_display[..root_display.len().min(8)];
+        assert_eq!(output.payload["stdout"], json!(expected_stdout));
+        assert_eq!(
+            output.payload["stdout_truncated"],
+            json!(root_display.len() > 8)
+        );
+
+        for (args, expected_rule) in [
+            (json!({ "command": "ls", "args": [] }), PolicyRule::Command),
+            (
+                json!({ "command": "sh", "args": ["../escape.sh"] }),
+                PolicyRule::WorkspacePath,
+            ),
+            (
+                json!({ "command": "sh", "args": ["--file=/etc/passwd"] }),
+                PolicyRule::WorkspacePath,
+            ),
+            (
+                json!({ "command": "sh", "args": ["-C/etc"] }),
+                PolicyRule::WorkspacePath,
+            ),
+            (
+                json!({ "command": "sh", "args": ["-xp../outside"] }),
+                PolicyRule::WorkspacePath,
+            ),
+            (
+                json!({ "command": "sh", "args": ["-I~/include"] }),
+                PolicyRule::WorkspacePath,
+            ),
+        ] {
+            let error = dispatch_tool_call(RUN_COMMAND_TOOL_NAME, args.clone(), &runtime)
+                .expect_err("command outside the policy should fail");
+            let ToolDispatchError::PolicyViolation { rule, .. } = error else {
+                panic!("expected policy violation for {args}, got {error:?}");
+            };
+            assert_eq!(rule, expected_rule, "{args}");
+        }
+
+        cleanup_dir(&root);
+    }
+
     #[test]
     fn dispatch_save_note_rejects_title_without_alphanumeric_characters() {
         let runtime = test_runtime_config("save_note_bad_title", false);
@@ -1334,6 +3131,56 @@ mod tests {
     fn normalize_note_title_converts_text_to_safe_slug() {
         let slug = normalize_note_title("  Daily_Note: Rust v1  ").expect("title should normalize");
         assert_eq!(slug, "daily-note-rust-v1");
+        let slug = normalize_note_title("Møte: Ærlig Tale").expect("title should normalize");
+        assert_eq!(slug, "møte-ærlig-tale");
+        assert_eq!(
+            normalize_note_title("日本語 メモ").as_deref(),
+            Some("日本語-メモ")
+        );
+        assert_eq!(
+            normalize_note_title("Cafe\u{301} notes"),
+            normalize_note_title("Café notes")
+        );
+    }
+
+    #[test]
+    fn notes_saved_under_the_legacy_ascii_slug_are_still_found() {
+        let runtime =
+            test_runtime_config("legacy_note_slug", true).with_save_note_allow_delete(true);
+        cleanup_dir(&runtime.notes_dir);
+        fs::create_dir_all(&runtime.notes_dir).expect("notes dir should be creatable");
+        let legacy_path = runtime.notes_dir.join("mte-notes.md");
+        fs::write(&legacy_path, "# Møte notes\n\nold\n").expect("note should be written");
+
+        let output = dispatch_tool_call(
+            READ_NOTE_TOOL_NAME,
+            json!({ "identifier": "Møte notes" }),
+            &runtime,
+        )
+        .expect("legacy note should be read");
+        assert_eq!(output.payload["content"], json!("# Møte notes\n\nold\n"));
+
+        dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({ "title": "Møte notes", "body": "new" }),
+            &runtime,
+        )
+        .expect("legacy note should be overwritten");
+        assert!(
+            fs::read_to_string(&legacy_path)
+                .expect("legacy note should remain")
+                .contains("new")
+        );
+        assert!(!runtime.notes_dir.join("møte-notes.md").exists());
+
+        dispatch_tool_call(
+            DELETE_NOTE_TOOL_NAME,
+            json!({ "title": "Møte notes", "confirm": true }),
+            &runtime,
+        )
+        .expect("legacy note should be deleted");
+        assert!(!legacy_path.exists());
+        cleanup_dir(&runtime.notes_dir);
     }
 
     #[test]
@@ -1471,6 +3318,71 @@ mod tests {
         assert!(reason.contains("FETCH_URL_MAX_BYTES"));
     }
 
+    #[test]
+    fn dispatch_fetch_url_follows_allowlisted_redirects_through_the_http_client() {
+        let stub = StubHttpClient::new();
+        stub.respond(
+            Method::GET,
+            "https://example.com/start",
+            StubResponse::redirect("https://docs.rs/page"),
+        )
+        .respond(
+            Method::GET,
+            "https://docs.rs/page",
+            StubResponse::new(reqwest::StatusCode::OK, "hello")
+                .with_header("content-type", "text/plain"),
+        )
+        .respond(
+            Method::GET,
+            "https://example.com/away",
+            StubResponse::redirect("https://evil.example.net/"),
+        );
+        let mut runtime = test_runtime_config("fetch_url_stub", false)
+            .with_http_client(SharedHttpClient::new(stub.clone()));
+        runtime.fetch_url_follow_redirects = true;
+
+        let output = dispatch_tool_call(
+            FETCH_URL_TOOL_NAME,
+            json!({ "url": "https://example.com/start" }),
+            &runtime,
+        )
+        .expect("redirect within the allowlist should be followed");
+        assert_eq!(output.payload["final_url"], "https://docs.rs/page");
+        assert_eq!(output.payload["content"], "hello");
+
+        let error = dispatch_tool_call(
+            FETCH_URL_TOOL_NAME,
+            json!({ "url": "https://example.com/away" }),
+            &runtime,
+        )
+        .expect_err("redirect off the allowlist should fail");
+        assert!(
+            matches!(
+                error,
+                ToolDispatchError::PolicyViolation {
+                    rule: PolicyRule::Domain,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+        assert_eq!(
+            stub.requests()
+                .iter()
+                .map(|request| request.url.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "https://example.com/start",
+                "https://docs.rs/page",
+                "https://example.com/away"
+            ]
+        );
+        assert_eq!(
+            stub.requests()[0].timeout,
+            Some(Duration::from_millis(5_000))
+        );
+    }
+
     #[test]
     fn resolve_redirect_target_allows_relative_location_on_allowlisted_host() {
         let mut headers = HeaderMap::new();
@@ -1495,9 +3407,10 @@ mod tests {
         let error = resolve_redirect_target(&current, &headers, &test_allowlist())
             .expect_err("disallowed redirect host should fail");
 
-        let ToolDispatchError::PolicyViolation { reason, .. } = error else {
+        let ToolDispatchError::PolicyViolation { rule, reason, .. } = error else {
             panic!("expected policy violation");
         };
+        assert_eq!(rule, PolicyRule::Domain);
         assert!(reason.contains("redirect target host"));
         assert!(reason.contains("allowlist"));
     }
@@ -1511,12 +3424,106 @@ mod tests {
         let error = resolve_redirect_target(&current, &headers, &test_allowlist())
             .expect_err("non-http redirect scheme should fail");
 
-        let ToolDispatchError::PolicyViolation { reason, .. } = error else {
+        let ToolDispatchError::PolicyViolation { rule, reason, .. } = error else {
             panic!("expected policy violation");
         };
+        assert_eq!(rule, PolicyRule::Scheme);
         assert!(reason.contains("redirect target scheme"));
     }
 
+    #[test]
+    fn runtime_resolves_relative_notes_dir_against_working_dir() {
+        let runtime = ToolRuntimeConfig::new(
+            test_allowlist(),
+            PathBuf::from("notes"),
+            false,
+            5_000,
+            100_000,
+            false,
+        );
+        assert_eq!(runtime.notes_dir_path(), PathBuf::from("notes"));
+
+        let working_dir = temp_path("tools_working_dir");
+        let runtime = runtime.with_working_dir(working_dir.clone());
+        assert_eq!(runtime.notes_dir_path(), working_dir.join("notes"));
+
+        let absolute = working_dir.join("elsewhere");
+        assert_eq!(runtime.resolve_path(&absolute), absolute);
+    }
+
+    #[test]
+    fn runtime_timeout_overrides_replace_the_global_deadline_per_tool() {
+        let runtime = ToolRuntimeConfig::new(
+            test_allowlist(),
+            PathBuf::from("notes"),
+            false,
+            5_000,
+            100_000,
+            false,
+        )
+        .with_tool_timeout_overrides(
+            [(FETCH_URL_TOOL_NAME.to_owned(), 30_000)]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!(runtime.timeout_ms_for(FETCH_URL_TOOL_NAME), 30_000);
+        assert_eq!(runtime.timeout_ms_for(SEARCH_NOTES_TOOL_NAME), 5_000);
+    }
+
+    #[test]
+    fn dispatch_save_note_writes_under_working_dir() {
+        let working_dir = temp_path("tools_save_note_working_dir");
+        let runtime = ToolRuntimeConfig::new(
+            test_allowlist(),
+            PathBuf::from("notes"),
+            false,
+            5_000,
+            100_000,
+            false,
+        )
+        .with_working_dir(working_dir.clone());
+
+        dispatch_tool_call(
+            SAVE_NOTE_TOOL_NAME,
+            json!({"title": "Working dir", "body": "hello"}),
+            &runtime,
+        )
+        .expect("save_note should succeed");
+
+        assert!(working_dir.join("notes/working-dir.md").is_file());
+        cleanup_dir(&working_dir);
+    }
+
+    #[test]
+    fn runtime_command_uses_only_configured_env_and_working_dir() {
+        let working_dir = temp_path("tools_command_working_dir");
+        let env = [("MJOLNE_TOOL_TEST".to_owned(), "1".to_owned())]
+            .into_iter()
+            .collect();
+        let runtime = test_runtime_config("command_env", false)
+            .with_working_dir(working_dir.clone())
+            .with_env(env);
+
+        let command = runtime.command("true");
+        let command = command.as_std();
+        assert_eq!(command.get_current_dir(), Some(working_dir.as_path()));
+        let envs = command
+            .get_envs()
+            .map(|(key, value)| (key.to_owned(), value.map(ToOwned::to_owned)))
+            .collect::<Vec<_>>();
+        assert_eq!(envs, vec![("MJOLNE_TOOL_TEST".into(), Some("1".into()))]);
+    }
+
+    #[test]
+    fn collect_tool_env_skips_unset_variables() {
+        let env = collect_tool_env(&[
+            "PATH".to_owned(),
+            "MJOLNE_TOOL_ENV_SHOULD_NOT_EXIST".to_owned(),
+        ]);
+        assert_eq!(env.keys().collect::<Vec<_>>(), vec!["PATH"]);
+    }
+
     fn test_allowlist() -> Vec<String> {
         vec!["example.com".to_owned(), "docs.rs".to_owned()]
     }
@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use super::{is_searchable_note_extension, normalize_note_title};
+
+/// The backlinks index, kept at the top of the notes directory. It only holds data
+/// derived from the notes, so a missing, unreadable, or outdated index is rebuilt from disk.
+pub(crate) const BACKLINKS_INDEX_FILE: &str = ".backlinks.json";
+
+/// Serializes read-modify-write updates of the index between concurrent tool calls.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Link keys of each note's `[[wikilinks]]`, by note file name. A key is the slug of
+/// the link target, which is how `save_note` names files; it is resolved against the
+/// notes present when backlinks are read, so links to notes saved later still count.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct BacklinksIndex {
+    links: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl BacklinksIndex {
+    /// An index of `notes`, given as `(file name, content)`.
+    pub(crate) fn from_notes<'a>(notes: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        Self {
+            links: notes
+                .into_iter()
+                .filter_map(|(file, content)| {
+                    let keys = link_keys(content);
+                    (!keys.is_empty()).then(|| (file.to_owned(), keys))
+                })
+                .collect(),
+        }
+    }
+
+    /// The stored index of `notes_dir`, or one built from `notes` and stored when there is
+    /// none yet, it cannot be read, or a note was modified after it was written (an edit
+    /// made outside the notes tools).
+    pub(crate) fn load_or_build<'a>(
+        notes_dir: &Path,
+        notes: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let _guard = lock_index();
+        if let Some(index) = read_index(notes_dir)
+            && !notes_changed_since_index(notes_dir)
+        {
+            return index;
+        }
+        let index = Self::from_notes(notes);
+        write_index(notes_dir, &index);
+        index
+    }
+
+    /// Files linking to each of `notes`, given as `(file name, title)` in file-name order.
+    /// Links resolve by slugged title or file stem, the first note claiming a key wins,
+    /// and self-links and links from notes no longer present are dropped.
+    pub(crate) fn backlinks(&self, notes: &[(String, String)]) -> BTreeMap<String, Vec<String>> {
+        let mut targets = HashMap::new();
+        for (file, title) in notes {
+            let stem = Path::new(file)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(file);
+            for key in [normalize_note_title(title), normalize_note_title(stem)]
+                .into_iter()
+                .flatten()
+            {
+                targets.entry(key).or_insert(file.as_str());
+            }
+        }
+
+        let mut backlinks = BTreeMap::<String, Vec<String>>::new();
+        for (source, keys) in &self.links {
+            if !notes.iter().any(|(file, _)| file == source) {
+                continue;
+            }
+            let linked = keys
+                .iter()
+                .filter_map(|key| targets.get(key).copied())
+                .filter(|target| target != source)
+                .collect::<BTreeSet<_>>();
+            for target in linked {
+                backlinks
+                    .entry(target.to_owned())
+                    .or_default()
+                    .push(source.clone());
+            }
+        }
+        backlinks
+    }
+}
+
+/// Replaces the links of `note_file` after it was written with `content`. Without a
+/// stored index nothing is written: the next read builds a complete one from disk.
+pub(crate) fn record_note_links(notes_dir: &Path, note_file: &str, content: &str) {
+    update_index(notes_dir, |index| {
+        let keys = link_keys(content);
+        if keys.is_empty() {
+            index.links.remove(note_file);
+        } else {
+            index.links.insert(note_file.to_owned(), keys);
+        }
+    });
+}
+
+/// Drops the links of `note_file` after it was deleted or moved away.
+pub(crate) fn forget_note_links(notes_dir: &Path, note_file: &str) {
+    update_index(notes_dir, |index| {
+        index.links.remove(note_file);
+    });
+}
+
+/// Targets of `[[target]]`, `[[target|alias]]`, and `[[target#heading]]` links.
+fn parse_wikilinks(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let mut rest = content;
+    while let Some(open) = rest.find("[[") {
+        rest = &rest[open + 2..];
+        let Some(close) = rest.find("]]") else {
+            break;
+        };
+        let link = &rest[..close];
+        rest = &rest[close + 2..];
+        if link.contains('\n') {
+            continue;
+        }
+        let target = link.split(['|', '#']).next().unwrap_or_default().trim();
+        if !target.is_empty() {
+            targets.push(target.to_owned());
+        }
+    }
+    targets
+}
+
+/// Slugs of the wikilink targets in `content`; `[[rust-tips.md]]` and `[[Rust Tips]]`
+/// share a key.
+fn link_keys(content: &str) -> BTreeSet<String> {
+    parse_wikilinks(content)
+        .into_iter()
+        .filter_map(|target| {
+            let path = Path::new(&target);
+            let target = if is_searchable_note_extension(path) {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or(&target)
+            } else {
+                &target
+            };
+            normalize_note_title(target)
+        })
+        .collect()
+}
+
+fn lock_index() -> MutexGuard<'static, ()> {
+    INDEX_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn update_index(notes_dir: &Path, update: impl FnOnce(&mut BacklinksIndex)) {
+    let _guard = lock_index();
+    let Some(mut index) = read_index(notes_dir) else {
+        return;
+    };
+    update(&mut index);
+    write_index(notes_dir, &index);
+}
+
+fn read_index(notes_dir: &Path) -> Option<BacklinksIndex> {
+    let raw = fs::read_to_string(notes_dir.join(BACKLINKS_INDEX_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// Whether any note in `notes_dir` has a newer modification time than the stored index.
+/// Notes the tools write are recorded after the write, so only outside edits count.
+fn notes_changed_since_index(notes_dir: &Path) -> bool {
+    let Ok(indexed_at) =
+        fs::metadata(notes_dir.join(BACKLINKS_INDEX_FILE)).and_then(|metadata| metadata.modified())
+    else {
+        return true;
+    };
+    let Ok(entries) = fs::read_dir(notes_dir) else {
+        return true;
+    };
+    entries.flatten().any(|entry| {
+        is_searchable_note_extension(&entry.path())
+            && entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified > indexed_at)
+    })
+}
+
+/// Writes through a temp file so readers never see half an index. On failure the stored
+/// index is removed, since a stale index would hide links until it is rebuilt.
+fn write_index(notes_dir: &Path, index: &BacklinksIndex) {
+    let path = notes_dir.join(BACKLINKS_INDEX_FILE);
+    let temp_path = notes_dir.join(format!("{BACKLINKS_INDEX_FILE}.{}.tmp", std::process::id()));
+    let written = serde_json::to_vec_pretty(index)
+        .map_err(std::io::Error::other)
+        .and_then(|raw| fs::write(&temp_path, raw))
+        .and_then(|()| fs::rename(&temp_path, &path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+        let _ = fs::remove_file(&path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+
+    use super::{
+        BACKLINKS_INDEX_FILE, BacklinksIndex, forget_note_links, parse_wikilinks, record_note_links,
+    };
+    use crate::test_support::{remove_dir_if_exists, temp_path};
+
+    #[test]
+    fn wikilinks_are_parsed_from_note_bodies() {
+        assert_eq!(
+            parse_wikilinks("See [[Rust Tips]], [[daily|today]] and [[plan#goals]] [[]] [[open"),
+            ["Rust Tips", "daily", "plan"]
+        );
+    }
+
+    #[test]
+    fn backlinks_resolve_by_title_or_file_and_follow_saves_and_deletes() {
+        let notes_dir = temp_path("note-backlinks");
+        fs::create_dir_all(&notes_dir).expect("notes dir");
+        let notes = [
+            (
+                "daily-log.md",
+                "[[Rust Tips]] [[rust-tips.md]] [[daily-log]]",
+            ),
+            ("rust-tips.md", "[[Later]]"),
+        ];
+        let titles = |files: &[&str]| {
+            files
+                .iter()
+                .map(|file| (file.to_string(), file.trim_end_matches(".md").to_owned()))
+                .collect::<Vec<_>>()
+        };
+
+        record_note_links(&notes_dir, "daily-log.md", "[[ignored]]");
+        assert!(!notes_dir.join(BACKLINKS_INDEX_FILE).exists());
+        let index = BacklinksIndex::load_or_build(&notes_dir, notes);
+        let backlinks = index.backlinks(&titles(&["daily-log.md", "rust-tips.md"]));
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks["rust-tips.md"], ["daily-log.md"]);
+        assert!(notes_dir.join(BACKLINKS_INDEX_FILE).is_file());
+
+        record_note_links(&notes_dir, "rust-tips.md", "[[Later]] [[Daily Log]]");
+        let index = BacklinksIndex::load_or_build(&notes_dir, []);
+        let backlinks = index.backlinks(&titles(&["daily-log.md", "later.md", "rust-tips.md"]));
+        assert_eq!(backlinks["daily-log.md"], ["rust-tips.md"]);
+        assert_eq!(backlinks["later.md"], ["rust-tips.md"]);
+
+        forget_note_links(&notes_dir, "rust-tips.md");
+        let index = BacklinksIndex::load_or_build(&notes_dir, []);
+        let backlinks = index.backlinks(&titles(&["daily-log.md", "later.md", "rust-tips.md"]));
+        assert_eq!(backlinks.keys().collect::<Vec<_>>(), ["rust-tips.md"]);
+        assert!(index.backlinks(&titles(&["rust-tips.md"])).is_empty());
+        remove_dir_if_exists(&notes_dir);
+    }
+
+    #[test]
+    fn notes_edited_after_the_index_was_written_rebuild_it() {
+        let notes_dir = temp_path("note-backlinks-stale");
+        fs::create_dir_all(&notes_dir).expect("notes dir");
+        let note = notes_dir.join("daily-log.md");
+        fs::write(&note, "[[Rust Tips]]").expect("note");
+        let files = [
+            ("daily-log.md".to_owned(), "daily-log".to_owned()),
+            ("later.md".to_owned(), "later".to_owned()),
+            ("rust-tips.md".to_owned(), "rust-tips".to_owned()),
+        ];
+        let index = BacklinksIndex::load_or_build(&notes_dir, [("daily-log.md", "[[Rust Tips]]")]);
+        assert_eq!(index.backlinks(&files)["rust-tips.md"], ["daily-log.md"]);
+
+        // Unchanged notes keep the stored index, whatever the caller read.
+        let index = BacklinksIndex::load_or_build(&notes_dir, []);
+        assert_eq!(index.backlinks(&files)["rust-tips.md"], ["daily-log.md"]);
+
+        fs::write(&note, "[[Later]]").expect("outside edit");
+        fs::File::options()
+            .write(true)
+            .open(&note)
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(5)))
+            .expect("note mtime");
+        let index = BacklinksIndex::load_or_build(&notes_dir, [("daily-log.md", "[[Later]]")]);
+        let backlinks = index.backlinks(&files);
+        assert_eq!(backlinks.keys().collect::<Vec<_>>(), ["later.md"]);
+        remove_dir_if_exists(&notes_dir);
+    }
+}
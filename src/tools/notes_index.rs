@@ -0,0 +1,441 @@
+use std::collections::{BTreeSet, HashMap};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::text::{truncate_graphemes, words_with_char_offsets};
+
+/// BM25 term-frequency saturation.
+const BM25_K1: f64 = 1.2;
+/// BM25 document-length normalization.
+const BM25_B: f64 = 0.75;
+/// Extra term frequency credited per occurrence in a note's title.
+const TITLE_TERM_WEIGHT: u32 = 2;
+/// Score multiplier added for each quoted phrase a note contains verbatim.
+const PHRASE_BOOST: f64 = 1.0;
+/// Snippet length in user-perceived characters (grapheme clusters).
+const SNIPPET_MAX_CHARS: usize = 160;
+/// Grapheme clusters of context kept before the first highlight when a long line is cut.
+const SNIPPET_LEAD_CHARS: usize = 40;
+
+/// Character ranges `(start, end)` of matches within a snippet.
+type Highlights = Vec<(usize, usize)>;
+
+/// A `search_notes` query: bare words, plus `"quoted phrases"` whose words also count
+/// as terms. An unterminated quote is read as bare words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct NotesQuery {
+    terms: Vec<String>,
+    phrases: Vec<Vec<String>>,
+}
+
+impl NotesQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut terms = Vec::new();
+        let mut phrases = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut push_terms = |words: &[String]| {
+            for word in words {
+                if seen.insert(word.clone()) {
+                    terms.push(word.clone());
+                }
+            }
+        };
+
+        let mut rest = query;
+        while let Some(open) = rest.find('"') {
+            let Some(close) = rest[open + 1..].find('"') else {
+                break;
+            };
+            push_terms(&token_texts(&rest[..open]));
+            let phrase = token_texts(&rest[open + 1..open + 1 + close]);
+            push_terms(&phrase);
+            if phrase.len() > 1 && !phrases.contains(&phrase) {
+                phrases.push(phrase);
+            }
+            rest = &rest[open + close + 2..];
+        }
+        push_terms(&token_texts(rest));
+        Self { terms, phrases }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// A readable note as handed to the index.
+#[derive(Debug, Clone)]
+pub(super) struct NoteDocument {
+    pub title: String,
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug)]
+struct IndexedNote {
+    document: NoteDocument,
+    tokens: Vec<String>,
+    title_tokens: Vec<String>,
+    term_frequency: HashMap<String, u32>,
+}
+
+/// In-memory inverted statistics over the notes directory, built once per search.
+#[derive(Debug)]
+pub(super) struct NotesIndex {
+    notes: Vec<IndexedNote>,
+    document_frequency: HashMap<String, usize>,
+    average_length: f64,
+}
+
+/// A ranked note with its best-matching line and the character ranges of matched
+/// terms and phrases within that snippet.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct NoteHit {
+    pub title: String,
+    pub path: String,
+    pub score: f64,
+    pub snippet: String,
+    pub highlights: Highlights,
+}
+
+impl NotesIndex {
+    pub fn build(documents: Vec<NoteDocument>) -> Self {
+        let mut document_frequency = HashMap::new();
+        let mut total_length = 0_usize;
+        let notes = documents
+            .into_iter()
+            .map(|document| {
+                let tokens = token_texts(&document.content);
+                let title_tokens = token_texts(&document.title);
+                let mut term_frequency = HashMap::<String, u32>::new();
+                for token in &tokens {
+                    *term_frequency.entry(token.clone()).or_default() += 1;
+                }
+                for token in &title_tokens {
+                    *term_frequency.entry(token.clone()).or_default() += TITLE_TERM_WEIGHT;
+                }
+                for term in term_frequency.keys() {
+                    *document_frequency.entry(term.clone()).or_default() += 1;
+                }
+                total_length += tokens.len();
+                IndexedNote {
+                    document,
+                    tokens,
+                    title_tokens,
+                    term_frequency,
+                }
+            })
+            .collect::<Vec<_>>();
+        let average_length = if notes.is_empty() {
+            0.0
+        } else {
+            total_length as f64 / notes.len() as f64
+        };
+        Self {
+            notes,
+            document_frequency,
+            average_length,
+        }
+    }
+
+    /// Notes matching at least one query term, best first; ties go to title, then path.
+    pub fn search(&self, query: &NotesQuery) -> Vec<NoteHit> {
+        let mut hits = self
+            .notes
+            .iter()
+            .filter_map(|note| {
+                let score = self.score(note, query);
+                (score > 0.0).then(|| {
+                    let (snippet, highlights) = best_snippet(&note.document.content, query);
+                    NoteHit {
+                        title: note.document.title.clone(),
+                        path: note.document.path.clone(),
+                        score: (score * 1000.0).round() / 1000.0,
+                        snippet,
+                        highlights,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|left, right| {
+            right
+                .score
+                .total_cmp(&left.score)
+                .then_with(|| left.title.cmp(&right.title))
+                .then_with(|| left.path.cmp(&right.path))
+        });
+        hits
+    }
+
+    fn score(&self, note: &IndexedNote, query: &NotesQuery) -> f64 {
+        let length_ratio = if self.average_length > 0.0 {
+            note.tokens.len() as f64 / self.average_length
+        } else {
+            1.0
+        };
+        let bm25 = query
+            .terms
+            .iter()
+            .map(|term| {
+                let frequency = f64::from(note.term_frequency.get(term).copied().unwrap_or(0));
+                if frequency == 0.0 {
+                    return 0.0;
+                }
+                let saturation = frequency * (BM25_K1 + 1.0)
+                    / (frequency + BM25_K1 * (1.0 - BM25_B + BM25_B * length_ratio));
+                self.inverse_document_frequency(term) * saturation
+            })
+            .sum::<f64>();
+        let matched_phrases = query
+            .phrases
+            .iter()
+            .filter(|phrase| {
+                contains_phrase(&note.tokens, phrase) || contains_phrase(&note.title_tokens, phrase)
+            })
+            .count();
+        bm25 * (1.0 + PHRASE_BOOST * matched_phrases as f64)
+    }
+
+    fn inverse_document_frequency(&self, term: &str) -> f64 {
+        let documents = self.notes.len() as f64;
+        let containing = self.document_frequency.get(term).copied().unwrap_or(0) as f64;
+        (1.0 + (documents - containing + 0.5) / (containing + 0.5)).ln()
+    }
+}
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    words_with_char_offsets(text)
+        .into_iter()
+        .map(|(text, start, end)| Token { text, start, end })
+        .collect()
+}
+
+fn token_texts(text: &str) -> Vec<String> {
+    tokenize(text).into_iter().map(|token| token.text).collect()
+}
+
+fn contains_phrase(tokens: &[String], phrase: &[String]) -> bool {
+    tokens.windows(phrase.len()).any(|window| window == phrase)
+}
+
+/// The non-empty line covering the most query terms (a quoted phrase counts double),
+/// cut to `SNIPPET_MAX_CHARS` around its first match. Falls back to the first non-empty
+/// line, without highlights, when only the title matched.
+fn best_snippet(content: &str, query: &NotesQuery) -> (String, Highlights) {
+    let mut best: Option<(usize, &str, Highlights)> = None;
+    let mut fallback = None;
+    for line in content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        fallback.get_or_insert(line);
+        let (coverage, highlights) = line_matches(line, query);
+        if coverage > best.as_ref().map_or(0, |(coverage, ..)| *coverage) {
+            best = Some((coverage, line, highlights));
+        }
+    }
+    match best {
+        Some((_, line, highlights)) => cut_snippet(line, highlights),
+        None => (
+            fallback
+                .map(|line| truncate_graphemes(line, SNIPPET_MAX_CHARS, ""))
+                .unwrap_or_default(),
+            Vec::new(),
+        ),
+    }
+}
+
+fn line_matches(line: &str, query: &NotesQuery) -> (usize, Highlights) {
+    let tokens = tokenize(line);
+    let texts = tokens
+        .iter()
+        .map(|token| token.text.clone())
+        .collect::<Vec<_>>();
+    let mut phrase_spans = Vec::new();
+    for phrase in &query.phrases {
+        let mut index = 0;
+        while index + phrase.len() <= texts.len() {
+            if texts[index..index + phrase.len()] == phrase[..] {
+                phrase_spans.push((index, index + phrase.len()));
+                index += phrase.len();
+            } else {
+                index += 1;
+            }
+        }
+    }
+    phrase_spans.sort_unstable();
+
+    let mut highlights = Vec::new();
+    let mut matched_terms = BTreeSet::new();
+    let mut spans = phrase_spans.iter().peekable();
+    let mut index = 0;
+    while index < tokens.len() {
+        if let Some(&&(start, end)) = spans.peek()
+            && start == index
+        {
+            spans.next();
+            highlights.push((tokens[start].start, tokens[end - 1].end));
+            matched_terms.extend(texts[start..end].iter());
+            index = end;
+            continue;
+        }
+        if query.terms.contains(&texts[index]) {
+            highlights.push((tokens[index].start, tokens[index].end));
+            matched_terms.insert(&texts[index]);
+        }
+        index += 1;
+    }
+    let distinct_phrases = query
+        .phrases
+        .iter()
+        .filter(|phrase| contains_phrase(&texts, phrase))
+        .count();
+    (matched_terms.len() + 2 * distinct_phrases, highlights)
+}
+
+/// Cuts on grapheme boundaries; highlight offsets stay in `char`s, shifted to the cut.
+fn cut_snippet(line: &str, highlights: Highlights) -> (String, Highlights) {
+    let mut char_starts = Vec::new();
+    let mut graphemes = Vec::new();
+    let mut offset = 0;
+    for grapheme in line.graphemes(true) {
+        char_starts.push(offset);
+        graphemes.push(grapheme);
+        offset += grapheme.chars().count();
+    }
+    if graphemes.len() <= SNIPPET_MAX_CHARS {
+        return (line.to_owned(), highlights);
+    }
+    let first_match = highlights.first().map_or(0, |(start, _)| *start);
+    let first_grapheme = char_starts.partition_point(|start| *start <= first_match) - 1;
+    let start = first_grapheme
+        .saturating_sub(SNIPPET_LEAD_CHARS)
+        .min(graphemes.len() - SNIPPET_MAX_CHARS);
+    let end = start + SNIPPET_MAX_CHARS;
+    let (start_char, end_char) = (
+        char_starts[start],
+        char_starts.get(end).copied().unwrap_or(offset),
+    );
+    let highlights = highlights
+        .into_iter()
+        .filter(|(from, to)| *from >= start_char && *to <= end_char)
+        .map(|(from, to)| (from - start_char, to - start_char))
+        .collect();
+    (graphemes[start..end].concat(), highlights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NoteDocument, NotesIndex, NotesQuery};
+    use crate::text::grapheme_count;
+
+    fn note(title: &str, content: &str) -> NoteDocument {
+        NoteDocument {
+            title: title.to_owned(),
+            path: format!("notes/{}.md", title.to_lowercase().replace(' ', "-")),
+            content: content.to_owned(),
+        }
+    }
+
+    #[test]
+    fn query_parsing_splits_quoted_phrases_from_bare_terms() {
+        let query = NotesQuery::parse(r#"Rust "Memory Safety" tips "unterminated phrase"#);
+        assert_eq!(
+            query.terms,
+            ["rust", "memory", "safety", "tips", "unterminated", "phrase"]
+        );
+        assert_eq!(query.phrases, vec![vec!["memory", "safety"]]);
+        assert!(NotesQuery::parse(r#" "" -- "#).is_empty());
+    }
+
+    #[test]
+    fn bm25_prefers_rare_terms_and_boosts_exact_phrases() {
+        let index = NotesIndex::build(vec![
+            note(
+                "Borrowing",
+                "# Borrowing\n\nSafety of memory comes from the borrow checker.\nMemory is freed on drop.",
+            ),
+            note(
+                "Ownership",
+                "# Ownership\n\nOwnership gives memory safety without a garbage collector.",
+            ),
+            note("Groceries", "# Groceries\n\nBuy milk."),
+        ]);
+
+        let hits = index.search(&NotesQuery::parse("memory safety"));
+        assert_eq!(hits.len(), 2);
+
+        let phrase_hits = index.search(&NotesQuery::parse(r#""memory safety""#));
+        assert_eq!(
+            phrase_hits
+                .iter()
+                .map(|hit| hit.title.as_str())
+                .collect::<Vec<_>>(),
+            ["Ownership", "Borrowing"]
+        );
+        let top = &phrase_hits[0];
+        assert_eq!(
+            top.snippet,
+            "Ownership gives memory safety without a garbage collector."
+        );
+        assert_eq!(top.highlights, vec![(16, 29)]);
+        assert!(top.score > phrase_hits[1].score * 1.5);
+
+        let title_only = index.search(&NotesQuery::parse("groceries"));
+        assert_eq!(title_only[0].snippet, "# Groceries");
+        assert_eq!(title_only[0].highlights, vec![(2, 11)]);
+        assert!(index.search(&NotesQuery::parse("tokio")).is_empty());
+    }
+
+    #[test]
+    fn long_lines_are_cut_around_the_first_match_with_shifted_offsets() {
+        let filler = "filler ".repeat(40);
+        let line = format!("{filler}needle {filler}");
+        let index = NotesIndex::build(vec![note("Haystack", &line)]);
+        let hit = &index.search(&NotesQuery::parse("needle"))[0];
+
+        assert_eq!(hit.snippet.chars().count(), 160);
+        assert_eq!(hit.highlights, vec![(40, 46)]);
+        let highlighted = hit.snippet.chars().skip(40).take(6).collect::<String>();
+        assert_eq!(highlighted, "needle");
+    }
+
+    #[test]
+    fn non_english_notes_match_case_insensitively_and_cut_on_graphemes() {
+        let index = NotesIndex::build(vec![
+            note(
+                "Møtereferat",
+                "# MØTEREFERAT\n\nÆrlig talt: ÅPEN kø ved ΣΧΟΛΕΊΟ.",
+            ),
+            note(
+                "Kafé",
+                &format!("{}café\u{301} ved sjøen", "👩‍💻".repeat(200)),
+            ),
+        ]);
+
+        let hits = index.search(&NotesQuery::parse("åpen σχολείο"));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].snippet, "Ærlig talt: ÅPEN kø ved ΣΧΟΛΕΊΟ.");
+        assert_eq!(hits[0].highlights, vec![(12, 16), (24, 31)]);
+
+        let hit = &index.search(&NotesQuery::parse("CAFÉ\u{301}"))[0];
+        assert_eq!(grapheme_count(&hit.snippet), 160);
+        assert!(hit.snippet.starts_with("👩‍💻"));
+        let (start, end) = hit.highlights[0];
+        let highlighted = hit
+            .snippet
+            .chars()
+            .skip(start)
+            .take(end - start)
+            .collect::<String>();
+        assert_eq!(highlighted, "café\u{301}");
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::Path;
+
+use super::{PolicyRule, SAVE_NOTE_TOOL_NAME, ToolDispatchError, is_searchable_note_extension};
+
+/// Caps on what `save_note` may store in the notes directory, so a runaway agent cannot
+/// fill the disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotesQuota {
+    /// Most note files the directory may hold; only new notes count against it.
+    pub max_files: usize,
+    /// Most bytes all notes together may take after the write.
+    pub max_total_bytes: u64,
+    /// Most bytes one note may have after the write, appends included.
+    pub max_note_bytes: usize,
+}
+
+impl NotesQuota {
+    pub const UNLIMITED: Self = Self {
+        max_files: usize::MAX,
+        max_total_bytes: u64::MAX,
+        max_note_bytes: usize::MAX,
+    };
+}
+
+impl Default for NotesQuota {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
+/// Refuses a write of `new_bytes` to a note that currently has `existing_bytes` (`None`
+/// for a new note) when it would break `quota`.
+pub(crate) fn check_notes_quota(
+    quota: NotesQuota,
+    notes_dir: &Path,
+    existing_bytes: Option<u64>,
+    new_bytes: usize,
+) -> Result<(), ToolDispatchError> {
+    if new_bytes > quota.max_note_bytes {
+        return Err(quota_violation(format!(
+            "note would be {new_bytes} bytes, over NOTES_MAX_NOTE_BYTES ({})",
+            quota.max_note_bytes
+        )));
+    }
+    if quota.max_files == usize::MAX && quota.max_total_bytes == u64::MAX {
+        return Ok(());
+    }
+
+    let (files, total_bytes) = notes_usage(notes_dir)?;
+    if existing_bytes.is_none() && files >= quota.max_files {
+        return Err(quota_violation(format!(
+            "notes directory already holds {files} notes, the NOTES_MAX_FILES limit; append to or replace an existing note instead"
+        )));
+    }
+    let total_after = total_bytes.saturating_sub(existing_bytes.unwrap_or(0)) + new_bytes as u64;
+    if total_after > quota.max_total_bytes {
+        return Err(quota_violation(format!(
+            "notes would take {total_after} bytes, over NOTES_MAX_TOTAL_BYTES ({})",
+            quota.max_total_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Count and total size of the regular note files directly in `notes_dir`.
+fn notes_usage(notes_dir: &Path) -> Result<(usize, u64), ToolDispatchError> {
+    let entries = fs::read_dir(notes_dir).map_err(|error| {
+        ToolDispatchError::execution_failed(
+            SAVE_NOTE_TOOL_NAME,
+            format!(
+                "failed to read notes directory `{}`: {error}",
+                notes_dir.display()
+            ),
+        )
+    })?;
+    let mut files = 0;
+    let mut total_bytes = 0u64;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_file() && is_searchable_note_extension(&path) {
+            files += 1;
+            total_bytes += metadata.len();
+        }
+    }
+    Ok((files, total_bytes))
+}
+
+fn quota_violation(reason: String) -> ToolDispatchError {
+    ToolDispatchError::policy_violation(SAVE_NOTE_TOOL_NAME, PolicyRule::NoteQuota, reason)
+}
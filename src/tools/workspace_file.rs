@@ -0,0 +1,162 @@
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read};
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use super::{PolicyRule, READ_WORKSPACE_FILE_TOOL_NAME, ReadWorkspaceFileArgs, ToolDispatchError};
+
+/// Reads up to `max_bytes` (capped at `byte_cap`) of a workspace file whose extension is
+/// in `extensions`. Longer files are cut at a character boundary and reported as
+/// `truncated` with their `total_bytes`.
+pub(super) fn run_read_workspace_file(
+    args: ReadWorkspaceFileArgs,
+    workspace_root: &Path,
+    extensions: &[String],
+    byte_cap: usize,
+) -> Result<Value, ToolDispatchError> {
+    let requested = args.path.trim();
+    if requested.is_empty() {
+        return Err(ToolDispatchError::invalid_args(
+            READ_WORKSPACE_FILE_TOOL_NAME,
+            "path cannot be empty",
+        ));
+    }
+    if args.max_bytes == 0 {
+        return Err(ToolDispatchError::invalid_args(
+            READ_WORKSPACE_FILE_TOOL_NAME,
+            "max_bytes must be at least 1",
+        ));
+    }
+
+    let relative = resolve_workspace_path(requested, workspace_root, extensions)?;
+    let file_path = workspace_root.join(&relative);
+    let metadata = match fs::symlink_metadata(&file_path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            return Err(ToolDispatchError::invalid_args(
+                READ_WORKSPACE_FILE_TOOL_NAME,
+                format!("no workspace file at `{requested}`"),
+            ));
+        }
+        Err(error) => {
+            return Err(ToolDispatchError::execution_failed(
+                READ_WORKSPACE_FILE_TOOL_NAME,
+                format!("failed to inspect `{}`: {error}", file_path.display()),
+            ));
+        }
+    };
+    if metadata.file_type().is_symlink() || !metadata.is_file() {
+        return Err(ToolDispatchError::policy_violation(
+            READ_WORKSPACE_FILE_TOOL_NAME,
+            PolicyRule::WorkspaceFileType,
+            format!("refusing to read `{requested}` because it is not a regular file"),
+        ));
+    }
+    // A symlinked directory inside the workspace could still lead elsewhere.
+    let canonicalize = |path: &Path| {
+        fs::canonicalize(path).map_err(|error| {
+            ToolDispatchError::execution_failed(
+                READ_WORKSPACE_FILE_TOOL_NAME,
+                format!("failed to resolve `{}`: {error}", path.display()),
+            )
+        })
+    };
+    if !canonicalize(&file_path)?.starts_with(canonicalize(workspace_root)?) {
+        return Err(ToolDispatchError::policy_violation(
+            READ_WORKSPACE_FILE_TOOL_NAME,
+            PolicyRule::WorkspacePath,
+            format!("`{requested}` resolves outside the workspace root"),
+        ));
+    }
+
+    let limit = (args.max_bytes as usize).min(byte_cap);
+    let mut raw = Vec::with_capacity(limit.min(metadata.len() as usize));
+    File::open(&file_path)
+        .and_then(|file| file.take(limit as u64).read_to_end(&mut raw))
+        .map_err(|error| {
+            ToolDispatchError::execution_failed(
+                READ_WORKSPACE_FILE_TOOL_NAME,
+                format!("failed to read `{}`: {error}", file_path.display()),
+            )
+        })?;
+    let total_bytes = metadata.len().max(raw.len() as u64);
+    let truncated = (raw.len() as u64) < total_bytes;
+    if truncated {
+        // Drop a multi-byte character the limit cut in half.
+        if let Err(error) = std::str::from_utf8(&raw)
+            && error.error_len().is_none()
+        {
+            raw.truncate(error.valid_up_to());
+        }
+    }
+    let content = String::from_utf8_lossy(&raw).to_string();
+    Ok(json!({
+        "path": relative.to_string_lossy().replace('\\', "/"),
+        "bytes": raw.len(),
+        "total_bytes": total_bytes,
+        "truncated": truncated,
+        "content": content
+    }))
+}
+
+/// `requested` relative to `workspace_root`. Absolute paths are accepted only under the
+/// root; `..`, hidden components (`.git`, `.env`), and extensions outside `extensions`
+/// are refused.
+fn resolve_workspace_path(
+    requested: &str,
+    workspace_root: &Path,
+    extensions: &[String],
+) -> Result<PathBuf, ToolDispatchError> {
+    let path = Path::new(requested);
+    let path = if path.is_absolute() {
+        path.strip_prefix(workspace_root).map_err(|_| {
+            ToolDispatchError::policy_violation(
+                READ_WORKSPACE_FILE_TOOL_NAME,
+                PolicyRule::WorkspacePath,
+                format!("`{requested}` is outside the workspace root"),
+            )
+        })?
+    } else {
+        path
+    };
+
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(part) if !part.to_string_lossy().starts_with('.') => {
+                relative.push(part);
+            }
+            Component::Normal(_) => {
+                return Err(ToolDispatchError::policy_violation(
+                    READ_WORKSPACE_FILE_TOOL_NAME,
+                    PolicyRule::WorkspacePath,
+                    format!("`{requested}` goes through a hidden file or directory"),
+                ));
+            }
+            _ => {
+                return Err(ToolDispatchError::policy_violation(
+                    READ_WORKSPACE_FILE_TOOL_NAME,
+                    PolicyRule::WorkspacePath,
+                    format!("`{requested}` must stay inside the workspace root"),
+                ));
+            }
+        }
+    }
+
+    let extension = relative
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    if !extension.is_some_and(|extension| extensions.contains(&extension)) {
+        return Err(ToolDispatchError::policy_violation(
+            READ_WORKSPACE_FILE_TOOL_NAME,
+            PolicyRule::WorkspaceFileType,
+            format!(
+                "`{requested}` does not have an allowed extension ({})",
+                extensions.join(", ")
+            ),
+        ));
+    }
+    Ok(relative)
+}
@@ -0,0 +1,221 @@
+use std::fs;
+use std::path::Path;
+
+use regex::{Regex, RegexBuilder};
+use serde_json::{Value, json};
+
+use super::{SEARCH_WORKSPACE_TOOL_NAME, SearchWorkspaceArgs, ToolDispatchError};
+use crate::graph::should_skip_dir;
+
+/// Longest `snippet` returned per matching line, in characters.
+const MAX_SNIPPET_CHARS: usize = 200;
+
+/// Limits one `search_workspace` call runs under.
+pub(super) struct WorkspaceSearchLimits<'a> {
+    /// Lowercase extensions of the files searched.
+    pub extensions: &'a [String],
+    pub max_results: usize,
+    /// File bytes scanned before the search stops early.
+    pub max_bytes: usize,
+}
+
+/// Searches workspace files matching `glob` line by line, case-insensitively, for `pattern`:
+/// a substring, or a regex when written as `/regex/`. Hidden entries, symlinks, and the
+/// directories the graph builder skips are never visited.
+pub(super) fn run_search_workspace(
+    args: SearchWorkspaceArgs,
+    workspace_root: &Path,
+    limits: WorkspaceSearchLimits<'_>,
+) -> Result<Value, ToolDispatchError> {
+    if args.max_results == 0 {
+        return Err(ToolDispatchError::invalid_args(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            "max_results must be at least 1",
+        ));
+    }
+    let matcher = pattern_matcher(&args.pattern)?;
+    let glob = glob_matcher(&args.glob)?;
+    let max_results = (args.max_results as usize).min(limits.max_results);
+
+    let mut files = Vec::new();
+    collect_files(
+        workspace_root,
+        workspace_root,
+        limits.extensions,
+        &mut files,
+    )
+    .map_err(|error| {
+        ToolDispatchError::execution_failed(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            format!("failed to list `{}`: {error}", workspace_root.display()),
+        )
+    })?;
+    files.retain(|path| glob.matches(path));
+
+    let mut results = Vec::new();
+    let mut files_scanned = 0usize;
+    let mut bytes_scanned = 0usize;
+    let mut truncated = false;
+    'files: for relative_path in &files {
+        let path = workspace_root.join(relative_path);
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        if bytes_scanned + metadata.len() as usize > limits.max_bytes {
+            truncated = true;
+            break;
+        }
+        bytes_scanned += metadata.len() as usize;
+        let Ok(source) = fs::read_to_string(&path) else {
+            // Unreadable or non-UTF-8 files are not source worth searching.
+            continue;
+        };
+        files_scanned += 1;
+        for (index, line) in source.lines().enumerate() {
+            if !matcher.is_match(line) {
+                continue;
+            }
+            if results.len() == max_results {
+                truncated = true;
+                break 'files;
+            }
+            results.push(json!({
+                "path": relative_path,
+                "line": index + 1,
+                "snippet": snippet(line)
+            }));
+        }
+    }
+
+    Ok(json!({
+        "pattern": args.pattern,
+        "glob": args.glob,
+        "files_scanned": files_scanned,
+        "truncated": truncated,
+        "results": results
+    }))
+}
+
+fn pattern_matcher(pattern: &str) -> Result<Regex, ToolDispatchError> {
+    let source = match pattern
+        .strip_prefix('/')
+        .and_then(|rest| rest.strip_suffix('/'))
+    {
+        Some(regex) => regex.to_owned(),
+        None => regex::escape(pattern),
+    };
+    if source.is_empty() {
+        return Err(ToolDispatchError::invalid_args(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            "pattern cannot be empty",
+        ));
+    }
+    RegexBuilder::new(&source)
+        .case_insensitive(true)
+        .build()
+        .map_err(|error| {
+            ToolDispatchError::invalid_args(
+                SEARCH_WORKSPACE_TOOL_NAME,
+                format!("invalid pattern regex: {error}"),
+            )
+        })
+}
+
+/// A glob over workspace-relative slash paths: `*` and `?` stay within one path segment,
+/// `**` crosses segments. A glob without `/` matches file names at any depth; an empty
+/// glob matches every file.
+struct GlobMatcher {
+    regex: Regex,
+    file_name_only: bool,
+}
+
+impl GlobMatcher {
+    fn matches(&self, relative_path: &str) -> bool {
+        let candidate = if self.file_name_only {
+            relative_path.rsplit('/').next().unwrap_or(relative_path)
+        } else {
+            relative_path
+        };
+        self.regex.is_match(candidate)
+    }
+}
+
+fn glob_matcher(glob: &str) -> Result<GlobMatcher, ToolDispatchError> {
+    let glob = glob.trim().trim_start_matches("./");
+    let glob = if glob.is_empty() { "**" } else { glob };
+    let mut source = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    source.push_str("(?:.*/)?");
+                } else {
+                    source.push_str(".*");
+                }
+            }
+            '*' => source.push_str("[^/]*"),
+            '?' => source.push_str("[^/]"),
+            _ => source.push_str(&regex::escape(ch.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    source.push('$');
+    let regex = Regex::new(&source).map_err(|error| {
+        ToolDispatchError::invalid_args(
+            SEARCH_WORKSPACE_TOOL_NAME,
+            format!("invalid glob: {error}"),
+        )
+    })?;
+    Ok(GlobMatcher {
+        regex,
+        file_name_only: !glob.contains('/'),
+    })
+}
+
+/// Workspace-relative slash paths of regular files with an allowed extension, sorted.
+fn collect_files(
+    workspace_root: &Path,
+    current_dir: &Path,
+    extensions: &[String],
+    files: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let mut entries = fs::read_dir(current_dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') {
+            continue;
+        }
+        // `DirEntry::file_type` does not follow symlinks, so linked files and
+        // directories are skipped here.
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            if !should_skip_dir(&name) {
+                collect_files(workspace_root, &path, extensions, files)?;
+            }
+            continue;
+        }
+        let allowed = path.extension().is_some_and(|extension| {
+            extensions.contains(&extension.to_string_lossy().to_ascii_lowercase())
+        });
+        if file_type.is_file()
+            && allowed
+            && let Ok(relative_path) = path.strip_prefix(workspace_root)
+        {
+            files.push(relative_path.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    Ok(())
+}
+
+fn snippet(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_owned(),
+    }
+}
@@ -0,0 +1,198 @@
+use std::cmp::Ordering;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::build_info::VERSION;
+use crate::config::AgentSettings;
+use crate::http::SharedHttpClient;
+use crate::tools::fetch_url_outside_turn;
+use crate::tr;
+
+/// Result of comparing this build against the latest published release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpdateCheck {
+    pub current_version: &'static str,
+    /// Release tag without a leading `v`.
+    pub latest_version: String,
+    pub release_url: String,
+    pub update_available: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+/// Queries `UPDATE_CHECK_URL` (the GitHub "latest release" endpoint by default) through
+/// the `fetch_url` policy, so the host must be in `FETCH_URL_ALLOWED_DOMAINS`. The request
+/// honors `HTTP_PROXY_URL` and the pool settings.
+pub async fn check_for_update(settings: &AgentSettings) -> Result<UpdateCheck> {
+    if settings.offline {
+        bail!("update check needs network access, which AGENT_OFFLINE=true disables");
+    }
+    let url = &settings.update_check_url;
+    let fetched = fetch_url_outside_turn(
+        url,
+        &settings.fetch_url_allowed_domains,
+        settings.fetch_url_follow_redirects,
+        settings.tool_timeout_ms,
+        settings.fetch_url_max_bytes as usize,
+        SharedHttpClient::from_settings(settings),
+    )
+    .await
+    .with_context(|| format!("update check against `{url}` failed"))?;
+    let content = fetched
+        .get("content")
+        .and_then(|content| content.as_str())
+        .unwrap_or_default();
+    parse_latest_release(content, VERSION)
+        .with_context(|| format!("unexpected release payload from `{url}`"))
+}
+
+fn parse_latest_release(payload: &str, current_version: &'static str) -> Result<UpdateCheck> {
+    let release = serde_json::from_str::<LatestRelease>(payload)
+        .context("expected a JSON object with `tag_name` and `html_url`")?;
+    let latest_version = release.tag_name.trim().trim_start_matches('v').to_owned();
+    let Some(update_available) = is_newer_version(&latest_version, current_version) else {
+        bail!("release tag `{}` is not a version", release.tag_name);
+    };
+    Ok(UpdateCheck {
+        current_version,
+        latest_version,
+        release_url: release.html_url,
+        update_available,
+    })
+}
+
+/// Whether `candidate` is a later release than `current`. Both are `major[.minor[.patch]]`
+/// with an optional `-prerelease` suffix, which sorts before the plain release; build
+/// metadata after `+` is ignored. `None` when either is not a version.
+fn is_newer_version(candidate: &str, current: &str) -> Option<bool> {
+    let (candidate_core, candidate_pre) = parse_version(candidate)?;
+    let (current_core, current_pre) = parse_version(current)?;
+    Some(match candidate_core.cmp(&current_core) {
+        Ordering::Greater => true,
+        Ordering::Less => false,
+        Ordering::Equal => match (candidate_pre, current_pre) {
+            (None, Some(_)) => true,
+            (Some(candidate_pre), Some(current_pre)) => candidate_pre > current_pre,
+            (_, None) => false,
+        },
+    })
+}
+
+/// `(major, minor, patch)` and the prerelease suffix, if any.
+type ParsedVersion<'a> = ((u64, u64, u64), Option<&'a str>);
+
+fn parse_version(version: &str) -> Option<ParsedVersion<'_>> {
+    let version = version.split('+').next()?;
+    let (core, prerelease) = match version.split_once('-') {
+        Some((core, prerelease)) => (core, Some(prerelease)),
+        None => (version, None),
+    };
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(((major, minor, patch), prerelease))
+}
+
+/// `update check`: reports whether a newer release exists, or the full result with `json`.
+pub async fn run_update_check_command(settings: &AgentSettings, json: bool) -> Result<()> {
+    let check = check_for_update(settings).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&check)?);
+    } else if check.update_available {
+        println!(
+            "{}",
+            tr!(
+                "update.available",
+                latest = check.latest_version,
+                current = check.current_version,
+                url = check.release_url
+            )
+        );
+    } else {
+        println!(
+            "{}",
+            tr!(
+                "update.up_to_date",
+                current = check.current_version,
+                latest = check.latest_version
+            )
+        );
+    }
+    Ok(())
+}
+
+/// With `UPDATE_CHECK_ON_START`, runs the update check in the background and logs a
+/// notice when a newer release exists, so `serve` and `studio` never wait on it. Skipped
+/// under `AGENT_OFFLINE`.
+pub fn spawn_startup_update_check(settings: &AgentSettings) {
+    if !settings.update_check_on_start || settings.offline {
+        return;
+    }
+    let settings = settings.clone();
+    tokio::spawn(async move {
+        match check_for_update(&settings).await {
+            Ok(check) if check.update_available => info!(
+                current_version = check.current_version,
+                latest_version = %check.latest_version,
+                release_url = %check.release_url,
+                "a newer mjolne_vibes release is available"
+            ),
+            Ok(check) => debug!(
+                current_version = check.current_version,
+                latest_version = %check.latest_version,
+                "mjolne_vibes is up to date"
+            ),
+            Err(error) => warn!(error = %format!("{error:#}"), "startup update check failed"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_for_update, is_newer_version, parse_latest_release};
+    use crate::agent::tests::test_settings;
+
+    #[test]
+    fn release_tags_compare_as_versions() {
+        let release = r#"{"tag_name":"v0.2.0","html_url":"https://github.com/o/r/releases/tag/v0.2.0","assets":[]}"#;
+        let check = parse_latest_release(release, "0.1.9").expect("release should parse");
+        assert!(check.update_available);
+        assert_eq!(check.latest_version, "0.2.0");
+
+        assert_eq!(is_newer_version("0.1.0", "0.1.0"), Some(false));
+        assert_eq!(is_newer_version("0.10.0", "0.9.3"), Some(true));
+        assert_eq!(is_newer_version("1", "0.9.3"), Some(true));
+        assert_eq!(is_newer_version("0.2.0-rc.1", "0.2.0"), Some(false));
+        assert_eq!(is_newer_version("0.2.0", "0.2.0-rc.1"), Some(true));
+        assert_eq!(is_newer_version("0.2.0+build.5", "0.2.0"), Some(false));
+        assert_eq!(is_newer_version("nightly", "0.2.0"), None);
+        assert!(parse_latest_release(r#"{"tag_name":"nightly","html_url":""}"#, "0.1.0").is_err());
+    }
+
+    #[tokio::test]
+    async fn update_check_follows_the_fetch_domain_allowlist() {
+        let mut settings = test_settings();
+        settings.update_check_url =
+            "https://api.github.com/repos/Skarsh/mjolne_vibes/releases/latest".to_owned();
+        settings.fetch_url_allowed_domains = vec!["example.com".to_owned()];
+
+        let error = check_for_update(&settings)
+            .await
+            .expect_err("api.github.com is not allowlisted");
+
+        assert!(
+            format!("{error:#}").contains("url host `api.github.com` is not in allowlist"),
+            "{error:#}"
+        );
+    }
+}
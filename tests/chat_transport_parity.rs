@@ -96,6 +96,201 @@ async fn http_returns_bad_gateway_for_unreachable_model() {
     );
 }
 
+#[tokio::test]
+async fn http_rejects_unusable_model_overrides_and_users_before_the_turn() {
+    let Some(server) = start_server(4000).await else {
+        eprintln!("skipping: local TCP bind is not permitted in this environment");
+        return;
+    };
+    let client = reqwest::Client::new();
+
+    for (request, expected) in [
+        (
+            json!({ "message": "hi", "provider": "anthropic", "model": "x" }),
+            "unknown provider",
+        ),
+        (
+            json!({ "message": "hi", "session_id": "override-1", "model": " " }),
+            "must not be empty",
+        ),
+        (
+            json!({ "message": "hi", "user": "alice smith" }),
+            "invalid user",
+        ),
+    ] {
+        let response = client
+            .post(format!("http://{}/chat", server.bind_addr))
+            .json(&request)
+            .send()
+            .await
+            .expect("HTTP request should complete");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .expect("HTTP error body should be valid JSON");
+        assert!(
+            body["error"]
+                .as_str()
+                .is_some_and(|error| error.contains(expected)),
+            "{body}"
+        );
+        assert!(body.get("code").is_none(), "{body}");
+    }
+}
+
+#[tokio::test]
+async fn http_stream_ends_with_the_chat_error_event() {
+    let Some(server) = start_server(4000).await else {
+        eprintln!("skipping: local TCP bind is not permitted in this environment");
+        return;
+    };
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("http://{}/chat/stream", server.bind_addr))
+        .json(&json!({ "message": "hi", "session_id": "stream-1" }))
+        .send()
+        .await
+        .expect("HTTP request should complete");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    let body = response.text().await.expect("stream should finish");
+    let data = body
+        .lines()
+        .skip_while(|line| *line != "event: error")
+        .find_map(|line| line.strip_prefix("data: "))
+        .expect("stream should end with an error event");
+    let error: serde_json::Value = serde_json::from_str(data).expect("event data is JSON");
+    assert_eq!(error["status"], 502);
+    assert!(
+        error["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("model chat failed")),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn http_exports_session_transcripts() {
+    let Some(server) = start_server(4).await else {
+        eprintln!("skipping: local TCP bind is not permitted in this environment");
+        return;
+    };
+    let client = reqwest::Client::new();
+    let base = format!("http://{}", server.bind_addr);
+
+    // Rejected by the input guardrail, but the session now exists.
+    let response = client
+        .post(format!("{base}/chat"))
+        .json(&json!({ "message": "hello", "session_id": "export-1" }))
+        .send()
+        .await
+        .expect("HTTP request should complete");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = client
+        .get(format!(
+            "{base}/sessions/export-1/transcript?format=markdown"
+        ))
+        .send()
+        .await
+        .expect("HTTP request should complete");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/markdown; charset=utf-8"
+    );
+    let markdown = response.text().await.expect("markdown body");
+    assert!(markdown.starts_with("# Session export-1\n"));
+
+    for (path, status) in [
+        ("/sessions/export-1/transcript", StatusCode::OK),
+        (
+            "/sessions/export-1/transcript?format=pdf",
+            StatusCode::BAD_REQUEST,
+        ),
+        ("/sessions/missing/transcript", StatusCode::NOT_FOUND),
+    ] {
+        let response = client
+            .get(format!("{base}{path}"))
+            .send()
+            .await
+            .expect("HTTP request should complete");
+        assert_eq!(response.status(), status, "{path}");
+    }
+}
+
+#[tokio::test]
+async fn cli_and_http_list_the_same_tool_schemas() {
+    let Some(server) = start_server(4000).await else {
+        eprintln!("skipping: local TCP bind is not permitted in this environment");
+        return;
+    };
+
+    let http_body: serde_json::Value = reqwest::get(format!("http://{}/tools", server.bind_addr))
+        .await
+        .expect("HTTP request should complete")
+        .json()
+        .await
+        .expect("tools body should be valid JSON");
+
+    let output = Command::new(bin_path())
+        .args(["tools", "list", "--json"])
+        .output()
+        .expect("CLI command should execute");
+    assert!(output.status.success(), "tools list should succeed");
+    let cli_body: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("CLI output should be valid JSON");
+
+    assert_eq!(http_body, cli_body);
+    let tools = http_body["tools"]
+        .as_array()
+        .expect("tools should be an array");
+    assert!(tools.iter().any(|tool| {
+        tool["name"] == "fetch_url" && tool["parameters"]["required"] == json!(["url"])
+    }));
+}
+
+#[tokio::test]
+async fn cli_and_http_report_the_same_capabilities() {
+    let Some(server) = start_server(1234).await else {
+        eprintln!("skipping: local TCP bind is not permitted in this environment");
+        return;
+    };
+
+    let http_body: serde_json::Value =
+        reqwest::get(format!("http://{}/capabilities", server.bind_addr))
+            .await
+            .expect("HTTP request should complete")
+            .json()
+            .await
+            .expect("capabilities body should be valid JSON");
+
+    let notes_dir = temp_path("integration-cli-notes");
+    let log_dir = temp_path("integration-cli-logs");
+    let mut command = Command::new(bin_path());
+    command.args(["capabilities", "--json"]);
+    apply_ollama_test_env(
+        &mut command,
+        &notes_dir,
+        &log_dir,
+        1234,
+        "http://127.0.0.1:9",
+    );
+    let output = command.output().expect("CLI command should execute");
+    remove_dir_if_exists(&notes_dir);
+    remove_dir_if_exists(&log_dir);
+    assert!(output.status.success(), "capabilities should succeed");
+    let cli_body: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("CLI output should be valid JSON");
+
+    assert_eq!(http_body, cli_body);
+    assert_eq!(http_body["provider"], "ollama");
+    assert_eq!(http_body["limits"]["max_input_chars"], 1234);
+}
+
 async fn start_server(max_input_chars: u32) -> Option<RunningServer> {
     let port = find_available_port()?;
     let bind_addr = format!("127.0.0.1:{port}");